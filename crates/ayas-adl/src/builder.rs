@@ -1,23 +1,49 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use ayas_graph::prelude::{ChannelSpec, CompiledStateGraph, ConditionalEdge, StateGraph};
+use ayas_graph::prelude::{
+    ChannelSpec, CompiledStateGraph, ConditionalEdge, NodeFn, StateGraph, subgraph_node,
+};
 use serde_json::Value;
 
 use crate::error::AdlError;
-use crate::expression;
+use crate::expression::CompiledExpression;
+use crate::policy::{ERROR_CHANNEL, NodePolicy};
 use crate::registry::ComponentRegistry;
-use crate::types::{AdlChannelType, AdlCondition, AdlDocument, AdlEdgeType, normalize_sentinel};
+use crate::types::{
+    AdlChannelType, AdlCondition, AdlDocument, AdlEdgeType, SUBGRAPH_NODE_TYPE, SubgraphConfig,
+    normalize_sentinel,
+};
 use crate::validation;
 
 /// Builder that converts ADL documents into compiled state graphs.
 pub struct AdlBuilder {
     registry: ComponentRegistry,
+    policies: HashMap<String, NodePolicy>,
+    documents: HashMap<String, AdlDocument>,
 }
 
 impl AdlBuilder {
     /// Create a builder with the given registry.
     pub fn new(registry: ComponentRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            policies: HashMap::new(),
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Make a document available to `subgraph` nodes under `id`.
+    pub fn with_document(mut self, id: impl Into<String>, doc: AdlDocument) -> Self {
+        self.documents.insert(id.into(), doc);
+        self
+    }
+
+    /// Attach an execution policy to a node. Its keys are merged into the
+    /// node's config, overriding any the document sets.
+    pub fn node_policy(mut self, node_id: impl Into<String>, policy: NodePolicy) -> Self {
+        self.policies.insert(node_id.into(), policy);
+        self
     }
 
     /// Create a builder with builtin node types pre-registered.
@@ -38,10 +64,59 @@ impl AdlBuilder {
         self.build(doc)
     }
 
+    /// Build the document registered under `id` with [`with_document`](Self::with_document).
+    pub fn build_document(&self, id: &str) -> Result<CompiledStateGraph, AdlError> {
+        let doc = self
+            .documents
+            .get(id)
+            .ok_or_else(|| AdlError::Validation(format!("Unknown document '{id}'")))?;
+        validation::validate_subgraphs(doc, id, &self.documents)?;
+        self.build_graph(doc)
+    }
+
     /// Build a `CompiledStateGraph` from a parsed ADL document.
     fn build(&self, doc: AdlDocument) -> Result<CompiledStateGraph, AdlError> {
+        validation::validate_subgraphs(&doc, "<root>", &self.documents)?;
+        self.build_graph(&doc)
+    }
+
+    /// Build a `subgraph` node: compile the document it names and run it
+    /// through `subgraph_node`.
+    fn build_subgraph_node(
+        &self,
+        node_id: &str,
+        config: &HashMap<String, Value>,
+    ) -> Result<NodeFn, AdlError> {
+        let SubgraphConfig {
+            graph,
+            document,
+            input_mapping,
+            output_mapping,
+        } = SubgraphConfig::from_config(node_id, config)?;
+        let compiled = match (graph, &document) {
+            (Some(id), _) => {
+                let doc = self.documents.get(&id).ok_or_else(|| AdlError::InvalidConfig {
+                    node_id: node_id.to_string(),
+                    detail: format!("unknown subgraph '{id}'"),
+                })?;
+                self.build_graph(doc)?
+            }
+            (None, Some(doc)) => self.build_graph(doc)?,
+            (None, None) => unreachable!("SubgraphConfig::from_config requires a source"),
+        };
+        Ok(subgraph_node(
+            node_id,
+            Arc::new(compiled),
+            input_mapping,
+            output_mapping,
+        ))
+    }
+
+    /// Validate and compile one document. Subgraph references must already
+    /// be checked for cycles.
+    fn build_graph(&self, doc: &AdlDocument) -> Result<CompiledStateGraph, AdlError> {
         // Step 1: Validate
-        validation::validate_document(&doc, &self.registry)?;
+        validation::validate_document(doc, &self.registry)?;
 
         // Step 2: Build state graph
         let mut graph = StateGraph::new();
@@ -57,15 +132,38 @@ impl AdlBuilder {
             graph.add_channel(&ch.name, spec);
         }
 
-        // Step 4: Add nodes
+        // Step 4: Add nodes, wrapped in their execution policies
+        let mut error_targets: HashMap<String, String> = HashMap::new();
         for node_def in &doc.nodes {
-            let node = self
-                .registry
-                .create_node(&node_def.id, &node_def.node_type, &node_def.config)?;
+            let mut config = node_def.config.clone();
+            if let Some(policy) = self.policies.get(&node_def.id) {
+                policy.write_to(&mut config);
+            }
+            let policy = NodePolicy::from_config(&node_def.id, &config)?;
+            if let Some(target) = &policy.on_error {
+                let target = normalize_sentinel(target);
+                if target != "__end__" && !doc.nodes.iter().any(|n| n.id == target) {
+                    return Err(AdlError::InvalidConfig {
+                        node_id: node_def.id.clone(),
+                        detail: format!("on_error target '{target}' is not a node"),
+                    });
+                }
+                error_targets.insert(node_def.id.clone(), target);
+            }
+
+            let node = if node_def.node_type == SUBGRAPH_NODE_TYPE {
+                self.build_subgraph_node(&node_def.id, &config)?
+            } else {
+                self.registry
+                    .create_node(&node_def.id, &node_def.node_type, &config)?
+            };
             graph
-                .add_node(node)
+                .add_node(policy.wrap(node))
                 .map_err(|e| AdlError::Validation(e.to_string()))?;
         }
+        if !error_targets.is_empty() {
+            graph.add_last_value_channel(ERROR_CHANNEL, Value::Null);
+        }
 
         // Step 5: Process edges
         for edge_def in &doc.edges {
@@ -77,6 +175,20 @@ impl AdlBuilder {
 
                     if from == "__start__" {
                         graph.set_entry_point(&to);
+                    } else if let Some(error_target) = error_targets.get(&from) {
+                        // Failed runs leave `__error` set; route them to the target
+                        let error_target = error_target.clone();
+                        graph.add_conditional_edges(ConditionalEdge::new(
+                            &from,
+                            move |state: &Value| {
+                                if state[ERROR_CHANNEL].is_null() {
+                                    to.clone()
+                                } else {
+                                    error_target.clone()
+                                }
+                            },
+                            None,
+                        ));
                     } else if to == "__end__" {
                         graph.set_finish_point(&from);
                     } else {
@@ -95,23 +207,33 @@ impl AdlBuilder {
 
                     // Build path_map for all non-default conditions + default
                     let mut path_map = HashMap::new();
-                    let expressions: Vec<(String, String)> = conditions
+                    // Parse each expression once; only the scope is rebuilt per call
+                    let expressions: Vec<(CompiledExpression, String)> = conditions
                         .iter()
-                        .map(|c| (c.expression.clone(), c.to.clone()))
-                        .collect();
+                        .map(|c| Ok((CompiledExpression::compile(&c.expression)?, c.to.clone())))
+                        .collect::<Result<_, AdlError>>()?;
 
                     for cond in &conditions {
                         path_map.insert(cond.to.clone(), cond.to.clone());
                     }
                     // Also map __end__ as itself
                     path_map.insert("__end__".to_string(), "__end__".to_string());
+                    let error_target = error_targets.get(&from).cloned();
+                    if let Some(target) = &error_target {
+                        path_map.insert(target.clone(), target.clone());
+                    }
 
                     let from_clone = from.clone();
                     let ce = ConditionalEdge::new(
                         &from,
                         move |state: &Value| {
+                            if let Some(target) = &error_target
+                                && !state[ERROR_CHANNEL].is_null()
+                            {
+                                return target.clone();
+                            }
                             for (expr, target) in &expressions {
-                                match expression::evaluate(expr, state) {
+                                match expr.evaluate(state) {
                                     Ok(true) => return target.clone(),
                                     Ok(false) => continue,
                                     Err(_) => continue,
@@ -352,4 +474,192 @@ edges:
             .unwrap();
         assert_eq!(result["output"], "transformed!");
     }
+
+    fn registry_with_failing_node() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::with_builtins();
+        registry.register(
+            "failing",
+            std::sync::Arc::new(|id: &str, _config: &HashMap<String, Value>| {
+                Ok(ayas_graph::prelude::NodeFn::new(
+                    id.to_string(),
+                    |_state: Value, _config: RunnableConfig| async move {
+                        Err(ayas_core::error::AyasError::Other("boom".into()))
+                    },
+                ))
+            }),
+        );
+        registry
+    }
+
+    const RISKY_YAML: &str = r#"
+version: "1.0"
+channels:
+  - name: result
+    type: last_value
+    default: ""
+nodes:
+  - id: risky
+    type: failing
+  - id: fallback
+    type: transform
+    config:
+      mapping:
+        result: __error
+edges:
+  - from: __start__
+    to: risky
+  - from: risky
+    to: __end__
+  - from: fallback
+    to: __end__
+"#;
+
+    #[tokio::test]
+    async fn node_policy_routes_failures_to_on_error_target() {
+        let builder = AdlBuilder::new(registry_with_failing_node()).node_policy(
+            "risky",
+            NodePolicy::default().with_max_retries(1).with_on_error("fallback"),
+        );
+        let compiled = builder.build_from_yaml(RISKY_YAML).unwrap();
+        let result = compiled
+            .invoke(json!({}), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(result["result"], "boom");
+    }
+
+    #[tokio::test]
+    async fn node_without_policy_fails_graph() {
+        // Without an on_error route, nothing leads to a fallback node
+        let yaml = r#"
+version: "1.0"
+channels:
+  - name: result
+    type: last_value
+    default: ""
+nodes:
+  - id: risky
+    type: failing
+edges:
+  - from: __start__
+    to: risky
+  - from: risky
+    to: __end__
+"#;
+        let builder = AdlBuilder::new(registry_with_failing_node());
+        let compiled = builder.build_from_yaml(yaml).unwrap();
+        let result = compiled.invoke(json!({}), &RunnableConfig::default()).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn node_policy_unknown_on_error_target_fails() {
+        let builder = AdlBuilder::new(registry_with_failing_node())
+            .node_policy("risky", NodePolicy::default().with_on_error("missing"));
+        let err = builder.build_from_yaml(RISKY_YAML).err().unwrap();
+        assert!(err.to_string().contains("on_error target 'missing'"));
+    }
+
+    const SHOUT_YAML: &str = r#"
+version: "1.0"
+channels:
+  - name: text
+    type: last_value
+    default: ""
+  - name: loud
+    type: last_value
+    default: ""
+nodes:
+  - id: copy
+    type: transform
+    config:
+      mapping:
+        loud: text
+edges:
+  - from: __start__
+    to: copy
+  - from: copy
+    to: __end__
+"#;
+
+    #[tokio::test]
+    async fn subgraph_node_runs_registered_document() {
+        let shout: AdlDocument = serde_yaml::from_str(SHOUT_YAML).unwrap();
+        let yaml = r#"
+version: "1.0"
+channels:
+  - name: message
+    type: last_value
+    default: ""
+  - name: reply
+    type: last_value
+    default: ""
+nodes:
+  - id: shout
+    type: subgraph
+    config:
+      graph: shout
+      input_mapping:
+        message: text
+      output_mapping:
+        loud: reply
+edges:
+  - from: __start__
+    to: shout
+  - from: shout
+    to: __end__
+"#;
+        let builder = AdlBuilder::with_defaults().with_document("shout", shout);
+        let compiled = builder.build_from_yaml(yaml).unwrap();
+        let result = compiled
+            .invoke(json!({"message": "hi"}), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(result["reply"], "hi");
+    }
+
+    #[tokio::test]
+    async fn subgraph_node_runs_inline_document() {
+        let inline: Value = serde_yaml::from_str(SHOUT_YAML).unwrap();
+        let doc = json!({
+            "version": "1.0",
+            "channels": [
+                {"name": "text", "type": "last_value", "default": ""},
+                {"name": "loud", "type": "last_value", "default": ""}
+            ],
+            "nodes": [{"id": "inner", "type": "subgraph", "config": {"document": inline}}],
+            "edges": [
+                {"from": "__start__", "to": "inner"},
+                {"from": "inner", "to": "__end__"}
+            ]
+        });
+        let builder = AdlBuilder::with_defaults();
+        let compiled = builder.build_from_json(&doc.to_string()).unwrap();
+        let result = compiled
+            .invoke(json!({"text": "hey"}), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(result["loud"], "hey");
+    }
+
+    #[test]
+    fn build_document_rejects_self_reference() {
+        let looping: AdlDocument = serde_yaml::from_str(
+            r#"
+version: "1.0"
+nodes:
+  - id: again
+    type: subgraph
+    config:
+      graph: loop
+edges:
+  - from: __start__
+    to: again
+"#,
+        )
+        .unwrap();
+        let builder = AdlBuilder::with_defaults().with_document("loop", looping);
+        let err = builder.build_document("loop").err().unwrap();
+        assert!(err.to_string().contains("Subgraph cycle: loop -> loop"));
+    }
 }
@@ -12,9 +12,15 @@ pub enum AdlError {
     #[error("Unknown node type: '{node_type}'")]
     UnknownNodeType { node_type: String },
 
+    #[error("Unknown node type: '{node_type}' (node '{node_id}')")]
+    UnknownComponent { node_id: String, node_type: String },
+
     #[error("Missing config field '{field}' for node type '{node_type}'")]
     MissingConfig { node_type: String, field: String },
 
+    #[error("Invalid config for node '{node_id}': {detail}")]
+    InvalidConfig { node_id: String, detail: String },
+
     #[error("Expression error in '{from}': {detail}")]
     ExpressionError { from: String, detail: String },
 
@@ -67,6 +73,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invalid_config_display() {
+        let err = AdlError::InvalidConfig {
+            node_id: "t1".into(),
+            detail: "parameter 'mapping' must be object, got string".into(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid config for node 't1': parameter 'mapping' must be object, got string"
+        );
+    }
+
     #[test]
     fn expression_error_display() {
         let err = AdlError::ExpressionError {
@@ -1,4 +1,6 @@
-use rhai::{Engine, Scope};
+use std::sync::OnceLock;
+
+use rhai::{AST, Dynamic, Engine, Scope};
 use serde_json::Value;
 
 use crate::error::AdlError;
@@ -8,34 +10,105 @@ use crate::error::AdlError;
 /// - `"default"` is treated as always-true (fallback condition).
 /// - Other expressions are evaluated using the Rhai scripting engine
 ///   with the state injected as a `state` variable in scope.
+///
+/// This parses `expression` on every call; use [`CompiledExpression`] to
+/// evaluate the same expression repeatedly.
 pub fn evaluate(expression: &str, state: &Value) -> Result<bool, AdlError> {
-    if expression == "default" {
-        return Ok(true);
+    CompiledExpression::compile(expression)?.evaluate(state)
+}
+
+/// An ADL condition expression parsed once, for repeated evaluation.
+///
+/// Only the scope holding `state` is rebuilt per call.
+#[derive(Debug, Clone)]
+pub struct CompiledExpression {
+    source: String,
+    /// `None` for the `"default"` fallback.
+    ast: Option<AST>,
+}
+
+impl CompiledExpression {
+    /// Parse `expression`, failing on syntax errors.
+    pub fn compile(expression: &str) -> Result<Self, AdlError> {
+        let ast = if expression == "default" {
+            None
+        } else {
+            let ast = sandboxed_engine()
+                .compile(expression)
+                .map_err(|e| AdlError::ExpressionError {
+                    from: expression.to_string(),
+                    detail: e.to_string(),
+                })?;
+            Some(ast)
+        };
+        Ok(Self {
+            source: expression.to_string(),
+            ast,
+        })
     }
 
-    let engine = create_sandboxed_engine();
+    /// The expression as written.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
 
-    // Convert serde_json::Value -> Rhai Dynamic
-    let dynamic_state = rhai::serde::to_dynamic(state).map_err(|e| AdlError::ExpressionError {
-        from: expression.to_string(),
-        detail: format!("Failed to convert state to Rhai dynamic: {e}"),
-    })?;
+    /// Evaluate against a JSON state.
+    pub fn evaluate(&self, state: &Value) -> Result<bool, AdlError> {
+        let Some(ast) = &self.ast else {
+            return Ok(true);
+        };
+        self.eval(ast, state)?
+            .as_bool()
+            .map_err(|actual| AdlError::ExpressionError {
+                from: self.source.clone(),
+                detail: format!("expected a bool result, got {actual}"),
+            })
+    }
 
-    let mut scope = Scope::new();
-    scope.push_dynamic("state", dynamic_state);
+    /// Evaluate against a JSON state, returning the result whatever its type.
+    /// A result with no JSON form is given as its string form; the
+    /// `"default"` fallback gives `true`.
+    pub fn evaluate_value(&self, state: &Value) -> Result<Value, AdlError> {
+        let Some(ast) = &self.ast else {
+            return Ok(Value::Bool(true));
+        };
+        let result = self.eval(ast, state)?;
+        Ok(rhai::serde::from_dynamic(&result)
+            .unwrap_or_else(|_| Value::String(result.to_string())))
+    }
 
-    let result: bool = engine
-        .eval_with_scope(&mut scope, expression)
-        .map_err(|e| AdlError::ExpressionError {
-            from: expression.to_string(),
-            detail: e.to_string(),
-        })?;
+    fn eval(&self, ast: &AST, state: &Value) -> Result<Dynamic, AdlError> {
+        // Convert serde_json::Value -> Rhai Dynamic
+        let dynamic_state =
+            rhai::serde::to_dynamic(state).map_err(|e| AdlError::ExpressionError {
+                from: self.source.clone(),
+                detail: format!("Failed to convert state to Rhai dynamic: {e}"),
+            })?;
+
+        let mut scope = Scope::new();
+        scope.push_dynamic("state", dynamic_state);
+
+        sandboxed_engine()
+            .eval_ast_with_scope(&mut scope, ast)
+            .map_err(|e| AdlError::ExpressionError {
+                from: self.source.clone(),
+                detail: e.to_string(),
+            })
+    }
+}
 
-    Ok(result)
+/// Shared sandboxed Rhai engine with safety limits.
+fn sandboxed_engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(new_sandboxed_engine)
 }
 
-/// Create a sandboxed Rhai engine with safety limits.
-fn create_sandboxed_engine() -> Engine {
+/// A Rhai engine with the safety limits used for ADL expressions, and with
+/// `print`/`debug` output discarded.
+///
+/// Other embedders of untrusted expressions build on this so every Rhai
+/// sandbox in the workspace shares the same limits.
+pub fn new_sandboxed_engine() -> Engine {
     let mut engine = Engine::new();
     engine.set_max_operations(10_000);
     engine.set_max_call_levels(8);
@@ -43,6 +116,8 @@ fn create_sandboxed_engine() -> Engine {
     engine.set_max_string_size(4096);
     engine.set_max_array_size(256);
     engine.set_max_map_size(128);
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
     engine
 }
 
@@ -107,4 +182,31 @@ mod tests {
         let result = evaluate("state.x + 1", &state);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn compiled_expression_reused_across_states() {
+        let expr = CompiledExpression::compile("state.score > 50").unwrap();
+        assert_eq!(expr.source(), "state.score > 50");
+        assert!(expr.evaluate(&json!({"score": 80})).unwrap());
+        assert!(!expr.evaluate(&json!({"score": 20})).unwrap());
+    }
+
+    #[test]
+    fn evaluate_value_returns_any_result_type() {
+        let expr = CompiledExpression::compile(
+            r#"if state.score > 80 { "high" } else { state.score * 2 }"#,
+        )
+        .unwrap();
+        assert_eq!(expr.evaluate_value(&json!({"score": 90})).unwrap(), json!("high"));
+        assert_eq!(expr.evaluate_value(&json!({"score": 10})).unwrap(), json!(20));
+        let default = CompiledExpression::compile("default").unwrap();
+        assert_eq!(default.evaluate_value(&json!({})).unwrap(), json!(true));
+    }
+
+    #[test]
+    fn compile_rejects_syntax_errors() {
+        let err = CompiledExpression::compile("state.x >").unwrap_err();
+        assert!(matches!(err, AdlError::ExpressionError { .. }));
+        assert!(CompiledExpression::compile("default").unwrap().evaluate(&json!({})).unwrap());
+    }
 }
@@ -1,6 +1,7 @@
 pub mod builder;
 pub mod error;
 pub mod expression;
+pub mod policy;
 pub mod reactflow;
 pub mod registry;
 pub mod types;
@@ -9,10 +10,11 @@ pub mod validation;
 pub mod prelude {
     pub use crate::builder::AdlBuilder;
     pub use crate::error::AdlError;
+    pub use crate::policy::NodePolicy;
     pub use crate::reactflow::{
         adl_to_reactflow, reactflow_to_adl, Position, ReactFlowEdge, ReactFlowGraph,
         ReactFlowNode,
     };
-    pub use crate::registry::ComponentRegistry;
-    pub use crate::types::AdlDocument;
+    pub use crate::registry::{ComponentRegistry, ParamSpec, ParamType};
+    pub use crate::types::{AdlDocument, SubgraphConfig};
 }
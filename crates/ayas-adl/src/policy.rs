@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ayas_core::config::RunnableConfig;
+use ayas_core::error::AyasError;
+use ayas_graph::prelude::NodeFn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AdlError;
+
+/// State key a failing node with an `on_error` target writes its error to.
+pub const ERROR_CHANNEL: &str = "__error";
+
+/// Longest wait between two attempts of a retried node.
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Wait before retry number `retry` (1-based): 100ms, doubling with each
+/// retry up to [`MAX_RETRY_DELAY`].
+pub fn retry_delay(retry: u32) -> Duration {
+    let factor = 1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX);
+    Duration::from_millis(100u64.saturating_mul(factor)).min(MAX_RETRY_DELAY)
+}
+
+/// Per-node execution policy.
+///
+/// Stored in the node's config under the same keys (`max_retries`,
+/// `timeout_ms`, `on_error`), so a policy can be written directly in the ADL
+/// document or attached with `AdlBuilder::node_policy`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodePolicy {
+    /// Extra attempts after the first failure, with exponential backoff.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Time limit for each attempt, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Node to route to once all attempts fail, instead of failing the graph.
+    /// The error message is written to the `__error` channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_error: Option<String>,
+}
+
+impl NodePolicy {
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    pub fn with_on_error(mut self, target: impl Into<String>) -> Self {
+        self.on_error = Some(target.into());
+        self
+    }
+
+    /// Read the policy keys from a node's config.
+    pub fn from_config(node_id: &str, config: &HashMap<String, Value>) -> Result<Self, AdlError> {
+        let keys = ["max_retries", "timeout_ms", "on_error"];
+        let fields: serde_json::Map<String, Value> = keys
+            .iter()
+            .filter_map(|k| config.get(*k).map(|v| (k.to_string(), v.clone())))
+            .collect();
+        serde_json::from_value(Value::Object(fields)).map_err(|e| AdlError::InvalidConfig {
+            node_id: node_id.to_string(),
+            detail: format!("invalid node policy: {e}"),
+        })
+    }
+
+    /// Write this policy's keys into a node's config, replacing existing ones.
+    pub fn write_to(&self, config: &mut HashMap<String, Value>) {
+        if let Ok(Value::Object(fields)) = serde_json::to_value(self) {
+            config.extend(fields);
+        }
+    }
+
+    /// Whether the policy changes how the node runs.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Run `attempt` with this policy's retries and per-attempt timeout;
+    /// `on_error` is left to the caller.
+    ///
+    /// On success, returns the output with the errors of the attempts that
+    /// failed before it. Otherwise returns the errors of all attempts, the
+    /// last one at the end.
+    pub async fn run_attempts<F, Fut>(
+        &self,
+        node: &str,
+        attempt: F,
+    ) -> Result<(Value, Vec<AyasError>), Vec<AyasError>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = ayas_core::error::Result<Value>>,
+    {
+        let max_retries = self.max_retries.unwrap_or(0);
+        let timeout = self.timeout_ms.map(Duration::from_millis);
+        let mut failures = Vec::new();
+        for retry in 0..=max_retries {
+            if retry > 0 {
+                tokio::time::sleep(retry_delay(retry)).await;
+            }
+            let result = match timeout {
+                Some(limit) => match tokio::time::timeout(limit, attempt()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(AyasError::Other(format!(
+                        "Node '{node}' timed out after {}ms",
+                        limit.as_millis()
+                    ))),
+                },
+                None => attempt().await,
+            };
+            match result {
+                Ok(output) => return Ok((output, failures)),
+                Err(e) => failures.push(e),
+            }
+        }
+        Err(failures)
+    }
+
+    /// Wrap `node` so each run follows this policy.
+    pub fn wrap(&self, node: NodeFn) -> NodeFn {
+        if self.is_empty() {
+            return node;
+        }
+        let name = node.name().to_string();
+        let inner = Arc::new(node);
+        let policy = self.clone();
+
+        NodeFn::new(name, move |state: Value, config: RunnableConfig| {
+            let inner = inner.clone();
+            let policy = policy.clone();
+            async move {
+                let run = policy
+                    .run_attempts(inner.name(), || inner.invoke(state.clone(), &config))
+                    .await;
+                let mut failures = match run {
+                    Ok((output, _)) => return Ok(output),
+                    Err(failures) => failures,
+                };
+                let err = failures.pop().expect("at least one attempt runs");
+                if policy.on_error.is_some() {
+                    let mut output = serde_json::Map::new();
+                    output.insert(ERROR_CHANNEL.to_string(), Value::String(err.to_string()));
+                    Ok(Value::Object(output))
+                } else {
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use serde_json::json;
+
+    fn flaky_node(failures: u32, calls: Arc<AtomicU32>) -> NodeFn {
+        NodeFn::new("flaky", move |state: Value, _config| {
+            let calls = calls.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < failures {
+                    Err(AyasError::Other("boom".into()))
+                } else {
+                    Ok(state)
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn config_roundtrip() {
+        let policy = NodePolicy::default()
+            .with_max_retries(2)
+            .with_timeout_ms(500)
+            .with_on_error("fallback");
+        let mut config = HashMap::from([("prompt".to_string(), json!("hi"))]);
+        policy.write_to(&mut config);
+        assert_eq!(config["timeout_ms"], json!(500));
+        assert_eq!(config["prompt"], json!("hi"));
+        assert_eq!(NodePolicy::from_config("n", &config).unwrap(), policy);
+    }
+
+    #[test]
+    fn invalid_policy_config_names_node() {
+        let config = HashMap::from([("max_retries".to_string(), json!("three"))]);
+        let err = NodePolicy::from_config("writer", &config).unwrap_err();
+        assert!(matches!(err, AdlError::InvalidConfig { ref node_id, .. } if node_id == "writer"));
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let node = NodePolicy::default()
+            .with_max_retries(2)
+            .wrap(flaky_node(2, calls.clone()));
+        let config = RunnableConfig::default();
+        let result = node.invoke(json!({"x": 1}), &config).await.unwrap();
+        assert_eq!(result, json!({"x": 1}));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn timeout_fails_attempt() {
+        let slow = NodeFn::new("slow", |state: Value, _config| async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(state)
+        });
+        let node = NodePolicy::default().with_timeout_ms(100).wrap(slow);
+        let err = node
+            .invoke(json!({}), &RunnableConfig::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out after 100ms"));
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_cap() {
+        assert_eq!(retry_delay(1), Duration::from_millis(100));
+        assert_eq!(retry_delay(3), Duration::from_millis(400));
+        assert_eq!(retry_delay(40), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn on_error_writes_error_channel() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let node = NodePolicy::default()
+            .with_on_error("fallback")
+            .wrap(flaky_node(1, calls));
+        let result = node
+            .invoke(json!({}), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(result, json!({"__error": "boom"}));
+    }
+}
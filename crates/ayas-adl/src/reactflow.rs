@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -32,6 +34,24 @@ pub struct ReactFlowEdge {
     pub target: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Edge semantics for the editor: `condition` (conditional edges),
+    /// `fanOut` and `onError` (static edges).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl ReactFlowEdge {
+    fn condition(&self) -> Option<&str> {
+        self.data.as_ref()?.get("condition")?.as_str()
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|d| d.get(key))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,11 +137,18 @@ pub fn adl_to_reactflow(doc: &AdlDocument) -> ReactFlowGraph {
                     .map(|t| normalize_id(t))
                     .unwrap_or_else(|| "__end__".to_string());
 
+                let data = (adl_edge.fan_out || adl_edge.on_error).then(|| {
+                    serde_json::json!({
+                        "fanOut": adl_edge.fan_out,
+                        "onError": adl_edge.on_error,
+                    })
+                });
                 edges.push(ReactFlowEdge {
                     id: format!("e{i}"),
                     source: from,
                     target: to,
                     label: None,
+                    data,
                 });
             }
             AdlEdgeType::Conditional => {
@@ -137,6 +164,7 @@ pub fn adl_to_reactflow(doc: &AdlDocument) -> ReactFlowGraph {
                         source: from.clone(),
                         target: to,
                         label,
+                        data: Some(serde_json::json!({"condition": cond.expression})),
                     });
                 }
             }
@@ -215,44 +243,61 @@ pub fn reactflow_to_adl(graph: &ReactFlowGraph) -> AdlDocument {
         }
     }
 
-    // Group edges by source to detect conditional edges
-    let mut edge_groups: std::collections::HashMap<String, Vec<&ReactFlowEdge>> =
-        std::collections::HashMap::new();
+    // Edges without `data` predate it: a labelled group of several edges from
+    // one source is read as a conditional edge
+    let mut group_sizes: HashMap<&str, usize> = HashMap::new();
+    let mut labelled_sources: HashSet<&str> = HashSet::new();
     for edge in &graph.edges {
-        edge_groups.entry(edge.source.clone()).or_default().push(edge);
+        *group_sizes.entry(edge.source.as_str()).or_default() += 1;
+        if edge.label.is_some() && edge.data.is_none() {
+            labelled_sources.insert(edge.source.as_str());
+        }
     }
 
-    let mut edges = Vec::new();
-    for (source, group) in &edge_groups {
-        // If any edge in this group has a label, treat as conditional
-        let has_labels = group.iter().any(|e| e.label.is_some());
-
-        if has_labels && group.len() > 1 {
-            let conditions: Vec<AdlCondition> = group
-                .iter()
-                .map(|e| AdlCondition {
-                    expression: e
-                        .label
-                        .as_deref()
-                        .unwrap_or("default")
-                        .to_string(),
-                    to: normalize_id(&e.target),
-                })
-                .collect();
+    // Conditions from one source are collected into a single conditional
+    // edge, placed where its first condition appears
+    let mut conditional_at: HashMap<String, usize> = HashMap::new();
+    let mut edges: Vec<AdlEdge> = Vec::new();
+    for e in &graph.edges {
+        let from = normalize_id(&e.source);
+        let legacy_condition = e.data.is_none()
+            && labelled_sources.contains(e.source.as_str())
+            && group_sizes[e.source.as_str()] > 1;
+        let expression = match e.condition() {
+            Some(expr) => Some(expr.to_string()),
+            None if legacy_condition => {
+                Some(e.label.as_deref().unwrap_or("default").to_string())
+            }
+            None => None,
+        };
 
+        let Some(expression) = expression else {
             edges.push(AdlEdge {
-                from: normalize_id(source),
-                to: None,
-                edge_type: AdlEdgeType::Conditional,
-                conditions,
+                from,
+                to: Some(normalize_id(&e.target)),
+                edge_type: AdlEdgeType::Static,
+                conditions: vec![],
+                fan_out: e.flag("fanOut"),
+                on_error: e.flag("onError"),
             });
-        } else {
-            for e in group {
+            continue;
+        };
+
+        let condition = AdlCondition {
+            expression,
+            to: normalize_id(&e.target),
+        };
+        match conditional_at.get(&from) {
+            Some(&idx) => edges[idx].conditions.push(condition),
+            None => {
+                conditional_at.insert(from.clone(), edges.len());
                 edges.push(AdlEdge {
-                    from: normalize_id(source),
-                    to: Some(normalize_id(&e.target)),
-                    edge_type: AdlEdgeType::Static,
-                    conditions: vec![],
+                    from,
+                    to: None,
+                    edge_type: AdlEdgeType::Conditional,
+                    conditions: vec![condition],
+                    fan_out: false,
+                    on_error: false,
                 });
             }
         }
@@ -321,18 +366,24 @@ mod tests {
                     to: Some("node_a".into()),
                     edge_type: AdlEdgeType::Static,
                     conditions: vec![],
+                    fan_out: false,
+                    on_error: false,
                 },
                 AdlEdge {
                     from: "node_a".into(),
                     to: Some("node_b".into()),
                     edge_type: AdlEdgeType::Static,
                     conditions: vec![],
+                    fan_out: false,
+                    on_error: false,
                 },
                 AdlEdge {
                     from: "node_b".into(),
                     to: Some("__end__".into()),
                     edge_type: AdlEdgeType::Static,
                     conditions: vec![],
+                    fan_out: false,
+                    on_error: false,
                 },
             ],
         }
@@ -366,6 +417,8 @@ mod tests {
                     to: Some("router".into()),
                     edge_type: AdlEdgeType::Static,
                     conditions: vec![],
+                    fan_out: false,
+                    on_error: false,
                 },
                 AdlEdge {
                     from: "router".into(),
@@ -381,6 +434,8 @@ mod tests {
                             to: "path_b".into(),
                         },
                     ],
+                    fan_out: false,
+                    on_error: false,
                 },
             ],
         }
@@ -474,12 +529,14 @@ mod tests {
                     source: "__start__".into(),
                     target: "my_node".into(),
                     label: None,
+                    data: None,
                 },
                 ReactFlowEdge {
                     id: "e1".into(),
                     source: "my_node".into(),
                     target: "__end__".into(),
                     label: None,
+                    data: None,
                 },
             ],
         };
@@ -539,12 +596,14 @@ mod tests {
                     source: "__start__".into(),
                     target: "processor".into(),
                     label: None,
+                    data: None,
                 },
                 ReactFlowEdge {
                     id: "e1".into(),
                     source: "processor".into(),
                     target: "__end__".into(),
                     label: None,
+                    data: None,
                 },
             ],
         };
@@ -559,6 +618,59 @@ mod tests {
         assert!(result.nodes.iter().any(|n| n.id == "processor"));
     }
 
+    fn static_edge(from: &str, to: &str, fan_out: bool, on_error: bool) -> AdlEdge {
+        AdlEdge {
+            from: from.into(),
+            to: Some(to.into()),
+            edge_type: AdlEdgeType::Static,
+            conditions: vec![],
+            fan_out,
+            on_error,
+        }
+    }
+
+    #[test]
+    fn roundtrip_preserves_edge_semantics() {
+        let mut original = make_conditional_adl();
+        original.edges.extend([
+            static_edge("path_a", "worker_1", true, false),
+            static_edge("path_a", "worker_2", true, false),
+            static_edge("path_b", "fallback", false, true),
+            static_edge("path_b", "__end__", false, false),
+        ]);
+
+        let graph = adl_to_reactflow(&original);
+        let fan_out = graph.edges.iter().find(|e| e.target == "worker_1").unwrap();
+        assert_eq!(fan_out.data, Some(json!({"fanOut": true, "onError": false})));
+        let cond = graph.edges.iter().find(|e| e.target == "path_a").unwrap();
+        assert_eq!(cond.data, Some(json!({"condition": "state.x > 0"})));
+
+        // Survives serialization to the editor and back
+        let json = serde_json::to_string(&graph).unwrap();
+        let graph: ReactFlowGraph = serde_json::from_str(&json).unwrap();
+
+        let result = reactflow_to_adl(&graph);
+        assert_eq!(result.edges, original.edges);
+    }
+
+    #[test]
+    fn reactflow_to_adl_single_condition_with_data() {
+        let graph = ReactFlowGraph {
+            nodes: vec![],
+            edges: vec![ReactFlowEdge {
+                id: "e0".into(),
+                source: "a".into(),
+                target: "b".into(),
+                label: Some("state.ok".into()),
+                data: Some(json!({"condition": "state.ok"})),
+            }],
+        };
+        let doc = reactflow_to_adl(&graph);
+        assert_eq!(doc.edges.len(), 1);
+        assert_eq!(doc.edges[0].edge_type, AdlEdgeType::Conditional);
+        assert_eq!(doc.edges[0].conditions[0].expression, "state.ok");
+    }
+
     #[test]
     fn reactflow_graph_serde_roundtrip() {
         let graph = ReactFlowGraph {
@@ -573,6 +685,7 @@ mod tests {
                 source: "n1".into(),
                 target: "n2".into(),
                 label: Some("connection".into()),
+                data: None,
             }],
         };
 
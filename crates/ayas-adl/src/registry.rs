@@ -6,40 +6,172 @@ use ayas_graph::prelude::NodeFn;
 use serde_json::Value;
 
 use crate::error::AdlError;
+use crate::types::SUBGRAPH_NODE_TYPE;
 
 /// Factory function signature: (node_id, config) -> NodeFn.
 pub type NodeFactory =
     Arc<dyn Fn(&str, &HashMap<String, Value>) -> Result<NodeFn, AdlError> + Send + Sync>;
 
+/// JSON type of a declared component parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+}
+
+impl ParamType {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            ParamType::String => value.is_string(),
+            ParamType::Number => value.is_number(),
+            ParamType::Bool => value.is_boolean(),
+            ParamType::Array => value.is_array(),
+            ParamType::Object => value.is_object(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ParamType::String => "string",
+            ParamType::Number => "number",
+            ParamType::Bool => "bool",
+            ParamType::Array => "array",
+            ParamType::Object => "object",
+        }
+    }
+}
+
+/// A config parameter a component declares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamSpec {
+    pub name: String,
+    pub param_type: ParamType,
+    pub required: bool,
+}
+
+impl ParamSpec {
+    /// A parameter every node of the component must set.
+    pub fn required(name: impl Into<String>, param_type: ParamType) -> Self {
+        Self {
+            name: name.into(),
+            param_type,
+            required: true,
+        }
+    }
+
+    /// A parameter that may be left out.
+    pub fn optional(name: impl Into<String>, param_type: ParamType) -> Self {
+        Self {
+            name: name.into(),
+            param_type,
+            required: false,
+        }
+    }
+
+    /// Describe what is wrong with `config` for this parameter, if anything.
+    pub fn check(&self, config: &HashMap<String, Value>) -> Option<String> {
+        match config.get(&self.name) {
+            None if self.required => Some(format!("missing required parameter '{}'", self.name)),
+            None => None,
+            Some(value) if !self.param_type.matches(value) => Some(format!(
+                "parameter '{}' must be {}, got {}",
+                self.name,
+                self.param_type.name(),
+                json_type_name(value)
+            )),
+            Some(_) => None,
+        }
+    }
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A registered node type: its factory and, optionally, its declared config
+/// parameters. Components without a parameter list accept any config.
+struct ComponentDescriptor {
+    factory: NodeFactory,
+    params: Option<Vec<ParamSpec>>,
+}
+
 /// Registry mapping node type strings to factory functions.
 pub struct ComponentRegistry {
-    node_factories: HashMap<String, NodeFactory>,
+    components: HashMap<String, ComponentDescriptor>,
 }
 
 impl ComponentRegistry {
     /// Create an empty registry.
     pub fn new() -> Self {
         Self {
-            node_factories: HashMap::new(),
+            components: HashMap::new(),
         }
     }
 
     /// Create a registry pre-loaded with builtin node types.
     pub fn with_builtins() -> Self {
         let mut registry = Self::new();
-        registry.register("passthrough", passthrough_factory());
-        registry.register("transform", transform_factory());
+        registry.register_with_params("passthrough", passthrough_factory(), vec![]);
+        registry.register_with_params(
+            "transform",
+            transform_factory(),
+            vec![ParamSpec::optional("mapping", ParamType::Object)],
+        );
+        registry.register_with_params(
+            SUBGRAPH_NODE_TYPE,
+            subgraph_factory(),
+            vec![
+                ParamSpec::optional("graph", ParamType::String),
+                ParamSpec::optional("document", ParamType::Object),
+                ParamSpec::optional("input_mapping", ParamType::Object),
+                ParamSpec::optional("output_mapping", ParamType::Object),
+            ],
+        );
         registry
     }
 
-    /// Register a node factory for a given type name.
+    /// Register a node factory for a given type name. Its config is not
+    /// checked during validation.
     pub fn register(&mut self, type_name: impl Into<String>, factory: NodeFactory) {
-        self.node_factories.insert(type_name.into(), factory);
+        self.insert(type_name.into(), factory, None);
+    }
+
+    /// Register a node factory along with the config parameters it accepts.
+    ///
+    /// Validation checks declared parameters for presence and JSON type;
+    /// config keys that are not declared are allowed.
+    pub fn register_with_params(
+        &mut self,
+        type_name: impl Into<String>,
+        factory: NodeFactory,
+        params: Vec<ParamSpec>,
+    ) {
+        self.insert(type_name.into(), factory, Some(params));
+    }
+
+    fn insert(&mut self, type_name: String, factory: NodeFactory, params: Option<Vec<ParamSpec>>) {
+        self.components
+            .insert(type_name, ComponentDescriptor { factory, params });
     }
 
     /// Check if a node type is registered.
     pub fn has_type(&self, type_name: &str) -> bool {
-        self.node_factories.contains_key(type_name)
+        self.components.contains_key(type_name)
+    }
+
+    /// Declared config parameters of a node type, if it declares any.
+    pub fn params(&self, type_name: &str) -> Option<&[ParamSpec]> {
+        self.components.get(type_name)?.params.as_deref()
     }
 
     /// Create a node from the registry using the type name and config.
@@ -49,12 +181,12 @@ impl ComponentRegistry {
         node_type: &str,
         config: &HashMap<String, Value>,
     ) -> Result<NodeFn, AdlError> {
-        let factory = self.node_factories.get(node_type).ok_or_else(|| {
+        let component = self.components.get(node_type).ok_or_else(|| {
             AdlError::UnknownNodeType {
                 node_type: node_type.to_string(),
             }
         })?;
-        factory(node_id, config)
+        (component.factory)(node_id, config)
     }
 }
 
@@ -74,6 +206,17 @@ fn passthrough_factory() -> NodeFactory {
     })
 }
 
+/// Placeholder for `subgraph` nodes, which `AdlBuilder` builds itself since
+/// they need the other documents it knows about.
+fn subgraph_factory() -> NodeFactory {
+    Arc::new(|node_id: &str, _config: &HashMap<String, Value>| {
+        Err(AdlError::InvalidConfig {
+            node_id: node_id.to_string(),
+            detail: "subgraph nodes can only be built by AdlBuilder".into(),
+        })
+    })
+}
+
 /// Built-in transform factory: copies specified fields from state to output.
 ///
 /// Config:
@@ -125,6 +268,7 @@ mod tests {
         let registry = ComponentRegistry::with_builtins();
         assert!(registry.has_type("passthrough"));
         assert!(registry.has_type("transform"));
+        assert!(registry.has_type("subgraph"));
         assert!(!registry.has_type("nonexistent"));
     }
 
@@ -137,6 +281,36 @@ mod tests {
         assert!(matches!(err, AdlError::UnknownNodeType { .. }));
     }
 
+    #[test]
+    fn builtins_declare_params() {
+        let registry = ComponentRegistry::with_builtins();
+        assert_eq!(registry.params("passthrough"), Some(&[][..]));
+        let transform = registry.params("transform").unwrap();
+        assert_eq!(transform[0], ParamSpec::optional("mapping", ParamType::Object));
+
+        let mut plain = ComponentRegistry::new();
+        plain.register("custom", passthrough_factory());
+        assert!(plain.params("custom").is_none());
+    }
+
+    #[test]
+    fn param_spec_check() {
+        let spec = ParamSpec::required("model", ParamType::String);
+        let mut config = HashMap::new();
+        assert_eq!(
+            spec.check(&config).as_deref(),
+            Some("missing required parameter 'model'")
+        );
+        config.insert("model".to_string(), json!(3));
+        assert_eq!(
+            spec.check(&config).as_deref(),
+            Some("parameter 'model' must be string, got number")
+        );
+        config.insert("model".to_string(), json!("gpt"));
+        assert!(spec.check(&config).is_none());
+        assert!(ParamSpec::optional("x", ParamType::Bool).check(&HashMap::new()).is_none());
+    }
+
     #[tokio::test]
     async fn passthrough_node_returns_input() {
         let registry = ComponentRegistry::with_builtins();
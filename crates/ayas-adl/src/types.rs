@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::error::AdlError;
+
 /// Top-level ADL document.
 #[derive(Debug, Deserialize)]
 pub struct AdlDocument {
@@ -70,7 +72,7 @@ pub struct AdlNode {
 }
 
 /// An edge definition in the ADL document.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct AdlEdge {
     /// Source node ID (or "__start__" / "START").
     pub from: String,
@@ -83,6 +85,14 @@ pub struct AdlEdge {
     /// Conditions for conditional edges.
     #[serde(default)]
     pub conditions: Vec<AdlCondition>,
+    /// When true, static edges sharing this `from` node run their targets in
+    /// parallel. Kept for visual editors; the builder treats them as static.
+    #[serde(default)]
+    pub fan_out: bool,
+    /// When true, the edge is only followed when the source node fails. Kept
+    /// for visual editors; the builder does not route on errors yet.
+    #[serde(default)]
+    pub on_error: bool,
 }
 
 fn default_edge_type() -> AdlEdgeType {
@@ -98,7 +108,7 @@ pub enum AdlEdgeType {
 }
 
 /// A condition in a conditional edge.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize)]
 pub struct AdlCondition {
     /// Rhai expression to evaluate against the state.
     /// "default" is treated as always-true (fallback).
@@ -107,6 +117,46 @@ pub struct AdlCondition {
     pub to: String,
 }
 
+/// Node type of a node that runs another ADL document as a subgraph.
+pub const SUBGRAPH_NODE_TYPE: &str = "subgraph";
+
+/// Config of a `subgraph` node: the document to run (exactly one of `graph`
+/// or `document`) and how state maps into and out of it.
+#[derive(Debug, Deserialize)]
+pub struct SubgraphConfig {
+    /// Id of a document registered with `AdlBuilder::with_document`.
+    #[serde(default)]
+    pub graph: Option<String>,
+    /// An inline document.
+    #[serde(default)]
+    pub document: Option<AdlDocument>,
+    /// Parent state key → subgraph input key. Empty passes the whole state.
+    #[serde(default)]
+    pub input_mapping: HashMap<String, String>,
+    /// Subgraph output key → parent state key. Empty returns the whole output.
+    #[serde(default)]
+    pub output_mapping: HashMap<String, String>,
+}
+
+impl SubgraphConfig {
+    /// Parse a `subgraph` node's config.
+    pub fn from_config(node_id: &str, config: &HashMap<String, Value>) -> Result<Self, AdlError> {
+        let invalid = |detail: String| AdlError::InvalidConfig {
+            node_id: node_id.to_string(),
+            detail,
+        };
+        let value = Value::Object(config.clone().into_iter().collect());
+        let parsed: Self = serde_json::from_value(value)
+            .map_err(|e| invalid(format!("invalid subgraph config: {e}")))?;
+        match (&parsed.graph, &parsed.document) {
+            (Some(_), None) | (None, Some(_)) => Ok(parsed),
+            _ => Err(invalid(
+                "subgraph config needs exactly one of 'graph' or 'document'".into(),
+            )),
+        }
+    }
+}
+
 /// Normalize sentinel names: accept both `__start__`/`START` and `__end__`/`END`.
 pub fn normalize_sentinel(name: &str) -> String {
     match name {
@@ -121,6 +171,26 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn subgraph_config_by_id_or_inline() {
+        let by_id = HashMap::from([
+            ("graph".to_string(), json!("summarize")),
+            ("output_mapping".to_string(), json!({"summary": "result"})),
+        ]);
+        let config = SubgraphConfig::from_config("sub", &by_id).unwrap();
+        assert_eq!(config.graph.as_deref(), Some("summarize"));
+        assert_eq!(config.output_mapping["summary"], "result");
+
+        let inline = HashMap::from([(
+            "document".to_string(),
+            json!({"version": "1.0", "nodes": [], "edges": []}),
+        )]);
+        assert!(SubgraphConfig::from_config("sub", &inline).unwrap().document.is_some());
+
+        let err = SubgraphConfig::from_config("sub", &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("exactly one of"));
+    }
+
     #[test]
     fn deserialize_minimal_yaml() {
         let yaml = r#"
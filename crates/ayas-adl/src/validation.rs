@@ -1,8 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::error::AdlError;
 use crate::registry::ComponentRegistry;
-use crate::types::{AdlDocument, AdlEdgeType, normalize_sentinel};
+use crate::types::{
+    AdlDocument, AdlEdgeType, SUBGRAPH_NODE_TYPE, SubgraphConfig, normalize_sentinel,
+};
 
 /// Validate an ADL document against the given registry.
 pub fn validate_document(doc: &AdlDocument, registry: &ComponentRegistry) -> Result<(), AdlError> {
@@ -48,13 +50,81 @@ fn validate_node_ids(doc: &AdlDocument) -> Result<(), AdlError> {
 }
 
 fn validate_node_types(doc: &AdlDocument, registry: &ComponentRegistry) -> Result<(), AdlError> {
+    validate_components(doc, registry).map_err(|mut errors| errors.remove(0))
+}
+
+/// Check that every node names a registered component and that its config
+/// matches the component's declared parameters.
+///
+/// Unlike [`validate_document`], this reports every problem found, each
+/// naming the offending node.
+pub fn validate_components(
+    doc: &AdlDocument,
+    registry: &ComponentRegistry,
+) -> Result<(), Vec<AdlError>> {
+    let mut errors = Vec::new();
     for node in &doc.nodes {
         if !registry.has_type(&node.node_type) {
-            return Err(AdlError::UnknownNodeType {
+            errors.push(AdlError::UnknownComponent {
+                node_id: node.id.clone(),
                 node_type: node.node_type.clone(),
             });
+            continue;
+        }
+        for param in registry.params(&node.node_type).unwrap_or_default() {
+            if let Some(detail) = param.check(&node.config) {
+                errors.push(AdlError::InvalidConfig {
+                    node_id: node.id.clone(),
+                    detail,
+                });
+            }
         }
     }
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Check that every `subgraph` node resolves to a document and that no
+/// document reaches itself through subgraph references.
+///
+/// `root` names `doc` in the reference chain reported for a cycle; documents
+/// referenced by id are looked up in `library`.
+pub fn validate_subgraphs(
+    doc: &AdlDocument,
+    root: &str,
+    library: &HashMap<String, AdlDocument>,
+) -> Result<(), AdlError> {
+    let mut chain = vec![root.to_string()];
+    walk_subgraphs(doc, library, &mut chain)
+}
+
+fn walk_subgraphs(
+    doc: &AdlDocument,
+    library: &HashMap<String, AdlDocument>,
+    chain: &mut Vec<String>,
+) -> Result<(), AdlError> {
+    for node in doc.nodes.iter().filter(|n| n.node_type == SUBGRAPH_NODE_TYPE) {
+        let config = SubgraphConfig::from_config(&node.id, &node.config)?;
+        let (name, sub) = match (&config.graph, &config.document) {
+            (Some(id), _) => {
+                if chain.contains(id) {
+                    return Err(AdlError::Validation(format!(
+                        "Subgraph cycle: {} -> {id}",
+                        chain.join(" -> ")
+                    )));
+                }
+                let sub = library.get(id).ok_or_else(|| AdlError::InvalidConfig {
+                    node_id: node.id.clone(),
+                    detail: format!("unknown subgraph '{id}'"),
+                })?;
+                (id.clone(), sub)
+            }
+            (None, Some(inline)) => (format!("{} (inline)", node.id), inline),
+            (None, None) => unreachable!("SubgraphConfig::from_config requires a source"),
+        };
+        chain.push(name);
+        walk_subgraphs(sub, library, chain)?;
+        chain.pop();
+    }
     Ok(())
 }
 
@@ -127,6 +197,14 @@ fn validate_conditional_edges(doc: &AdlDocument) -> Result<(), AdlError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use ayas_core::config::RunnableConfig;
+    use ayas_graph::prelude::NodeFn;
+    use serde_json::Value;
+
+    use crate::registry::{NodeFactory, ParamSpec, ParamType};
     use crate::types::AdlDocument;
 
     fn registry() -> ComponentRegistry {
@@ -223,6 +301,115 @@ edges:
         assert!(err.to_string().contains("Unknown node type"));
     }
 
+    #[test]
+    fn validate_components_reports_every_node() {
+        let doc = parse(
+            r#"
+version: "1.0"
+nodes:
+  - id: a
+    type: passthrough
+  - id: b
+    type: llm
+  - id: c
+    type: transform
+    config:
+      mapping: "result"
+edges:
+  - from: __start__
+    to: a
+"#,
+        );
+        let errors = validate_components(&doc, &registry()).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            &errors[0],
+            AdlError::UnknownComponent { node_id, node_type }
+                if node_id == "b" && node_type == "llm"
+        ));
+        assert!(matches!(
+            &errors[1],
+            AdlError::InvalidConfig { node_id, detail }
+                if node_id == "c" && detail.contains("'mapping' must be object, got string")
+        ));
+    }
+
+    #[test]
+    fn required_param_missing_fails() {
+        let factory: NodeFactory = Arc::new(|id: &str, _config: &HashMap<String, Value>| {
+            Ok(NodeFn::new(id.to_string(), |state: Value, _config: RunnableConfig| async move {
+                Ok(state)
+            }))
+        });
+        let mut registry = registry();
+        registry.register_with_params(
+            "llm",
+            factory,
+            vec![ParamSpec::required("model", ParamType::String)],
+        );
+        let doc = parse(
+            r#"
+version: "1.0"
+nodes:
+  - id: writer
+    type: llm
+edges:
+  - from: __start__
+    to: writer
+"#,
+        );
+        let err = validate_document(&doc, &registry).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid config for node 'writer': missing required parameter 'model'"
+        );
+    }
+
+    fn subgraph_doc(target: &str) -> AdlDocument {
+        parse(&format!(
+            r#"
+version: "1.0"
+nodes:
+  - id: call_{target}
+    type: subgraph
+    config:
+      graph: {target}
+edges:
+  - from: __start__
+    to: call_{target}
+"#
+        ))
+    }
+
+    #[test]
+    fn subgraph_cycle_reports_chain() {
+        let library = HashMap::from([
+            ("a".to_string(), subgraph_doc("b")),
+            ("b".to_string(), subgraph_doc("a")),
+        ]);
+        let err = validate_subgraphs(&subgraph_doc("a"), "main", &library).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ADL validation error: Subgraph cycle: main -> a -> b -> a"
+        );
+    }
+
+    #[test]
+    fn subgraph_unknown_reference_fails() {
+        let err = validate_subgraphs(&subgraph_doc("missing"), "main", &HashMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown subgraph 'missing'"));
+    }
+
+    #[test]
+    fn subgraph_acyclic_references_pass() {
+        let library = HashMap::from([
+            ("a".to_string(), subgraph_doc("leaf")),
+            ("leaf".to_string(), parse("version: \"1.0\"\nnodes: []\nedges: []\n")),
+        ]);
+        assert!(validate_subgraphs(&subgraph_doc("a"), "main", &library).is_ok());
+    }
+
     #[test]
     fn edge_unknown_source_fails() {
         let doc = parse(
@@ -6,7 +6,15 @@ pub mod tool_calling;
 /// Prelude module for convenient imports.
 pub mod prelude {
     pub use crate::map_reduce::create_map_reduce_graph;
-    pub use crate::react::create_react_agent;
-    pub use crate::supervisor::{create_supervisor_agent, WorkerConfig};
-    pub use crate::tool_calling::create_tool_calling_agent;
+    pub use crate::react::{
+        create_react_agent, create_react_agent_with_options, OnMaxIterations, ReactAgentOptions,
+    };
+    pub use crate::supervisor::{
+        create_supervisor_agent, create_supervisor_agent_with_strategy, SupervisorStrategy,
+        WorkerConfig,
+    };
+    pub use crate::tool_calling::{
+        create_tool_calling_agent, create_tool_calling_agent_with_approval,
+        PENDING_APPROVAL_CHANNEL,
+    };
 }
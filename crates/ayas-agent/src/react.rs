@@ -3,8 +3,8 @@ use std::sync::Arc;
 
 use ayas_core::error::{AyasError, Result};
 use ayas_core::message::{AIContent, Message, ToolCall};
-use ayas_core::model::{CallOptions, ChatModel};
-use ayas_core::tool::{Tool, ToolDefinition};
+use ayas_core::model::{generate_with_config, CallOptions, ChatModel};
+use ayas_core::tool::{CoercingTool, Tool, ToolDefinition};
 use ayas_graph::compiled::CompiledStateGraph;
 use ayas_graph::constants::END;
 use ayas_graph::edge::ConditionalEdge;
@@ -13,6 +13,72 @@ use ayas_graph::state_graph::StateGraph;
 use futures::stream::{self, StreamExt, TryStreamExt};
 use serde_json::{json, Value};
 
+/// What a ReAct agent does when it reaches `max_iterations` while the model
+/// still wants to call tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnMaxIterations {
+    /// Stop and return the state as it is; the last message is the model's
+    /// unanswered tool-call request.
+    #[default]
+    ReturnLast,
+    /// Fail the run with an error.
+    Error,
+}
+
+/// Predicate on the graph state; returning `true` ends the run after the
+/// current agent step.
+pub type StopCondition = Arc<dyn Fn(&Value) -> bool + Send + Sync>;
+
+/// Optional limits and model settings for [`create_react_agent_with_options`].
+#[derive(Clone, Default)]
+pub struct ReactAgentOptions {
+    /// Maximum number of model calls. `None` leaves only `recursion_limit`.
+    pub max_iterations: Option<usize>,
+    pub on_max_iterations: OnMaxIterations,
+    /// Checked after every agent step, before routing to tools.
+    pub stop_condition: Option<StopCondition>,
+    /// Options for every model call, e.g. `temperature`. Their `tools` are
+    /// replaced by the agent's tools.
+    pub call_options: CallOptions,
+    /// Coerce stringified tool-call arguments to their schema types before
+    /// each tool call; see [`CoercingTool`].
+    pub coerce_arguments: bool,
+}
+
+impl ReactAgentOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn with_on_max_iterations(mut self, on_max_iterations: OnMaxIterations) -> Self {
+        self.on_max_iterations = on_max_iterations;
+        self
+    }
+
+    pub fn with_stop_condition(
+        mut self,
+        condition: impl Fn(&Value) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.stop_condition = Some(Arc::new(condition));
+        self
+    }
+
+    pub fn with_call_options(mut self, call_options: CallOptions) -> Self {
+        self.call_options = call_options;
+        self
+    }
+
+    pub fn with_coerce_arguments(mut self, coerce_arguments: bool) -> Self {
+        self.coerce_arguments = coerce_arguments;
+        self
+    }
+}
+
 /// Create a ReAct-style agent graph.
 ///
 /// The graph follows the cycle: `agent` -> `tools` -> `agent` -> ... -> END
@@ -35,36 +101,73 @@ pub fn create_react_agent(
     model: Arc<dyn ChatModel>,
     tools: Vec<Arc<dyn Tool>>,
 ) -> Result<CompiledStateGraph> {
-    let tool_defs: Vec<ToolDefinition> = tools.iter().map(|t| t.definition()).collect();
+    create_react_agent_with_options(model, tools, ReactAgentOptions::default())
+}
+
+/// Create a ReAct agent with an iteration cap, an early-stop condition and/or
+/// model call options.
+///
+/// The run ends when the model answers without tool calls, when
+/// `stop_condition` holds after an agent step, or when the model has been
+/// called `max_iterations` times; the last case follows `on_max_iterations`.
+/// With a cap set, the state gains an `iterations` channel counting model calls.
+pub fn create_react_agent_with_options(
+    model: Arc<dyn ChatModel>,
+    tools: Vec<Arc<dyn Tool>>,
+    options: ReactAgentOptions,
+) -> Result<CompiledStateGraph> {
+    let tool_defs = tools
+        .iter()
+        .map(|t| {
+            let def = t.definition();
+            def.validate()?;
+            Ok(def)
+        })
+        .collect::<Result<Vec<ToolDefinition>>>()?;
 
     // Build tool lookup map
+    let coerce_arguments = options.coerce_arguments;
     let tools_map: Arc<HashMap<String, Arc<dyn Tool>>> = Arc::new(
         tools
             .into_iter()
-            .map(|t| (t.definition().name, t))
+            .map(|t| {
+                let tool = if coerce_arguments {
+                    Arc::new(CoercingTool::new(t)) as Arc<dyn Tool>
+                } else {
+                    t
+                };
+                (tool.definition().name, tool)
+            })
             .collect(),
     );
 
     let mut graph = StateGraph::new();
     graph.add_append_channel("messages");
+    let max_iterations = options.max_iterations;
+    if max_iterations.is_some() {
+        graph.add_last_value_channel("iterations", json!(0));
+    }
 
     // Agent node: calls LLM with messages + tool definitions
     let model_clone = model.clone();
-    let tool_defs_clone = tool_defs.clone();
+    let call_options = Arc::new(CallOptions {
+        tools: tool_defs,
+        ..options.call_options
+    });
     graph.add_node(NodeFn::new(
         "agent",
-        move |state: Value, _config| {
+        move |state: Value, config| {
             let model = model_clone.clone();
-            let tool_defs = tool_defs_clone.clone();
+            let options = call_options.clone();
             async move {
                 let messages = parse_messages(&state["messages"])?;
-                let options = CallOptions {
-                    tools: tool_defs,
-                    ..Default::default()
-                };
-                let result = model.generate(&messages, &options).await?;
+                let result = generate_with_config(&*model, &messages, &options, &config).await?;
                 let msg_value = serde_json::to_value(&result.message)
                     .map_err(AyasError::Serialization)?;
+                if max_iterations.is_some() {
+                    let iterations = state["iterations"].as_u64().unwrap_or(0) + 1;
+                    return Ok(json!({"messages": msg_value, "iterations": iterations}));
+                }
                 Ok(json!({"messages": msg_value}))
             }
         },
@@ -90,8 +193,11 @@ pub fn create_react_agent(
                                     tc.name.clone(),
                                 ))
                             })?;
-                            let output = tool.call(tc.arguments.clone()).await?;
-                            let tool_msg = Message::tool(output, &tc.id);
+                            // Feed failures back to the model rather than aborting the run.
+                            let tool_msg = match tool.call(tc.arguments.clone()).await {
+                                Ok(output) => Message::tool(output, &tc.id),
+                                Err(e) => Message::tool_failure(&e, &tc.id),
+                            };
                             serde_json::to_value(&tool_msg).map_err(AyasError::Serialization)
                         }
                     },
@@ -112,13 +218,33 @@ pub fn create_react_agent(
     path_map.insert("tools".to_string(), "tools".to_string());
     path_map.insert("end".to_string(), END.to_string());
 
+    let fail_at_cap = options.on_max_iterations == OnMaxIterations::Error;
+    if let (Some(max), true) = (max_iterations, fail_at_cap) {
+        graph.add_node(NodeFn::new("max_iterations", move |_state: Value, _config| async move {
+            Err::<Value, _>(AyasError::Other(format!(
+                "ReAct agent reached max_iterations ({max}) without a final answer"
+            )))
+        }))?;
+        path_map.insert("max_iterations".to_string(), "max_iterations".to_string());
+    }
+
+    let stop_condition = options.stop_condition;
     graph.add_conditional_edges(ConditionalEdge::new(
         "agent",
-        |state: &Value| {
-            if last_message_has_tool_calls(state) {
-                "tools".to_string()
-            } else {
-                "end".to_string()
+        move |state: &Value| {
+            if !last_message_has_tool_calls(state) {
+                return "end".to_string();
+            }
+            if stop_condition.as_ref().is_some_and(|stop| stop(state)) {
+                return "end".to_string();
+            }
+            let at_cap = max_iterations.is_some_and(|max| {
+                state["iterations"].as_u64().unwrap_or(0) >= max as u64
+            });
+            match (at_cap, fail_at_cap) {
+                (true, true) => "max_iterations".to_string(),
+                (true, false) => "end".to_string(),
+                (false, _) => "tools".to_string(),
             }
         },
         Some(path_map),
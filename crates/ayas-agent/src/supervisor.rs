@@ -5,7 +5,7 @@ use ayas_checkpoint::prelude::{send_output, SendDirective};
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::{AyasError, Result};
 use ayas_core::message::Message;
-use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::model::{generate_with_config, CallOptions, ChatModel};
 use ayas_core::runnable::Runnable;
 use ayas_graph::compiled::CompiledStateGraph;
 use ayas_graph::constants::END;
@@ -24,12 +24,50 @@ pub struct WorkerConfig {
     pub agent: Arc<CompiledStateGraph>,
 }
 
+/// How the supervisor's router picks the workers for each turn.
+#[derive(Clone)]
+pub enum SupervisorStrategy {
+    /// Ask the model which worker(s) to dispatch until it answers `FINISH`.
+    RouteByLlm(Arc<dyn ChatModel>),
+    /// Dispatch one worker per turn in the order given, cycling through all
+    /// workers `rounds` times. The LLM is not consulted.
+    RoundRobin { rounds: usize },
+    /// Dispatch every worker in parallel once and finish; their messages are
+    /// aggregated in the `messages` channel. The LLM is not consulted.
+    RouteAll,
+}
+
+impl SupervisorStrategy {
+    /// Workers to dispatch on the given turn (0-based) for the non-LLM strategies.
+    fn next_workers(&self, workers: &[String], turn: usize) -> Vec<String> {
+        let finish = || vec!["FINISH".to_string()];
+        match *self {
+            Self::RouteByLlm(_) => finish(),
+            Self::RoundRobin { rounds } => {
+                if workers.is_empty() || turn >= workers.len() * rounds {
+                    finish()
+                } else {
+                    vec![workers[turn % workers.len()].clone()]
+                }
+            }
+            Self::RouteAll => {
+                if turn == 0 && !workers.is_empty() {
+                    workers.to_vec()
+                } else {
+                    finish()
+                }
+            }
+        }
+    }
+}
+
 /// Create a supervisor agent that orchestrates multiple worker agents via LLM routing.
 ///
 /// The supervisor follows a loop: an LLM-powered **router** decides which
 /// worker(s) to dispatch, a **dispatch** node fans them out via the Send API
 /// for parallel execution, and the results are fed back to the router until
-/// it signals `FINISH`.
+/// it signals `FINISH`. See [`create_supervisor_agent_with_strategy`] for
+/// routing without an LLM.
 ///
 /// # Graph structure
 /// ```text
@@ -40,6 +78,7 @@ pub struct WorkerConfig {
 /// # State schema
 /// - `messages`: `AppendChannel` — shared conversation history
 /// - `next`: `LastValue` — the router's decision (`["worker_name"]` or `["FINISH"]`)
+/// - `turn`: `LastValue` — router turns taken (RoundRobin / RouteAll only)
 ///
 /// # Example
 /// ```ignore
@@ -60,10 +99,28 @@ pub fn create_supervisor_agent(
     model: Arc<dyn ChatModel>,
     workers: Vec<WorkerConfig>,
     system_prompt: Option<String>,
+) -> Result<CompiledStateGraph> {
+    create_supervisor_agent_with_strategy(
+        workers,
+        system_prompt,
+        SupervisorStrategy::RouteByLlm(model),
+    )
+}
+
+/// Like [`create_supervisor_agent`], with the router picking workers
+/// according to `strategy`. `system_prompt` is only used by
+/// [`SupervisorStrategy::RouteByLlm`].
+pub fn create_supervisor_agent_with_strategy(
+    workers: Vec<WorkerConfig>,
+    system_prompt: Option<String>,
+    strategy: SupervisorStrategy,
 ) -> Result<CompiledStateGraph> {
     let mut graph = StateGraph::new();
     graph.add_append_channel("messages");
     graph.add_last_value_channel("next", json!([]));
+    if !matches!(strategy, SupervisorStrategy::RouteByLlm(_)) {
+        graph.add_last_value_channel("turn", json!(0));
+    }
 
     // Build worker descriptions for the router prompt
     let worker_descriptions: String = workers
@@ -84,37 +141,51 @@ pub fn create_supervisor_agent(
          You can select multiple workers to run in parallel: {{\"next\": [\"worker_a\", \"worker_b\"]}}"
     );
 
-    // ── Router node: calls LLM to decide which workers to dispatch ──
-    let model_clone = model;
-    let router_prompt = router_system_prompt;
-    graph.add_node(NodeFn::new(
-        "router",
-        move |state: Value, _config| {
-            let model = model_clone.clone();
-            let system_prompt = router_prompt.clone();
-            async move {
-                let mut messages = parse_messages(&state["messages"])?;
+    // ── Router node: picks the workers to dispatch next ──
+    if let SupervisorStrategy::RouteByLlm(model) = strategy {
+        let model_clone = model;
+        let router_prompt = router_system_prompt;
+        graph.add_node(NodeFn::new(
+            "router",
+            move |state: Value, config: RunnableConfig| {
+                let model = model_clone.clone();
+                let system_prompt = router_prompt.clone();
+                async move {
+                    let mut messages = parse_messages(&state["messages"])?;
 
-                let has_system = messages
-                    .iter()
-                    .any(|m| matches!(m, Message::System { .. }));
-                if !has_system {
-                    messages.insert(0, Message::system(system_prompt.as_str()));
-                }
+                    let has_system = messages
+                        .iter()
+                        .any(|m| matches!(m, Message::System { .. }));
+                    if !has_system {
+                        messages.insert(0, Message::system(system_prompt.as_str()));
+                    }
 
-                let options = CallOptions::default();
-                let result = model.generate(&messages, &options).await?;
+                    let options = CallOptions::default();
+                    let result =
+                        generate_with_config(&*model, &messages, &options, &config).await?;
 
-                let content = result.message.content().to_string();
-                let next = parse_next_from_response(&content);
+                    let content = result.message.content().to_string();
+                    let next = parse_next_from_response(&content);
 
-                let msg_value = serde_json::to_value(&result.message)
-                    .map_err(AyasError::Serialization)?;
+                    let msg_value = serde_json::to_value(&result.message)
+                        .map_err(AyasError::Serialization)?;
 
-                Ok(json!({"messages": msg_value, "next": next}))
+                    Ok(json!({"messages": msg_value, "next": next}))
+                }
+            },
+        ))?;
+    } else {
+        let router_workers = worker_names.clone();
+        graph.add_node(NodeFn::new("router", move |state: Value, _config| {
+            let workers = router_workers.clone();
+            let strategy = strategy.clone();
+            async move {
+                let turn = state["turn"].as_u64().unwrap_or(0) as usize;
+                let next = strategy.next_workers(&workers, turn);
+                Ok(json!({"next": next, "turn": turn + 1}))
             }
-        },
-    ))?;
+        }))?;
+    }
 
     // ── Dispatch node: creates Send directives to selected workers ──
     let valid_worker_names = worker_names;
@@ -272,49 +343,7 @@ fn parse_messages(value: &Value) -> Result<Vec<Message>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use async_trait::async_trait;
-    use ayas_core::model::ChatResult;
-    use std::sync::atomic::{AtomicUsize, Ordering};
-
-    // ── Mock Chat Model ──
-
-    struct MockSupervisorModel {
-        responses: Vec<String>,
-        call_count: AtomicUsize,
-    }
-
-    impl MockSupervisorModel {
-        fn new(responses: Vec<String>) -> Self {
-            Self {
-                responses,
-                call_count: AtomicUsize::new(0),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl ChatModel for MockSupervisorModel {
-        async fn generate(
-            &self,
-            _messages: &[Message],
-            _options: &CallOptions,
-        ) -> ayas_core::error::Result<ChatResult> {
-            let idx = self.call_count.fetch_add(1, Ordering::SeqCst);
-            let content = self
-                .responses
-                .get(idx)
-                .cloned()
-                .unwrap_or_else(|| r#"{"next": ["FINISH"]}"#.to_string());
-            Ok(ChatResult {
-                message: Message::ai(content),
-                usage: None,
-            })
-        }
-
-        fn model_name(&self) -> &str {
-            "mock-supervisor"
-        }
-    }
+    use ayas_chain::mock::MockChatModel;
 
     // ── Worker Helper ──
 
@@ -344,7 +373,7 @@ mod tests {
 
     #[test]
     fn test_supervisor_compiles() {
-        let model = Arc::new(MockSupervisorModel::new(vec![]));
+        let model = Arc::new(MockChatModel::with_response(r#"{"next": ["FINISH"]}"#));
         let researcher = Arc::new(build_worker_graph("researcher"));
         let coder = Arc::new(build_worker_graph("coder"));
 
@@ -370,7 +399,7 @@ mod tests {
     #[tokio::test]
     async fn test_supervisor_sequential_routing() {
         // Router: researcher → coder → FINISH
-        let model = Arc::new(MockSupervisorModel::new(vec![
+        let model = Arc::new(MockChatModel::new(vec![
             r#"{"next": ["researcher"]}"#.to_string(),
             r#"{"next": ["coder"]}"#.to_string(),
             r#"{"next": ["FINISH"]}"#.to_string(),
@@ -420,7 +449,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_supervisor_immediate_finish() {
-        let model = Arc::new(MockSupervisorModel::new(vec![
+        let model = Arc::new(MockChatModel::new(vec![
             r#"{"next": ["FINISH"]}"#.to_string(),
         ]));
 
@@ -454,7 +483,7 @@ mod tests {
     #[tokio::test]
     async fn test_supervisor_parallel_workers() {
         // Router dispatches both workers in parallel, then FINISH
-        let model = Arc::new(MockSupervisorModel::new(vec![
+        let model = Arc::new(MockChatModel::new(vec![
             r#"{"next": ["researcher", "coder"]}"#.to_string(),
             r#"{"next": ["FINISH"]}"#.to_string(),
         ]));
@@ -501,6 +530,119 @@ mod tests {
         assert!(contents.contains(&"Result from coder"));
     }
 
+    #[tokio::test]
+    async fn test_router_streams_through_config() {
+        let model = Arc::new(MockChatModel::with_response(r#"{"next": ["FINISH"]}"#));
+        let supervisor = create_supervisor_agent(model, researcher_and_coder(), None).unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let config = RunnableConfig::default().with_stream_tx(tx);
+        supervisor
+            .invoke(
+                json!({"messages": [{"type": "user", "content": "Hello"}]}),
+                &config,
+            )
+            .await
+            .unwrap();
+
+        let mut tokens = String::new();
+        while let Ok(event) = rx.try_recv() {
+            if let ayas_core::model::ChatStreamEvent::Token(token) = event {
+                tokens.push_str(&token);
+            }
+        }
+        assert!(tokens.contains("FINISH"), "router tokens not streamed: {tokens:?}");
+    }
+
+    // ── Strategy tests ──
+
+    fn researcher_and_coder() -> Vec<WorkerConfig> {
+        vec![
+            WorkerConfig {
+                name: "researcher".into(),
+                description: "Searches for information".into(),
+                agent: Arc::new(build_worker_graph("researcher")),
+            },
+            WorkerConfig {
+                name: "coder".into(),
+                description: "Writes code".into(),
+                agent: Arc::new(build_worker_graph("coder")),
+            },
+        ]
+    }
+
+    /// Run a supervisor with `strategy` and return the message contents.
+    async fn run_with_strategy(strategy: SupervisorStrategy) -> Vec<String> {
+        let supervisor =
+            create_supervisor_agent_with_strategy(researcher_and_coder(), None, strategy).unwrap();
+
+        let result = supervisor
+            .invoke(
+                json!({"messages": [{"type": "user", "content": "Build something"}]}),
+                &RunnableConfig::default(),
+            )
+            .await
+            .unwrap();
+
+        result["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|m| m["content"].as_str().map(String::from))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_strategy_route_by_llm_follows_model() {
+        let model = Arc::new(MockChatModel::new(vec![
+            r#"{"next": ["coder"]}"#.to_string(),
+            r#"{"next": ["FINISH"]}"#.to_string(),
+        ]));
+        let contents = run_with_strategy(SupervisorStrategy::RouteByLlm(model.clone())).await;
+
+        assert_eq!(model.call_count(), 2);
+        assert!(contents.contains(&"Result from coder".to_string()));
+        assert!(!contents.contains(&"Result from researcher".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_strategy_round_robin_runs_workers_in_order() {
+        let contents = run_with_strategy(SupervisorStrategy::RoundRobin { rounds: 2 }).await;
+
+        assert_eq!(
+            contents,
+            [
+                "Build something",
+                "Result from researcher",
+                "Result from coder",
+                "Result from researcher",
+                "Result from coder",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strategy_route_all_runs_every_worker_once() {
+        let contents = run_with_strategy(SupervisorStrategy::RouteAll).await;
+
+        // user + both worker results, aggregated in one super-step
+        assert_eq!(contents.len(), 3);
+        assert!(contents.contains(&"Result from researcher".to_string()));
+        assert!(contents.contains(&"Result from coder".to_string()));
+    }
+
+    #[test]
+    fn test_next_workers_without_workers_finishes() {
+        assert_eq!(
+            SupervisorStrategy::RouteAll.next_workers(&[], 0),
+            vec!["FINISH"]
+        );
+        assert_eq!(
+            SupervisorStrategy::RoundRobin { rounds: 3 }.next_workers(&[], 0),
+            vec!["FINISH"]
+        );
+    }
+
     // ── parse_next_from_response tests ──
 
     #[test]
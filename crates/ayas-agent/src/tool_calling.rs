@@ -1,9 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use ayas_checkpoint::prelude::{interrupt_output_for, RESUME_VALUES_CHANNEL};
 use ayas_core::error::{AyasError, Result};
 use ayas_core::message::{AIContent, Message, ToolCall};
-use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::model::{generate_with_config, CallOptions, ChatModel};
 use ayas_core::tool::{Tool, ToolDefinition};
 use ayas_graph::compiled::CompiledStateGraph;
 use ayas_graph::constants::END;
@@ -45,7 +46,42 @@ pub fn create_tool_calling_agent(
     tools: Vec<Arc<dyn Tool>>,
     system_prompt: Option<String>,
 ) -> Result<CompiledStateGraph> {
-    let tool_defs: Vec<ToolDefinition> = tools.iter().map(|t| t.definition()).collect();
+    create_tool_calling_agent_with_approval(model, tools, system_prompt, HashSet::new())
+}
+
+/// Channel holding the tool call that is waiting for approval, or `null`.
+pub const PENDING_APPROVAL_CHANNEL: &str = "pending_approval";
+
+/// Create a tool-calling agent that pauses before running approval-gated tools.
+///
+/// When the model calls a tool named in `approval_required`, the **tools**
+/// node emits a keyed interrupt (keyed by the tool call id) whose value is
+/// `{"tool_call": {...}}` instead of executing the batch. Resume with `true`
+/// to run the tool or `false` to send the model a denial instead. A batch with
+/// several gated calls interrupts once per call; the batch runs after the last
+/// answer.
+///
+/// Approval relies on checkpoints, so run the graph with
+/// `invoke_resumable`; a plain `invoke` fails when it reaches a gated call.
+///
+/// # State schema
+/// - `messages`: `AppendChannel` — conversation history
+/// - `pending_approval`: `LastValue` — the gated tool call awaiting an answer
+/// - `resume_values`: `LastValue` — answers keyed by tool call id
+pub fn create_tool_calling_agent_with_approval(
+    model: Arc<dyn ChatModel>,
+    tools: Vec<Arc<dyn Tool>>,
+    system_prompt: Option<String>,
+    approval_required: HashSet<String>,
+) -> Result<CompiledStateGraph> {
+    let tool_defs = tools
+        .iter()
+        .map(|t| {
+            let def = t.definition();
+            def.validate()?;
+            Ok(def)
+        })
+        .collect::<Result<Vec<ToolDefinition>>>()?;
 
     // Build tool lookup map
     let tools_map: Arc<HashMap<String, Arc<dyn Tool>>> = Arc::new(
@@ -55,8 +91,15 @@ pub fn create_tool_calling_agent(
             .collect(),
     );
 
+    let gated = !approval_required.is_empty();
+    let approval_required = Arc::new(approval_required);
+
     let mut graph = StateGraph::new();
     graph.add_append_channel("messages");
+    if gated {
+        graph.add_last_value_channel(PENDING_APPROVAL_CHANNEL, Value::Null);
+        graph.add_last_value_channel(RESUME_VALUES_CHANNEL, json!({}));
+    }
 
     // Agent node: optionally prepends system message, then calls LLM with tool definitions
     let model_clone = model.clone();
@@ -64,7 +107,7 @@ pub fn create_tool_calling_agent(
     let system_prompt_clone = system_prompt.clone();
     graph.add_node(NodeFn::new(
         "agent",
-        move |state: Value, _config| {
+        move |state: Value, config| {
             let model = model_clone.clone();
             let tool_defs = tool_defs_clone.clone();
             let system_prompt = system_prompt_clone.clone();
@@ -85,7 +128,7 @@ pub fn create_tool_calling_agent(
                     tools: tool_defs,
                     ..Default::default()
                 };
-                let result = model.generate(&messages, &options).await?;
+                let result = generate_with_config(&*model, &messages, &options, &config).await?;
                 let msg_value = serde_json::to_value(&result.message)
                     .map_err(AyasError::Serialization)?;
                 Ok(json!({"messages": msg_value}))
@@ -95,26 +138,44 @@ pub fn create_tool_calling_agent(
 
     // Tools node: executes tool calls from the last AI message in parallel
     let tools_map_clone = tools_map.clone();
+    let approval_required_clone = approval_required.clone();
     graph.add_node(NodeFn::new(
         "tools",
         move |state: Value, _config| {
             let tools_map = tools_map_clone.clone();
+            let approval_required = approval_required_clone.clone();
             async move {
                 let messages = parse_messages(&state["messages"])?;
                 let tool_calls = extract_tool_calls(&messages);
                 let concurrency = tool_calls.len().max(1);
 
+                let denied = match check_approvals(&state, &tool_calls, &approval_required)? {
+                    Approval::Decided(denied) => denied,
+                    Approval::Pending(output) => return Ok(output),
+                };
+
                 let results: Vec<Value> = stream::iter(tool_calls.into_iter().map(
                     |tc| {
                         let tools_map = tools_map.clone();
+                        let denied = denied.contains(&tc.id);
                         async move {
                             let tool = tools_map.get(&tc.name).ok_or_else(|| {
                                 AyasError::Tool(ayas_core::error::ToolError::NotFound(
                                     tc.name.clone(),
                                 ))
                             })?;
-                            let output = tool.call(tc.arguments.clone()).await?;
-                            let tool_msg = Message::tool(output, &tc.id);
+                            let tool_msg = if denied {
+                                Message::tool_error(
+                                    format!("Tool call '{}' was denied by the user", tc.name),
+                                    &tc.id,
+                                )
+                            } else {
+                                // Feed failures back to the model rather than aborting the run.
+                                match tool.call(tc.arguments.clone()).await {
+                                    Ok(output) => Message::tool(output, &tc.id),
+                                    Err(e) => Message::tool_failure(&e, &tc.id),
+                                }
+                            };
                             serde_json::to_value(&tool_msg).map_err(AyasError::Serialization)
                         }
                     },
@@ -123,7 +184,11 @@ pub fn create_tool_calling_agent(
                 .try_collect()
                 .await?;
 
-                Ok(json!({"messages": results}))
+                if approval_required.is_empty() {
+                    Ok(json!({"messages": results}))
+                } else {
+                    Ok(json!({"messages": results, PENDING_APPROVAL_CHANNEL: Value::Null}))
+                }
             }
         },
     ))?;
@@ -147,12 +212,74 @@ pub fn create_tool_calling_agent(
         Some(path_map),
     ));
 
-    // tools -> agent (cycle back)
-    graph.add_edge("tools", "agent");
+    if gated {
+        // tools -> tools while a call awaits approval, otherwise back to agent
+        let mut approval_map = HashMap::new();
+        approval_map.insert("tools".to_string(), "tools".to_string());
+        approval_map.insert("agent".to_string(), "agent".to_string());
+
+        graph.add_conditional_edges(ConditionalEdge::new(
+            "tools",
+            |state: &Value| {
+                if state[PENDING_APPROVAL_CHANNEL].is_null() {
+                    "agent".to_string()
+                } else {
+                    "tools".to_string()
+                }
+            },
+            Some(approval_map),
+        ));
+    } else {
+        // tools -> agent (cycle back)
+        graph.add_edge("tools", "agent");
+    }
 
     graph.compile()
 }
 
+/// Outcome of checking a tool batch against the approval gate.
+enum Approval {
+    /// Every gated call has an answer; holds the ids of the denied calls.
+    Decided(HashSet<String>),
+    /// A gated call has no answer yet; holds the interrupt output to emit.
+    Pending(Value),
+}
+
+/// Look up the answer for each gated call in `resume_values`, interrupting on
+/// the first call that has none.
+fn check_approvals(
+    state: &Value,
+    tool_calls: &[ToolCall],
+    approval_required: &HashSet<String>,
+) -> Result<Approval> {
+    let mut denied = HashSet::new();
+    for tc in tool_calls.iter().filter(|tc| approval_required.contains(&tc.name)) {
+        match state[RESUME_VALUES_CHANNEL].get(&tc.id) {
+            Some(answer) => {
+                if answer.as_bool() != Some(true) {
+                    denied.insert(tc.id.clone());
+                }
+            }
+            None => {
+                // Already asked and still unanswered: nothing resumed this run
+                if state[PENDING_APPROVAL_CHANNEL]["id"].as_str() == Some(tc.id.as_str()) {
+                    return Err(AyasError::Other(format!(
+                        "Tool call '{}' ({}) requires approval; resume the interrupted \
+                         checkpoint with true or false",
+                        tc.name, tc.id
+                    )));
+                }
+                let pending = serde_json::to_value(tc).map_err(AyasError::Serialization)?;
+                let mut output =
+                    interrupt_output_for(&tc.id, json!({"tool_call": pending.clone()}));
+                output[PENDING_APPROVAL_CHANNEL] = pending;
+                return Ok(Approval::Pending(output));
+            }
+        }
+    }
+    Ok(Approval::Decided(denied))
+}
+
 /// Parse messages from a JSON array value.
 fn parse_messages(value: &Value) -> Result<Vec<Message>> {
     match value {
@@ -239,10 +366,7 @@ mod tests {
                 .get(idx)
                 .cloned()
                 .unwrap_or_else(|| Message::ai("done"));
-            Ok(ChatResult {
-                message,
-                usage: None,
-            })
+            Ok(ChatResult::new(message))
         }
 
         fn model_name(&self) -> &str {
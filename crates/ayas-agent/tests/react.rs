@@ -4,14 +4,18 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use serde_json::{json, Value};
 
+use ayas_chain::mock::MockChatModel;
 use ayas_core::config::RunnableConfig;
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, Result, ToolError};
 use ayas_core::message::{Message, ToolCall};
 use ayas_core::model::{CallOptions, ChatModel, ChatResult};
 use ayas_core::runnable::Runnable;
 use ayas_core::tool::{Tool, ToolDefinition};
+use ayas_graph::compiled::CompiledStateGraph;
 
-use ayas_agent::react::create_react_agent;
+use ayas_agent::react::{
+    create_react_agent, create_react_agent_with_options, OnMaxIterations, ReactAgentOptions,
+};
 
 // --- Mock ChatModel that returns tool calls on first call, final answer on second ---
 
@@ -33,23 +37,17 @@ impl ChatModel for MockReActModel {
         let count = self.call_count.fetch_add(1, Ordering::Relaxed);
         if count == 0 {
             // First call: request tool use
-            Ok(ChatResult {
-                message: Message::ai_with_tool_calls(
-                    "",
-                    vec![ToolCall {
-                        id: "call_1".into(),
-                        name: "calculator".into(),
-                        arguments: json!({"expression": "6 + 7"}),
-                    }],
-                ),
-                usage: None,
-            })
+            Ok(ChatResult::new(Message::ai_with_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "calculator".into(),
+                    arguments: json!({"expression": "6 + 7"}),
+                }],
+            )))
         } else {
             // Second call: final answer
-            Ok(ChatResult {
-                message: Message::ai("The answer is 13."),
-                usage: None,
-            })
+            Ok(ChatResult::new(Message::ai("The answer is 13.")))
         }
     }
 
@@ -65,10 +63,7 @@ struct MockDirectModel;
 #[async_trait]
 impl ChatModel for MockDirectModel {
     async fn generate(&self, _messages: &[Message], _options: &CallOptions) -> Result<ChatResult> {
-        Ok(ChatResult {
-            message: Message::ai("Direct answer without tools."),
-            usage: None,
-        })
+        Ok(ChatResult::new(Message::ai("Direct answer without tools.")))
     }
 
     fn model_name(&self) -> &str {
@@ -103,6 +98,19 @@ impl Tool for MockCalculator {
     }
 }
 
+struct FailingCalculator;
+
+#[async_trait]
+impl Tool for FailingCalculator {
+    fn definition(&self) -> ToolDefinition {
+        MockCalculator.definition()
+    }
+
+    async fn call(&self, _input: Value) -> Result<String> {
+        Err(ToolError::ExecutionFailed("division by zero".into()).into())
+    }
+}
+
 /// Full ReAct cycle: user -> agent (tool call) -> tool -> agent (final answer) -> END
 #[tokio::test]
 async fn react_agent_full_cycle() {
@@ -183,29 +191,23 @@ async fn react_agent_multiple_tool_calls() {
         ) -> Result<ChatResult> {
             let count = self.0.fetch_add(1, Ordering::Relaxed);
             if count == 0 {
-                Ok(ChatResult {
-                    message: Message::ai_with_tool_calls(
-                        "",
-                        vec![
-                            ToolCall {
-                                id: "call_1".into(),
-                                name: "calculator".into(),
-                                arguments: json!({"expression": "1+1"}),
-                            },
-                            ToolCall {
-                                id: "call_2".into(),
-                                name: "calculator".into(),
-                                arguments: json!({"expression": "2+2"}),
-                            },
-                        ],
-                    ),
-                    usage: None,
-                })
+                Ok(ChatResult::new(Message::ai_with_tool_calls(
+                    "",
+                    vec![
+                        ToolCall {
+                            id: "call_1".into(),
+                            name: "calculator".into(),
+                            arguments: json!({"expression": "1+1"}),
+                        },
+                        ToolCall {
+                            id: "call_2".into(),
+                            name: "calculator".into(),
+                            arguments: json!({"expression": "2+2"}),
+                        },
+                    ],
+                )))
             } else {
-                Ok(ChatResult {
-                    message: Message::ai("Both results received."),
-                    usage: None,
-                })
+                Ok(ChatResult::new(Message::ai("Both results received.")))
             }
         }
 
@@ -232,3 +234,232 @@ async fn react_agent_multiple_tool_calls() {
     assert_eq!(messages[2]["type"], "tool");
     assert_eq!(messages[3]["type"], "tool");
 }
+
+/// A model that never stops calling tools.
+fn looping_model() -> MockChatModel {
+    MockChatModel::with_handler(|messages| {
+        ChatResult::new(Message::ai_with_tool_calls(
+            "",
+            vec![ToolCall {
+                id: format!("call_{}", messages.len()),
+                name: "calculator".into(),
+                arguments: json!({"expression": "1 + 1"}),
+            }],
+        ))
+    })
+}
+
+fn looping_agent(options: ReactAgentOptions) -> (Arc<MockChatModel>, CompiledStateGraph) {
+    let model = Arc::new(looping_model());
+    let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(MockCalculator)];
+    let graph = create_react_agent_with_options(model.clone(), tools, options).unwrap();
+    (model, graph)
+}
+
+fn user_input() -> Value {
+    json!({"messages": [{"type": "user", "content": "Loop forever"}]})
+}
+
+/// Hitting the cap with ReturnLast ends the run with the pending tool call as the last message
+#[tokio::test]
+async fn react_agent_max_iterations_returns_last() {
+    let (model, graph) = looping_agent(
+        ReactAgentOptions::new()
+            .with_max_iterations(2)
+            .with_on_max_iterations(OnMaxIterations::ReturnLast),
+    );
+
+    let result = graph
+        .invoke(user_input(), &RunnableConfig::default())
+        .await
+        .unwrap();
+
+    assert_eq!(model.call_count(), 2);
+    assert_eq!(result["iterations"], json!(2));
+    let messages = result["messages"].as_array().unwrap();
+    // user + (ai + tool) + ai with unanswered tool call
+    assert_eq!(messages.len(), 4);
+    assert!(!messages[3]["tool_calls"].as_array().unwrap().is_empty());
+}
+
+/// Hitting the cap with Error fails the run instead of returning partial state
+#[tokio::test]
+async fn react_agent_max_iterations_errors() {
+    let (model, graph) = looping_agent(
+        ReactAgentOptions::new()
+            .with_max_iterations(3)
+            .with_on_max_iterations(OnMaxIterations::Error),
+    );
+
+    let err = graph
+        .invoke(user_input(), &RunnableConfig::default())
+        .await
+        .unwrap_err();
+
+    assert_eq!(model.call_count(), 3);
+    assert!(err.to_string().contains("max_iterations (3)"), "{err}");
+}
+
+/// The stop condition ends the run even though the model still wants tools
+#[tokio::test]
+async fn react_agent_stop_condition() {
+    let (model, graph) = looping_agent(ReactAgentOptions::new().with_stop_condition(|state| {
+        state["messages"].as_array().is_some_and(|m| m.len() >= 4)
+    }));
+
+    let result = graph
+        .invoke(user_input(), &RunnableConfig::default())
+        .await
+        .unwrap();
+
+    assert_eq!(model.call_count(), 2);
+    assert_eq!(result["messages"].as_array().unwrap().len(), 4);
+    assert!(result.get("iterations").is_none());
+}
+
+/// A failing tool is reported back to the model as an error result
+#[tokio::test]
+async fn react_agent_tool_error_fed_back() {
+    let model: Arc<dyn ChatModel> = Arc::new(MockReActModel::new());
+    let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(FailingCalculator)];
+
+    let graph = create_react_agent(model, tools).unwrap();
+    let result = graph
+        .invoke(user_input(), &RunnableConfig::default())
+        .await
+        .unwrap();
+
+    let messages = result["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2]["type"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_1");
+    assert_eq!(messages[2]["is_error"], true);
+    let content = messages[2]["content"].as_str().unwrap();
+    assert!(content.contains("division by zero"), "{content}");
+    assert!(!content.starts_with("Tool error"), "{content}");
+    assert_eq!(messages[3]["type"], "ai");
+}
+
+/// Call options reach every model call, alongside the agent's tools
+#[tokio::test]
+async fn react_agent_call_options_passthrough() {
+    struct RecordingModel(std::sync::Mutex<Vec<CallOptions>>);
+
+    #[async_trait]
+    impl ChatModel for RecordingModel {
+        async fn generate(
+            &self,
+            _messages: &[Message],
+            options: &CallOptions,
+        ) -> Result<ChatResult> {
+            self.0.lock().unwrap().push(options.clone());
+            Ok(ChatResult::new(Message::ai("done")))
+        }
+
+        fn model_name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    let model = Arc::new(RecordingModel(std::sync::Mutex::new(Vec::new())));
+    let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(MockCalculator)];
+    let options = ReactAgentOptions::new().with_call_options(CallOptions {
+        temperature: Some(0.2),
+        top_p: Some(0.9),
+        max_tokens: Some(128),
+        stop: vec!["END".into()],
+        ..Default::default()
+    });
+    let graph = create_react_agent_with_options(model.clone(), tools, options).unwrap();
+    graph.invoke(user_input(), &RunnableConfig::default()).await.unwrap();
+
+    let calls = model.0.lock().unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].temperature, Some(0.2));
+    assert_eq!(calls[0].top_p, Some(0.9));
+    assert_eq!(calls[0].max_tokens, Some(128));
+    assert_eq!(calls[0].stop, vec!["END".to_string()]);
+    assert_eq!(calls[0].tools.len(), 1);
+}
+
+/// Tools are checked when the agent is built, not when a provider rejects them.
+#[tokio::test]
+async fn react_agent_rejects_malformed_tool_schema() {
+    struct UntypedTool;
+
+    #[async_trait]
+    impl Tool for UntypedTool {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "untyped".into(),
+                description: "Schema without a type".into(),
+                parameters: json!({"properties": {"q": {"type": "string"}}}),
+            }
+        }
+
+        async fn call(&self, _input: Value) -> Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    let model: Arc<dyn ChatModel> = Arc::new(MockReActModel::new());
+    let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(MockCalculator), Arc::new(UntypedTool)];
+    let err = create_react_agent(model, tools).err().unwrap();
+    assert!(matches!(err, AyasError::Tool(ToolError::InvalidDefinition(_))));
+    assert!(err.to_string().contains("'untyped'"));
+}
+
+/// With `coerce_arguments`, stringified primitives reach the tool as their schema types.
+#[tokio::test]
+async fn react_agent_coerces_tool_arguments() {
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "echo".into(),
+                description: "Echoes its input".into(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "count": {"type": "integer"},
+                        "exact": {"type": "boolean"}
+                    }
+                }),
+            }
+        }
+
+        async fn call(&self, input: Value) -> Result<String> {
+            Ok(input.to_string())
+        }
+    }
+
+    for (coerce, expected) in [
+        (false, json!({"count": "5", "exact": "true"})),
+        (true, json!({"count": 5, "exact": true})),
+    ] {
+        let model: Arc<dyn ChatModel> = Arc::new(MockChatModel::with_script(vec![
+            ChatResult::new(Message::ai_with_tool_calls(
+                "",
+                vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "echo".into(),
+                    arguments: json!({"count": "5", "exact": "true"}),
+                }],
+            )),
+            MockChatModel::text_result("done"),
+        ]));
+        let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(EchoTool)];
+        let options = ReactAgentOptions::new().with_coerce_arguments(coerce);
+        let graph = create_react_agent_with_options(model, tools, options).unwrap();
+        let result = graph
+            .invoke(user_input(), &RunnableConfig::default())
+            .await
+            .unwrap();
+
+        let messages = result["messages"].as_array().unwrap();
+        let echoed: Value = serde_json::from_str(messages[2]["content"].as_str().unwrap()).unwrap();
+        assert_eq!(echoed, expected, "coerce = {coerce}");
+    }
+}
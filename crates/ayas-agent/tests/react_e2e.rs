@@ -41,10 +41,7 @@ impl ChatModel for MockChatModel {
             .get(idx)
             .cloned()
             .unwrap_or_else(|| Message::ai("done"));
-        Ok(ChatResult {
-            message,
-            usage: None,
-        })
+        Ok(ChatResult::new(message))
     }
 
     fn model_name(&self) -> &str {
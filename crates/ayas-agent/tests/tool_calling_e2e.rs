@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -11,7 +12,10 @@ use ayas_core::model::{CallOptions, ChatModel, ChatResult};
 use ayas_core::runnable::Runnable;
 use ayas_core::tool::{Tool, ToolDefinition};
 
-use ayas_agent::tool_calling::create_tool_calling_agent;
+use ayas_agent::tool_calling::{
+    create_tool_calling_agent, create_tool_calling_agent_with_approval,
+};
+use ayas_checkpoint::prelude::{CheckpointConfigExt, GraphOutput, MemoryCheckpointStore};
 
 // ---------------------------------------------------------------------------
 // Mock helpers
@@ -41,10 +45,7 @@ impl ChatModel for MockChatModel {
             .get(idx)
             .cloned()
             .unwrap_or_else(|| Message::ai("done"));
-        Ok(ChatResult {
-            message,
-            usage: None,
-        })
+        Ok(ChatResult::new(message))
     }
 
     fn model_name(&self) -> &str {
@@ -86,10 +87,7 @@ impl ChatModel for CapturingChatModel {
             .get(idx)
             .cloned()
             .unwrap_or_else(|| Message::ai("done"));
-        Ok(ChatResult {
-            message,
-            usage: None,
-        })
+        Ok(ChatResult::new(message))
     }
 
     fn model_name(&self) -> &str {
@@ -298,9 +296,9 @@ async fn parallel_tool_calls() {
     assert_eq!(messages[4]["content"], "Both results are in.");
 }
 
-/// 5. Tool error handling — tool returns an error, graph propagates it.
+/// 5. Tool error handling — tool returns an error, which is fed back to the model.
 #[tokio::test]
-async fn tool_error_propagates() {
+async fn tool_error_fed_back_to_model() {
     let model = Arc::new(MockChatModel::new(vec![
         Message::ai_with_tool_calls(
             "",
@@ -310,6 +308,7 @@ async fn tool_error_propagates() {
                 arguments: json!({}),
             }],
         ),
+        Message::ai("The tool failed."),
     ]));
 
     let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(ErrorTool {
@@ -319,14 +318,17 @@ async fn tool_error_propagates() {
     let graph = create_tool_calling_agent(model, tools, None).unwrap();
     let config = RunnableConfig::default();
     let input = json!({"messages": [{"type": "user", "content": "Do something"}]});
-    let result = graph.invoke(input, &config).await;
+    let result = graph.invoke(input, &config).await.unwrap();
 
-    assert!(result.is_err());
-    let err_msg = result.unwrap_err().to_string();
-    assert!(
-        err_msg.contains("tool exploded"),
-        "Expected error about tool failure, got: {err_msg}"
-    );
+    let messages = result["messages"].as_array().unwrap();
+    // user -> AI(tool_call) -> tool error -> AI(final)
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2]["type"], "tool");
+    assert_eq!(messages[2]["tool_call_id"], "call_err");
+    assert_eq!(messages[2]["is_error"], true);
+    let content = messages[2]["content"].as_str().unwrap();
+    assert!(content.contains("tool exploded"), "{content}");
+    assert_eq!(messages[3]["content"], "The tool failed.");
 }
 
 /// 6. System prompt influence — verify system prompt is included in the first call.
@@ -575,3 +577,114 @@ async fn system_prompt_not_duplicated() {
         );
     }
 }
+
+/// Agent whose `danger` tool needs approval, next to an ungated `search` tool.
+fn approval_agent() -> ayas_graph::compiled::CompiledStateGraph {
+    let model = Arc::new(MockChatModel::new(vec![
+        Message::ai_with_tool_calls(
+            "",
+            vec![
+                ToolCall {
+                    id: "call_search".into(),
+                    name: "search".into(),
+                    arguments: json!({}),
+                },
+                ToolCall {
+                    id: "call_danger".into(),
+                    name: "danger".into(),
+                    arguments: json!({"path": "/tmp/x"}),
+                },
+            ],
+        ),
+        Message::ai("All done."),
+    ]));
+    let tools: Vec<Arc<dyn Tool>> = vec![
+        Arc::new(MockTool {
+            name: "search".into(),
+            result: "found".into(),
+        }),
+        Arc::new(MockTool {
+            name: "danger".into(),
+            result: "deleted".into(),
+        }),
+    ];
+    let approval_required = HashSet::from(["danger".to_string()]);
+    create_tool_calling_agent_with_approval(model, tools, None, approval_required).unwrap()
+}
+
+/// Run the approval agent to its interrupt, then resume with `answer`.
+async fn run_with_approval(thread_id: &str, answer: Value) -> Value {
+    let graph = approval_agent();
+    let store = MemoryCheckpointStore::new();
+    let config = RunnableConfig::default().with_thread_id(thread_id);
+    let input = json!({"messages": [{"type": "user", "content": "Clean up"}]});
+
+    let result = graph.invoke_resumable(input, &config, &store).await.unwrap();
+    let checkpoint_id = match result {
+        GraphOutput::Interrupted {
+            checkpoint_id,
+            interrupt_value,
+            state,
+        } => {
+            assert_eq!(interrupt_value["tool_call"]["name"], "danger");
+            assert_eq!(interrupt_value["tool_call"]["arguments"]["path"], "/tmp/x");
+            // Nothing in the batch has run yet
+            assert_eq!(state["messages"].as_array().unwrap().len(), 2);
+            checkpoint_id
+        }
+        _ => panic!("Expected Interrupted"),
+    };
+
+    let resume_config = RunnableConfig::default()
+        .with_thread_id(thread_id)
+        .with_checkpoint_id(&checkpoint_id)
+        .with_resume_value(answer);
+    let result = graph
+        .invoke_resumable(json!({}), &resume_config, &store)
+        .await
+        .unwrap();
+    assert!(result.is_complete());
+    result.into_value()
+}
+
+/// 13. Approval-gated tool runs after the user approves it.
+#[tokio::test]
+async fn approval_granted_runs_tool() {
+    let state = run_with_approval("approve", json!(true)).await;
+    let messages = state["messages"].as_array().unwrap();
+
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[2]["content"], "found");
+    assert_eq!(messages[3]["tool_call_id"], "call_danger");
+    assert_eq!(messages[3]["content"], "deleted");
+    assert!(messages[3].get("is_error").is_none());
+    assert_eq!(messages[4]["content"], "All done.");
+    assert!(state["pending_approval"].is_null());
+}
+
+/// 14. Denied tool is not run; the model gets a denial instead.
+#[tokio::test]
+async fn approval_denied_returns_denial() {
+    let state = run_with_approval("deny", json!(false)).await;
+    let messages = state["messages"].as_array().unwrap();
+
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[2]["content"], "found");
+    assert_eq!(messages[3]["tool_call_id"], "call_danger");
+    assert_eq!(messages[3]["is_error"], true);
+    assert!(messages[3]["content"].as_str().unwrap().contains("denied"));
+    assert_eq!(messages[4]["content"], "All done.");
+}
+
+/// 15. Approval needs a checkpoint; a plain invoke fails at the gated call.
+#[tokio::test]
+async fn approval_without_checkpoint_errors() {
+    let graph = approval_agent();
+    let input = json!({"messages": [{"type": "user", "content": "Clean up"}]});
+    let err = graph
+        .invoke(input, &RunnableConfig::default())
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("requires approval"), "{err}");
+}
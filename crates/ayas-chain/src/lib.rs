@@ -10,8 +10,8 @@ pub mod prelude {
     pub use crate::mock::MockChatModel;
     pub use crate::parallel::RunnableParallel;
     pub use crate::parser::{
-        JsonOutputParser, MessageContentParser, RegexOutputParser, StringOutputParser,
-        StructuredOutputParser,
+        JsonOutputParser, MessageContentParser, RegexOutputParser, StreamingStringParser,
+        StringOutputParser, StructuredOutputParser,
     };
     pub use crate::prompt::PromptTemplate;
     pub use crate::sequence::RunnableSequence;
@@ -1,28 +1,51 @@
+use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 
 use ayas_core::config::RunnableConfig;
-use ayas_core::error::Result;
-use ayas_core::message::{AIContent, Message};
+use ayas_core::error::{AyasError, ModelError, Result};
+use ayas_core::message::{Message, UsageMetadata};
+use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent};
 use ayas_core::runnable::Runnable;
 
+type ScriptFn = dyn Fn(&[Message]) -> ChatResult + Send + Sync;
+
+/// Where a [`MockChatModel`] gets its responses from.
+enum Responses {
+    /// Cycle through the results forever.
+    Cycle(Vec<ChatResult>),
+    /// Return the results once each, in order, then fail.
+    Script(Vec<ChatResult>),
+    /// Compute each result from the conversation so far.
+    Handler(Box<ScriptFn>),
+}
+
 /// A mock ChatModel that returns preset responses and tracks call counts.
 ///
 /// This implements `Runnable<Input = Vec<Message>, Output = Vec<Message>>`
-/// to be composable in chains with PromptTemplate and OutputParser.
+/// to be composable in chains with PromptTemplate and OutputParser, and
+/// [`ChatModel`] so it can drive agents and graph nodes. Use
+/// [`with_script`](Self::with_script) to script a multi-turn tool-calling
+/// conversation.
+///
+/// `ChatModel::stream` splits each response deterministically into token
+/// events (one per word by default), followed by `Usage` and `Done`.
 pub struct MockChatModel {
-    responses: Vec<String>,
+    responses: Responses,
     call_count: AtomicUsize,
+    chunk_size: Option<usize>,
+    stream_delay: Option<Duration>,
 }
 
 impl MockChatModel {
     /// Create a `MockChatModel` that cycles through the given responses.
     pub fn new(responses: Vec<String>) -> Self {
-        Self {
-            responses,
-            call_count: AtomicUsize::new(0),
-        }
+        Self::from_responses(Responses::Cycle(
+            responses.into_iter().map(text_result).collect(),
+        ))
     }
 
     /// Create a `MockChatModel` that always returns the same response.
@@ -30,10 +53,108 @@ impl MockChatModel {
         Self::new(vec![response.into()])
     }
 
+    /// Create a `MockChatModel` that returns each result once, in order.
+    ///
+    /// Results may carry tool calls, e.g. a tool-call turn followed by the
+    /// final answer. Calling the model after the script runs out is an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ayas_chain::mock::MockChatModel;
+    /// use ayas_core::message::{Message, ToolCall};
+    /// use ayas_core::model::ChatResult;
+    ///
+    /// let model = MockChatModel::with_script(vec![
+    ///     ChatResult::new(Message::ai_with_tool_calls(
+    ///         "",
+    ///         vec![ToolCall {
+    ///             id: "call_1".into(),
+    ///             name: "calculator".into(),
+    ///             arguments: serde_json::json!({"expression": "2+3"}),
+    ///         }],
+    ///     )),
+    ///     MockChatModel::text_result("The answer is 5"),
+    /// ]);
+    /// ```
+    pub fn with_script(script: Vec<ChatResult>) -> Self {
+        Self::from_responses(Responses::Script(script))
+    }
+
+    /// Create a `MockChatModel` that computes each response from the
+    /// messages it is called with.
+    pub fn with_handler(
+        handler: impl Fn(&[Message]) -> ChatResult + Send + Sync + 'static,
+    ) -> Self {
+        Self::from_responses(Responses::Handler(Box::new(handler)))
+    }
+
+    /// A plain-text AI result, for building scripts.
+    pub fn text_result(content: impl Into<String>) -> ChatResult {
+        text_result(content.into())
+    }
+
+    /// Stream responses in chunks of `chars` characters instead of words.
+    pub fn with_chunk_size(mut self, chars: usize) -> Self {
+        self.chunk_size = Some(chars.max(1));
+        self
+    }
+
+    /// Wait `delay` before each streamed token.
+    pub fn with_stream_delay(mut self, delay: Duration) -> Self {
+        self.stream_delay = Some(delay);
+        self
+    }
+
     /// Get the number of times this model has been invoked.
     pub fn call_count(&self) -> usize {
         self.call_count.load(Ordering::Relaxed)
     }
+
+    fn from_responses(responses: Responses) -> Self {
+        Self {
+            responses,
+            call_count: AtomicUsize::new(0),
+            chunk_size: None,
+            stream_delay: None,
+        }
+    }
+
+    /// Split `content` into token chunks that concatenate back to it.
+    fn chunks(&self, content: &str) -> Vec<String> {
+        match self.chunk_size {
+            Some(size) => {
+                let chars: Vec<char> = content.chars().collect();
+                chars.chunks(size).map(|c| c.iter().collect()).collect()
+            }
+            None => content.split_inclusive(' ').map(String::from).collect(),
+        }
+    }
+
+    fn next_result(&self, messages: &[Message]) -> Result<ChatResult> {
+        let idx = self.call_count.fetch_add(1, Ordering::Relaxed);
+        match &self.responses {
+            Responses::Cycle(results) if !results.is_empty() => {
+                Ok(results[idx % results.len()].clone())
+            }
+            Responses::Cycle(_) => Err(mock_error("MockChatModel has no responses".into())),
+            Responses::Script(results) => results.get(idx).cloned().ok_or_else(|| {
+                mock_error(format!(
+                    "MockChatModel script exhausted after {} responses",
+                    results.len()
+                ))
+            }),
+            Responses::Handler(handler) => Ok(handler(messages)),
+        }
+    }
+}
+
+fn text_result(content: String) -> ChatResult {
+    ChatResult::new(Message::ai(content))
+}
+
+fn mock_error(message: String) -> AyasError {
+    AyasError::Model(ModelError::InvalidResponse(message))
 }
 
 #[async_trait]
@@ -46,16 +167,61 @@ impl Runnable for MockChatModel {
         mut input: Self::Input,
         _config: &RunnableConfig,
     ) -> Result<Self::Output> {
-        let idx = self.call_count.fetch_add(1, Ordering::Relaxed);
-        let response = &self.responses[idx % self.responses.len()];
+        let result = self.next_result(&input)?;
+        input.push(result.message);
+        Ok(input)
+    }
+}
 
-        input.push(Message::AI(AIContent {
-            content: response.clone(),
-            tool_calls: Vec::new(),
-            usage: None,
-        }));
+#[async_trait]
+impl ChatModel for MockChatModel {
+    async fn generate(&self, messages: &[Message], _options: &CallOptions) -> Result<ChatResult> {
+        self.next_result(messages)
+    }
 
-        Ok(input)
+    fn model_name(&self) -> &str {
+        "mock-chat-model"
+    }
+
+    async fn stream(
+        &self,
+        messages: &[Message],
+        _options: &CallOptions,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
+        let result = self.next_result(messages)?;
+        let tokens = self.chunks(result.message.content());
+        let output_tokens = tokens.len() as u64;
+
+        let mut events: Vec<ChatStreamEvent> =
+            tokens.into_iter().map(ChatStreamEvent::Token).collect();
+        if let Message::AI(ai) = &result.message {
+            for tc in &ai.tool_calls {
+                events.push(ChatStreamEvent::ToolCallStart {
+                    id: tc.id.clone(),
+                    name: tc.name.clone(),
+                });
+                events.push(ChatStreamEvent::ToolCallDelta {
+                    id: tc.id.clone(),
+                    arguments: tc.arguments.to_string(),
+                });
+            }
+        }
+        events.push(ChatStreamEvent::Usage(result.usage.unwrap_or(UsageMetadata {
+            input_tokens: 0,
+            output_tokens,
+            total_tokens: output_tokens,
+            ..Default::default()
+        })));
+        events.push(ChatStreamEvent::Done);
+
+        let delay = self.stream_delay;
+        let stream = futures::stream::iter(events).then(move |event| async move {
+            if let (Some(delay), ChatStreamEvent::Token(_)) = (delay, &event) {
+                tokio::time::sleep(delay).await;
+            }
+            Ok(event)
+        });
+        Ok(Box::pin(stream))
     }
 }
 
@@ -115,4 +281,137 @@ mod tests {
         assert_eq!(result[1].content(), "Question");
         assert_eq!(result[2].content(), "Response");
     }
+
+    fn tool_call_result(name: &str) -> ChatResult {
+        ChatResult::new(Message::ai_with_tool_calls(
+            "",
+            vec![ayas_core::message::ToolCall {
+                id: "call_1".into(),
+                name: name.into(),
+                arguments: serde_json::json!({"expression": "2+3"}),
+            }],
+        ))
+    }
+
+    #[tokio::test]
+    async fn mock_script_returns_in_order_then_errors() {
+        let model = MockChatModel::with_script(vec![
+            tool_call_result("calculator"),
+            MockChatModel::text_result("The answer is 5"),
+        ]);
+        let options = CallOptions::default();
+
+        let first = model.generate(&[Message::user("2+3?")], &options).await.unwrap();
+        match &first.message {
+            Message::AI(ai) => assert_eq!(ai.tool_calls[0].name, "calculator"),
+            _ => panic!("expected AI message"),
+        }
+
+        let second = model.generate(&[], &options).await.unwrap();
+        assert_eq!(second.message.content(), "The answer is 5");
+
+        let err = model.generate(&[], &options).await.unwrap_err();
+        assert!(err.to_string().contains("exhausted after 2"));
+        assert_eq!(model.call_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_script_as_runnable() {
+        let model = MockChatModel::with_script(vec![tool_call_result("search")]);
+        let result = model
+            .invoke(vec![Message::user("Hi")], &RunnableConfig::default())
+            .await
+            .unwrap();
+        match result.last().unwrap() {
+            Message::AI(ai) => assert_eq!(ai.tool_calls[0].name, "search"),
+            _ => panic!("expected AI message"),
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_handler_sees_messages() {
+        let model = MockChatModel::with_handler(|messages| {
+            MockChatModel::text_result(format!("{} messages", messages.len()))
+        });
+        let options = CallOptions::default();
+
+        let result = model
+            .generate(&[Message::system("s"), Message::user("u")], &options)
+            .await
+            .unwrap();
+        assert_eq!(result.message.content(), "2 messages");
+    }
+
+    async fn collect_stream(model: &MockChatModel) -> Vec<ChatStreamEvent> {
+        // Qualified: `Runnable` also has a `stream` method
+        ChatModel::stream(model, &[Message::user("Hi")], &CallOptions::default())
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn mock_stream_splits_words() {
+        let model = MockChatModel::with_response("Hello brave new world");
+        let events = collect_stream(&model).await;
+
+        let tokens: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                ChatStreamEvent::Token(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tokens, vec!["Hello ", "brave ", "new ", "world"]);
+        assert_eq!(tokens.concat(), "Hello brave new world");
+
+        match &events[4] {
+            ChatStreamEvent::Usage(usage) => assert_eq!(usage.output_tokens, 4),
+            other => panic!("expected Usage, got {other:?}"),
+        }
+        assert_eq!(events[5], ChatStreamEvent::Done);
+        assert_eq!(events.len(), 6);
+        assert_eq!(model.call_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn mock_stream_chunk_size() {
+        let model = MockChatModel::with_response("abcdefg").with_chunk_size(3);
+        let events = collect_stream(&model).await;
+
+        assert_eq!(events[0], ChatStreamEvent::Token("abc".into()));
+        assert_eq!(events[1], ChatStreamEvent::Token("def".into()));
+        assert_eq!(events[2], ChatStreamEvent::Token("g".into()));
+        assert!(matches!(events[3], ChatStreamEvent::Usage(_)));
+    }
+
+    #[tokio::test]
+    async fn mock_stream_includes_tool_calls() {
+        let model = MockChatModel::with_script(vec![tool_call_result("calculator")]);
+        let events = collect_stream(&model).await;
+
+        assert_eq!(
+            events[0],
+            ChatStreamEvent::ToolCallStart {
+                id: "call_1".into(),
+                name: "calculator".into(),
+            }
+        );
+        assert!(matches!(&events[1], ChatStreamEvent::ToolCallDelta { arguments, .. }
+            if arguments.contains("2+3")));
+        assert_eq!(events.last(), Some(&ChatStreamEvent::Done));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn mock_stream_delay() {
+        let model = MockChatModel::with_response("one two three")
+            .with_stream_delay(Duration::from_millis(50));
+        let start = tokio::time::Instant::now();
+        let events = collect_stream(&model).await;
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(start.elapsed(), Duration::from_millis(150));
+    }
 }
@@ -1,11 +1,14 @@
 use std::marker::PhantomData;
+use std::pin::Pin;
 
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 use serde::de::DeserializeOwned;
 
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::{AyasError, ChainError, Result};
 use ayas_core::message::Message;
+use ayas_core::model::ChatStreamEvent;
 use ayas_core::runnable::Runnable;
 
 /// Extracts the content of the last AI message from a `Vec<Message>`.
@@ -60,6 +63,112 @@ impl Runnable for StringOutputParser {
     }
 }
 
+/// Streaming counterpart of `StringOutputParser`.
+///
+/// Turns a model's event stream into a stream of text chunks whose
+/// concatenation is the final answer with an optional prefix and suffix
+/// stripped once each. Text that could still turn out to be a marker is held
+/// back until the next token settles it, so markers split across tokens are
+/// stripped too and never partially emitted.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingStringParser {
+    prefix: String,
+    suffix: String,
+}
+
+impl StreamingStringParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Strip `prefix` when the output starts with it.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Strip `suffix` when the output ends with it.
+    pub fn with_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Transform a `ChatStreamEvent` stream into text chunks.
+    ///
+    /// Only `Token` events contribute text; held-back text is flushed on
+    /// `Done` or when the stream ends. Errors are passed through.
+    pub fn transform<'a, S>(
+        &self,
+        events: S,
+    ) -> Pin<Box<dyn Stream<Item = Result<String>> + Send + 'a>>
+    where
+        S: Stream<Item = Result<ChatStreamEvent>> + Send + 'a,
+    {
+        let mut state = MarkerStripper {
+            prefix: self.prefix.clone(),
+            suffix: self.suffix.clone(),
+            prefix_done: self.prefix.is_empty(),
+            pending: String::new(),
+        };
+        let events = events.map(Some).chain(futures::stream::once(async { None }));
+        Box::pin(events.filter_map(move |event| {
+            let chunk = match event {
+                Some(Ok(ChatStreamEvent::Token(token))) => state.push(&token).map(Ok),
+                Some(Ok(ChatStreamEvent::Done)) | None => state.finish().map(Ok),
+                Some(Ok(_)) => None,
+                Some(Err(e)) => Some(Err(e)),
+            };
+            futures::future::ready(chunk)
+        }))
+    }
+}
+
+/// Incremental prefix/suffix stripping for `StreamingStringParser`.
+struct MarkerStripper {
+    prefix: String,
+    suffix: String,
+    prefix_done: bool,
+    /// Text received but not yet emitted.
+    pending: String,
+}
+
+impl MarkerStripper {
+    /// Add a token, returning whatever text is now known not to be a marker.
+    fn push(&mut self, token: &str) -> Option<String> {
+        self.pending.push_str(token);
+        if !self.prefix_done {
+            if self.pending.len() < self.prefix.len() && self.prefix.starts_with(&self.pending) {
+                return None;
+            }
+            if self.pending.starts_with(&self.prefix) {
+                self.pending.drain(..self.prefix.len());
+            }
+            self.prefix_done = true;
+        }
+        // Hold back the longest tail that could be the start of the suffix.
+        let held = (1..=self.suffix.len().min(self.pending.len()))
+            .rev()
+            .find(|&k| {
+                let start = self.pending.len() - k;
+                self.pending.is_char_boundary(start)
+                    && self.suffix.starts_with(&self.pending[start..])
+            })
+            .unwrap_or(0);
+        let ready: String = self.pending.drain(..self.pending.len() - held).collect();
+        (!ready.is_empty()).then_some(ready)
+    }
+
+    /// Flush the held-back text, dropping a complete trailing suffix.
+    fn finish(&mut self) -> Option<String> {
+        self.prefix_done = true;
+        if !self.suffix.is_empty() && self.pending.ends_with(&self.suffix) {
+            self.pending.truncate(self.pending.len() - self.suffix.len());
+        }
+        let rest = std::mem::take(&mut self.pending);
+        (!rest.is_empty()).then_some(rest)
+    }
+}
+
 /// Parses a single `Message` to extract its text content.
 pub struct MessageContentParser;
 
@@ -239,6 +348,62 @@ mod tests {
         assert_eq!(result, "question");
     }
 
+    // --- StreamingStringParser tests ---
+
+    async fn collect_chunks(parser: &StreamingStringParser, tokens: &[&str]) -> Vec<String> {
+        let mut events: Vec<Result<ChatStreamEvent>> = tokens
+            .iter()
+            .map(|t| Ok(ChatStreamEvent::Token(t.to_string())))
+            .collect();
+        events.push(Ok(ChatStreamEvent::Done));
+        parser
+            .transform(futures::stream::iter(events))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn streaming_string_parser_passes_tokens_through() {
+        let parser = StreamingStringParser::new();
+        let chunks = collect_chunks(&parser, &["Hel", "lo", "!"]).await;
+        assert_eq!(chunks, vec!["Hel", "lo", "!"]);
+    }
+
+    #[tokio::test]
+    async fn streaming_string_parser_strips_markers_split_across_tokens() {
+        let parser = StreamingStringParser::new()
+            .with_prefix("<answer>")
+            .with_suffix("</answer>");
+        let chunks = collect_chunks(&parser, &["<ans", "wer>Hi", " there</", "answer>"]).await;
+        assert_eq!(chunks.concat(), "Hi there");
+        assert!(chunks.iter().all(|c| !c.contains('<')));
+    }
+
+    #[tokio::test]
+    async fn streaming_string_parser_keeps_near_miss_markers() {
+        let parser = StreamingStringParser::new()
+            .with_prefix("<answer>")
+            .with_suffix("</answer>");
+        let chunks = collect_chunks(&parser, &["<a", "b> x </ans", "w"]).await;
+        assert_eq!(chunks.concat(), "<ab> x </answ");
+    }
+
+    #[tokio::test]
+    async fn streaming_string_parser_ignores_other_events_and_forwards_errors() {
+        let parser = StreamingStringParser::new();
+        let events = vec![
+            Ok(ChatStreamEvent::Reasoning("thinking".into())),
+            Ok(ChatStreamEvent::Token("ok".into())),
+            Err(AyasError::Other("stream broke".into())),
+        ];
+        let chunks: Vec<Result<String>> =
+            parser.transform(futures::stream::iter(events)).collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap(), "ok");
+        assert!(chunks[1].is_err());
+    }
+
     // --- JsonOutputParser tests ---
 
     #[tokio::test]
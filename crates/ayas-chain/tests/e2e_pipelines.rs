@@ -4,6 +4,7 @@
 //! into realistic multi-stage pipelines.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use serde::Deserialize;
 use serde_json::Value;
@@ -15,7 +16,10 @@ use ayas_chain::prompt::PromptTemplate;
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::AyasError;
 use ayas_core::message::Message;
-use ayas_core::runnable::{Runnable, RunnableBranch, RunnableExt, RunnablePassthrough};
+use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::runnable::{
+    Runnable, RunnableBranch, RunnableExt, RunnablePassthrough, SequenceSignal,
+};
 
 // ---------------------------------------------------------------------------
 // Test 1: Prompt -> Model -> JsonOutputParser pipeline
@@ -271,3 +275,87 @@ async fn error_propagation_through_pipeline() {
     let err = result.unwrap_err().to_string();
     assert!(err.contains("stage 2 processing failed"));
 }
+
+// ---------------------------------------------------------------------------
+// Test 8: Guardrail short-circuits the rest of the pipeline
+// ---------------------------------------------------------------------------
+
+/// A guardrail step rejects blocked topics before the prompt and model run;
+/// allowed input flows through to the model as usual.
+#[tokio::test]
+async fn guardrail_stops_pipeline() {
+    let guardrail = RunnableLambda::new(|vars: HashMap<String, String>, _config| async move {
+        let question = vars.get("question").cloned().unwrap_or_default();
+        if question.to_lowercase().contains("password") {
+            Ok(SequenceSignal::Stop(
+                "Sorry, I can't help with credentials.".to_string(),
+            ))
+        } else {
+            Ok(SequenceSignal::Continue(vars))
+        }
+    });
+    let prompt = PromptTemplate::from_messages(vec![("user", "{question}")]);
+    let model = MockChatModel::with_response("Rust is a systems language.");
+    let chain = guardrail.pipe_or_stop(prompt.pipe(model).pipe(StringOutputParser));
+    let config = RunnableConfig::default();
+
+    let mut blocked = HashMap::new();
+    blocked.insert("question".into(), "What is the admin password?".into());
+    let result = chain.invoke(blocked, &config).await.unwrap();
+    assert_eq!(result, "Sorry, I can't help with credentials.");
+
+    let mut allowed = HashMap::new();
+    allowed.insert("question".into(), "What is Rust?".into());
+    let result = chain.invoke(allowed, &config).await.unwrap();
+    assert_eq!(result, "Rust is a systems language.");
+}
+
+// ---------------------------------------------------------------------------
+// Test 9: RunnableBranch routing on an async model classifier
+// ---------------------------------------------------------------------------
+
+/// A classifier model labels each question; the branch awaits it to pick the
+/// billing or general chain.
+#[tokio::test]
+async fn branch_routes_on_async_classifier() {
+    let classifier = Arc::new(MockChatModel::with_handler(|messages| {
+        if messages[0].content().contains("invoice") {
+            MockChatModel::text_result("billing")
+        } else {
+            MockChatModel::text_result("general")
+        }
+    }));
+    let billing_chain = PromptTemplate::from_messages(vec![("user", "{question}")])
+        .pipe(MockChatModel::with_response("Billing team here."))
+        .pipe(StringOutputParser);
+    let general_chain = PromptTemplate::from_messages(vec![("user", "{question}")])
+        .pipe(MockChatModel::with_response("Happy to help."))
+        .pipe(StringOutputParser);
+
+    let branch = RunnableBranch::builder()
+        .async_branch(
+            move |vars: &HashMap<String, String>| {
+                let classifier = classifier.clone();
+                let question = vars.get("question").cloned().unwrap_or_default();
+                Box::pin(async move {
+                    let result = classifier
+                        .generate(&[Message::user(question)], &CallOptions::default())
+                        .await?;
+                    Ok::<_, AyasError>(result.message.content() == "billing")
+                })
+            },
+            billing_chain,
+        )
+        .default(general_chain)
+        .build()
+        .unwrap();
+    let config = RunnableConfig::default();
+
+    let mut vars = HashMap::new();
+    vars.insert("question".into(), "Where is my invoice?".into());
+    assert_eq!(branch.invoke(vars, &config).await.unwrap(), "Billing team here.");
+
+    let mut vars = HashMap::new();
+    vars.insert("question".into(), "What are your hours?".into());
+    assert_eq!(branch.invoke(vars, &config).await.unwrap(), "Happy to help.");
+}
@@ -26,16 +26,56 @@ pub fn command_output(update: Value, goto: impl Into<String>) -> Value {
     })
 }
 
+/// Create a command output that applies `update` and routes to several nodes.
+///
+/// The targets run in parallel in the next super-step, as with a fan-out
+/// edge, so a supervisor node can update shared state and dispatch workers
+/// in one return value.
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use ayas_checkpoint::command::{command_fan_out, extract_command};
+///
+/// let output = command_fan_out(json!({"plan": "split"}), ["research", "write"]);
+/// let (_, goto) = extract_command(&output).unwrap();
+/// assert_eq!(goto, vec!["research", "write"]);
+/// ```
+pub fn command_fan_out<I, S>(update: Value, goto: I) -> Value
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let goto: Vec<String> = goto.into_iter().map(Into::into).collect();
+    json!({
+        COMMAND_KEY: {
+            "update": update,
+            "goto": goto
+        }
+    })
+}
+
 /// Check if a node output contains a command.
 pub fn is_command(output: &Value) -> bool {
     output.get(COMMAND_KEY).is_some()
 }
 
-/// Extract command parts (update, goto) from a node output.
-pub fn extract_command(output: &Value) -> Option<(Value, String)> {
+/// Extract command parts (update, goto targets) from a node output.
+///
+/// `goto` may be a single node name or an array of names; anything else
+/// (including an array with a non-string entry) is not a valid command.
+pub fn extract_command(output: &Value) -> Option<(Value, Vec<String>)> {
     let cmd = output.get(COMMAND_KEY)?;
     let update = cmd.get("update")?.clone();
-    let goto = cmd.get("goto")?.as_str()?.to_string();
+    let goto = match cmd.get("goto")? {
+        Value::String(target) => vec![target.clone()],
+        Value::Array(targets) => targets
+            .iter()
+            .map(|t| t.as_str().map(String::from))
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
     Some((update, goto))
 }
 
@@ -85,7 +125,7 @@ mod tests {
 
         let (extracted_update, extracted_goto) = extract_command(&output).unwrap();
         assert_eq!(extracted_update, update);
-        assert_eq!(extracted_goto, "target_node");
+        assert_eq!(extracted_goto, vec!["target_node"]);
     }
 
     #[test]
@@ -117,7 +157,7 @@ mod tests {
         let output = command_output(json!({}), "end");
         let (update, goto) = extract_command(&output).unwrap();
         assert_eq!(update, json!({}));
-        assert_eq!(goto, "end");
+        assert_eq!(goto, vec!["end"]);
     }
 
     #[test]
@@ -130,4 +170,18 @@ mod tests {
         let (extracted, _) = extract_command(&output).unwrap();
         assert_eq!(extracted, update);
     }
+
+    #[test]
+    fn extract_command_goto_array() {
+        let output = command_fan_out(json!({"plan": 1}), ["a", "b"]);
+        let (update, goto) = extract_command(&output).unwrap();
+        assert_eq!(update, json!({"plan": 1}));
+        assert_eq!(goto, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn extract_command_goto_array_with_non_string() {
+        let output = json!({ COMMAND_KEY: { "update": {}, "goto": ["a", 1] } });
+        assert!(extract_command(&output).is_none());
+    }
 }
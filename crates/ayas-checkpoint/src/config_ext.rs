@@ -1,5 +1,5 @@
 use ayas_core::config::RunnableConfig;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
 use crate::interrupt::config_keys;
 
@@ -8,9 +8,16 @@ pub trait CheckpointConfigExt {
     fn with_thread_id(self, thread_id: impl Into<String>) -> Self;
     fn with_checkpoint_id(self, checkpoint_id: impl Into<String>) -> Self;
     fn with_resume_value(self, value: Value) -> Self;
+    /// Resume value aimed at the interrupt with `key`; resuming a checkpoint
+    /// that waits on a different interrupt fails instead of misrouting it.
+    fn with_resume_value_for(self, key: impl Into<String>, value: Value) -> Self;
+    /// Stamp `key: value` into the metadata of every checkpoint the run writes.
+    fn with_checkpoint_metadata(self, key: impl Into<String>, value: Value) -> Self;
     fn thread_id(&self) -> Option<String>;
     fn checkpoint_id(&self) -> Option<String>;
     fn resume_value(&self) -> Option<Value>;
+    fn resume_key(&self) -> Option<String>;
+    fn checkpoint_metadata(&self) -> Map<String, Value>;
 }
 
 impl CheckpointConfigExt for RunnableConfig {
@@ -32,6 +39,20 @@ impl CheckpointConfigExt for RunnableConfig {
         self
     }
 
+    fn with_resume_value_for(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.configurable
+            .insert(config_keys::RESUME_KEY.into(), json!(key.into()));
+        self.with_resume_value(value)
+    }
+
+    fn with_checkpoint_metadata(mut self, key: impl Into<String>, value: Value) -> Self {
+        let mut metadata = self.checkpoint_metadata();
+        metadata.insert(key.into(), value);
+        self.configurable
+            .insert(config_keys::CHECKPOINT_METADATA.into(), Value::Object(metadata));
+        self
+    }
+
     fn thread_id(&self) -> Option<String> {
         self.configurable
             .get(config_keys::THREAD_ID)
@@ -49,6 +70,21 @@ impl CheckpointConfigExt for RunnableConfig {
     fn resume_value(&self) -> Option<Value> {
         self.configurable.get(config_keys::RESUME_VALUE).cloned()
     }
+
+    fn resume_key(&self) -> Option<String> {
+        self.configurable
+            .get(config_keys::RESUME_KEY)
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }
+
+    fn checkpoint_metadata(&self) -> Map<String, Value> {
+        self.configurable
+            .get(config_keys::CHECKPOINT_METADATA)
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -74,6 +110,24 @@ mod tests {
         assert_eq!(config.resume_value(), Some(value));
     }
 
+    #[test]
+    fn set_and_get_keyed_resume_value() {
+        let config = RunnableConfig::default().with_resume_value_for("review", json!(true));
+        assert_eq!(config.resume_key(), Some("review".to_string()));
+        assert_eq!(config.resume_value(), Some(json!(true)));
+    }
+
+    #[test]
+    fn checkpoint_metadata_accumulates() {
+        let config = RunnableConfig::default()
+            .with_checkpoint_metadata("graph", json!({"nodes": []}))
+            .with_checkpoint_metadata("owner", json!("alice"));
+        let metadata = config.checkpoint_metadata();
+        assert_eq!(metadata["graph"], json!({"nodes": []}));
+        assert_eq!(metadata["owner"], json!("alice"));
+        assert!(RunnableConfig::default().checkpoint_metadata().is_empty());
+    }
+
     #[test]
     fn missing_values_return_none() {
         let config = RunnableConfig::default();
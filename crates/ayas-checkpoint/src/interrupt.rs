@@ -1,8 +1,13 @@
 use serde_json::{json, Value};
 
+use crate::types::Checkpoint;
+
 /// The key used in node output to signal an interrupt.
 pub const INTERRUPT_KEY: &str = "__interrupt__";
 
+/// Channel that collects answers to keyed interrupts, as `{key: resume_value}`.
+pub const RESUME_VALUES_CHANNEL: &str = "resume_values";
+
 /// Create an interrupt output from a node.
 /// Use this in node functions to signal that human input is needed.
 ///
@@ -19,6 +24,25 @@ pub fn interrupt_output(value: Value) -> Value {
     json!({ INTERRUPT_KEY: { "value": value } })
 }
 
+/// Create an interrupt output identified by `key`.
+///
+/// The value passed on resume is stored under `resume_values[key]` instead
+/// of the single `resume_value` channel, so a graph with several interrupt
+/// points keeps every answer.
+///
+/// # Example
+///
+/// ```
+/// use serde_json::json;
+/// use ayas_checkpoint::interrupt::{extract_interrupt_key, interrupt_output_for};
+///
+/// let output = interrupt_output_for("legal_review", json!("Approve the contract?"));
+/// assert_eq!(extract_interrupt_key(&output).as_deref(), Some("legal_review"));
+/// ```
+pub fn interrupt_output_for(key: impl Into<String>, value: Value) -> Value {
+    json!({ INTERRUPT_KEY: { "value": value, "key": key.into() } })
+}
+
 /// Check if a node output contains an interrupt signal.
 pub fn is_interrupt(output: &Value) -> bool {
     output.get(INTERRUPT_KEY).is_some()
@@ -32,11 +56,35 @@ pub fn extract_interrupt_value(output: &Value) -> Option<Value> {
         .cloned()
 }
 
+/// Extract the interrupt key from a node output, if the interrupt has one.
+pub fn extract_interrupt_key(output: &Value) -> Option<String> {
+    output
+        .get(INTERRUPT_KEY)
+        .and_then(|v| v.get("key"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
+/// The key of the interrupt a checkpoint is waiting on, if it was keyed.
+///
+/// Interrupt checkpoints record the key under [`INTERRUPT_KEY`] in their
+/// channel values; no channel uses that name, so it is never restored.
+pub fn pending_interrupt_key(checkpoint: &Checkpoint) -> Option<String> {
+    checkpoint
+        .channel_values
+        .get(INTERRUPT_KEY)
+        .and_then(|v| v.get("key"))
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
 /// Config key constants for checkpoint-related configuration.
 pub mod config_keys {
     pub const THREAD_ID: &str = "thread_id";
     pub const CHECKPOINT_ID: &str = "checkpoint_id";
     pub const RESUME_VALUE: &str = "resume_value";
+    pub const RESUME_KEY: &str = "resume_key";
+    pub const CHECKPOINT_METADATA: &str = "checkpoint_metadata";
 }
 
 #[cfg(test)]
@@ -126,4 +174,18 @@ mod tests {
         assert_eq!(config_keys::CHECKPOINT_ID, "checkpoint_id");
         assert_eq!(config_keys::RESUME_VALUE, "resume_value");
     }
+
+    #[test]
+    fn keyed_interrupt_output() {
+        let output = interrupt_output_for("approve_a", json!("ok?"));
+        assert!(is_interrupt(&output));
+        assert_eq!(extract_interrupt_value(&output), Some(json!("ok?")));
+        assert_eq!(extract_interrupt_key(&output), Some("approve_a".to_string()));
+    }
+
+    #[test]
+    fn unkeyed_interrupt_has_no_key() {
+        let output = interrupt_output(json!("ok?"));
+        assert_eq!(extract_interrupt_key(&output), None);
+    }
 }
@@ -10,10 +10,14 @@ pub mod store;
 pub mod types;
 
 pub mod prelude {
-    pub use crate::command::{command_output, extract_command, is_command, COMMAND_KEY};
+    pub use crate::command::{
+        command_fan_out, command_output, extract_command, is_command, COMMAND_KEY,
+    };
     pub use crate::config_ext::CheckpointConfigExt;
     pub use crate::interrupt::{
-        config_keys, extract_interrupt_value, interrupt_output, is_interrupt, INTERRUPT_KEY,
+        config_keys, extract_interrupt_key, extract_interrupt_value, interrupt_output,
+        interrupt_output_for, is_interrupt, pending_interrupt_key, INTERRUPT_KEY,
+        RESUME_VALUES_CHANNEL,
     };
     pub use crate::memory::MemoryCheckpointStore;
     #[cfg(feature = "postgres")]
@@ -94,11 +94,7 @@ mod tests {
             step,
             channel_values: HashMap::from([("count".into(), json!(step))]),
             pending_nodes: vec![],
-            metadata: CheckpointMetadata {
-                source: "loop".into(),
-                step,
-                node_name: Some(format!("node_{step}")),
-            },
+            metadata: CheckpointMetadata::new("loop", step, Some(format!("node_{step}"))),
             created_at: Utc::now(),
         }
     }
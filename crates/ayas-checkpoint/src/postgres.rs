@@ -218,11 +218,7 @@ mod tests {
 
     #[test]
     fn checkpoint_metadata_serde() {
-        let meta = CheckpointMetadata {
-            source: "loop".into(),
-            step: 3,
-            node_name: Some("agent".into()),
-        };
+        let meta = CheckpointMetadata::new("loop", 3, Some("agent".into()));
         let json = serde_json::to_value(&meta).unwrap();
         let parsed: CheckpointMetadata = serde_json::from_value(json).unwrap();
         assert_eq!(parsed.source, "loop");
@@ -10,6 +10,10 @@ pub struct SendDirective {
     pub node: String,
     /// Private input to merge into state before executing the target node.
     pub input: Value,
+    /// When set, each top-level value of the branch's output is nested under
+    /// this key before the channel update, so `{"results": r}` is applied as
+    /// `{"results": {key: r}}`. Lets an aggregator correlate branches.
+    pub key: Option<String>,
 }
 
 impl SendDirective {
@@ -18,6 +22,29 @@ impl SendDirective {
         Self {
             node: node.into(),
             input,
+            key: None,
+        }
+    }
+
+    /// Namespace the branch's output under `key` (see [`SendDirective::key`]).
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Apply this directive's `key` to the target node's output.
+    pub fn namespace_output(&self, output: Value) -> Value {
+        match (&self.key, output) {
+            (Some(key), Value::Object(map)) => Value::Object(
+                map.into_iter()
+                    .map(|(channel, value)| {
+                        let mut branch = serde_json::Map::new();
+                        branch.insert(key.clone(), value);
+                        (channel, Value::Object(branch))
+                    })
+                    .collect(),
+            ),
+            (_, output) => output,
         }
     }
 }
@@ -42,7 +69,13 @@ impl SendDirective {
 pub fn send_output(sends: Vec<SendDirective>) -> Value {
     let arr: Vec<Value> = sends
         .iter()
-        .map(|s| json!({"node": s.node, "input": s.input}))
+        .map(|s| {
+            let mut item = json!({"node": s.node, "input": s.input});
+            if let Some(key) = &s.key {
+                item["key"] = Value::String(key.clone());
+            }
+            item
+        })
         .collect();
     json!({ SEND_KEY: arr })
 }
@@ -59,7 +92,8 @@ pub fn extract_sends(output: &Value) -> Option<Vec<SendDirective>> {
     for item in arr {
         let node = item.get("node")?.as_str()?.to_string();
         let input = item.get("input")?.clone();
-        sends.push(SendDirective { node, input });
+        let key = item.get("key").and_then(Value::as_str).map(String::from);
+        sends.push(SendDirective { node, input, key });
     }
     Some(sends)
 }
@@ -146,6 +180,24 @@ mod tests {
         assert!(extract_sends(&output).is_none());
     }
 
+    #[test]
+    fn send_key_roundtrips_and_namespaces_output() {
+        let output = send_output(vec![
+            SendDirective::new("worker", json!({})).with_key("0"),
+            SendDirective::new("worker", json!({})),
+        ]);
+        let sends = extract_sends(&output).unwrap();
+        assert_eq!(sends[0].key.as_deref(), Some("0"));
+        assert_eq!(sends[1].key, None);
+
+        let branch = json!({"results": "r0", "count": 1});
+        assert_eq!(
+            sends[0].namespace_output(branch.clone()),
+            json!({"results": {"0": "r0"}, "count": {"0": 1}})
+        );
+        assert_eq!(sends[1].namespace_output(branch.clone()), branch);
+    }
+
     #[test]
     fn send_output_empty_vec() {
         let output = send_output(vec![]);
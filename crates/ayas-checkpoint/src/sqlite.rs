@@ -80,11 +80,8 @@ fn row_to_checkpoint(row: &rusqlite::Row<'_>) -> rusqlite::Result<Checkpoint> {
     let pending_nodes: Vec<String> =
         serde_json::from_str(&pending_nodes_json).unwrap_or_default();
     let metadata: CheckpointMetadata =
-        serde_json::from_str(&metadata_json).unwrap_or(CheckpointMetadata {
-            source: "unknown".into(),
-            step: step as usize,
-            node_name: None,
-        });
+        serde_json::from_str(&metadata_json)
+            .unwrap_or_else(|_| CheckpointMetadata::new("unknown", step as usize, None));
     let created_at: DateTime<Utc> = created_at_str
         .parse()
         .unwrap_or_else(|_| Utc::now());
@@ -256,11 +253,7 @@ mod tests {
             step,
             channel_values: HashMap::from([("count".into(), json!(step))]),
             pending_nodes: vec![format!("node_{step}")],
-            metadata: CheckpointMetadata {
-                source: "loop".into(),
-                step,
-                node_name: Some(format!("node_{step}")),
-            },
+            metadata: CheckpointMetadata::new("loop", step, Some(format!("node_{step}"))),
             created_at: Utc::now(),
         }
     }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 /// A snapshot of graph state at a particular execution step.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +34,27 @@ pub struct CheckpointMetadata {
     pub step: usize,
     /// The node that was just executed (if applicable).
     pub node_name: Option<String>,
+    /// Caller-supplied values stamped on every checkpoint of a run
+    /// (see `CheckpointConfigExt::with_checkpoint_metadata`).
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+impl CheckpointMetadata {
+    pub fn new(source: impl Into<String>, step: usize, node_name: Option<String>) -> Self {
+        Self {
+            source: source.into(),
+            step,
+            node_name,
+            extra: Map::new(),
+        }
+    }
+
+    /// Attach caller-supplied values, typically `config.checkpoint_metadata()`.
+    pub fn with_extra(mut self, extra: Map<String, Value>) -> Self {
+        self.extra = extra;
+        self
+    }
 }
 
 /// The outcome of a resumable graph execution.
@@ -86,11 +107,7 @@ mod tests {
             step: 0,
             channel_values: HashMap::from([("count".into(), json!(42))]),
             pending_nodes: vec!["node_b".into()],
-            metadata: CheckpointMetadata {
-                source: "loop".into(),
-                step: 0,
-                node_name: Some("node_a".into()),
-            },
+            metadata: CheckpointMetadata::new("loop", 0, Some("node_a".into())),
             created_at: Utc::now(),
         };
 
@@ -101,6 +118,21 @@ mod tests {
         assert_eq!(deserialized.channel_values["count"], json!(42));
     }
 
+    #[test]
+    fn metadata_extra_is_optional() {
+        let meta: CheckpointMetadata =
+            serde_json::from_value(json!({"source": "loop", "step": 1, "node_name": null}))
+                .unwrap();
+        assert!(meta.extra.is_empty());
+
+        let mut extra = Map::new();
+        extra.insert("graph".into(), json!({"nodes": []}));
+        let meta = CheckpointMetadata::new("loop", 1, None).with_extra(extra);
+        let roundtrip: CheckpointMetadata =
+            serde_json::from_value(serde_json::to_value(&meta).unwrap()).unwrap();
+        assert_eq!(roundtrip.extra["graph"], json!({"nodes": []}));
+    }
+
     #[test]
     fn graph_output_complete() {
         let output = GraphOutput::Complete(json!({"result": "done"}));
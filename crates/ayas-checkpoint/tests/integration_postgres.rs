@@ -38,11 +38,7 @@ fn make_checkpoint(thread_id: &str, id: &str, step: usize) -> Checkpoint {
         step,
         channel_values,
         pending_nodes: vec![],
-        metadata: CheckpointMetadata {
-            source: "loop".into(),
-            step,
-            node_name: Some("agent".into()),
-        },
+        metadata: CheckpointMetadata::new("loop", step, Some("agent".into())),
         created_at: Utc::now(),
     }
 }
@@ -43,10 +43,8 @@ fn arb_metadata() -> impl Strategy<Value = CheckpointMetadata> {
         0..100usize,
         proptest::option::of("[a-z_]{1,12}".prop_map(String::from)),
     )
-        .prop_map(|(source, step, node_name)| CheckpointMetadata {
-            source: source.to_string(),
-            step,
-            node_name,
+        .prop_map(|(source, step, node_name)| {
+            CheckpointMetadata::new(source.to_string(), step, node_name)
         })
 }
 
@@ -200,11 +198,7 @@ proptest! {
                     step: i,
                     channel_values: HashMap::from([("v".into(), val.clone())]),
                     pending_nodes: vec![],
-                    metadata: CheckpointMetadata {
-                        source: "loop".into(),
-                        step: i,
-                        node_name: None,
-                    },
+                    metadata: CheckpointMetadata::new("loop", i, None),
                     created_at: Utc.timestamp_opt(1_700_000_000 + i as i64, 0).unwrap(),
                 };
                 mem.put(cp.clone()).await.unwrap();
@@ -242,11 +236,7 @@ proptest! {
                     step,
                     channel_values: HashMap::from([("s".into(), Value::Number(step.into()))]),
                     pending_nodes: vec![],
-                    metadata: CheckpointMetadata {
-                        source: "loop".into(),
-                        step,
-                        node_name: None,
-                    },
+                    metadata: CheckpointMetadata::new("loop", step, None),
                     created_at: Utc.timestamp_opt(1_700_000_000 + step as i64, 0).unwrap(),
                 };
                 mem.put(cp.clone()).await.unwrap();
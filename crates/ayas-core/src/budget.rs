@@ -0,0 +1,139 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::UsageMetadata;
+
+/// Spending cap for a run. Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// Maximum total (input + output) tokens across all model calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    /// Maximum cost in USD, as priced by the run's [`UsageAccumulator`].
+    ///
+    /// Requires an accumulator with a pricer (see
+    /// [`UsageAccumulator::with_pricer`]); graph runs reject the budget
+    /// otherwise rather than let the limit silently never fire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<f64>,
+}
+
+impl Budget {
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Whether `spent` goes over either limit.
+    pub fn is_exceeded_by(&self, spent: &Spent) -> bool {
+        self.max_tokens.is_some_and(|max| spent.tokens > max)
+            || self.max_cost.is_some_and(|max| spent.cost > max)
+    }
+}
+
+/// Tokens and cost reported so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Spent {
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+/// Prices one model call in USD from the model name and its usage.
+pub type Pricer = Arc<dyn Fn(&str, &UsageMetadata) -> f64 + Send + Sync>;
+
+/// Ambient usage sink for a run.
+///
+/// Clones share the same totals, so nodes that receive a clone of the run's
+/// config all report into one accumulator. Without a pricer, calls cost 0,
+/// so only token limits can be enforced.
+#[derive(Clone, Default)]
+pub struct UsageAccumulator {
+    spent: Arc<Mutex<Spent>>,
+    pricer: Option<Pricer>,
+}
+
+impl UsageAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_pricer(mut self, pricer: Pricer) -> Self {
+        self.pricer = Some(pricer);
+        self
+    }
+
+    /// Whether calls are priced, i.e. a cost limit can be enforced.
+    pub fn is_priced(&self) -> bool {
+        self.pricer.is_some()
+    }
+
+    /// Add one model call's usage to the totals.
+    pub fn record(&self, model: &str, usage: &UsageMetadata) {
+        let cost = self.pricer.as_ref().map_or(0.0, |price| price(model, usage));
+        let mut spent = self.spent.lock().unwrap();
+        spent.tokens += usage.total_tokens;
+        spent.cost += cost;
+    }
+
+    /// Totals reported so far.
+    pub fn spent(&self) -> Spent {
+        *self.spent.lock().unwrap()
+    }
+}
+
+impl fmt::Debug for UsageAccumulator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UsageAccumulator")
+            .field("spent", &self.spent())
+            .field("priced", &self.pricer.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: u64, output: u64) -> UsageMetadata {
+        UsageMetadata {
+            input_tokens: input,
+            output_tokens: output,
+            total_tokens: input + output,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clones_share_totals() {
+        let acc = UsageAccumulator::new();
+        acc.clone().record("m", &usage(10, 5));
+        acc.record("m", &usage(1, 1));
+        assert_eq!(acc.spent(), Spent { tokens: 17, cost: 0.0 });
+    }
+
+    #[test]
+    fn pricer_adds_cost() {
+        let pricer: Pricer = Arc::new(|_model: &str, u: &UsageMetadata| {
+            u.total_tokens as f64 * 0.01
+        });
+        let acc = UsageAccumulator::new().with_pricer(pricer);
+        acc.record("m", &usage(50, 50));
+        assert!((acc.spent().cost - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_limits() {
+        let spent = Spent { tokens: 100, cost: 0.5 };
+        assert!(!Budget::default().is_exceeded_by(&spent));
+        assert!(!Budget::default().with_max_tokens(100).is_exceeded_by(&spent));
+        assert!(Budget::default().with_max_tokens(99).is_exceeded_by(&spent));
+        assert!(Budget::default().with_max_cost(0.25).is_exceeded_by(&spent));
+    }
+}
@@ -1,8 +1,22 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use crate::budget::{Budget, UsageAccumulator};
+use crate::model::ChatStreamEvent;
+
+/// Sender for model stream events emitted from inside a running node.
+pub type StreamSender = mpsc::UnboundedSender<ChatStreamEvent>;
+
+/// Reserved `configurable` key holding per-request model parameter overrides.
+///
+/// The value is an object; see [`crate::model::CallOptions::with_overrides`]
+/// for the honored keys.
+pub const MODEL_OVERRIDES_KEY: &str = "model_overrides";
+
 /// Configuration passed through the Runnable chain.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunnableConfig {
@@ -23,6 +37,32 @@ pub struct RunnableConfig {
     /// Arbitrary configurable values accessible by runnables.
     #[serde(default)]
     pub configurable: HashMap<String, serde_json::Value>,
+
+    /// Ambient sink for token-level model output.
+    ///
+    /// Set by streaming graph execution around each node; model-backed nodes
+    /// stream through it (see [`crate::model::generate_with_config`]).
+    #[serde(skip)]
+    pub stream_tx: Option<StreamSender>,
+
+    /// Spending cap enforced by graph execution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+
+    /// Ambient accumulator model calls report their usage into.
+    ///
+    /// Graph execution attaches one when `budget` is set (see
+    /// [`with_usage_tracking`](Self::with_usage_tracking)); model-backed
+    /// nodes record into it via [`crate::model::generate_with_config`].
+    #[serde(skip)]
+    pub usage: Option<UsageAccumulator>,
+
+    /// Cancels the run when triggered.
+    ///
+    /// Graph execution stops with `GraphError::Cancelled` at the start of
+    /// the next super-step; nodes already running are not interrupted.
+    #[serde(skip)]
+    pub cancel: Option<CancellationToken>,
 }
 
 impl Default for RunnableConfig {
@@ -33,6 +73,10 @@ impl Default for RunnableConfig {
             recursion_limit: 25,
             run_id: Uuid::new_v4(),
             configurable: HashMap::new(),
+            stream_tx: None,
+            budget: None,
+            usage: None,
+            cancel: None,
         }
     }
 }
@@ -61,6 +105,47 @@ impl RunnableConfig {
         self.run_id = run_id;
         self
     }
+
+    pub fn with_stream_tx(mut self, stream_tx: StreamSender) -> Self {
+        self.stream_tx = Some(stream_tx);
+        self
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_usage(mut self, usage: UsageAccumulator) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Whether the run's cancellation token has been triggered.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Override model parameters (e.g. `{"temperature": 0.2}`) for every
+    /// model call made with this config, under [`MODEL_OVERRIDES_KEY`].
+    pub fn with_model_overrides(mut self, overrides: serde_json::Value) -> Self {
+        self.configurable.insert(MODEL_OVERRIDES_KEY.into(), overrides);
+        self
+    }
+
+    /// Attach a fresh [`UsageAccumulator`] if a budget is set and none is
+    /// attached yet, so every node of the run reports into the same one.
+    pub fn with_usage_tracking(mut self) -> Self {
+        if self.budget.is_some() && self.usage.is_none() {
+            self.usage = Some(UsageAccumulator::new());
+        }
+        self
+    }
 }
 
 #[cfg(test)]
@@ -125,4 +210,40 @@ mod tests {
         assert_eq!(deserialized.recursion_limit, config.recursion_limit);
         assert_eq!(deserialized.run_id, config.run_id);
     }
+
+    #[test]
+    fn usage_tracking_only_with_budget() {
+        assert!(RunnableConfig::new().with_usage_tracking().usage.is_none());
+
+        let config = RunnableConfig::new()
+            .with_budget(Budget::default().with_max_tokens(10))
+            .with_usage_tracking();
+        let usage = config.usage.clone().unwrap();
+        let again = config.with_usage_tracking();
+        usage.record("m", &crate::message::UsageMetadata {
+            total_tokens: 3,
+            ..Default::default()
+        });
+        assert_eq!(again.usage.unwrap().spent().tokens, 3);
+    }
+
+    #[test]
+    fn stream_tx_is_cloned_but_not_serialized() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let config = RunnableConfig::new().with_stream_tx(tx);
+
+        let cloned = config.clone();
+        cloned
+            .stream_tx
+            .as_ref()
+            .unwrap()
+            .send(ChatStreamEvent::Token("hi".into()))
+            .unwrap();
+        assert_eq!(rx.try_recv().unwrap(), ChatStreamEvent::Token("hi".into()));
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("stream_tx"));
+        let deserialized: RunnableConfig = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.stream_tx.is_none());
+    }
 }
@@ -18,14 +18,22 @@ pub enum AyasError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Validation error: {0}")]
+    Validation(String),
+
     #[error("{0}")]
     Other(String),
 }
 
 #[derive(Debug, Error)]
 pub enum ModelError {
-    #[error("API request failed: {0}")]
-    ApiRequest(String),
+    #[error("API request failed: {}", format_api_request(*.status, .message))]
+    ApiRequest {
+        /// Status of the non-success HTTP response, or `None` when the
+        /// request got no usable response (connection or decoding failures).
+        status: Option<u16>,
+        message: String,
+    },
 
     #[error("Invalid response: {0}")]
     InvalidResponse(String),
@@ -35,6 +43,94 @@ pub enum ModelError {
 
     #[error("Rate limited: retry after {retry_after_secs:?}s")]
     RateLimited { retry_after_secs: Option<u64> },
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("Content filtered: {reason} {categories:?}")]
+    ContentFiltered {
+        reason: String,
+        categories: Vec<String>,
+    },
+
+    #[error("Unknown model '{model}'{}", format_suggestions(.suggestions))]
+    UnknownModel {
+        model: String,
+        /// Known model ids close to `model`, best match first.
+        suggestions: Vec<String>,
+    },
+
+    #[error(
+        "Deep Research interaction {interaction_id} timed out after {polls} polls ({elapsed_secs}s)"
+    )]
+    DeepResearchTimeout {
+        interaction_id: String,
+        polls: u32,
+        elapsed_secs: u64,
+    },
+
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
+
+    #[error("All models failed: {}", format_attempts(.attempts))]
+    AllFailed {
+        /// `(model_name, error)` for each attempted model, in order.
+        attempts: Vec<(String, AyasError)>,
+    },
+}
+
+impl ModelError {
+    /// An `ApiRequest` error without an HTTP status.
+    pub fn api_request(message: impl Into<String>) -> Self {
+        ModelError::ApiRequest {
+            status: None,
+            message: message.into(),
+        }
+    }
+
+    /// An `ApiRequest` error for a non-success HTTP response.
+    pub fn http(status: u16, message: impl Into<String>) -> Self {
+        ModelError::ApiRequest {
+            status: Some(status),
+            message: message.into(),
+        }
+    }
+
+    /// Whether retrying (or falling back to another model) may succeed:
+    /// rate limits, server errors and transport failures. Other HTTP errors
+    /// (bad requests, bad credentials) would fail again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ModelError::RateLimited { .. } => true,
+            ModelError::ApiRequest { status, .. } => {
+                status.is_none_or(|status| status == 429 || status >= 500)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn format_api_request(status: Option<u16>, message: &str) -> String {
+    match status {
+        Some(status) => format!("HTTP {status}: {message}"),
+        None => message.to_string(),
+    }
+}
+
+fn format_suggestions(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean {}?)", suggestions.join(", "))
+    }
+}
+
+fn format_attempts(attempts: &[(String, AyasError)]) -> String {
+    attempts
+        .iter()
+        .map(|(model, err)| format!("[{model}] {err}"))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 #[derive(Debug, Error)]
@@ -47,6 +143,9 @@ pub enum ToolError {
 
     #[error("Execution failed: {0}")]
     ExecutionFailed(String),
+
+    #[error("Invalid tool definition: {0}")]
+    InvalidDefinition(String),
 }
 
 #[derive(Debug, Error)]
@@ -69,6 +168,12 @@ pub enum GraphError {
     #[error("Recursion limit ({limit}) exceeded")]
     RecursionLimit { limit: usize },
 
+    #[error("Budget exceeded: {tokens} tokens, ${cost:.4} spent")]
+    BudgetExceeded { tokens: u64, cost: f64 },
+
+    #[error("Budget sets max_cost but the run's usage accumulator has no pricer")]
+    UnpricedBudget,
+
     #[error("Channel error: {0}")]
     Channel(String),
 
@@ -83,6 +188,9 @@ pub enum GraphError {
 
     #[error("Thread not found: {0}")]
     ThreadNotFound(String),
+
+    #[error("Execution cancelled")]
+    Cancelled,
 }
 
 pub type Result<T> = std::result::Result<T, AyasError>;
@@ -93,8 +201,10 @@ mod tests {
 
     #[test]
     fn model_error_display() {
-        let err = ModelError::ApiRequest("timeout".into());
+        let err = ModelError::api_request("timeout");
         assert_eq!(err.to_string(), "API request failed: timeout");
+        let err = ModelError::http(503, "busy");
+        assert_eq!(err.to_string(), "API request failed: HTTP 503: busy");
     }
 
     #[test]
@@ -105,6 +215,87 @@ mod tests {
         assert_eq!(err.to_string(), "Rate limited: retry after Some(30)s");
     }
 
+    #[test]
+    fn model_error_invalid_config_display() {
+        let err = ModelError::InvalidConfig("bad header".into());
+        assert_eq!(err.to_string(), "Invalid configuration: bad header");
+    }
+
+    #[test]
+    fn model_error_content_filtered_display() {
+        let err = ModelError::ContentFiltered {
+            reason: "SAFETY".into(),
+            categories: vec!["HARM_CATEGORY_HARASSMENT".into()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Content filtered: SAFETY [\"HARM_CATEGORY_HARASSMENT\"]"
+        );
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn model_error_deep_research_timeout_display() {
+        let err = ModelError::DeepResearchTimeout {
+            interaction_id: "int-1".into(),
+            polls: 12,
+            elapsed_secs: 60,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Deep Research interaction int-1 timed out after 12 polls (60s)"
+        );
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn model_error_is_retryable() {
+        assert!(ModelError::RateLimited { retry_after_secs: None }.is_retryable());
+        assert!(ModelError::api_request("timeout").is_retryable());
+        assert!(ModelError::http(503, "busy").is_retryable());
+        assert!(ModelError::http(429, "slow").is_retryable());
+        assert!(!ModelError::http(400, "bad").is_retryable());
+        assert!(!ModelError::http(403, "no").is_retryable());
+        // The status decides, not the message text
+        assert!(!ModelError::http(400, "HTTP 503 upstream").is_retryable());
+        assert!(!ModelError::Auth("bad key".into()).is_retryable());
+        assert!(!ModelError::InvalidResponse("garbage".into()).is_retryable());
+        assert!(!ModelError::Unsupported("no counting".into()).is_retryable());
+    }
+
+    #[test]
+    fn model_error_unknown_model_display() {
+        let err = ModelError::UnknownModel {
+            model: "gemini-2.5-flsh".into(),
+            suggestions: vec!["gemini-2.5-flash".into()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Unknown model 'gemini-2.5-flsh' (did you mean gemini-2.5-flash?)"
+        );
+        let err = ModelError::UnknownModel {
+            model: "x".into(),
+            suggestions: vec![],
+        };
+        assert_eq!(err.to_string(), "Unknown model 'x'");
+    }
+
+    #[test]
+    fn model_error_all_failed_display() {
+        let err = ModelError::AllFailed {
+            attempts: vec![
+                (
+                    "gemini".into(),
+                    ModelError::RateLimited { retry_after_secs: None }.into(),
+                ),
+                ("claude".into(), ModelError::api_request("timeout").into()),
+            ],
+        };
+        let msg = err.to_string();
+        assert!(msg.starts_with("All models failed: [gemini]"));
+        assert!(msg.contains("[claude] Model error: API request failed: timeout"));
+    }
+
     #[test]
     fn tool_error_display() {
         let err = ToolError::NotFound("web_search".into());
@@ -121,6 +312,11 @@ mod tests {
     fn graph_error_display() {
         let err = GraphError::RecursionLimit { limit: 25 };
         assert_eq!(err.to_string(), "Recursion limit (25) exceeded");
+        let err = GraphError::BudgetExceeded {
+            tokens: 1200,
+            cost: 0.05,
+        };
+        assert_eq!(err.to_string(), "Budget exceeded: 1200 tokens, $0.0500 spent");
     }
 
     #[test]
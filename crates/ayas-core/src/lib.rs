@@ -1,7 +1,10 @@
+pub mod budget;
 pub mod config;
 pub mod error;
+pub mod hash;
 pub mod message;
 pub mod model;
+pub mod moderation;
 pub mod runnable;
 pub mod stream;
 pub mod tool;
@@ -12,8 +15,10 @@ pub mod prelude {
     pub use crate::error::{AyasError, Result};
     pub use crate::message::{ContentPart, ContentSource, Message, MessageContent, ToolCall};
     pub use crate::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent};
+    pub use crate::moderation::{ModerationResult, Moderator, NoopModerator};
     pub use crate::runnable::{
-        Runnable, RunnableBranch, RunnableExt, RunnablePassthrough, RunnableWithFallback,
+        Runnable, RunnableBranch, RunnableExt, RunnablePassthrough, RunnableStoppableSequence,
+        RunnableValidated, RunnableWithFallback, SequenceSignal,
     };
     pub use crate::stream::{StreamEvent, StreamMode, parse_stream_modes};
     pub use crate::tool::{Tool, ToolDefinition};
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::error::AyasError;
+
 /// Metadata about token usage from a model call.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UsageMetadata {
@@ -7,6 +9,32 @@ pub struct UsageMetadata {
     pub output_tokens: u64,
     #[serde(default)]
     pub total_tokens: u64,
+    /// Input tokens served from the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    /// Input tokens written to the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
+    /// The model that served the call, when it differs from the called
+    /// model's `model_name` (e.g. a fallback model answered).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl UsageMetadata {
+    /// Add another call's usage to this one, e.g. across a tool-calling loop.
+    pub fn accumulate(&mut self, other: &UsageMetadata) {
+        fn add(total: &mut Option<u64>, more: Option<u64>) {
+            if let Some(more) = more {
+                *total = Some(total.unwrap_or(0) + more);
+            }
+        }
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+        add(&mut self.cache_read_tokens, other.cache_read_tokens);
+        add(&mut self.cache_write_tokens, other.cache_write_tokens);
+    }
 }
 
 /// A request from the AI to call a tool.
@@ -127,6 +155,9 @@ pub enum Message {
     Tool {
         content: String,
         tool_call_id: String,
+        /// The tool failed and `content` describes the error.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
     },
 }
 
@@ -175,9 +206,31 @@ impl Message {
         Message::Tool {
             content: content.into(),
             tool_call_id: tool_call_id.into(),
+            is_error: false,
+        }
+    }
+
+    /// A tool result reporting that the call failed, so the model can see the
+    /// error and recover instead of the run aborting.
+    pub fn tool_error(content: impl Into<String>, tool_call_id: impl Into<String>) -> Self {
+        Message::Tool {
+            content: content.into(),
+            tool_call_id: tool_call_id.into(),
+            is_error: true,
         }
     }
 
+    /// A [`Message::tool_error`] for a failed tool call. A tool's own failure
+    /// is given without the "Tool error: " prefix, since the result is
+    /// already flagged as an error.
+    pub fn tool_failure(error: &AyasError, tool_call_id: impl Into<String>) -> Self {
+        let content = match error {
+            AyasError::Tool(cause) => cause.to_string(),
+            other => other.to_string(),
+        };
+        Message::tool_error(content, tool_call_id)
+    }
+
     /// Extract the text content from any message variant.
     ///
     /// For multimodal messages (System/User with `Parts`), returns an empty string.
@@ -206,6 +259,28 @@ impl Message {
 mod tests {
     use super::*;
 
+    #[test]
+    fn usage_accumulate_sums_calls() {
+        let mut total = UsageMetadata {
+            input_tokens: 10,
+            output_tokens: 2,
+            total_tokens: 12,
+            ..Default::default()
+        };
+        total.accumulate(&UsageMetadata {
+            input_tokens: 20,
+            output_tokens: 5,
+            total_tokens: 25,
+            cache_read_tokens: Some(8),
+            ..Default::default()
+        });
+        assert_eq!(total.input_tokens, 30);
+        assert_eq!(total.output_tokens, 7);
+        assert_eq!(total.total_tokens, 37);
+        assert_eq!(total.cache_read_tokens, Some(8));
+        assert_eq!(total.cache_write_tokens, None);
+    }
+
     #[test]
     fn system_message_serde_roundtrip() {
         let msg = Message::system("You are a helpful assistant.");
@@ -264,6 +339,7 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 20,
                 total_tokens: 30,
+                ..Default::default()
             }),
         });
         let json = serde_json::to_string(&msg).unwrap();
@@ -278,6 +354,17 @@ mod tests {
         let deserialized: Message = serde_json::from_str(&json).unwrap();
         assert_eq!(msg, deserialized);
         assert!(json.contains(r#""type":"tool"#));
+        assert!(!json.contains("is_error"));
+    }
+
+    #[test]
+    fn tool_error_message_serde_roundtrip() {
+        let msg = Message::tool_error("division by zero", "call_1");
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains(r#""is_error":true"#));
+        let deserialized: Message = serde_json::from_str(&json).unwrap();
+        assert_eq!(msg, deserialized);
+        assert_eq!(msg.content(), "division by zero");
     }
 
     #[test]
@@ -1,11 +1,13 @@
+use std::collections::HashMap;
 use std::pin::Pin;
 
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 
-use crate::error::Result;
-use crate::message::{Message, UsageMetadata};
+use crate::config::{MODEL_OVERRIDES_KEY, RunnableConfig, StreamSender};
+use crate::error::{AyasError, ModelError, Result};
+use crate::message::{AIContent, Message, ToolCall, UsageMetadata};
 
 fn default_true() -> bool {
     true
@@ -29,6 +31,16 @@ pub enum ResponseFormat {
     },
 }
 
+/// A provider safety filter setting (category + blocking threshold).
+///
+/// Values use the provider's own identifiers, e.g. Gemini's
+/// `HARM_CATEGORY_HARASSMENT` / `BLOCK_ONLY_HIGH`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
 /// Options controlling a ChatModel invocation.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CallOptions {
@@ -40,6 +52,10 @@ pub struct CallOptions {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
 
+    /// Nucleus sampling: only tokens within this cumulative probability.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+
     /// Tool definitions available for the model to call.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tools: Vec<crate::tool::ToolDefinition>,
@@ -51,6 +67,64 @@ pub struct CallOptions {
     /// Structured output format.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub response_format: Option<ResponseFormat>,
+
+    /// Mark the prompt as cacheable (currently honored by Claude only,
+    /// via `cache_control` breakpoints).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
+
+    /// Safety filter settings (currently honored by Gemini only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety_settings: Vec<SafetySetting>,
+
+    /// Return the log probability of each output token in
+    /// [`ChatResult::logprobs`] (currently honored by OpenAI's `generate`
+    /// only; streamed calls return none).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub logprobs: bool,
+
+    /// With `logprobs`, also return this many most likely alternatives per
+    /// token (OpenAI allows 0-20).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+}
+
+impl CallOptions {
+    /// Apply the request-level overrides in `config.configurable`
+    /// (under [`MODEL_OVERRIDES_KEY`]) on top of these options.
+    ///
+    /// Overrides take precedence over the options a chain or agent was built
+    /// with. Honored keys are `temperature`, `top_p`, `max_tokens` and `stop`
+    /// (an array of strings); other keys and values of the wrong type are
+    /// ignored.
+    pub fn with_overrides(mut self, config: &RunnableConfig) -> Self {
+        let Some(overrides) = config
+            .configurable
+            .get(MODEL_OVERRIDES_KEY)
+            .and_then(|v| v.as_object())
+        else {
+            return self;
+        };
+        if let Some(t) = overrides.get("temperature").and_then(|v| v.as_f64()) {
+            self.temperature = Some(t);
+        }
+        if let Some(p) = overrides.get("top_p").and_then(|v| v.as_f64()) {
+            self.top_p = Some(p);
+        }
+        if let Some(n) = overrides
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .and_then(|n| u32::try_from(n).ok())
+        {
+            self.max_tokens = Some(n);
+        }
+        if let Some(stop) = overrides.get("stop").and_then(|v| v.as_array())
+            && stop.iter().all(|s| s.is_string())
+        {
+            self.stop = stop.iter().filter_map(|s| s.as_str().map(String::from)).collect();
+        }
+        self
+    }
 }
 
 /// Result of a chat model generation.
@@ -62,6 +136,45 @@ pub struct ChatResult {
     /// Token usage metadata.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub usage: Option<UsageMetadata>,
+
+    /// Accumulated reasoning/thinking text, for models that expose it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+
+    /// Per-token log probabilities of the output, when requested with
+    /// [`CallOptions::logprobs`] and supported by the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<Vec<TokenLogprob>>,
+}
+
+impl ChatResult {
+    /// A result carrying only `message`, with every optional field unset.
+    pub fn new(message: Message) -> Self {
+        Self {
+            message,
+            usage: None,
+            reasoning: None,
+            logprobs: None,
+        }
+    }
+}
+
+/// Log probability of one generated token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// The most likely tokens at this position, most likely first
+    /// (see [`CallOptions::top_logprobs`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+/// An alternative token considered at a position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
 }
 
 /// Events emitted during streaming model generation.
@@ -71,6 +184,8 @@ pub struct ChatResult {
 pub enum ChatStreamEvent {
     /// A text token from the model.
     Token(String),
+    /// A reasoning/thinking token, separate from the final answer.
+    Reasoning(String),
     /// Start of a tool call.
     ToolCallStart { id: String, name: String },
     /// Partial arguments for an in-progress tool call.
@@ -97,6 +212,20 @@ pub trait ChatModel: Send + Sync {
     /// Return the model name/identifier.
     fn model_name(&self) -> &str;
 
+    /// Count the input tokens `messages` would use, without generating.
+    ///
+    /// Providers count with their own endpoint or estimate locally (see
+    /// [`estimate_tokens`]); either way the figure may differ from the tokens
+    /// billed for a real call. The default fails with
+    /// [`ModelError::Unsupported`].
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        let _ = messages;
+        Err(AyasError::Model(ModelError::Unsupported(format!(
+            "{} cannot count tokens",
+            self.model_name()
+        ))))
+    }
+
     /// Stream a response token by token.
     ///
     /// Default implementation calls `generate` and wraps the result as events.
@@ -107,6 +236,9 @@ pub trait ChatModel: Send + Sync {
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
         let result = self.generate(messages, options).await?;
         let mut events: Vec<Result<ChatStreamEvent>> = Vec::new();
+        if let Some(reasoning) = result.reasoning.filter(|r| !r.is_empty()) {
+            events.push(Ok(ChatStreamEvent::Reasoning(reasoning)));
+        }
         let content = result.message.content().to_string();
         if !content.is_empty() {
             events.push(Ok(ChatStreamEvent::Token(content)));
@@ -131,11 +263,134 @@ pub trait ChatModel: Send + Sync {
     }
 }
 
+/// Tokens assumed per message for role markers and separators.
+const TOKENS_PER_MESSAGE: u64 = 3;
+
+/// Rough token count of `messages` for models without a counting endpoint.
+///
+/// Follows the usual tokenizer ratio of about four ASCII characters per
+/// token and one token per other character (CJK text, emoji), plus a small
+/// overhead per message. Images and files are not counted. Good enough for
+/// budgeting and trimming, not for billing.
+pub fn estimate_tokens(messages: &[Message]) -> u64 {
+    let text_tokens = |text: &str| {
+        let ascii = text.bytes().filter(u8::is_ascii).count() as u64;
+        let other = text.chars().filter(|c| !c.is_ascii()).count() as u64;
+        ascii.div_ceil(4) + other
+    };
+    let per_message: u64 = messages
+        .iter()
+        .map(|message| {
+            let text = match message.message_content() {
+                Some(content) => text_tokens(&content.text()),
+                None => text_tokens(message.content()),
+            };
+            let tool_calls: u64 = match message {
+                Message::AI(ai) => ai
+                    .tool_calls
+                    .iter()
+                    .map(|tc| text_tokens(&tc.name) + text_tokens(&tc.arguments.to_string()))
+                    .sum(),
+                _ => 0,
+            };
+            TOKENS_PER_MESSAGE + text + tool_calls
+        })
+        .sum();
+    // Every reply is primed with an assistant header.
+    per_message + TOKENS_PER_MESSAGE
+}
+
+/// Generate a response, streaming it through `config.stream_tx` when set.
+///
+/// Without a stream handle this is just `model.generate`. With one, the model
+/// is called through `stream`, every event is forwarded as it arrives, and
+/// the events are folded back into a [`ChatResult`]. Forwarding stops quietly
+/// if the receiver is gone.
+///
+/// The call's usage is recorded into `config.usage` when an accumulator is
+/// attached, priced as [`UsageMetadata::model`] when set (the model that
+/// actually served the call) and as `model.model_name()` otherwise.
+/// Request-level model overrides in `config` are applied to
+/// `options` first (see [`CallOptions::with_overrides`]).
+pub async fn generate_with_config(
+    model: &dyn ChatModel,
+    messages: &[Message],
+    options: &CallOptions,
+    config: &RunnableConfig,
+) -> Result<ChatResult> {
+    let options = &options.clone().with_overrides(config);
+    let result = match &config.stream_tx {
+        Some(stream_tx) => generate_streaming(model, messages, options, stream_tx).await?,
+        None => model.generate(messages, options).await?,
+    };
+    if let (Some(accumulator), Some(usage)) = (&config.usage, &result.usage) {
+        let served_by = usage.model.as_deref().unwrap_or(model.model_name());
+        accumulator.record(served_by, usage);
+    }
+    Ok(result)
+}
+
+/// Call `model` through `stream`, forwarding every event to `stream_tx`, and
+/// fold the events back into a [`ChatResult`].
+async fn generate_streaming(
+    model: &dyn ChatModel,
+    messages: &[Message],
+    options: &CallOptions,
+    stream_tx: &StreamSender,
+) -> Result<ChatResult> {
+    let mut events = model.stream(messages, options).await?;
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut tool_calls: Vec<ToolCall> = Vec::new();
+    let mut tool_args: HashMap<String, String> = HashMap::new();
+    let mut usage: Option<UsageMetadata> = None;
+
+    while let Some(event) = events.next().await {
+        let event = event?;
+        let _ = stream_tx.send(event.clone());
+        match event {
+            ChatStreamEvent::Token(t) => text.push_str(&t),
+            ChatStreamEvent::Reasoning(r) => reasoning.push_str(&r),
+            ChatStreamEvent::ToolCallStart { id, name } => {
+                tool_args.insert(id.clone(), String::new());
+                tool_calls.push(ToolCall {
+                    id,
+                    name,
+                    arguments: serde_json::Value::Null,
+                });
+            }
+            ChatStreamEvent::ToolCallDelta { id, arguments } => {
+                if let Some(buf) = tool_args.get_mut(&id) {
+                    buf.push_str(&arguments);
+                }
+            }
+            ChatStreamEvent::Usage(u) => usage = Some(u),
+            ChatStreamEvent::Done => break,
+        }
+    }
+
+    for tc in &mut tool_calls {
+        if let Some(args) = tool_args.get(&tc.id) {
+            tc.arguments = serde_json::from_str(args)
+                .unwrap_or_else(|_| serde_json::Value::String(args.clone()));
+        }
+    }
+
+    let message = Message::AI(AIContent {
+        content: text,
+        tool_calls,
+        usage: usage.clone(),
+    });
+    Ok(ChatResult {
+        usage,
+        reasoning: (!reasoning.is_empty()).then_some(reasoning),
+        ..ChatResult::new(message)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::{AIContent, ToolCall};
-    use futures::StreamExt;
 
     struct MockChatModel {
         response: String,
@@ -148,21 +403,24 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> Result<ChatResult> {
-            Ok(ChatResult {
-                message: Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: Some(UsageMetadata {
-                        input_tokens: 10,
-                        output_tokens: 5,
-                        total_tokens: 15,
-                    }),
+            let message = Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
+                usage: Some(UsageMetadata {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    total_tokens: 15,
+                    ..Default::default()
                 }),
+            });
+            Ok(ChatResult {
                 usage: Some(UsageMetadata {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 15,
+                    ..Default::default()
                 }),
+                ..ChatResult::new(message)
             })
         }
 
@@ -199,6 +457,7 @@ mod tests {
         assert!(opts.temperature.is_none());
         assert!(opts.tools.is_empty());
         assert!(opts.stop.is_empty());
+        assert!(opts.safety_settings.is_empty());
     }
 
     // -----------------------------------------------------------------------
@@ -214,6 +473,45 @@ mod tests {
         assert_eq!(event, parsed);
     }
 
+    #[test]
+    fn stream_event_reasoning_serde_roundtrip() {
+        let event = ChatStreamEvent::Reasoning("Let me think".into());
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""type":"reasoning""#));
+        let parsed: ChatStreamEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(event, parsed);
+    }
+
+    #[test]
+    fn chat_result_reasoning_omitted_when_none() {
+        let result = ChatResult::new(Message::ai("hi"));
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("reasoning"));
+        let parsed: ChatResult = serde_json::from_str(&json).unwrap();
+        assert!(parsed.reasoning.is_none());
+    }
+
+    #[test]
+    fn chat_result_logprobs_roundtrip() {
+        let result = ChatResult {
+            logprobs: Some(vec![TokenLogprob {
+                token: "Yes".into(),
+                logprob: -0.01,
+                top_logprobs: vec![TopLogprob {
+                    token: "No".into(),
+                    logprob: -4.6,
+                }],
+            }]),
+            ..ChatResult::new(Message::ai("Yes"))
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        let parsed: ChatResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.logprobs, result.logprobs);
+
+        let options = CallOptions::default();
+        assert!(!serde_json::to_string(&options).unwrap().contains("logprobs"));
+    }
+
     #[test]
     fn stream_event_tool_call_start_serde_roundtrip() {
         let event = ChatStreamEvent::ToolCallStart {
@@ -244,6 +542,7 @@ mod tests {
             input_tokens: 10,
             output_tokens: 5,
             total_tokens: 15,
+            ..Default::default()
         });
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains(r#""type":"usage""#));
@@ -310,20 +609,22 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> Result<ChatResult> {
+            let message = Message::ai_with_tool_calls(
+                "thinking",
+                vec![ToolCall {
+                    id: "call_1".into(),
+                    name: "calculator".into(),
+                    arguments: serde_json::json!({"expr": "2+2"}),
+                }],
+            );
             Ok(ChatResult {
-                message: Message::ai_with_tool_calls(
-                    "thinking",
-                    vec![ToolCall {
-                        id: "call_1".into(),
-                        name: "calculator".into(),
-                        arguments: serde_json::json!({"expr": "2+2"}),
-                    }],
-                ),
                 usage: Some(UsageMetadata {
                     input_tokens: 10,
                     output_tokens: 20,
                     total_tokens: 30,
+                    ..Default::default()
                 }),
+                ..ChatResult::new(message)
             })
         }
 
@@ -332,19 +633,89 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn generate_with_config_without_stream_tx() {
+        let config = RunnableConfig::default();
+        let result = generate_with_config(&MockToolCallModel, &[], &CallOptions::default(), &config)
+            .await
+            .unwrap();
+        assert_eq!(result.message.content(), "thinking");
+    }
+
+    #[tokio::test]
+    async fn generate_with_config_forwards_and_rebuilds() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let config = RunnableConfig::default().with_stream_tx(tx);
+        let result = generate_with_config(&MockToolCallModel, &[], &CallOptions::default(), &config)
+            .await
+            .unwrap();
+
+        let mut forwarded = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            forwarded.push(event);
+        }
+        assert_eq!(forwarded.len(), 5);
+        assert_eq!(forwarded[0], ChatStreamEvent::Token("thinking".into()));
+
+        match &result.message {
+            Message::AI(ai) => {
+                assert_eq!(ai.content, "thinking");
+                assert_eq!(ai.tool_calls.len(), 1);
+                assert_eq!(ai.tool_calls[0].arguments, serde_json::json!({"expr": "2+2"}));
+            }
+            _ => panic!("expected AI message"),
+        }
+        assert_eq!(result.usage.unwrap().total_tokens, 30);
+    }
+
+    #[tokio::test]
+    async fn generate_with_config_records_usage() {
+        let usage = crate::budget::UsageAccumulator::new();
+        let config = RunnableConfig::default().with_usage(usage.clone());
+        generate_with_config(&MockToolCallModel, &[], &CallOptions::default(), &config)
+            .await
+            .unwrap();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let config = config.with_stream_tx(tx);
+        generate_with_config(&MockToolCallModel, &[], &CallOptions::default(), &config)
+            .await
+            .unwrap();
+        assert_eq!(usage.spent().tokens, 60);
+    }
+
     #[test]
-    fn chat_result_with_tool_calls() {
-        let result = ChatResult {
-            message: Message::ai_with_tool_calls(
-                "",
-                vec![ToolCall {
-                    id: "call_1".into(),
-                    name: "search".into(),
-                    arguments: serde_json::json!({"q": "test"}),
-                }],
-            ),
-            usage: None,
+    fn call_options_request_overrides_take_precedence() {
+        let defaults = CallOptions {
+            temperature: Some(0.2),
+            max_tokens: Some(100),
+            stop: vec!["###".into()],
+            ..Default::default()
         };
+        let config = RunnableConfig::default().with_model_overrides(serde_json::json!({
+            "temperature": 0.9,
+            "max_tokens": "lots",
+            "stop": ["END"],
+            "model": "ignored"
+        }));
+        let options = defaults.clone().with_overrides(&config);
+        assert_eq!(options.temperature, Some(0.9));
+        assert_eq!(options.max_tokens, Some(100));
+        assert_eq!(options.stop, vec!["END".to_string()]);
+
+        let unchanged = defaults.with_overrides(&RunnableConfig::default());
+        assert_eq!(unchanged.temperature, Some(0.2));
+    }
+
+    #[test]
+    fn chat_result_with_tool_calls() {
+        let result = ChatResult::new(Message::ai_with_tool_calls(
+            "",
+            vec![ToolCall {
+                id: "call_1".into(),
+                name: "search".into(),
+                arguments: serde_json::json!({"q": "test"}),
+            }],
+        ));
         match &result.message {
             Message::AI(ai) => {
                 assert_eq!(ai.tool_calls.len(), 1);
@@ -353,4 +724,33 @@ mod tests {
             _ => panic!("expected AI message"),
         }
     }
+
+    #[tokio::test]
+    async fn count_tokens_is_unsupported_by_default() {
+        let model = MockChatModel {
+            response: "hi".into(),
+        };
+        let err = model.count_tokens(&[Message::user("hello")]).await.unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::Unsupported(_))));
+    }
+
+    #[test]
+    fn estimate_tokens_counts_text_and_overhead() {
+        assert_eq!(estimate_tokens(&[]), 3);
+        // 11 ASCII bytes round up to 3 tokens, plus 3 per message and 3 priming.
+        assert_eq!(estimate_tokens(&[Message::user("hello world")]), 9);
+        // Each non-ASCII character is a token of its own.
+        assert_eq!(estimate_tokens(&[Message::user("こんにちは")]), 11);
+
+        let with_call = Message::ai_with_tool_calls(
+            "",
+            vec![ToolCall {
+                id: "call_1".into(),
+                name: "search".into(),
+                arguments: serde_json::json!({"q": "test"}),
+            }],
+        );
+        // "search" (2) + `{"q":"test"}` (3)
+        assert_eq!(estimate_tokens(&[with_call]), 11);
+    }
 }
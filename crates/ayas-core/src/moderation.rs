@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Outcome of a moderation check.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModerationResult {
+    /// Whether the text violates the moderation policy.
+    pub flagged: bool,
+
+    /// The policy categories the text was flagged for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+}
+
+impl ModerationResult {
+    /// A result that lets the text through.
+    pub fn allowed() -> Self {
+        Self::default()
+    }
+
+    /// A result that flags the text for the given categories.
+    pub fn flagged(categories: Vec<String>) -> Self {
+        Self {
+            flagged: true,
+            categories,
+        }
+    }
+}
+
+/// Trait for content moderation checks.
+///
+/// Servers call a moderator on user input before it reaches a model and on
+/// model output before it is returned.
+#[async_trait]
+pub trait Moderator: Send + Sync {
+    /// Check a piece of text against the moderation policy.
+    async fn check(&self, text: &str) -> Result<ModerationResult>;
+}
+
+/// A moderator that allows all text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopModerator;
+
+#[async_trait]
+impl Moderator for NoopModerator {
+    async fn check(&self, _text: &str) -> Result<ModerationResult> {
+        Ok(ModerationResult::allowed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn noop_moderator_allows_everything() {
+        let result = NoopModerator.check("anything at all").await.unwrap();
+        assert_eq!(result, ModerationResult::allowed());
+        assert!(!result.flagged);
+    }
+
+    #[test]
+    fn flagged_result_serializes_categories() {
+        let result = ModerationResult::flagged(vec!["violence".into()]);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json, serde_json::json!({"flagged": true, "categories": ["violence"]}));
+
+        let allowed = serde_json::to_value(ModerationResult::allowed()).unwrap();
+        assert_eq!(allowed, serde_json::json!({"flagged": false}));
+    }
+}
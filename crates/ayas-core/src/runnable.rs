@@ -2,10 +2,11 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 use futures::Stream;
+use futures::future::BoxFuture;
 use serde_json::Value;
 
 use crate::config::RunnableConfig;
-use crate::error::Result;
+use crate::error::{AyasError, Result};
 
 /// Core abstraction for composable, async computation units.
 ///
@@ -68,6 +69,22 @@ pub trait RunnableExt: Runnable + Sized {
         }
     }
 
+    /// Compose with `next`, letting `self` end the sequence early.
+    ///
+    /// `self` returns a [`SequenceSignal`]: `Continue(value)` feeds `value` to
+    /// `next`, while `Stop(output)` skips `next` and returns `output` as the
+    /// sequence's result.
+    fn pipe_or_stop<R>(self, next: R) -> RunnableStoppableSequence<Self, R>
+    where
+        R: Runnable,
+        Self: Runnable<Output = SequenceSignal<R::Input, R::Output>>,
+    {
+        RunnableStoppableSequence {
+            first: self,
+            second: next,
+        }
+    }
+
     /// Wrap this Runnable with a fallback. If `self` fails, the `fallback`
     /// Runnable is invoked with the same input.
     fn with_fallback<R>(self, fallback: R) -> RunnableWithFallback<Self, R>
@@ -80,6 +97,25 @@ pub trait RunnableExt: Runnable + Sized {
             fallback,
         }
     }
+
+    /// Check this Runnable's `serde_json::Value` input and/or output against
+    /// JSON schemas. A `None` schema leaves that side unchecked.
+    ///
+    /// Fails with [`AyasError::Validation`] if either schema is itself invalid.
+    fn validate_with(
+        self,
+        input_schema: Option<Value>,
+        output_schema: Option<Value>,
+    ) -> Result<RunnableValidated<Self>>
+    where
+        Self: Runnable<Input = Value, Output = Value>,
+    {
+        Ok(RunnableValidated {
+            inner: self,
+            input: input_schema.as_ref().map(compile_schema).transpose()?,
+            output: output_schema.as_ref().map(compile_schema).transpose()?,
+        })
+    }
 }
 
 impl<T: Runnable + Sized> RunnableExt for T {}
@@ -105,6 +141,43 @@ where
     }
 }
 
+// ---------------------------------------------------------------------------
+// RunnableStoppableSequence
+// ---------------------------------------------------------------------------
+
+/// What a step in a [`RunnableStoppableSequence`] wants to happen next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceSignal<T, O> {
+    /// Pass the value on to the remaining steps.
+    Continue(T),
+    /// Skip the remaining steps and return this as the final output.
+    Stop(O),
+}
+
+/// A sequence whose first step can short-circuit the rest, e.g. a guardrail
+/// that rejects the input. Created by [`RunnableExt::pipe_or_stop`].
+pub struct RunnableStoppableSequence<A, B> {
+    pub(crate) first: A,
+    pub(crate) second: B,
+}
+
+#[async_trait]
+impl<A, B> Runnable for RunnableStoppableSequence<A, B>
+where
+    A: Runnable<Output = SequenceSignal<B::Input, B::Output>>,
+    B: Runnable,
+{
+    type Input = A::Input;
+    type Output = B::Output;
+
+    async fn invoke(&self, input: Self::Input, config: &RunnableConfig) -> Result<Self::Output> {
+        match self.first.invoke(input, config).await? {
+            SequenceSignal::Continue(value) => self.second.invoke(value, config).await,
+            SequenceSignal::Stop(output) => Ok(output),
+        }
+    }
+}
+
 /// A Runnable that passes its input through unchanged.
 pub struct IdentityRunnable<T>(std::marker::PhantomData<T>);
 
@@ -134,26 +207,108 @@ impl<T: Send + Sync + 'static> Runnable for IdentityRunnable<T> {
 // RunnableBranch
 // ---------------------------------------------------------------------------
 
+/// Predicate of a [`RunnableBranch`] arm. It may await (e.g. a classifier
+/// model call); an error aborts routing.
+pub type BranchPredicate<I> =
+    Box<dyn for<'a> Fn(&'a I) -> BoxFuture<'a, Result<bool>> + Send + Sync>;
+
+/// A [`RunnableBranch`] arm: its predicate and the Runnable it routes to.
+type BranchArm<I, O> = (BranchPredicate<I>, Box<dyn Runnable<Input = I, Output = O>>);
+
+/// A [`RunnableBranch::new`] arm: a synchronous condition and its Runnable.
+type SyncBranchArm<I, O> = (
+    Box<dyn Fn(&I) -> bool + Send + Sync>,
+    Box<dyn Runnable<Input = I, Output = O>>,
+);
+
 /// Conditional routing: evaluates conditions in order, routes input to
 /// the first matching branch, or to a default Runnable.
 pub struct RunnableBranch<I, O> {
-    branches: Vec<(
-        Box<dyn Fn(&I) -> bool + Send + Sync>,
-        Box<dyn Runnable<Input = I, Output = O>>,
-    )>,
+    branches: Vec<BranchArm<I, O>>,
     default: Box<dyn Runnable<Input = I, Output = O>>,
 }
 
-impl<I, O> RunnableBranch<I, O> {
+impl<I, O> RunnableBranch<I, O>
+where
+    I: 'static,
+{
     pub fn new(
-        branches: Vec<(
-            Box<dyn Fn(&I) -> bool + Send + Sync>,
-            Box<dyn Runnable<Input = I, Output = O>>,
-        )>,
+        branches: Vec<SyncBranchArm<I, O>>,
         default: Box<dyn Runnable<Input = I, Output = O>>,
     ) -> Self {
+        let branches = branches
+            .into_iter()
+            .map(|(condition, runnable)| (sync_predicate(condition), runnable))
+            .collect();
         Self { branches, default }
     }
+
+    /// Start building a branch arm by arm; `build` fails without a default.
+    pub fn builder() -> RunnableBranchBuilder<I, O> {
+        RunnableBranchBuilder {
+            branches: Vec::new(),
+            default: None,
+        }
+    }
+}
+
+fn sync_predicate<I: 'static>(
+    condition: Box<dyn Fn(&I) -> bool + Send + Sync>,
+) -> BranchPredicate<I> {
+    Box::new(move |input| {
+        let matched = condition(input);
+        Box::pin(async move { Ok(matched) })
+    })
+}
+
+/// Builder for a [`RunnableBranch`] mixing sync and async predicates.
+pub struct RunnableBranchBuilder<I, O> {
+    branches: Vec<BranchArm<I, O>>,
+    default: Option<Box<dyn Runnable<Input = I, Output = O>>>,
+}
+
+impl<I, O> RunnableBranchBuilder<I, O>
+where
+    I: 'static,
+{
+    /// Add an arm taken when `condition` returns true.
+    pub fn branch(
+        mut self,
+        condition: impl Fn(&I) -> bool + Send + Sync + 'static,
+        runnable: impl Runnable<Input = I, Output = O> + 'static,
+    ) -> Self {
+        self.branches
+            .push((sync_predicate(Box::new(condition)), Box::new(runnable)));
+        self
+    }
+
+    /// Add an arm taken when the awaited `condition` returns true.
+    pub fn async_branch(
+        mut self,
+        condition: impl for<'a> Fn(&'a I) -> BoxFuture<'a, Result<bool>> + Send + Sync + 'static,
+        runnable: impl Runnable<Input = I, Output = O> + 'static,
+    ) -> Self {
+        self.branches.push((Box::new(condition), Box::new(runnable)));
+        self
+    }
+
+    /// Set the arm taken when no condition matches.
+    pub fn default(mut self, runnable: impl Runnable<Input = I, Output = O> + 'static) -> Self {
+        self.default = Some(Box::new(runnable));
+        self
+    }
+
+    /// Finish the branch. Errors if no default arm was set, so every input
+    /// is guaranteed a route.
+    pub fn build(self) -> Result<RunnableBranch<I, O>> {
+        let default = self.default.ok_or_else(|| {
+            AyasError::Validation("RunnableBranch requires a default branch".into())
+        })?;
+        Ok(RunnableBranch {
+            branches: self.branches,
+            default,
+        })
+    }
 }
 
 #[async_trait]
@@ -167,7 +322,7 @@ where
 
     async fn invoke(&self, input: Self::Input, config: &RunnableConfig) -> Result<Self::Output> {
         for (condition, runnable) in &self.branches {
-            if condition(&input) {
+            if condition(&input).await? {
                 return runnable.invoke(input, config).await;
             }
         }
@@ -205,6 +360,63 @@ where
     }
 }
 
+// ---------------------------------------------------------------------------
+// RunnableValidated
+// ---------------------------------------------------------------------------
+
+/// Wraps a `Value -> Value` Runnable and rejects inputs or outputs that do not
+/// match a JSON schema. Created by [`RunnableExt::validate_with`].
+pub struct RunnableValidated<R> {
+    pub(crate) inner: R,
+    input: Option<jsonschema::Validator>,
+    output: Option<jsonschema::Validator>,
+}
+
+fn compile_schema(schema: &Value) -> Result<jsonschema::Validator> {
+    jsonschema::validator_for(schema)
+        .map_err(|e| AyasError::Validation(format!("invalid JSON schema: {e}")))
+}
+
+/// Validate `value`, listing every violation with its JSON pointer path.
+fn check_schema(validator: &jsonschema::Validator, value: &Value, side: &str) -> Result<()> {
+    let problems: Vec<String> = validator
+        .iter_errors(value)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            let path = if path.is_empty() { "/" } else { path.as_str() };
+            format!("{path}: {e}")
+        })
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(AyasError::Validation(format!(
+            "{side} does not match schema: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+#[async_trait]
+impl<R> Runnable for RunnableValidated<R>
+where
+    R: Runnable<Input = Value, Output = Value>,
+{
+    type Input = Value;
+    type Output = Value;
+
+    async fn invoke(&self, input: Self::Input, config: &RunnableConfig) -> Result<Self::Output> {
+        if let Some(validator) = &self.input {
+            check_schema(validator, &input, "input")?;
+        }
+        let output = self.inner.invoke(input, config).await?;
+        if let Some(validator) = &self.output {
+            check_schema(validator, &output, "output")?;
+        }
+        Ok(output)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // RunnablePassthrough
 // ---------------------------------------------------------------------------
@@ -359,6 +571,52 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Stops with a message on negative input, otherwise passes it on.
+    struct RejectNegative;
+
+    #[async_trait]
+    impl Runnable for RejectNegative {
+        type Input = i32;
+        type Output = SequenceSignal<i32, i32>;
+
+        async fn invoke(
+            &self,
+            input: i32,
+            _config: &RunnableConfig,
+        ) -> Result<SequenceSignal<i32, i32>> {
+            if input < 0 {
+                Ok(SequenceSignal::Stop(0))
+            } else {
+                Ok(SequenceSignal::Continue(input))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn pipe_or_stop_continues() {
+        let chain = RejectNegative.pipe_or_stop(AddOne.pipe(MultiplyTwo));
+        let config = RunnableConfig::default();
+        // (4 + 1) * 2 = 10
+        assert_eq!(chain.invoke(4, &config).await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn pipe_or_stop_skips_remaining_steps() {
+        // FailRunnable would error if it ran
+        let chain = RejectNegative.pipe_or_stop(AddOne.pipe(FailRunnable));
+        let config = RunnableConfig::default();
+        assert_eq!(chain.invoke(-3, &config).await.unwrap(), 0);
+        assert!(chain.invoke(3, &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn pipe_or_stop_after_earlier_steps() {
+        let chain = AddOne.pipe(RejectNegative).pipe_or_stop(MultiplyTwo);
+        let config = RunnableConfig::default();
+        assert_eq!(chain.invoke(-5, &config).await.unwrap(), 0);
+        assert_eq!(chain.invoke(2, &config).await.unwrap(), 6);
+    }
+
     #[tokio::test]
     async fn batch_default_implementation() {
         let r = AddOne;
@@ -440,6 +698,28 @@ mod tests {
         assert_eq!(result, 4);
     }
 
+    #[tokio::test]
+    async fn branch_builder_requires_default() {
+        let missing = RunnableBranch::<i32, i32>::builder()
+            .branch(|x: &i32| *x > 0, AddOne)
+            .build();
+        assert!(matches!(missing, Err(AyasError::Validation(_))));
+
+        let branch = RunnableBranch::builder()
+            .async_branch(
+                |x: &i32| Box::pin(async move { Ok::<_, AyasError>(*x > 10) }),
+                MultiplyTwo,
+            )
+            .branch(|x: &i32| *x > 5, AddOne)
+            .default(IdentityRunnable::<i32>::new())
+            .build()
+            .unwrap();
+        let config = RunnableConfig::default();
+        assert_eq!(branch.invoke(20, &config).await.unwrap(), 40);
+        assert_eq!(branch.invoke(7, &config).await.unwrap(), 8);
+        assert_eq!(branch.invoke(1, &config).await.unwrap(), 1);
+    }
+
     // -----------------------------------------------------------------------
     // RunnableWithFallback tests
     // -----------------------------------------------------------------------
@@ -547,4 +827,61 @@ mod tests {
         assert_eq!(result["greeting"], "Hello, Bob!");
         assert_eq!(result["upper_name"], "BOB");
     }
+
+    // -----------------------------------------------------------------------
+    // RunnableValidated tests
+    // -----------------------------------------------------------------------
+
+    fn person_schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {"name": {"type": "string"}},
+            "required": ["name"]
+        })
+    }
+
+    #[tokio::test]
+    async fn validated_accepts_matching_values() {
+        let r = ExtractName
+            .validate_with(Some(person_schema()), Some(serde_json::json!({"type": "string"})))
+            .unwrap();
+        let config = RunnableConfig::default();
+        let result = r.invoke(serde_json::json!({"name": "Alice"}), &config).await.unwrap();
+        assert_eq!(result, "Hello, Alice!");
+    }
+
+    #[tokio::test]
+    async fn validated_rejects_bad_input_with_path() {
+        let r = ExtractName.validate_with(Some(person_schema()), None).unwrap();
+        let config = RunnableConfig::default();
+        let err = r
+            .invoke(serde_json::json!({"name": 42}), &config)
+            .await
+            .unwrap_err();
+        let msg = match err {
+            AyasError::Validation(msg) => msg,
+            other => panic!("expected validation error, got {other:?}"),
+        };
+        assert!(msg.starts_with("input does not match schema"));
+        assert!(msg.contains("/name"));
+    }
+
+    #[tokio::test]
+    async fn validated_rejects_bad_output() {
+        let r = ExtractName
+            .validate_with(None, Some(serde_json::json!({"type": "object"})))
+            .unwrap();
+        let config = RunnableConfig::default();
+        let err = r
+            .invoke(serde_json::json!({"name": "Alice"}), &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Validation(ref m) if m.starts_with("output")));
+    }
+
+    #[test]
+    fn validate_with_rejects_invalid_schema() {
+        let result = ExtractName.validate_with(Some(serde_json::json!({"type": 12})), None);
+        assert!(matches!(result, Err(AyasError::Validation(_))));
+    }
 }
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::error::Result;
+use crate::error::{AyasError, Result, ToolError};
 
 /// Definition of a tool that can be called by a model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +16,130 @@ pub struct ToolDefinition {
     pub parameters: serde_json::Value,
 }
 
+impl ToolDefinition {
+    /// Check that `parameters` is a JSON Schema object providers accept: a
+    /// JSON object with `"type": "object"`, a `properties` object of schema
+    /// objects and, if present, a `required` array of strings.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |problem: String| {
+            Err(AyasError::Tool(ToolError::InvalidDefinition(format!(
+                "'{}': {problem}",
+                self.name
+            ))))
+        };
+        let Some(schema) = self.parameters.as_object() else {
+            return invalid(format!("parameters must be a JSON object, got {}", self.parameters));
+        };
+        match schema.get("type") {
+            Some(serde_json::Value::String(t)) if t == "object" => {}
+            Some(other) => {
+                return invalid(format!("parameters type must be \"object\", got {other}"));
+            }
+            None => return invalid("parameters is missing \"type\": \"object\"".into()),
+        }
+        if let Some(properties) = schema.get("properties") {
+            let Some(properties) = properties.as_object() else {
+                return invalid("parameters.properties must be an object".into());
+            };
+            if let Some((key, _)) = properties.iter().find(|(_, v)| !v.is_object()) {
+                return invalid(format!("property '{key}' must be a schema object"));
+            }
+        }
+        if let Some(required) = schema.get("required")
+            && !required.as_array().is_some_and(|r| r.iter().all(|v| v.is_string()))
+        {
+            return invalid("parameters.required must be an array of strings".into());
+        }
+        Ok(())
+    }
+}
+
+/// Coerce stringified primitives in tool-call `args` to the types declared by
+/// the tool's JSON `schema`.
+///
+/// Models sometimes emit `"5"` or `"true"` where the schema asks for an
+/// integer, number or boolean. Such strings are converted, recursing through
+/// nested object properties and array items. Values that don't parse, and
+/// everything the schema doesn't type, are passed through unchanged so the
+/// tool reports the problem itself.
+pub fn coerce_arguments(args: serde_json::Value, schema: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match (schema.get("type").and_then(Value::as_str), args) {
+        (Some("integer"), Value::String(s)) => match s.trim().parse::<i64>() {
+            Ok(n) => Value::from(n),
+            Err(_) => Value::String(s),
+        },
+        (Some("number"), Value::String(s)) => {
+            let trimmed = s.trim();
+            if let Ok(n) = trimmed.parse::<i64>() {
+                Value::from(n)
+            } else if let Some(n) = trimmed
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+            {
+                Value::Number(n)
+            } else {
+                Value::String(s)
+            }
+        }
+        (Some("boolean"), Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(s),
+        },
+        (_, Value::Object(map)) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let value = match properties.and_then(|p| p.get(&key)) {
+                            Some(prop_schema) => coerce_arguments(value, prop_schema),
+                            None => value,
+                        };
+                        (key, value)
+                    })
+                    .collect(),
+            )
+        }
+        (_, Value::Array(items)) => match schema.get("items") {
+            Some(item_schema) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| coerce_arguments(item, item_schema))
+                    .collect(),
+            ),
+            None => Value::Array(items),
+        },
+        (_, args) => args,
+    }
+}
+
+/// A tool that runs [`coerce_arguments`] against its schema on every input
+/// before calling `inner`. Coercion is opt-in: wrap the tools that need it.
+pub struct CoercingTool {
+    inner: std::sync::Arc<dyn Tool>,
+}
+
+impl CoercingTool {
+    pub fn new(inner: std::sync::Arc<dyn Tool>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Tool for CoercingTool {
+    fn definition(&self) -> ToolDefinition {
+        self.inner.definition()
+    }
+
+    async fn call(&self, input: serde_json::Value) -> Result<String> {
+        let input = coerce_arguments(input, &self.inner.definition().parameters);
+        self.inner.call(input).await
+    }
+}
+
 /// Trait for callable tools.
 ///
 /// Tools accept JSON input and return string output.
@@ -33,7 +157,6 @@ pub trait Tool: Send + Sync {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::error::{AyasError, ToolError};
 
     struct CalculatorTool;
 
@@ -83,6 +206,130 @@ mod tests {
         }
     }
 
+    fn definition(parameters: serde_json::Value) -> ToolDefinition {
+        ToolDefinition {
+            name: "lookup".into(),
+            description: "Looks things up".into(),
+            parameters,
+        }
+    }
+
+    #[test]
+    fn validate_accepts_calculator_schema() {
+        assert!(CalculatorTool.definition().validate().is_ok());
+        assert!(definition(serde_json::json!({"type": "object"})).validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_missing_type() {
+        let err = definition(serde_json::json!({"properties": {"q": {"type": "string"}}}))
+            .validate()
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Tool(ToolError::InvalidDefinition(_))));
+        let msg = err.to_string();
+        assert!(msg.contains("'lookup'") && msg.contains("missing \"type\""), "{msg}");
+    }
+
+    #[test]
+    fn validate_rejects_non_object_schema() {
+        let err = definition(serde_json::json!(["q"])).validate().unwrap_err();
+        assert!(err.to_string().contains("must be a JSON object"));
+
+        let err = definition(serde_json::json!({"type": "string"})).validate().unwrap_err();
+        assert!(err.to_string().contains("must be \"object\""));
+    }
+
+    #[test]
+    fn validate_rejects_malformed_properties() {
+        let err = definition(serde_json::json!({"type": "object", "properties": {"q": "string"}}))
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("property 'q'"));
+
+        let err = definition(serde_json::json!({"type": "object", "required": "q"}))
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("required"));
+    }
+
+    fn coercion_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer"},
+                "ratio": {"type": "number"},
+                "verbose": {"type": "boolean"},
+                "label": {"type": "string"},
+                "ids": {"type": "array", "items": {"type": "integer"}},
+                "filter": {
+                    "type": "object",
+                    "properties": {"min": {"type": "number"}}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn coerce_arguments_converts_stringified_primitives() {
+        let args = serde_json::json!({
+            "count": "5",
+            "ratio": " 0.25 ",
+            "verbose": "True",
+            "label": "7",
+            "ids": ["1", 2],
+            "filter": {"min": "3"},
+            "extra": "9"
+        });
+        assert_eq!(
+            coerce_arguments(args, &coercion_schema()),
+            serde_json::json!({
+                "count": 5,
+                "ratio": 0.25,
+                "verbose": true,
+                "label": "7",
+                "ids": [1, 2],
+                "filter": {"min": 3},
+                "extra": "9"
+            })
+        );
+    }
+
+    #[test]
+    fn coerce_arguments_passes_unparseable_values_through() {
+        let args = serde_json::json!({"count": "five", "ratio": "NaN", "verbose": "yes"});
+        assert_eq!(coerce_arguments(args.clone(), &coercion_schema()), args);
+        let raw = serde_json::json!("not an object");
+        assert_eq!(coerce_arguments(raw.clone(), &coercion_schema()), raw);
+    }
+
+    /// Echoes its input; declares `count` as an integer.
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "echo".into(),
+                description: "Echo the input".into(),
+                parameters: coercion_schema(),
+            }
+        }
+
+        async fn call(&self, input: serde_json::Value) -> Result<String> {
+            Ok(input.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn coercion_is_opt_in() {
+        let args = serde_json::json!({"count": "5"});
+        assert_eq!(EchoTool.call(args.clone()).await.unwrap(), r#"{"count":"5"}"#);
+
+        let tool = CoercingTool::new(std::sync::Arc::new(EchoTool));
+        assert_eq!(tool.definition().name, "echo");
+        assert_eq!(tool.call(args).await.unwrap(), r#"{"count":5}"#);
+    }
+
     #[tokio::test]
     async fn calculator_tool_definition() {
         let tool = CalculatorTool;
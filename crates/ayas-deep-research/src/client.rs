@@ -5,12 +5,93 @@ use async_trait::async_trait;
 use futures::Stream;
 use tracing::{info, warn};
 
-use ayas_core::error::{AyasError, Result};
+use tokio::time::Instant;
+
+use ayas_core::error::{AyasError, ModelError, Result};
 
 use crate::types::{
     CreateInteractionRequest, Interaction, InteractionStatus, StreamEvent,
 };
 
+/// Delay schedule between status polls: starts at `initial` and is
+/// multiplied by `factor` after every poll, capped at `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: f64,
+}
+
+impl PollBackoff {
+    /// A factor below 1 (or NaN) is treated as 1, and `max` is raised to
+    /// `initial` if smaller.
+    pub fn new(initial: Duration, max: Duration, factor: f64) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            factor: factor.max(1.0),
+        }
+    }
+
+    /// Poll every `interval`.
+    pub fn constant(interval: Duration) -> Self {
+        Self::new(interval, interval, 1.0)
+    }
+
+    /// Delay before poll number `poll` (zero-based).
+    pub fn delay(&self, poll: u32) -> Duration {
+        let exponent = i32::try_from(poll).unwrap_or(i32::MAX);
+        let scaled = self.initial.as_secs_f64() * self.factor.powi(exponent);
+        Duration::from_secs_f64(scaled.min(self.max.as_secs_f64()))
+    }
+}
+
+/// How to poll an interaction: the delay schedule, how many transient
+/// failures to tolerate, and optional bounds on the whole wait.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollOptions {
+    pub backoff: PollBackoff,
+    /// Consecutive transient poll failures tolerated before giving up.
+    pub max_poll_retries: u32,
+    /// Give up once this much time has passed since polling started.
+    pub timeout: Option<Duration>,
+    /// Give up after this many status polls.
+    pub max_polls: Option<usize>,
+}
+
+impl PollOptions {
+    /// Poll every `interval`, failing on the first poll error, without
+    /// bounds on the wait.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            backoff: PollBackoff::constant(interval),
+            max_poll_retries: 0,
+            timeout: None,
+            max_polls: None,
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: PollBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_max_poll_retries(mut self, retries: u32) -> Self {
+        self.max_poll_retries = retries;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_max_polls(mut self, max_polls: usize) -> Self {
+        self.max_polls = Some(max_polls);
+        self
+    }
+}
+
 /// Client trait for the Interactions API.
 #[async_trait]
 pub trait InteractionsClient: Send + Sync {
@@ -28,8 +109,65 @@ pub trait InteractionsClient: Send + Sync {
     ) -> Result<Interaction> {
         let interaction = self.create(request).await?;
         info!(id = %interaction.id, status = ?interaction.status, "Interaction created");
+        self.poll_until_done(interaction, poll_interval, 0).await
+    }
+
+    /// Re-attach to an existing interaction by id and poll until completion.
+    async fn resume(
+        &self,
+        interaction_id: &str,
+        poll_interval: Duration,
+        max_poll_retries: u32,
+    ) -> Result<Interaction> {
+        let options = PollOptions::new(poll_interval).with_max_poll_retries(max_poll_retries);
+        self.resume_with(interaction_id, options).await
+    }
+
+    /// [`resume`](Self::resume), polling as `options` prescribes.
+    async fn resume_with(&self, interaction_id: &str, options: PollOptions) -> Result<Interaction> {
+        let interaction = self.get(interaction_id).await?;
+        info!(id = %interaction.id, status = ?interaction.status, "Interaction resumed");
+        self.poll_until_done_with(interaction, options).await
+    }
+
+    /// Poll `interaction` until it completes or fails.
+    ///
+    /// A poll GET that fails with a transient error (see [`is_transient`]) is
+    /// retried on the next tick, up to `max_poll_retries` consecutive
+    /// failures; a successful poll resets the count.
+    async fn poll_until_done(
+        &self,
+        interaction: Interaction,
+        poll_interval: Duration,
+        max_poll_retries: u32,
+    ) -> Result<Interaction> {
+        let options = PollOptions::new(poll_interval).with_max_poll_retries(max_poll_retries);
+        self.poll_until_done_with(interaction, options).await
+    }
+
+    /// [`poll_until_done`](Self::poll_until_done), waiting as
+    /// `options.backoff` prescribes before each poll.
+    ///
+    /// Exceeding `options.timeout` or `options.max_polls` fails with
+    /// [`ModelError::DeepResearchTimeout`].
+    async fn poll_until_done_with(
+        &self,
+        interaction: Interaction,
+        options: PollOptions,
+    ) -> Result<Interaction> {
         let mut current = interaction;
         let mut poll_count = 0u32;
+        let mut failed_polls = 0u32;
+        let started = Instant::now();
+        let deadline = options.timeout.map(|timeout| started + timeout);
+        let timed_out = |id: &str, polls: u32| {
+            warn!(id, polls, "Interaction polling timed out");
+            AyasError::Model(ModelError::DeepResearchTimeout {
+                interaction_id: id.to_string(),
+                polls,
+                elapsed_secs: started.elapsed().as_secs(),
+            })
+        };
 
         loop {
             match current.status {
@@ -48,15 +186,39 @@ pub trait InteractionsClient: Send + Sync {
                     )));
                 }
                 InteractionStatus::InProgress => {
-                    tokio::time::sleep(poll_interval).await;
+                    if options.max_polls.is_some_and(|max| poll_count as usize >= max) {
+                        return Err(timed_out(&current.id, poll_count));
+                    }
+                    let mut delay = options.backoff.delay(poll_count);
+                    if let Some(deadline) = deadline {
+                        delay = delay.min(deadline.saturating_duration_since(Instant::now()));
+                    }
+                    tokio::time::sleep(delay).await;
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Err(timed_out(&current.id, poll_count));
+                    }
                     poll_count += 1;
-                    match self.get(&current.id).await {
+                    let polled = match deadline {
+                        Some(deadline) => {
+                            match tokio::time::timeout_at(deadline, self.get(&current.id)).await {
+                                Ok(polled) => polled,
+                                Err(_) => return Err(timed_out(&current.id, poll_count)),
+                            }
+                        }
+                        None => self.get(&current.id).await,
+                    };
+                    match polled {
                         Ok(updated) => {
                             if poll_count % 12 == 0 {
                                 info!(id = %updated.id, poll_count, status = ?updated.status, "Polling...");
                             }
+                            failed_polls = 0;
                             current = updated;
                         }
+                        Err(e) if is_transient(&e) && failed_polls < options.max_poll_retries => {
+                            failed_polls += 1;
+                            warn!(id = %current.id, poll_count, failed_polls, error = %e, "Poll GET failed, retrying");
+                        }
                         Err(e) => {
                             warn!(id = %current.id, poll_count, error = %e, "Poll GET failed");
                             return Err(e);
@@ -74,6 +236,11 @@ pub trait InteractionsClient: Send + Sync {
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>>;
 }
 
+/// Whether a poll error is worth retrying (network failures, 5xx, rate limits).
+pub fn is_transient(err: &AyasError) -> bool {
+    matches!(err, AyasError::Model(e) if e.is_retryable())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,4 +283,119 @@ mod tests {
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("something went wrong"));
     }
+
+    #[tokio::test]
+    async fn resume_polls_existing_interaction() {
+        let client = MockInteractionsClient::with_polling(2, "resumed result");
+
+        let result = client
+            .resume("mock-interaction-1", Duration::from_millis(1), 0)
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, InteractionStatus::Completed);
+        assert_eq!(result.outputs.unwrap()[0].text, "resumed result");
+    }
+
+    #[tokio::test]
+    async fn poll_retries_transient_errors() {
+        let client = MockInteractionsClient::with_polling(2, "done").with_get_errors(2);
+        let req = CreateInteractionRequest::new(
+            crate::types::InteractionInput::Text("test".into()),
+            "test-agent",
+        );
+        let created = client.create(&req).await.unwrap();
+
+        let result = client
+            .poll_until_done(created, Duration::from_millis(1), 2)
+            .await
+            .unwrap();
+        assert_eq!(result.status, InteractionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn poll_gives_up_after_retry_limit() {
+        let client = MockInteractionsClient::with_polling(2, "done").with_get_errors(3);
+        let req = CreateInteractionRequest::new(
+            crate::types::InteractionInput::Text("test".into()),
+            "test-agent",
+        );
+        let created = client.create(&req).await.unwrap();
+
+        let result = client
+            .poll_until_done(created, Duration::from_millis(1), 2)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn poll_stops_at_max_polls() {
+        let client = MockInteractionsClient::with_polling(10, "done");
+        let req = CreateInteractionRequest::new(
+            crate::types::InteractionInput::Text("test".into()),
+            "test-agent",
+        );
+        let created = client.create(&req).await.unwrap();
+        let options = PollOptions::new(Duration::from_millis(1)).with_max_polls(3);
+
+        let err = client
+            .poll_until_done_with(created, options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AyasError::Model(ModelError::DeepResearchTimeout { polls: 3, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn poll_stops_at_timeout() {
+        let client = MockInteractionsClient::with_polling(1000, "done");
+        let req = CreateInteractionRequest::new(
+            crate::types::InteractionInput::Text("test".into()),
+            "test-agent",
+        );
+        let created = client.create(&req).await.unwrap();
+        let options =
+            PollOptions::new(Duration::from_millis(5)).with_timeout(Duration::from_millis(30));
+
+        let err = client
+            .poll_until_done_with(created, options)
+            .await
+            .unwrap_err();
+        match err {
+            AyasError::Model(ModelError::DeepResearchTimeout { interaction_id, polls, .. }) => {
+                assert_eq!(interaction_id, "mock-interaction-1");
+                assert!(polls < 1000);
+            }
+            other => panic!("expected timeout, got {other}"),
+        }
+    }
+
+    #[test]
+    fn poll_backoff_grows_to_max() {
+        let backoff = PollBackoff::new(Duration::from_secs(2), Duration::from_secs(30), 2.0);
+        let delays: Vec<u64> = (0..6).map(|poll| backoff.delay(poll).as_secs()).collect();
+        assert_eq!(delays, vec![2, 4, 8, 16, 30, 30]);
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(30));
+
+        let constant = PollBackoff::constant(Duration::from_secs(5));
+        assert_eq!(constant.delay(0), constant.delay(100));
+        assert_eq!(PollBackoff::new(Duration::from_secs(5), Duration::ZERO, 0.5), constant);
+    }
+
+    #[test]
+    fn transient_error_classification() {
+        assert!(is_transient(&AyasError::Model(ModelError::http(
+            503,
+            "Service Unavailable"
+        ))));
+        assert!(is_transient(&AyasError::Model(ModelError::RateLimited {
+            retry_after_secs: None
+        })));
+        assert!(!is_transient(&AyasError::Model(ModelError::Auth(
+            "bad key".into()
+        ))));
+        assert!(!is_transient(&AyasError::Other("failed".into())));
+    }
 }
@@ -1,16 +1,41 @@
+use std::sync::Mutex;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use reqwest::StatusCode;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tracing::{info, warn};
 
 use ayas_core::error::{AyasError, ModelError, Result};
 
-use crate::types::{FileSearchStore, Operation, UploadFileResponse, UploadedFile};
+use crate::types::{
+    FileSearchDocument, FileSearchStore, ListDocumentsResponse, ListFileSearchStoresResponse,
+    Operation, UploadFileResponse, UploadedFile,
+};
 
 const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
 const DEFAULT_UPLOAD_BASE_URL: &str = "https://generativelanguage.googleapis.com/upload/v1beta";
 
+/// Resumable upload chunks must be a multiple of this size (except the last).
+const UPLOAD_CHUNK_GRANULARITY: usize = 256 * 1024;
+const DEFAULT_UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+/// Times a streamed upload is restarted after its session expires.
+const MAX_UPLOAD_RESTARTS: u32 = 2;
+
+/// Progress of a streamed upload, reported after each chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub bytes_uploaded: u64,
+    pub total_bytes: u64,
+}
+
+/// Result of pushing chunks through one resumable upload session.
+enum ChunkedUpload {
+    Done(UploadedFile),
+    /// The session URL is no longer valid; the upload must start over.
+    SessionExpired,
+}
+
 /// Client trait for the Gemini File Search Store API.
 #[async_trait]
 pub trait FileSearchClient: Send + Sync {
@@ -34,6 +59,17 @@ pub trait FileSearchClient: Send + Sync {
     /// FileSearchStores: delete a store.
     async fn delete_store(&self, store_name: &str) -> Result<()>;
 
+    /// FileSearchStores: list all stores (every page).
+    async fn list_stores(&self) -> Result<Vec<FileSearchStore>>;
+
+    /// Documents: list all documents in a store (every page).
+    async fn list_documents(&self, store_name: &str) -> Result<Vec<FileSearchDocument>>;
+
+    /// Documents: delete a document and its chunks.
+    ///
+    /// `doc_name` may be the full resource name or just the document id.
+    async fn delete_document(&self, store_name: &str, doc_name: &str) -> Result<()>;
+
     /// Operations: get operation status.
     async fn get_operation(&self, operation_name: &str) -> Result<Operation>;
 
@@ -68,6 +104,7 @@ pub struct GeminiFileSearchClient {
     api_key: String,
     base_url: String,
     upload_base_url: String,
+    upload_chunk_size: usize,
     client: reqwest::Client,
 }
 
@@ -78,6 +115,7 @@ impl GeminiFileSearchClient {
             api_key: api_key.into(),
             base_url: DEFAULT_BASE_URL.into(),
             upload_base_url: DEFAULT_UPLOAD_BASE_URL.into(),
+            upload_chunk_size: DEFAULT_UPLOAD_CHUNK_SIZE,
             client: reqwest::Client::new(),
         }
     }
@@ -92,61 +130,29 @@ impl GeminiFileSearchClient {
             api_key: api_key.into(),
             base_url: base_url.into(),
             upload_base_url: upload_base_url.into(),
+            upload_chunk_size: DEFAULT_UPLOAD_CHUNK_SIZE,
             client: reqwest::Client::new(),
         }
     }
 
-    fn files_url(&self) -> String {
-        format!("{}/files?key={}", self.upload_base_url, self.api_key)
-    }
-
-    fn stores_url(&self) -> String {
-        format!("{}/fileSearchStores?key={}", self.base_url, self.api_key)
-    }
-
-    fn store_url(&self, store_name: &str) -> String {
-        format!("{}/{}?key={}", self.base_url, store_name, self.api_key)
-    }
-
-    fn import_url(&self, store_name: &str) -> String {
-        format!(
-            "{}/{}:importFile?key={}",
-            self.base_url, store_name, self.api_key
-        )
-    }
-
-    fn operation_url(&self, operation_name: &str) -> String {
-        format!(
-            "{}/{}?key={}",
-            self.base_url, operation_name, self.api_key
-        )
-    }
-
-    fn map_status_error(status: StatusCode, body: String) -> AyasError {
-        match status.as_u16() {
-            401 | 403 => AyasError::Model(ModelError::Auth(body)),
-            429 => AyasError::Model(ModelError::RateLimited {
-                retry_after_secs: None,
-            }),
-            _ => AyasError::Model(ModelError::ApiRequest(format!(
-                "HTTP {}: {}",
-                status, body
-            ))),
-        }
+    /// Set the chunk size for [`upload_file_streamed`](Self::upload_file_streamed)
+    /// (default 8 MiB). Rounded up to a multiple of 256 KiB as the protocol
+    /// requires.
+    pub fn with_upload_chunk_size(mut self, size: usize) -> Self {
+        self.upload_chunk_size = size
+            .max(1)
+            .div_ceil(UPLOAD_CHUNK_GRANULARITY)
+            * UPLOAD_CHUNK_GRANULARITY;
+        self
     }
-}
 
-#[async_trait]
-impl FileSearchClient for GeminiFileSearchClient {
-    async fn upload_file(
+    /// Start a resumable upload session and return its upload URL.
+    async fn start_resumable_upload(
         &self,
         display_name: &str,
         mime_type: &str,
-        content: &[u8],
-    ) -> Result<UploadedFile> {
-        info!(display_name, mime_type, size = content.len(), "Uploading file (resumable)");
-
-        // Step 1: Initiate resumable upload
+        total_bytes: u64,
+    ) -> Result<String> {
         let metadata = serde_json::json!({
             "file": {
                 "displayName": display_name,
@@ -159,13 +165,13 @@ impl FileSearchClient for GeminiFileSearchClient {
             .post(self.files_url())
             .header("X-Goog-Upload-Protocol", "resumable")
             .header("X-Goog-Upload-Command", "start")
-            .header("X-Goog-Upload-Header-Content-Length", content.len())
+            .header("X-Goog-Upload-Header-Content-Length", total_bytes)
             .header("X-Goog-Upload-Header-Content-Type", mime_type)
             .header("Content-Type", "application/json")
             .body(metadata.to_string())
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = init_response.status();
         if !status.is_success() {
@@ -192,19 +198,220 @@ impl FileSearchClient for GeminiFileSearchClient {
             })?
             .to_string();
 
+        Ok(upload_url)
+    }
+
+    /// Upload `total_bytes` of data in chunks using the resumable protocol,
+    /// calling `on_progress` after each chunk.
+    ///
+    /// Only one chunk is held in memory at a time, unlike
+    /// [`FileSearchClient::upload_file`], which suits small in-memory files.
+    /// `open` returns a reader from the start of the data. If the upload
+    /// session expires mid-way (HTTP 404/410), the upload is restarted with a
+    /// new session and a reader from a fresh `open` call, up to two times.
+    pub async fn upload_file_streamed<R, O, Fut, F>(
+        &self,
+        display_name: &str,
+        mime_type: &str,
+        total_bytes: u64,
+        mut open: O,
+        on_progress: F,
+    ) -> Result<UploadedFile>
+    where
+        R: AsyncRead + Unpin + Send,
+        O: FnMut() -> Fut,
+        Fut: std::future::Future<Output = std::io::Result<R>>,
+        F: Fn(UploadProgress) + Send + Sync,
+    {
+        info!(
+            display_name,
+            mime_type,
+            size = total_bytes,
+            "Uploading file (resumable, streamed)"
+        );
+
+        let mut restarts = 0;
+        loop {
+            let mut reader = open()
+                .await
+                .map_err(|e| AyasError::Other(format!("Failed to open upload data: {e}")))?;
+            let upload_url = self
+                .start_resumable_upload(display_name, mime_type, total_bytes)
+                .await?;
+
+            match self
+                .upload_chunks(&upload_url, mime_type, &mut reader, total_bytes, &on_progress)
+                .await?
+            {
+                ChunkedUpload::Done(file) => {
+                    info!(name = %file.name, "File uploaded successfully");
+                    return Ok(file);
+                }
+                ChunkedUpload::SessionExpired if restarts < MAX_UPLOAD_RESTARTS => {
+                    restarts += 1;
+                    warn!(display_name, restarts, "Upload session expired, restarting upload");
+                }
+                ChunkedUpload::SessionExpired => {
+                    return Err(AyasError::Model(ModelError::api_request(format!(
+                        "Upload session for '{display_name}' expired {} times",
+                        restarts + 1
+                    ))));
+                }
+            }
+        }
+    }
+
+    /// Send the reader's contents to `upload_url` chunk by chunk.
+    async fn upload_chunks<R, F>(
+        &self,
+        upload_url: &str,
+        mime_type: &str,
+        reader: &mut R,
+        total_bytes: u64,
+        on_progress: &F,
+    ) -> Result<ChunkedUpload>
+    where
+        R: AsyncRead + Unpin + Send,
+        F: Fn(UploadProgress) + Send + Sync,
+    {
+        let mut buf = vec![0u8; self.upload_chunk_size];
+        let mut offset = 0u64;
+
+        loop {
+            // Data past `total_bytes` is not sent
+            let want = (total_bytes - offset).min(buf.len() as u64) as usize;
+            let n = read_chunk(reader, &mut buf[..want])
+                .await
+                .map_err(|e| AyasError::Other(format!("Failed to read upload data: {e}")))?;
+            if n == 0 && offset < total_bytes {
+                return Err(AyasError::Other(format!(
+                    "Upload data ended at {offset} of {total_bytes} bytes"
+                )));
+            }
+            let end = offset + n as u64;
+            let command = chunk_command(end, total_bytes);
+
+            let response = self
+                .client
+                .post(upload_url)
+                .header("X-Goog-Upload-Command", command)
+                .header("X-Goog-Upload-Offset", offset)
+                .header("Content-Type", mime_type)
+                .body(buf[..n].to_vec())
+                .send()
+                .await
+                .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+
+            let status = response.status();
+            if matches!(status, StatusCode::NOT_FOUND | StatusCode::GONE) {
+                return Ok(ChunkedUpload::SessionExpired);
+            }
+            if !status.is_success() {
+                let body = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "failed to read response body".into());
+                return Err(Self::map_status_error(status, body));
+            }
+
+            offset = end;
+            on_progress(UploadProgress {
+                bytes_uploaded: offset,
+                total_bytes,
+            });
+
+            if command == FINALIZE_COMMAND {
+                let resp: UploadFileResponse = response
+                    .json()
+                    .await
+                    .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+                return Ok(ChunkedUpload::Done(resp.file));
+            }
+        }
+    }
+
+    fn files_url(&self) -> String {
+        format!("{}/files?key={}", self.upload_base_url, self.api_key)
+    }
+
+    fn stores_url(&self) -> String {
+        format!("{}/fileSearchStores?key={}", self.base_url, self.api_key)
+    }
+
+    fn store_url(&self, store_name: &str) -> String {
+        format!("{}/{}?key={}", self.base_url, store_name, self.api_key)
+    }
+
+    fn documents_url(&self, store_name: &str) -> String {
+        format!(
+            "{}/{}/documents?key={}",
+            self.base_url, store_name, self.api_key
+        )
+    }
+
+    fn document_url(&self, store_name: &str, doc_name: &str) -> String {
+        let name = if doc_name.starts_with(&format!("{store_name}/")) {
+            doc_name.to_string()
+        } else {
+            format!("{store_name}/documents/{doc_name}")
+        };
+        // force: also delete the document's chunks
+        format!("{}/{}?force=true&key={}", self.base_url, name, self.api_key)
+    }
+
+    fn import_url(&self, store_name: &str) -> String {
+        format!(
+            "{}/{}:importFile?key={}",
+            self.base_url, store_name, self.api_key
+        )
+    }
+
+    fn operation_url(&self, operation_name: &str) -> String {
+        format!(
+            "{}/{}?key={}",
+            self.base_url, operation_name, self.api_key
+        )
+    }
+
+    fn map_status_error(status: StatusCode, body: String) -> AyasError {
+        match status.as_u16() {
+            401 | 403 => AyasError::Model(ModelError::Auth(body)),
+            429 => AyasError::Model(ModelError::RateLimited {
+                retry_after_secs: None,
+            }),
+            _ => AyasError::Model(ModelError::http(status.as_u16(), body)),
+        }
+    }
+}
+
+#[async_trait]
+impl FileSearchClient for GeminiFileSearchClient {
+    async fn upload_file(
+        &self,
+        display_name: &str,
+        mime_type: &str,
+        content: &[u8],
+    ) -> Result<UploadedFile> {
+        info!(display_name, mime_type, size = content.len(), "Uploading file (resumable)");
+
+        // Step 1: Initiate resumable upload
+        let upload_url = self
+            .start_resumable_upload(display_name, mime_type, content.len() as u64)
+            .await?;
+
         info!("Resumable upload URL obtained, uploading bytes");
 
         // Step 2: Upload file content
         let upload_response = self
             .client
             .post(&upload_url)
-            .header("X-Goog-Upload-Command", "upload, finalize")
+            .header("X-Goog-Upload-Command", FINALIZE_COMMAND)
             .header("X-Goog-Upload-Offset", "0")
             .header("Content-Type", mime_type)
             .body(content.to_vec())
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = upload_response.status();
         if !status.is_success() {
@@ -237,7 +444,7 @@ impl FileSearchClient for GeminiFileSearchClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -270,7 +477,7 @@ impl FileSearchClient for GeminiFileSearchClient {
             .json(&body)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -296,7 +503,7 @@ impl FileSearchClient for GeminiFileSearchClient {
             .get(self.store_url(store_name))
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -321,7 +528,7 @@ impl FileSearchClient for GeminiFileSearchClient {
             .delete(self.store_url(store_name))
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -337,13 +544,108 @@ impl FileSearchClient for GeminiFileSearchClient {
         Ok(())
     }
 
+    async fn list_stores(&self) -> Result<Vec<FileSearchStore>> {
+        let mut stores = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self.client.get(self.stores_url());
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "failed to read response body".into());
+                return Err(Self::map_status_error(status, body));
+            }
+
+            let page: ListFileSearchStoresResponse = response
+                .json()
+                .await
+                .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+            stores.extend(page.file_search_stores);
+
+            match page.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => return Ok(stores),
+            }
+        }
+    }
+
+    async fn list_documents(&self, store_name: &str) -> Result<Vec<FileSearchDocument>> {
+        let mut documents = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut request = self.client.get(self.documents_url(store_name));
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "failed to read response body".into());
+                return Err(Self::map_status_error(status, body));
+            }
+
+            let page: ListDocumentsResponse = response
+                .json()
+                .await
+                .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+            documents.extend(page.documents);
+
+            match page.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => return Ok(documents),
+            }
+        }
+    }
+
+    async fn delete_document(&self, store_name: &str, doc_name: &str) -> Result<()> {
+        info!(store = %store_name, document = %doc_name, "Deleting document");
+
+        let response = self
+            .client
+            .delete(self.document_url(store_name, doc_name))
+            .send()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "failed to read response body".into());
+            warn!(store = %store_name, document = %doc_name, %status, "Delete document failed");
+            return Err(Self::map_status_error(status, body));
+        }
+
+        Ok(())
+    }
+
     async fn get_operation(&self, operation_name: &str) -> Result<Operation> {
         let response = self
             .client
             .get(self.operation_url(operation_name))
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -361,19 +663,51 @@ impl FileSearchClient for GeminiFileSearchClient {
     }
 }
 
+const FINALIZE_COMMAND: &str = "upload, finalize";
+
+/// Upload command for the chunk ending at `end`: the last chunk finalizes.
+fn chunk_command(end: u64, total_bytes: u64) -> &'static str {
+    if end >= total_bytes {
+        FINALIZE_COMMAND
+    } else {
+        "upload"
+    }
+}
+
+/// Fill `buf` from `reader`, returning fewer bytes only at end of input.
+async fn read_chunk<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
 /// Mock implementation for testing.
+///
+/// Created stores and imported files are kept in memory so `list_stores` and
+/// `list_documents` reflect them, and deletions are recorded for tests that
+/// assert cleanup happened.
 pub struct MockFileSearchClient {
     store_name: String,
     pending_polls: std::sync::atomic::AtomicU32,
+    stores: Mutex<Vec<FileSearchStore>>,
+    documents: Mutex<Vec<FileSearchDocument>>,
+    deleted_stores: Mutex<Vec<String>>,
+    deleted_documents: Mutex<Vec<String>>,
 }
 
 impl MockFileSearchClient {
     /// Create a mock that returns the given store name and becomes ready immediately.
     pub fn ready(store_name: impl Into<String>) -> Self {
-        Self {
-            store_name: store_name.into(),
-            pending_polls: std::sync::atomic::AtomicU32::new(0),
-        }
+        Self::with_pending(store_name, 0)
     }
 
     /// Create a mock that requires `polls` get_store calls before becoming ready.
@@ -381,8 +715,48 @@ impl MockFileSearchClient {
         Self {
             store_name: store_name.into(),
             pending_polls: std::sync::atomic::AtomicU32::new(polls),
+            stores: Mutex::new(Vec::new()),
+            documents: Mutex::new(Vec::new()),
+            deleted_stores: Mutex::new(Vec::new()),
+            deleted_documents: Mutex::new(Vec::new()),
         }
     }
+
+    /// Pre-populate stores (e.g. orphans left by earlier runs), given as
+    /// `(name, display_name)` pairs.
+    pub fn with_existing_stores<'a>(
+        self,
+        stores: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        self.stores
+            .lock()
+            .unwrap()
+            .extend(stores.into_iter().map(|(name, display_name)| FileSearchStore {
+                name: name.into(),
+                display_name: display_name.into(),
+                active_documents_count: Some("0".into()),
+                pending_documents_count: Some("0".into()),
+                failed_documents_count: Some("0".into()),
+                create_time: None,
+            }));
+        self
+    }
+
+    /// Pre-populate a store given in full, e.g. with a creation time.
+    pub fn with_existing_store(self, store: FileSearchStore) -> Self {
+        self.stores.lock().unwrap().push(store);
+        self
+    }
+
+    /// Names of stores deleted so far, in order.
+    pub fn deleted_stores(&self) -> Vec<String> {
+        self.deleted_stores.lock().unwrap().clone()
+    }
+
+    /// Names of documents deleted so far, in order.
+    pub fn deleted_documents(&self) -> Vec<String> {
+        self.deleted_documents.lock().unwrap().clone()
+    }
 }
 
 #[async_trait]
@@ -402,16 +776,28 @@ impl FileSearchClient for MockFileSearchClient {
     }
 
     async fn create_store(&self, display_name: &str) -> Result<FileSearchStore> {
-        Ok(FileSearchStore {
+        let store = FileSearchStore {
             name: self.store_name.clone(),
             display_name: display_name.to_string(),
             active_documents_count: Some("0".into()),
             pending_documents_count: Some("0".into()),
             failed_documents_count: Some("0".into()),
-        })
+            create_time: None,
+        };
+        self.stores.lock().unwrap().push(store.clone());
+        Ok(store)
     }
 
-    async fn import_file(&self, _store_name: &str, _file_name: &str) -> Result<Operation> {
+    async fn import_file(&self, store_name: &str, file_name: &str) -> Result<Operation> {
+        let doc_id = file_name.trim_start_matches("files/");
+        self.documents.lock().unwrap().push(FileSearchDocument {
+            name: format!("{store_name}/documents/{doc_id}"),
+            display_name: doc_id.to_string(),
+            state: Some("STATE_ACTIVE".into()),
+            size_bytes: None,
+            mime_type: None,
+            create_time: None,
+        });
         Ok(Operation {
             name: "operations/mock-import".into(),
             done: true,
@@ -441,10 +827,45 @@ impl FileSearchClient for MockFileSearchClient {
             active_documents_count: Some("2".into()),
             pending_documents_count: pending,
             failed_documents_count: Some("0".into()),
+            create_time: None,
         })
     }
 
-    async fn delete_store(&self, _store_name: &str) -> Result<()> {
+    async fn delete_store(&self, store_name: &str) -> Result<()> {
+        self.stores.lock().unwrap().retain(|s| s.name != store_name);
+        let prefix = format!("{store_name}/");
+        self.documents
+            .lock()
+            .unwrap()
+            .retain(|d| !d.name.starts_with(&prefix));
+        self.deleted_stores.lock().unwrap().push(store_name.to_string());
+        Ok(())
+    }
+
+    async fn list_stores(&self) -> Result<Vec<FileSearchStore>> {
+        Ok(self.stores.lock().unwrap().clone())
+    }
+
+    async fn list_documents(&self, store_name: &str) -> Result<Vec<FileSearchDocument>> {
+        let prefix = format!("{store_name}/documents/");
+        Ok(self
+            .documents
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|d| d.name.starts_with(&prefix))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_document(&self, store_name: &str, doc_name: &str) -> Result<()> {
+        let name = if doc_name.starts_with(&format!("{store_name}/")) {
+            doc_name.to_string()
+        } else {
+            format!("{store_name}/documents/{doc_name}")
+        };
+        self.documents.lock().unwrap().retain(|d| d.name != name);
+        self.deleted_documents.lock().unwrap().push(name);
         Ok(())
     }
 
@@ -511,6 +932,7 @@ mod tests {
             active_documents_count: Some("2".into()),
             pending_documents_count: Some("1".into()),
             failed_documents_count: None,
+            create_time: None,
         };
         let json = serde_json::to_string(&store).unwrap();
         assert!(json.contains("displayName"));
@@ -616,6 +1038,85 @@ mod tests {
         assert_eq!(store.pending_documents_count.as_deref(), Some("0"));
     }
 
+    #[test]
+    fn upload_chunk_size_rounds_to_granularity() {
+        let client = GeminiFileSearchClient::new("key").with_upload_chunk_size(1);
+        assert_eq!(client.upload_chunk_size, UPLOAD_CHUNK_GRANULARITY);
+        let client = client.with_upload_chunk_size(UPLOAD_CHUNK_GRANULARITY * 2 + 1);
+        assert_eq!(client.upload_chunk_size, UPLOAD_CHUNK_GRANULARITY * 3);
+        assert_eq!(
+            GeminiFileSearchClient::new("key").upload_chunk_size,
+            DEFAULT_UPLOAD_CHUNK_SIZE
+        );
+    }
+
+    #[test]
+    fn chunk_command_finalizes_last_chunk() {
+        assert_eq!(chunk_command(100, 300), "upload");
+        assert_eq!(chunk_command(300, 300), FINALIZE_COMMAND);
+        // Empty files are a single finalizing request
+        assert_eq!(chunk_command(0, 0), FINALIZE_COMMAND);
+    }
+
+    #[tokio::test]
+    async fn read_chunk_fills_until_eof() {
+        let mut reader = std::io::Cursor::new(vec![7u8; 10]);
+        let mut buf = [0u8; 4];
+        assert_eq!(read_chunk(&mut reader, &mut buf).await.unwrap(), 4);
+        assert_eq!(read_chunk(&mut reader, &mut buf).await.unwrap(), 4);
+        assert_eq!(read_chunk(&mut reader, &mut buf).await.unwrap(), 2);
+        assert_eq!(read_chunk(&mut reader, &mut buf).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn document_urls() {
+        let client = GeminiFileSearchClient::new("k");
+        assert_eq!(
+            client.documents_url("fileSearchStores/abc"),
+            "https://generativelanguage.googleapis.com/v1beta/fileSearchStores/abc/documents?key=k"
+        );
+        let expected = "https://generativelanguage.googleapis.com/v1beta/fileSearchStores/abc/documents/d1?force=true&key=k";
+        assert_eq!(client.document_url("fileSearchStores/abc", "d1"), expected);
+        assert_eq!(
+            client.document_url("fileSearchStores/abc", "fileSearchStores/abc/documents/d1"),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_client_lists_and_cleans_up() {
+        let client = MockFileSearchClient::ready("fileSearchStores/new")
+            .with_existing_stores([("fileSearchStores/orphan", "pipeline-old")]);
+
+        let store = client.create_store("pipeline-new").await.unwrap();
+        client.import_file(&store.name, "files/needs").await.unwrap();
+        client.import_file(&store.name, "files/seeds").await.unwrap();
+
+        let names: Vec<String> = client
+            .list_stores()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["fileSearchStores/orphan", "fileSearchStores/new"]);
+
+        let docs = client.list_documents(&store.name).await.unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].name, "fileSearchStores/new/documents/needs");
+
+        client.delete_document(&store.name, "needs").await.unwrap();
+        assert_eq!(client.list_documents(&store.name).await.unwrap().len(), 1);
+        assert_eq!(
+            client.deleted_documents(),
+            ["fileSearchStores/new/documents/needs"]
+        );
+
+        client.delete_store("fileSearchStores/orphan").await.unwrap();
+        assert_eq!(client.list_stores().await.unwrap().len(), 1);
+        assert_eq!(client.deleted_stores(), ["fileSearchStores/orphan"]);
+    }
+
     #[tokio::test]
     async fn mock_client_delete_store() {
         let client = MockFileSearchClient::ready("fileSearchStores/mock-123");
@@ -55,10 +55,7 @@ impl GeminiInteractionsClient {
             429 => AyasError::Model(ModelError::RateLimited {
                 retry_after_secs: None,
             }),
-            _ => AyasError::Model(ModelError::ApiRequest(format!(
-                "HTTP {}: {}",
-                status, body
-            ))),
+            _ => AyasError::Model(ModelError::http(status.as_u16(), body)),
         }
     }
 }
@@ -75,7 +72,7 @@ impl InteractionsClient for GeminiInteractionsClient {
             .await
             .map_err(|e| {
                 warn!(error = %e, "Create interaction POST failed");
-                AyasError::Model(ModelError::ApiRequest(e.to_string()))
+                AyasError::Model(ModelError::api_request(e.to_string()))
             })?;
 
         let status = response.status();
@@ -101,7 +98,7 @@ impl InteractionsClient for GeminiInteractionsClient {
             .get(self.interaction_url(interaction_id))
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -132,7 +129,7 @@ impl InteractionsClient for GeminiInteractionsClient {
             .json(&stream_request)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -175,7 +172,7 @@ where
                     }
                     Some(Err(e)) => {
                         let err: Result<StreamEvent> =
-                            Err(AyasError::Model(ModelError::ApiRequest(e.to_string())));
+                            Err(AyasError::Model(ModelError::api_request(e.to_string())));
                         return Some((err, (stream, buf)));
                     }
                     None => return None,
@@ -256,7 +253,7 @@ mod tests {
             "oops".into(),
         );
         assert!(
-            matches!(err, AyasError::Model(ModelError::ApiRequest(msg)) if msg.contains("500"))
+            matches!(err, AyasError::Model(ModelError::ApiRequest { status: Some(500), .. }))
         );
     }
 
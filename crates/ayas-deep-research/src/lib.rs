@@ -2,19 +2,28 @@ pub mod client;
 pub mod file_search;
 pub mod gemini;
 pub mod mock;
+#[cfg(feature = "openai")]
+pub mod openai;
 pub mod runnable;
 pub mod types;
 
 /// Prelude module for convenient imports.
 pub mod prelude {
-    pub use crate::client::InteractionsClient;
-    pub use crate::file_search::{FileSearchClient, GeminiFileSearchClient, MockFileSearchClient};
+    pub use crate::client::{InteractionsClient, PollBackoff, PollOptions};
+    pub use crate::file_search::{
+        FileSearchClient, GeminiFileSearchClient, MockFileSearchClient, UploadProgress,
+    };
     pub use crate::gemini::GeminiInteractionsClient;
     pub use crate::mock::MockInteractionsClient;
-    pub use crate::runnable::{DeepResearchInput, DeepResearchOutput, DeepResearchRunnable};
+    #[cfg(feature = "openai")]
+    pub use crate::openai::OpenAiInteractionsClient;
+    pub use crate::runnable::{
+        DeepResearchInput, DeepResearchOutput, DeepResearchRunnable, InteractionCreatedCallback,
+    };
     pub use crate::types::{
-        AgentConfig, ContentPart, CreateInteractionRequest, FileSearchStore, Interaction,
-        InteractionInput, InteractionOutput, InteractionStatus, Operation, OperationError,
-        StreamDelta, StreamEvent, StreamEventType, ToolConfig, UploadedFile,
+        AgentConfig, Citation, ContentPart, CreateInteractionRequest, FileSearchDocument,
+        FileSearchStore, GroundingMetadata, Interaction, InteractionInput, InteractionOutput,
+        InteractionStatus, Operation, OperationError, StreamDelta, StreamEvent, StreamEventType,
+        ToolConfig, UploadedFile,
     };
 }
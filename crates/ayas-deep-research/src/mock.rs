@@ -5,34 +5,49 @@ use std::sync::Mutex;
 use async_trait::async_trait;
 use futures::Stream;
 
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, ModelError, Result};
 
 use crate::client::InteractionsClient;
 use crate::types::{
-    CreateInteractionRequest, Interaction, InteractionOutput, InteractionStatus,
-    StreamEvent,
+    CreateInteractionRequest, GroundingMetadata, Interaction, InteractionOutput,
+    InteractionStatus, StreamEvent,
 };
 
 /// Mock client for testing without HTTP.
 pub struct MockInteractionsClient {
     responses: Mutex<VecDeque<Interaction>>,
     stream_events: Mutex<Option<Vec<StreamEvent>>>,
+    get_errors: Mutex<u32>,
 }
 
 impl MockInteractionsClient {
     /// Immediately returns a completed interaction.
     pub fn completed(text: impl Into<String>) -> Self {
+        Self::completed_output(InteractionOutput {
+            text: text.into(),
+            grounding_metadata: None,
+        })
+    }
+
+    /// Immediately returns a completed interaction grounded on `metadata`.
+    pub fn completed_with_grounding(text: impl Into<String>, metadata: GroundingMetadata) -> Self {
+        Self::completed_output(InteractionOutput {
+            text: text.into(),
+            grounding_metadata: Some(metadata),
+        })
+    }
+
+    fn completed_output(output: InteractionOutput) -> Self {
         let interaction = Interaction {
             id: "mock-interaction-1".into(),
             status: InteractionStatus::Completed,
-            outputs: Some(vec![InteractionOutput {
-                text: text.into(),
-            }]),
+            outputs: Some(vec![output]),
             error: None,
         };
         Self {
             responses: Mutex::new(VecDeque::from([interaction])),
             stream_events: Mutex::new(None),
+            get_errors: Mutex::new(0),
         }
     }
 
@@ -63,13 +78,17 @@ impl MockInteractionsClient {
         responses.push_back(Interaction {
             id: "mock-interaction-1".into(),
             status: InteractionStatus::Completed,
-            outputs: Some(vec![InteractionOutput { text }]),
+            outputs: Some(vec![InteractionOutput {
+                text,
+                grounding_metadata: None,
+            }]),
             error: None,
         });
 
         Self {
             responses: Mutex::new(responses),
             stream_events: Mutex::new(None),
+            get_errors: Mutex::new(0),
         }
     }
 
@@ -84,6 +103,7 @@ impl MockInteractionsClient {
         Self {
             responses: Mutex::new(VecDeque::from([interaction])),
             stream_events: Mutex::new(None),
+            get_errors: Mutex::new(0),
         }
     }
 
@@ -92,9 +112,16 @@ impl MockInteractionsClient {
         Self {
             responses: Mutex::new(VecDeque::new()),
             stream_events: Mutex::new(Some(events)),
+            get_errors: Mutex::new(0),
         }
     }
 
+    /// Fail the next `count` `get` calls with a transient API error.
+    pub fn with_get_errors(self, count: u32) -> Self {
+        *self.get_errors.lock().unwrap() = count;
+        self
+    }
+
     fn next_response(&self) -> Interaction {
         let mut responses = self.responses.lock().unwrap();
         responses
@@ -104,6 +131,7 @@ impl MockInteractionsClient {
                 status: InteractionStatus::Completed,
                 outputs: Some(vec![InteractionOutput {
                     text: "default".into(),
+                    grounding_metadata: None,
                 }]),
                 error: None,
             })
@@ -117,6 +145,13 @@ impl InteractionsClient for MockInteractionsClient {
     }
 
     async fn get(&self, _interaction_id: &str) -> Result<Interaction> {
+        {
+            let mut errors = self.get_errors.lock().unwrap();
+            if *errors > 0 {
+                *errors -= 1;
+                return Err(AyasError::Model(ModelError::http(503, "mock")));
+            }
+        }
         Ok(self.next_response())
     }
 
@@ -210,6 +245,7 @@ mod tests {
                     status: InteractionStatus::Completed,
                     outputs: Some(vec![InteractionOutput {
                         text: "Hello World".into(),
+                        grounding_metadata: None,
                     }]),
                     error: None,
                 }),
@@ -0,0 +1,668 @@
+//! [`InteractionsClient`] over OpenAI's Responses API.
+//!
+//! Interactions map onto background responses: `create` posts a response
+//! with `background: true`, `get` retrieves it by id, and `create_stream`
+//! streams it. The agent name is used as the model (e.g.
+//! `o3-deep-research`).
+
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+use futures::stream::StreamExt;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+
+use crate::client::InteractionsClient;
+use crate::types::{
+    ContentPart, CreateInteractionRequest, GroundingChunk, GroundingMetadata, GroundingSegment,
+    GroundingSupport, Interaction, InteractionInput, InteractionOutput, InteractionStatus,
+    RetrievedContext, StreamDelta, StreamEvent, StreamEventType, ToolConfig,
+};
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// HTTP client for deep research through OpenAI's Responses API.
+pub struct OpenAiInteractionsClient {
+    api_key: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl OpenAiInteractionsClient {
+    /// Create a client with the default OpenAI API base URL.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self::with_base_url(api_key, DEFAULT_BASE_URL)
+    }
+
+    /// Create a client with a custom base URL (for testing or proxies).
+    pub fn with_base_url(api_key: impl Into<String>, base_url: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: base_url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn responses_url(&self) -> String {
+        format!("{}/responses", self.base_url)
+    }
+
+    fn response_url(&self, id: &str) -> String {
+        format!("{}/responses/{}", self.base_url, id)
+    }
+
+    fn map_status_error(status: StatusCode, body: String) -> AyasError {
+        let message = serde_json::from_str::<ApiErrorBody>(&body)
+            .map(|e| e.error.message)
+            .unwrap_or(body);
+        match status.as_u16() {
+            401 | 403 => AyasError::Model(ModelError::Auth(message)),
+            429 => AyasError::Model(ModelError::RateLimited {
+                retry_after_secs: None,
+            }),
+            _ => AyasError::Model(ModelError::http(status.as_u16(), message)),
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let response = request
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "failed to read response body".into());
+            warn!(%status, body = %body, "Responses API error");
+            return Err(Self::map_status_error(status, body));
+        }
+        Ok(response)
+    }
+
+    async fn read_response(response: reqwest::Response) -> Result<Interaction> {
+        response
+            .json::<ResponseObject>()
+            .await
+            .map(Interaction::from)
+            .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))
+    }
+}
+
+#[async_trait]
+impl InteractionsClient for OpenAiInteractionsClient {
+    async fn create(&self, request: &CreateInteractionRequest) -> Result<Interaction> {
+        info!(agent = %request.agent, background = request.background, "Creating response");
+        let body = ResponsesRequest::try_from(request)?;
+        let response = self.send(self.client.post(self.responses_url()).json(&body)).await?;
+        Self::read_response(response).await
+    }
+
+    async fn get(&self, interaction_id: &str) -> Result<Interaction> {
+        let response = self.send(self.client.get(self.response_url(interaction_id))).await?;
+        Self::read_response(response).await
+    }
+
+    async fn create_stream(
+        &self,
+        request: &CreateInteractionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>> {
+        let mut body = ResponsesRequest::try_from(request)?;
+        body.stream = Some(true);
+        let response = self.send(self.client.post(self.responses_url()).json(&body)).await?;
+        Ok(Box::pin(parse_sse_stream(response.bytes_stream())))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request mapping
+// ---------------------------------------------------------------------------
+
+/// Body of `POST /responses`.
+#[derive(Debug, Serialize)]
+pub struct ResponsesRequest {
+    pub model: String,
+    pub input: ResponsesInput,
+    pub background: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ResponsesTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<ReasoningConfig>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ResponsesInput {
+    Text(String),
+    Messages(Vec<InputMessage>),
+}
+
+#[derive(Debug, Serialize)]
+pub struct InputMessage {
+    pub role: String,
+    pub content: Vec<InputContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputContent {
+    InputText {
+        text: String,
+    },
+    InputImage {
+        image_url: String,
+    },
+    InputFile {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_url: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponsesTool {
+    /// Searches OpenAI vector stores.
+    FileSearch { vector_store_ids: Vec<String> },
+    WebSearchPreview,
+    CodeInterpreter { container: serde_json::Value },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReasoningConfig {
+    pub summary: String,
+}
+
+impl TryFrom<&CreateInteractionRequest> for ResponsesRequest {
+    type Error = AyasError;
+
+    /// Tools map onto their OpenAI counterparts, with File Search store names
+    /// taken as vector store ids. Files are sent by id when the URI is an
+    /// OpenAI file id (`file-...`) and by URL otherwise; video input is
+    /// not supported.
+    fn try_from(request: &CreateInteractionRequest) -> Result<Self> {
+        let input = match &request.input {
+            InteractionInput::Text(text) => ResponsesInput::Text(text.clone()),
+            InteractionInput::Multimodal(parts) => {
+                let content = parts.iter().map(to_input_content).collect::<Result<_>>()?;
+                ResponsesInput::Messages(vec![InputMessage {
+                    role: "user".into(),
+                    content,
+                }])
+            }
+        };
+        let tools = request
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| match tool {
+                ToolConfig::FileSearch {
+                    file_search_store_names,
+                } => ResponsesTool::FileSearch {
+                    vector_store_ids: file_search_store_names.clone(),
+                },
+                ToolConfig::WebSearch => ResponsesTool::WebSearchPreview,
+                ToolConfig::CodeExecution => ResponsesTool::CodeInterpreter {
+                    container: serde_json::json!({"type": "auto"}),
+                },
+            })
+            .collect();
+        let reasoning = request
+            .agent_config
+            .as_ref()
+            .and_then(|config| config.thinking_summaries.clone())
+            .map(|summary| ReasoningConfig { summary });
+
+        Ok(Self {
+            model: request.agent.clone(),
+            input,
+            background: request.background,
+            stream: request.stream,
+            previous_response_id: request.previous_interaction_id.clone(),
+            tools,
+            reasoning,
+        })
+    }
+}
+
+fn to_input_content(part: &ContentPart) -> Result<InputContent> {
+    Ok(match part {
+        ContentPart::Text { text } => InputContent::InputText { text: text.clone() },
+        ContentPart::Image { uri } => InputContent::InputImage {
+            image_url: uri.clone(),
+        },
+        ContentPart::File { uri } if uri.starts_with("file-") => InputContent::InputFile {
+            file_id: Some(uri.clone()),
+            file_url: None,
+        },
+        ContentPart::File { uri } => InputContent::InputFile {
+            file_id: None,
+            file_url: Some(uri.clone()),
+        },
+        ContentPart::Video { .. } => {
+            return Err(AyasError::Model(ModelError::Unsupported(
+                "OpenAI Responses API does not accept video input".into(),
+            )));
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Response mapping
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    error: ApiError,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+/// A response object as returned by `POST /responses` and
+/// `GET /responses/{id}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseObject {
+    pub id: String,
+    /// `queued`, `in_progress`, `completed`, `failed`, `cancelled` or
+    /// `incomplete`.
+    pub status: String,
+    #[serde(default)]
+    pub output: Vec<OutputItem>,
+    #[serde(default)]
+    pub error: Option<ResponseError>,
+    #[serde(default)]
+    pub incomplete_details: Option<IncompleteDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputItem {
+    Message {
+        #[serde(default)]
+        content: Vec<OutputContent>,
+    },
+    /// Reasoning, tool calls and other intermediate items.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputContent {
+    OutputText {
+        text: String,
+        #[serde(default)]
+        annotations: Vec<Annotation>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    /// A web source; indices are character offsets into the text.
+    UrlCitation {
+        url: String,
+        #[serde(default)]
+        title: String,
+        start_index: usize,
+        end_index: usize,
+    },
+    /// A file retrieved by File Search.
+    FileCitation {
+        file_id: String,
+        #[serde(default)]
+        filename: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseError {
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncompleteDetails {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl From<ResponseObject> for Interaction {
+    /// The last `message` item becomes the single output; deep research
+    /// models emit the report there, after their tool calls.
+    fn from(response: ResponseObject) -> Self {
+        let status = match response.status.as_str() {
+            "completed" => InteractionStatus::Completed,
+            "failed" | "cancelled" | "incomplete" => InteractionStatus::Failed,
+            _ => InteractionStatus::InProgress,
+        };
+        let error = match (&status, response.error, response.incomplete_details) {
+            (InteractionStatus::Failed, Some(error), _) => Some(match error.code {
+                Some(code) => format!("{code}: {}", error.message),
+                None => error.message,
+            }),
+            (InteractionStatus::Failed, None, Some(details)) => Some(format!(
+                "incomplete: {}",
+                details.reason.unwrap_or_else(|| "unknown reason".into())
+            )),
+            (InteractionStatus::Failed, None, None) => Some(response.status.clone()),
+            _ => None,
+        };
+        let outputs = response.output.iter().rev().find_map(|item| match item {
+            OutputItem::Message { content } => Some(vec![message_output(content)]),
+            OutputItem::Other => None,
+        });
+        Interaction {
+            id: response.id,
+            status,
+            outputs,
+            error,
+        }
+    }
+}
+
+/// Join a message's text parts and turn their citations into grounding
+/// metadata.
+fn message_output(content: &[OutputContent]) -> InteractionOutput {
+    let mut text = String::new();
+    let mut metadata = GroundingMetadata::default();
+    for part in content {
+        let OutputContent::OutputText {
+            text: part_text,
+            annotations,
+        } = part
+        else {
+            continue;
+        };
+        let offset = text.chars().count();
+        text.push_str(part_text);
+        for annotation in annotations {
+            let (context, segment) = match annotation {
+                Annotation::UrlCitation {
+                    url,
+                    title,
+                    start_index,
+                    end_index,
+                } => {
+                    let segment = GroundingSegment {
+                        start_index: offset + start_index,
+                        end_index: offset + end_index,
+                        text: part_text
+                            .chars()
+                            .skip(*start_index)
+                            .take(end_index.saturating_sub(*start_index))
+                            .collect(),
+                    };
+                    let context = RetrievedContext {
+                        uri: url.clone(),
+                        title: title.clone(),
+                        text: String::new(),
+                    };
+                    (context, Some(segment))
+                }
+                Annotation::FileCitation { file_id, filename } => {
+                    let context = RetrievedContext {
+                        uri: file_id.clone(),
+                        title: filename.clone(),
+                        text: String::new(),
+                    };
+                    (context, None)
+                }
+                Annotation::Other => continue,
+            };
+            metadata.grounding_supports.push(GroundingSupport {
+                segment,
+                grounding_chunk_indices: vec![metadata.grounding_chunks.len()],
+                confidence_scores: Vec::new(),
+            });
+            metadata.grounding_chunks.push(GroundingChunk {
+                retrieved_context: Some(context),
+            });
+        }
+    }
+    InteractionOutput {
+        text,
+        grounding_metadata: (!metadata.grounding_chunks.is_empty()).then_some(metadata),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming
+// ---------------------------------------------------------------------------
+
+/// One event of a streamed response.
+#[derive(Debug, Deserialize)]
+struct ResponseStreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    sequence_number: Option<u64>,
+    #[serde(default)]
+    delta: Option<String>,
+    #[serde(default)]
+    response: Option<ResponseObject>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Map one SSE `data` payload to a [`StreamEvent`]; events without a
+/// counterpart (reasoning summaries, tool progress) yield `None`.
+pub fn parse_stream_event(data: &str) -> Option<Result<StreamEvent>> {
+    if data.is_empty() || data == "[DONE]" {
+        return None;
+    }
+    let event = match serde_json::from_str::<ResponseStreamEvent>(data) {
+        Ok(event) => event,
+        Err(e) => return Some(Err(AyasError::Model(ModelError::InvalidResponse(e.to_string())))),
+    };
+    let (event_type, delta) = match event.event_type.as_str() {
+        "response.created" => (StreamEventType::InteractionStart, None),
+        "response.output_text.delta" => (StreamEventType::ContentDelta, event.delta),
+        "response.completed" | "response.failed" | "response.incomplete" => {
+            (StreamEventType::InteractionComplete, None)
+        }
+        "error" => (StreamEventType::Error, event.message),
+        _ => return None,
+    };
+    let delta_type = match event_type {
+        StreamEventType::Error => "error",
+        _ => "text",
+    };
+    Some(Ok(StreamEvent {
+        event_type,
+        event_id: event.sequence_number.map(|n| n.to_string()),
+        delta: delta.map(|text| StreamDelta {
+            delta_type: delta_type.into(),
+            text: Some(text),
+        }),
+        interaction: event.response.map(Interaction::from),
+    }))
+}
+
+type ByteStream =
+    Pin<Box<dyn Stream<Item = std::result::Result<bytes::Bytes, reqwest::Error>> + Send>>;
+
+/// Parse an SSE byte stream into StreamEvent items, skipping events that
+/// [`parse_stream_event`] ignores.
+fn parse_sse_stream<S>(byte_stream: S) -> impl Stream<Item = Result<StreamEvent>> + Send
+where
+    S: Stream<Item = std::result::Result<bytes::Bytes, reqwest::Error>> + Send + 'static,
+{
+    let pinned: ByteStream = Box::pin(byte_stream);
+    futures::stream::unfold(
+        (pinned, String::new()),
+        |(mut stream, mut buf): (ByteStream, String)| async move {
+            loop {
+                while let Some(data) = next_sse_data(&mut buf) {
+                    if let Some(event) = parse_stream_event(&data) {
+                        return Some((event, (stream, buf)));
+                    }
+                }
+                match stream.next().await {
+                    Some(Ok(bytes)) => buf.push_str(&String::from_utf8_lossy(&bytes)),
+                    Some(Err(e)) => {
+                        let err = Err(AyasError::Model(ModelError::api_request(e.to_string())));
+                        return Some((err, (stream, buf)));
+                    }
+                    None => return None,
+                }
+            }
+        },
+    )
+}
+
+/// Take the next complete SSE event from `buf` and return its `data`,
+/// or `None` until one is complete. Events without data give an empty
+/// string.
+fn next_sse_data(buf: &mut String) -> Option<String> {
+    let normalized = buf.replace("\r\n", "\n");
+    let pos = normalized.find("\n\n")?;
+    let data = normalized[..pos]
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect::<Vec<_>>()
+        .join("\n");
+    *buf = normalized[pos + 2..].to_string();
+    Some(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AgentConfig;
+
+    fn request(input: InteractionInput) -> CreateInteractionRequest {
+        CreateInteractionRequest::new(input, "o3-deep-research")
+    }
+
+    #[test]
+    fn request_maps_tools_and_options() {
+        let req = request(InteractionInput::Text("topic".into()))
+            .with_previous_interaction_id("resp_prev")
+            .with_agent_config(AgentConfig {
+                agent_type: "deep-research".into(),
+                thinking_summaries: Some("auto".into()),
+            })
+            .with_tools(vec![
+                ToolConfig::FileSearch {
+                    file_search_store_names: vec!["vs_1".into()],
+                },
+                ToolConfig::WebSearch,
+                ToolConfig::CodeExecution,
+            ]);
+
+        let json = serde_json::to_value(ResponsesRequest::try_from(&req).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "model": "o3-deep-research",
+                "input": "topic",
+                "background": true,
+                "previous_response_id": "resp_prev",
+                "tools": [
+                    {"type": "file_search", "vector_store_ids": ["vs_1"]},
+                    {"type": "web_search_preview"},
+                    {"type": "code_interpreter", "container": {"type": "auto"}}
+                ],
+                "reasoning": {"summary": "auto"}
+            })
+        );
+    }
+
+    #[test]
+    fn request_maps_multimodal_input() {
+        let req = request(InteractionInput::Multimodal(vec![
+            ContentPart::Text { text: "read".into() },
+            ContentPart::Image {
+                uri: "https://example.com/a.png".into(),
+            },
+            ContentPart::File {
+                uri: "file-abc".into(),
+            },
+            ContentPart::File {
+                uri: "https://example.com/b.pdf".into(),
+            },
+        ]));
+
+        let json = serde_json::to_value(ResponsesRequest::try_from(&req).unwrap()).unwrap();
+        assert_eq!(
+            json["input"],
+            serde_json::json!([{
+                "role": "user",
+                "content": [
+                    {"type": "input_text", "text": "read"},
+                    {"type": "input_image", "image_url": "https://example.com/a.png"},
+                    {"type": "input_file", "file_id": "file-abc"},
+                    {"type": "input_file", "file_url": "https://example.com/b.pdf"}
+                ]
+            }])
+        );
+
+        let video = request(InteractionInput::Multimodal(vec![ContentPart::Video {
+            uri: "clip.mp4".into(),
+        }]));
+        let err = ResponsesRequest::try_from(&video).unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::Unsupported(_))));
+    }
+
+    #[test]
+    fn status_mapping() {
+        let interaction = |status: &str| {
+            let response: ResponseObject =
+                serde_json::from_value(serde_json::json!({"id": "resp_1", "status": status}))
+                    .unwrap();
+            Interaction::from(response)
+        };
+        assert_eq!(interaction("queued").status, InteractionStatus::InProgress);
+        assert_eq!(interaction("in_progress").status, InteractionStatus::InProgress);
+        assert_eq!(interaction("completed").status, InteractionStatus::Completed);
+        let cancelled = interaction("cancelled");
+        assert_eq!(cancelled.status, InteractionStatus::Failed);
+        assert_eq!(cancelled.error.as_deref(), Some("cancelled"));
+    }
+
+    #[test]
+    fn map_status_error_reads_api_message() {
+        let body = r#"{"error": {"message": "Incorrect API key provided"}}"#;
+        let err = OpenAiInteractionsClient::map_status_error(StatusCode::UNAUTHORIZED, body.into());
+        assert!(matches!(err, AyasError::Model(ModelError::Auth(msg)) if msg.contains("API key")));
+
+        let err = OpenAiInteractionsClient::map_status_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "oops".into(),
+        );
+        assert!(
+            matches!(err, AyasError::Model(ModelError::ApiRequest { status: Some(500), .. }))
+        );
+    }
+
+    #[test]
+    fn next_sse_data_splits_events() {
+        let mut buf = "event: a\r\ndata: {\"x\":1}\r\n\r\n".to_string();
+        buf.push_str("event: b\ndata: {}\n\nevent: c");
+        assert_eq!(next_sse_data(&mut buf).as_deref(), Some("{\"x\":1}"));
+        assert_eq!(next_sse_data(&mut buf).as_deref(), Some("{}"));
+        assert_eq!(next_sse_data(&mut buf), None);
+        assert_eq!(buf, "event: c");
+    }
+}
@@ -8,14 +8,18 @@ use ayas_core::error::{AyasError, Result};
 use ayas_core::message::{ContentPart, ContentSource};
 use ayas_core::runnable::Runnable;
 
-use crate::client::InteractionsClient;
+use crate::client::{InteractionsClient, PollBackoff, PollOptions};
 use crate::types::{
-    AgentConfig, CreateInteractionRequest, InteractionInput, InteractionStatus,
-    ToolConfig,
+    AgentConfig, Citation, CreateInteractionRequest, Interaction, InteractionInput,
+    InteractionStatus, ToolConfig,
 };
 
 const DEFAULT_AGENT: &str = "deep-research-pro-preview-12-2025";
 const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_POLL_RETRIES: u32 = 3;
+
+/// Called with the interaction id as soon as the interaction is created.
+pub type InteractionCreatedCallback = Arc<dyn Fn(&str) + Send + Sync>;
 
 /// Input for the DeepResearchRunnable.
 #[derive(Debug, Clone)]
@@ -26,6 +30,9 @@ pub struct DeepResearchInput {
     pub agent_config: Option<AgentConfig>,
     pub tools: Option<Vec<ToolConfig>>,
     pub previous_interaction_id: Option<String>,
+    /// Re-attach to this in-progress interaction instead of creating a new
+    /// one; the other fields are ignored.
+    pub resume_interaction_id: Option<String>,
 }
 
 impl DeepResearchInput {
@@ -37,6 +44,7 @@ impl DeepResearchInput {
             agent_config: None,
             tools: None,
             previous_interaction_id: None,
+            resume_interaction_id: None,
         }
     }
 
@@ -55,6 +63,8 @@ impl DeepResearchInput {
         self
     }
 
+    /// Tools available to the agent, e.g. File Search together with web
+    /// search.
     pub fn with_tools(mut self, tools: Vec<ToolConfig>) -> Self {
         self.tools = Some(tools);
         self
@@ -64,6 +74,11 @@ impl DeepResearchInput {
         self.previous_interaction_id = Some(id.into());
         self
     }
+
+    pub fn with_resume_interaction_id(mut self, id: impl Into<String>) -> Self {
+        self.resume_interaction_id = Some(id.into());
+        self
+    }
 }
 
 /// Output from the DeepResearchRunnable.
@@ -72,6 +87,9 @@ pub struct DeepResearchOutput {
     pub interaction_id: String,
     pub text: String,
     pub status: InteractionStatus,
+    /// Reference documents the text was grounded on; empty when the
+    /// interaction carried no grounding metadata.
+    pub citations: Vec<Citation>,
 }
 
 /// Convert a core ContentPart to an Interactions API ContentPart.
@@ -99,10 +117,15 @@ fn source_to_uri(source: &ContentSource) -> String {
 }
 
 /// Runnable that wraps the Interactions API for deep research.
+///
+/// Long-running research can be made crash-safe by persisting the id passed
+/// to [`with_on_interaction_created`](Self::with_on_interaction_created) and
+/// later calling [`resume`](Self::resume) with it.
 pub struct DeepResearchRunnable {
     client: Arc<dyn InteractionsClient>,
     default_agent: String,
-    poll_interval: Duration,
+    poll: PollOptions,
+    on_interaction_created: Option<InteractionCreatedCallback>,
 }
 
 impl DeepResearchRunnable {
@@ -110,7 +133,9 @@ impl DeepResearchRunnable {
         Self {
             client,
             default_agent: DEFAULT_AGENT.into(),
-            poll_interval: DEFAULT_POLL_INTERVAL,
+            poll: PollOptions::new(DEFAULT_POLL_INTERVAL)
+                .with_max_poll_retries(DEFAULT_MAX_POLL_RETRIES),
+            on_interaction_created: None,
         }
     }
 
@@ -119,10 +144,90 @@ impl DeepResearchRunnable {
         self
     }
 
+    /// Poll every `interval` (default 5s).
     pub fn with_poll_interval(mut self, interval: Duration) -> Self {
-        self.poll_interval = interval;
+        self.poll.backoff = PollBackoff::constant(interval);
+        self
+    }
+
+    /// Poll after `initial`, then multiply the wait by `factor` after each
+    /// poll, up to `max`: responsive early on, fewer requests for
+    /// multi-minute research.
+    pub fn with_poll_backoff(mut self, initial: Duration, max: Duration, factor: f64) -> Self {
+        self.poll.backoff = PollBackoff::new(initial, max, factor);
+        self
+    }
+
+    /// Consecutive transient poll failures tolerated before giving up
+    /// (default 3).
+    pub fn with_max_poll_retries(mut self, retries: u32) -> Self {
+        self.poll.max_poll_retries = retries;
         self
     }
+
+    /// Stop polling with [`ModelError::DeepResearchTimeout`] once `timeout`
+    /// has passed since polling started (default: no limit).
+    ///
+    /// [`ModelError::DeepResearchTimeout`]: ayas_core::error::ModelError::DeepResearchTimeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.poll.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop polling with a timeout error after `max_polls` status polls
+    /// (default: no limit).
+    pub fn with_max_polls(mut self, max_polls: usize) -> Self {
+        self.poll.max_polls = Some(max_polls);
+        self
+    }
+
+    /// Register a callback that receives the interaction id right after
+    /// creation, before polling starts.
+    pub fn with_on_interaction_created(
+        mut self,
+        callback: impl Fn(&str) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_interaction_created = Some(Arc::new(callback));
+        self
+    }
+
+    /// Re-attach to an existing interaction and poll it to completion.
+    pub async fn resume(&self, interaction_id: &str) -> Result<DeepResearchOutput> {
+        tracing::info!(interaction_id, "DeepResearch resume");
+        let interaction = self
+            .client
+            .resume_with(interaction_id, self.poll)
+            .await?;
+        to_output(interaction)
+    }
+}
+
+/// Extract the first output text and its citations from a completed
+/// interaction.
+fn to_output(interaction: Interaction) -> Result<DeepResearchOutput> {
+    let output = interaction
+        .outputs
+        .as_ref()
+        .and_then(|outputs| outputs.first())
+        .ok_or_else(|| {
+            AyasError::Other(format!(
+                "Interaction {} completed but has no outputs",
+                interaction.id
+            ))
+        })?;
+    let text = output.text.clone();
+    let citations = output
+        .grounding_metadata
+        .as_ref()
+        .map(|metadata| metadata.citations())
+        .unwrap_or_default();
+
+    Ok(DeepResearchOutput {
+        interaction_id: interaction.id,
+        text,
+        status: interaction.status,
+        citations,
+    })
 }
 
 #[async_trait]
@@ -135,6 +240,10 @@ impl Runnable for DeepResearchRunnable {
         input: Self::Input,
         _config: &RunnableConfig,
     ) -> Result<Self::Output> {
+        if let Some(id) = input.resume_interaction_id {
+            return self.resume(&id).await;
+        }
+
         let agent = input.agent.unwrap_or_else(|| self.default_agent.clone());
         tracing::info!(agent = %agent, attachments = input.attachments.len(), "DeepResearch invoke start");
 
@@ -158,29 +267,17 @@ impl Runnable for DeepResearchRunnable {
             request = request.with_previous_interaction_id(prev_id);
         }
 
-        tracing::info!("Calling create_and_poll");
+        let interaction = self.client.create(&request).await?;
+        tracing::info!(id = %interaction.id, status = ?interaction.status, "Interaction created");
+        if let Some(callback) = &self.on_interaction_created {
+            callback(&interaction.id);
+        }
+
         let interaction = self
             .client
-            .create_and_poll(&request, self.poll_interval)
+            .poll_until_done_with(interaction, self.poll)
             .await?;
-
-        let text = interaction
-            .outputs
-            .as_ref()
-            .and_then(|outputs| outputs.first())
-            .map(|o| o.text.clone())
-            .ok_or_else(|| {
-                AyasError::Other(format!(
-                    "Interaction {} completed but has no outputs",
-                    interaction.id
-                ))
-            })?;
-
-        Ok(DeepResearchOutput {
-            interaction_id: interaction.id,
-            text,
-            status: interaction.status,
-        })
+        to_output(interaction)
     }
 }
 
@@ -188,6 +285,8 @@ impl Runnable for DeepResearchRunnable {
 mod tests {
     use super::*;
     use crate::mock::MockInteractionsClient;
+    use crate::types::GroundingMetadata;
+    use ayas_core::error::ModelError;
 
     #[tokio::test]
     async fn invoke_success() {
@@ -202,6 +301,39 @@ mod tests {
         assert_eq!(output.text, "Deep research result");
         assert_eq!(output.status, InteractionStatus::Completed);
         assert_eq!(output.interaction_id, "mock-interaction-1");
+        assert!(output.citations.is_empty());
+    }
+
+    #[tokio::test]
+    async fn invoke_returns_file_search_citations() {
+        let metadata: GroundingMetadata = serde_json::from_value(serde_json::json!({
+            "grounding_chunks": [
+                {"retrieved_context": {"title": "spec.pdf", "text": "Qubits hold superpositions."}}
+            ],
+            "grounding_supports": [
+                {"grounding_chunk_indices": [0], "confidence_scores": [0.8]}
+            ]
+        }))
+        .unwrap();
+        let client = Arc::new(MockInteractionsClient::completed_with_grounding(
+            "Qubits can be in superposition.",
+            metadata,
+        ));
+        let runnable = DeepResearchRunnable::new(client);
+
+        let output = runnable
+            .invoke(DeepResearchInput::new("qubits"), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            output.citations,
+            vec![Citation {
+                source: "spec.pdf".into(),
+                chunk: "Qubits hold superpositions.".into(),
+                score: Some(0.8),
+                segment: None,
+            }]
+        );
     }
 
     #[tokio::test]
@@ -239,6 +371,112 @@ mod tests {
         assert_eq!(output.status, InteractionStatus::Completed);
     }
 
+    #[tokio::test]
+    async fn invoke_with_poll_backoff() {
+        let client = Arc::new(MockInteractionsClient::with_polling(4, "backed off"));
+        let runnable = DeepResearchRunnable::new(client).with_poll_backoff(
+            Duration::from_millis(1),
+            Duration::from_millis(4),
+            2.0,
+        );
+        assert_eq!(
+            runnable.poll.backoff,
+            PollBackoff::new(Duration::from_millis(1), Duration::from_millis(4), 2.0)
+        );
+
+        let input = DeepResearchInput::new("test query");
+        let output = runnable.invoke(input, &RunnableConfig::default()).await.unwrap();
+        assert_eq!(output.text, "backed off");
+    }
+
+    #[tokio::test]
+    async fn invoke_times_out_on_stuck_interaction() {
+        let client = Arc::new(MockInteractionsClient::with_polling(1000, "never"));
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(5))
+            .with_timeout(Duration::from_millis(30));
+
+        let err = runnable
+            .invoke(DeepResearchInput::new("stuck"), &RunnableConfig::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::DeepResearchTimeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn invoke_stops_after_max_polls() {
+        let client = Arc::new(MockInteractionsClient::with_polling(5, "late"));
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_max_polls(2);
+
+        let err = runnable
+            .invoke(DeepResearchInput::new("slow"), &RunnableConfig::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out after 2 polls"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn invoke_reports_interaction_id_on_create() {
+        let client = Arc::new(MockInteractionsClient::with_polling(2, "result"));
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_on_interaction_created(move |id| sink.lock().unwrap().push(id.to_string()));
+
+        let input = DeepResearchInput::new("test query");
+        runnable.invoke(input, &RunnableConfig::default()).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!["mock-interaction-1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn invoke_resumes_existing_interaction() {
+        let client = Arc::new(MockInteractionsClient::with_polling(2, "resumed"));
+        let created = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = created.clone();
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_on_interaction_created(move |_| {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst)
+            });
+
+        let input = DeepResearchInput::new("ignored")
+            .with_resume_interaction_id("mock-interaction-1");
+        let output = runnable.invoke(input, &RunnableConfig::default()).await.unwrap();
+        assert_eq!(output.text, "resumed");
+        assert_eq!(output.interaction_id, "mock-interaction-1");
+        // Resuming never creates a new interaction
+        assert!(!created.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn invoke_survives_transient_poll_errors() {
+        let client = Arc::new(MockInteractionsClient::with_polling(2, "ok").with_get_errors(1));
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(1));
+
+        let output = runnable
+            .invoke(DeepResearchInput::new("q"), &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(output.text, "ok");
+    }
+
+    #[tokio::test]
+    async fn invoke_fails_when_retries_disabled() {
+        let client = Arc::new(MockInteractionsClient::with_polling(2, "ok").with_get_errors(1));
+        let runnable = DeepResearchRunnable::new(client)
+            .with_poll_interval(Duration::from_millis(1))
+            .with_max_poll_retries(0);
+
+        let result = runnable
+            .invoke(DeepResearchInput::new("q"), &RunnableConfig::default())
+            .await;
+        assert!(result.is_err());
+    }
+
     // --- New multimodal tests ---
 
     #[test]
@@ -36,13 +36,19 @@ pub struct AgentConfig {
     pub thinking_summaries: Option<String>,
 }
 
-/// Tool configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Tool configuration. Several tools can be combined in one request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ToolConfig {
+    /// Retrieve from uploaded documents in File Search stores.
     FileSearch {
         file_search_store_names: Vec<String>,
     },
+    /// Ground answers in live Google Search results.
+    #[serde(rename = "google_search")]
+    WebSearch,
+    /// Let the agent write and run code.
+    CodeExecution,
 }
 
 /// Request to create an interaction.
@@ -100,6 +106,117 @@ impl CreateInteractionRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InteractionOutput {
     pub text: String,
+    /// Sources the text was grounded on, present when tools such as File
+    /// Search were used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
+}
+
+/// Grounding metadata attached to an output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroundingMetadata {
+    #[serde(default)]
+    pub grounding_chunks: Vec<GroundingChunk>,
+    #[serde(default)]
+    pub grounding_supports: Vec<GroundingSupport>,
+}
+
+/// A retrieved chunk the output may cite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundingChunk {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrieved_context: Option<RetrievedContext>,
+}
+
+/// Where a grounding chunk came from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetrievedContext {
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// Links a segment of the output to the chunks supporting it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundingSupport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment: Option<GroundingSegment>,
+    /// Indices into [`GroundingMetadata::grounding_chunks`].
+    #[serde(default)]
+    pub grounding_chunk_indices: Vec<usize>,
+    /// One score per entry of `grounding_chunk_indices`.
+    #[serde(default)]
+    pub confidence_scores: Vec<f32>,
+}
+
+/// A span of the output text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroundingSegment {
+    #[serde(default)]
+    pub start_index: usize,
+    #[serde(default)]
+    pub end_index: usize,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// A reference document backing part of a research output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// File name (the chunk's title, or its URI when untitled).
+    pub source: String,
+    /// The cited chunk text.
+    pub chunk: String,
+    /// Confidence that the chunk supports the claim, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// The output text the chunk supports, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segment: Option<String>,
+}
+
+impl GroundingMetadata {
+    /// One citation per (support, chunk) pair. Without supports, every
+    /// retrieved chunk is cited once without a score.
+    pub fn citations(&self) -> Vec<Citation> {
+        let citation = |index: usize, score: Option<f32>, segment: Option<String>| {
+            let context = self.grounding_chunks.get(index)?.retrieved_context.as_ref()?;
+            let source = if context.title.is_empty() {
+                context.uri.clone()
+            } else {
+                context.title.clone()
+            };
+            Some(Citation {
+                source,
+                chunk: context.text.clone(),
+                score,
+                segment,
+            })
+        };
+
+        if self.grounding_supports.is_empty() {
+            return (0..self.grounding_chunks.len())
+                .filter_map(|i| citation(i, None, None))
+                .collect();
+        }
+        self.grounding_supports
+            .iter()
+            .flat_map(|support| {
+                let segment = support.segment.as_ref().map(|s| s.text.clone());
+                support
+                    .grounding_chunk_indices
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(j, &index)| {
+                        let score = support.confidence_scores.get(j).copied();
+                        citation(index, score, segment.clone())
+                    })
+            })
+            .collect()
+    }
 }
 
 /// Interaction response.
@@ -160,6 +277,46 @@ pub struct FileSearchStore {
     pub pending_documents_count: Option<String>,
     #[serde(default)]
     pub failed_documents_count: Option<String>,
+    #[serde(default)]
+    pub create_time: Option<String>,
+}
+
+/// A document inside a File Search Store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchDocument {
+    /// Full resource name: `fileSearchStores/{store}/documents/{document}`.
+    pub name: String,
+    #[serde(default)]
+    pub display_name: String,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub size_bytes: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub create_time: Option<String>,
+}
+
+/// One page of `fileSearchStores.list`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFileSearchStoresResponse {
+    #[serde(default)]
+    pub file_search_stores: Vec<FileSearchStore>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// One page of `fileSearchStores.documents.list`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDocumentsResponse {
+    #[serde(default)]
+    pub documents: Vec<FileSearchDocument>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
 }
 
 /// Uploaded file metadata (from Files API).
@@ -302,6 +459,32 @@ mod tests {
         assert_eq!(deserialized.agent, "deep-research-pro-preview-12-2025");
     }
 
+    #[test]
+    fn tool_config_json_per_variant() {
+        let tools = vec![
+            ToolConfig::FileSearch {
+                file_search_store_names: vec!["fileSearchStores/docs".into()],
+            },
+            ToolConfig::WebSearch,
+            ToolConfig::CodeExecution,
+        ];
+        let req = CreateInteractionRequest::new(InteractionInput::Text("topic".into()), "agent")
+            .with_tools(tools.clone());
+
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["tools"],
+            serde_json::json!([
+                {"type": "file_search", "file_search_store_names": ["fileSearchStores/docs"]},
+                {"type": "google_search"},
+                {"type": "code_execution"}
+            ])
+        );
+
+        let deserialized: CreateInteractionRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.tools, Some(tools));
+    }
+
     #[test]
     fn stream_event_serde() {
         let event = StreamEvent {
@@ -323,6 +506,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn grounding_metadata_citations() {
+        let json = r#"{
+            "text": "Revenue grew 10%.",
+            "grounding_metadata": {
+                "grounding_chunks": [
+                    {"retrieved_context": {"title": "report.pdf", "text": "Revenue: +10%"}},
+                    {"retrieved_context": {"uri": "files/notes", "text": "Growth noted"}}
+                ],
+                "grounding_supports": [{
+                    "segment": {"start_index": 0, "end_index": 17, "text": "Revenue grew 10%."},
+                    "grounding_chunk_indices": [0, 1, 7],
+                    "confidence_scores": [0.9, 0.4, 0.1]
+                }]
+            }
+        }"#;
+        let output: InteractionOutput = serde_json::from_str(json).unwrap();
+        let citations = output.grounding_metadata.unwrap().citations();
+        // Index 7 points past the chunks and is skipped.
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[0].source, "report.pdf");
+        assert_eq!(citations[0].chunk, "Revenue: +10%");
+        assert_eq!(citations[0].score, Some(0.9));
+        assert_eq!(citations[0].segment.as_deref(), Some("Revenue grew 10%."));
+        assert_eq!(citations[1].source, "files/notes");
+        assert_eq!(citations[1].score, Some(0.4));
+    }
+
+    #[test]
+    fn grounding_chunks_without_supports_are_cited_once() {
+        let metadata: GroundingMetadata = serde_json::from_str(
+            r#"{"grounding_chunks": [{"retrieved_context": {"title": "a.md", "text": "x"}}]}"#,
+        )
+        .unwrap();
+        let citations = metadata.citations();
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].score, None);
+        assert!(GroundingMetadata::default().citations().is_empty());
+    }
+
     #[test]
     fn file_search_store_json() {
         let json = r#"{
@@ -344,6 +567,46 @@ mod tests {
         assert_eq!(deserialized.name, store.name);
     }
 
+    #[test]
+    fn list_stores_response_json() {
+        let json = r#"{
+            "fileSearchStores": [
+                {"name": "fileSearchStores/a", "displayName": "pipeline-1"},
+                {"name": "fileSearchStores/b"}
+            ],
+            "nextPageToken": "tok"
+        }"#;
+        let resp: ListFileSearchStoresResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.file_search_stores.len(), 2);
+        assert_eq!(resp.file_search_stores[0].display_name, "pipeline-1");
+        assert_eq!(resp.next_page_token.as_deref(), Some("tok"));
+
+        // An account with no stores returns an empty object
+        let empty: ListFileSearchStoresResponse = serde_json::from_str("{}").unwrap();
+        assert!(empty.file_search_stores.is_empty());
+        assert!(empty.next_page_token.is_none());
+    }
+
+    #[test]
+    fn list_documents_response_json() {
+        let json = r#"{
+            "documents": [{
+                "name": "fileSearchStores/a/documents/d1",
+                "displayName": "needs.md",
+                "state": "STATE_ACTIVE",
+                "sizeBytes": "1024",
+                "mimeType": "text/markdown"
+            }]
+        }"#;
+        let resp: ListDocumentsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.documents.len(), 1);
+        let doc = &resp.documents[0];
+        assert_eq!(doc.name, "fileSearchStores/a/documents/d1");
+        assert_eq!(doc.state.as_deref(), Some("STATE_ACTIVE"));
+        assert_eq!(doc.size_bytes.as_deref(), Some("1024"));
+        assert!(resp.next_page_token.is_none());
+    }
+
     #[test]
     fn uploaded_file_json() {
         let json = r#"{
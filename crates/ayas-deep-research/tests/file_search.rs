@@ -0,0 +1,128 @@
+//! `GeminiFileSearchClient` against a local mock of the Files and File Search
+//! Store APIs.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Path, Query};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use serde_json::json;
+
+use ayas_deep_research::file_search::{FileSearchClient, GeminiFileSearchClient, UploadProgress};
+
+/// A page token that must be percent-encoded in a query string.
+const PAGE_TOKEN: &str = "a+b/c==&d";
+
+const CHUNK: usize = 256 * 1024;
+
+/// Bytes each upload session received, by session number.
+type Sessions = Arc<Mutex<HashMap<u32, Vec<u8>>>>;
+
+/// Serve resumable uploads (session 0 expires on its first chunk) and a
+/// two-page `fileSearchStores` listing. Returns the base URL and the bytes
+/// each session received.
+async fn mock_gemini() -> (String, Sessions) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+    let started = Arc::new(AtomicU32::new(0));
+
+    let session_base = base_url.clone();
+    let received = sessions.clone();
+    let app = Router::new()
+        .route(
+            "/upload/files",
+            post(move || async move {
+                let n = started.fetch_add(1, Ordering::SeqCst);
+                [("x-goog-upload-url", format!("{session_base}/session/{n}"))]
+            }),
+        )
+        .route(
+            "/session/{n}",
+            post(move |Path(n): Path<u32>, headers: HeaderMap, body: Bytes| async move {
+                if n == 0 {
+                    return StatusCode::NOT_FOUND.into_response();
+                }
+                received.lock().unwrap().entry(n).or_default().extend_from_slice(&body);
+                if headers["x-goog-upload-command"] == "upload, finalize" {
+                    let file = json!({"file": {"name": "files/abc", "displayName": "big.pdf"}});
+                    return axum::Json(file).into_response();
+                }
+                StatusCode::OK.into_response()
+            }),
+        )
+        .route(
+            "/fileSearchStores",
+            get(|Query(query): Query<HashMap<String, String>>| async move {
+                match query.get("pageToken").map(String::as_str) {
+                    None => axum::Json(json!({
+                        "fileSearchStores": [{"name": "fileSearchStores/one"}],
+                        "nextPageToken": PAGE_TOKEN
+                    }))
+                    .into_response(),
+                    Some(PAGE_TOKEN) => axum::Json(json!({
+                        "fileSearchStores": [{"name": "fileSearchStores/two"}]
+                    }))
+                    .into_response(),
+                    Some(_) => StatusCode::BAD_REQUEST.into_response(),
+                }
+            }),
+        );
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (base_url, sessions)
+}
+
+#[tokio::test]
+async fn streamed_upload_restarts_after_session_expiry() {
+    let (base_url, sessions) = mock_gemini().await;
+    let upload_url = format!("{base_url}/upload");
+    let client = GeminiFileSearchClient::with_base_urls("key", &base_url, upload_url)
+        .with_upload_chunk_size(CHUNK);
+    let data: Vec<u8> = (0..CHUNK * 2 + 10).map(|i| (i % 251) as u8).collect();
+
+    let opens = AtomicU32::new(0);
+    let progress = Mutex::new(Vec::new());
+    let file = client
+        .upload_file_streamed(
+            "big.pdf",
+            "application/pdf",
+            data.len() as u64,
+            || {
+                opens.fetch_add(1, Ordering::SeqCst);
+                let data = &data[..];
+                async move { Ok(data) }
+            },
+            |p: UploadProgress| progress.lock().unwrap().push(p.bytes_uploaded),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(file.name, "files/abc");
+    // The expired session is restarted from a fresh reader
+    assert_eq!(opens.load(Ordering::SeqCst), 2);
+    assert_eq!(sessions.lock().unwrap()[&1], data);
+    let total = data.len() as u64;
+    assert_eq!(*progress.lock().unwrap(), [CHUNK as u64, 2 * CHUNK as u64, total]);
+}
+
+#[tokio::test]
+async fn list_stores_encodes_the_page_token() {
+    let (base_url, _) = mock_gemini().await;
+    let client = GeminiFileSearchClient::with_base_urls("key", &base_url, &base_url);
+
+    let names: Vec<String> = client
+        .list_stores()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, ["fileSearchStores/one", "fileSearchStores/two"]);
+}
@@ -70,6 +70,7 @@ async fn stream_e2e() {
                 status: InteractionStatus::Completed,
                 outputs: Some(vec![InteractionOutput {
                     text: "First chunk. Second chunk.".into(),
+                    grounding_metadata: None,
                 }]),
                 error: None,
             }),
@@ -0,0 +1,161 @@
+//! `OpenAiInteractionsClient` against a local mock of the Responses API
+//! serving recorded responses from `tests/fixtures/openai`.
+
+#![cfg(feature = "openai")]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::Router;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use futures::StreamExt;
+use serde_json::Value;
+
+use ayas_core::config::RunnableConfig;
+use ayas_core::runnable::Runnable;
+use ayas_deep_research::client::InteractionsClient;
+use ayas_deep_research::openai::{OpenAiInteractionsClient, ResponseObject};
+use ayas_deep_research::runnable::{DeepResearchInput, DeepResearchRunnable};
+use ayas_deep_research::types::*;
+
+const QUEUED: &str = include_str!("fixtures/openai/response_queued.json");
+const IN_PROGRESS: &str = include_str!("fixtures/openai/response_in_progress.json");
+const COMPLETED: &str = include_str!("fixtures/openai/response_completed.json");
+const FAILED: &str = include_str!("fixtures/openai/response_failed.json");
+const STREAM: &str = include_str!("fixtures/openai/stream.txt");
+
+/// Serve `POST /responses` (the queued fixture, or the recorded stream when
+/// `stream` is set) and `GET /responses/{id}` (in progress once, then
+/// completed). Returns the base URL and the last body POSTed.
+async fn mock_openai() -> (String, Arc<Mutex<Option<Value>>>) {
+    let created = Arc::new(Mutex::new(None));
+    let recorder = created.clone();
+    let polls = Arc::new(AtomicU32::new(0));
+    let json = |body: &'static str| ([(header::CONTENT_TYPE, "application/json")], body);
+    let app = Router::new()
+        .route(
+            "/responses",
+            post(move |axum::Json(body): axum::Json<Value>| {
+                let streaming = body["stream"] == true;
+                *recorder.lock().unwrap() = Some(body);
+                async move {
+                    if streaming {
+                        ([(header::CONTENT_TYPE, "text/event-stream")], STREAM).into_response()
+                    } else {
+                        json(QUEUED).into_response()
+                    }
+                }
+            }),
+        )
+        .route(
+            "/responses/{id}",
+            get(move || async move {
+                match polls.fetch_add(1, Ordering::SeqCst) {
+                    0 => json(IN_PROGRESS),
+                    _ => json(COMPLETED),
+                }
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (format!("http://{addr}"), created)
+}
+
+#[tokio::test]
+async fn runnable_polls_background_response_to_completion() {
+    let (base_url, created) = mock_openai().await;
+    let client = Arc::new(OpenAiInteractionsClient::with_base_url("key", base_url));
+    let runnable = DeepResearchRunnable::new(client)
+        .with_agent("o3-deep-research")
+        .with_poll_interval(Duration::from_millis(1));
+
+    let input = DeepResearchInput::new("Survey solid-state batteries")
+        .with_tools(vec![ToolConfig::WebSearch]);
+    let output = runnable.invoke(input, &RunnableConfig::default()).await.unwrap();
+
+    assert_eq!(output.interaction_id, "resp_68a1f0c2e4b88190a3c1d2e3f4a5b6c7");
+    assert_eq!(output.status, InteractionStatus::Completed);
+    assert!(output.text.starts_with("# Solid-State Batteries"));
+    assert_eq!(output.citations.len(), 2);
+    assert_eq!(output.citations[0].source, "Battery Outlook 2025");
+    assert_eq!(
+        output.citations[0].segment.as_deref(),
+        Some("Pilot cells reach 400 Wh/kg.")
+    );
+    assert_eq!(output.citations[1].source, "internal_notes.pdf");
+
+    let body = created.lock().unwrap().take().unwrap();
+    assert_eq!(body["model"], "o3-deep-research");
+    assert_eq!(body["background"], true);
+    assert_eq!(body["input"], "Survey solid-state batteries");
+    assert_eq!(body["tools"][0]["type"], "web_search_preview");
+}
+
+#[tokio::test]
+async fn stream_maps_recorded_events() {
+    let (base_url, created) = mock_openai().await;
+    let client = OpenAiInteractionsClient::with_base_url("key", base_url);
+    let request = CreateInteractionRequest::new(
+        InteractionInput::Text("Survey solid-state batteries".into()),
+        "o3-deep-research",
+    );
+
+    let events: Vec<StreamEvent> = client
+        .create_stream(&request)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    // Progress and reasoning-summary events have no counterpart.
+    let types: Vec<StreamEventType> = events.iter().map(|e| e.event_type.clone()).collect();
+    assert_eq!(
+        types,
+        vec![
+            StreamEventType::InteractionStart,
+            StreamEventType::ContentDelta,
+            StreamEventType::ContentDelta,
+            StreamEventType::InteractionComplete,
+        ]
+    );
+    let text: String = events
+        .iter()
+        .filter_map(|e| e.delta.as_ref()?.text.clone())
+        .collect();
+    assert_eq!(text, "# Solid-State Batteries\n\nPilot cells reach 400 Wh/kg.");
+    let done = events[3].interaction.as_ref().unwrap();
+    assert_eq!(done.status, InteractionStatus::Completed);
+    assert_eq!(done.outputs.as_ref().unwrap()[0].text, text);
+    assert_eq!(events[0].event_id.as_deref(), Some("0"));
+
+    assert_eq!(created.lock().unwrap().take().unwrap()["stream"], true);
+}
+
+#[test]
+fn recorded_responses_map_to_interactions() {
+    let parse = |json: &str| {
+        let response: ResponseObject = serde_json::from_str(json).unwrap();
+        Interaction::from(response)
+    };
+
+    let queued = parse(QUEUED);
+    assert_eq!(queued.status, InteractionStatus::InProgress);
+    assert!(queued.outputs.is_none());
+
+    // Reasoning and tool-call items are not outputs.
+    assert!(parse(IN_PROGRESS).outputs.is_none());
+
+    let failed = parse(FAILED);
+    assert_eq!(failed.status, InteractionStatus::Failed);
+    assert_eq!(
+        failed.error.as_deref(),
+        Some("server_error: The model failed to generate a response.")
+    );
+}
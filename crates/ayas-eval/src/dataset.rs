@@ -1,6 +1,10 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use ayas_core::error::{AyasError, Result as AyasResult};
+
 /// A single evaluation example with input and optional expected output.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Example {
@@ -64,6 +68,262 @@ impl Dataset {
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
+
+    /// Load from a JSON Lines file (one `Example` object per line).
+    ///
+    /// Blank lines are skipped. Examples without an `id` get their 1-based
+    /// line number. The dataset is named after the file stem.
+    pub fn from_jsonl(path: impl AsRef<Path>) -> AyasResult<Self> {
+        let path = path.as_ref();
+        let content = read_file(path)?;
+        Self::from_jsonl_str(file_stem(path), &content)
+    }
+
+    /// Parse JSON Lines content; see [`Dataset::from_jsonl`].
+    pub fn from_jsonl_str(name: impl Into<String>, content: &str) -> AyasResult<Self> {
+        let mut dataset = Self::new(name);
+        for (idx, line) in content.lines().enumerate() {
+            let line_no = idx + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut value: Value = serde_json::from_str(line)
+                .map_err(|e| AyasError::Other(format!("line {line_no}: invalid JSON: {e}")))?;
+            if let Value::Object(map) = &mut value {
+                map.entry("id").or_insert_with(|| Value::String(line_no.to_string()));
+            }
+            let example: Example = serde_json::from_value(value)
+                .map_err(|e| AyasError::Other(format!("line {line_no}: invalid example: {e}")))?;
+            dataset.add_example(example);
+        }
+        Ok(dataset)
+    }
+
+    /// Write the examples as JSON Lines (one `Example` per line).
+    pub fn to_jsonl(&self, path: impl AsRef<Path>) -> AyasResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_jsonl_string()?)
+            .map_err(|e| AyasError::Other(format!("failed to write {}: {e}", path.display())))
+    }
+
+    /// Serialize the examples as JSON Lines.
+    pub fn to_jsonl_string(&self) -> AyasResult<String> {
+        let mut out = String::new();
+        for example in &self.examples {
+            out.push_str(&serde_json::to_string(example)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// Load a comma-separated file with a header row.
+    ///
+    /// See [`Dataset::from_csv_with_delimiter`].
+    pub fn from_csv(path: impl AsRef<Path>, input_col: &str, output_col: &str) -> AyasResult<Self> {
+        Self::from_csv_with_delimiter(path, input_col, output_col, ',')
+    }
+
+    /// Load a delimited file with a header row.
+    ///
+    /// `input_col` becomes `Example::input` and `output_col` becomes
+    /// `Example::expected` (omitted when the cell is empty). An `id` column is
+    /// used as the example ID if present, otherwise the 1-based line number.
+    /// All other columns are stored in `metadata` as strings. Fields may be
+    /// quoted with `"` (embedded delimiters, newlines and `""` escapes are
+    /// supported). Rows with the wrong number of fields or an unterminated
+    /// quote are reported with their line number.
+    pub fn from_csv_with_delimiter(
+        path: impl AsRef<Path>,
+        input_col: &str,
+        output_col: &str,
+        delimiter: char,
+    ) -> AyasResult<Self> {
+        let path = path.as_ref();
+        let content = read_file(path)?;
+        Self::from_csv_str(file_stem(path), &content, input_col, output_col, delimiter)
+    }
+
+    /// Parse delimited content; see [`Dataset::from_csv_with_delimiter`].
+    pub fn from_csv_str(
+        name: impl Into<String>,
+        content: &str,
+        input_col: &str,
+        output_col: &str,
+        delimiter: char,
+    ) -> AyasResult<Self> {
+        let mut records = parse_delimited(content, delimiter)?.into_iter();
+        let (_, header) = records
+            .next()
+            .ok_or_else(|| AyasError::Other("CSV is empty: missing header row".into()))?;
+        let column = |col: &str| {
+            header.iter().position(|h| h == col).ok_or_else(|| {
+                AyasError::Other(format!("line 1: column '{col}' not found in header"))
+            })
+        };
+        let input_idx = column(input_col)?;
+        let output_idx = column(output_col)?;
+        let id_idx = header.iter().position(|h| h == "id");
+
+        let mut dataset = Self::new(name);
+        for (line_no, fields) in records {
+            if fields.len() != header.len() {
+                return Err(AyasError::Other(format!(
+                    "line {line_no}: expected {} fields, found {}",
+                    header.len(),
+                    fields.len()
+                )));
+            }
+            let metadata = header
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != input_idx && *i != output_idx && Some(*i) != id_idx)
+                .map(|(i, h)| (h.clone(), Value::String(fields[i].clone())))
+                .collect();
+            let output = &fields[output_idx];
+            dataset.add_example(Example {
+                id: id_idx
+                    .map(|i| fields[i].clone())
+                    .unwrap_or_else(|| line_no.to_string()),
+                input: Value::String(fields[input_idx].clone()),
+                expected: (!output.is_empty()).then(|| Value::String(output.clone())),
+                metadata,
+            });
+        }
+        Ok(dataset)
+    }
+
+    /// Shuffle with `seed` and split into `(train, dev)` datasets.
+    ///
+    /// `ratio` (clamped to `[0.0, 1.0]`) is the fraction of examples placed in
+    /// the first dataset, rounded to the nearest example. The same seed always
+    /// produces the same split.
+    pub fn split(&self, ratio: f64, seed: u64) -> (Dataset, Dataset) {
+        let mut examples = self.examples.clone();
+        shuffle(&mut examples, seed);
+        let first_len = (examples.len() as f64 * ratio.clamp(0.0, 1.0)).round() as usize;
+        let second = examples.split_off(first_len);
+        (
+            self.derive(format!("{}-train", self.name), examples),
+            self.derive(format!("{}-dev", self.name), second),
+        )
+    }
+
+    /// Deterministically sample up to `n` examples using `seed`.
+    pub fn sample(&self, n: usize, seed: u64) -> Dataset {
+        let mut examples = self.examples.clone();
+        shuffle(&mut examples, seed);
+        examples.truncate(n);
+        self.derive(self.name.clone(), examples)
+    }
+
+    /// Keep only the examples matching `predicate`, preserving order.
+    pub fn filter(&self, predicate: impl Fn(&Example) -> bool) -> Dataset {
+        let examples = self
+            .examples
+            .iter()
+            .filter(|e| predicate(e))
+            .cloned()
+            .collect();
+        self.derive(self.name.clone(), examples)
+    }
+
+    fn derive(&self, name: String, examples: Vec<Example>) -> Dataset {
+        Dataset {
+            name,
+            description: self.description.clone(),
+            examples,
+        }
+    }
+}
+
+fn read_file(path: &Path) -> AyasResult<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| AyasError::Other(format!("failed to read {}: {e}", path.display())))
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Split delimited text into records of fields, each tagged with the 1-based
+/// line number it starts on. Blank lines are skipped.
+fn parse_delimited(content: &str, delimiter: char) -> AyasResult<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line_no = 1;
+    let mut record_start = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line_no += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '"' => {
+                return Err(AyasError::Other(format!(
+                    "line {line_no}: unexpected quote in unquoted field"
+                )));
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                if !(fields.len() == 1 && fields[0].is_empty()) {
+                    records.push((record_start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line_no += 1;
+                record_start = line_no;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(AyasError::Other(format!(
+            "line {record_start}: unterminated quoted field"
+        )));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_start, fields));
+    }
+    Ok(records)
+}
+
+/// Seeded Fisher–Yates shuffle.
+///
+/// Uses SplitMix64 so results are stable across platforms and releases.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
 }
 
 #[cfg(test)]
@@ -139,6 +399,166 @@ mod tests {
         assert_eq!(ds.examples[0].id, "1");
     }
 
+    fn numbered(n: usize) -> Dataset {
+        let mut ds = Dataset::new("numbered").with_description("numbers");
+        for i in 0..n {
+            ds.add_example(sample_example(&format!("ex{i}")));
+        }
+        ds
+    }
+
+    fn ids(ds: &Dataset) -> Vec<String> {
+        ds.examples.iter().map(|e| e.id.clone()).collect()
+    }
+
+    #[test]
+    fn split_sizes_and_disjoint() {
+        let ds = numbered(10);
+        let (train, dev) = ds.split(0.8, 42);
+        assert_eq!(train.len(), 8);
+        assert_eq!(dev.len(), 2);
+        assert_eq!(train.name, "numbered-train");
+        assert_eq!(dev.name, "numbered-dev");
+        assert_eq!(train.description, "numbers");
+
+        let train_ids = ids(&train);
+        assert!(ids(&dev).iter().all(|id| !train_ids.contains(id)));
+
+        let mut all: Vec<String> = train_ids.into_iter().chain(ids(&dev)).collect();
+        all.sort();
+        let mut expected = ids(&ds);
+        expected.sort();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn split_is_deterministic() {
+        let ds = numbered(20);
+        let (a, _) = ds.split(0.5, 7);
+        let (b, _) = ds.split(0.5, 7);
+        let (c, _) = ds.split(0.5, 8);
+        assert_eq!(ids(&a), ids(&b));
+        assert_ne!(ids(&a), ids(&c));
+    }
+
+    #[test]
+    fn split_extremes() {
+        let ds = numbered(5);
+        let (all, none) = ds.split(1.0, 1);
+        assert_eq!((all.len(), none.len()), (5, 0));
+        let (none, all) = ds.split(-0.5, 1);
+        assert_eq!((none.len(), all.len()), (0, 5));
+    }
+
+    #[test]
+    fn sample_is_deterministic_and_bounded() {
+        let ds = numbered(10);
+        let s1 = ds.sample(3, 99);
+        let s2 = ds.sample(3, 99);
+        assert_eq!(s1.len(), 3);
+        assert_eq!(ids(&s1), ids(&s2));
+        assert_eq!(ds.sample(50, 99).len(), 10);
+
+        let mut unique = ids(&s1);
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn filter_preserves_order() {
+        let ds = numbered(6);
+        let even = ds.filter(|e| e.id.trim_start_matches("ex").parse::<u32>().unwrap() % 2 == 0);
+        assert_eq!(ids(&even), ["ex0", "ex2", "ex4"]);
+        assert_eq!(even.name, "numbered");
+    }
+
+    #[test]
+    fn jsonl_parse_and_default_ids() {
+        let content = r#"{"id": "a", "input": "hi", "expected": "hello"}
+
+{"input": {"q": 1}}
+"#;
+        let ds = Dataset::from_jsonl_str("jl", content).unwrap();
+        assert_eq!(ds.len(), 2);
+        assert_eq!(ds.examples[0].id, "a");
+        assert_eq!(ds.examples[0].expected, Some(json!("hello")));
+        assert_eq!(ds.examples[1].id, "3");
+        assert_eq!(ds.examples[1].input, json!({"q": 1}));
+    }
+
+    #[test]
+    fn jsonl_malformed_reports_line() {
+        let content = "{\"id\": \"a\", \"input\": 1}\n{not json}\n";
+        let err = Dataset::from_jsonl_str("jl", content).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{err}");
+
+        let err = Dataset::from_jsonl_str("jl", "{\"id\": \"x\"}\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn jsonl_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golden.jsonl");
+        let mut ds = Dataset::new("golden");
+        ds.add_example(sample_example("ex1"));
+        ds.add_example(sample_example("ex2"));
+        ds.to_jsonl(&path).unwrap();
+
+        let loaded = Dataset::from_jsonl(&path).unwrap();
+        assert_eq!(loaded.name, "golden");
+        assert_eq!(ids(&loaded), ["ex1", "ex2"]);
+        assert_eq!(loaded.examples[0].input, ds.examples[0].input);
+    }
+
+    #[test]
+    fn csv_quoted_fields_and_metadata() {
+        let content = "question,answer,topic\n\
+            \"What is 1, 2?\",\"a \"\"list\"\"\",math\n\
+            \"multi\nline\",,misc\n";
+        let ds = Dataset::from_csv_str("qa", content, "question", "answer", ',').unwrap();
+        assert_eq!(ds.len(), 2);
+        assert_eq!(ds.examples[0].id, "2");
+        assert_eq!(ds.examples[0].input, json!("What is 1, 2?"));
+        assert_eq!(ds.examples[0].expected, Some(json!("a \"list\"")));
+        assert_eq!(ds.examples[0].metadata["topic"], json!("math"));
+        assert_eq!(ds.examples[1].input, json!("multi\nline"));
+        assert_eq!(ds.examples[1].expected, None);
+    }
+
+    #[test]
+    fn csv_custom_delimiter_and_id_column() {
+        let content = "id\tin\tout\r\nq1\thello\tworld\r\n";
+        let ds = Dataset::from_csv_str("tsv", content, "in", "out", '\t').unwrap();
+        assert_eq!(ds.examples[0].id, "q1");
+        assert_eq!(ds.examples[0].expected, Some(json!("world")));
+        assert!(ds.examples[0].metadata.is_empty());
+    }
+
+    #[test]
+    fn csv_malformed_rows_report_line() {
+        let err = Dataset::from_csv_str("bad", "a,b\n1,2\n3\n", "a", "b", ',').unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{err}");
+
+        let err =
+            Dataset::from_csv_str("bad", "a,b\n1,2\n\"open,2\n", "a", "b", ',').unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{err}");
+
+        let err = Dataset::from_csv_str("bad", "a,b\n1,2\n", "a", "missing", ',').unwrap_err();
+        assert!(err.to_string().contains("missing"), "{err}");
+    }
+
+    #[test]
+    fn csv_file_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sheet.csv");
+        std::fs::write(&path, "input,expected\nping,pong\n").unwrap();
+        let ds = Dataset::from_csv(&path, "input", "expected").unwrap();
+        assert_eq!(ds.name, "sheet");
+        assert_eq!(ds.examples[0].expected, Some(json!("pong")));
+    }
+
     #[test]
     fn empty_dataset() {
         let ds = Dataset::new("empty");
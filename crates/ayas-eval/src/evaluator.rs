@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, Result};
 
 use crate::dataset::Example;
 
@@ -29,6 +29,9 @@ pub struct EvalResult {
     pub scores: Vec<EvalScore>,
     /// Latency in milliseconds.
     pub latency_ms: u64,
+    /// Error from invoking the system or running an evaluator, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// Trait for evaluators.
@@ -156,6 +159,213 @@ impl Evaluator for JsonKeyEvaluator {
     }
 }
 
+/// Regex evaluator — scores 1.0 if the output matches the pattern.
+///
+/// String outputs are matched as-is; other values are matched against their
+/// JSON serialization.
+pub struct RegexEvaluator {
+    pub pattern: String,
+    pub flags: String,
+    regex: regex::Regex,
+}
+
+impl RegexEvaluator {
+    /// Compile `pattern` with the given flags (any of `i`, `m`, `s`, `x`, `U`).
+    ///
+    /// Returns an error for an invalid pattern or unknown flag.
+    pub fn new(pattern: impl Into<String>, flags: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        let flags = flags.into();
+        let mut builder = regex::RegexBuilder::new(&pattern);
+        for flag in flags.chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'x' => builder.ignore_whitespace(true),
+                'U' => builder.swap_greed(true),
+                other => {
+                    return Err(AyasError::Other(format!("unknown regex flag '{other}'")));
+                }
+            };
+        }
+        let regex = builder
+            .build()
+            .map_err(|e| AyasError::Other(format!("invalid regex '{pattern}': {e}")))?;
+        Ok(Self {
+            pattern,
+            flags,
+            regex,
+        })
+    }
+}
+
+#[async_trait]
+impl Evaluator for RegexEvaluator {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    async fn evaluate(&self, _example: &Example, actual: &Value) -> Result<EvalScore> {
+        let actual_str = match actual {
+            Value::String(s) => s.clone(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        };
+        let (score, explanation) = match self.regex.find(&actual_str) {
+            Some(m) => (1.0, format!("matched '{}'", m.as_str())),
+            None => (
+                0.0,
+                format!("'{actual_str}' does not match /{}/{}", self.pattern, self.flags),
+            ),
+        };
+        Ok(EvalScore {
+            value: score,
+            metric: "regex".into(),
+            explanation: Some(explanation),
+        })
+    }
+}
+
+/// One step of a parsed JSON path.
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// JSON path evaluator — extracts a value from JSON output and compares it.
+///
+/// Supports the common subset of JSONPath: `$`, `.key`, `['key']` /
+/// `["key"]` and `[index]`, e.g. `$.items[0].name`. String outputs are
+/// parsed as JSON first. If `expected` is `None`, the example's `expected`
+/// value is used.
+pub struct JsonPathEvaluator {
+    pub path: String,
+    pub expected: Option<Value>,
+    segments: Vec<PathSegment>,
+}
+
+impl JsonPathEvaluator {
+    /// Parse `path`; returns an error if it is malformed.
+    pub fn new(path: impl Into<String>, expected: Option<Value>) -> Result<Self> {
+        let path = path.into();
+        let segments = parse_json_path(&path)?;
+        Ok(Self {
+            path,
+            expected,
+            segments,
+        })
+    }
+
+    fn extract<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match segment {
+                PathSegment::Key(key) => current.get(key),
+                PathSegment::Index(i) => current.get(*i),
+            })
+    }
+}
+
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>> {
+    let invalid = |msg: &str| AyasError::Other(format!("invalid JSON path '{path}': {msg}"));
+    let rest = path.trim();
+    let rest = rest.strip_prefix('$').unwrap_or(rest);
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                if end == start {
+                    return Err(invalid("empty key"));
+                }
+                segments.push(PathSegment::Key(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|p| p + i)
+                    .ok_or_else(|| invalid("unclosed '['"))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                if let Some(key) = quoted {
+                    segments.push(PathSegment::Key(key.to_string()));
+                } else {
+                    let index = inner
+                        .parse::<usize>()
+                        .map_err(|_| invalid(&format!("bad index '{inner}'")))?;
+                    segments.push(PathSegment::Index(index));
+                }
+                i = close + 1;
+            }
+            _ if i == 0 => {
+                // Allow a bare leading key, e.g. `items[0]`.
+                let mut end = 0;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                segments.push(PathSegment::Key(chars[..end].iter().collect()));
+                i = end;
+            }
+            c => return Err(invalid(&format!("unexpected '{c}'"))),
+        }
+    }
+    Ok(segments)
+}
+
+#[async_trait]
+impl Evaluator for JsonPathEvaluator {
+    fn name(&self) -> &str {
+        "json_path"
+    }
+
+    async fn evaluate(&self, example: &Example, actual: &Value) -> Result<EvalScore> {
+        let score = |value: f64, explanation: String| EvalScore {
+            value,
+            metric: "json_path".into(),
+            explanation: Some(explanation),
+        };
+
+        let Some(expected) = self.expected.as_ref().or(example.expected.as_ref()) else {
+            return Ok(score(0.0, "No expected value".into()));
+        };
+        let parsed;
+        let document = match actual {
+            Value::String(s) => match serde_json::from_str::<Value>(s) {
+                Ok(v) => {
+                    parsed = v;
+                    &parsed
+                }
+                Err(_) => return Ok(score(0.0, "Output is not valid JSON".into())),
+            },
+            other => other,
+        };
+
+        Ok(match self.extract(document) {
+            Some(found) if found == expected => {
+                score(1.0, format!("{} == {expected}", self.path))
+            }
+            Some(found) => score(
+                0.0,
+                format!("{}: captured {found}, expected {expected}", self.path),
+            ),
+            None => score(0.0, format!("{}: path not found, expected {expected}", self.path)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,6 +477,90 @@ mod tests {
         );
     }
 
+    // --- RegexEvaluator tests ---
+
+    #[tokio::test]
+    async fn regex_match() {
+        let eval = RegexEvaluator::new(r"\d{4}-\d{2}-\d{2}", "").unwrap();
+        let ex = example_with_expected(None);
+        let score = eval.evaluate(&ex, &json!("Due 2024-05-01.")).await.unwrap();
+        assert_eq!(score.value, 1.0);
+        assert_eq!(score.metric, "regex");
+        assert_eq!(score.explanation.as_deref(), Some("matched '2024-05-01'"));
+    }
+
+    #[tokio::test]
+    async fn regex_flags() {
+        let ex = example_with_expected(None);
+        let strict = RegexEvaluator::new("^hello$", "").unwrap();
+        let score = strict.evaluate(&ex, &json!("HELLO")).await.unwrap();
+        assert_eq!(score.value, 0.0);
+        assert!(score.explanation.unwrap().contains("does not match /^hello$/"));
+
+        let relaxed = RegexEvaluator::new("^hello$", "im").unwrap();
+        let score = relaxed.evaluate(&ex, &json!("x\nHELLO")).await.unwrap();
+        assert_eq!(score.value, 1.0);
+    }
+
+    #[test]
+    fn regex_invalid_at_construction() {
+        assert!(RegexEvaluator::new("(unclosed", "").is_err());
+        assert!(RegexEvaluator::new("ok", "q").is_err());
+    }
+
+    // --- JsonPathEvaluator tests ---
+
+    #[test]
+    fn json_path_parsing() {
+        assert_eq!(
+            parse_json_path("$.items[0]['full name']").unwrap(),
+            vec![
+                PathSegment::Key("items".into()),
+                PathSegment::Index(0),
+                PathSegment::Key("full name".into()),
+            ]
+        );
+        assert_eq!(
+            parse_json_path("a.b").unwrap(),
+            vec![PathSegment::Key("a".into()), PathSegment::Key("b".into())]
+        );
+        assert!(parse_json_path("$").unwrap().is_empty());
+        assert!(JsonPathEvaluator::new("$.items[", None).is_err());
+        assert!(JsonPathEvaluator::new("$.items[x]", None).is_err());
+        assert!(JsonPathEvaluator::new("$..a", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn json_path_match() {
+        let eval = JsonPathEvaluator::new("$.user.roles[1]", Some(json!("admin"))).unwrap();
+        let ex = example_with_expected(None);
+        let actual = json!({"user": {"roles": ["reader", "admin"]}});
+        let score = eval.evaluate(&ex, &actual).await.unwrap();
+        assert_eq!(score.value, 1.0);
+        assert_eq!(score.metric, "json_path");
+    }
+
+    #[tokio::test]
+    async fn json_path_mismatch_reports_values() {
+        let eval = JsonPathEvaluator::new("$.total", None).unwrap();
+        let ex = example_with_expected(Some(json!(42)));
+        let score = eval.evaluate(&ex, &json!("{\"total\": 41}")).await.unwrap();
+        assert_eq!(score.value, 0.0);
+        assert_eq!(
+            score.explanation.as_deref(),
+            Some("$.total: captured 41, expected 42")
+        );
+    }
+
+    #[tokio::test]
+    async fn json_path_missing() {
+        let eval = JsonPathEvaluator::new("$.missing", Some(json!(1))).unwrap();
+        let ex = example_with_expected(None);
+        let score = eval.evaluate(&ex, &json!({"a": 1})).await.unwrap();
+        assert_eq!(score.value, 0.0);
+        assert!(score.explanation.unwrap().contains("path not found"));
+    }
+
     #[tokio::test]
     async fn json_keys_empty_required() {
         let eval = JsonKeyEvaluator {
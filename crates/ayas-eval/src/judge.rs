@@ -1,19 +1,31 @@
 use async_trait::async_trait;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::sync::Arc;
 
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, ModelError, Result};
 use ayas_core::message::Message;
-use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::model::{CallOptions, ChatModel, ResponseFormat};
 
 use crate::dataset::Example;
 use crate::evaluator::{EvalScore, Evaluator};
 
+/// How `LlmJudge` asks for and parses its verdict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JudgeMode {
+    /// Plain prompt; the score is scraped from free-form text (0.0 if absent).
+    #[default]
+    FreeText,
+    /// Structured JSON output via `ResponseFormat::JsonSchema`; see
+    /// [`LlmJudge::score_with_rationale`].
+    Structured,
+}
+
 /// LLM-based evaluator that uses a ChatModel to judge outputs.
 pub struct LlmJudge {
     model: Arc<dyn ChatModel>,
     criteria: String,
     metric_name: String,
+    mode: JudgeMode,
 }
 
 impl LlmJudge {
@@ -22,6 +34,7 @@ impl LlmJudge {
             model,
             criteria: criteria.into(),
             metric_name: "llm_judge".into(),
+            mode: JudgeMode::default(),
         }
     }
 
@@ -29,15 +42,93 @@ impl LlmJudge {
         self.metric_name = name.into();
         self
     }
-}
 
-#[async_trait]
-impl Evaluator for LlmJudge {
-    fn name(&self) -> &str {
-        &self.metric_name
+    /// Select how `evaluate` obtains its score.
+    pub fn with_mode(mut self, mode: JudgeMode) -> Self {
+        self.mode = mode;
+        self
     }
 
-    async fn evaluate(&self, example: &Example, actual: &Value) -> Result<EvalScore> {
+    /// Ask the model for a 0–1 score and a rationale as structured JSON.
+    ///
+    /// The rationale is returned in `EvalScore::explanation` and the score is
+    /// clamped to `[0.0, 1.0]`. If the response cannot be parsed, the request
+    /// is retried once with a stricter reminder; a second failure returns
+    /// `ModelError::InvalidResponse`.
+    pub async fn score_with_rationale(
+        &self,
+        example: &Example,
+        actual: &Value,
+    ) -> Result<EvalScore> {
+        let prompt = format!(
+            "{}\n\n\
+            Respond with a JSON object: {{\"score\": <float between 0.0 and 1.0>, \"rationale\": \"<reason>\"}}",
+            self.build_prompt(example, actual)
+        );
+        let (value, rationale) = self
+            .generate_structured(
+                prompt,
+                "judge_score",
+                score_schema(),
+                "Your previous response could not be parsed. Reply with ONLY a JSON object \
+                with a numeric \"score\" between 0.0 and 1.0 and a string \"rationale\". \
+                Do not include any other text.",
+                parse_structured_response,
+            )
+            .await?;
+
+        Ok(EvalScore {
+            value,
+            metric: self.metric_name.clone(),
+            explanation: Some(rationale),
+        })
+    }
+
+    /// The judging criteria.
+    pub fn criteria(&self) -> &str {
+        &self.criteria
+    }
+
+    /// Prompt the model with a JSON schema response format and parse the reply.
+    ///
+    /// On parse failure the conversation is retried once with `reminder`
+    /// appended; a second failure returns `ModelError::InvalidResponse`.
+    pub(crate) async fn generate_structured<T>(
+        &self,
+        prompt: String,
+        schema_name: &str,
+        schema: Value,
+        reminder: &str,
+        parse: fn(&str) -> Option<T>,
+    ) -> Result<T> {
+        let options = CallOptions {
+            response_format: Some(ResponseFormat::JsonSchema {
+                name: schema_name.into(),
+                schema,
+                strict: true,
+            }),
+            ..Default::default()
+        };
+
+        let mut messages = vec![Message::user(prompt)];
+        let result = self.model.generate(&messages, &options).await?;
+        let first = result.message.content().to_string();
+        if let Some(parsed) = parse(&first) {
+            return Ok(parsed);
+        }
+
+        messages.push(Message::ai(first));
+        messages.push(Message::user(reminder));
+        let retry = self.model.generate(&messages, &options).await?;
+        let text = retry.message.content();
+        parse(text).ok_or_else(|| {
+            AyasError::Model(ModelError::InvalidResponse(format!(
+                "could not parse {schema_name} from: {text}"
+            )))
+        })
+    }
+
+    fn build_prompt(&self, example: &Example, actual: &Value) -> String {
         let input_str = serde_json::to_string_pretty(&example.input).unwrap_or_default();
         let actual_str = serde_json::to_string_pretty(actual).unwrap_or_default();
         let expected_str = example
@@ -46,14 +137,32 @@ impl Evaluator for LlmJudge {
             .map(|e| serde_json::to_string_pretty(e).unwrap_or_default())
             .unwrap_or_else(|| "N/A".into());
 
-        let prompt = format!(
+        format!(
             "You are an expert evaluator. Score the following output on a scale of 0.0 to 1.0.\n\n\
             Criteria: {}\n\n\
             Input: {}\n\n\
             Expected output: {}\n\n\
-            Actual output: {}\n\n\
-            Respond with ONLY a JSON object: {{\"score\": <float>, \"explanation\": \"<reason>\"}}",
+            Actual output: {}",
             self.criteria, input_str, expected_str, actual_str
+        )
+    }
+}
+
+#[async_trait]
+impl Evaluator for LlmJudge {
+    fn name(&self) -> &str {
+        &self.metric_name
+    }
+
+    async fn evaluate(&self, example: &Example, actual: &Value) -> Result<EvalScore> {
+        if self.mode == JudgeMode::Structured {
+            return self.score_with_rationale(example, actual).await;
+        }
+
+        let prompt = format!(
+            "{}\n\n\
+            Respond with ONLY a JSON object: {{\"score\": <float>, \"explanation\": \"<reason>\"}}",
+            self.build_prompt(example, actual)
         );
 
         let messages = vec![Message::user(prompt)];
@@ -74,6 +183,46 @@ impl Evaluator for LlmJudge {
     }
 }
 
+/// JSON schema for structured judge output.
+fn score_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "score": {"type": "number"},
+            "rationale": {"type": "string"}
+        },
+        "required": ["score", "rationale"],
+        "additionalProperties": false
+    })
+}
+
+/// Parse a JSON object from a model reply, tolerating Markdown code fences.
+pub(crate) fn parse_json_reply(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(body.trim()).ok()
+}
+
+/// Parse a structured `{"score", "rationale"}` response.
+///
+/// Returns `None` if the JSON is malformed or the score is missing or not a
+/// finite number.
+fn parse_structured_response(text: &str) -> Option<(f64, String)> {
+    let val = parse_json_reply(text)?;
+    let score = val.get("score")?.as_f64().filter(|s| s.is_finite())?;
+    let rationale = val
+        .get("rationale")
+        .or_else(|| val.get("explanation"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    Some((score.clamp(0.0, 1.0), rationale))
+}
+
 /// Parse the judge's response to extract score and explanation.
 fn parse_judge_response(text: &str) -> (f64, String) {
     // Try to parse as JSON
@@ -144,6 +293,39 @@ mod tests {
         assert!(explanation.contains("Could not parse score"));
     }
 
+    // --- parse_structured_response tests ---
+
+    #[test]
+    fn parse_structured_valid() {
+        let (score, rationale) =
+            parse_structured_response(r#"{"score": 0.4, "rationale": "Partially correct"}"#)
+                .unwrap();
+        assert!((score - 0.4).abs() < 1e-10);
+        assert_eq!(rationale, "Partially correct");
+    }
+
+    #[test]
+    fn parse_structured_clamps() {
+        let (score, _) = parse_structured_response(r#"{"score": 7, "rationale": "x"}"#).unwrap();
+        assert_eq!(score, 1.0);
+        let (score, _) = parse_structured_response(r#"{"score": -2, "rationale": "x"}"#).unwrap();
+        assert_eq!(score, 0.0);
+    }
+
+    #[test]
+    fn parse_structured_code_fence() {
+        let text = "```json\n{\"score\": 0.6, \"rationale\": \"ok\"}\n```";
+        let (score, _) = parse_structured_response(text).unwrap();
+        assert!((score - 0.6).abs() < 1e-10);
+    }
+
+    #[test]
+    fn parse_structured_rejects_invalid() {
+        assert!(parse_structured_response("The score is 0.7").is_none());
+        assert!(parse_structured_response(r#"{"rationale": "no score"}"#).is_none());
+        assert!(parse_structured_response(r#"{"score": "high"}"#).is_none());
+    }
+
     // --- MockChatModel for LlmJudge tests ---
 
     struct MockChatModel {
@@ -157,14 +339,11 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> Result<ChatResult> {
-            Ok(ChatResult {
-                message: Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: None,
-                }),
+            Ok(ChatResult::new(Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
                 usage: None,
-            })
+            })))
         }
 
         fn model_name(&self) -> &str {
@@ -216,6 +395,121 @@ mod tests {
         assert_eq!(score.metric, "helpfulness");
     }
 
+    /// Returns queued responses in order and records the call options.
+    struct SequenceChatModel {
+        responses: std::sync::Mutex<Vec<String>>,
+        calls: std::sync::Mutex<Vec<(usize, CallOptions)>>,
+    }
+
+    impl SequenceChatModel {
+        fn new(responses: &[&str]) -> Self {
+            Self {
+                responses: std::sync::Mutex::new(
+                    responses.iter().rev().map(|r| r.to_string()).collect(),
+                ),
+                calls: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChatModel for SequenceChatModel {
+        async fn generate(
+            &self,
+            messages: &[Message],
+            options: &CallOptions,
+        ) -> Result<ChatResult> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((messages.len(), options.clone()));
+            let response = self.responses.lock().unwrap().pop().unwrap_or_default();
+            Ok(ChatResult::new(Message::ai(response)))
+        }
+
+        fn model_name(&self) -> &str {
+            "sequence-judge"
+        }
+    }
+
+    fn sample_example() -> Example {
+        Example {
+            id: "s-1".into(),
+            input: json!("What is 2+2?"),
+            expected: Some(json!("4")),
+            metadata: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn score_with_rationale_uses_json_schema() {
+        let model = Arc::new(SequenceChatModel::new(&[
+            r#"{"score": 1.0, "rationale": "Correct"}"#,
+        ]));
+        let judge = LlmJudge::new(model.clone(), "correctness");
+        let score = judge
+            .score_with_rationale(&sample_example(), &json!("4"))
+            .await
+            .unwrap();
+        assert_eq!(score.value, 1.0);
+        assert_eq!(score.explanation.as_deref(), Some("Correct"));
+
+        let calls = model.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert!(matches!(
+            &calls[0].1.response_format,
+            Some(ResponseFormat::JsonSchema { name, .. }) if name == "judge_score"
+        ));
+    }
+
+    #[tokio::test]
+    async fn score_with_rationale_retries_once() {
+        let model = Arc::new(SequenceChatModel::new(&[
+            "Looks right to me",
+            r#"{"score": 0.8, "rationale": "Mostly correct"}"#,
+        ]));
+        let judge = LlmJudge::new(model.clone(), "correctness");
+        let score = judge
+            .score_with_rationale(&sample_example(), &json!("4"))
+            .await
+            .unwrap();
+        assert!((score.value - 0.8).abs() < 1e-10);
+
+        let calls = model.calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        // Retry includes the failed answer and the reminder.
+        assert_eq!(calls[1].0, 3);
+    }
+
+    #[tokio::test]
+    async fn score_with_rationale_fails_after_retry() {
+        let model = Arc::new(SequenceChatModel::new(&["nope", "still nope"]));
+        let judge = LlmJudge::new(model.clone(), "correctness");
+        let err = judge
+            .score_with_rationale(&sample_example(), &json!("4"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AyasError::Model(ModelError::InvalidResponse(_))
+        ));
+        assert_eq!(model.calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn structured_mode_evaluate() {
+        let model = Arc::new(SequenceChatModel::new(&[
+            r#"{"score": 1.3, "rationale": "Perfect"}"#,
+        ]));
+        let judge = LlmJudge::new(model, "correctness")
+            .with_mode(JudgeMode::Structured)
+            .with_metric_name("correctness");
+        let score = judge.evaluate(&sample_example(), &json!("4")).await.unwrap();
+        assert_eq!(score.value, 1.0);
+        assert_eq!(score.metric, "correctness");
+        assert_eq!(score.explanation.as_deref(), Some("Perfect"));
+    }
+
     #[tokio::test]
     async fn llm_judge_unparseable_response() {
         let model = Arc::new(MockChatModel {
@@ -2,14 +2,17 @@ pub mod dataset;
 pub mod evaluator;
 pub mod judge;
 pub mod online;
+pub mod pairwise;
 pub mod runner;
 
 pub mod prelude {
     pub use crate::dataset::{Dataset, Example};
     pub use crate::evaluator::{
         ContainsEvaluator, EvalResult, EvalScore, Evaluator, ExactMatchEvaluator,
+        JsonPathEvaluator, RegexEvaluator,
     };
-    pub use crate::judge::LlmJudge;
+    pub use crate::judge::{JudgeMode, LlmJudge};
     pub use crate::online::{run_online_eval, OnlineEvaluator, OnlineRun, OnlineSmithStore};
-    pub use crate::runner::{EvalReport, EvalRunner};
+    pub use crate::pairwise::{PairwiseEvaluator, PairwiseOutcome, PairwiseReport, PairwiseWinner};
+    pub use crate::runner::{EvalProgress, EvalReport, EvalRunner};
 }
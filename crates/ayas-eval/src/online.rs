@@ -3,8 +3,10 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use tokio::sync::Mutex;
+use tokio::task::JoinSet;
 
 use ayas_core::error::Result;
+use ayas_core::hash::Fnv1a;
 
 use crate::evaluator::{EvalScore, Evaluator};
 
@@ -38,12 +40,19 @@ pub struct OnlineRun {
 }
 
 /// Online evaluator that polls a SmithStore for new runs and applies evaluators.
+///
+/// Only a `sample_rate` fraction of runs is evaluated; the decision is a
+/// deterministic hash of the run ID, so re-polling the same run makes the
+/// same choice. Evaluations are dispatched to background tasks and their
+/// failures are logged, never propagated.
 pub struct OnlineEvaluator {
     store: Arc<dyn OnlineSmithStore>,
-    evaluators: Vec<Box<dyn Evaluator>>,
+    evaluators: Vec<Arc<dyn Evaluator>>,
     project: String,
     poll_interval: Duration,
+    sample_rate: f64,
     last_seen: Mutex<DateTime<Utc>>,
+    pending: Mutex<JoinSet<()>>,
 }
 
 impl OnlineEvaluator {
@@ -57,17 +66,47 @@ impl OnlineEvaluator {
             evaluators: Vec::new(),
             project: project.into(),
             poll_interval,
+            sample_rate: 1.0,
             last_seen: Mutex::new(Utc::now()),
+            pending: Mutex::new(JoinSet::new()),
         }
     }
 
     pub fn add_evaluator(mut self, eval: impl Evaluator + 'static) -> Self {
-        self.evaluators.push(Box::new(eval));
+        self.evaluators.push(Arc::new(eval));
         self
     }
 
-    /// Poll once for new runs, evaluate them, and store feedback.
-    /// Returns the number of runs evaluated.
+    /// Fraction of runs to evaluate, clamped to `[0.0, 1.0]` (default 1.0).
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether the run with this ID falls inside the sample.
+    pub fn should_sample(&self, run_id: &uuid::Uuid) -> bool {
+        if self.sample_rate >= 1.0 {
+            return true;
+        }
+        let mut hasher = Fnv1a::new();
+        hasher.write(run_id.as_bytes());
+        hasher.finish_unit() < self.sample_rate
+    }
+
+    /// Wait for all dispatched evaluations to finish.
+    pub async fn flush(&self) {
+        let mut pending = self.pending.lock().await;
+        while let Some(res) = pending.join_next().await {
+            if let Err(e) = res {
+                tracing::warn!("Online eval task failed: {e}");
+            }
+        }
+    }
+
+    /// Poll once for new runs and dispatch evaluation of the sampled ones.
+    ///
+    /// Evaluation and feedback writes run on background tasks (see
+    /// [`OnlineEvaluator::flush`]). Returns the number of runs dispatched.
     pub async fn poll_once(&self) -> Result<usize> {
         let start_after = {
             let guard = self.last_seen.lock().await;
@@ -96,6 +135,10 @@ impl OnlineEvaluator {
                 None => continue,
             };
 
+            if !self.should_sample(&run.run_id) {
+                continue;
+            }
+
             // Create a dummy example for evaluation (no expected value for online eval)
             let example = crate::dataset::Example {
                 id: run.run_id.to_string(),
@@ -104,17 +147,30 @@ impl OnlineEvaluator {
                 metadata: Default::default(),
             };
 
-            for evaluator in &self.evaluators {
-                let score: EvalScore = evaluator.evaluate(&example, &output).await?;
-                self.store
-                    .put_feedback(
-                        run.run_id,
-                        &score.metric,
-                        score.value,
-                        score.explanation.as_deref(),
+            let store = self.store.clone();
+            let evaluators = self.evaluators.clone();
+            let run_id = run.run_id;
+            let mut pending = self.pending.lock().await;
+            // Reap finished tasks so the set does not grow unbounded.
+            while pending.try_join_next().is_some() {}
+            pending.spawn(async move {
+                for evaluator in &evaluators {
+                    let result = evaluate_and_store(
+                        store.as_ref(),
+                        evaluator.as_ref(),
+                        run_id,
+                        &example,
+                        &output,
                     )
-                    .await?;
-            }
+                    .await;
+                    if let Err(e) = result {
+                        tracing::warn!(
+                            "Online eval '{}' failed for run {run_id}: {e}",
+                            evaluator.name()
+                        );
+                    }
+                }
+            });
 
             count += 1;
         }
@@ -129,6 +185,24 @@ impl OnlineEvaluator {
     }
 }
 
+async fn evaluate_and_store(
+    store: &dyn OnlineSmithStore,
+    evaluator: &dyn Evaluator,
+    run_id: uuid::Uuid,
+    example: &crate::dataset::Example,
+    output: &serde_json::Value,
+) -> Result<()> {
+    let score: EvalScore = evaluator.evaluate(example, output).await?;
+    store
+        .put_feedback(
+            run_id,
+            &score.metric,
+            score.value,
+            score.explanation.as_deref(),
+        )
+        .await
+}
+
 /// Spawn a background task that continuously polls for new runs and evaluates them.
 /// Returns a JoinHandle that can be used to cancel the loop.
 pub fn run_online_eval(evaluator: Arc<OnlineEvaluator>) -> tokio::task::JoinHandle<()> {
@@ -212,6 +286,7 @@ mod tests {
         .add_evaluator(ContainsEvaluator);
 
         let count = evaluator.poll_once().await.unwrap();
+        evaluator.flush().await;
         assert_eq!(count, 2);
         assert_eq!(store.feedback_count.load(Ordering::SeqCst), 2);
     }
@@ -232,6 +307,7 @@ mod tests {
             .add_evaluator(ContainsEvaluator);
 
         let count = evaluator.poll_once().await.unwrap();
+        evaluator.flush().await;
         assert_eq!(count, 0);
         assert_eq!(store.feedback_count.load(Ordering::SeqCst), 0);
     }
@@ -290,7 +366,111 @@ mod tests {
             .add_evaluator(crate::evaluator::ExactMatchEvaluator);
 
         evaluator.poll_once().await.unwrap();
+        evaluator.flush().await;
         // 1 run * 2 evaluators = 2 feedback entries
         assert_eq!(store.feedback_count.load(Ordering::SeqCst), 2);
     }
+
+    fn runs_with_output(n: usize) -> Vec<OnlineRun> {
+        let now = Utc::now();
+        (0..n)
+            .map(|i| OnlineRun {
+                run_id: uuid::Uuid::new_v4(),
+                output: Some(json!("out")),
+                start_time: now + chrono::Duration::milliseconds(i as i64 + 1),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sampling_fraction_over_many_runs() {
+        let store = Arc::new(MockSmithStore {
+            runs: vec![],
+            feedback_count: AtomicUsize::new(0),
+        });
+        let evaluator = OnlineEvaluator::new(store, "proj", Duration::from_secs(1))
+            .with_sample_rate(0.25);
+
+        let ids: Vec<_> = (0..20_000).map(|_| uuid::Uuid::new_v4()).collect();
+        let sampled = ids.iter().filter(|id| evaluator.should_sample(id)).count();
+        let fraction = sampled as f64 / ids.len() as f64;
+        assert!((0.23..0.27).contains(&fraction), "fraction was {fraction}");
+
+        // Deterministic per run ID.
+        for id in ids.iter().take(100) {
+            assert_eq!(evaluator.should_sample(id), evaluator.should_sample(id));
+        }
+    }
+
+    #[test]
+    fn sampling_rate_bounds() {
+        let store: Arc<dyn OnlineSmithStore> = Arc::new(MockSmithStore {
+            runs: vec![],
+            feedback_count: AtomicUsize::new(0),
+        });
+        let none = OnlineEvaluator::new(store.clone(), "proj", Duration::from_secs(1))
+            .with_sample_rate(0.0);
+        let all = OnlineEvaluator::new(store, "proj", Duration::from_secs(1))
+            .with_sample_rate(3.0);
+        for _ in 0..100 {
+            let id = uuid::Uuid::new_v4();
+            assert!(!none.should_sample(&id));
+            assert!(all.should_sample(&id));
+        }
+    }
+
+    #[tokio::test]
+    async fn poll_once_dispatches_only_sampled_runs() {
+        let store = Arc::new(MockSmithStore {
+            runs: runs_with_output(200),
+            feedback_count: AtomicUsize::new(0),
+        });
+        let evaluator = OnlineEvaluator::new(store.clone(), "proj", Duration::from_secs(1))
+            .with_sample_rate(0.5)
+            .add_evaluator(ContainsEvaluator);
+
+        let expected = store
+            .runs
+            .iter()
+            .filter(|r| evaluator.should_sample(&r.run_id))
+            .count();
+        let count = evaluator.poll_once().await.unwrap();
+        evaluator.flush().await;
+        assert_eq!(count, expected);
+        assert_eq!(store.feedback_count.load(Ordering::SeqCst), expected);
+    }
+
+    struct FailingEvaluator;
+
+    #[async_trait::async_trait]
+    impl Evaluator for FailingEvaluator {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn evaluate(
+            &self,
+            _example: &crate::dataset::Example,
+            _actual: &serde_json::Value,
+        ) -> Result<EvalScore> {
+            Err(ayas_core::error::AyasError::Other("judge unavailable".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn evaluator_failure_does_not_propagate() {
+        let store = Arc::new(MockSmithStore {
+            runs: runs_with_output(3),
+            feedback_count: AtomicUsize::new(0),
+        });
+        let evaluator = OnlineEvaluator::new(store.clone(), "proj", Duration::from_secs(1))
+            .add_evaluator(FailingEvaluator)
+            .add_evaluator(ContainsEvaluator);
+
+        let count = evaluator.poll_once().await.unwrap();
+        evaluator.flush().await;
+        assert_eq!(count, 3);
+        // The failing evaluator is skipped; the other still records feedback.
+        assert_eq!(store.feedback_count.load(Ordering::SeqCst), 3);
+    }
 }
@@ -0,0 +1,384 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use ayas_core::error::{AyasError, Result};
+
+use crate::dataset::Example;
+use crate::evaluator::{EvalScore, Evaluator};
+use crate::judge::{parse_json_reply, LlmJudge};
+
+/// Which candidate won a pairwise comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairwiseWinner {
+    A,
+    B,
+    Tie,
+}
+
+/// Position a candidate was shown to the judge in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    First,
+    Second,
+}
+
+/// Outcome of comparing two candidate outputs for one example.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairwiseOutcome {
+    pub example_id: String,
+    pub winner: PairwiseWinner,
+    /// How decisively the winner won, 0.0 (barely) to 1.0 (clearly). 0.0 for ties.
+    pub margin: f64,
+    /// Whether candidate A was shown first.
+    pub a_first: bool,
+    /// Position of the winning candidate as shown to the judge (`None` for ties).
+    pub winning_position: Option<Position>,
+    #[serde(default)]
+    pub rationale: String,
+}
+
+impl PairwiseOutcome {
+    /// Score from A's perspective: 1.0 = A wins, 0.0 = B wins, 0.5 = tie.
+    pub fn score(&self) -> f64 {
+        match self.winner {
+            PairwiseWinner::A => 1.0,
+            PairwiseWinner::B => 0.0,
+            PairwiseWinner::Tie => 0.5,
+        }
+    }
+}
+
+/// Aggregate results of a pairwise run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairwiseReport {
+    pub win_rate_a: f64,
+    pub win_rate_b: f64,
+    pub tie_rate: f64,
+    /// Fraction of decided comparisons won by the candidate shown first.
+    /// Values far from 0.5 suggest position bias.
+    pub first_position_win_rate: f64,
+    pub outcomes: Vec<PairwiseOutcome>,
+}
+
+impl PairwiseReport {
+    pub fn from_outcomes(outcomes: Vec<PairwiseOutcome>) -> Self {
+        let total = outcomes.len().max(1) as f64;
+        let count =
+            |w: PairwiseWinner| outcomes.iter().filter(|o| o.winner == w).count() as f64;
+        let decided = outcomes
+            .iter()
+            .filter(|o| o.winning_position.is_some())
+            .count();
+        let first_wins = outcomes
+            .iter()
+            .filter(|o| o.winning_position == Some(Position::First))
+            .count();
+        Self {
+            win_rate_a: count(PairwiseWinner::A) / total,
+            win_rate_b: count(PairwiseWinner::B) / total,
+            tie_rate: count(PairwiseWinner::Tie) / total,
+            first_position_win_rate: if decided == 0 {
+                0.0
+            } else {
+                first_wins as f64 / decided as f64
+            },
+            outcomes,
+        }
+    }
+}
+
+/// Compares two candidate outputs using an `LlmJudge`.
+///
+/// The presentation order of A and B is randomized per comparison to
+/// mitigate position bias. As an `Evaluator`, `actual` must be an object
+/// `{"a": <output>, "b": <output>}`; the score is from A's perspective
+/// (see [`PairwiseOutcome::score`]). Use `EvalRunner::run_pairwise` to
+/// compare two runnables over a dataset.
+pub struct PairwiseEvaluator {
+    judge: LlmJudge,
+    metric_name: String,
+    randomize: bool,
+}
+
+impl PairwiseEvaluator {
+    pub fn new(judge: LlmJudge) -> Self {
+        Self {
+            judge,
+            metric_name: "pairwise".into(),
+            randomize: true,
+        }
+    }
+
+    pub fn with_metric_name(mut self, name: impl Into<String>) -> Self {
+        self.metric_name = name.into();
+        self
+    }
+
+    /// Disable order randomization (A is always shown first).
+    pub fn with_randomize(mut self, randomize: bool) -> Self {
+        self.randomize = randomize;
+        self
+    }
+
+    /// Ask the judge which of `a` and `b` better answers the example.
+    pub async fn compare(
+        &self,
+        example: &Example,
+        a: &Value,
+        b: &Value,
+    ) -> Result<PairwiseOutcome> {
+        let a_first = !self.randomize || (uuid::Uuid::new_v4().as_bytes()[0] & 1) == 0;
+        let (first, second) = if a_first { (a, b) } else { (b, a) };
+
+        let to_str = |v: &Value| serde_json::to_string_pretty(v).unwrap_or_default();
+        let expected_str = example
+            .expected
+            .as_ref()
+            .map(to_str)
+            .unwrap_or_else(|| "N/A".into());
+        let prompt = format!(
+            "You are an expert evaluator. Compare two responses to the same input and decide \
+            which one is better.\n\n\
+            Criteria: {}\n\n\
+            Input: {}\n\n\
+            Reference output: {}\n\n\
+            Response 1: {}\n\n\
+            Response 2: {}\n\n\
+            Respond with a JSON object: {{\"winner\": \"1\" | \"2\" | \"tie\", \
+            \"margin\": <float between 0.0 and 1.0>, \"rationale\": \"<reason>\"}}",
+            self.judge.criteria(),
+            to_str(&example.input),
+            expected_str,
+            to_str(first),
+            to_str(second),
+        );
+
+        let (position, margin, rationale) = self
+            .judge
+            .generate_structured(
+                prompt,
+                "pairwise_verdict",
+                verdict_schema(),
+                "Your previous response could not be parsed. Reply with ONLY a JSON object \
+                with \"winner\" set to \"1\", \"2\" or \"tie\", a numeric \"margin\" between \
+                0.0 and 1.0 and a string \"rationale\". Do not include any other text.",
+                parse_verdict,
+            )
+            .await?;
+
+        let winner = match (position, a_first) {
+            (None, _) => PairwiseWinner::Tie,
+            (Some(Position::First), true) | (Some(Position::Second), false) => PairwiseWinner::A,
+            (Some(_), _) => PairwiseWinner::B,
+        };
+
+        Ok(PairwiseOutcome {
+            example_id: example.id.clone(),
+            winner,
+            margin: if position.is_some() { margin } else { 0.0 },
+            a_first,
+            winning_position: position,
+            rationale,
+        })
+    }
+}
+
+#[async_trait]
+impl Evaluator for PairwiseEvaluator {
+    fn name(&self) -> &str {
+        &self.metric_name
+    }
+
+    async fn evaluate(&self, example: &Example, actual: &Value) -> Result<EvalScore> {
+        let (Some(a), Some(b)) = (actual.get("a"), actual.get("b")) else {
+            return Err(AyasError::Other(
+                "PairwiseEvaluator expects {\"a\": ..., \"b\": ...} as the actual output".into(),
+            ));
+        };
+        let outcome = self.compare(example, a, b).await?;
+        Ok(EvalScore {
+            value: outcome.score(),
+            metric: self.metric_name.clone(),
+            explanation: Some(format!(
+                "winner: {:?} (margin {:.2}): {}",
+                outcome.winner, outcome.margin, outcome.rationale
+            )),
+        })
+    }
+}
+
+fn verdict_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "winner": {"type": "string", "enum": ["1", "2", "tie"]},
+            "margin": {"type": "number"},
+            "rationale": {"type": "string"}
+        },
+        "required": ["winner", "margin", "rationale"],
+        "additionalProperties": false
+    })
+}
+
+/// Parse `{"winner", "margin", "rationale"}` into (winning position, margin, rationale).
+fn parse_verdict(text: &str) -> Option<(Option<Position>, f64, String)> {
+    let val = parse_json_reply(text)?;
+    let winner = match &val["winner"] {
+        Value::String(s) => s.trim().to_ascii_lowercase(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    let position = match winner.as_str() {
+        "1" | "first" => Some(Position::First),
+        "2" | "second" => Some(Position::Second),
+        "tie" => None,
+        _ => return None,
+    };
+    let margin = val["margin"]
+        .as_f64()
+        .filter(|m| m.is_finite())
+        .unwrap_or(0.0)
+        .clamp(0.0, 1.0);
+    let rationale = val["rationale"].as_str().unwrap_or("").to_string();
+    Some((position, margin, rationale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ayas_core::message::Message;
+    use ayas_core::model::{CallOptions, ChatModel, ChatResult};
+    use std::sync::Arc;
+
+    struct FixedModel(String);
+
+    #[async_trait]
+    impl ChatModel for FixedModel {
+        async fn generate(&self, _: &[Message], _: &CallOptions) -> Result<ChatResult> {
+            Ok(ChatResult::new(Message::ai(self.0.clone())))
+        }
+
+        fn model_name(&self) -> &str {
+            "fixed-judge"
+        }
+    }
+
+    fn evaluator(response: &str) -> PairwiseEvaluator {
+        let judge = LlmJudge::new(Arc::new(FixedModel(response.into())), "helpfulness");
+        PairwiseEvaluator::new(judge)
+    }
+
+    fn example() -> Example {
+        Example {
+            id: "p-1".into(),
+            input: json!("Explain Rust"),
+            expected: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn parse_verdict_variants() {
+        let (pos, margin, rationale) =
+            parse_verdict(r#"{"winner": "1", "margin": 0.7, "rationale": "clearer"}"#).unwrap();
+        assert_eq!(pos, Some(Position::First));
+        assert!((margin - 0.7).abs() < 1e-10);
+        assert_eq!(rationale, "clearer");
+
+        let (pos, _, _) = parse_verdict(r#"{"winner": 2, "margin": 3}"#).unwrap();
+        assert_eq!(pos, Some(Position::Second));
+
+        let (pos, _, _) = parse_verdict(r#"{"winner": "tie", "margin": 0}"#).unwrap();
+        assert_eq!(pos, None);
+
+        assert!(parse_verdict(r#"{"winner": "A"}"#).is_none());
+        assert!(parse_verdict("Response 1 is better").is_none());
+    }
+
+    #[tokio::test]
+    async fn compare_fixed_order_maps_positions() {
+        let eval = evaluator(r#"{"winner": "2", "margin": 0.4, "rationale": "more detail"}"#)
+            .with_randomize(false);
+        let outcome = eval
+            .compare(&example(), &json!("short"), &json!("long"))
+            .await
+            .unwrap();
+        assert!(outcome.a_first);
+        assert_eq!(outcome.winner, PairwiseWinner::B);
+        assert_eq!(outcome.winning_position, Some(Position::Second));
+        assert!((outcome.margin - 0.4).abs() < 1e-10);
+        assert_eq!(outcome.score(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn compare_randomized_is_consistent() {
+        // The judge always prefers the first position; the winner must follow
+        // whichever candidate was shown first.
+        let eval = evaluator(r#"{"winner": "1", "margin": 1.0, "rationale": "first"}"#);
+        for _ in 0..16 {
+            let outcome = eval
+                .compare(&example(), &json!("a"), &json!("b"))
+                .await
+                .unwrap();
+            let expected = if outcome.a_first {
+                PairwiseWinner::A
+            } else {
+                PairwiseWinner::B
+            };
+            assert_eq!(outcome.winner, expected);
+            assert_eq!(outcome.winning_position, Some(Position::First));
+        }
+    }
+
+    #[tokio::test]
+    async fn tie_has_zero_margin() {
+        let eval = evaluator(r#"{"winner": "tie", "margin": 0.9, "rationale": "same"}"#);
+        let outcome = eval
+            .compare(&example(), &json!("a"), &json!("b"))
+            .await
+            .unwrap();
+        assert_eq!(outcome.winner, PairwiseWinner::Tie);
+        assert_eq!(outcome.margin, 0.0);
+        assert_eq!(outcome.winning_position, None);
+        assert_eq!(outcome.score(), 0.5);
+    }
+
+    #[tokio::test]
+    async fn evaluate_requires_pair() {
+        let eval = evaluator(r#"{"winner": "1", "margin": 1.0, "rationale": ""}"#);
+        assert!(eval.evaluate(&example(), &json!("single")).await.is_err());
+
+        let score = eval
+            .with_randomize(false)
+            .evaluate(&example(), &json!({"a": "x", "b": "y"}))
+            .await
+            .unwrap();
+        assert_eq!(score.value, 1.0);
+        assert_eq!(score.metric, "pairwise");
+    }
+
+    #[test]
+    fn report_rates() {
+        let outcome = |winner, winning_position| PairwiseOutcome {
+            example_id: "x".into(),
+            winner,
+            margin: 0.5,
+            a_first: true,
+            winning_position,
+            rationale: String::new(),
+        };
+        let report = PairwiseReport::from_outcomes(vec![
+            outcome(PairwiseWinner::A, Some(Position::First)),
+            outcome(PairwiseWinner::A, Some(Position::Second)),
+            outcome(PairwiseWinner::B, Some(Position::First)),
+            outcome(PairwiseWinner::Tie, None),
+        ]);
+        assert!((report.win_rate_a - 0.5).abs() < 1e-10);
+        assert!((report.win_rate_b - 0.25).abs() < 1e-10);
+        assert!((report.tie_rate - 0.25).abs() < 1e-10);
+        assert!((report.first_position_win_rate - 2.0 / 3.0).abs() < 1e-10);
+    }
+}
@@ -1,14 +1,19 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
 use ayas_core::config::RunnableConfig;
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, Result};
 use ayas_core::runnable::Runnable;
 
-use crate::dataset::Dataset;
-use crate::evaluator::{EvalResult, Evaluator};
+use crate::dataset::{Dataset, Example};
+use crate::evaluator::{EvalResult, EvalScore, Evaluator};
+use crate::pairwise::{PairwiseEvaluator, PairwiseReport};
 
 /// Summary report of an evaluation run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,53 +23,134 @@ pub struct EvalReport {
     pub results: Vec<EvalResult>,
     pub aggregate_scores: std::collections::HashMap<String, f64>,
     pub mean_latency_ms: f64,
+    /// Number of examples whose invocation or evaluation failed.
+    #[serde(default)]
+    pub failed_examples: usize,
+    /// Win rates and per-example winners (pairwise runs only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pairwise: Option<PairwiseReport>,
 }
 
+/// Progress notification emitted after each example completes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalProgress {
+    /// Number of examples finished so far (including failures).
+    pub completed: usize,
+    pub total: usize,
+    /// The example that just finished.
+    pub example_id: String,
+    /// Error message if the example failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Callback invoked with progress updates during `EvalRunner::run`.
+pub type ProgressCallback = Arc<dyn Fn(&EvalProgress) + Send + Sync>;
+
 /// Runs evaluation of a Runnable against a Dataset.
 pub struct EvalRunner {
-    evaluators: Vec<Box<dyn Evaluator>>,
+    evaluators: Vec<Arc<dyn Evaluator>>,
+    max_concurrency: usize,
+    progress: Option<ProgressCallback>,
 }
 
 impl EvalRunner {
     pub fn new() -> Self {
         Self {
             evaluators: Vec::new(),
+            max_concurrency: 1,
+            progress: None,
         }
     }
 
     pub fn add_evaluator(mut self, eval: impl Evaluator + 'static) -> Self {
-        self.evaluators.push(Box::new(eval));
+        self.evaluators.push(Arc::new(eval));
+        self
+    }
+
+    /// Maximum number of examples evaluated at once (default 1, minimum 1).
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Register a callback invoked after each example completes.
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&EvalProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
         self
     }
 
     /// Run evaluation: invoke the runnable for each example, then evaluate.
-    pub async fn run<R: Runnable<Input = Value, Output = Value>>(
+    ///
+    /// Up to `max_concurrency` examples run at once. Results keep the
+    /// dataset's order. A failing invocation or evaluator does not abort the
+    /// run; the error is recorded in that example's `EvalResult::error` and
+    /// counted in `EvalReport::failed_examples`.
+    pub async fn run<R>(
         &self,
-        runnable: &R,
+        runnable: Arc<R>,
         dataset: &Dataset,
         config: &RunnableConfig,
-    ) -> Result<EvalReport> {
-        let mut results = Vec::new();
-
-        for example in &dataset.examples {
-            let start = Instant::now();
-            let actual = runnable.invoke(example.input.clone(), config).await?;
-            let latency = start.elapsed().as_millis() as u64;
+    ) -> Result<EvalReport>
+    where
+        R: Runnable<Input = Value, Output = Value> + 'static,
+    {
+        let total = dataset.examples.len();
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrency));
+        let mut join_set = JoinSet::new();
+        let mut task_index = HashMap::new();
+
+        for (idx, example) in dataset.examples.iter().enumerate() {
+            let runnable = runnable.clone();
+            let evaluators = self.evaluators.clone();
+            let semaphore = semaphore.clone();
+            let example = example.clone();
+            let cfg = config.clone();
+            let handle = join_set.spawn(async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| AyasError::Other(format!("Eval semaphore closed: {e}")))?;
+                let result =
+                    evaluate_example(runnable.as_ref(), &evaluators, &example, &cfg).await;
+                Ok::<_, AyasError>((idx, result))
+            });
+            task_index.insert(handle.id(), idx);
+        }
 
-            let mut scores = Vec::new();
-            for evaluator in &self.evaluators {
-                let score = evaluator.evaluate(example, &actual).await?;
-                scores.push(score);
+        let mut slots: Vec<Option<EvalResult>> = vec![None; total];
+        let mut completed = 0;
+        while let Some(joined) = join_set.join_next().await {
+            let (idx, result) = match joined {
+                Ok(res) => res?,
+                Err(e) => {
+                    // A panicking example is recorded as a failure, not propagated.
+                    let idx = task_index[&e.id()];
+                    let result = failed_result(
+                        &dataset.examples[idx],
+                        format!("Eval task panicked: {e}"),
+                        0,
+                    );
+                    (idx, result)
+                }
+            };
+            completed += 1;
+            if let Some(progress) = &self.progress {
+                progress(&EvalProgress {
+                    completed,
+                    total,
+                    example_id: result.example_id.clone(),
+                    error: result.error.clone(),
+                });
             }
-
-            results.push(EvalResult {
-                example_id: example.id.clone(),
-                actual_output: actual,
-                scores,
-                latency_ms: latency,
-            });
+            slots[idx] = Some(result);
         }
 
+        let results: Vec<EvalResult> = slots.into_iter().flatten().collect();
+
         // Compute aggregates
         let mut aggregate_scores = std::collections::HashMap::new();
         for evaluator in &self.evaluators {
@@ -85,22 +171,138 @@ impl EvalRunner {
             }
         }
 
-        let mean_latency = if results.is_empty() {
-            0.0
-        } else {
-            results.iter().map(|r| r.latency_ms as f64).sum::<f64>() / results.len() as f64
-        };
+        let failed_examples = results.iter().filter(|r| r.error.is_some()).count();
+
+        Ok(EvalReport {
+            dataset_name: dataset.name.clone(),
+            total_examples: total,
+            mean_latency_ms: mean_latency(&results),
+            results,
+            aggregate_scores,
+            failed_examples,
+            pairwise: None,
+        })
+    }
+
+    /// Run two runnables over the dataset and compare their outputs pairwise.
+    ///
+    /// Each result's `actual_output` is `{"a": ..., "b": ...}` and its latency
+    /// is the sum of both invocations. Only `pairwise` is applied; evaluators
+    /// added with `add_evaluator` are ignored. The aggregate score under the
+    /// evaluator's name is A's mean score (ties count as 0.5).
+    pub async fn run_pairwise<A, B>(
+        &self,
+        runnable_a: &A,
+        runnable_b: &B,
+        pairwise: &PairwiseEvaluator,
+        dataset: &Dataset,
+        config: &RunnableConfig,
+    ) -> Result<EvalReport>
+    where
+        A: Runnable<Input = Value, Output = Value>,
+        B: Runnable<Input = Value, Output = Value>,
+    {
+        let mut results = Vec::new();
+        let mut outcomes = Vec::new();
+
+        for example in &dataset.examples {
+            let start = Instant::now();
+            let a = runnable_a.invoke(example.input.clone(), config).await?;
+            let b = runnable_b.invoke(example.input.clone(), config).await?;
+            let latency = start.elapsed().as_millis() as u64;
+
+            let outcome = pairwise.compare(example, &a, &b).await?;
+            results.push(EvalResult {
+                example_id: example.id.clone(),
+                actual_output: serde_json::json!({ "a": a, "b": b }),
+                scores: vec![EvalScore {
+                    value: outcome.score(),
+                    metric: pairwise.name().to_string(),
+                    explanation: Some(outcome.rationale.clone()),
+                }],
+                latency_ms: latency,
+                error: None,
+            });
+            outcomes.push(outcome);
+        }
+
+        let mut aggregate_scores = std::collections::HashMap::new();
+        if !results.is_empty() {
+            let mean =
+                results.iter().map(|r| r.scores[0].value).sum::<f64>() / results.len() as f64;
+            aggregate_scores.insert(pairwise.name().to_string(), mean);
+        }
 
         Ok(EvalReport {
             dataset_name: dataset.name.clone(),
             total_examples: dataset.examples.len(),
+            mean_latency_ms: mean_latency(&results),
             results,
             aggregate_scores,
-            mean_latency_ms: mean_latency,
+            failed_examples: 0,
+            pairwise: Some(PairwiseReport::from_outcomes(outcomes)),
         })
     }
 }
 
+/// Invoke the runnable on one example and apply every evaluator.
+///
+/// Errors are captured in the returned result rather than propagated.
+async fn evaluate_example<R>(
+    runnable: &R,
+    evaluators: &[Arc<dyn Evaluator>],
+    example: &Example,
+    config: &RunnableConfig,
+) -> EvalResult
+where
+    R: Runnable<Input = Value, Output = Value>,
+{
+    let start = Instant::now();
+    let actual = match runnable.invoke(example.input.clone(), config).await {
+        Ok(actual) => actual,
+        Err(e) => {
+            let latency = start.elapsed().as_millis() as u64;
+            return failed_result(example, e.to_string(), latency);
+        }
+    };
+    let latency = start.elapsed().as_millis() as u64;
+
+    let mut scores = Vec::new();
+    let mut errors = Vec::new();
+    for evaluator in evaluators {
+        match evaluator.evaluate(example, &actual).await {
+            Ok(score) => scores.push(score),
+            Err(e) => errors.push(format!("{}: {e}", evaluator.name())),
+        }
+    }
+
+    EvalResult {
+        example_id: example.id.clone(),
+        actual_output: actual,
+        scores,
+        latency_ms: latency,
+        error: (!errors.is_empty()).then(|| errors.join("; ")),
+    }
+}
+
+fn failed_result(example: &Example, error: String, latency_ms: u64) -> EvalResult {
+    EvalResult {
+        example_id: example.id.clone(),
+        actual_output: Value::Null,
+        scores: Vec::new(),
+        latency_ms,
+        error: Some(error),
+    }
+}
+
+fn mean_latency(results: &[EvalResult]) -> f64 {
+    if results.is_empty() {
+        0.0
+    } else {
+        results.iter().map(|r| r.latency_ms as f64).sum::<f64>() / results.len() as f64
+    }
+}
+
 impl Default for EvalRunner {
     fn default() -> Self {
         Self::new()
@@ -110,7 +312,6 @@ impl Default for EvalRunner {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dataset::Example;
     use crate::evaluator::ExactMatchEvaluator;
     use async_trait::async_trait;
     use serde_json::json;
@@ -151,7 +352,7 @@ mod tests {
         let dataset = make_dataset();
         let config = RunnableConfig::default();
 
-        let report = runner.run(&EchoRunnable, &dataset, &config).await.unwrap();
+        let report = runner.run(Arc::new(EchoRunnable), &dataset, &config).await.unwrap();
 
         assert_eq!(report.dataset_name, "test-ds");
         assert_eq!(report.total_examples, 2);
@@ -176,7 +377,7 @@ mod tests {
         let dataset = make_dataset();
         let config = RunnableConfig::default();
 
-        let report = runner.run(&EchoRunnable, &dataset, &config).await.unwrap();
+        let report = runner.run(Arc::new(EchoRunnable), &dataset, &config).await.unwrap();
 
         // Verify serialization works
         let json_str = serde_json::to_string(&report).unwrap();
@@ -193,7 +394,7 @@ mod tests {
         let dataset = Dataset::new("empty");
         let config = RunnableConfig::default();
 
-        let report = runner.run(&EchoRunnable, &dataset, &config).await.unwrap();
+        let report = runner.run(Arc::new(EchoRunnable), &dataset, &config).await.unwrap();
 
         assert_eq!(report.total_examples, 0);
         assert!(report.results.is_empty());
@@ -201,6 +402,170 @@ mod tests {
         assert_eq!(report.mean_latency_ms, 0.0);
     }
 
+    /// A mock Runnable that returns a constant.
+    struct ConstRunnable(&'static str);
+
+    #[async_trait]
+    impl Runnable for ConstRunnable {
+        type Input = Value;
+        type Output = Value;
+
+        async fn invoke(&self, _input: Self::Input, _config: &RunnableConfig) -> Result<Value> {
+            Ok(json!(self.0))
+        }
+    }
+
+    /// Judge that always prefers whichever response contains "good".
+    struct PreferGoodModel;
+
+    #[async_trait]
+    impl ayas_core::model::ChatModel for PreferGoodModel {
+        async fn generate(
+            &self,
+            messages: &[ayas_core::message::Message],
+            _options: &ayas_core::model::CallOptions,
+        ) -> Result<ayas_core::model::ChatResult> {
+            let prompt = messages[0].content();
+            let first = prompt.split("Response 1:").nth(1).unwrap_or("");
+            let first = first.split("Response 2:").next().unwrap_or("");
+            let winner = if first.contains("good") { "1" } else { "2" };
+            Ok(ayas_core::model::ChatResult::new(ayas_core::message::Message::ai(format!(
+                r#"{{"winner": "{winner}", "margin": 0.8, "rationale": "better"}}"#
+            ))))
+        }
+
+        fn model_name(&self) -> &str {
+            "prefer-good"
+        }
+    }
+
+    #[tokio::test]
+    async fn run_pairwise_win_rate() {
+        let judge = crate::judge::LlmJudge::new(std::sync::Arc::new(PreferGoodModel), "quality");
+        let pairwise = PairwiseEvaluator::new(judge);
+        let runner = EvalRunner::new();
+        let dataset = make_dataset();
+        let config = RunnableConfig::default();
+
+        let report = runner
+            .run_pairwise(
+                &ConstRunnable("good answer"),
+                &ConstRunnable("bad answer"),
+                &pairwise,
+                &dataset,
+                &config,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.results[0].actual_output["b"], "bad answer");
+        assert_eq!(report.aggregate_scores["pairwise"], 1.0);
+
+        let summary = report.pairwise.unwrap();
+        assert_eq!(summary.win_rate_a, 1.0);
+        assert_eq!(summary.outcomes.len(), 2);
+        assert_eq!(summary.outcomes[1].example_id, "ex2");
+        assert_eq!(
+            summary.outcomes[0].winner,
+            crate::pairwise::PairwiseWinner::A
+        );
+    }
+
+    /// Sleeps briefly, fails on "boom", and tracks peak concurrency.
+    struct TrackingRunnable {
+        active: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Runnable for TrackingRunnable {
+        type Input = Value;
+        type Output = Value;
+
+        async fn invoke(&self, input: Self::Input, _config: &RunnableConfig) -> Result<Value> {
+            use std::sync::atomic::Ordering;
+            let now = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            if input == json!("boom") {
+                return Err(AyasError::Other("exploded".into()));
+            }
+            Ok(input)
+        }
+    }
+
+    fn numbered_dataset(n: usize) -> Dataset {
+        let mut ds = Dataset::new("numbered");
+        for i in 0..n {
+            let input = if i == 2 { json!("boom") } else { json!(format!("v{i}")) };
+            ds.add_example(Example {
+                id: format!("ex{i}"),
+                input: input.clone(),
+                expected: Some(input),
+                metadata: Default::default(),
+            });
+        }
+        ds
+    }
+
+    #[tokio::test]
+    async fn concurrent_run_preserves_order_and_collects_errors() {
+        let runnable = Arc::new(TrackingRunnable {
+            active: Default::default(),
+            peak: Default::default(),
+        });
+        let runner = EvalRunner::new()
+            .add_evaluator(ExactMatchEvaluator)
+            .with_max_concurrency(3);
+        let dataset = numbered_dataset(8);
+
+        let report = runner
+            .run(runnable.clone(), &dataset, &RunnableConfig::default())
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = report.results.iter().map(|r| r.example_id.as_str()).collect();
+        assert_eq!(ids, ["ex0", "ex1", "ex2", "ex3", "ex4", "ex5", "ex6", "ex7"]);
+        assert_eq!(report.failed_examples, 1);
+        assert!(report.results[2].error.as_deref().unwrap().contains("exploded"));
+        assert!(report.results[2].scores.is_empty());
+        assert!(report.results[3].error.is_none());
+        assert_eq!(report.aggregate_scores["exact_match"], 1.0);
+
+        let peak = runnable.peak.load(std::sync::atomic::Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 3, "peak concurrency was {peak}");
+    }
+
+    #[tokio::test]
+    async fn progress_callback_reports_each_example() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let runner = EvalRunner::new()
+            .with_max_concurrency(4)
+            .with_progress(move |p| sink.lock().unwrap().push(p.clone()));
+        let dataset = numbered_dataset(5);
+        let runnable = Arc::new(TrackingRunnable {
+            active: Default::default(),
+            peak: Default::default(),
+        });
+
+        runner
+            .run(runnable, &dataset, &RunnableConfig::default())
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 5);
+        let completed: Vec<_> = seen.iter().map(|p| p.completed).collect();
+        assert_eq!(completed, [1, 2, 3, 4, 5]);
+        assert!(seen.iter().all(|p| p.total == 5));
+        let failed: Vec<_> = seen.iter().filter(|p| p.error.is_some()).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].example_id, "ex2");
+    }
+
     #[tokio::test]
     async fn run_no_evaluators() {
         let runner = EvalRunner::new();
@@ -213,7 +578,7 @@ mod tests {
         });
         let config = RunnableConfig::default();
 
-        let report = runner.run(&EchoRunnable, &dataset, &config).await.unwrap();
+        let report = runner.run(Arc::new(EchoRunnable), &dataset, &config).await.unwrap();
         assert_eq!(report.total_examples, 1);
         assert!(report.results[0].scores.is_empty());
         assert!(report.aggregate_scores.is_empty());
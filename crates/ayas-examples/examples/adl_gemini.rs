@@ -174,7 +174,7 @@ fn llm_node_factory(api_key: String, model: String) -> NodeFactory {
                         1024,
                     )
                     .await
-                    .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+                    .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
                     let mut output = serde_json::Map::new();
                     output.insert(output_key, Value::String(result));
@@ -241,7 +241,7 @@ impl ChatModel for ClaudeChatModel {
             .json(&request_body)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -257,7 +257,7 @@ impl ChatModel for ClaudeChatModel {
                 429 => ModelError::RateLimited {
                     retry_after_secs: None,
                 },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
+                _ => ModelError::http(status.as_u16(), error_msg),
             }));
         }
 
@@ -276,15 +276,17 @@ impl ChatModel for ClaudeChatModel {
             input_tokens: api_response.usage.input_tokens,
             output_tokens: api_response.usage.output_tokens,
             total_tokens: api_response.usage.input_tokens + api_response.usage.output_tokens,
+            ..Default::default()
         };
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls: Vec::new(),
+            usage: Some(usage.clone()),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls: Vec::new(),
-                usage: Some(usage.clone()),
-            }),
             usage: Some(usage),
+            ..ChatResult::new(message)
         })
     }
 
@@ -250,7 +250,7 @@ impl ChatModel for GeminiChatModel {
             .json(&request_body)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -265,7 +265,7 @@ impl ChatModel for GeminiChatModel {
                     retry_after_secs: None,
                 }
             } else {
-                ModelError::ApiRequest(format!("HTTP {status}: {body}"))
+                ModelError::http(status.as_u16(), body)
             }));
         }
 
@@ -286,15 +286,17 @@ impl ChatModel for GeminiChatModel {
             input_tokens: u.prompt_token_count,
             output_tokens: u.candidates_token_count,
             total_tokens: u.total_token_count,
+            ..Default::default()
         });
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls: Vec::new(),
+            usage: usage.clone(),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls: Vec::new(),
-                usage: usage.clone(),
-            }),
             usage,
+            ..ChatResult::new(message)
         })
     }
 
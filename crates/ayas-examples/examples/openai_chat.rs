@@ -223,7 +223,7 @@ impl ChatModel for OpenAIChatModel {
             .json(&request_body)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if !status.is_success() {
@@ -239,7 +239,7 @@ impl ChatModel for OpenAIChatModel {
                 429 => ModelError::RateLimited {
                     retry_after_secs: None,
                 },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
+                _ => ModelError::http(status.as_u16(), error_msg),
             }));
         }
 
@@ -258,15 +258,17 @@ impl ChatModel for OpenAIChatModel {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
             total_tokens: u.total_tokens,
+            ..Default::default()
         });
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls: Vec::new(),
+            usage: usage.clone(),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls: Vec::new(),
-                usage: usage.clone(),
-            }),
             usage,
+            ..ChatResult::new(message)
         })
     }
 
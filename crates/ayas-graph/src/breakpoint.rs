@@ -1,21 +1,24 @@
 use std::collections::HashMap;
 
 use chrono::Utc;
-use serde_json::Value;
+use serde_json::{json, Map, Value};
 use uuid::Uuid;
 
 use ayas_checkpoint::prelude::{
-    extract_command, extract_interrupt_value, extract_sends, is_command, is_interrupt, is_send,
-    Checkpoint, CheckpointConfigExt, CheckpointMetadata, CheckpointStore, GraphOutput,
-    INTERRUPT_KEY, SEND_KEY,
+    extract_command, extract_interrupt_key, extract_interrupt_value, extract_sends, is_command,
+    is_interrupt, is_send, Checkpoint, CheckpointConfigExt, CheckpointMetadata, CheckpointStore,
+    GraphOutput, INTERRUPT_KEY, SEND_KEY,
 };
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::{GraphError, Result};
 
-use crate::channel::{Channel, ChannelSpec};
+use crate::channel::Channel;
 use crate::compiled::CompiledStateGraph;
 use crate::constants::END;
 
+/// Checkpoint source of a pause before a breakpoint's node has run.
+pub(crate) const BREAKPOINT_BEFORE_SOURCE: &str = "breakpoint_before";
+
 /// Configuration for dynamic breakpoints during graph execution.
 ///
 /// Breakpoints pause execution at specified nodes, saving a checkpoint
@@ -67,13 +70,19 @@ impl BreakpointConfig {
         self
     }
 
-    fn should_break(&self, node: &str, before: bool, state: &Value) -> bool {
+    /// Whether `node` is listed to break before (or after) it runs,
+    /// regardless of the condition.
+    fn lists(&self, node: &str, before: bool) -> bool {
         let list = if before {
             &self.break_before
         } else {
             &self.break_after
         };
-        if !list.iter().any(|n| n == node) {
+        list.iter().any(|n| n == node)
+    }
+
+    fn should_break(&self, node: &str, before: bool, state: &Value) -> bool {
+        if !self.lists(node, before) {
             return false;
         }
         match &self.condition {
@@ -89,7 +98,77 @@ impl Default for BreakpointConfig {
     }
 }
 
+/// Where a run saves the checkpoint when it pauses at a static breakpoint.
+pub(crate) struct PauseCheckpoint<'a> {
+    pub(crate) channels: &'a HashMap<String, Box<dyn Channel>>,
+    pub(crate) checkpointer: &'a dyn CheckpointStore,
+    pub(crate) thread_id: &'a str,
+    pub(crate) parent_id: Option<String>,
+    pub(crate) step: usize,
+    /// Caller metadata stamped on the run's checkpoints.
+    pub(crate) extra: &'a Map<String, Value>,
+}
+
 impl CompiledStateGraph {
+    /// Pause at a static breakpoint (`StateGraph::interrupt_before` /
+    /// `interrupt_after`) on one of `current`, the nodes of a super-step, if
+    /// any is listed: before the super-step runs or after it has run.
+    ///
+    /// Checkpoints and interrupt values match
+    /// [`invoke_with_breakpoints`](Self::invoke_with_breakpoints): a pause
+    /// before is saved as [`BREAKPOINT_BEFORE_SOURCE`], one after as a
+    /// `"loop"` checkpoint, and resuming runs `pending`.
+    pub(crate) async fn pause_at_static_breakpoint(
+        &self,
+        before: bool,
+        current: &[String],
+        pending: &[String],
+        save: PauseCheckpoint<'_>,
+    ) -> Result<Option<GraphOutput>> {
+        let Some(node) = current
+            .iter()
+            .find(|n| self.static_breakpoints.lists(n, before))
+        else {
+            return Ok(None);
+        };
+        let PauseCheckpoint {
+            channels,
+            checkpointer,
+            thread_id,
+            parent_id,
+            step,
+            extra,
+        } = save;
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4().to_string(),
+            thread_id: thread_id.to_string(),
+            parent_id,
+            step,
+            channel_values: channels
+                .iter()
+                .map(|(k, ch)| (k.clone(), ch.checkpoint()))
+                .collect(),
+            pending_nodes: pending.to_vec(),
+            metadata: CheckpointMetadata::new(
+                if before { BREAKPOINT_BEFORE_SOURCE } else { "loop" },
+                step,
+                Some(node.clone()),
+            )
+            .with_extra(extra.clone()),
+            created_at: Utc::now(),
+        };
+        let checkpoint_id = checkpoint.id.clone();
+        checkpointer.put(checkpoint).await?;
+        Ok(Some(GraphOutput::Interrupted {
+            checkpoint_id,
+            interrupt_value: json!({
+                "breakpoint": if before { "before" } else { "after" },
+                "node": node,
+            }),
+            state: Self::build_state(channels),
+        }))
+    }
+
     /// Execute the graph with checkpoint support and dynamic breakpoints.
     ///
     /// Behaves like `invoke_resumable`, but additionally checks the
@@ -104,9 +183,11 @@ impl CompiledStateGraph {
         checkpointer: &dyn CheckpointStore,
         breakpoints: &BreakpointConfig,
     ) -> Result<GraphOutput> {
+        let config = &config.clone().with_usage_tracking();
         let thread_id = config
             .thread_id()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let checkpoint_extra = config.checkpoint_metadata();
 
         let mut channels: HashMap<String, Box<dyn Channel>> = self
             .channel_specs
@@ -135,15 +216,7 @@ impl CompiledStateGraph {
                 }
             }
 
-            if let Some(resume_val) = config.resume_value() {
-                if let Some(ch) = channels.get_mut("resume_value") {
-                    ch.update(vec![resume_val])?;
-                } else {
-                    let mut ch = ChannelSpec::LastValue { default: Value::Null }.create();
-                    ch.update(vec![resume_val])?;
-                    channels.insert("resume_value".to_string(), ch);
-                }
-            }
+            Self::inject_resume_value(&mut channels, &checkpoint, config)?;
 
             current_nodes = checkpoint.pending_nodes.clone();
             checkpoint_step = checkpoint.step + 1;
@@ -152,7 +225,7 @@ impl CompiledStateGraph {
             if let Value::Object(map) = &input {
                 for (key, value) in map {
                     if let Some(ch) = channels.get_mut(key) {
-                        ch.update(vec![value.clone()])?;
+                        Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                     }
                 }
             }
@@ -162,12 +235,14 @@ impl CompiledStateGraph {
         let mut _node_step = 0usize;
 
         while !current_nodes.is_empty() {
+            Self::check_cancelled(config)?;
             if step >= config.recursion_limit {
                 return Err(GraphError::RecursionLimit {
                     limit: config.recursion_limit,
                 }
                 .into());
             }
+            Self::check_budget(config)?;
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -192,11 +267,12 @@ impl CompiledStateGraph {
                         step: checkpoint_step,
                         channel_values,
                         pending_nodes: pending,
-                        metadata: CheckpointMetadata {
-                            source: "breakpoint_before".into(),
-                            step: checkpoint_step,
-                            node_name: Some(node_name.clone()),
-                        },
+                        metadata: CheckpointMetadata::new(
+                            BREAKPOINT_BEFORE_SOURCE,
+                            checkpoint_step,
+                            Some(node_name.clone()),
+                        )
+                        .with_extra(checkpoint_extra.clone()),
                         created_at: Utc::now(),
                     };
 
@@ -230,11 +306,7 @@ impl CompiledStateGraph {
                     if let Some((update, goto)) = extract_command(&output) {
                         Self::update_channels(&mut channels, &update)?;
                         let state_after = Self::build_state(&channels);
-                        let next = if goto == END {
-                            Vec::new()
-                        } else {
-                            vec![goto]
-                        };
+                        let next: Vec<String> = goto.into_iter().filter(|g| g != END).collect();
 
                         let cp_id = Uuid::new_v4().to_string();
                         let channel_values: HashMap<String, Value> = channels
@@ -249,11 +321,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "command".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "command",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -295,10 +368,14 @@ impl CompiledStateGraph {
                     let next = self.next_nodes(node_name, &state_after);
 
                     let cp_id = Uuid::new_v4().to_string();
-                    let channel_values: HashMap<String, Value> = channels
+                    let mut channel_values: HashMap<String, Value> = channels
                         .iter()
                         .map(|(k, ch)| (k.clone(), ch.checkpoint()))
                         .collect();
+                    // Remember which interrupt is pending so resume can route its answer
+                    if let Some(key) = extract_interrupt_key(&output) {
+                        channel_values.insert(INTERRUPT_KEY.to_string(), json!({ "key": key }));
+                    }
 
                     let checkpoint = Checkpoint {
                         id: cp_id.clone(),
@@ -307,11 +384,12 @@ impl CompiledStateGraph {
                         step: checkpoint_step,
                         channel_values,
                         pending_nodes: next,
-                        metadata: CheckpointMetadata {
-                            source: "interrupt".into(),
-                            step: checkpoint_step,
-                            node_name: Some(node_name.clone()),
-                        },
+                        metadata: CheckpointMetadata::new(
+                            "interrupt",
+                            checkpoint_step,
+                            Some(node_name.clone()),
+                        )
+                        .with_extra(checkpoint_extra.clone()),
                         created_at: Utc::now(),
                     };
 
@@ -342,7 +420,7 @@ impl CompiledStateGraph {
                                 })?;
                             let mut send_state = Self::build_state(&channels);
                             if let (Value::Object(state_map), Value::Object(input_map)) =
-                                (&mut send_state, send.input)
+                                (&mut send_state, send.input.clone())
                             {
                                 for (k, v) in input_map {
                                     state_map.insert(k, v);
@@ -355,6 +433,7 @@ impl CompiledStateGraph {
                                         source: Box::new(e),
                                     }
                                 })?;
+                            let send_output = send.namespace_output(send_output);
                             Self::update_channels(&mut channels, &send_output)?;
                         }
 
@@ -374,11 +453,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "send".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "send",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -423,11 +503,12 @@ impl CompiledStateGraph {
                     step: checkpoint_step,
                     channel_values,
                     pending_nodes: next.clone(),
-                    metadata: CheckpointMetadata {
-                        source: "loop".into(),
-                        step: checkpoint_step,
-                        node_name: Some(node_name.clone()),
-                    },
+                    metadata: CheckpointMetadata::new(
+                        "loop",
+                        checkpoint_step,
+                        Some(node_name.clone()),
+                    )
+                    .with_extra(checkpoint_extra.clone()),
                     created_at: Utc::now(),
                 };
 
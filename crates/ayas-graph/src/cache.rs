@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ayas_core::hash::Fnv1a;
+use serde_json::Value;
+
+/// Storage for node outputs, keyed by the node name and its input.
+///
+/// Used through [`NodeFn::cached`](crate::node::NodeFn::cached). One cache
+/// can be shared by several nodes, since keys include the node name.
+#[async_trait]
+pub trait NodeCache: Send + Sync {
+    /// The output stored under `key`, if still valid. Implementations that
+    /// address entries by [`CacheKey::hash`] must check [`CacheKey::input`]
+    /// too, since the hash can collide.
+    async fn get(&self, key: &CacheKey) -> Option<Value>;
+
+    /// Store `output` under `key`.
+    async fn set(&self, key: CacheKey, output: Value);
+}
+
+/// Identifies a node run: the node name and its input as canonical JSON
+/// (object keys sorted), plus a stable FNV-1a hash of that text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub hash: u64,
+    pub input: String,
+}
+
+/// An in-memory [`NodeCache`] whose entries expire after a fixed TTL.
+///
+/// Expired entries are dropped when looked up and whenever a new entry is
+/// stored.
+pub struct InMemoryNodeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<u64, Entry>>,
+}
+
+struct Entry {
+    stored: Instant,
+    input: String,
+    output: Value,
+}
+
+impl InMemoryNodeCache {
+    /// Create a cache whose entries stay valid for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of entries currently stored, including expired ones not yet
+    /// dropped.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all entries.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl NodeCache for InMemoryNodeCache {
+    async fn get(&self, key: &CacheKey) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get(&key.hash)?;
+        if entry.input != key.input {
+            return None;
+        }
+        if entry.stored.elapsed() < self.ttl {
+            return Some(entry.output.clone());
+        }
+        entries.remove(&key.hash);
+        None
+    }
+
+    async fn set(&self, key: CacheKey, output: Value) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| entry.stored.elapsed() < self.ttl);
+        let entry = Entry {
+            stored: Instant::now(),
+            input: key.input,
+            output,
+        };
+        entries.insert(key.hash, entry);
+    }
+}
+
+/// Cache key for `node` run on `input`. Object keys are sorted so that the
+/// key does not depend on how the state was built.
+pub(crate) fn cache_key(node: &str, input: &Value) -> CacheKey {
+    let input = Value::Array(vec![Value::String(node.to_string()), sorted(input)]).to_string();
+    CacheKey {
+        hash: Fnv1a::hash(input.as_bytes()),
+        input,
+    }
+}
+
+/// `value` with the keys of every object in sorted order.
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            Value::Object(entries.into_iter().map(|(k, v)| (k.clone(), sorted(v))).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn key_ignores_object_key_order() {
+        let mut a = serde_json::Map::new();
+        a.insert("x".into(), json!(1));
+        a.insert("y".into(), json!(2));
+        let mut b = serde_json::Map::new();
+        b.insert("y".into(), json!(2));
+        b.insert("x".into(), json!(1));
+        assert_eq!(
+            cache_key("n", &Value::Object(a)),
+            cache_key("n", &Value::Object(b))
+        );
+    }
+
+    #[test]
+    fn key_depends_on_node_and_input() {
+        let key = cache_key("n", &json!({"x": 1}));
+        assert_ne!(key, cache_key("m", &json!({"x": 1})));
+        assert_ne!(key, cache_key("n", &json!({"x": "1"})));
+        assert_ne!(key, cache_key("n", &json!({"x": [1]})));
+    }
+
+    #[tokio::test]
+    async fn in_memory_entries_expire() {
+        let (one, two) = (cache_key("n", &json!(1)), cache_key("n", &json!(2)));
+        let cache = InMemoryNodeCache::new(Duration::from_secs(60));
+        cache.set(one.clone(), json!("out")).await;
+        assert_eq!(cache.get(&one).await, Some(json!("out")));
+        assert_eq!(cache.get(&two).await, None);
+
+        let expired = InMemoryNodeCache::new(Duration::ZERO);
+        expired.set(one.clone(), json!("out")).await;
+        assert_eq!(expired.get(&one).await, None);
+        assert!(expired.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hash_collision_is_a_miss() {
+        let cache = InMemoryNodeCache::new(Duration::from_secs(60));
+        let key = cache_key("n", &json!({"x": 1}));
+        cache.set(key.clone(), json!("out")).await;
+        let colliding = CacheKey {
+            hash: key.hash,
+            input: cache_key("n", &json!({"x": 2})).input,
+        };
+        assert_eq!(cache.get(&colliding).await, None);
+        assert_eq!(cache.get(&key).await, Some(json!("out")));
+    }
+}
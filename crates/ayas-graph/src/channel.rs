@@ -29,9 +29,12 @@ impl std::fmt::Debug for AggregateOp {
     }
 }
 
+/// A reducer closure for [`ChannelSpec::Reducer`]: `(current, update) -> new`.
+pub type ReducerFn = Arc<dyn Fn(Value, Value) -> Value + Send + Sync>;
+
 /// Specification for creating a channel. Used by `CompiledStateGraph` to
 /// create fresh channel instances for each invocation.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub enum ChannelSpec {
     /// A `LastValue` channel with the given default.
     LastValue { default: Value },
@@ -43,9 +46,109 @@ pub enum ChannelSpec {
     Ephemeral,
     /// A `TopicChannel` (message-queue style).
     Topic { accumulate: bool },
+    /// A `ReducerChannel` folding each update into the value with `reduce`.
+    ///
+    /// Closures are not serializable, so this spec can only be built in code,
+    /// not from a graph definition; checkpoints store the reduced value.
+    Reducer { init: Value, reduce: ReducerFn },
+    /// A `MapMergeChannel` merging object keys with per-key last-writer-wins.
+    MapMerge,
+    /// Another spec whose writes must be of the given kind; see
+    /// [`ChannelSpec::typed`].
+    Typed { kind: ValueKind, spec: Box<ChannelSpec> },
+}
+
+/// The JSON type a typed channel accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+}
+
+impl ValueKind {
+    /// Whether `value` is of this kind.
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            ValueKind::String => value.is_string(),
+            ValueKind::Number => value.is_number(),
+            ValueKind::Bool => value.is_boolean(),
+            ValueKind::Array => value.is_array(),
+            ValueKind::Object => value.is_object(),
+        }
+    }
+
+    /// Name of the JSON type of `value`, for error messages.
+    fn name_of(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ValueKind::String => "string",
+            ValueKind::Number => "number",
+            ValueKind::Bool => "bool",
+            ValueKind::Array => "array",
+            ValueKind::Object => "object",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::fmt::Debug for ChannelSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelSpec::LastValue { default } => {
+                f.debug_struct("LastValue").field("default", default).finish()
+            }
+            ChannelSpec::Append => write!(f, "Append"),
+            ChannelSpec::BinaryOperator { default, op } => f
+                .debug_struct("BinaryOperator")
+                .field("default", default)
+                .field("op", op)
+                .finish(),
+            ChannelSpec::Ephemeral => write!(f, "Ephemeral"),
+            ChannelSpec::Topic { accumulate } => {
+                f.debug_struct("Topic").field("accumulate", accumulate).finish()
+            }
+            ChannelSpec::Reducer { init, .. } => f
+                .debug_struct("Reducer")
+                .field("init", init)
+                .field("reduce", &"...")
+                .finish(),
+            ChannelSpec::MapMerge => write!(f, "MapMerge"),
+            ChannelSpec::Typed { kind, spec } => f
+                .debug_struct("Typed")
+                .field("kind", kind)
+                .field("spec", spec)
+                .finish(),
+        }
+    }
 }
 
 impl ChannelSpec {
+    /// Reject writes to this channel that are not of `kind`.
+    ///
+    /// Untyped channels accept any value. `null` is always accepted so nodes
+    /// can still clear a typed channel.
+    pub fn typed(self, kind: ValueKind) -> Self {
+        ChannelSpec::Typed {
+            kind,
+            spec: Box::new(self),
+        }
+    }
+
     /// Create a fresh `Channel` instance from this spec.
     pub fn create(&self) -> Box<dyn Channel> {
         match self {
@@ -56,6 +159,14 @@ impl ChannelSpec {
             }
             ChannelSpec::Ephemeral => Box::new(EphemeralValue::new()),
             ChannelSpec::Topic { accumulate } => Box::new(TopicChannel::new(*accumulate)),
+            ChannelSpec::Reducer { init, reduce } => {
+                Box::new(ReducerChannel::new(init.clone(), reduce.clone()))
+            }
+            ChannelSpec::MapMerge => Box::new(MapMergeChannel::new()),
+            ChannelSpec::Typed { kind, spec } => Box::new(TypedChannel {
+                inner: spec.create(),
+                kind: *kind,
+            }),
         }
     }
 }
@@ -290,6 +401,181 @@ impl Channel for BinaryOperatorAggregate {
     }
 }
 
+// ---------------------------------------------------------------------------
+// ReducerChannel
+// ---------------------------------------------------------------------------
+
+/// A channel that folds each incoming value into the current one with a
+/// user-supplied reducer, e.g. a deep merge of JSON objects.
+///
+/// Unlike `AggregateOp::Custom`, the reducer takes both values by ownership,
+/// so it can move data instead of cloning it.
+pub struct ReducerChannel {
+    value: Value,
+    init: Value,
+    reduce: ReducerFn,
+}
+
+impl ReducerChannel {
+    /// Create a new `ReducerChannel` starting from `init`.
+    pub fn new(init: Value, reduce: ReducerFn) -> Self {
+        Self {
+            value: init.clone(),
+            init,
+            reduce,
+        }
+    }
+}
+
+impl Channel for ReducerChannel {
+    fn update(&mut self, values: Vec<Value>) -> Result<bool> {
+        if values.is_empty() {
+            return Ok(false);
+        }
+        let old = self.value.clone();
+        for v in values {
+            let current = std::mem::take(&mut self.value);
+            self.value = (self.reduce)(current, v);
+        }
+        Ok(self.value != old)
+    }
+
+    fn get(&self) -> &Value {
+        &self.value
+    }
+
+    fn checkpoint(&self) -> Value {
+        self.value.clone()
+    }
+
+    fn restore(&mut self, data: Value) {
+        self.value = data;
+    }
+
+    fn reset(&mut self) {
+        self.value = self.init.clone();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MapMergeChannel
+// ---------------------------------------------------------------------------
+
+/// A channel holding a JSON object whose keys are updated independently.
+///
+/// Each update must be an object; its keys overwrite the stored ones and all
+/// other keys are kept. This lets parallel nodes each contribute one field
+/// without clobbering each other. Within a step, later writes win per key.
+pub struct MapMergeChannel {
+    value: Value,
+}
+
+impl MapMergeChannel {
+    /// Create a new, empty `MapMergeChannel`.
+    pub fn new() -> Self {
+        Self {
+            value: Value::Object(serde_json::Map::new()),
+        }
+    }
+}
+
+impl Default for MapMergeChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Channel for MapMergeChannel {
+    fn update(&mut self, values: Vec<Value>) -> Result<bool> {
+        let Value::Object(map) = &mut self.value else {
+            unreachable!("MapMergeChannel always holds an object");
+        };
+        let mut changed = false;
+        for value in values {
+            let incoming = match value {
+                Value::Object(incoming) => incoming,
+                other => {
+                    return Err(GraphError::Channel(format!(
+                        "MapMerge channel expects object updates, got {other}"
+                    ))
+                    .into());
+                }
+            };
+            for (key, v) in incoming {
+                if map.get(&key) != Some(&v) {
+                    map.insert(key, v);
+                    changed = true;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    fn get(&self) -> &Value {
+        &self.value
+    }
+
+    fn checkpoint(&self) -> Value {
+        self.value.clone()
+    }
+
+    fn restore(&mut self, data: Value) {
+        self.value = match data {
+            Value::Object(_) => data,
+            _ => Value::Object(serde_json::Map::new()),
+        };
+    }
+
+    fn reset(&mut self) {
+        self.value = Value::Object(serde_json::Map::new());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TypedChannel
+// ---------------------------------------------------------------------------
+
+/// Wraps another channel and rejects writes that are not of a `ValueKind`.
+/// Created from [`ChannelSpec::Typed`].
+struct TypedChannel {
+    inner: Box<dyn Channel>,
+    kind: ValueKind,
+}
+
+impl Channel for TypedChannel {
+    fn update(&mut self, values: Vec<Value>) -> Result<bool> {
+        if let Some(bad) = values.iter().find(|v| !v.is_null() && !self.kind.matches(v)) {
+            return Err(GraphError::Channel(format!(
+                "expected a {} value, got {} ({bad})",
+                self.kind,
+                ValueKind::name_of(bad)
+            ))
+            .into());
+        }
+        self.inner.update(values)
+    }
+
+    fn get(&self) -> &Value {
+        self.inner.get()
+    }
+
+    fn checkpoint(&self) -> Value {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, data: Value) {
+        self.inner.restore(data);
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn on_step_end(&mut self) {
+        self.inner.on_step_end();
+    }
+}
+
 // ---------------------------------------------------------------------------
 // EphemeralValue
 // ---------------------------------------------------------------------------
@@ -798,4 +1084,165 @@ mod tests {
         ch.on_step_end();
         assert_eq!(ch.get(), &json!([]));
     }
+
+    // --- ReducerChannel tests ---
+
+    fn deep_merge(current: Value, update: Value) -> Value {
+        match (current, update) {
+            (Value::Object(mut base), Value::Object(patch)) => {
+                for (key, value) in patch {
+                    let merged = match base.remove(&key) {
+                        Some(existing) => deep_merge(existing, value),
+                        None => value,
+                    };
+                    base.insert(key, merged);
+                }
+                Value::Object(base)
+            }
+            (_, update) => update,
+        }
+    }
+
+    fn deep_merge_spec() -> ChannelSpec {
+        ChannelSpec::Reducer {
+            init: json!({}),
+            reduce: Arc::new(deep_merge),
+        }
+    }
+
+    #[test]
+    fn reducer_deep_merge() {
+        let mut ch = deep_merge_spec().create();
+        ch.update(vec![json!({"user": {"name": "Ann", "prefs": {"theme": "dark"}}})])
+            .unwrap();
+        ch.update(vec![
+            json!({"user": {"prefs": {"lang": "en"}}}),
+            json!({"user": {"name": "Anna"}, "count": 1}),
+        ])
+        .unwrap();
+        assert_eq!(
+            ch.get(),
+            &json!({
+                "user": {"name": "Anna", "prefs": {"theme": "dark", "lang": "en"}},
+                "count": 1
+            })
+        );
+    }
+
+    #[test]
+    fn reducer_reports_unchanged() {
+        let mut ch = deep_merge_spec().create();
+        assert!(ch.update(vec![json!({"a": 1})]).unwrap());
+        assert!(!ch.update(vec![json!({"a": 1})]).unwrap());
+        assert!(!ch.update(vec![]).unwrap());
+    }
+
+    #[test]
+    fn reducer_join_with_separator() {
+        let join = |a: Value, b: Value| match (a.as_str(), b.as_str()) {
+            (Some(""), Some(b)) => json!(b),
+            (Some(a), Some(b)) => json!(format!("{a}, {b}")),
+            _ => a,
+        };
+        let mut ch = ReducerChannel::new(json!(""), Arc::new(join));
+        ch.update(vec![json!("x"), json!("y")]).unwrap();
+        ch.update(vec![json!("z")]).unwrap();
+        assert_eq!(ch.get(), &json!("x, y, z"));
+    }
+
+    #[test]
+    fn reducer_checkpoint_restore_reset() {
+        let mut ch = deep_merge_spec().create();
+        ch.update(vec![json!({"a": 1})]).unwrap();
+        let cp = ch.checkpoint();
+        ch.update(vec![json!({"b": 2})]).unwrap();
+        ch.restore(cp);
+        assert_eq!(ch.get(), &json!({"a": 1}));
+        ch.reset();
+        assert_eq!(ch.get(), &json!({}));
+    }
+
+    #[test]
+    fn reducer_spec_debug_hides_closure() {
+        let dbg = format!("{:?}", deep_merge_spec());
+        assert!(dbg.starts_with("Reducer"));
+    }
+
+    // --- MapMergeChannel tests ---
+
+    #[test]
+    fn map_merge_combines_keys_in_one_step() {
+        let mut ch = ChannelSpec::MapMerge.create();
+        assert_eq!(ch.get(), &json!({}));
+        let changed = ch
+            .update(vec![json!({"a": 1}), json!({"b": 2}), json!({"c": 3})])
+            .unwrap();
+        assert!(changed);
+        assert_eq!(ch.get(), &json!({"a": 1, "b": 2, "c": 3}));
+    }
+
+    #[test]
+    fn map_merge_last_writer_wins_per_key() {
+        let mut ch = MapMergeChannel::new();
+        ch.update(vec![json!({"a": 1, "b": 1})]).unwrap();
+        ch.update(vec![json!({"a": 2}), json!({"a": 3})]).unwrap();
+        assert_eq!(ch.get(), &json!({"a": 3, "b": 1}));
+        assert!(!ch.update(vec![json!({"b": 1})]).unwrap());
+    }
+
+    #[test]
+    fn map_merge_rejects_non_object() {
+        let mut ch = MapMergeChannel::new();
+        let err = ch.update(vec![json!([1, 2])]).unwrap_err();
+        assert!(err.to_string().contains("expects object"));
+    }
+
+    #[test]
+    fn map_merge_checkpoint_restore_reset() {
+        let mut ch = MapMergeChannel::new();
+        ch.update(vec![json!({"a": 1})]).unwrap();
+        let cp = ch.checkpoint();
+        ch.update(vec![json!({"b": 2})]).unwrap();
+        ch.restore(cp);
+        assert_eq!(ch.get(), &json!({"a": 1}));
+        ch.reset();
+        assert_eq!(ch.get(), &json!({}));
+    }
+
+    // --- Typed channel tests ---
+
+    #[test]
+    fn typed_channel_accepts_matching_kind() {
+        let spec = ChannelSpec::LastValue { default: json!("") }.typed(ValueKind::String);
+        let mut ch = spec.create();
+        assert!(ch.update(vec![json!("hi")]).unwrap());
+        assert_eq!(ch.get(), &json!("hi"));
+        assert!(ch.update(vec![Value::Null]).unwrap());
+    }
+
+    #[test]
+    fn typed_channel_rejects_mismatch() {
+        let spec = ChannelSpec::LastValue { default: json!("") }.typed(ValueKind::String);
+        let mut ch = spec.create();
+        let err = ch.update(vec![json!(42)]).unwrap_err().to_string();
+        assert!(err.contains("expected a string value, got number (42)"), "{err}");
+        assert_eq!(ch.get(), &json!(""));
+    }
+
+    #[test]
+    fn typed_channel_checks_every_write() {
+        let mut ch = ChannelSpec::Append.typed(ValueKind::Number).create();
+        assert!(ch.update(vec![json!(1), json!("two")]).is_err());
+        assert_eq!(ch.get(), &json!([]));
+        ch.update(vec![json!(1), json!(2.5)]).unwrap();
+        assert_eq!(ch.get(), &json!([1, 2.5]));
+    }
+
+    #[test]
+    fn value_kind_matches() {
+        assert!(ValueKind::Bool.matches(&json!(true)));
+        assert!(ValueKind::Array.matches(&json!([])));
+        assert!(ValueKind::Object.matches(&json!({})));
+        assert!(!ValueKind::Number.matches(&json!("1")));
+    }
 }
@@ -1,21 +1,26 @@
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use chrono::Utc;
-use serde_json::Value;
+use serde_json::{json, Value};
 use uuid::Uuid;
 
 use ayas_checkpoint::prelude::{
-    extract_command, extract_interrupt_value, extract_sends, is_command, is_interrupt, is_send,
-    Checkpoint, CheckpointConfigExt, CheckpointMetadata, CheckpointStore, GraphOutput,
-    SendDirective, INTERRUPT_KEY, SEND_KEY,
+    extract_command, extract_interrupt_key, extract_interrupt_value, extract_sends, is_command,
+    is_interrupt, is_send, pending_interrupt_key, Checkpoint, CheckpointConfigExt,
+    CheckpointMetadata, CheckpointStore, GraphOutput, SendDirective, INTERRUPT_KEY,
+    RESUME_VALUES_CHANNEL, SEND_KEY,
 };
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::{AyasError, GraphError, Result};
+use ayas_core::model::ChatStreamEvent;
 use ayas_core::runnable::Runnable;
 
 use tokio::sync::mpsc;
 
+use crate::breakpoint::{BREAKPOINT_BEFORE_SOURCE, BreakpointConfig, PauseCheckpoint};
 use crate::channel::{Channel, ChannelSpec};
 use crate::constants::END;
 use crate::edge::{ConditionalEdge, ConditionalFanOutEdge};
@@ -28,6 +33,8 @@ pub struct StepInfo {
     pub step_number: usize,
     pub node_name: String,
     pub state_after: Value,
+    /// Time spent in the node's `invoke`.
+    pub duration: Duration,
 }
 
 /// A compiled state graph ready for execution.
@@ -42,6 +49,9 @@ pub struct CompiledStateGraph {
     pub(crate) channel_specs: HashMap<String, ChannelSpec>,
     pub(crate) entry_point: String,
     pub(crate) finish_points: Vec<String>,
+    /// `StateGraph::interrupt_before` / `interrupt_after`, applied by the
+    /// resumable executors.
+    pub(crate) static_breakpoints: BreakpointConfig,
 }
 
 impl CompiledStateGraph {
@@ -92,6 +102,28 @@ impl CompiledStateGraph {
         Value::Object(map)
     }
 
+    /// Emit a [`StreamEvent::Interrupted`] for `output` if it is a pause, and
+    /// hand it back.
+    async fn stream_interrupted(
+        tx: &mpsc::Sender<StreamEvent>,
+        output: GraphOutput,
+    ) -> GraphOutput {
+        if let GraphOutput::Interrupted {
+            checkpoint_id,
+            interrupt_value,
+            ..
+        } = &output
+        {
+            let _ = tx
+                .send(StreamEvent::Interrupted {
+                    checkpoint_id: checkpoint_id.clone(),
+                    interrupt_value: interrupt_value.clone(),
+                })
+                .await;
+        }
+        output
+    }
+
     /// Update channels from a node's partial output.
     pub(crate) fn update_channels(
         channels: &mut HashMap<String, Box<dyn Channel>>,
@@ -100,13 +132,144 @@ impl CompiledStateGraph {
         if let Value::Object(map) = output {
             for (key, value) in map {
                 if let Some(ch) = channels.get_mut(key) {
-                    ch.update(vec![value.clone()])?;
+                    Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                 }
             }
         }
         Ok(())
     }
 
+    /// Update a single channel, naming it in any channel error.
+    pub(crate) fn update_channel(
+        key: &str,
+        ch: &mut dyn Channel,
+        values: Vec<Value>,
+    ) -> Result<bool> {
+        ch.update(values).map_err(|e| match e {
+            AyasError::Graph(GraphError::Channel(msg)) => {
+                GraphError::Channel(format!("channel '{key}': {msg}")).into()
+            }
+            other => other,
+        })
+    }
+
+    /// Route the config's resume value into channels restored from `checkpoint`.
+    ///
+    /// A checkpoint waiting on a keyed interrupt gets the value under
+    /// `resume_values[key]`, keeping answers to earlier interrupts; otherwise it
+    /// goes to the `resume_value` channel. Either channel is created on demand.
+    pub(crate) fn inject_resume_value(
+        channels: &mut HashMap<String, Box<dyn Channel>>,
+        checkpoint: &Checkpoint,
+        config: &RunnableConfig,
+    ) -> Result<()> {
+        let Some(resume_val) = config.resume_value() else {
+            return Ok(());
+        };
+        let pending_key = pending_interrupt_key(checkpoint);
+
+        if let Some(target) = config.resume_key()
+            && pending_key.as_deref() != Some(target.as_str())
+        {
+            let pending = pending_key
+                .map(|k| format!("interrupt '{k}'"))
+                .unwrap_or_else(|| "an unkeyed interrupt".into());
+            return Err(GraphError::Checkpoint(format!(
+                "Resume targets interrupt '{target}' but checkpoint '{}' waits on {pending}",
+                checkpoint.id
+            ))
+            .into());
+        }
+
+        let (channel_name, value) = match pending_key {
+            Some(key) => {
+                // Channels added here aren't in the specs, so restore them by hand
+                let mut answers = channels
+                    .get(RESUME_VALUES_CHANNEL)
+                    .map(|ch| ch.get().clone())
+                    .or_else(|| checkpoint.channel_values.get(RESUME_VALUES_CHANNEL).cloned())
+                    .and_then(|v| v.as_object().cloned())
+                    .unwrap_or_default();
+                answers.insert(key, resume_val);
+                (RESUME_VALUES_CHANNEL, Value::Object(answers))
+            }
+            None => ("resume_value", resume_val),
+        };
+
+        let ch = channels
+            .entry(channel_name.to_string())
+            .or_insert_with(|| ChannelSpec::LastValue { default: Value::Null }.create());
+        ch.update(vec![value])?;
+        Ok(())
+    }
+
+    /// Fail once `config.cancel` has been triggered.
+    ///
+    /// Checked before each super-step; nodes already running are not
+    /// interrupted.
+    pub(crate) fn check_cancelled(config: &RunnableConfig) -> std::result::Result<(), GraphError> {
+        if config.is_cancelled() {
+            return Err(GraphError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Fail once the usage reported into `config.usage` goes over
+    /// `config.budget`.
+    ///
+    /// Checked before each super-step, so the step that crosses the limit
+    /// completes and no further node runs. A cost limit without a priced
+    /// accumulator fails with [`GraphError::UnpricedBudget`] before the first
+    /// step.
+    pub(crate) fn check_budget(config: &RunnableConfig) -> std::result::Result<(), GraphError> {
+        let (Some(budget), Some(usage)) = (&config.budget, &config.usage) else {
+            return Ok(());
+        };
+        if budget.max_cost.is_some() && !usage.is_priced() {
+            return Err(GraphError::UnpricedBudget);
+        }
+        let spent = usage.spent();
+        if budget.is_exceeded_by(&spent) {
+            return Err(GraphError::BudgetExceeded {
+                tokens: spent.tokens,
+                cost: spent.cost,
+            });
+        }
+        Ok(())
+    }
+
+    /// Invoke `node` with a `stream_tx` in its config, passing each chat event
+    /// the node emits to `forward` while it runs.
+    pub(crate) async fn invoke_node_streaming<F, Fut>(
+        node: &NodeFn,
+        state: Value,
+        config: &RunnableConfig,
+        mut forward: F,
+    ) -> Result<Value>
+    where
+        F: FnMut(ChatStreamEvent) -> Fut,
+        Fut: Future,
+    {
+        let (token_tx, mut token_rx) = mpsc::unbounded_channel();
+        let node_config = config.clone().with_stream_tx(token_tx);
+        let run = node.invoke(state, &node_config);
+        tokio::pin!(run);
+
+        let output = loop {
+            tokio::select! {
+                output = &mut run => break output,
+                Some(event) = token_rx.recv() => {
+                    forward(event).await;
+                }
+            }
+        };
+        // Events sent just before the node returned
+        while let Ok(event) = token_rx.try_recv() {
+            forward(event).await;
+        }
+        output
+    }
+
     /// Determine the next nodes to execute after a given node.
     pub(crate) fn next_nodes(&self, current: &str, state: &Value) -> Vec<String> {
         // Check fan-out conditional edges first (highest priority for multi-target)
@@ -144,7 +307,8 @@ impl CompiledStateGraph {
     ///
     /// All sends receive the same base state snapshot (built from channels before
     /// sends start), with each send's private input merged on top. Results are
-    /// applied to channels in the original send order for determinism.
+    /// applied to channels in the original send order for determinism, each
+    /// namespaced under its send's `key` when one is set.
     async fn execute_sends_parallel(
         nodes: &HashMap<String, NodeFn>,
         sends: Vec<SendDirective>,
@@ -154,8 +318,8 @@ impl CompiledStateGraph {
         let base_state = Self::build_state(channels);
         let mut join_set = tokio::task::JoinSet::new();
 
-        for (idx, send) in sends.into_iter().enumerate() {
-            let node_name = send.node;
+        for (idx, mut send) in sends.into_iter().enumerate() {
+            let node_name = send.node.clone();
             let send_node = nodes
                 .get(&node_name)
                 .ok_or_else(|| {
@@ -167,7 +331,7 @@ impl CompiledStateGraph {
 
             let mut send_state = base_state.clone();
             if let (Value::Object(state_map), Value::Object(input_map)) =
-                (&mut send_state, send.input)
+                (&mut send_state, std::mem::take(&mut send.input))
             {
                 for (k, v) in input_map {
                     state_map.insert(k, v);
@@ -182,7 +346,7 @@ impl CompiledStateGraph {
                         source: Box::new(e),
                     }
                 })?;
-                Ok::<_, AyasError>((idx, output))
+                Ok::<_, AyasError>((idx, send.namespace_output(output)))
             });
         }
 
@@ -213,6 +377,8 @@ impl CompiledStateGraph {
     where
         F: Fn(StepInfo) + Send,
     {
+        let config = &config.clone().with_usage_tracking();
+
         // Create fresh channels for this invocation
         let mut channels: HashMap<String, Box<dyn Channel>> = self
             .channel_specs
@@ -224,7 +390,7 @@ impl CompiledStateGraph {
         if let Value::Object(map) = &input {
             for (key, value) in map {
                 if let Some(ch) = channels.get_mut(key) {
-                    ch.update(vec![value.clone()])?;
+                    Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                 }
             }
         }
@@ -235,6 +401,7 @@ impl CompiledStateGraph {
         let mut node_step = 0;
 
         while !current_nodes.is_empty() {
+            Self::check_cancelled(config)?;
             // Check recursion limit
             if step >= config.recursion_limit {
                 return Err(
@@ -244,6 +411,7 @@ impl CompiledStateGraph {
                     .into(),
                 );
             }
+            Self::check_budget(config)?;
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -258,12 +426,14 @@ impl CompiledStateGraph {
                 })?;
 
                 // Execute node
+                let started = Instant::now();
                 let output = node.invoke(state.clone(), config).await.map_err(|e| {
                     GraphError::NodeExecution {
                         node: node_name.clone(),
                         source: Box::new(e),
                     }
                 })?;
+                let duration = started.elapsed();
 
                 // Priority: command → send → normal
                 if is_command(&output) {
@@ -273,12 +443,11 @@ impl CompiledStateGraph {
                         observer(StepInfo {
                             step_number: node_step,
                             node_name: node_name.clone(),
+                            duration,
                             state_after: state_after.clone(),
                         });
                         node_step += 1;
-                        if goto != END {
-                            all_next.push(goto);
-                        }
+                        all_next.extend(goto.into_iter().filter(|g| g != END));
                         continue;
                     }
                 }
@@ -300,6 +469,7 @@ impl CompiledStateGraph {
                         observer(StepInfo {
                             step_number: node_step,
                             node_name: node_name.clone(),
+                            duration,
                             state_after: state_after.clone(),
                         });
                         node_step += 1;
@@ -317,6 +487,7 @@ impl CompiledStateGraph {
                 observer(StepInfo {
                     step_number: node_step,
                     node_name: node_name.clone(),
+                    duration,
                     state_after: state_after.clone(),
                 });
 
@@ -347,14 +518,17 @@ impl CompiledStateGraph {
     /// via a tokio mpsc channel. The caller manages the receiver and can
     /// spawn its own task to consume events.
     ///
-    /// Send errors (e.g. receiver dropped) are silently ignored so that the
-    /// graph always runs to completion.
+    /// Send errors (e.g. receiver dropped) are silently ignored, so the graph
+    /// runs to completion unless `config.cancel` is triggered, in which case
+    /// it stops with [`GraphError::Cancelled`] at the next super-step.
     pub async fn invoke_with_streaming(
         &self,
         input: Value,
         config: &RunnableConfig,
         tx: mpsc::Sender<StreamEvent>,
     ) -> Result<Value> {
+        let config = &config.clone().with_usage_tracking();
+
         // Create fresh channels for this invocation
         let mut channels: HashMap<String, Box<dyn Channel>> = self
             .channel_specs
@@ -366,7 +540,7 @@ impl CompiledStateGraph {
         if let Value::Object(map) = &input {
             for (key, value) in map {
                 if let Some(ch) = channels.get_mut(key) {
-                    ch.update(vec![value.clone()])?;
+                    Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                 }
             }
         }
@@ -377,6 +551,14 @@ impl CompiledStateGraph {
         let mut node_step = 0;
 
         while !current_nodes.is_empty() {
+            if let Err(err) = Self::check_cancelled(config) {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: err.to_string(),
+                    })
+                    .await;
+                return Err(err.into());
+            }
             // Check recursion limit
             if step >= config.recursion_limit {
                 let err = GraphError::RecursionLimit {
@@ -389,6 +571,14 @@ impl CompiledStateGraph {
                     .await;
                 return Err(err.into());
             }
+            if let Err(err) = Self::check_budget(config) {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: err.to_string(),
+                    })
+                    .await;
+                return Err(err.into());
+            }
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -410,12 +600,18 @@ impl CompiledStateGraph {
                     ))
                 })?;
 
-                // Execute node
-                let output = node.invoke(state.clone(), config).await.map_err(|e| {
-                    GraphError::NodeExecution {
-                        node: node_name.clone(),
-                        source: Box::new(e),
-                    }
+                // Execute node, relaying any model tokens it streams
+                let output = Self::invoke_node_streaming(node, state.clone(), config, |event| {
+                    tx.send(StreamEvent::Token {
+                        node_name: node_name.clone(),
+                        step: node_step,
+                        event,
+                    })
+                })
+                .await
+                .map_err(|e| GraphError::NodeExecution {
+                    node: node_name.clone(),
+                    source: Box::new(e),
                 })?;
 
                 // Priority: command → send → normal
@@ -433,9 +629,7 @@ impl CompiledStateGraph {
                             .await;
                         node_step += 1;
 
-                        if goto != END {
-                            all_next.push(goto);
-                        }
+                        all_next.extend(goto.into_iter().filter(|g| g != END));
                         continue;
                     }
                 }
@@ -517,7 +711,11 @@ impl CompiledStateGraph {
     /// After each node execution a checkpoint is saved via `checkpointer`.
     /// If a node output contains `"__interrupt__"`, execution pauses and
     /// returns `GraphOutput::Interrupted` with a checkpoint that can be
-    /// resumed later.
+    /// resumed later. It also pauses before a super-step that would run a
+    /// node listed in `StateGraph::interrupt_before`, and after one that ran
+    /// a node listed in `StateGraph::interrupt_after`, with the same
+    /// interrupt value and checkpoint as
+    /// [`invoke_with_breakpoints`](Self::invoke_with_breakpoints).
     pub async fn invoke_resumable(
         &self,
         input: Value,
@@ -539,9 +737,11 @@ impl CompiledStateGraph {
     where
         F: Fn(StepInfo) + Send,
     {
+        let config = &config.clone().with_usage_tracking();
         let thread_id = config
             .thread_id()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let checkpoint_extra = config.checkpoint_metadata();
 
         // Create fresh channels
         let mut channels: HashMap<String, Box<dyn Channel>> = self
@@ -554,6 +754,8 @@ impl CompiledStateGraph {
         let mut step = 0usize;
         let mut checkpoint_step = 0usize;
         let mut parent_checkpoint_id: Option<String> = None;
+        // Resuming from a pause before a breakpoint runs its node
+        let mut skip_break_before = false;
 
         // Check if resuming from a checkpoint
         if let Some(checkpoint_id) = config.checkpoint_id() {
@@ -574,18 +776,10 @@ impl CompiledStateGraph {
             }
 
             // Inject resume_value if provided
-            if let Some(resume_val) = config.resume_value() {
-                if let Some(ch) = channels.get_mut("resume_value") {
-                    ch.update(vec![resume_val])?;
-                } else {
-                    let mut ch =
-                        ChannelSpec::LastValue { default: Value::Null }.create();
-                    ch.update(vec![resume_val])?;
-                    channels.insert("resume_value".to_string(), ch);
-                }
-            }
+            Self::inject_resume_value(&mut channels, &checkpoint, config)?;
 
             current_nodes = checkpoint.pending_nodes.clone();
+            skip_break_before = checkpoint.metadata.source == BREAKPOINT_BEFORE_SOURCE;
             checkpoint_step = checkpoint.step + 1;
             parent_checkpoint_id = Some(checkpoint.id.clone());
         } else {
@@ -593,7 +787,7 @@ impl CompiledStateGraph {
             if let Value::Object(map) = &input {
                 for (key, value) in map {
                     if let Some(ch) = channels.get_mut(key) {
-                        ch.update(vec![value.clone()])?;
+                        Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                     }
                 }
             }
@@ -604,12 +798,34 @@ impl CompiledStateGraph {
 
         // Execute Pregel loop
         while !current_nodes.is_empty() {
+            Self::check_cancelled(config)?;
             if step >= config.recursion_limit {
                 return Err(GraphError::RecursionLimit {
                     limit: config.recursion_limit,
                 }
                 .into());
             }
+            Self::check_budget(config)?;
+
+            if !std::mem::take(&mut skip_break_before)
+                && let Some(paused) = self
+                    .pause_at_static_breakpoint(
+                        true,
+                        &current_nodes,
+                        &current_nodes,
+                        PauseCheckpoint {
+                            channels: &channels,
+                            checkpointer,
+                            thread_id: &thread_id,
+                            parent_id: parent_checkpoint_id.clone(),
+                            step: checkpoint_step,
+                            extra: &checkpoint_extra,
+                        },
+                    )
+                    .await?
+            {
+                return Ok(paused);
+            }
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -622,12 +838,14 @@ impl CompiledStateGraph {
                     ))
                 })?;
 
+                let started = Instant::now();
                 let output = node.invoke(state.clone(), config).await.map_err(|e| {
                     GraphError::NodeExecution {
                         node: node_name.clone(),
                         source: Box::new(e),
                     }
                 })?;
+                let duration = started.elapsed();
 
                 // Priority: command → interrupt → send → normal
 
@@ -636,15 +854,12 @@ impl CompiledStateGraph {
                     if let Some((update, goto)) = extract_command(&output) {
                         Self::update_channels(&mut channels, &update)?;
                         let state_after = Self::build_state(&channels);
-                        let next = if goto == END {
-                            Vec::new()
-                        } else {
-                            vec![goto]
-                        };
+                        let next: Vec<String> = goto.into_iter().filter(|g| g != END).collect();
 
                         observer(StepInfo {
                             step_number: node_step,
                             node_name: node_name.clone(),
+                            duration,
                             state_after: state_after.clone(),
                         });
 
@@ -661,11 +876,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "command".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "command",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -696,10 +912,14 @@ impl CompiledStateGraph {
                     let next = self.next_nodes(node_name, &state_after);
 
                     let cp_id = Uuid::new_v4().to_string();
-                    let channel_values: HashMap<String, Value> = channels
+                    let mut channel_values: HashMap<String, Value> = channels
                         .iter()
                         .map(|(k, ch)| (k.clone(), ch.checkpoint()))
                         .collect();
+                    // Remember which interrupt is pending so resume can route its answer
+                    if let Some(key) = extract_interrupt_key(&output) {
+                        channel_values.insert(INTERRUPT_KEY.to_string(), json!({ "key": key }));
+                    }
 
                     let checkpoint = Checkpoint {
                         id: cp_id.clone(),
@@ -708,11 +928,12 @@ impl CompiledStateGraph {
                         step: checkpoint_step,
                         channel_values,
                         pending_nodes: next,
-                        metadata: CheckpointMetadata {
-                            source: "interrupt".into(),
-                            step: checkpoint_step,
-                            node_name: Some(node_name.clone()),
-                        },
+                        metadata: CheckpointMetadata::new(
+                            "interrupt",
+                            checkpoint_step,
+                            Some(node_name.clone()),
+                        )
+                        .with_extra(checkpoint_extra.clone()),
                         created_at: Utc::now(),
                     };
 
@@ -721,6 +942,7 @@ impl CompiledStateGraph {
                     observer(StepInfo {
                         step_number: node_step,
                         node_name: node_name.clone(),
+                        duration,
                         state_after: state_after.clone(),
                     });
 
@@ -751,6 +973,7 @@ impl CompiledStateGraph {
                         observer(StepInfo {
                             step_number: node_step,
                             node_name: node_name.clone(),
+                            duration,
                             state_after: state_after.clone(),
                         });
 
@@ -767,11 +990,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "send".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "send",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -794,6 +1018,7 @@ impl CompiledStateGraph {
                 observer(StepInfo {
                     step_number: node_step,
                     node_name: node_name.clone(),
+                    duration,
                     state_after: state_after.clone(),
                 });
 
@@ -810,11 +1035,12 @@ impl CompiledStateGraph {
                     step: checkpoint_step,
                     channel_values,
                     pending_nodes: next.clone(),
-                    metadata: CheckpointMetadata {
-                        source: "loop".into(),
-                        step: checkpoint_step,
-                        node_name: Some(node_name.clone()),
-                    },
+                    metadata: CheckpointMetadata::new(
+                        "loop",
+                        checkpoint_step,
+                        Some(node_name.clone()),
+                    )
+                    .with_extra(checkpoint_extra.clone()),
                     created_at: Utc::now(),
                 };
 
@@ -835,6 +1061,25 @@ impl CompiledStateGraph {
                 ch.on_step_end();
             }
 
+            if let Some(paused) = self
+                .pause_at_static_breakpoint(
+                    false,
+                    &current_nodes,
+                    &all_next,
+                    PauseCheckpoint {
+                        channels: &channels,
+                        checkpointer,
+                        thread_id: &thread_id,
+                        parent_id: parent_checkpoint_id.clone(),
+                        step: checkpoint_step,
+                        extra: &checkpoint_extra,
+                    },
+                )
+                .await?
+            {
+                return Ok(paused);
+            }
+
             current_nodes = all_next;
             step += 1;
         }
@@ -858,6 +1103,8 @@ impl CompiledStateGraph {
     ) -> Result<Value> {
         use ayas_core::stream::{StreamEvent as CoreEvent, StreamMode};
 
+        let config = &config.clone().with_usage_tracking();
+
         let has = |m: StreamMode| modes.contains(&m);
 
         let mut channels: HashMap<String, Box<dyn Channel>> = self
@@ -869,7 +1116,7 @@ impl CompiledStateGraph {
         if let Value::Object(map) = &input {
             for (key, value) in map {
                 if let Some(ch) = channels.get_mut(key) {
-                    ch.update(vec![value.clone()])?;
+                    Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                 }
             }
         }
@@ -879,6 +1126,10 @@ impl CompiledStateGraph {
         let mut node_step = 0;
 
         while !current_nodes.is_empty() {
+            if let Err(err) = Self::check_cancelled(config) {
+                let _ = tx.send(CoreEvent::Error { message: err.to_string() }).await;
+                return Err(err.into());
+            }
             if step >= config.recursion_limit {
                 let err = GraphError::RecursionLimit {
                     limit: config.recursion_limit,
@@ -886,6 +1137,10 @@ impl CompiledStateGraph {
                 let _ = tx.send(CoreEvent::Error { message: err.to_string() }).await;
                 return Err(err.into());
             }
+            if let Err(err) = Self::check_budget(config) {
+                let _ = tx.send(CoreEvent::Error { message: err.to_string() }).await;
+                return Err(err.into());
+            }
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -908,24 +1163,60 @@ impl CompiledStateGraph {
                     ))
                 })?;
 
-                let output = node.invoke(state.clone(), config).await.map_err(|e| {
-                    GraphError::NodeExecution {
-                        node: node_name.clone(),
-                        source: Box::new(e),
-                    }
+                let output = if has(StreamMode::Messages) {
+                    Self::invoke_node_streaming(node, state.clone(), config, |event| {
+                        let tx = &tx;
+                        async move {
+                            if let ChatStreamEvent::Token(chunk) = event {
+                                let _ = tx.send(CoreEvent::Message { chunk }).await;
+                            }
+                        }
+                    })
+                    .await
+                } else {
+                    node.invoke(state.clone(), config).await
+                }
+                .map_err(|e| GraphError::NodeExecution {
+                    node: node_name.clone(),
+                    source: Box::new(e),
                 })?;
 
-                // Normal flow (command/send/interrupt handling omitted for
-                // simplicity in this streaming method; use
-                // invoke_with_streaming for the full feature set)
-                Self::update_channels(&mut channels, &output)?;
+                // Priority: command → send → normal (interrupt handling is
+                // omitted here; use invoke_resumable_with_streaming for it)
+                let command = if is_command(&output) {
+                    extract_command(&output)
+                } else {
+                    None
+                };
+                let sends = if command.is_none() && is_send(&output) {
+                    extract_sends(&output)
+                } else {
+                    None
+                };
+
+                let (update, goto) = if let Some((update, goto)) = command {
+                    Self::update_channels(&mut channels, &update)?;
+                    (update, Some(goto))
+                } else if let Some(sends) = sends {
+                    let mut filtered = output;
+                    if let Value::Object(ref mut map) = filtered {
+                        map.remove(SEND_KEY);
+                    }
+                    Self::update_channels(&mut channels, &filtered)?;
+                    Self::execute_sends_parallel(&self.nodes, sends, &mut channels, config)
+                        .await?;
+                    (filtered, None)
+                } else {
+                    Self::update_channels(&mut channels, &output)?;
+                    (output, None)
+                };
 
                 let state_after = Self::build_state(&channels);
 
                 if has(StreamMode::Updates) {
                     let _ = tx.send(CoreEvent::Updates {
                         node: node_name.clone(),
-                        data: output.clone(),
+                        data: update,
                     }).await;
                 }
 
@@ -945,8 +1236,10 @@ impl CompiledStateGraph {
                     }).await;
                 }
 
-                let next = self.next_nodes(node_name, &state_after);
-                all_next.extend(next);
+                match goto {
+                    Some(goto) => all_next.extend(goto.into_iter().filter(|g| g != END)),
+                    None => all_next.extend(self.next_nodes(node_name, &state_after)),
+                }
                 node_step += 1;
 
                 if has(StreamMode::Debug) && !all_next.is_empty() {
@@ -987,9 +1280,11 @@ impl CompiledStateGraph {
         checkpointer: &dyn CheckpointStore,
         tx: mpsc::Sender<StreamEvent>,
     ) -> Result<GraphOutput> {
+        let config = &config.clone().with_usage_tracking();
         let thread_id = config
             .thread_id()
             .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let checkpoint_extra = config.checkpoint_metadata();
 
         // Create fresh channels
         let mut channels: HashMap<String, Box<dyn Channel>> = self
@@ -1002,6 +1297,8 @@ impl CompiledStateGraph {
         let mut step = 0usize;
         let mut checkpoint_step = 0usize;
         let mut parent_checkpoint_id: Option<String> = None;
+        // Resuming from a pause before a breakpoint runs its node
+        let mut skip_break_before = false;
 
         // Check if resuming from a checkpoint
         if let Some(checkpoint_id) = config.checkpoint_id() {
@@ -1020,25 +1317,17 @@ impl CompiledStateGraph {
                 }
             }
 
-            if let Some(resume_val) = config.resume_value() {
-                if let Some(ch) = channels.get_mut("resume_value") {
-                    ch.update(vec![resume_val])?;
-                } else {
-                    let mut ch =
-                        ChannelSpec::LastValue { default: Value::Null }.create();
-                    ch.update(vec![resume_val])?;
-                    channels.insert("resume_value".to_string(), ch);
-                }
-            }
+            Self::inject_resume_value(&mut channels, &checkpoint, config)?;
 
             current_nodes = checkpoint.pending_nodes.clone();
+            skip_break_before = checkpoint.metadata.source == BREAKPOINT_BEFORE_SOURCE;
             checkpoint_step = checkpoint.step + 1;
             parent_checkpoint_id = Some(checkpoint.id.clone());
         } else {
             if let Value::Object(map) = &input {
                 for (key, value) in map {
                     if let Some(ch) = channels.get_mut(key) {
-                        ch.update(vec![value.clone()])?;
+                        Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                     }
                 }
             }
@@ -1048,6 +1337,14 @@ impl CompiledStateGraph {
         let mut node_step = 0usize;
 
         while !current_nodes.is_empty() {
+            if let Err(err) = Self::check_cancelled(config) {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: err.to_string(),
+                    })
+                    .await;
+                return Err(err.into());
+            }
             if step >= config.recursion_limit {
                 let err = GraphError::RecursionLimit {
                     limit: config.recursion_limit,
@@ -1059,6 +1356,34 @@ impl CompiledStateGraph {
                     .await;
                 return Err(err.into());
             }
+            if let Err(err) = Self::check_budget(config) {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: err.to_string(),
+                    })
+                    .await;
+                return Err(err.into());
+            }
+
+            if !std::mem::take(&mut skip_break_before)
+                && let Some(paused) = self
+                    .pause_at_static_breakpoint(
+                        true,
+                        &current_nodes,
+                        &current_nodes,
+                        PauseCheckpoint {
+                            channels: &channels,
+                            checkpointer,
+                            thread_id: &thread_id,
+                            parent_id: parent_checkpoint_id.clone(),
+                            step: checkpoint_step,
+                            extra: &checkpoint_extra,
+                        },
+                    )
+                    .await?
+            {
+                return Ok(Self::stream_interrupted(&tx, paused).await);
+            }
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -1091,11 +1416,7 @@ impl CompiledStateGraph {
                     if let Some((update, goto)) = extract_command(&output) {
                         Self::update_channels(&mut channels, &update)?;
                         let state_after = Self::build_state(&channels);
-                        let next = if goto == END {
-                            Vec::new()
-                        } else {
-                            vec![goto]
-                        };
+                        let next: Vec<String> = goto.into_iter().filter(|g| g != END).collect();
 
                         let _ = tx
                             .send(StreamEvent::NodeEnd {
@@ -1118,11 +1439,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "command".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "command",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -1152,10 +1474,14 @@ impl CompiledStateGraph {
                     let next = self.next_nodes(node_name, &state_after);
 
                     let cp_id = Uuid::new_v4().to_string();
-                    let channel_values: HashMap<String, Value> = channels
+                    let mut channel_values: HashMap<String, Value> = channels
                         .iter()
                         .map(|(k, ch)| (k.clone(), ch.checkpoint()))
                         .collect();
+                    // Remember which interrupt is pending so resume can route its answer
+                    if let Some(key) = extract_interrupt_key(&output) {
+                        channel_values.insert(INTERRUPT_KEY.to_string(), json!({ "key": key }));
+                    }
 
                     let checkpoint = Checkpoint {
                         id: cp_id.clone(),
@@ -1164,11 +1490,12 @@ impl CompiledStateGraph {
                         step: checkpoint_step,
                         channel_values,
                         pending_nodes: next,
-                        metadata: CheckpointMetadata {
-                            source: "interrupt".into(),
-                            step: checkpoint_step,
-                            node_name: Some(node_name.clone()),
-                        },
+                        metadata: CheckpointMetadata::new(
+                            "interrupt",
+                            checkpoint_step,
+                            Some(node_name.clone()),
+                        )
+                        .with_extra(checkpoint_extra.clone()),
                         created_at: Utc::now(),
                     };
 
@@ -1233,11 +1560,12 @@ impl CompiledStateGraph {
                             step: checkpoint_step,
                             channel_values,
                             pending_nodes: next.clone(),
-                            metadata: CheckpointMetadata {
-                                source: "send".into(),
-                                step: checkpoint_step,
-                                node_name: Some(node_name.clone()),
-                            },
+                            metadata: CheckpointMetadata::new(
+                                "send",
+                                checkpoint_step,
+                                Some(node_name.clone()),
+                            )
+                            .with_extra(checkpoint_extra.clone()),
                             created_at: Utc::now(),
                         };
 
@@ -1278,11 +1606,12 @@ impl CompiledStateGraph {
                     step: checkpoint_step,
                     channel_values,
                     pending_nodes: next.clone(),
-                    metadata: CheckpointMetadata {
-                        source: "loop".into(),
-                        step: checkpoint_step,
-                        node_name: Some(node_name.clone()),
-                    },
+                    metadata: CheckpointMetadata::new(
+                        "loop",
+                        checkpoint_step,
+                        Some(node_name.clone()),
+                    )
+                    .with_extra(checkpoint_extra.clone()),
                     created_at: Utc::now(),
                 };
 
@@ -1301,6 +1630,25 @@ impl CompiledStateGraph {
                 ch.on_step_end();
             }
 
+            if let Some(paused) = self
+                .pause_at_static_breakpoint(
+                    false,
+                    &current_nodes,
+                    &all_next,
+                    PauseCheckpoint {
+                        channels: &channels,
+                        checkpointer,
+                        thread_id: &thread_id,
+                        parent_id: parent_checkpoint_id.clone(),
+                        step: checkpoint_step,
+                        extra: &checkpoint_extra,
+                    },
+                )
+                .await?
+            {
+                return Ok(Self::stream_interrupted(&tx, paused).await);
+            }
+
             current_nodes = all_next;
             step += 1;
         }
@@ -1321,6 +1669,7 @@ impl Runnable for CompiledStateGraph {
     type Output = Value;
 
     async fn invoke(&self, input: Self::Input, config: &RunnableConfig) -> Result<Self::Output> {
+        let config = &config.clone().with_usage_tracking();
         // Create fresh channels for this invocation
         let mut channels: HashMap<String, Box<dyn Channel>> = self
             .channel_specs
@@ -1332,7 +1681,7 @@ impl Runnable for CompiledStateGraph {
         if let Value::Object(map) = &input {
             for (key, value) in map {
                 if let Some(ch) = channels.get_mut(key) {
-                    ch.update(vec![value.clone()])?;
+                    Self::update_channel(key, ch.as_mut(), vec![value.clone()])?;
                 }
             }
         }
@@ -1342,6 +1691,7 @@ impl Runnable for CompiledStateGraph {
         let mut step = 0;
 
         while !current_nodes.is_empty() {
+            Self::check_cancelled(config)?;
             // Check recursion limit
             if step >= config.recursion_limit {
                 return Err(
@@ -1351,6 +1701,7 @@ impl Runnable for CompiledStateGraph {
                     .into(),
                 );
             }
+            Self::check_budget(config)?;
 
             let mut all_next: Vec<String> = Vec::new();
 
@@ -1376,9 +1727,7 @@ impl Runnable for CompiledStateGraph {
                 if is_command(&output) {
                     if let Some((update, goto)) = extract_command(&output) {
                         Self::update_channels(&mut channels, &update)?;
-                        if goto != END {
-                            all_next.push(goto);
-                        }
+                        all_next.extend(goto.into_iter().filter(|g| g != END));
                         continue;
                     }
                 }
@@ -1444,6 +1793,11 @@ mod tests {
     /// Helper: build a 3-node linear graph: a → b → c
     /// Channel "count" (LastValue, default 0); each node increments count by 1.
     fn build_linear_graph() -> CompiledStateGraph {
+        linear_state_graph().compile().unwrap()
+    }
+
+    /// The uncompiled graph behind [`build_linear_graph`].
+    fn linear_state_graph() -> StateGraph {
         let mut g = StateGraph::new();
         g.add_last_value_channel("count", json!(0));
 
@@ -1466,7 +1820,7 @@ mod tests {
         g.add_edge("a", "b");
         g.add_edge("b", "c");
         g.set_finish_point("c");
-        g.compile().unwrap()
+        g
     }
 
     fn default_config() -> RunnableConfig {
@@ -1745,6 +2099,136 @@ mod tests {
         assert_eq!(checkpoints.len(), 5);
     }
 
+    #[tokio::test]
+    async fn test_static_interrupt_before_node() {
+        let mut g = linear_state_graph();
+        g.interrupt_before(&["b"]);
+        let graph = g.compile().unwrap();
+        let store = MemoryCheckpointStore::new();
+        let config = default_config().with_thread_id("thread-before");
+
+        let result = graph
+            .invoke_resumable(json!({}), &config, &store)
+            .await
+            .unwrap();
+        let GraphOutput::Interrupted {
+            checkpoint_id,
+            interrupt_value,
+            state,
+        } = result
+        else {
+            panic!("Expected Interrupted");
+        };
+        assert_eq!(interrupt_value, json!({"breakpoint": "before", "node": "b"}));
+        // b has not run yet
+        assert_eq!(state["count"], json!(1));
+        let checkpoint = store.get("thread-before", &checkpoint_id).await.unwrap().unwrap();
+        assert_eq!(checkpoint.pending_nodes, vec!["b".to_string()]);
+
+        // Resuming runs the pending node instead of pausing again
+        let resume_config = default_config()
+            .with_thread_id("thread-before")
+            .with_checkpoint_id(&checkpoint_id);
+        let result = graph
+            .invoke_resumable(json!({}), &resume_config, &store)
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.into_value()["count"], json!(3));
+    }
+
+    #[tokio::test]
+    async fn test_static_interrupt_after_node() {
+        let mut g = linear_state_graph();
+        g.interrupt_after(&["b"]);
+        let graph = g.compile().unwrap();
+        let store = MemoryCheckpointStore::new();
+        let config = default_config().with_thread_id("thread-after");
+
+        let result = graph
+            .invoke_resumable(json!({}), &config, &store)
+            .await
+            .unwrap();
+        let GraphOutput::Interrupted {
+            checkpoint_id,
+            interrupt_value,
+            state,
+        } = result
+        else {
+            panic!("Expected Interrupted");
+        };
+        assert_eq!(interrupt_value, json!({"breakpoint": "after", "node": "b"}));
+        // b has run, c has not
+        assert_eq!(state["count"], json!(2));
+        let checkpoint = store.get("thread-after", &checkpoint_id).await.unwrap().unwrap();
+        assert_eq!(checkpoint.pending_nodes, vec!["c".to_string()]);
+
+        let resume_config = default_config()
+            .with_thread_id("thread-after")
+            .with_checkpoint_id(&checkpoint_id);
+        let result = graph
+            .invoke_resumable(json!({}), &resume_config, &store)
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.into_value()["count"], json!(3));
+    }
+
+    #[tokio::test]
+    async fn test_static_interrupt_streams_interrupted_event() {
+        let mut g = linear_state_graph();
+        g.interrupt_before(&["b"]);
+        let graph = g.compile().unwrap();
+        let store = MemoryCheckpointStore::new();
+        let config = default_config().with_thread_id("thread-stream");
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let result = graph
+            .invoke_resumable_with_streaming(json!({}), &config, &store, tx)
+            .await
+            .unwrap();
+        let GraphOutput::Interrupted { checkpoint_id, .. } = result else {
+            panic!("Expected Interrupted");
+        };
+        let mut interrupted = None;
+        while let Ok(event) = rx.try_recv() {
+            if let StreamEvent::Interrupted {
+                checkpoint_id,
+                interrupt_value,
+            } = event
+            {
+                interrupted = Some((checkpoint_id, interrupt_value));
+            }
+        }
+        assert_eq!(
+            interrupted,
+            Some((
+                checkpoint_id.clone(),
+                json!({"breakpoint": "before", "node": "b"})
+            ))
+        );
+
+        let resume_config = default_config()
+            .with_thread_id("thread-stream")
+            .with_checkpoint_id(&checkpoint_id);
+        let (tx, _rx) = mpsc::channel(64);
+        let result = graph
+            .invoke_resumable_with_streaming(json!({}), &resume_config, &store, tx)
+            .await
+            .unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.into_value()["count"], json!(3));
+    }
+
+    #[tokio::test]
+    async fn test_static_interrupt_does_not_affect_invoke() {
+        let mut g = linear_state_graph();
+        g.interrupt_before(&["b"]).interrupt_after(&["c"]);
+        let graph = g.compile().unwrap();
+        let result = graph.invoke(json!({}), &default_config()).await.unwrap();
+        assert_eq!(result["count"], json!(3));
+    }
+
     #[tokio::test]
     async fn test_invoke_resumable_interrupt() {
         let mut g = StateGraph::new();
@@ -1863,6 +2347,119 @@ mod tests {
         assert_eq!(final_state["resume_value"], json!("approved"));
     }
 
+    /// Two keyed approvals: draft → legal (interrupt) → finance (interrupt) → publish.
+    fn build_two_approval_graph() -> CompiledStateGraph {
+        use ayas_checkpoint::prelude::interrupt_output_for;
+
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("doc", json!(""));
+        g.add_last_value_channel("published", json!(null));
+
+        g.add_node(NodeFn::new("draft", |_state: Value, _cfg| async move {
+            Ok(json!({"doc": "contract"}))
+        }))
+        .unwrap();
+        g.add_node(NodeFn::new("legal", |_state: Value, _cfg| async move {
+            Ok(interrupt_output_for("legal", json!("Legal approval?")))
+        }))
+        .unwrap();
+        g.add_node(NodeFn::new("finance", |_state: Value, _cfg| async move {
+            Ok(interrupt_output_for("finance", json!("Finance approval?")))
+        }))
+        .unwrap();
+        g.add_node(NodeFn::new("publish", |state: Value, _cfg| async move {
+            Ok(json!({"published": state["resume_values"].clone()}))
+        }))
+        .unwrap();
+
+        g.set_entry_point("draft");
+        g.add_edge("draft", "legal");
+        g.add_edge("legal", "finance");
+        g.add_edge("finance", "publish");
+        g.set_finish_point("publish");
+        g.compile().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_invoke_resumable_two_keyed_interrupts() {
+        let graph = build_two_approval_graph();
+        let store = MemoryCheckpointStore::new();
+        let thread = "thread-two-approvals";
+
+        let first = graph
+            .invoke_resumable(json!({}), &default_config().with_thread_id(thread), &store)
+            .await
+            .unwrap();
+        let legal_cp = match first {
+            GraphOutput::Interrupted {
+                checkpoint_id,
+                interrupt_value,
+                ..
+            } => {
+                assert_eq!(interrupt_value, json!("Legal approval?"));
+                checkpoint_id
+            }
+            _ => panic!("Expected Interrupted at legal"),
+        };
+
+        let config = default_config()
+            .with_thread_id(thread)
+            .with_checkpoint_id(&legal_cp)
+            .with_resume_value_for("legal", json!("ok by legal"));
+        let second = graph.invoke_resumable(json!({}), &config, &store).await.unwrap();
+        let finance_cp = match second {
+            GraphOutput::Interrupted {
+                checkpoint_id,
+                interrupt_value,
+                state,
+            } => {
+                assert_eq!(interrupt_value, json!("Finance approval?"));
+                assert_eq!(state["resume_values"], json!({"legal": "ok by legal"}));
+                checkpoint_id
+            }
+            _ => panic!("Expected Interrupted at finance"),
+        };
+
+        // An unkeyed resume still routes to the pending interrupt's key
+        let config = default_config()
+            .with_thread_id(thread)
+            .with_checkpoint_id(&finance_cp)
+            .with_resume_value(json!("ok by finance"));
+        let result = graph.invoke_resumable(json!({}), &config, &store).await.unwrap();
+
+        let final_state = result.into_value();
+        assert_eq!(
+            final_state["published"],
+            json!({"legal": "ok by legal", "finance": "ok by finance"})
+        );
+        assert_eq!(final_state.get("resume_value"), None);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_resume_rejects_wrong_interrupt() {
+        let graph = build_two_approval_graph();
+        let store = MemoryCheckpointStore::new();
+        let thread = "thread-wrong-key";
+
+        let first = graph
+            .invoke_resumable(json!({}), &default_config().with_thread_id(thread), &store)
+            .await
+            .unwrap();
+        let GraphOutput::Interrupted { checkpoint_id, .. } = first else {
+            panic!("Expected Interrupted");
+        };
+
+        let config = default_config()
+            .with_thread_id(thread)
+            .with_checkpoint_id(&checkpoint_id)
+            .with_resume_value_for("finance", json!("too early"));
+        let err = graph
+            .invoke_resumable(json!({}), &config, &store)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("legal"));
+    }
+
     #[tokio::test]
     async fn test_invoke_resumable_thread_isolation() {
         let graph = build_linear_graph();
@@ -2104,6 +2701,84 @@ mod tests {
         assert_eq!(result.into_value()["count"], json!(43));
     }
 
+    /// supervisor updates "plan" and dispatches worker_a and worker_b via one command.
+    fn build_supervisor_graph() -> CompiledStateGraph {
+        use ayas_checkpoint::prelude::command_fan_out;
+
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("plan", json!(null));
+        g.add_channel("log", crate::channel::ChannelSpec::Append);
+
+        g.add_node(NodeFn::new("supervisor", |_state: Value, _cfg| async move {
+            Ok(command_fan_out(json!({"plan": "split"}), ["worker_a", "worker_b"]))
+        }))
+        .unwrap();
+        for name in ["worker_a", "worker_b"] {
+            g.add_node(NodeFn::new(name, move |state: Value, _cfg| async move {
+                Ok(json!({"log": format!("{name}:{}", state["plan"].as_str().unwrap_or(""))}))
+            }))
+            .unwrap();
+            g.add_edge(name, END);
+        }
+        // Never taken: the command overrides static edges
+        g.add_node(NodeFn::new("unused", |_state: Value, _cfg| async move {
+            Ok(json!({"log": "unused"}))
+        }))
+        .unwrap();
+
+        g.set_entry_point("supervisor");
+        g.add_edge("supervisor", "unused");
+        // Only the command reaches the workers. A conditional edge without a
+        // path map counts every node as a possible target, which gets them
+        // past the reachability check; at run time it always routes to END.
+        g.add_conditional_edges(ConditionalEdge::new(
+            "unused",
+            |_: &Value| END.to_string(),
+            None,
+        ));
+        g.set_finish_point("unused");
+        g.compile().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_command_fan_out_to_multiple_nodes() {
+        let graph = build_supervisor_graph();
+        let result = graph.invoke(json!({}), &default_config()).await.unwrap();
+
+        assert_eq!(result["plan"], json!("split"));
+        let mut log: Vec<&str> = result["log"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        log.sort();
+        assert_eq!(log, ["worker_a:split", "worker_b:split"]);
+    }
+
+    #[tokio::test]
+    async fn test_command_fan_out_resumable() {
+        let graph = build_supervisor_graph();
+        let store = MemoryCheckpointStore::new();
+        let config = default_config().with_thread_id("thread-cmd-fan-out");
+
+        let result = graph
+            .invoke_resumable(json!({}), &config, &store)
+            .await
+            .unwrap();
+        assert_eq!(result.into_value()["log"].as_array().unwrap().len(), 2);
+
+        // The command checkpoint records both workers as pending
+        let checkpoints = store.list("thread-cmd-fan-out").await.unwrap();
+        let command_cp = checkpoints
+            .iter()
+            .find(|cp| cp.metadata.source == "command")
+            .unwrap();
+        let mut pending = command_cp.pending_nodes.clone();
+        pending.sort();
+        assert_eq!(pending, ["worker_a", "worker_b"]);
+    }
+
     // ---- Send API tests ----
 
     #[tokio::test]
@@ -2232,6 +2907,44 @@ mod tests {
         assert_eq!(log[1], json!("done_b"));
     }
 
+    #[tokio::test]
+    async fn test_send_results_map_merge() {
+        // Parallel sends each contribute one key to a MapMerge channel
+        let mut g = StateGraph::new();
+        g.add_map_merge_channel("fields");
+
+        g.add_node(NodeFn::new(
+            "dispatcher",
+            |_state: Value, _cfg| async move {
+                Ok(send_output(vec![
+                    SendDirective::new("worker", json!({"key": "a", "value": 1})),
+                    SendDirective::new("worker", json!({"key": "b", "value": 2})),
+                    SendDirective::new("worker", json!({"key": "c", "value": 3})),
+                ]))
+            },
+        ))
+        .unwrap();
+        g.add_node(NodeFn::new("worker", |state: Value, _cfg| async move {
+            let key = state["key"].as_str().unwrap_or("?").to_string();
+            let mut field = serde_json::Map::new();
+            field.insert(key, state["value"].clone());
+            Ok(json!({"fields": field}))
+        }))
+        .unwrap();
+
+        g.set_entry_point("dispatcher");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "dispatcher",
+            |_: &Value| END.to_string(),
+            None,
+        ));
+
+        let graph = g.compile().unwrap();
+        let config = default_config();
+        let result = graph.invoke(json!({}), &config).await.unwrap();
+        assert_eq!(result["fields"], json!({"a": 1, "b": 2, "c": 3}));
+    }
+
     #[tokio::test]
     async fn test_send_with_normal_output() {
         // Send output can contain both __send__ and normal channel updates
@@ -2381,4 +3094,83 @@ mod tests {
             elapsed.as_millis()
         );
     }
+
+    #[tokio::test]
+    async fn test_send_keys_correlate_out_of_order_branches() {
+        let mut g = StateGraph::new();
+        g.add_map_merge_channel("results");
+
+        g.add_node(NodeFn::new(
+            "dispatcher",
+            |_state: Value, _cfg| async move {
+                let sends = [30, 20, 10]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, delay)| {
+                        SendDirective::new("worker", json!({"delay": delay, "id": i}))
+                            .with_key(i.to_string())
+                    })
+                    .collect();
+                Ok(send_output(sends))
+            },
+        ))
+        .unwrap();
+        // Later sends finish first
+        g.add_node(NodeFn::new("worker", |state: Value, _cfg| async move {
+            let delay = state["delay"].as_u64().unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            Ok(json!({"results": format!("worker_{}", state["id"])}))
+        }))
+        .unwrap();
+
+        g.set_entry_point("dispatcher");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "dispatcher",
+            |_: &Value| END.to_string(),
+            None,
+        ));
+
+        let graph = g.compile().unwrap();
+        let result = graph.invoke(json!({}), &default_config()).await.unwrap();
+        assert_eq!(
+            result["results"],
+            json!({"0": "worker_0", "1": "worker_1", "2": "worker_2"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_writes_merge_into_map_channel() {
+        let mut g = StateGraph::new();
+        g.add_map_merge_channel("fields");
+
+        g.add_node(NodeFn::new(
+            "dispatcher",
+            |_state: Value, _cfg| async move {
+                Ok(send_output(vec![
+                    SendDirective::new("worker", json!({"key": "a", "n": 1})),
+                    SendDirective::new("worker", json!({"key": "b", "n": 2})),
+                    SendDirective::new("worker", json!({"key": "c", "n": 3})),
+                ]))
+            },
+        ))
+        .unwrap();
+        g.add_node(NodeFn::new("worker", |state: Value, _cfg| async move {
+            let mut fields = serde_json::Map::new();
+            let key = state["key"].as_str().unwrap_or("?").to_string();
+            fields.insert(key, state["n"].clone());
+            Ok(json!({"fields": fields}))
+        }))
+        .unwrap();
+
+        g.set_entry_point("dispatcher");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "dispatcher",
+            |_: &Value| END.to_string(),
+            None,
+        ));
+
+        let graph = g.compile().unwrap();
+        let result = graph.invoke(json!({}), &default_config()).await.unwrap();
+        assert_eq!(result["fields"], json!({"a": 1, "b": 2, "c": 3}));
+    }
 }
@@ -1,27 +1,34 @@
 pub mod breakpoint;
+pub mod cache;
 pub mod channel;
 pub mod compiled;
 pub mod constants;
 pub mod edge;
+pub mod metrics;
 pub mod node;
+pub mod plan;
 pub mod state_graph;
 pub mod stream;
 pub mod subgraph;
 pub mod time_travel;
+mod visualize;
 
 /// Prelude module for convenient imports.
 pub mod prelude {
     pub use ayas_checkpoint::prelude::GraphOutput;
 
     pub use crate::breakpoint::BreakpointConfig;
+    pub use crate::cache::{CacheKey, InMemoryNodeCache, NodeCache};
     pub use crate::channel::{
         AggregateOp, AppendChannel, BinaryOperatorAggregate, Channel, ChannelSpec, EphemeralValue,
-        LastValue, TopicChannel,
+        LastValue, MapMergeChannel, ReducerChannel, ReducerFn, TopicChannel, ValueKind,
     };
     pub use crate::compiled::{CompiledStateGraph, StepInfo};
     pub use crate::constants::{END, START};
     pub use crate::edge::{ConditionalEdge, ConditionalFanOutEdge, Edge};
+    pub use crate::metrics::{ExecutionMetrics, NodeMetrics};
     pub use crate::node::NodeFn;
+    pub use crate::plan::{PlanCertainty, PlanStep};
     pub use crate::state_graph::StateGraph;
     pub use crate::stream::StreamEvent;
     pub use ayas_core::stream::{
@@ -29,4 +36,5 @@ pub mod prelude {
     };
     pub use crate::subgraph::subgraph_node;
     pub use crate::time_travel::{fork_from_checkpoint, get_state_history, replay_to_step};
+    pub use tokio_util::sync::CancellationToken;
 }
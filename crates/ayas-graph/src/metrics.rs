@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use ayas_core::config::RunnableConfig;
+use ayas_core::error::{AyasError, GraphError, Result};
+
+use crate::compiled::{CompiledStateGraph, StepInfo};
+
+/// Call count and timing of one node over a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeMetrics {
+    /// Number of times the node was invoked, including failed calls.
+    pub calls: usize,
+    /// Time spent in successful calls.
+    pub total_duration: Duration,
+    /// Number of calls that returned an error.
+    pub errors: usize,
+}
+
+/// Per-node metrics collected by [`CompiledStateGraph::invoke_with_metrics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionMetrics {
+    /// Node name → metrics. Nodes that never ran are absent.
+    pub nodes: HashMap<String, NodeMetrics>,
+}
+
+impl ExecutionMetrics {
+    /// Metrics for one node, if it ran.
+    pub fn get(&self, node: &str) -> Option<&NodeMetrics> {
+        self.nodes.get(node)
+    }
+
+    /// The node with the largest total duration.
+    pub fn slowest(&self) -> Option<(&str, &NodeMetrics)> {
+        self.nodes
+            .iter()
+            .max_by_key(|(_, m)| m.total_duration)
+            .map(|(name, m)| (name.as_str(), m))
+    }
+
+    /// Add a completed step.
+    pub fn record_step(&mut self, step: &StepInfo) {
+        let entry = self.nodes.entry(step.node_name.clone()).or_default();
+        entry.calls += 1;
+        entry.total_duration += step.duration;
+    }
+
+    /// Add a failed call of `node`.
+    pub fn record_error(&mut self, node: &str) {
+        let entry = self.nodes.entry(node.to_string()).or_default();
+        entry.calls += 1;
+        entry.errors += 1;
+    }
+}
+
+impl CompiledStateGraph {
+    /// Execute the graph like `invoke`, collecting per-node call counts and
+    /// timings.
+    ///
+    /// Timing comes from the [`StepInfo`] observer, so only nodes on the main
+    /// path are measured (not `Send` targets). The metrics are returned
+    /// whether or not the run succeeds; a node that failed it counts one
+    /// error.
+    pub async fn invoke_with_metrics(
+        &self,
+        input: Value,
+        config: &RunnableConfig,
+    ) -> (Result<Value>, ExecutionMetrics) {
+        let metrics = Mutex::new(ExecutionMetrics::default());
+        let output = self
+            .invoke_with_observer(input, config, |step| {
+                metrics.lock().unwrap().record_step(&step);
+            })
+            .await;
+        let mut metrics = metrics.into_inner().unwrap();
+        if let Err(AyasError::Graph(GraphError::NodeExecution { node, .. })) = &output {
+            metrics.record_error(node);
+        }
+        (output, metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use serde_json::json;
+
+    fn sleeper(name: &str, millis: u64) -> NodeFn {
+        NodeFn::new(name.to_string(), move |_state: Value, _cfg| async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            Ok(json!({}))
+        })
+    }
+
+    #[tokio::test]
+    async fn metrics_count_calls_and_time() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("n", json!(0));
+        g.add_node(sleeper("fast", 1)).unwrap();
+        g.add_node(NodeFn::new("slow", |state: Value, _cfg| async move {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            let n = state["n"].as_i64().unwrap_or(0);
+            Ok(json!({"n": n + 1}))
+        }))
+        .unwrap();
+        g.set_entry_point("fast");
+        g.add_edge("fast", "slow");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "slow",
+            |state: &Value| {
+                if state["n"].as_i64().unwrap_or(0) < 2 {
+                    "fast".to_string()
+                } else {
+                    END.to_string()
+                }
+            },
+            None,
+        ));
+
+        let graph = g.compile().unwrap();
+        let (output, metrics) = graph
+            .invoke_with_metrics(json!({}), &RunnableConfig::default())
+            .await;
+        assert_eq!(output.unwrap()["n"], json!(2));
+
+        let slow = metrics.get("slow").unwrap();
+        assert_eq!(slow.calls, 2);
+        assert_eq!(slow.errors, 0);
+        assert!(slow.total_duration >= Duration::from_millis(60));
+        assert_eq!(metrics.get("fast").unwrap().calls, 2);
+        assert_eq!(metrics.slowest().unwrap().0, "slow");
+    }
+
+    #[tokio::test]
+    async fn failed_run_keeps_metrics_and_counts_the_error() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("n", json!(0));
+        g.add_node(sleeper("ok", 1)).unwrap();
+        g.add_node(NodeFn::new("boom", |_state: Value, _cfg| async move {
+            Err(AyasError::Other("boom".into()))
+        }))
+        .unwrap();
+        g.set_entry_point("ok");
+        g.add_edge("ok", "boom");
+        g.set_finish_point("boom");
+
+        let graph = g.compile().unwrap();
+        let (output, metrics) = graph
+            .invoke_with_metrics(json!({}), &RunnableConfig::default())
+            .await;
+        assert!(output.unwrap_err().to_string().contains("boom"));
+
+        let boom = metrics.get("boom").unwrap();
+        assert_eq!((boom.calls, boom.errors), (1, 1));
+        let ok = metrics.get("ok").unwrap();
+        assert_eq!((ok.calls, ok.errors), (1, 0));
+    }
+
+    #[test]
+    fn record_error_counts_a_failed_call() {
+        let mut metrics = ExecutionMetrics::default();
+        metrics.record_error("boom");
+        let boom = metrics.get("boom").unwrap();
+        assert_eq!((boom.calls, boom.errors), (1, 1));
+    }
+}
@@ -2,14 +2,16 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
+use ayas_checkpoint::prelude::is_interrupt;
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::Result;
 use serde_json::Value;
 
-type AsyncNodeFn =
-    dyn Fn(Value, RunnableConfig) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>>
-        + Send
-        + Sync;
+use crate::cache::{NodeCache, cache_key};
+
+type NodeFuture = Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
+
+type AsyncNodeFn = dyn Fn(Value, RunnableConfig) -> NodeFuture + Send + Sync;
 
 /// A graph node that wraps an async function operating on JSON state.
 ///
@@ -42,6 +44,56 @@ impl NodeFn {
         }
     }
 
+    /// Serve this node's output from `cache` when it already ran on the same
+    /// state.
+    ///
+    /// Only use this for deterministic nodes: the config is not part of the
+    /// key, and a hit skips the node's side effects. Errors and interrupts
+    /// are never cached.
+    pub fn cached(self, cache: Arc<dyn NodeCache>) -> Self {
+        self.cache_by(cache, None)
+    }
+
+    /// Like [`cached`](Self::cached), but keyed only on the state channels in
+    /// `keys`, so that changes to channels the node does not read still hit.
+    pub fn cached_on(self, cache: Arc<dyn NodeCache>, keys: &[&str]) -> Self {
+        let keys = keys.iter().map(|k| k.to_string()).collect();
+        self.cache_by(cache, Some(keys))
+    }
+
+    fn cache_by(self, cache: Arc<dyn NodeCache>, keys: Option<Vec<String>>) -> Self {
+        let name = self.name.clone();
+        let inner = self.func;
+        let func = move |state: Value, config: RunnableConfig| -> NodeFuture {
+            let key = match &keys {
+                Some(keys) => {
+                    let relevant: serde_json::Map<String, Value> = keys
+                        .iter()
+                        .map(|k| (k.clone(), state.get(k).cloned().unwrap_or(Value::Null)))
+                        .collect();
+                    cache_key(&name, &Value::Object(relevant))
+                }
+                None => cache_key(&name, &state),
+            };
+            let cache = cache.clone();
+            let inner = inner.clone();
+            Box::pin(async move {
+                if let Some(output) = cache.get(&key).await {
+                    return Ok(output);
+                }
+                let output = inner(state, config).await?;
+                if !is_interrupt(&output) {
+                    cache.set(key, output.clone()).await;
+                }
+                Ok(output)
+            })
+        };
+        Self {
+            name: self.name,
+            func: Arc::new(func),
+        }
+    }
+
     /// Get the name of this node.
     pub fn name(&self) -> &str {
         &self.name
@@ -88,4 +140,30 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("node failed"));
     }
+
+    #[tokio::test]
+    async fn cached_on_ignores_other_channels() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        use crate::cache::InMemoryNodeCache;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let node = NodeFn::new("len", move |state: Value, _config| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(json!({"len": state["text"].as_str().unwrap().len()})) }
+        })
+        .cached_on(Arc::new(InMemoryNodeCache::new(Duration::from_secs(60))), &["text"]);
+
+        let config = RunnableConfig::default();
+        let first = node.invoke(json!({"text": "abc", "step": 1}), &config).await.unwrap();
+        let second = node.invoke(json!({"text": "abc", "step": 2}), &config).await.unwrap();
+        assert_eq!(first, json!({"len": 3}));
+        assert_eq!(second, first);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        node.invoke(json!({"text": "abcd", "step": 2}), &config).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::channel::Channel;
+use crate::compiled::CompiledStateGraph;
+
+/// Super-steps [`CompiledStateGraph::plan`] projects before giving up on a
+/// cycle; matches the default recursion limit.
+const PLAN_STEP_LIMIT: usize = 25;
+
+/// How sure a plan is that a step will run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanCertainty {
+    /// Reached only through static edges from the entry point.
+    Certain,
+    /// Reached through a conditional or fan-out edge, which was evaluated
+    /// against the input state but may route elsewhere once nodes have
+    /// written their outputs.
+    Uncertain,
+}
+
+/// One node in a projected execution plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanStep {
+    /// Super-step the node would run in (0 for the entry point).
+    pub step: usize,
+    pub node: String,
+    pub certainty: PlanCertainty,
+}
+
+impl CompiledStateGraph {
+    /// Project the order nodes would run in for `input`, without invoking any.
+    ///
+    /// Nodes are treated as producing no output, so every edge is evaluated
+    /// against the initial state built from `input`. Nodes within a super-step
+    /// are listed in name order, as the executor runs them. Cycles are
+    /// followed for at most 25 super-steps.
+    pub fn plan(&self, input: &Value) -> Vec<PlanStep> {
+        let mut channels: HashMap<String, Box<dyn Channel>> = self
+            .channel_specs
+            .iter()
+            .map(|(k, spec)| (k.clone(), spec.create()))
+            .collect();
+        if let Value::Object(map) = input {
+            for (key, value) in map {
+                if let Some(ch) = channels.get_mut(key) {
+                    // A value the channel rejects would fail the real run at
+                    // the same point; the plan just leaves it out.
+                    let _ = Self::update_channel(key, ch.as_mut(), vec![value.clone()]);
+                }
+            }
+        }
+        let state = Self::build_state(&channels);
+
+        let mut plan = Vec::new();
+        let mut current = BTreeMap::from([(self.entry_point.clone(), PlanCertainty::Certain)]);
+        for step in 0..PLAN_STEP_LIMIT {
+            if current.is_empty() {
+                break;
+            }
+            let mut next: BTreeMap<String, PlanCertainty> = BTreeMap::new();
+            for (node, certainty) in &current {
+                plan.push(PlanStep {
+                    step,
+                    node: node.clone(),
+                    certainty: *certainty,
+                });
+                let routed = self.is_routed(node);
+                for target in self.next_nodes(node, &state) {
+                    let target_certainty = if routed {
+                        PlanCertainty::Uncertain
+                    } else {
+                        *certainty
+                    };
+                    next.entry(target)
+                        .and_modify(|c| {
+                            if target_certainty == PlanCertainty::Certain {
+                                *c = PlanCertainty::Certain;
+                            }
+                        })
+                        .or_insert(target_certainty);
+                }
+            }
+            current = next;
+        }
+        plan
+    }
+
+    /// Whether `node`'s successors are chosen at runtime by a conditional or
+    /// fan-out edge.
+    fn is_routed(&self, node: &str) -> bool {
+        self.fan_out_edges.iter().any(|e| e.from == node)
+            || self.conditional_edges.iter().any(|e| e.from == node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use serde_json::json;
+
+    fn noop(name: &str) -> NodeFn {
+        NodeFn::new(name.to_string(), |_state: Value, _cfg| async move { Ok(json!({})) })
+    }
+
+    fn step(step: usize, node: &str, certainty: PlanCertainty) -> PlanStep {
+        PlanStep {
+            step,
+            node: node.into(),
+            certainty,
+        }
+    }
+
+    #[test]
+    fn plan_follows_static_and_conditional_edges() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("route", json!("left"));
+        for name in ["fetch", "classify", "left", "right", "fan_a", "fan_b", "summarize"] {
+            g.add_node(noop(name)).unwrap();
+        }
+        g.set_entry_point("fetch");
+        g.add_edge("fetch", "classify");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "classify",
+            |state: &Value| state["route"].as_str().unwrap_or("left").to_string(),
+            None,
+        ));
+        g.add_edge("right", "fan_a");
+        g.add_edge("right", "fan_b");
+        g.add_edge("fan_a", "summarize");
+        g.add_edge("fan_b", "summarize");
+        g.add_edge("left", END);
+        g.set_finish_point("summarize");
+        let graph = g.compile().unwrap();
+
+        assert_eq!(
+            graph.plan(&json!({"route": "right"})),
+            vec![
+                step(0, "fetch", PlanCertainty::Certain),
+                step(1, "classify", PlanCertainty::Certain),
+                step(2, "right", PlanCertainty::Uncertain),
+                step(3, "fan_a", PlanCertainty::Uncertain),
+                step(3, "fan_b", PlanCertainty::Uncertain),
+                step(4, "summarize", PlanCertainty::Uncertain),
+            ]
+        );
+        assert_eq!(graph.plan(&json!({})).last().unwrap().node, "left");
+    }
+
+    #[test]
+    fn plan_stops_on_cycles() {
+        let mut g = StateGraph::new();
+        g.add_node(noop("a")).unwrap();
+        g.add_node(noop("b")).unwrap();
+        g.set_entry_point("a");
+        g.add_edge("a", "b");
+        g.add_edge("b", "a");
+        let graph = g.compile().unwrap();
+
+        let plan = graph.plan(&json!({}));
+        assert_eq!(plan.len(), PLAN_STEP_LIMIT);
+        assert!(plan.iter().all(|s| s.certainty == PlanCertainty::Certain));
+    }
+}
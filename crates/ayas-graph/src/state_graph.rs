@@ -3,7 +3,8 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use ayas_core::error::{GraphError, Result};
 use serde_json::Value;
 
-use crate::channel::{AggregateOp, ChannelSpec};
+use crate::breakpoint::BreakpointConfig;
+use crate::channel::{AggregateOp, ChannelSpec, ReducerFn};
 use crate::compiled::CompiledStateGraph;
 use crate::constants::{END, START};
 use crate::edge::{ConditionalEdge, ConditionalFanOutEdge, Edge};
@@ -22,6 +23,8 @@ pub struct StateGraph {
     fan_out_edges: Vec<ConditionalFanOutEdge>,
     entry_point: Option<String>,
     finish_points: Vec<String>,
+    interrupt_before: Vec<String>,
+    interrupt_after: Vec<String>,
 }
 
 impl StateGraph {
@@ -35,6 +38,8 @@ impl StateGraph {
             fan_out_edges: Vec::new(),
             entry_point: None,
             finish_points: Vec::new(),
+            interrupt_before: Vec::new(),
+            interrupt_after: Vec::new(),
         }
     }
 
@@ -72,6 +77,21 @@ impl StateGraph {
         self.add_channel(name, ChannelSpec::BinaryOperator { default, op })
     }
 
+    /// Convenience: add a `ReducerChannel` folding updates with `reduce`.
+    pub fn add_reducer_channel(
+        &mut self,
+        name: impl Into<String>,
+        init: Value,
+        reduce: ReducerFn,
+    ) -> &mut Self {
+        self.add_channel(name, ChannelSpec::Reducer { init, reduce })
+    }
+
+    /// Convenience: add a `MapMergeChannel` (per-key last-writer-wins object).
+    pub fn add_map_merge_channel(&mut self, name: impl Into<String>) -> &mut Self {
+        self.add_channel(name, ChannelSpec::MapMerge)
+    }
+
     /// Convenience: add an `EphemeralValue` channel.
     pub fn add_ephemeral_channel(&mut self, name: impl Into<String>) -> &mut Self {
         self.add_channel(name, ChannelSpec::Ephemeral)
@@ -146,6 +166,22 @@ impl StateGraph {
         self
     }
 
+    /// Pause resumable execution before any of `nodes` runs.
+    ///
+    /// `invoke_resumable` checkpoints with the node still pending and returns
+    /// `GraphOutput::Interrupted`; resuming from that checkpoint runs it.
+    pub fn interrupt_before(&mut self, nodes: &[&str]) -> &mut Self {
+        self.interrupt_before.extend(nodes.iter().map(|n| n.to_string()));
+        self
+    }
+
+    /// Pause resumable execution after any of `nodes` has run, once its
+    /// super-step completes. Resuming continues with the nodes that follow.
+    pub fn interrupt_after(&mut self, nodes: &[&str]) -> &mut Self {
+        self.interrupt_after.extend(nodes.iter().map(|n| n.to_string()));
+        self
+    }
+
     /// Validate the graph and produce a `CompiledStateGraph`.
     pub fn compile(self) -> Result<CompiledStateGraph> {
         self.validate()?;
@@ -183,6 +219,11 @@ impl StateGraph {
             channel_specs: self.channel_specs,
             entry_point,
             finish_points: self.finish_points,
+            static_breakpoints: BreakpointConfig {
+                break_before: self.interrupt_before,
+                break_after: self.interrupt_after,
+                condition: None,
+            },
         })
     }
 
@@ -227,6 +268,16 @@ impl StateGraph {
             }
         }
 
+        // 5b. Static interrupts must reference existing nodes
+        for node in self.interrupt_before.iter().chain(&self.interrupt_after) {
+            if !self.nodes.contains_key(node) {
+                return Err(GraphError::InvalidGraph(format!(
+                    "Interrupt node '{node}' does not exist"
+                ))
+                .into());
+            }
+        }
+
         // 6. BFS reachability check from entry point (cycles are allowed)
         self.validate_reachability(entry)?;
 
@@ -526,4 +577,15 @@ mod tests {
         let compiled = graph.compile();
         assert!(compiled.is_ok());
     }
+
+    #[test]
+    fn compile_unknown_interrupt_node() {
+        let mut graph = StateGraph::new();
+        graph.add_node(noop_node("a")).unwrap();
+        graph.set_entry_point("a");
+        graph.set_finish_point("a");
+        graph.interrupt_before(&["a"]).interrupt_after(&["missing"]);
+        let err = graph.compile().err().unwrap();
+        assert!(err.to_string().contains("Interrupt node 'missing'"), "{err}");
+    }
 }
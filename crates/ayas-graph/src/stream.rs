@@ -1,3 +1,4 @@
+use ayas_core::model::ChatStreamEvent;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -13,6 +14,12 @@ pub enum StreamEvent {
         step: usize,
         state: Value,
     },
+    /// A model-backed node streamed part of its response.
+    Token {
+        node_name: String,
+        step: usize,
+        event: ChatStreamEvent,
+    },
     /// The graph completed successfully.
     GraphComplete { output: Value },
     /// The graph was interrupted (HITL).
@@ -37,11 +37,8 @@ pub async fn fork_from_checkpoint(
         step: 0,
         channel_values: checkpoint.channel_values.clone(),
         pending_nodes: checkpoint.pending_nodes.clone(),
-        metadata: CheckpointMetadata {
-            source: "fork".into(),
-            step: 0,
-            node_name: checkpoint.metadata.node_name.clone(),
-        },
+        metadata: CheckpointMetadata::new("fork", 0, checkpoint.metadata.node_name.clone())
+            .with_extra(checkpoint.metadata.extra.clone()),
         created_at: Utc::now(),
     };
 
@@ -79,11 +76,7 @@ mod tests {
             step,
             channel_values: HashMap::from([("count".into(), json!(step * 10))]),
             pending_nodes: vec![format!("node_{}", step + 1)],
-            metadata: CheckpointMetadata {
-                source: "loop".into(),
-                step,
-                node_name: Some(format!("node_{step}")),
-            },
+            metadata: CheckpointMetadata::new("loop", step, Some(format!("node_{step}"))),
             created_at: Utc::now(),
         }
     }
@@ -0,0 +1,352 @@
+//! Diagram export of a compiled graph's topology.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::compiled::CompiledStateGraph;
+use crate::constants::{END, START};
+
+/// How an edge is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Static,
+    Conditional,
+    FanOut,
+}
+
+/// An edge of the diagram. `label` is the routing key for conditional and
+/// fan-out edges.
+struct DiagramEdge<'a> {
+    from: &'a str,
+    to: &'a str,
+    label: Option<&'a str>,
+    kind: EdgeKind,
+}
+
+impl CompiledStateGraph {
+    /// Render the graph as a Mermaid flowchart.
+    ///
+    /// `START` and `END` are drawn as rounded terminals. Conditional edges
+    /// are dashed and fan-out edges thick, both labeled with their routing
+    /// keys. Conditional edges without a path map route by node names
+    /// computed at run time and have no edges to draw. Nodes whose escaped
+    /// ids clash (`read web` and `read_web`) get numbered ids.
+    pub fn to_mermaid(&self) -> String {
+        let nodes = self.diagram_nodes();
+        let ids = mermaid_ids(&nodes);
+        let mut out = String::from("graph TD\n");
+        for name in nodes {
+            let id = &ids[name];
+            let label = mermaid_label(name);
+            if name == START || name == END {
+                out.push_str(&format!("    {id}([\"{label}\"])\n"));
+            } else {
+                out.push_str(&format!("    {id}[\"{label}\"]\n"));
+            }
+        }
+        for edge in self.diagram_edges() {
+            let (from, to) = (&ids[edge.from], &ids[edge.to]);
+            let arrow = match edge.kind {
+                EdgeKind::Static => "-->",
+                EdgeKind::Conditional => "-.->",
+                EdgeKind::FanOut => "==>",
+            };
+            match edge.label {
+                Some(label) => {
+                    let label = mermaid_label(label);
+                    out.push_str(&format!("    {from} {arrow}|\"{label}\"| {to}\n"));
+                }
+                None => out.push_str(&format!("    {from} {arrow} {to}\n")),
+            }
+        }
+        out
+    }
+
+    /// Render the graph in Graphviz DOT.
+    ///
+    /// Uses the same conventions as [`to_mermaid`](Self::to_mermaid):
+    /// conditional edges are dashed, fan-out edges bold.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n");
+        for name in self.diagram_nodes() {
+            let id = dot_quote(name);
+            if name == START || name == END {
+                out.push_str(&format!("    {id} [shape=oval];\n"));
+            } else {
+                out.push_str(&format!("    {id} [shape=box];\n"));
+            }
+        }
+        for edge in self.diagram_edges() {
+            let (from, to) = (dot_quote(edge.from), dot_quote(edge.to));
+            let mut attrs = Vec::new();
+            match edge.kind {
+                EdgeKind::Static => {}
+                EdgeKind::Conditional => attrs.push("style=dashed".to_string()),
+                EdgeKind::FanOut => attrs.push("style=bold".to_string()),
+            }
+            if let Some(label) = edge.label {
+                attrs.push(format!("label={}", dot_quote(label)));
+            }
+            if attrs.is_empty() {
+                out.push_str(&format!("    {from} -> {to};\n"));
+            } else {
+                out.push_str(&format!("    {from} -> {to} [{}];\n", attrs.join(", ")));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// `START`, the nodes sorted by name, then `END`.
+    fn diagram_nodes(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.insert(0, START);
+        names.push(END);
+        names
+    }
+
+    /// Static edges by source (in [`diagram_nodes`](Self::diagram_nodes)
+    /// order, duplicates dropped), then conditional and fan-out edges in the
+    /// order they were added, each sorted by routing key.
+    fn diagram_edges(&self) -> Vec<DiagramEdge<'_>> {
+        let mut edges: Vec<DiagramEdge<'_>> = Vec::new();
+        for from in self.diagram_nodes() {
+            let mut seen: Vec<&str> = Vec::new();
+            for to in self.edges_from(from) {
+                if !seen.contains(&to.as_str()) {
+                    seen.push(to);
+                    edges.push(DiagramEdge {
+                        from,
+                        to,
+                        label: None,
+                        kind: EdgeKind::Static,
+                    });
+                }
+            }
+        }
+        for edge in &self.conditional_edges {
+            let Some(path_map) = edge.path_map() else {
+                continue;
+            };
+            let mut routes: Vec<(&String, &String)> = path_map.iter().collect();
+            routes.sort_unstable();
+            for (key, to) in routes {
+                edges.push(DiagramEdge {
+                    from: &edge.from,
+                    to,
+                    label: Some(key),
+                    kind: EdgeKind::Conditional,
+                });
+            }
+        }
+        for edge in &self.fan_out_edges {
+            let mut routes: Vec<(&String, &String)> = edge.target_map().iter().collect();
+            routes.sort_unstable();
+            for (key, to) in routes {
+                edges.push(DiagramEdge {
+                    from: &edge.from,
+                    to,
+                    label: Some(key),
+                    kind: EdgeKind::FanOut,
+                });
+            }
+        }
+        edges
+    }
+}
+
+/// A Mermaid node id for `name`. Characters Mermaid treats as syntax are
+/// replaced, and the reserved word `end` is suffixed.
+fn mermaid_id(name: &str) -> String {
+    let id: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if id.eq_ignore_ascii_case("end") {
+        format!("{id}_")
+    } else {
+        id
+    }
+}
+
+/// Distinct Mermaid ids for `names`, in order: a name whose
+/// [`mermaid_id`] is already taken gets the first free `_2`, `_3`, ... suffix.
+fn mermaid_ids<'a>(names: &[&'a str]) -> HashMap<&'a str, String> {
+    let mut taken = HashSet::new();
+    let mut ids = HashMap::new();
+    for &name in names {
+        let base = mermaid_id(name);
+        let mut id = base.clone();
+        let mut n = 2;
+        while !taken.insert(id.clone()) {
+            id = format!("{base}_{n}");
+            n += 1;
+        }
+        ids.insert(name, id);
+    }
+    ids
+}
+
+/// Escape `text` for use inside a quoted Mermaid label.
+fn mermaid_label(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Quote `text` as a DOT identifier.
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{Value, json};
+
+    use crate::edge::{ConditionalEdge, ConditionalFanOutEdge};
+    use crate::node::NodeFn;
+    use crate::state_graph::StateGraph;
+
+    fn noop(name: &str) -> NodeFn {
+        NodeFn::new(name, |_state: Value, _cfg| async move { Ok(json!({})) })
+    }
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    /// `plan` routes to `search` or `answer`; `search` fans out to two
+    /// readers, which both lead back to `plan`.
+    fn build_routing_graph() -> super::CompiledStateGraph {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("done", json!(false));
+        for name in ["plan", "search", "read web", "read docs", "answer"] {
+            g.add_node(noop(name)).unwrap();
+        }
+        g.set_entry_point("plan");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "plan",
+            |_: &Value| "continue".to_string(),
+            Some(map(&[("continue", "search"), ("finish", "answer")])),
+        ));
+        g.add_conditional_fan_out_edges(ConditionalFanOutEdge::new(
+            "search",
+            |_: &Value| vec!["web".to_string(), "docs".to_string()],
+            map(&[("web", "read web"), ("docs", "read docs")]),
+        ));
+        g.add_edge("read web", "plan");
+        g.add_edge("read docs", "plan");
+        g.set_finish_point("answer");
+        g.compile().unwrap()
+    }
+
+    #[test]
+    fn mermaid_draws_conditional_and_fan_out_edges() {
+        let mermaid = build_routing_graph().to_mermaid();
+        assert_eq!(
+            mermaid,
+            r#"graph TD
+    __start__(["__start__"])
+    answer["answer"]
+    plan["plan"]
+    read_docs["read docs"]
+    read_web["read web"]
+    search["search"]
+    __end__(["__end__"])
+    __start__ --> plan
+    answer --> __end__
+    read_docs --> plan
+    read_web --> plan
+    plan -.->|"continue"| search
+    plan -.->|"finish"| answer
+    search ==>|"docs"| read_docs
+    search ==>|"web"| read_web
+"#
+        );
+    }
+
+    #[test]
+    fn dot_draws_conditional_and_fan_out_edges() {
+        let dot = build_routing_graph().to_dot();
+        assert!(dot.starts_with("digraph {\n    \"__start__\" [shape=oval];\n"));
+        assert!(dot.contains("    \"read web\" [shape=box];\n"));
+        assert!(dot.contains("    \"answer\" -> \"__end__\";\n"));
+        assert!(dot.contains("    \"plan\" -> \"answer\" [style=dashed, label=\"finish\"];\n"));
+        assert!(dot.contains("    \"search\" -> \"read web\" [style=bold, label=\"web\"];\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn conditional_edge_without_path_map_draws_no_edges() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("next", json!("b"));
+        g.add_node(noop("a")).unwrap();
+        g.add_node(noop("b")).unwrap();
+        g.set_entry_point("a");
+        g.add_conditional_edges(ConditionalEdge::new(
+            "a",
+            |state: &Value| state["next"].as_str().unwrap_or("b").to_string(),
+            None,
+        ));
+        g.set_finish_point("b");
+        let mermaid = g.compile().unwrap().to_mermaid();
+        assert!(!mermaid.contains("-.->"));
+        assert!(mermaid.contains("    b --> __end__\n"));
+    }
+
+    #[test]
+    fn identifiers_are_escaped() {
+        assert_eq!(super::mermaid_id("end"), "end_");
+        assert_eq!(super::mermaid_id("fetch-url"), "fetch_url");
+        assert_eq!(super::mermaid_label("say \"hi\""), "say #quot;hi#quot;");
+        assert_eq!(super::dot_quote("a \"b\" \\c"), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn clashing_identifiers_are_numbered() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("v", json!(null));
+        g.add_node(noop("read web")).unwrap();
+        g.add_node(noop("read_web")).unwrap();
+        g.set_entry_point("read web");
+        g.add_edge("read web", "read_web");
+        g.set_finish_point("read_web");
+
+        let mermaid = g.compile().unwrap().to_mermaid();
+        assert!(mermaid.contains("    read_web[\"read web\"]\n"), "{mermaid}");
+        assert!(mermaid.contains("    read_web_2[\"read_web\"]\n"), "{mermaid}");
+        assert!(mermaid.contains("    read_web --> read_web_2\n"), "{mermaid}");
+        assert!(mermaid.contains("    read_web_2 --> __end__\n"), "{mermaid}");
+    }
+
+    #[test]
+    fn linear_graph_mermaid_snapshot() {
+        let mut g = StateGraph::new();
+        g.add_last_value_channel("count", json!(0));
+        for name in ["a", "b", "c"] {
+            g.add_node(noop(name)).unwrap();
+        }
+        g.set_entry_point("a");
+        g.add_edge("a", "b");
+        g.add_edge("b", "c");
+        g.set_finish_point("c");
+
+        assert_eq!(
+            g.compile().unwrap().to_mermaid(),
+            r#"graph TD
+    __start__(["__start__"])
+    a["a"]
+    b["b"]
+    c["c"]
+    __end__(["__end__"])
+    __start__ --> a
+    a --> b
+    b --> c
+    c --> __end__
+"#
+        );
+    }
+}
@@ -2,8 +2,11 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use ayas_checkpoint::prelude::MemoryCheckpointStore;
+use ayas_core::budget::Budget;
 use ayas_core::config::RunnableConfig;
-use ayas_core::error::AyasError;
+use ayas_core::error::{AyasError, GraphError};
+use ayas_core::message::UsageMetadata;
 use ayas_core::runnable::Runnable;
 use ayas_graph::prelude::*;
 use serde_json::{json, Value};
@@ -204,6 +207,86 @@ async fn execute_recursion_limit_exceeded() {
     assert!(err.contains("Recursion limit"));
 }
 
+/// A self-looping node that reports 40 tokens per call, counting its calls.
+fn spender_graph(calls: Arc<AtomicUsize>) -> CompiledStateGraph {
+    let mut graph = StateGraph::new();
+    graph.add_last_value_channel("x", json!(0));
+    graph
+        .add_node(NodeFn::new(
+            "spender",
+            move |_state: Value, config: RunnableConfig| {
+                let calls = calls.clone();
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    let usage = UsageMetadata {
+                        total_tokens: 40,
+                        ..Default::default()
+                    };
+                    config.usage.as_ref().unwrap().record("mock", &usage);
+                    Ok(json!({}))
+                }
+            },
+        ))
+        .unwrap();
+    graph.set_entry_point("spender");
+    graph.add_edge("spender", "spender");
+    graph.compile().unwrap()
+}
+
+/// A token budget stops a runaway loop once the usage nodes report exceeds it.
+#[tokio::test]
+async fn execute_budget_exceeded() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let compiled = spender_graph(calls.clone());
+    let config = RunnableConfig::default()
+        .with_recursion_limit(100)
+        .with_budget(Budget::default().with_max_tokens(100));
+    let err = compiled.invoke(json!({}), &config).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        AyasError::Graph(GraphError::BudgetExceeded { tokens: 120, .. })
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+/// Runs with breakpoints enforce the budget too.
+#[tokio::test]
+async fn execute_budget_exceeded_with_breakpoints() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let compiled = spender_graph(calls.clone());
+    let config = RunnableConfig::default()
+        .with_recursion_limit(100)
+        .with_budget(Budget::default().with_max_tokens(100));
+    let err = compiled
+        .invoke_with_breakpoints(
+            json!({}),
+            &config,
+            &MemoryCheckpointStore::new(),
+            &BreakpointConfig::new(),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        AyasError::Graph(GraphError::BudgetExceeded { tokens: 120, .. })
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+/// A cost limit without a pricer is rejected before any node runs.
+#[tokio::test]
+async fn execute_unpriced_cost_budget_rejected() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let compiled = spender_graph(calls.clone());
+    let config = RunnableConfig::default().with_budget(Budget::default().with_max_cost(1.0));
+    let err = compiled.invoke(json!({}), &config).await.unwrap_err();
+
+    assert!(matches!(err, AyasError::Graph(GraphError::UnpricedBudget)));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
+
 /// AppendChannel accumulates messages across steps.
 #[tokio::test]
 async fn execute_with_append_channel() {
@@ -395,3 +478,120 @@ async fn execute_react_style_cycle() {
     assert_eq!(messages.len(), 4); // user, ai (tool call), tool result, ai (final)
     assert_eq!(call_count.load(Ordering::Relaxed), 2);
 }
+
+/// ReducerChannel folds each node's partial object into the state key.
+#[tokio::test]
+async fn execute_with_reducer_channel() {
+    let mut graph = StateGraph::new();
+    graph.add_reducer_channel(
+        "profile",
+        json!({}),
+        Arc::new(|current: Value, update: Value| match (current, update) {
+            (Value::Object(mut base), Value::Object(patch)) => {
+                base.extend(patch);
+                Value::Object(base)
+            }
+            (_, update) => update,
+        }),
+    );
+
+    graph
+        .add_node(NodeFn::new("name", |_state: Value, _config| async move {
+            Ok(json!({"profile": {"name": "Ann"}}))
+        }))
+        .unwrap();
+    graph
+        .add_node(NodeFn::new("age", |_state: Value, _config| async move {
+            Ok(json!({"profile": {"age": 30}}))
+        }))
+        .unwrap();
+
+    graph.set_entry_point("name");
+    graph.add_edge("name", "age");
+    graph.set_finish_point("age");
+
+    let compiled = graph.compile().unwrap();
+    let config = RunnableConfig::default();
+    let result = compiled.invoke(json!({}), &config).await.unwrap();
+
+    assert_eq!(result["profile"], json!({"name": "Ann", "age": 30}));
+}
+
+/// A typed channel rejects a node's mismatched write, naming the channel.
+#[tokio::test]
+async fn execute_typed_channel_rejects_wrong_type() {
+    let mut graph = StateGraph::new();
+    graph.add_channel(
+        "title",
+        ChannelSpec::LastValue { default: json!("") }.typed(ValueKind::String),
+    );
+
+    graph
+        .add_node(NodeFn::new("transform", |_state: Value, _config| async move {
+            Ok(json!({"title": 42}))
+        }))
+        .unwrap();
+
+    graph.set_entry_point("transform");
+    graph.set_finish_point("transform");
+
+    let compiled = graph.compile().unwrap();
+    let config = RunnableConfig::default();
+    let err = compiled.invoke(json!({}), &config).await.unwrap_err();
+
+    assert!(matches!(err, AyasError::Graph(GraphError::Channel(_))));
+    assert!(err.to_string().contains("channel 'title'"), "{err}");
+}
+
+/// Build a graph whose single transform node upper-cases `text` into `title`,
+/// cached in `cache`. Returns the graph and the node's invocation counter.
+fn cached_transform_graph(cache: Arc<dyn NodeCache>) -> (CompiledStateGraph, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let mut graph = StateGraph::new();
+    graph.add_last_value_channel("text", json!(""));
+    graph.add_last_value_channel("title", json!(""));
+
+    let transform = NodeFn::new("transform", move |state: Value, _config| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            let text = state["text"].as_str().unwrap_or_default().to_uppercase();
+            Ok(json!({"title": text}))
+        }
+    });
+    graph.add_node(transform.cached(cache)).unwrap();
+    graph.set_entry_point("transform");
+    graph.set_finish_point("transform");
+    (graph.compile().unwrap(), calls)
+}
+
+/// A cached node is not re-invoked on identical input within the TTL.
+#[tokio::test]
+async fn execute_cached_node_skips_identical_input() {
+    let cache = Arc::new(InMemoryNodeCache::new(std::time::Duration::from_secs(60)));
+    let (compiled, calls) = cached_transform_graph(cache.clone());
+    let config = RunnableConfig::default();
+
+    let first = compiled.invoke(json!({"text": "hello"}), &config).await.unwrap();
+    let second = compiled.invoke(json!({"text": "hello"}), &config).await.unwrap();
+    assert_eq!(first["title"], json!("HELLO"));
+    assert_eq!(second, first);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let other = compiled.invoke(json!({"text": "world"}), &config).await.unwrap();
+    assert_eq!(other["title"], json!("WORLD"));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(cache.len(), 2);
+}
+
+/// Expired entries are recomputed.
+#[tokio::test]
+async fn execute_cached_node_reruns_after_ttl() {
+    let cache = Arc::new(InMemoryNodeCache::new(std::time::Duration::ZERO));
+    let (compiled, calls) = cached_transform_graph(cache);
+    let config = RunnableConfig::default();
+
+    compiled.invoke(json!({"text": "hello"}), &config).await.unwrap();
+    compiled.invoke(json!({"text": "hello"}), &config).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
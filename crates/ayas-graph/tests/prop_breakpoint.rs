@@ -56,11 +56,7 @@ fn make_checkpoint(id: &str, thread_id: &str, step: usize, value: Value) -> Chec
         step,
         channel_values: HashMap::from([("count".into(), value)]),
         pending_nodes: vec![format!("node_{}", step + 1)],
-        metadata: CheckpointMetadata {
-            source: "test".into(),
-            step,
-            node_name: Some(format!("node_{step}")),
-        },
+        metadata: CheckpointMetadata::new("test", step, Some(format!("node_{step}"))),
         created_at: chrono::Utc::now(),
     }
 }
@@ -123,11 +119,7 @@ proptest! {
                 step: 1,
                 channel_values: HashMap::from([("count".into(), json!(999))]),
                 pending_nodes: vec![],
-                metadata: CheckpointMetadata {
-                    source: "test".into(),
-                    step: 1,
-                    node_name: Some("extra".into()),
-                },
+                metadata: CheckpointMetadata::new("test", 1, Some("extra".into())),
                 created_at: chrono::Utc::now(),
             };
             store.put(extra).await.unwrap();
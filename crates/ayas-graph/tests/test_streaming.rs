@@ -5,6 +5,7 @@ use ayas_checkpoint::prelude::{
     command_output, send_output, CheckpointConfigExt, MemoryCheckpointStore, SendDirective,
 };
 use ayas_core::config::RunnableConfig;
+use ayas_core::runnable::Runnable;
 use ayas_graph::prelude::*;
 
 /// Helper: build a 2-node linear graph: a → b → END
@@ -593,3 +594,245 @@ async fn test_stream_with_modes_graph_complete_always_sent() {
         .count();
     assert_eq!(complete_count, 1);
 }
+
+// ---- Cancellation ----
+
+use ayas_core::error::{AyasError, GraphError};
+
+#[tokio::test]
+async fn test_streaming_cancelled_before_start() {
+    let graph = build_abc_graph();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let config = default_config().with_cancellation(cancel);
+    let (tx, rx) = mpsc::channel(64);
+
+    let result = graph.invoke_with_streaming(json!({}), &config, tx).await;
+    assert!(matches!(result, Err(AyasError::Graph(GraphError::Cancelled))));
+
+    let events = collect_events(rx).await;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], StreamEvent::Error { .. }));
+}
+
+#[tokio::test]
+async fn test_streaming_cancelled_between_super_steps() {
+    let cancel = CancellationToken::new();
+    let node_cancel = cancel.clone();
+
+    let mut g = StateGraph::new();
+    g.add_last_value_channel("count", json!(0));
+    g.add_node(NodeFn::new("a", move |_state: Value, _cfg| {
+        let cancel = node_cancel.clone();
+        async move {
+            // Simulates the client disconnecting while "a" runs
+            cancel.cancel();
+            Ok(json!({"count": 1}))
+        }
+    }))
+    .unwrap();
+    g.add_node(NodeFn::new("b", |_state: Value, _cfg| async move {
+        Ok(json!({"count": 100}))
+    }))
+    .unwrap();
+    g.set_entry_point("a");
+    g.add_edge("a", "b");
+    g.set_finish_point("b");
+    let graph = g.compile().unwrap();
+
+    let (tx, rx) = mpsc::channel(64);
+    let config = default_config().with_cancellation(cancel);
+    let result = graph.invoke_with_streaming(json!({}), &config, tx).await;
+    assert!(result.is_err());
+
+    // "a" finished, "b" never started
+    let events = collect_events(rx).await;
+    let started: Vec<&str> = events
+        .iter()
+        .filter_map(|e| match e {
+            StreamEvent::NodeStart { node_name, .. } => Some(node_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(started, ["a"]);
+    assert!(matches!(events.last(), Some(StreamEvent::Error { .. })));
+}
+
+#[tokio::test]
+async fn test_streaming_uncancelled_token_runs_to_completion() {
+    let graph = build_abc_graph();
+    let (tx, _rx) = mpsc::channel(64);
+    let config = default_config().with_cancellation(CancellationToken::new());
+
+    let result = graph
+        .invoke_with_streaming(json!({}), &config, tx)
+        .await
+        .unwrap();
+    assert_eq!(result["count"], json!(3));
+}
+
+#[tokio::test]
+async fn test_stream_with_modes_cancelled() {
+    let graph = build_abc_graph();
+    let (tx, rx) = mpsc::channel(64);
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let config = default_config().with_cancellation(cancel);
+
+    let result = graph
+        .stream_with_modes(json!({}), &config, &[StreamMode::Values], tx)
+        .await;
+    assert!(result.is_err());
+
+    let events = collect_core_events(rx).await;
+    assert_eq!(events.len(), 1);
+    assert!(matches!(&events[0], CoreStreamEvent::Error { .. }));
+}
+
+#[tokio::test]
+async fn test_resumable_streaming_cancelled() {
+    let graph = build_abc_graph();
+    let store = MemoryCheckpointStore::new();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let config = RunnableConfig::default()
+        .with_thread_id("cancel-thread")
+        .with_cancellation(cancel);
+    let (tx, _rx) = mpsc::channel(64);
+
+    let result = graph
+        .invoke_resumable_with_streaming(json!({}), &config, &store, tx)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_invoke_cancelled() {
+    let graph = build_abc_graph();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let config = default_config().with_cancellation(cancel);
+
+    let result = graph.invoke(json!({}), &config).await;
+    assert!(matches!(result, Err(AyasError::Graph(GraphError::Cancelled))));
+}
+
+#[tokio::test]
+async fn test_breakpoints_cancelled() {
+    let graph = build_abc_graph();
+    let store = MemoryCheckpointStore::new();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let config = default_config().with_cancellation(cancel);
+
+    let result = graph
+        .invoke_with_breakpoints(json!({}), &config, &store, &BreakpointConfig::new())
+        .await;
+    assert!(matches!(result, Err(AyasError::Graph(GraphError::Cancelled))));
+}
+
+// ---- Token streaming from inside nodes ----
+
+use ayas_core::model::ChatStreamEvent;
+
+/// Helper: a → talker → END, where `talker` streams two tokens through the
+/// config's `stream_tx` before returning.
+fn build_token_graph() -> CompiledStateGraph {
+    let mut g = StateGraph::new();
+    g.add_last_value_channel("count", json!(0));
+    g.add_last_value_channel("reply", json!(""));
+
+    g.add_node(NodeFn::new("a", |_state: Value, _cfg| async move {
+        Ok(json!({"count": 1}))
+    }))
+    .unwrap();
+    g.add_node(NodeFn::new("talker", |_state: Value, cfg: RunnableConfig| async move {
+        if let Some(tx) = &cfg.stream_tx {
+            let _ = tx.send(ChatStreamEvent::Token("Hel".into()));
+            let _ = tx.send(ChatStreamEvent::Token("lo".into()));
+            let _ = tx.send(ChatStreamEvent::Done);
+        }
+        Ok(json!({"reply": "Hello"}))
+    }))
+    .unwrap();
+
+    g.set_entry_point("a");
+    g.add_edge("a", "talker");
+    g.set_finish_point("talker");
+    g.compile().unwrap()
+}
+
+#[tokio::test]
+async fn test_streaming_relays_node_tokens() {
+    let graph = build_token_graph();
+    let (tx, rx) = mpsc::channel(64);
+
+    graph
+        .invoke_with_streaming(json!({}), &default_config(), tx)
+        .await
+        .unwrap();
+
+    let events = collect_events(rx).await;
+    let tokens: Vec<(&str, usize, &ChatStreamEvent)> = events
+        .iter()
+        .filter_map(|e| match e {
+            StreamEvent::Token {
+                node_name,
+                step,
+                event,
+            } => Some((node_name.as_str(), *step, event)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0], ("talker", 1, &ChatStreamEvent::Token("Hel".into())));
+    assert_eq!(tokens[1].2, &ChatStreamEvent::Token("lo".into()));
+
+    // Tokens arrive between the node's start and end
+    let start = events
+        .iter()
+        .position(|e| matches!(e, StreamEvent::NodeStart { node_name, .. } if node_name == "talker"))
+        .unwrap();
+    let end = events
+        .iter()
+        .position(|e| matches!(e, StreamEvent::NodeEnd { node_name, .. } if node_name == "talker"))
+        .unwrap();
+    assert_eq!(end - start, 4);
+}
+
+#[tokio::test]
+async fn test_stream_with_modes_messages() {
+    let graph = build_token_graph();
+    let (tx, rx) = mpsc::channel(64);
+
+    let result = graph
+        .stream_with_modes(json!({}), &default_config(), &[StreamMode::Messages], tx)
+        .await
+        .unwrap();
+    assert_eq!(result["reply"], json!("Hello"));
+
+    let events = collect_core_events(rx).await;
+    let chunks: Vec<&str> = events
+        .iter()
+        .filter_map(|e| match e {
+            CoreStreamEvent::Message { chunk } => Some(chunk.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(chunks, vec!["Hel", "lo"]);
+    assert!(matches!(events.last(), Some(CoreStreamEvent::GraphComplete { .. })));
+}
+
+#[tokio::test]
+async fn test_stream_with_modes_without_messages_skips_tokens() {
+    let graph = build_token_graph();
+    let (tx, rx) = mpsc::channel(64);
+
+    graph
+        .stream_with_modes(json!({}), &default_config(), &[StreamMode::Values], tx)
+        .await
+        .unwrap();
+
+    let events = collect_core_events(rx).await;
+    assert!(!events.iter().any(|e| matches!(e, CoreStreamEvent::Message { .. })));
+}
@@ -4,6 +4,7 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 
 use ayas_core::error::{AyasError, ModelError, Result};
@@ -12,7 +13,10 @@ use ayas_core::message::{
 };
 use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent, ResponseFormat};
 
+use crate::config::ProviderConfig;
+use crate::retry::{retry_after_secs, send_with_retry};
 use crate::sse::sse_data_stream;
+use crate::stop::stop_at_sequences;
 
 // ---------------------------------------------------------------------------
 // Anthropic Messages API request/response types
@@ -35,11 +39,13 @@ pub struct AnthropicRequest {
     pub model: String,
     pub max_tokens: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub system: Option<String>,
+    pub system: Option<AnthropicContent>,
     pub messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<AnthropicToolDef>>,
@@ -68,6 +74,75 @@ pub struct AnthropicMessage {
 pub enum AnthropicContent {
     Text(String),
     Parts(Vec<AnthropicContentPart>),
+    /// Parts with optional per-block `cache_control` breakpoints.
+    Cached(Vec<AnthropicCachedPart>),
+}
+
+impl AnthropicContent {
+    /// Return the text if this content is a single text block.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            AnthropicContent::Text(s) => Some(s),
+            AnthropicContent::Parts(parts) => match parts.as_slice() {
+                [AnthropicContentPart::Text { text }] => Some(text),
+                _ => None,
+            },
+            AnthropicContent::Cached(parts) => match parts.as_slice() {
+                [AnthropicCachedPart {
+                    part: AnthropicContentPart::Text { text },
+                    ..
+                }] => Some(text),
+                _ => None,
+            },
+        }
+    }
+
+    /// Convert to block form with an ephemeral cache breakpoint on the last block.
+    pub fn with_cache_breakpoint(self) -> AnthropicContent {
+        let parts = match self {
+            AnthropicContent::Text(text) => vec![AnthropicContentPart::Text { text }],
+            AnthropicContent::Parts(parts) => parts,
+            AnthropicContent::Cached(mut parts) => {
+                if let Some(last) = parts.last_mut() {
+                    last.cache_control = Some(AnthropicCacheControl::ephemeral());
+                }
+                return AnthropicContent::Cached(parts);
+            }
+        };
+        let last = parts.len().saturating_sub(1);
+        AnthropicContent::Cached(
+            parts
+                .into_iter()
+                .enumerate()
+                .map(|(i, part)| AnthropicCachedPart {
+                    part,
+                    cache_control: (i == last).then(AnthropicCacheControl::ephemeral),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnthropicCachedPart {
+    #[serde(flatten)]
+    pub part: AnthropicContentPart,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<AnthropicCacheControl>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnthropicCacheControl {
+    #[serde(rename = "type")]
+    pub cache_type: String,
+}
+
+impl AnthropicCacheControl {
+    pub fn ephemeral() -> Self {
+        Self {
+            cache_type: "ephemeral".into(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -89,6 +164,8 @@ pub enum AnthropicContentPart {
     ToolResult {
         tool_use_id: String,
         content: String,
+        #[serde(skip_serializing_if = "is_false")]
+        is_error: bool,
     },
 }
 
@@ -129,12 +206,20 @@ pub enum AnthropicResponseContent {
         name: String,
         input: serde_json::Value,
     },
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking {},
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AnthropicUsage {
     pub input_tokens: u64,
     pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    pub cache_creation_input_tokens: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -147,6 +232,36 @@ pub struct AnthropicErrorDetail {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct AnthropicCountTokensResponse {
+    input_tokens: u64,
+}
+
+/// Request fields accepted by `/v1/messages/count_tokens`.
+const COUNT_TOKENS_FIELDS: &[&str] = &["model", "system", "messages", "tools", "tool_choice"];
+
+/// Map a non-success response to an error: 401 to `Auth`, 429 to
+/// `RateLimited`, anything else to `ApiRequest`, using the API's error
+/// message when the body has one.
+async fn error_response(response: reqwest::Response) -> AyasError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "failed to read response body".into());
+    let error_msg = serde_json::from_str::<AnthropicError>(&body)
+        .map(|e| e.error.message)
+        .unwrap_or(body);
+    AyasError::Model(match status.as_u16() {
+        401 => ModelError::Auth(error_msg),
+        429 => ModelError::RateLimited {
+            retry_after_secs: retry_after,
+        },
+        _ => ModelError::http(status.as_u16(), error_msg),
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Conversion helpers
 // ---------------------------------------------------------------------------
@@ -206,6 +321,9 @@ pub struct ClaudeChatModel {
     api_key: String,
     model_id: String,
     client: reqwest::Client,
+    extra_headers: HeaderMap,
+    base_url: String,
+    max_auto_retries: u32,
 }
 
 impl ClaudeChatModel {
@@ -214,9 +332,33 @@ impl ClaudeChatModel {
             api_key,
             model_id,
             client: reqwest::Client::new(),
+            extra_headers: HeaderMap::new(),
+            base_url: "https://api.anthropic.com".into(),
+            max_auto_retries: 0,
         }
     }
 
+    /// Apply a provider config; its extra headers are sent with every request.
+    ///
+    /// Returns an error if any header name or value is invalid.
+    pub fn with_config(mut self, config: &ProviderConfig) -> Result<Self> {
+        self.extra_headers = config.header_map()?;
+        Ok(self)
+    }
+
+    /// Send requests to `base_url` instead of the public API (e.g. a proxy).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Retry requests answered with 429 or 503 up to `max_auto_retries` times,
+    /// honoring `Retry-After`. The default of 0 returns the error right away.
+    pub fn with_max_auto_retries(mut self, max_auto_retries: u32) -> Self {
+        self.max_auto_retries = max_auto_retries;
+        self
+    }
+
     pub fn build_request(&self, messages: &[Message], options: &CallOptions) -> AnthropicRequest {
         let mut system: Option<String> = None;
         let mut api_messages: Vec<AnthropicMessage> = Vec::new();
@@ -262,12 +404,14 @@ impl ClaudeChatModel {
                 Message::Tool {
                     content,
                     tool_call_id,
+                    is_error,
                 } => {
                     api_messages.push(AnthropicMessage {
                         role: "user".into(),
                         content: AnthropicContent::Parts(vec![AnthropicContentPart::ToolResult {
                             tool_use_id: tool_call_id.clone(),
                             content: content.clone(),
+                            is_error: *is_error,
                         }]),
                     });
                 }
@@ -310,12 +454,25 @@ impl ClaudeChatModel {
 
         let tools_opt = if tools.is_empty() { None } else { Some(tools) };
 
+        let mut system = system.map(AnthropicContent::Text);
+        if options.cache {
+            // Breakpoints on the system prompt and the latest message cache
+            // the whole prefix for subsequent calls.
+            system = system.map(AnthropicContent::with_cache_breakpoint);
+            if let Some(last) = api_messages.last_mut() {
+                let content =
+                    std::mem::replace(&mut last.content, AnthropicContent::Text(String::new()));
+                last.content = content.with_cache_breakpoint();
+            }
+        }
+
         AnthropicRequest {
             model: self.model_id.clone(),
             max_tokens: options.max_tokens.unwrap_or(1024),
             system,
             messages: api_messages,
             temperature: options.temperature,
+            top_p: options.top_p,
             stop_sequences: if options.stop.is_empty() {
                 None
             } else {
@@ -337,32 +494,19 @@ impl ChatModel for ClaudeChatModel {
         );
         let request_body = self.build_request(messages, options);
 
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read response body".into());
-            let error_msg = serde_json::from_str::<AnthropicError>(&body)
-                .map(|e| e.error.message)
-                .unwrap_or(body);
-            return Err(AyasError::Model(match status.as_u16() {
-                401 => ModelError::Auth(error_msg),
-                429 => ModelError::RateLimited {
-                    retry_after_secs: None,
-                },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
-            }));
+        let url = format!("{}/v1/messages", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
         }
 
         let api_response: AnthropicResponse = response
@@ -371,6 +515,7 @@ impl ChatModel for ClaudeChatModel {
             .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
 
         let mut text_parts = Vec::new();
+        let mut reasoning_parts = Vec::new();
         let mut tool_calls = Vec::new();
 
         for block in &api_response.content {
@@ -378,6 +523,10 @@ impl ChatModel for ClaudeChatModel {
                 AnthropicResponseContent::Text { text } => {
                     text_parts.push(text.clone());
                 }
+                AnthropicResponseContent::Thinking { thinking } => {
+                    reasoning_parts.push(thinking.clone());
+                }
+                AnthropicResponseContent::RedactedThinking {} => {}
                 AnthropicResponseContent::ToolUse { id, name, input } => {
                     if is_structured {
                         // Convert tool_use input to text content for structured output
@@ -394,20 +543,30 @@ impl ChatModel for ClaudeChatModel {
         }
 
         let text = text_parts.join("");
+        let reasoning = if reasoning_parts.is_empty() {
+            None
+        } else {
+            Some(reasoning_parts.join(""))
+        };
 
         let usage = UsageMetadata {
             input_tokens: api_response.usage.input_tokens,
             output_tokens: api_response.usage.output_tokens,
             total_tokens: api_response.usage.input_tokens + api_response.usage.output_tokens,
+            cache_read_tokens: api_response.usage.cache_read_input_tokens,
+            cache_write_tokens: api_response.usage.cache_creation_input_tokens,
+            ..Default::default()
         };
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls,
+            usage: Some(usage.clone()),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls,
-                usage: Some(usage.clone()),
-            }),
             usage: Some(usage),
+            reasoning,
+            ..ChatResult::new(message)
         })
     }
 
@@ -415,6 +574,38 @@ impl ChatModel for ClaudeChatModel {
         &self.model_id
     }
 
+    /// Count with the `/v1/messages/count_tokens` endpoint, which sees the
+    /// same system prompt, messages and tools as `generate`. Counts may
+    /// still differ from the tokens billed for a real call.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        let request = self.build_request(messages, &CallOptions::default());
+        let mut request_body = serde_json::to_value(request)?;
+        // The endpoint rejects generation parameters.
+        if let Some(fields) = request_body.as_object_mut() {
+            fields.retain(|key, _| COUNT_TOKENS_FIELDS.contains(&key.as_str()));
+        }
+
+        let url = format!("{}/v1/messages/count_tokens", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
+        }
+
+        let body: AnthropicCountTokensResponse = response
+            .json()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+        Ok(body.input_tokens)
+    }
+
     async fn stream(
         &self,
         messages: &[Message],
@@ -423,32 +614,19 @@ impl ChatModel for ClaudeChatModel {
         let mut request_body = self.build_request(messages, options);
         request_body.stream = true;
 
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read response body".into());
-            let error_msg = serde_json::from_str::<AnthropicError>(&body)
-                .map(|e| e.error.message)
-                .unwrap_or(body);
-            return Err(AyasError::Model(match status.as_u16() {
-                401 => ModelError::Auth(error_msg),
-                429 => ModelError::RateLimited {
-                    retry_after_secs: None,
-                },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
-            }));
+        let url = format!("{}/v1/messages", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
         }
 
         let data_stream = sse_data_stream(response);
@@ -456,6 +634,8 @@ impl ChatModel for ClaudeChatModel {
         let event_stream = async_stream::stream! {
             let mut current_tool_id = String::new();
             let mut input_tokens = 0u64;
+            let mut cache_read_tokens: Option<u64> = None;
+            let mut cache_write_tokens: Option<u64> = None;
             let mut data_stream = Box::pin(data_stream);
 
             while let Some(data) = data_stream.next().await {
@@ -466,9 +646,10 @@ impl ChatModel for ClaudeChatModel {
 
                 match json["type"].as_str().unwrap_or("") {
                     "message_start" => {
-                        input_tokens = json["message"]["usage"]["input_tokens"]
-                            .as_u64()
-                            .unwrap_or(0);
+                        let usage = &json["message"]["usage"];
+                        input_tokens = usage["input_tokens"].as_u64().unwrap_or(0);
+                        cache_read_tokens = usage["cache_read_input_tokens"].as_u64();
+                        cache_write_tokens = usage["cache_creation_input_tokens"].as_u64();
                     }
                     "content_block_start" => {
                         let block = &json["content_block"];
@@ -489,6 +670,11 @@ impl ChatModel for ClaudeChatModel {
                                     yield Ok(ChatStreamEvent::Token(text.to_string()));
                                 }
                             }
+                            Some("thinking_delta") => {
+                                if let Some(thinking) = delta["thinking"].as_str() {
+                                    yield Ok(ChatStreamEvent::Reasoning(thinking.to_string()));
+                                }
+                            }
                             Some("input_json_delta") => {
                                 if let Some(partial) = delta["partial_json"].as_str() {
                                     yield Ok(ChatStreamEvent::ToolCallDelta {
@@ -507,6 +693,13 @@ impl ChatModel for ClaudeChatModel {
                             input_tokens,
                             output_tokens,
                             total_tokens: input_tokens + output_tokens,
+                            cache_read_tokens: json["usage"]["cache_read_input_tokens"]
+                                .as_u64()
+                                .or(cache_read_tokens),
+                            cache_write_tokens: json["usage"]["cache_creation_input_tokens"]
+                                .as_u64()
+                                .or(cache_write_tokens),
+                            ..Default::default()
                         }));
                     }
                     "message_stop" => {
@@ -517,7 +710,7 @@ impl ChatModel for ClaudeChatModel {
             }
         };
 
-        Ok(Box::pin(event_stream))
+        Ok(stop_at_sequences(event_stream, &options.stop))
     }
 }
 
@@ -557,6 +750,11 @@ pub fn parse_claude_sse_data(
                         events.push(ChatStreamEvent::Token(text.to_string()));
                     }
                 }
+                Some("thinking_delta") => {
+                    if let Some(thinking) = delta["thinking"].as_str() {
+                        events.push(ChatStreamEvent::Reasoning(thinking.to_string()));
+                    }
+                }
                 Some("input_json_delta") => {
                     if let Some(partial) = delta["partial_json"].as_str() {
                         events.push(ChatStreamEvent::ToolCallDelta {
@@ -574,6 +772,9 @@ pub fn parse_claude_sse_data(
                 input_tokens: *input_tokens,
                 output_tokens,
                 total_tokens: *input_tokens + output_tokens,
+                cache_read_tokens: json["usage"]["cache_read_input_tokens"].as_u64(),
+                cache_write_tokens: json["usage"]["cache_creation_input_tokens"].as_u64(),
+                ..Default::default()
             }));
         }
         "message_stop" => {
@@ -596,6 +797,19 @@ mod tests {
         ClaudeChatModel::new("test-key".into(), "claude-sonnet-4-5-20250929".into())
     }
 
+    #[test]
+    fn with_config_sets_extra_headers() {
+        let config = crate::config::ProviderConfig::new().with_header("anthropic-beta", "value-1");
+        let model = make_model().with_config(&config).unwrap();
+        assert_eq!(model.extra_headers.get("anthropic-beta").unwrap(), "value-1");
+    }
+
+    #[test]
+    fn with_config_rejects_invalid_header() {
+        let config = crate::config::ProviderConfig::new().with_header("bad header", "x");
+        assert!(make_model().with_config(&config).is_err());
+    }
+
     #[test]
     fn build_request_basic() {
         let model = make_model();
@@ -609,6 +823,20 @@ mod tests {
         assert!(req.tools.is_none());
     }
 
+    #[test]
+    fn build_request_applies_request_overrides() {
+        let model = make_model();
+        let chain_default = CallOptions {
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+        let config = ayas_core::config::RunnableConfig::default()
+            .with_model_overrides(serde_json::json!({"temperature": 0.9}));
+        let options = chain_default.with_overrides(&config);
+        let req = model.build_request(&[Message::user("Hello")], &options);
+        assert_eq!(req.temperature, Some(0.9));
+    }
+
     #[test]
     fn build_request_system_extract() {
         let model = make_model();
@@ -618,7 +846,10 @@ mod tests {
         ];
         let options = CallOptions::default();
         let req = model.build_request(&messages, &options);
-        assert_eq!(req.system.as_deref(), Some("You are helpful"));
+        assert_eq!(
+            req.system.as_ref().and_then(|s| s.as_text()),
+            Some("You are helpful")
+        );
         assert_eq!(req.messages.len(), 1); // system not in messages
     }
 
@@ -696,15 +927,33 @@ mod tests {
                     AnthropicContentPart::ToolResult {
                         tool_use_id,
                         content,
+                        is_error,
                     } => {
                         assert_eq!(tool_use_id, "call_123");
                         assert_eq!(content, "4");
+                        assert!(!is_error);
                     }
                     _ => panic!("expected ToolResult"),
                 }
             }
             _ => panic!("expected Parts"),
         }
+        let json = serde_json::to_value(&req).unwrap();
+        assert!(json["messages"][0]["content"][0].get("is_error").is_none());
+    }
+
+    #[test]
+    fn build_request_tool_error_message() {
+        let model = make_model();
+        let messages = vec![Message::tool_error("division by zero", "call_123")];
+        let options = CallOptions::default();
+        let req = model.build_request(&messages, &options);
+        let json = serde_json::to_value(&req).unwrap();
+        let part = &json["messages"][0]["content"][0];
+        assert_eq!(part["type"], "tool_result");
+        assert_eq!(part["tool_use_id"], "call_123");
+        assert_eq!(part["content"], "division by zero");
+        assert_eq!(part["is_error"], true);
     }
 
     #[test]
@@ -841,7 +1090,13 @@ mod tests {
         };
         let req = model.build_request(&messages, &options);
         // Should append JSON instruction to system prompt
-        assert!(req.system.as_deref().unwrap().contains("Always respond in valid JSON."));
+        assert!(
+            req.system
+                .as_ref()
+                .and_then(|s| s.as_text())
+                .unwrap()
+                .contains("Always respond in valid JSON.")
+        );
         // No tool_choice for JsonObject
         assert!(req.tool_choice.is_none());
     }
@@ -858,7 +1113,7 @@ mod tests {
             ..Default::default()
         };
         let req = model.build_request(&messages, &options);
-        let sys = req.system.as_deref().unwrap();
+        let sys = req.system.as_ref().and_then(|s| s.as_text()).unwrap();
         assert!(sys.starts_with("You are helpful"));
         assert!(sys.contains("Always respond in valid JSON."));
     }
@@ -924,6 +1179,84 @@ mod tests {
         assert_eq!(tools[1].name, "person");
     }
 
+    // -----------------------------------------------------------------------
+    // Prompt caching tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn build_request_cache_marks_system_and_last_message() {
+        let model = make_model();
+        let messages = vec![
+            Message::system("Long system prompt"),
+            Message::user("First"),
+            Message::user("Second"),
+        ];
+        let options = CallOptions {
+            cache: true,
+            ..Default::default()
+        };
+        let req = model.build_request(&messages, &options);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["system"],
+            serde_json::json!([{
+                "type": "text",
+                "text": "Long system prompt",
+                "cache_control": {"type": "ephemeral"}
+            }])
+        );
+        // Only the last message gets a breakpoint.
+        assert_eq!(json["messages"][0]["content"], "First");
+        assert_eq!(
+            json["messages"][1]["content"][0]["cache_control"],
+            serde_json::json!({"type": "ephemeral"})
+        );
+        assert_eq!(
+            req.system.as_ref().and_then(|s| s.as_text()),
+            Some("Long system prompt")
+        );
+    }
+
+    #[test]
+    fn build_request_cache_marks_last_block_of_parts() {
+        let model = make_model();
+        let messages = vec![Message::tool("result", "call_1")];
+        let options = CallOptions {
+            cache: true,
+            ..Default::default()
+        };
+        let req = model.build_request(&messages, &options);
+        let json = serde_json::to_value(&req).unwrap();
+        let block = &json["messages"][0]["content"][0];
+        assert_eq!(block["type"], "tool_result");
+        assert_eq!(block["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn build_request_no_cache_by_default() {
+        let model = make_model();
+        let messages = vec![Message::system("sys"), Message::user("Hello")];
+        let req = model.build_request(&messages, &CallOptions::default());
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(!json.contains("cache_control"));
+    }
+
+    #[test]
+    fn parse_response_cache_usage() {
+        let json = r#"{
+            "content": [{"type": "text", "text": "Hi"}],
+            "usage": {
+                "input_tokens": 10,
+                "output_tokens": 5,
+                "cache_read_input_tokens": 2000,
+                "cache_creation_input_tokens": 0
+            }
+        }"#;
+        let resp: AnthropicResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.usage.cache_read_input_tokens, Some(2000));
+        assert_eq!(resp.usage.cache_creation_input_tokens, Some(0));
+    }
+
     // -----------------------------------------------------------------------
     // SSE parsing tests
     // -----------------------------------------------------------------------
@@ -948,6 +1281,33 @@ mod tests {
         assert_eq!(events[0], ChatStreamEvent::Token("Hello".into()));
     }
 
+    #[test]
+    fn parse_sse_thinking_delta() {
+        let data = r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Let me think"}}"#;
+        let mut tool_id = String::new();
+        let mut input_tokens = 0u64;
+        let events = parse_claude_sse_data(data, &mut tool_id, &mut input_tokens);
+        assert_eq!(events, vec![ChatStreamEvent::Reasoning("Let me think".into())]);
+    }
+
+    #[test]
+    fn parse_response_thinking_block() {
+        let json = r#"{
+            "content": [
+                {"type": "thinking", "thinking": "2+2 is 4", "signature": "sig"},
+                {"type": "redacted_thinking", "data": "xyz"},
+                {"type": "text", "text": "4"}
+            ],
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }"#;
+        let resp: AnthropicResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.content.len(), 3);
+        match &resp.content[0] {
+            AnthropicResponseContent::Thinking { thinking } => assert_eq!(thinking, "2+2 is 4"),
+            _ => panic!("expected Thinking"),
+        }
+    }
+
     #[test]
     fn parse_sse_tool_use_start() {
         let data = r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_01","name":"calculator","input":{}}}"#;
@@ -995,6 +1355,7 @@ mod tests {
                 input_tokens: 25,
                 output_tokens: 12,
                 total_tokens: 37,
+                ..Default::default()
             })
         );
     }
@@ -1038,6 +1399,7 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 5,
                 total_tokens: 15,
+                ..Default::default()
             })
         );
         assert_eq!(all_events[3], ChatStreamEvent::Done);
@@ -0,0 +1,215 @@
+//! Shared provider configuration (extra HTTP headers sent with every request,
+//! model id validation).
+
+use std::collections::HashMap;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+
+use crate::provider::Provider;
+
+/// Extra configuration applied to every request made by a provider model.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderConfig {
+    /// Additional HTTP headers (e.g. `OpenAI-Organization`, `anthropic-beta`).
+    pub extra_headers: HashMap<String, String>,
+    /// Accept model ids outside [`Provider::known_models`] instead of failing
+    /// with `ModelError::UnknownModel`.
+    pub allow_unknown_models: bool,
+}
+
+impl ProviderConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single extra header.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Accept model ids the provider does not list as known.
+    pub fn with_allow_unknown_models(mut self, allow: bool) -> Self {
+        self.allow_unknown_models = allow;
+        self
+    }
+
+    /// Build a config from environment variables for the given provider.
+    ///
+    /// | Provider | Variable               | Header                |
+    /// |----------|------------------------|-----------------------|
+    /// | OpenAI   | `OPENAI_ORGANIZATION`  | `OpenAI-Organization` |
+    /// | OpenAI   | `OPENAI_PROJECT`       | `OpenAI-Project`      |
+    /// | Claude   | `ANTHROPIC_BETA`       | `anthropic-beta`      |
+    ///
+    /// Additionally, `<PREFIX>_EXTRA_HEADERS` (`OPENAI_`, `ANTHROPIC_`, `GEMINI_`)
+    /// accepts `Name: value` pairs separated by `;`, and
+    /// `AYAS_ALLOW_UNKNOWN_MODELS=true` (or `1`) sets `allow_unknown_models`.
+    ///
+    /// Returns an error if any header name or value is invalid.
+    pub fn from_env(provider: &Provider) -> Result<Self> {
+        Self::from_lookup(provider, |key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(provider: &Provider, lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let (prefix, known): (&str, &[(&str, &str)]) = match provider {
+            Provider::OpenAI => (
+                "OPENAI",
+                &[
+                    ("OPENAI_ORGANIZATION", "OpenAI-Organization"),
+                    ("OPENAI_PROJECT", "OpenAI-Project"),
+                ],
+            ),
+            Provider::Claude => ("ANTHROPIC", &[("ANTHROPIC_BETA", "anthropic-beta")]),
+            Provider::Gemini => ("GEMINI", &[]),
+        };
+
+        let mut config = Self {
+            allow_unknown_models: lookup("AYAS_ALLOW_UNKNOWN_MODELS")
+                .is_some_and(|v| v == "true" || v == "1"),
+            ..Self::default()
+        };
+        for (var, header) in known {
+            if let Some(value) = lookup(var).filter(|v| !v.is_empty()) {
+                config.extra_headers.insert((*header).to_string(), value);
+            }
+        }
+
+        let var = format!("{prefix}_EXTRA_HEADERS");
+        if let Some(raw) = lookup(&var) {
+            for pair in raw.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+                let (name, value) = pair.split_once(':').ok_or_else(|| {
+                    AyasError::Model(ModelError::InvalidConfig(format!(
+                        "{var}: expected 'Name: value', got '{pair}'"
+                    )))
+                })?;
+                config
+                    .extra_headers
+                    .insert(name.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        config.header_map()?;
+        Ok(config)
+    }
+
+    /// Validate and convert the extra headers into a `HeaderMap`.
+    pub fn header_map(&self) -> Result<HeaderMap> {
+        build_header_map(&self.extra_headers)
+    }
+}
+
+/// Validate header names/values and convert them into a `HeaderMap`.
+pub fn build_header_map(headers: &HashMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+            AyasError::Model(ModelError::InvalidConfig(format!(
+                "invalid header name '{name}': {e}"
+            )))
+        })?;
+        let header_value = HeaderValue::from_str(value).map_err(|e| {
+            AyasError::Model(ModelError::InvalidConfig(format!(
+                "invalid value for header '{name}': {e}"
+            )))
+        })?;
+        map.insert(header_name, header_value);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn build_header_map_valid() {
+        let config = ProviderConfig::new().with_header("OpenAI-Organization", "org-123");
+        let map = config.header_map().unwrap();
+        assert_eq!(map.get("openai-organization").unwrap(), "org-123");
+    }
+
+    #[test]
+    fn build_header_map_invalid_name() {
+        let config = ProviderConfig::new().with_header("bad header", "x");
+        let err = config.header_map().unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn build_header_map_invalid_value() {
+        let config = ProviderConfig::new().with_header("x-test", "line\nbreak");
+        assert!(config.header_map().is_err());
+    }
+
+    #[test]
+    fn from_env_openai_known_vars() {
+        let config = ProviderConfig::from_lookup(
+            &Provider::OpenAI,
+            lookup(&[("OPENAI_ORGANIZATION", "org-1"), ("OPENAI_PROJECT", "proj-1")]),
+        )
+        .unwrap();
+        assert_eq!(config.extra_headers["OpenAI-Organization"], "org-1");
+        assert_eq!(config.extra_headers["OpenAI-Project"], "proj-1");
+    }
+
+    #[test]
+    fn from_env_claude_beta() {
+        let config = ProviderConfig::from_lookup(
+            &Provider::Claude,
+            lookup(&[("ANTHROPIC_BETA", "prompt-caching-2024-07-31")]),
+        )
+        .unwrap();
+        assert_eq!(
+            config.extra_headers["anthropic-beta"],
+            "prompt-caching-2024-07-31"
+        );
+    }
+
+    #[test]
+    fn from_env_extra_headers_list() {
+        let config = ProviderConfig::from_lookup(
+            &Provider::Gemini,
+            lookup(&[("GEMINI_EXTRA_HEADERS", "x-a: 1; x-b: two")]),
+        )
+        .unwrap();
+        assert_eq!(config.extra_headers["x-a"], "1");
+        assert_eq!(config.extra_headers["x-b"], "two");
+    }
+
+    #[test]
+    fn from_env_extra_headers_malformed() {
+        let result = ProviderConfig::from_lookup(
+            &Provider::Gemini,
+            lookup(&[("GEMINI_EXTRA_HEADERS", "no-colon")]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_env_empty() {
+        let config = ProviderConfig::from_lookup(&Provider::OpenAI, lookup(&[])).unwrap();
+        assert!(config.extra_headers.is_empty());
+        assert!(!config.allow_unknown_models);
+    }
+
+    #[test]
+    fn from_env_allow_unknown_models() {
+        let config = ProviderConfig::from_lookup(
+            &Provider::Gemini,
+            lookup(&[("AYAS_ALLOW_UNKNOWN_MODELS", "1")]),
+        )
+        .unwrap();
+        assert!(config.allow_unknown_models);
+    }
+}
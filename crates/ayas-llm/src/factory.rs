@@ -1,21 +1,89 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ayas_core::error::{AyasError, ModelError, Result};
 use ayas_core::model::ChatModel;
 
 use crate::claude::ClaudeChatModel;
+use crate::config::ProviderConfig;
+use crate::fallback::ChatModelWithFallback;
 use crate::gemini::GeminiChatModel;
 use crate::openai::OpenAIChatModel;
 use crate::provider::Provider;
 
 /// Create a ChatModel instance for the given provider.
+///
+/// Extra headers are populated from environment variables via
+/// [`ProviderConfig::from_env`]. Returns an error if those variables hold
+/// invalid header names or values, or if `model_id` is not a known model or
+/// alias (see [`create_chat_model_with_config`]).
 pub fn create_chat_model(
     provider: &Provider,
     api_key: String,
     model_id: String,
-) -> Box<dyn ChatModel> {
-    match provider {
-        Provider::Gemini => Box::new(GeminiChatModel::new(api_key, model_id)),
-        Provider::Claude => Box::new(ClaudeChatModel::new(api_key, model_id)),
-        Provider::OpenAI => Box::new(OpenAIChatModel::new(api_key, model_id)),
+) -> Result<Box<dyn ChatModel>> {
+    let config = ProviderConfig::from_env(provider)?;
+    create_chat_model_with_config(provider, api_key, model_id, &config)
+}
+
+/// Create a ChatModel instance with an explicit provider config.
+///
+/// `model_id` may be one of [`Provider::model_aliases`]. Returns an error if
+/// the config contains invalid header names or values, or if the model is not
+/// one of [`Provider::known_models`] and `config.allow_unknown_models` is off.
+pub fn create_chat_model_with_config(
+    provider: &Provider,
+    api_key: String,
+    model_id: String,
+    config: &ProviderConfig,
+) -> Result<Box<dyn ChatModel>> {
+    let model_id = provider.resolve_model(&model_id, config.allow_unknown_models)?;
+    Ok(match provider {
+        Provider::Gemini => Box::new(GeminiChatModel::new(api_key, model_id).with_config(config)?),
+        Provider::Claude => Box::new(ClaudeChatModel::new(api_key, model_id).with_config(config)?),
+        Provider::OpenAI => Box::new(OpenAIChatModel::new(api_key, model_id).with_config(config)?),
+    })
+}
+
+/// Create a ChatModel that fails over from `primary` to each of `fallbacks`
+/// in order on retryable errors (rate limits, server errors, transport failures).
+///
+/// Each model is a `(provider, model_id)` pair, resolved as in
+/// [`create_chat_model`]. `keys` maps each provider to its API key.
+pub fn create_with_fallback(
+    primary: (Provider, String),
+    fallbacks: Vec<(Provider, String)>,
+    keys: &HashMap<Provider, String>,
+) -> Result<Arc<dyn ChatModel>> {
+    let build = |(provider, model_id): (Provider, String)| -> Result<Arc<dyn ChatModel>> {
+        let api_key = keys.get(&provider).cloned().ok_or_else(|| {
+            AyasError::Model(ModelError::InvalidConfig(format!(
+                "missing API key for {provider:?}"
+            )))
+        })?;
+        Ok(Arc::from(create_chat_model(&provider, api_key, model_id)?))
+    };
+
+    let primary_model = build(primary)?;
+    let fallback_models = fallbacks
+        .into_iter()
+        .map(build)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Arc::new(ChatModelWithFallback::new(
+        primary_model,
+        fallback_models,
+    )))
+}
+
+/// Validate the environment-derived provider configs for all providers.
+///
+/// Intended to be called once at startup so that malformed header
+/// variables fail fast instead of being silently ignored.
+pub fn validate_env_config() -> Result<()> {
+    for provider in [Provider::Gemini, Provider::Claude, Provider::OpenAI] {
+        ProviderConfig::from_env(&provider)?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -24,23 +92,114 @@ mod tests {
 
     #[test]
     fn create_gemini_model() {
-        let model = create_chat_model(&Provider::Gemini, "key".into(), "gemini-2.0-flash".into());
+        let model =
+            create_chat_model(&Provider::Gemini, "key".into(), "gemini-2.0-flash".into()).unwrap();
         assert_eq!(model.model_name(), "gemini-2.0-flash");
     }
 
+    #[test]
+    fn create_gemini_model_resolves_alias() {
+        let model =
+            create_chat_model(&Provider::Gemini, "key".into(), "gemini-flash".into()).unwrap();
+        assert_eq!(model.model_name(), "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn create_gemini_model_unknown_fails_before_request() {
+        let err = create_chat_model(&Provider::Gemini, "key".into(), "gemini-2.5-flsh".into())
+            .err()
+            .unwrap();
+        match err {
+            AyasError::Model(ModelError::UnknownModel { suggestions, .. }) => {
+                assert_eq!(suggestions[0], "gemini-2.5-flash");
+            }
+            other => panic!("expected UnknownModel, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn create_with_config_unknown_model() {
+        let create = |config: &ProviderConfig| {
+            create_chat_model_with_config(
+                &Provider::Gemini,
+                "key".into(),
+                "gemini-4-flash".into(),
+                config,
+            )
+        };
+        let err = create(&ProviderConfig::new()).err().unwrap();
+        assert!(matches!(err, AyasError::Model(ModelError::UnknownModel { .. })));
+
+        let model = create(&ProviderConfig::new().with_allow_unknown_models(true)).unwrap();
+        assert_eq!(model.model_name(), "gemini-4-flash");
+    }
+
     #[test]
     fn create_claude_model() {
         let model = create_chat_model(
             &Provider::Claude,
             "key".into(),
             "claude-sonnet-4-5-20250929".into(),
-        );
+        )
+        .unwrap();
         assert_eq!(model.model_name(), "claude-sonnet-4-5-20250929");
     }
 
     #[test]
     fn create_openai_model() {
-        let model = create_chat_model(&Provider::OpenAI, "key".into(), "gpt-4o-mini".into());
+        let model =
+            create_chat_model(&Provider::OpenAI, "key".into(), "gpt-4o-mini".into()).unwrap();
+        assert_eq!(model.model_name(), "gpt-4o-mini");
+    }
+
+    #[test]
+    fn create_with_fallback_uses_primary_name() {
+        let keys = HashMap::from([
+            (Provider::Gemini, "gk".to_string()),
+            (Provider::Claude, "ck".to_string()),
+        ]);
+        let model = create_with_fallback(
+            (Provider::Gemini, "gemini-flash".into()),
+            vec![(Provider::Claude, "claude-haiku-4-5-20251001".into())],
+            &keys,
+        )
+        .unwrap();
+        assert_eq!(model.model_name(), "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn create_with_fallback_missing_key() {
+        let keys = HashMap::from([(Provider::Gemini, "gk".to_string())]);
+        let result = create_with_fallback(
+            (Provider::Gemini, "gemini-2.0-flash".into()),
+            vec![(Provider::OpenAI, "gpt-4o-mini".into())],
+            &keys,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_with_config_valid_headers() {
+        let config = ProviderConfig::new().with_header("OpenAI-Project", "proj-1");
+        let model = create_chat_model_with_config(
+            &Provider::OpenAI,
+            "key".into(),
+            "gpt-4o-mini".into(),
+            &config,
+        )
+        .unwrap();
         assert_eq!(model.model_name(), "gpt-4o-mini");
     }
+
+    #[test]
+    fn create_with_config_invalid_headers() {
+        let config = ProviderConfig::new().with_header("anthropic-beta", "bad\nvalue");
+        let result = create_chat_model_with_config(
+            &Provider::Claude,
+            "key".into(),
+            "claude-sonnet-4-5-20250929".into(),
+            &config,
+        );
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,331 @@
+//! ChatModel-level provider failover.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+use ayas_core::message::Message;
+use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent};
+
+/// Wraps a primary ChatModel and an ordered list of fallbacks.
+///
+/// Each model is tried in order, moving on only when the error is retryable
+/// (see [`ModelError::is_retryable`]). Each model converts the shared
+/// `CallOptions` into its own provider request format. Non-retryable errors
+/// are returned immediately; if every model fails, the individual errors are
+/// collected into [`ModelError::AllFailed`].
+///
+/// For `stream`, failover only applies to errors raised while opening the
+/// stream; errors yielded mid-stream are passed through.
+///
+/// [`ChatModel::model_name`] reports the primary, so usage from a call served
+/// by another model is tagged with that model's name in
+/// [`UsageMetadata::model`](ayas_core::message::UsageMetadata::model) and
+/// priced accordingly.
+pub struct ChatModelWithFallback {
+    models: Vec<Arc<dyn ChatModel>>,
+}
+
+impl ChatModelWithFallback {
+    pub fn new(primary: Arc<dyn ChatModel>, fallbacks: Vec<Arc<dyn ChatModel>>) -> Self {
+        let mut models = Vec::with_capacity(fallbacks.len() + 1);
+        models.push(primary);
+        models.extend(fallbacks);
+        Self { models }
+    }
+
+    /// The wrapped models, primary first.
+    pub fn models(&self) -> &[Arc<dyn ChatModel>] {
+        &self.models
+    }
+
+    /// Run `call` on each model in turn until one succeeds or fails with a
+    /// non-retryable error.
+    async fn failover<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn ChatModel>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempts = Vec::new();
+        for model in &self.models {
+            match call(model.clone()).await {
+                Err(AyasError::Model(e)) if e.is_retryable() => {
+                    attempts.push((model.model_name().to_string(), AyasError::Model(e)));
+                }
+                result => return result,
+            }
+        }
+        Err(AyasError::Model(ModelError::AllFailed { attempts }))
+    }
+}
+
+#[async_trait]
+impl ChatModel for ChatModelWithFallback {
+    async fn generate(&self, messages: &[Message], options: &CallOptions) -> Result<ChatResult> {
+        self.failover(|model| async move {
+            let mut result = model.generate(messages, options).await?;
+            if let Some(usage) = result.usage.as_mut() {
+                usage
+                    .model
+                    .get_or_insert_with(|| model.model_name().to_string());
+            }
+            Ok(result)
+        })
+        .await
+    }
+
+    fn model_name(&self) -> &str {
+        self.models[0].model_name()
+    }
+
+    /// Count with the first model that supports counting.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        let mut last_err = None;
+        for model in &self.models {
+            match model.count_tokens(messages).await {
+                Err(AyasError::Model(e @ ModelError::Unsupported(_))) => {
+                    last_err = Some(AyasError::Model(e));
+                }
+                other => return other,
+            }
+        }
+        Err(last_err.expect("at least one model"))
+    }
+
+    async fn stream(
+        &self,
+        messages: &[Message],
+        options: &CallOptions,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
+        self.failover(|model| async move {
+            let stream = model.stream(messages, options).await?;
+            let served_by = model.model_name().to_string();
+            let stream = stream.map(move |event| match event {
+                Ok(ChatStreamEvent::Usage(mut usage)) => {
+                    usage.model.get_or_insert_with(|| served_by.clone());
+                    Ok(ChatStreamEvent::Usage(usage))
+                }
+                other => other,
+            });
+            Ok(Box::pin(stream) as Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ayas_core::budget::{Pricer, UsageAccumulator};
+    use ayas_core::config::RunnableConfig;
+    use ayas_core::message::UsageMetadata;
+    use ayas_core::model::generate_with_config;
+
+    /// A model that answers every call with `result()`.
+    struct MockModel {
+        name: &'static str,
+        result: fn() -> Result<ChatResult>,
+        token_count: Option<u64>,
+        calls: AtomicUsize,
+    }
+
+    fn mock(name: &'static str, result: fn() -> Result<ChatResult>) -> MockModel {
+        MockModel {
+            name,
+            result,
+            token_count: None,
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    fn answer() -> Result<ChatResult> {
+        Ok(ChatResult {
+            usage: Some(UsageMetadata {
+                input_tokens: 3,
+                output_tokens: 2,
+                total_tokens: 5,
+                ..Default::default()
+            }),
+            ..ChatResult::new(Message::ai("from fallback"))
+        })
+    }
+
+    fn rate_limited() -> Result<ChatResult> {
+        Err(ModelError::RateLimited {
+            retry_after_secs: Some(10),
+        }
+        .into())
+    }
+
+    #[async_trait]
+    impl ChatModel for MockModel {
+        async fn generate(&self, _: &[Message], _: &CallOptions) -> Result<ChatResult> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            (self.result)()
+        }
+        fn model_name(&self) -> &str {
+            self.name
+        }
+        async fn count_tokens(&self, _: &[Message]) -> Result<u64> {
+            self.token_count
+                .ok_or_else(|| ModelError::Unsupported("count_tokens".into()).into())
+        }
+    }
+
+    async fn generate(model: &ChatModelWithFallback) -> Result<ChatResult> {
+        model
+            .generate(&[Message::user("Hi")], &CallOptions::default())
+            .await
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_rate_limit() {
+        let primary = Arc::new(mock("rate-limited", rate_limited));
+        let model = ChatModelWithFallback::new(primary.clone(), vec![Arc::new(mock("ok", answer))]);
+        let result = generate(&model).await.unwrap();
+        assert_eq!(result.message.content(), "from fallback");
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn usage_is_tagged_with_serving_model() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("rate-limited", rate_limited)),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        let result = generate(&model).await.unwrap();
+        assert_eq!(result.usage.unwrap().model.as_deref(), Some("ok"));
+
+        let model = ChatModelWithFallback::new(Arc::new(mock("ok", answer)), vec![]);
+        let result = generate(&model).await.unwrap();
+        assert_eq!(result.usage.unwrap().model.as_deref(), Some("ok"));
+    }
+
+    #[tokio::test]
+    async fn usage_is_priced_as_serving_model() {
+        let pricer: Pricer = Arc::new(|model: &str, _: &UsageMetadata| match model {
+            "ok" => 1.0,
+            _ => 100.0,
+        });
+        let usage = UsageAccumulator::new().with_pricer(pricer);
+        let config = RunnableConfig::default().with_usage(usage.clone());
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("rate-limited", rate_limited)),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        generate_with_config(&model, &[], &CallOptions::default(), &config)
+            .await
+            .unwrap();
+        assert_eq!(usage.spent().cost, 1.0);
+    }
+
+    #[tokio::test]
+    async fn all_failed_preserves_errors() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("rate-limited", rate_limited)),
+            vec![Arc::new(mock("rate-limited", rate_limited))],
+        );
+        match generate(&model).await.unwrap_err() {
+            AyasError::Model(ModelError::AllFailed { attempts }) => {
+                assert_eq!(attempts.len(), 2);
+                assert!(matches!(
+                    attempts[0].1,
+                    AyasError::Model(ModelError::RateLimited { .. })
+                ));
+            }
+            other => panic!("expected AllFailed, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn count_tokens_uses_first_model_that_counts() {
+        let messages = [Message::user("Hi")];
+        let counting = MockModel {
+            token_count: Some(42),
+            ..mock("counting", answer)
+        };
+        let model =
+            ChatModelWithFallback::new(Arc::new(mock("ok", answer)), vec![Arc::new(counting)]);
+        assert_eq!(model.count_tokens(&messages).await.unwrap(), 42);
+
+        let model = ChatModelWithFallback::new(Arc::new(mock("ok", answer)), vec![]);
+        let err = model.count_tokens(&messages).await.unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::Unsupported(_))));
+    }
+
+    #[tokio::test]
+    async fn non_retryable_error_short_circuits() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("auth-error", || {
+                Err(ModelError::Auth("bad key".into()).into())
+            })),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        let err = generate(&model).await.unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn inner_all_failed_is_returned_as_is() {
+        // A nested wrapper whose own fallbacks were all exhausted.
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("exhausted", || {
+                Err(ModelError::AllFailed { attempts: Vec::new() }.into())
+            })),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        let err = generate(&model).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AyasError::Model(ModelError::AllFailed { attempts }) if attempts.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn bad_request_does_not_fall_back() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("bad-request", || {
+                Err(ModelError::http(400, "invalid tool schema").into())
+            })),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        let err = generate(&model).await.unwrap_err();
+        assert!(matches!(err, AyasError::Model(ModelError::ApiRequest { .. })));
+    }
+
+    #[tokio::test]
+    async fn stream_falls_back() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("rate-limited", rate_limited)),
+            vec![Arc::new(mock("ok", answer))],
+        );
+        let events: Vec<_> = model
+            .stream(&[Message::user("Hi")], &CallOptions::default())
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        assert_eq!(events[0], ChatStreamEvent::Token("from fallback".into()));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            ChatStreamEvent::Usage(usage) if usage.model.as_deref() == Some("ok")
+        )));
+    }
+
+    #[test]
+    fn model_name_is_primary() {
+        let model = ChatModelWithFallback::new(
+            Arc::new(mock("ok", answer)),
+            vec![Arc::new(mock("rate-limited", rate_limited))],
+        );
+        assert_eq!(model.model_name(), "ok");
+        assert_eq!(model.models().len(), 2);
+    }
+}
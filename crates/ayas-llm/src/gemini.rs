@@ -2,6 +2,7 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 
 use ayas_core::error::{AyasError, ModelError, Result};
@@ -10,7 +11,10 @@ use ayas_core::message::{
 };
 use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent, ResponseFormat};
 
+use crate::config::ProviderConfig;
+use crate::retry::{retry_after_secs, send_with_retry};
 use crate::sse::sse_data_stream;
+use crate::stop::stop_at_sequences;
 
 // ---------------------------------------------------------------------------
 // Gemini API request types
@@ -25,9 +29,17 @@ pub struct GeminiRequest {
     pub generation_config: Option<GenerationConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<GeminiToolConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_settings: Option<Vec<GeminiSafetySetting>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
+pub struct GeminiSafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct GeminiContent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub role: Option<String>,
@@ -87,11 +99,13 @@ pub struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_sequences: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_mime_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub response_json_schema: Option<serde_json::Value>,
+    pub response_schema: Option<serde_json::Value>,
 }
 
 // ---------------------------------------------------------------------------
@@ -107,7 +121,11 @@ pub struct GeminiResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct GeminiCandidate {
+    /// Absent when the candidate was blocked before any content was produced.
+    #[serde(default)]
     pub content: GeminiContent,
+    #[serde(rename = "finishReason", default)]
+    pub finish_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -181,6 +199,165 @@ fn content_source_to_gemini(source: &ContentSource) -> GeminiPart {
     }
 }
 
+// ---------------------------------------------------------------------------
+// JSON Schema -> Gemini schema conversion
+// ---------------------------------------------------------------------------
+
+/// Keywords dropped silently because Gemini does not support them but they
+/// do not change the shape of valid output.
+const STRIPPED_SCHEMA_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "additionalProperties",
+    "unevaluatedProperties",
+    "examples",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "uniqueItems",
+];
+
+/// Keywords that change validation semantics in ways Gemini cannot express.
+const UNSUPPORTED_SCHEMA_KEYWORDS: &[&str] = &[
+    "allOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "patternProperties",
+    "dependentSchemas",
+    "dependentRequired",
+    "prefixItems",
+    "contains",
+];
+
+/// Convert a JSON Schema into Gemini's OpenAPI-style `responseSchema` dialect.
+///
+/// - Types are upper-cased (`"string"` -> `"STRING"`); `["T", "null"]`
+///   becomes `type: T` with `nullable: true`.
+/// - Local `$ref`s (`#/$defs/...`, `#/definitions/...`) are inlined.
+/// - `oneOf` is mapped to `anyOf`; `const` to a single-value `enum`.
+/// - Unsupported-but-harmless keywords (`$schema`, `additionalProperties`, ...)
+///   are stripped.
+///
+/// Returns `ModelError::InvalidConfig` for features Gemini cannot express
+/// (e.g. `allOf`, `not`, recursive or external `$ref`s, multi-type unions).
+pub fn to_gemini_schema(schema: &serde_json::Value) -> Result<serde_json::Value> {
+    convert_schema_node(schema, schema, &mut Vec::new()).map_err(|msg| {
+        AyasError::Model(ModelError::InvalidConfig(format!(
+            "unsupported JSON schema for Gemini: {msg}"
+        )))
+    })
+}
+
+fn resolve_local_ref<'a>(
+    root: &'a serde_json::Value,
+    reference: &str,
+) -> std::result::Result<&'a serde_json::Value, String> {
+    let path = reference
+        .strip_prefix("#/")
+        .ok_or_else(|| format!("external $ref '{reference}'"))?;
+    let mut node = root;
+    for segment in path.split('/') {
+        node = node
+            .get(segment)
+            .ok_or_else(|| format!("unresolvable $ref '{reference}'"))?;
+    }
+    Ok(node)
+}
+
+fn convert_schema_node(
+    node: &serde_json::Value,
+    root: &serde_json::Value,
+    ref_stack: &mut Vec<String>,
+) -> std::result::Result<serde_json::Value, String> {
+    use serde_json::{Map, Value};
+
+    let obj = match node {
+        Value::Object(obj) => obj,
+        Value::Bool(true) => return Ok(Value::Object(Map::new())),
+        other => return Err(format!("expected schema object, got {other}")),
+    };
+
+    if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+        if ref_stack.iter().any(|r| r == reference) {
+            return Err(format!("recursive $ref '{reference}'"));
+        }
+        let target = resolve_local_ref(root, reference)?;
+        ref_stack.push(reference.to_string());
+        let converted = convert_schema_node(target, root, ref_stack);
+        ref_stack.pop();
+        return converted;
+    }
+
+    let mut out = Map::new();
+    for (key, value) in obj {
+        let key = key.as_str();
+        if STRIPPED_SCHEMA_KEYWORDS.contains(&key) || key == "$defs" || key == "definitions" {
+            continue;
+        }
+        if UNSUPPORTED_SCHEMA_KEYWORDS.contains(&key) {
+            return Err(format!("keyword '{key}' is not supported"));
+        }
+        match key {
+            "type" => match value {
+                Value::String(t) => {
+                    out.insert("type".into(), Value::String(t.to_uppercase()));
+                }
+                Value::Array(types) => {
+                    let non_null: Vec<&str> = types
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .filter(|t| *t != "null")
+                        .collect();
+                    if non_null.len() != 1 {
+                        return Err(format!("multi-type union {value} is not supported"));
+                    }
+                    out.insert("type".into(), Value::String(non_null[0].to_uppercase()));
+                    if non_null.len() < types.len() {
+                        out.insert("nullable".into(), Value::Bool(true));
+                    }
+                }
+                other => return Err(format!("invalid type {other}")),
+            },
+            "properties" => {
+                let props = value
+                    .as_object()
+                    .ok_or_else(|| "'properties' must be an object".to_string())?;
+                let mut converted = Map::new();
+                for (name, prop) in props {
+                    converted.insert(name.clone(), convert_schema_node(prop, root, ref_stack)?);
+                }
+                out.insert("properties".into(), Value::Object(converted));
+            }
+            "items" => {
+                out.insert("items".into(), convert_schema_node(value, root, ref_stack)?);
+            }
+            "anyOf" | "oneOf" => {
+                let variants = value
+                    .as_array()
+                    .ok_or_else(|| format!("'{key}' must be an array"))?;
+                let converted = variants
+                    .iter()
+                    .map(|v| convert_schema_node(v, root, ref_stack))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                out.insert("anyOf".into(), Value::Array(converted));
+            }
+            "const" => {
+                out.insert("enum".into(), Value::Array(vec![value.clone()]));
+            }
+            _ => {
+                out.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+    Ok(Value::Object(out))
+}
+
 // ---------------------------------------------------------------------------
 // GeminiChatModel
 // ---------------------------------------------------------------------------
@@ -189,6 +366,9 @@ pub struct GeminiChatModel {
     api_key: String,
     model_id: String,
     client: reqwest::Client,
+    extra_headers: HeaderMap,
+    base_url: String,
+    max_auto_retries: u32,
 }
 
 impl GeminiChatModel {
@@ -197,10 +377,42 @@ impl GeminiChatModel {
             api_key,
             model_id,
             client: reqwest::Client::new(),
+            extra_headers: HeaderMap::new(),
+            base_url: "https://generativelanguage.googleapis.com/v1beta".into(),
+            max_auto_retries: 0,
         }
     }
 
-    pub fn build_request(&self, messages: &[Message], options: &CallOptions) -> GeminiRequest {
+    /// Apply a provider config; its extra headers are sent with every request.
+    ///
+    /// Returns an error if any header name or value is invalid.
+    pub fn with_config(mut self, config: &ProviderConfig) -> Result<Self> {
+        self.extra_headers = config.header_map()?;
+        Ok(self)
+    }
+
+    /// Send requests to `base_url` instead of the public API (e.g. a proxy).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Retry requests answered with 429 or 503 up to `max_auto_retries` times,
+    /// honoring `Retry-After`. The default of 0 returns the error right away.
+    pub fn with_max_auto_retries(mut self, max_auto_retries: u32) -> Self {
+        self.max_auto_retries = max_auto_retries;
+        self
+    }
+
+    /// Build a Gemini request.
+    ///
+    /// Returns an error if a `JsonSchema` response format uses features that
+    /// Gemini's schema dialect cannot express.
+    pub fn build_request(
+        &self,
+        messages: &[Message],
+        options: &CallOptions,
+    ) -> Result<GeminiRequest> {
         let mut system_instruction: Option<GeminiContent> = None;
         let mut contents: Vec<GeminiContent> = Vec::new();
 
@@ -242,19 +454,24 @@ impl GeminiChatModel {
                         parts,
                     });
                 }
-                Message::Tool { content, .. } => {
+                Message::Tool { content, is_error, .. } => {
+                    let text = if *is_error {
+                        format!("Error: {content}")
+                    } else {
+                        content.clone()
+                    };
                     contents.push(GeminiContent {
                         role: Some("user".into()),
-                        parts: vec![text_part(content.clone())],
+                        parts: vec![text_part(text)],
                     });
                 }
             }
         }
 
-        let (response_mime_type, response_json_schema) = match &options.response_format {
+        let (response_mime_type, response_schema) = match &options.response_format {
             Some(ResponseFormat::JsonObject) => (Some("application/json".into()), None),
             Some(ResponseFormat::JsonSchema { schema, .. }) => {
-                (Some("application/json".into()), Some(schema.clone()))
+                (Some("application/json".into()), Some(to_gemini_schema(schema)?))
             }
             Some(ResponseFormat::Text) | None => (None, None),
         };
@@ -262,19 +479,21 @@ impl GeminiChatModel {
         let has_response_format = response_mime_type.is_some();
         let generation_config = if options.max_tokens.is_some()
             || options.temperature.is_some()
+            || options.top_p.is_some()
             || !options.stop.is_empty()
             || has_response_format
         {
             Some(GenerationConfig {
                 max_output_tokens: options.max_tokens,
                 temperature: options.temperature,
+                top_p: options.top_p,
                 stop_sequences: if options.stop.is_empty() {
                     None
                 } else {
                     Some(options.stop.clone())
                 },
                 response_mime_type,
-                response_json_schema,
+                response_schema,
             })
         } else {
             None
@@ -296,12 +515,28 @@ impl GeminiChatModel {
             }])
         };
 
-        GeminiRequest {
+        let safety_settings = if options.safety_settings.is_empty() {
+            None
+        } else {
+            Some(
+                options
+                    .safety_settings
+                    .iter()
+                    .map(|s| GeminiSafetySetting {
+                        category: s.category.clone(),
+                        threshold: s.threshold.clone(),
+                    })
+                    .collect(),
+            )
+        };
+
+        Ok(GeminiRequest {
             system_instruction,
             contents,
             generation_config,
             tools,
-        }
+            safety_settings,
+        })
     }
 }
 
@@ -309,43 +544,33 @@ impl GeminiChatModel {
 impl ChatModel for GeminiChatModel {
     async fn generate(&self, messages: &[Message], options: &CallOptions) -> Result<ChatResult> {
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model_id, self.api_key
+            "{}/models/{}:generateContent?key={}",
+            self.base_url, self.model_id, self.api_key
         );
 
-        let request_body = self.build_request(messages, options);
+        let request_body = self.build_request(messages, options)?;
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read response body".into());
-            return Err(AyasError::Model(
-                if status.as_u16() == 401 || status.as_u16() == 403 {
-                    ModelError::Auth(body)
-                } else if status.as_u16() == 429 {
-                    ModelError::RateLimited {
-                        retry_after_secs: None,
-                    }
-                } else {
-                    ModelError::ApiRequest(format!("HTTP {status}: {body}"))
-                },
-            ));
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
         }
 
-        let gemini_response: GeminiResponse = response
+        let body: serde_json::Value = response
             .json()
             .await
             .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+        if let Some(err) = content_filter_error(&body) {
+            return Err(AyasError::Model(err));
+        }
+        let gemini_response: GeminiResponse = serde_json::from_value(body)
+            .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
 
         let mut tool_calls = Vec::new();
         let mut text_parts = Vec::new();
@@ -373,15 +598,17 @@ impl ChatModel for GeminiChatModel {
             input_tokens: u.prompt_token_count,
             output_tokens: u.candidates_token_count,
             total_tokens: u.total_token_count,
+            ..Default::default()
         });
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls,
+            usage: usage.clone(),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls,
-                usage: usage.clone(),
-            }),
             usage,
+            ..ChatResult::new(message)
         })
     }
 
@@ -389,43 +616,60 @@ impl ChatModel for GeminiChatModel {
         &self.model_id
     }
 
+    /// Count with the `countTokens` endpoint, which sees the same contents,
+    /// system instruction and tools as `generate`. Counts may still differ
+    /// from the tokens billed for a real call.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        let url = format!(
+            "{}/models/{}:countTokens?key={}",
+            self.base_url, self.model_id, self.api_key
+        );
+
+        let request = self.build_request(messages, &CallOptions::default())?;
+        let mut request = serde_json::to_value(request)?;
+        request["model"] = format!("models/{}", self.model_id).into();
+        let request_body = serde_json::json!({ "generateContentRequest": request });
+
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
+        }
+
+        let body: GeminiCountTokensResponse = response
+            .json()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+        Ok(body.total_tokens)
+    }
+
     async fn stream(
         &self,
         messages: &[Message],
         options: &CallOptions,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:streamGenerateContent?alt=sse&key={}",
-            self.model_id, self.api_key
+            "{}/models/{}:streamGenerateContent?alt=sse&key={}",
+            self.base_url, self.model_id, self.api_key
         );
 
-        let request_body = self.build_request(messages, options);
+        let request_body = self.build_request(messages, options)?;
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "failed to read response body".into());
-            return Err(AyasError::Model(
-                if status.as_u16() == 401 || status.as_u16() == 403 {
-                    ModelError::Auth(body)
-                } else if status.as_u16() == 429 {
-                    ModelError::RateLimited {
-                        retry_after_secs: None,
-                    }
-                } else {
-                    ModelError::ApiRequest(format!("HTTP {status}: {body}"))
-                },
-            ));
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            return Err(error_response(response).await);
         }
 
         let data_stream = sse_data_stream(response);
@@ -435,6 +679,12 @@ impl ChatModel for GeminiChatModel {
             let mut last_usage: Option<UsageMetadata> = None;
 
             while let Some(data) = data_stream.next().await {
+                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&data)
+                    && let Some(err) = content_filter_error(&json)
+                {
+                    yield Err(AyasError::Model(err));
+                    return;
+                }
                 let (events, usage) = parse_gemini_sse_data(&data);
                 for event in events {
                     yield Ok(event);
@@ -451,10 +701,76 @@ impl ChatModel for GeminiChatModel {
             yield Ok(ChatStreamEvent::Done);
         };
 
-        Ok(Box::pin(event_stream))
+        Ok(stop_at_sequences(event_stream, &options.stop))
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCountTokensResponse {
+    #[serde(default)]
+    total_tokens: u64,
+}
+
+/// Map a non-success response to an error: 401/403 to `Auth`, 429 to
+/// `RateLimited`, anything else to `ApiRequest`.
+async fn error_response(response: reqwest::Response) -> AyasError {
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "failed to read response body".into());
+    AyasError::Model(match status.as_u16() {
+        401 | 403 => ModelError::Auth(body),
+        429 => ModelError::RateLimited {
+            retry_after_secs: retry_after,
+        },
+        _ => ModelError::http(status.as_u16(), body),
+    })
+}
+
+/// Finish reasons that indicate the candidate was blocked by a content filter.
+const BLOCKED_FINISH_REASONS: &[&str] = &["SAFETY", "PROHIBITED_CONTENT", "BLOCKLIST", "SPII"];
+
+/// Detect a blocked prompt or candidate in a Gemini response (or stream chunk).
+///
+/// Checks `promptFeedback.blockReason` and the first candidate's
+/// `finishReason`. Categories are taken from the matching `safetyRatings`
+/// entries that were blocked or rated above `LOW` probability.
+pub fn content_filter_error(json: &serde_json::Value) -> Option<ModelError> {
+    let (reason, ratings) = if let Some(reason) = json["promptFeedback"]["blockReason"].as_str()
+    {
+        (reason, &json["promptFeedback"]["safetyRatings"])
+    } else {
+        let candidate = &json["candidates"][0];
+        let reason = candidate["finishReason"].as_str()?;
+        if !BLOCKED_FINISH_REASONS.contains(&reason) {
+            return None;
+        }
+        (reason, &candidate["safetyRatings"])
+    };
+
+    let categories = ratings
+        .as_array()
+        .map(|ratings| {
+            ratings
+                .iter()
+                .filter(|r| {
+                    r["blocked"].as_bool() == Some(true)
+                        || matches!(r["probability"].as_str(), Some("MEDIUM" | "HIGH"))
+                })
+                .filter_map(|r| r["category"].as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ModelError::ContentFiltered {
+        reason: reason.to_string(),
+        categories,
+    })
+}
+
 /// Parse a single Gemini SSE data line into stream events (for testing).
 ///
 /// Returns `(events, optional_usage)`. Usage is tracked separately since
@@ -502,6 +818,7 @@ pub fn parse_gemini_sse_data(data: &str) -> (Vec<ChatStreamEvent>, Option<UsageM
             input_tokens: u["promptTokenCount"].as_u64()?,
             output_tokens: u["candidatesTokenCount"].as_u64().unwrap_or(0),
             total_tokens: u["totalTokenCount"].as_u64().unwrap_or(0),
+            ..Default::default()
         })
     });
 
@@ -523,12 +840,25 @@ mod tests {
         GeminiChatModel::new("test-key".into(), "gemini-2.0-flash".into())
     }
 
+    #[test]
+    fn with_config_sets_extra_headers() {
+        let config = crate::config::ProviderConfig::new().with_header("x-goog-user-project", "value-1");
+        let model = make_model().with_config(&config).unwrap();
+        assert_eq!(model.extra_headers.get("x-goog-user-project").unwrap(), "value-1");
+    }
+
+    #[test]
+    fn with_config_rejects_invalid_header() {
+        let config = crate::config::ProviderConfig::new().with_header("bad header", "x");
+        assert!(make_model().with_config(&config).is_err());
+    }
+
     #[test]
     fn build_request_basic() {
         let model = make_model();
         let messages = vec![Message::user("Hello")];
         let options = CallOptions::default();
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         assert_eq!(req.contents.len(), 1);
         assert_eq!(req.contents[0].role.as_deref(), Some("user"));
         assert!(req.system_instruction.is_none());
@@ -544,7 +874,7 @@ mod tests {
             Message::user("Hello"),
         ];
         let options = CallOptions::default();
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         assert!(req.system_instruction.is_some());
         let sys = req.system_instruction.unwrap();
         assert!(sys.parts[0].text.as_deref() == Some("You are helpful"));
@@ -560,7 +890,7 @@ mod tests {
             max_tokens: Some(100),
             ..Default::default()
         };
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         let config = req.generation_config.unwrap();
         assert_eq!(config.temperature, Some(0.5));
         assert_eq!(config.max_output_tokens, Some(100));
@@ -578,7 +908,7 @@ mod tests {
             }],
             ..Default::default()
         };
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         assert!(req.tools.is_some());
         let tools = req.tools.unwrap();
         assert_eq!(tools[0].function_declarations.len(), 1);
@@ -675,10 +1005,10 @@ mod tests {
             response_format: Some(ayas_core::model::ResponseFormat::JsonObject),
             ..Default::default()
         };
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         let config = req.generation_config.unwrap();
         assert_eq!(config.response_mime_type.as_deref(), Some("application/json"));
-        assert!(config.response_json_schema.is_none());
+        assert!(config.response_schema.is_none());
     }
 
     #[test]
@@ -701,10 +1031,20 @@ mod tests {
             }),
             ..Default::default()
         };
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         let config = req.generation_config.unwrap();
         assert_eq!(config.response_mime_type.as_deref(), Some("application/json"));
-        assert_eq!(config.response_json_schema.unwrap(), schema);
+        assert_eq!(
+            config.response_schema.unwrap(),
+            serde_json::json!({
+                "type": "OBJECT",
+                "properties": {
+                    "name": {"type": "STRING"},
+                    "age": {"type": "INTEGER"}
+                },
+                "required": ["name", "age"]
+            })
+        );
     }
 
     #[test]
@@ -715,11 +1055,203 @@ mod tests {
             response_format: Some(ayas_core::model::ResponseFormat::Text),
             ..Default::default()
         };
-        let req = model.build_request(&messages, &options);
+        let req = model.build_request(&messages, &options).unwrap();
         // Text format should not force generation_config
         assert!(req.generation_config.is_none());
     }
 
+    #[test]
+    fn build_request_json_schema_strips_unsupported_keywords() {
+        let model = make_model();
+        let schema = serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "note": {"type": ["string", "null"]}
+            }
+        });
+        let options = CallOptions {
+            response_format: Some(ayas_core::model::ResponseFormat::JsonSchema {
+                name: "doc".into(),
+                schema,
+                strict: true,
+            }),
+            ..Default::default()
+        };
+        let req = model
+            .build_request(&[Message::user("Extract")], &options)
+            .unwrap();
+        let converted = req.generation_config.unwrap().response_schema.unwrap();
+        assert_eq!(
+            converted,
+            serde_json::json!({
+                "type": "OBJECT",
+                "properties": {
+                    "tags": {"type": "ARRAY", "items": {"type": "STRING"}},
+                    "note": {"type": "STRING", "nullable": true}
+                }
+            })
+        );
+        let json = serde_json::to_string(&converted).unwrap();
+        assert!(!json.contains("$schema"));
+        assert!(!json.contains("additionalProperties"));
+    }
+
+    #[test]
+    fn build_request_json_schema_unsupported_feature_errors() {
+        let model = make_model();
+        let options = CallOptions {
+            response_format: Some(ayas_core::model::ResponseFormat::JsonSchema {
+                name: "bad".into(),
+                schema: serde_json::json!({
+                    "allOf": [{"type": "object"}, {"required": ["a"]}]
+                }),
+                strict: true,
+            }),
+            ..Default::default()
+        };
+        let err = model
+            .build_request(&[Message::user("Extract")], &options)
+            .unwrap_err();
+        assert!(err.to_string().contains("allOf"));
+    }
+
+    #[test]
+    fn to_gemini_schema_inlines_local_refs() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"item": {"$ref": "#/$defs/Item"}},
+            "$defs": {"Item": {"type": "object", "properties": {"id": {"type": "integer"}}}}
+        });
+        let converted = to_gemini_schema(&schema).unwrap();
+        assert_eq!(
+            converted["properties"]["item"],
+            serde_json::json!({"type": "OBJECT", "properties": {"id": {"type": "INTEGER"}}})
+        );
+        assert!(converted.get("$defs").is_none());
+    }
+
+    #[test]
+    fn to_gemini_schema_rejects_recursive_refs() {
+        let schema = serde_json::json!({
+            "$ref": "#/$defs/Node",
+            "$defs": {"Node": {
+                "type": "object",
+                "properties": {"child": {"$ref": "#/$defs/Node"}}
+            }}
+        });
+        let err = to_gemini_schema(&schema).unwrap_err();
+        assert!(err.to_string().contains("recursive"));
+    }
+
+    #[test]
+    fn to_gemini_schema_one_of_and_const() {
+        let schema = serde_json::json!({
+            "oneOf": [{"type": "string", "const": "a"}, {"type": "integer"}]
+        });
+        let converted = to_gemini_schema(&schema).unwrap();
+        assert_eq!(
+            converted,
+            serde_json::json!({"anyOf": [
+                {"type": "STRING", "enum": ["a"]},
+                {"type": "INTEGER"}
+            ]})
+        );
+    }
+
+    #[test]
+    fn to_gemini_schema_rejects_multi_type_union() {
+        let schema = serde_json::json!({"type": ["string", "integer"]});
+        assert!(to_gemini_schema(&schema).is_err());
+    }
+
+    // -----------------------------------------------------------------------
+    // Safety tests
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn build_request_with_safety_settings() {
+        let model = make_model();
+        let messages = vec![Message::user("Hello")];
+        let options = CallOptions {
+            safety_settings: vec![ayas_core::model::SafetySetting {
+                category: "HARM_CATEGORY_HARASSMENT".into(),
+                threshold: "BLOCK_ONLY_HIGH".into(),
+            }],
+            ..Default::default()
+        };
+        let req = model.build_request(&messages, &options).unwrap();
+        let settings = req.safety_settings.unwrap();
+        assert_eq!(settings.len(), 1);
+        assert_eq!(settings[0].category, "HARM_CATEGORY_HARASSMENT");
+        assert_eq!(settings[0].threshold, "BLOCK_ONLY_HIGH");
+    }
+
+    #[test]
+    fn build_request_no_safety_settings_omitted() {
+        let model = make_model();
+        let req = model
+            .build_request(&[Message::user("Hello")], &CallOptions::default())
+            .unwrap();
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(!json.contains("safety_settings"));
+    }
+
+    #[test]
+    fn content_filter_prompt_block_reason() {
+        let json = serde_json::json!({
+            "promptFeedback": {
+                "blockReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_HARASSMENT", "probability": "HIGH", "blocked": true},
+                    {"category": "HARM_CATEGORY_HATE_SPEECH", "probability": "NEGLIGIBLE"}
+                ]
+            }
+        });
+        match content_filter_error(&json) {
+            Some(ModelError::ContentFiltered { reason, categories }) => {
+                assert_eq!(reason, "SAFETY");
+                assert_eq!(categories, vec!["HARM_CATEGORY_HARASSMENT".to_string()]);
+            }
+            other => panic!("expected ContentFiltered, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn content_filter_candidate_finish_reason() {
+        let json = serde_json::json!({
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "safetyRatings": [
+                    {"category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "MEDIUM"}
+                ]
+            }]
+        });
+        match content_filter_error(&json) {
+            Some(ModelError::ContentFiltered { reason, categories }) => {
+                assert_eq!(reason, "SAFETY");
+                assert_eq!(categories, vec!["HARM_CATEGORY_DANGEROUS_CONTENT".to_string()]);
+            }
+            other => panic!("expected ContentFiltered, got {other:?}"),
+        }
+        // The blocked candidate (no content) still deserializes.
+        let resp: GeminiResponse = serde_json::from_value(json).unwrap();
+        assert!(resp.candidates.unwrap()[0].content.parts.is_empty());
+    }
+
+    #[test]
+    fn content_filter_normal_stop_is_none() {
+        let json = serde_json::json!({
+            "candidates": [{
+                "content": {"parts": [{"text": "Hi"}], "role": "model"},
+                "finishReason": "STOP"
+            }]
+        });
+        assert!(content_filter_error(&json).is_none());
+    }
+
     // -----------------------------------------------------------------------
     // SSE parsing tests
     // -----------------------------------------------------------------------
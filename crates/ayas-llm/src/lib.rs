@@ -1,7 +1,12 @@
 pub mod provider;
+pub mod config;
 pub mod gemini;
 pub mod claude;
+pub mod moderation;
 pub mod openai;
 pub mod factory;
+pub mod fallback;
+pub mod retry;
 pub mod runnable;
 pub mod sse;
+pub mod stop;
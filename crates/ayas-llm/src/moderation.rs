@@ -0,0 +1,147 @@
+//! OpenAI Moderation API integration.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+use ayas_core::moderation::{ModerationResult, Moderator};
+
+const DEFAULT_MODERATION_MODEL: &str = "omni-moderation-latest";
+
+#[derive(Debug, Serialize)]
+struct OpenAIModerationRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModerationResponse {
+    results: Vec<OpenAIModerationEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModerationEntry {
+    flagged: bool,
+    #[serde(default)]
+    categories: BTreeMap<String, bool>,
+}
+
+impl From<OpenAIModerationResponse> for ModerationResult {
+    fn from(response: OpenAIModerationResponse) -> Self {
+        let mut result = ModerationResult::allowed();
+        for entry in response.results {
+            result.flagged |= entry.flagged;
+            for (category, hit) in entry.categories {
+                if hit && !result.categories.contains(&category) {
+                    result.categories.push(category);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Moderator backed by the OpenAI Moderation API.
+pub struct OpenAIModerator {
+    api_key: String,
+    model_id: String,
+    client: reqwest::Client,
+}
+
+impl OpenAIModerator {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            model_id: DEFAULT_MODERATION_MODEL.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Use a different moderation model (default `omni-moderation-latest`).
+    pub fn with_model(mut self, model_id: impl Into<String>) -> Self {
+        self.model_id = model_id.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Moderator for OpenAIModerator {
+    async fn check(&self, text: &str) -> Result<ModerationResult> {
+        let request_body = OpenAIModerationRequest {
+            model: &self.model_id,
+            input: text,
+        };
+
+        let response = self
+            .client
+            .post("https://api.openai.com/v1/moderations")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "failed to read response body".into());
+            return Err(AyasError::Model(match status.as_u16() {
+                401 => ModelError::Auth(body),
+                429 => ModelError::RateLimited {
+                    retry_after_secs: None,
+                },
+                _ => ModelError::http(status.as_u16(), body),
+            }));
+        }
+
+        let api_response: OpenAIModerationResponse = response
+            .json()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::InvalidResponse(e.to_string())))?;
+        Ok(api_response.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_serialization() {
+        let req = OpenAIModerationRequest {
+            model: DEFAULT_MODERATION_MODEL,
+            input: "hello",
+        };
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["model"], "omni-moderation-latest");
+        assert_eq!(json["input"], "hello");
+    }
+
+    #[test]
+    fn flagged_response_lists_hit_categories() {
+        let body = r#"{
+            "id": "modr-1",
+            "model": "omni-moderation-latest",
+            "results": [{
+                "flagged": true,
+                "categories": {"harassment": true, "violence": true, "self-harm": false},
+                "category_scores": {"harassment": 0.9, "violence": 0.8, "self-harm": 0.01}
+            }]
+        }"#;
+        let response: OpenAIModerationResponse = serde_json::from_str(body).unwrap();
+        let result = ModerationResult::from(response);
+        assert!(result.flagged);
+        assert_eq!(result.categories, vec!["harassment", "violence"]);
+    }
+
+    #[test]
+    fn clean_response_is_allowed() {
+        let body = r#"{"results": [{"flagged": false, "categories": {"violence": false}}]}"#;
+        let response: OpenAIModerationResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(ModerationResult::from(response), ModerationResult::allowed());
+    }
+}
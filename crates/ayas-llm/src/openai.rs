@@ -4,15 +4,22 @@ use std::pin::Pin;
 
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
 
 use ayas_core::error::{AyasError, ModelError, Result};
 use ayas_core::message::{
     AIContent, ContentPart, ContentSource, Message, MessageContent, ToolCall, UsageMetadata,
 };
-use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent, ResponseFormat};
+use ayas_core::model::{
+    CallOptions, ChatModel, ChatResult, ChatStreamEvent, ResponseFormat, TokenLogprob,
+    estimate_tokens,
+};
 
+use crate::config::ProviderConfig;
+use crate::retry::{retry_after_secs, send_with_retry};
 use crate::sse::sse_data_stream;
+use crate::stop::stop_at_sequences;
 
 // ---------------------------------------------------------------------------
 // OpenAI Chat Completions API request/response types
@@ -31,12 +38,18 @@ pub struct OpenAIRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<OpenAIToolDef>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<OpenAIResponseFormat>,
     #[serde(skip_serializing_if = "is_false")]
+    pub logprobs: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u8>,
+    #[serde(skip_serializing_if = "is_false")]
     pub stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream_options: Option<OpenAIStreamOptions>,
@@ -127,15 +140,39 @@ pub struct OpenAIResponse {
 #[derive(Debug, Deserialize)]
 pub struct OpenAIChoice {
     pub message: OpenAIResponseMessage,
+    #[serde(default)]
+    pub logprobs: Option<OpenAILogprobs>,
+}
+
+/// Per-token log probabilities of a choice; each entry's extra `bytes`
+/// field is ignored.
+#[derive(Debug, Deserialize)]
+pub struct OpenAILogprobs {
+    #[serde(default)]
+    pub content: Option<Vec<TokenLogprob>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OpenAIResponseMessage {
     pub content: Option<String>,
+    /// Reasoning text exposed by reasoning models (and compatible APIs).
+    #[serde(default)]
+    pub reasoning_content: Option<String>,
+    /// The same, under the key some compatible APIs use instead; a response
+    /// may carry both.
+    #[serde(default)]
+    pub reasoning: Option<String>,
     #[serde(default)]
     pub tool_calls: Option<Vec<OpenAIRespToolCall>>,
 }
 
+impl OpenAIResponseMessage {
+    /// The reasoning text, from `reasoning_content` or else `reasoning`.
+    pub fn reasoning_text(&self) -> Option<&str> {
+        self.reasoning_content.as_deref().or(self.reasoning.as_deref())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIRespToolCall {
     pub id: String,
@@ -236,6 +273,9 @@ pub struct OpenAIChatModel {
     api_key: String,
     model_id: String,
     client: reqwest::Client,
+    extra_headers: HeaderMap,
+    base_url: String,
+    max_auto_retries: u32,
 }
 
 impl OpenAIChatModel {
@@ -244,9 +284,33 @@ impl OpenAIChatModel {
             api_key,
             model_id,
             client: reqwest::Client::new(),
+            extra_headers: HeaderMap::new(),
+            base_url: "https://api.openai.com/v1".into(),
+            max_auto_retries: 0,
         }
     }
 
+    /// Apply a provider config; its extra headers are sent with every request.
+    ///
+    /// Returns an error if any header name or value is invalid.
+    pub fn with_config(mut self, config: &ProviderConfig) -> Result<Self> {
+        self.extra_headers = config.header_map()?;
+        Ok(self)
+    }
+
+    /// Send requests to `base_url` instead of the public API (e.g. a proxy).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Retry requests answered with 429 or 503 up to `max_auto_retries` times,
+    /// honoring `Retry-After`. The default of 0 returns the error right away.
+    pub fn with_max_auto_retries(mut self, max_auto_retries: u32) -> Self {
+        self.max_auto_retries = max_auto_retries;
+        self
+    }
+
     pub fn build_request(&self, messages: &[Message], options: &CallOptions) -> OpenAIRequest {
         let api_messages: Vec<OpenAIMessage> = messages
             .iter()
@@ -292,9 +356,15 @@ impl OpenAIChatModel {
                 Message::Tool {
                     content,
                     tool_call_id,
+                    is_error,
                 } => OpenAIMessage {
                     role: "tool".into(),
-                    content: OpenAIContent::Text(content.clone()),
+                    // No error flag on OpenAI tool messages; mark it in the text.
+                    content: OpenAIContent::Text(if *is_error {
+                        format!("Error: {content}")
+                    } else {
+                        content.clone()
+                    }),
                     tool_call_id: Some(tool_call_id.clone()),
                     tool_calls: None,
                 },
@@ -345,6 +415,7 @@ impl OpenAIChatModel {
             messages: api_messages,
             max_tokens: options.max_tokens,
             temperature: options.temperature,
+            top_p: options.top_p,
             stop: if options.stop.is_empty() {
                 None
             } else {
@@ -352,6 +423,9 @@ impl OpenAIChatModel {
             },
             tools,
             response_format,
+            logprobs: options.logprobs,
+            // Rejected by the API unless `logprobs` is set.
+            top_logprobs: options.top_logprobs.filter(|_| options.logprobs),
             stream: false,
             stream_options: None,
         }
@@ -363,17 +437,19 @@ impl ChatModel for OpenAIChatModel {
     async fn generate(&self, messages: &[Message], options: &CallOptions) -> Result<ChatResult> {
         let request_body = self.build_request(messages, options);
 
-        let response = self
-            .client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+        let url = format!("{}/chat/completions", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(response.headers());
             let body = response
                 .text()
                 .await
@@ -384,9 +460,9 @@ impl ChatModel for OpenAIChatModel {
             return Err(AyasError::Model(match status.as_u16() {
                 401 => ModelError::Auth(error_msg),
                 429 => ModelError::RateLimited {
-                    retry_after_secs: None,
+                    retry_after_secs: retry_after,
                 },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
+                _ => ModelError::http(status.as_u16(), error_msg),
             }));
         }
 
@@ -400,6 +476,15 @@ impl ChatModel for OpenAIChatModel {
             .and_then(|c| c.message.content.clone())
             .unwrap_or_default();
 
+        let reasoning = choice
+            .and_then(|c| c.message.reasoning_text())
+            .filter(|r| !r.is_empty())
+            .map(String::from);
+
+        let logprobs = choice
+            .and_then(|c| c.logprobs.as_ref())
+            .and_then(|l| l.content.clone());
+
         let tool_calls = choice
             .and_then(|c| c.message.tool_calls.as_ref())
             .map(|tcs| {
@@ -418,15 +503,19 @@ impl ChatModel for OpenAIChatModel {
             input_tokens: u.prompt_tokens,
             output_tokens: u.completion_tokens,
             total_tokens: u.total_tokens,
+            ..Default::default()
         });
 
+        let message = Message::AI(AIContent {
+            content: text,
+            tool_calls,
+            usage: usage.clone(),
+        });
         Ok(ChatResult {
-            message: Message::AI(AIContent {
-                content: text,
-                tool_calls,
-                usage: usage.clone(),
-            }),
             usage,
+            reasoning,
+            logprobs,
+            ..ChatResult::new(message)
         })
     }
 
@@ -434,6 +523,13 @@ impl ChatModel for OpenAIChatModel {
         &self.model_id
     }
 
+    /// A local [`estimate_tokens`] estimate: the Chat Completions API has no
+    /// counting endpoint. It can be well off the billed count, especially
+    /// for code or tool definitions, which are not counted.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        Ok(estimate_tokens(messages))
+    }
+
     async fn stream(
         &self,
         messages: &[Message],
@@ -445,17 +541,19 @@ impl ChatModel for OpenAIChatModel {
             include_usage: true,
         });
 
-        let response = self
-            .client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+        let url = format!("{}/chat/completions", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .headers(self.extra_headers.clone())
+                .json(&request_body)
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(response.headers());
             let body = response
                 .text()
                 .await
@@ -466,9 +564,9 @@ impl ChatModel for OpenAIChatModel {
             return Err(AyasError::Model(match status.as_u16() {
                 401 => ModelError::Auth(error_msg),
                 429 => ModelError::RateLimited {
-                    retry_after_secs: None,
+                    retry_after_secs: retry_after,
                 },
-                _ => ModelError::ApiRequest(format!("HTTP {status}: {error_msg}")),
+                _ => ModelError::http(status.as_u16(), error_msg),
             }));
         }
 
@@ -489,7 +587,7 @@ impl ChatModel for OpenAIChatModel {
             }
         };
 
-        Ok(Box::pin(event_stream))
+        Ok(stop_at_sequences(event_stream, &options.stop))
     }
 }
 
@@ -511,6 +609,7 @@ pub fn parse_openai_sse_data(data: &str) -> Vec<ChatStreamEvent> {
                 input_tokens: prompt,
                 output_tokens: completion,
                 total_tokens: total,
+                ..Default::default()
             }));
         }
     }
@@ -523,6 +622,15 @@ pub fn parse_openai_sse_data(data: &str) -> Vec<ChatStreamEvent> {
     for choice in choices {
         let delta = &choice["delta"];
 
+        // Reasoning content (o-series / compatible APIs)
+        if let Some(reasoning) = delta["reasoning_content"]
+            .as_str()
+            .or_else(|| delta["reasoning"].as_str())
+            && !reasoning.is_empty()
+        {
+            events.push(ChatStreamEvent::Reasoning(reasoning.to_string()));
+        }
+
         // Text content
         if let Some(text) = delta["content"].as_str() {
             if !text.is_empty() {
@@ -570,6 +678,19 @@ mod tests {
         OpenAIChatModel::new("test-key".into(), "gpt-4o-mini".into())
     }
 
+    #[test]
+    fn with_config_sets_extra_headers() {
+        let config = crate::config::ProviderConfig::new().with_header("OpenAI-Organization", "value-1");
+        let model = make_model().with_config(&config).unwrap();
+        assert_eq!(model.extra_headers.get("openai-organization").unwrap(), "value-1");
+    }
+
+    #[test]
+    fn with_config_rejects_invalid_header() {
+        let config = crate::config::ProviderConfig::new().with_header("bad header", "x");
+        assert!(make_model().with_config(&config).is_err());
+    }
+
     #[test]
     fn build_request_basic() {
         let model = make_model();
@@ -717,6 +838,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn build_request_tool_error_message() {
+        let model = make_model();
+        let messages = vec![Message::tool_error("division by zero", "call_123")];
+        let options = CallOptions::default();
+        let req = model.build_request(&messages, &options);
+        assert_eq!(req.messages[0].tool_call_id.as_deref(), Some("call_123"));
+        match &req.messages[0].content {
+            OpenAIContent::Text(text) => assert_eq!(text, "Error: division by zero"),
+            _ => panic!("expected Text"),
+        }
+    }
+
     #[test]
     fn build_request_ai_with_tool_calls() {
         let model = make_model();
@@ -839,6 +973,66 @@ mod tests {
         assert!(!json.contains("stream"));
     }
 
+    #[test]
+    fn build_request_logprobs() {
+        let model = make_model();
+        let messages = vec![Message::user("Hello")];
+        let options = CallOptions {
+            logprobs: true,
+            top_logprobs: Some(3),
+            ..Default::default()
+        };
+        let req = model.build_request(&messages, &options);
+        assert!(req.logprobs);
+        assert_eq!(req.top_logprobs, Some(3));
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["logprobs"], true);
+        assert_eq!(json["top_logprobs"], 3);
+    }
+
+    #[test]
+    fn build_request_omits_logprobs_by_default() {
+        let model = make_model();
+        let messages = vec![Message::user("Hello")];
+        let req = model.build_request(&messages, &CallOptions::default());
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(!json.contains("logprobs"));
+
+        // Alternatives are only requested along with logprobs.
+        let options = CallOptions {
+            top_logprobs: Some(3),
+            ..Default::default()
+        };
+        assert!(model.build_request(&messages, &options).top_logprobs.is_none());
+    }
+
+    #[test]
+    fn parse_response_logprobs() {
+        let json = r#"{
+            "choices": [{
+                "message": {"content": "Yes"},
+                "logprobs": {"content": [{
+                    "token": "Yes",
+                    "logprob": -0.01,
+                    "bytes": [89, 101, 115],
+                    "top_logprobs": [
+                        {"token": "Yes", "logprob": -0.01, "bytes": [89, 101, 115]},
+                        {"token": "No", "logprob": -4.6, "bytes": [78, 111]}
+                    ]
+                }]}
+            }]
+        }"#;
+        let resp: OpenAIResponse = serde_json::from_str(json).unwrap();
+        let tokens = resp.choices[0].logprobs.as_ref().unwrap().content.as_ref().unwrap();
+        assert_eq!(tokens[0].token, "Yes");
+        assert_eq!(tokens[0].logprob, -0.01);
+        assert_eq!(tokens[0].top_logprobs[1].token, "No");
+
+        let json = r#"{"choices": [{"message": {"content": "Hi"}, "logprobs": null}]}"#;
+        let resp: OpenAIResponse = serde_json::from_str(json).unwrap();
+        assert!(resp.choices[0].logprobs.is_none());
+    }
+
     // -----------------------------------------------------------------------
     // Structured output tests
     // -----------------------------------------------------------------------
@@ -910,6 +1104,39 @@ mod tests {
         assert_eq!(events[0], ChatStreamEvent::Token("Hello".into()));
     }
 
+    #[test]
+    fn parse_sse_reasoning_content() {
+        let data = r#"{"choices":[{"index":0,"delta":{"reasoning_content":"Thinking..."}}]}"#;
+        let events = parse_openai_sse_data(data);
+        assert_eq!(events, vec![ChatStreamEvent::Reasoning("Thinking...".into())]);
+    }
+
+    #[test]
+    fn parse_response_reasoning_content() {
+        let json = r#"{
+            "choices": [{"message": {"content": "4", "reasoning_content": "2+2=4"}}]
+        }"#;
+        let resp: OpenAIResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.choices[0].message.reasoning_text(), Some("2+2=4"));
+    }
+
+    #[test]
+    fn parse_response_with_both_reasoning_keys() {
+        let json = r#"{
+            "choices": [{"message": {
+                "content": "4",
+                "reasoning_content": "2+2=4",
+                "reasoning": "2+2=4"
+            }}]
+        }"#;
+        let resp: OpenAIResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.choices[0].message.reasoning_text(), Some("2+2=4"));
+
+        let json = r#"{"choices": [{"message": {"content": "4", "reasoning": "sum"}}]}"#;
+        let resp: OpenAIResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.choices[0].message.reasoning_text(), Some("sum"));
+    }
+
     #[test]
     fn parse_sse_empty_content_skipped() {
         let data = r#"{"id":"chatcmpl-1","choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":null}]}"#;
@@ -954,6 +1181,7 @@ mod tests {
                 input_tokens: 10,
                 output_tokens: 5,
                 total_tokens: 15,
+                ..Default::default()
             })
         );
     }
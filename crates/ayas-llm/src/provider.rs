@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use ayas_core::error::{ModelError, Result};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Provider {
@@ -33,6 +35,97 @@ impl Provider {
             ],
         }
     }
+
+    /// Model ids accepted without opting into unknown models, or `None` when
+    /// the provider's ids are not validated.
+    pub fn known_models(&self) -> Option<&[&str]> {
+        match self {
+            Provider::Gemini => Some(&[
+                "gemini-2.5-flash",
+                "gemini-2.5-flash-lite",
+                "gemini-2.5-pro",
+                "gemini-3-flash-preview",
+                "gemini-3-pro-preview",
+                "deep-research-pro-preview-12-2025",
+                "gemini-2.0-flash",
+                "gemini-2.0-flash-lite",
+                "gemini-1.5-pro",
+                "gemini-1.5-flash",
+            ]),
+            Provider::Claude | Provider::OpenAI => None,
+        }
+    }
+
+    /// Short names accepted in place of a full model id, as `(alias, model)`.
+    pub fn model_aliases(&self) -> &[(&str, &str)] {
+        match self {
+            Provider::Gemini => &[
+                ("gemini-flash", "gemini-2.5-flash"),
+                ("gemini-flash-lite", "gemini-2.5-flash-lite"),
+                ("gemini-pro", "gemini-2.5-pro"),
+                ("gemini-3-flash", "gemini-3-flash-preview"),
+                ("gemini-3-pro", "gemini-3-pro-preview"),
+                ("deep-research", "deep-research-pro-preview-12-2025"),
+            ],
+            Provider::Claude | Provider::OpenAI => &[],
+        }
+    }
+
+    /// Resolve an alias to its model id and check the id against
+    /// [`known_models`](Self::known_models).
+    ///
+    /// An unknown id fails with [`ModelError::UnknownModel`] listing the
+    /// closest known ids and aliases, unless `allow_unknown` is set, in which
+    /// case it is passed through as-is (e.g. for a model released after this
+    /// list was written).
+    pub fn resolve_model(&self, model: &str, allow_unknown: bool) -> Result<String> {
+        let aliases = self.model_aliases();
+        if let Some((_, target)) = aliases.iter().find(|(alias, _)| *alias == model) {
+            return Ok((*target).to_string());
+        }
+        let Some(known) = self.known_models() else {
+            return Ok(model.to_string());
+        };
+        if allow_unknown || known.contains(&model) {
+            return Ok(model.to_string());
+        }
+
+        let candidates = known
+            .iter()
+            .copied()
+            .chain(aliases.iter().map(|(alias, _)| *alias));
+        let max_distance = (model.len() / 3).max(2);
+        let mut close: Vec<(usize, &str)> = candidates
+            .map(|candidate| (edit_distance(model, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        close.sort();
+        Err(ModelError::UnknownModel {
+            model: model.to_string(),
+            suggestions: close
+                .into_iter()
+                .take(3)
+                .map(|(_, candidate)| candidate.to_string())
+                .collect(),
+        }
+        .into())
+    }
+}
+
+/// Levenshtein distance between two strings, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
 }
 
 pub fn model_map() -> HashMap<Provider, Vec<&'static str>> {
@@ -46,6 +139,7 @@ pub fn model_map() -> HashMap<Provider, Vec<&'static str>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ayas_core::error::AyasError;
 
     #[test]
     fn provider_serialize_gemini() {
@@ -85,4 +179,47 @@ mod tests {
             assert!(!models.is_empty());
         }
     }
+
+    #[test]
+    fn resolve_model_aliases_and_known_ids() {
+        let gemini = Provider::Gemini;
+        assert_eq!(gemini.resolve_model("gemini-flash", false).unwrap(), "gemini-2.5-flash");
+        assert_eq!(gemini.resolve_model("gemini-2.5-pro", false).unwrap(), "gemini-2.5-pro");
+        for model in gemini.default_models() {
+            assert!(gemini.known_models().unwrap().contains(model));
+        }
+    }
+
+    #[test]
+    fn resolve_model_unknown_suggests_close_matches() {
+        let err = Provider::Gemini
+            .resolve_model("gemini-2.5-flsh", false)
+            .unwrap_err();
+        match err {
+            AyasError::Model(ModelError::UnknownModel { model, suggestions }) => {
+                assert_eq!(model, "gemini-2.5-flsh");
+                assert_eq!(suggestions[0], "gemini-2.5-flash");
+            }
+            other => panic!("expected UnknownModel, got {other:?}"),
+        }
+
+        let err = Provider::Gemini.resolve_model("llama-3", false).unwrap_err();
+        assert!(err.to_string().ends_with("Unknown model 'llama-3'"));
+    }
+
+    #[test]
+    fn resolve_model_allow_unknown_passes_through() {
+        let model = Provider::Gemini.resolve_model("gemini-9-ultra", true).unwrap();
+        assert_eq!(model, "gemini-9-ultra");
+        // Providers without a known set are not validated.
+        let model = Provider::OpenAI.resolve_model("gpt-4o-mini", false).unwrap();
+        assert_eq!(model, "gpt-4o-mini");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("flash", "flash"), 0);
+        assert_eq!(edit_distance("flsh", "flash"), 1);
+        assert_eq!(edit_distance("pro", "flash"), 5);
+    }
 }
@@ -0,0 +1,71 @@
+//! Automatic retries of rate-limited provider requests.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+
+/// Wait before the first retry when the response has no `Retry-After`;
+/// doubled on each further retry.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper bound on a single wait, whatever the provider asks for.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Seconds to wait according to a `Retry-After` header.
+///
+/// Only the delay-seconds form is understood; an HTTP date yields `None`.
+pub fn retry_after_secs(headers: &HeaderMap) -> Option<u64> {
+    headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Send a request, resending it up to `max_retries` times while the provider
+/// answers 429 or 503.
+///
+/// Each retry waits for the response's `Retry-After` when present, otherwise
+/// for an exponential backoff starting at 500ms. `build` is called once per
+/// attempt. The last response is returned whatever its status, so callers
+/// map errors exactly as without retries.
+pub async fn send_with_retry(
+    max_retries: u32,
+    build: impl Fn() -> RequestBuilder,
+) -> Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let response = build()
+            .send()
+            .await
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
+        let status = response.status();
+        let retryable =
+            status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE;
+        if !retryable || attempt >= max_retries {
+            return Ok(response);
+        }
+
+        let delay = retry_after_secs(response.headers())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)))
+            .min(MAX_BACKOFF);
+        attempt += 1;
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn retry_after_parses_seconds() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after_secs(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(retry_after_secs(&headers), Some(12));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after_secs(&headers), None);
+    }
+}
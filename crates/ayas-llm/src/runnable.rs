@@ -37,8 +37,9 @@ impl<M: ChatModel + 'static> Runnable for ChatModelRunnable<M> {
     type Input = Vec<Message>;
     type Output = Vec<Message>;
 
-    async fn invoke(&self, input: Self::Input, _config: &RunnableConfig) -> Result<Self::Output> {
-        let result: ChatResult = self.model.generate(&input, &self.options).await?;
+    async fn invoke(&self, input: Self::Input, config: &RunnableConfig) -> Result<Self::Output> {
+        let options = self.options.clone().with_overrides(config);
+        let result: ChatResult = self.model.generate(&input, &options).await?;
         let mut messages = input;
         messages.push(result.message);
         Ok(messages)
@@ -47,12 +48,13 @@ impl<M: ChatModel + 'static> Runnable for ChatModelRunnable<M> {
     async fn stream(
         &self,
         input: Self::Input,
-        _config: &RunnableConfig,
+        config: &RunnableConfig,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Self::Output>> + Send>>>
     where
         Self::Output: 'static,
     {
-        let event_stream = self.model.stream(&input, &self.options).await?;
+        let options = self.options.clone().with_overrides(config);
+        let event_stream = self.model.stream(&input, &options).await?;
 
         let output_stream = async_stream::stream! {
             let mut text = String::new();
@@ -64,6 +66,7 @@ impl<M: ChatModel + 'static> Runnable for ChatModelRunnable<M> {
             while let Some(event_result) = event_stream.next().await {
                 match event_result {
                     Ok(ChatStreamEvent::Token(t)) => text.push_str(&t),
+                    Ok(ChatStreamEvent::Reasoning(_)) => {}
                     Ok(ChatStreamEvent::ToolCallStart { id, name }) => {
                         tool_calls.push(ToolCall {
                             id: id.clone(),
@@ -124,21 +127,24 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> ayas_core::error::Result<ChatResult> {
-            Ok(ChatResult {
-                message: Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: Some(UsageMetadata {
-                        input_tokens: 5,
-                        output_tokens: 3,
-                        total_tokens: 8,
-                    }),
+            let message = Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
+                usage: Some(UsageMetadata {
+                    input_tokens: 5,
+                    output_tokens: 3,
+                    total_tokens: 8,
+                    ..Default::default()
                 }),
+            });
+            Ok(ChatResult {
                 usage: Some(UsageMetadata {
                     input_tokens: 5,
                     output_tokens: 3,
                     total_tokens: 8,
+                    ..Default::default()
                 }),
+                ..ChatResult::new(message)
             })
         }
         fn model_name(&self) -> &str {
@@ -155,9 +161,7 @@ mod tests {
             _: &[Message],
             _: &CallOptions,
         ) -> ayas_core::error::Result<ChatResult> {
-            Err(AyasError::Model(ModelError::ApiRequest(
-                "mock error".into(),
-            )))
+            Err(AyasError::Model(ModelError::api_request("mock error")))
         }
         fn model_name(&self) -> &str {
             "error-mock"
@@ -0,0 +1,180 @@
+//! Client-side stop sequences for streamed responses.
+
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+
+use ayas_core::error::Result;
+use ayas_core::model::ChatStreamEvent;
+
+/// Cut a token stream at the first of `stop`.
+///
+/// Providers are asked to stop on these sequences too, but not all of them
+/// honor it for every model, and a sequence may arrive split across chunks.
+/// Text that could be the start of a stop sequence is held back until the
+/// next token decides it. Once a stop sequence is seen, the text before it
+/// is emitted, followed by `Done`, and the provider stream is dropped, so
+/// usage reported after that point is lost. Without stop sequences the
+/// stream is returned unchanged.
+pub fn stop_at_sequences<S>(
+    events: S,
+    stop: &[String],
+) -> Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>
+where
+    S: Stream<Item = Result<ChatStreamEvent>> + Send + 'static,
+{
+    let mut filter = StopFilter::new(stop);
+    if filter.stop.is_empty() {
+        return Box::pin(events);
+    }
+    Box::pin(async_stream::stream! {
+        let mut events = Box::pin(events);
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(ChatStreamEvent::Token(token)) => {
+                    let (text, stopped) = filter.push(&token);
+                    if !text.is_empty() {
+                        yield Ok(ChatStreamEvent::Token(text));
+                    }
+                    if stopped {
+                        yield Ok(ChatStreamEvent::Done);
+                        return;
+                    }
+                }
+                Ok(ChatStreamEvent::Done) => break,
+                other => yield other,
+            }
+        }
+        let rest = filter.flush();
+        if !rest.is_empty() {
+            yield Ok(ChatStreamEvent::Token(rest));
+        }
+        yield Ok(ChatStreamEvent::Done);
+    })
+}
+
+/// Incremental stop-sequence matcher over streamed text.
+struct StopFilter {
+    stop: Vec<String>,
+    /// Text not yet emitted: a possible start of a stop sequence.
+    pending: String,
+}
+
+impl StopFilter {
+    fn new(stop: &[String]) -> Self {
+        Self {
+            stop: stop.iter().filter(|s| !s.is_empty()).cloned().collect(),
+            pending: String::new(),
+        }
+    }
+
+    /// Add `token`; returns the text that is safe to emit and whether a stop
+    /// sequence was reached.
+    fn push(&mut self, token: &str) -> (String, bool) {
+        self.pending.push_str(token);
+        let hit = self.stop.iter().filter_map(|s| self.pending.find(s.as_str())).min();
+        if let Some(pos) = hit {
+            self.pending.truncate(pos);
+            return (std::mem::take(&mut self.pending), true);
+        }
+        let keep = self.partial_match_len();
+        let emit = self.pending[..self.pending.len() - keep].to_string();
+        self.pending.drain(..self.pending.len() - keep);
+        (emit, false)
+    }
+
+    /// Length of the longest suffix of `pending` that starts a stop sequence.
+    fn partial_match_len(&self) -> usize {
+        let len = self.pending.len();
+        let longest = self.stop.iter().map(|s| s.len() - 1).max().unwrap_or(0);
+        (1..=longest.min(len))
+            .rev()
+            .find(|&k| {
+                self.pending.is_char_boundary(len - k)
+                    && self.stop.iter().any(|s| s.starts_with(&self.pending[len - k..]))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Text held back when the stream ends without a stop sequence.
+    fn flush(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(chunks: &[&str], stop: &[&str]) -> Vec<ChatStreamEvent> {
+        let mut events: Vec<Result<ChatStreamEvent>> = chunks
+            .iter()
+            .map(|c| Ok(ChatStreamEvent::Token(c.to_string())))
+            .collect();
+        events.push(Ok(ChatStreamEvent::Done));
+        let stop: Vec<String> = stop.iter().map(|s| s.to_string()).collect();
+        stop_at_sequences(futures::stream::iter(events), &stop)
+            .map(|e| e.unwrap())
+            .collect()
+            .await
+    }
+
+    fn text(events: &[ChatStreamEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                ChatStreamEvent::Token(t) => Some(t.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stop_sequence_split_across_chunks() {
+        let chunks = ["The answer", " is 4\n\n", "#", "## Next", " question"];
+        let events = run(&chunks, &["\n###"]).await;
+        assert_eq!(text(&events), "The answer is 4\n");
+        assert_eq!(events.last(), Some(&ChatStreamEvent::Done));
+        assert_eq!(events.iter().filter(|e| **e == ChatStreamEvent::Done).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn earliest_stop_sequence_wins() {
+        let events = run(&["a STOP b END c"], &["END", "STOP"]).await;
+        assert_eq!(text(&events), "a ");
+    }
+
+    #[tokio::test]
+    async fn partial_match_is_released_when_it_diverges() {
+        let events = run(&["value: EN", "ough", " said"], &["END"]).await;
+        assert_eq!(text(&events), "value: ENough said");
+        // "EN" was held back until the next chunk ruled out "END".
+        assert_eq!(events[0], ChatStreamEvent::Token("value: ".into()));
+    }
+
+    #[tokio::test]
+    async fn held_back_text_is_flushed_at_the_end() {
+        let events = run(&["almost E", "N"], &["END"]).await;
+        assert_eq!(text(&events), "almost EN");
+        assert_eq!(events.last(), Some(&ChatStreamEvent::Done));
+    }
+
+    #[tokio::test]
+    async fn multibyte_stop_sequence() {
+        let events = run(&["答えは4です。", "終", "わり。続き"], &["終わり"]).await;
+        assert_eq!(text(&events), "答えは4です。");
+    }
+
+    #[tokio::test]
+    async fn without_stop_sequences_events_pass_through() {
+        let events = run(&["a", "b"], &[]).await;
+        assert_eq!(
+            events,
+            vec![
+                ChatStreamEvent::Token("a".into()),
+                ChatStreamEvent::Token("b".into()),
+                ChatStreamEvent::Done,
+            ]
+        );
+    }
+}
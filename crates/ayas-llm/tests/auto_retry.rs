@@ -0,0 +1,93 @@
+//! Auto-retry of rate-limited requests against a local mock provider.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use axum::Router;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use serde_json::json;
+
+use ayas_core::error::{AyasError, ModelError};
+use ayas_core::message::Message;
+use ayas_core::model::{CallOptions, ChatModel};
+use ayas_llm::claude::ClaudeChatModel;
+
+/// Serve a Claude-compatible `/v1/messages` that answers 429 (with
+/// `retry-after: 0`) to the first `failures` calls and succeeds afterwards.
+/// Returns the base URL and the call counter.
+async fn mock_claude(failures: u32) -> (String, Arc<AtomicU32>) {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let app = Router::new().route(
+        "/v1/messages",
+        post(move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    let body = json!({"error": {"message": "slow down"}}).to_string();
+                    return (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "0")], body)
+                        .into_response();
+                }
+                let body = json!({
+                    "content": [{"type": "text", "text": "hello"}],
+                    "usage": {"input_tokens": 3, "output_tokens": 1}
+                });
+                axum::Json(body).into_response()
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (format!("http://{addr}"), calls)
+}
+
+async fn generate(model: &ClaudeChatModel) -> ayas_core::error::Result<String> {
+    let result = model
+        .generate(&[Message::user("hi")], &CallOptions::default())
+        .await?;
+    Ok(result.message.content().to_string())
+}
+
+#[tokio::test]
+async fn claude_retries_rate_limited_requests() {
+    let (base_url, calls) = mock_claude(2).await;
+    let model = ClaudeChatModel::new("key".into(), "claude-haiku-4-5-20251001".into())
+        .with_base_url(base_url)
+        .with_max_auto_retries(2);
+
+    assert_eq!(generate(&model).await.unwrap(), "hello");
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn claude_without_auto_retry_reports_retry_after() {
+    let (base_url, calls) = mock_claude(1).await;
+    let model = ClaudeChatModel::new("key".into(), "claude-haiku-4-5-20251001".into())
+        .with_base_url(base_url);
+
+    let err = generate(&model).await.unwrap_err();
+    assert!(matches!(
+        err,
+        AyasError::Model(ModelError::RateLimited {
+            retry_after_secs: Some(0)
+        })
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn claude_gives_up_after_max_auto_retries() {
+    let (base_url, calls) = mock_claude(5).await;
+    let model = ClaudeChatModel::new("key".into(), "claude-haiku-4-5-20251001".into())
+        .with_base_url(base_url)
+        .with_max_auto_retries(1);
+
+    let err = generate(&model).await.unwrap_err();
+    assert!(matches!(err, AyasError::Model(ModelError::RateLimited { .. })));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,74 @@
+//! `ChatModel::count_tokens` against local mocks of the providers' counting
+//! endpoints.
+
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::http::Uri;
+use serde_json::{Value, json};
+
+use ayas_core::message::Message;
+use ayas_core::model::ChatModel;
+use ayas_llm::claude::ClaudeChatModel;
+use ayas_llm::gemini::GeminiChatModel;
+
+/// The path and JSON body of the last request the mock received.
+type Received = Arc<Mutex<Option<(String, Value)>>>;
+
+/// Serve `response` to any POST, recording what was sent. Returns the base
+/// URL and the recorded request.
+async fn mock_provider(response: Value) -> (String, Received) {
+    let received: Received = Arc::default();
+    let recorder = received.clone();
+    let app = Router::new().fallback(move |uri: Uri, axum::Json(body): axum::Json<Value>| {
+        let recorder = recorder.clone();
+        let response = response.clone();
+        async move {
+            *recorder.lock().unwrap() = Some((uri.path().to_string(), body));
+            axum::Json(response)
+        }
+    });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (format!("http://{addr}"), received)
+}
+
+fn messages() -> Vec<Message> {
+    vec![Message::system("Be brief."), Message::user("hello")]
+}
+
+#[tokio::test]
+async fn gemini_counts_with_count_tokens_endpoint() {
+    let (base_url, received) = mock_provider(json!({"totalTokens": 7})).await;
+    let model =
+        GeminiChatModel::new("key".into(), "gemini-2.5-flash".into()).with_base_url(base_url);
+
+    assert_eq!(model.count_tokens(&messages()).await.unwrap(), 7);
+
+    let (path, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(path, "/models/gemini-2.5-flash:countTokens");
+    let request = &body["generateContentRequest"];
+    assert_eq!(request["model"], "models/gemini-2.5-flash");
+    assert_eq!(request["contents"][0]["parts"][0]["text"], "hello");
+    assert!(request.get("system_instruction").is_some());
+}
+
+#[tokio::test]
+async fn claude_counts_with_count_tokens_endpoint() {
+    let (base_url, received) = mock_provider(json!({"input_tokens": 12})).await;
+    let model = ClaudeChatModel::new("key".into(), "claude-haiku-4-5-20251001".into())
+        .with_base_url(base_url);
+
+    assert_eq!(model.count_tokens(&messages()).await.unwrap(), 12);
+
+    let (path, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(path, "/v1/messages/count_tokens");
+    assert_eq!(body["model"], "claude-haiku-4-5-20251001");
+    assert_eq!(body["messages"][0]["content"], "hello");
+    assert!(body.get("system").is_some());
+    // Generation parameters are rejected by the endpoint, so none are sent.
+    assert!(body.get("max_tokens").is_none());
+}
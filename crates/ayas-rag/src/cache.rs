@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use ayas_core::error::{AyasError, ModelError, Result};
+use ayas_core::hash::Fnv1a;
+
+use crate::embedding::Embedding;
+use crate::types::EmbeddingVector;
+
+/// Entries kept in memory unless [`CachingEmbedding::with_capacity`] says
+/// otherwise.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// An [`Embedding`] wrapper that caches vectors by model and text.
+///
+/// Lookups go to an in-memory LRU cache, then to the optional disk cache,
+/// and only then to the wrapped embedding. Entries older than the TTL (if
+/// set) count as misses. Disk I/O failures are logged and treated as
+/// misses, never as errors.
+pub struct CachingEmbedding {
+    inner: Arc<dyn Embedding>,
+    model: String,
+    capacity: usize,
+    ttl: Option<Duration>,
+    disk_dir: Option<PathBuf>,
+    memory: Mutex<Lru>,
+}
+
+impl CachingEmbedding {
+    /// Wrap `inner`, whose vectors are cached under `model` so that caches
+    /// shared on disk never mix models.
+    pub fn new(inner: Arc<dyn Embedding>, model: impl Into<String>) -> Self {
+        Self {
+            inner,
+            model: model.into(),
+            capacity: DEFAULT_CAPACITY,
+            ttl: None,
+            disk_dir: None,
+            memory: Mutex::new(Lru::default()),
+        }
+    }
+
+    /// Maximum number of vectors kept in memory; least recently used go first.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// How long a cached vector stays valid.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Also persist vectors as JSON files under `dir`.
+    pub fn with_disk_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.disk_dir = Some(dir.into());
+        self
+    }
+
+    /// Number of vectors currently cached in memory.
+    pub fn len(&self) -> usize {
+        self.memory.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cache key: FNV-1a over the model and text, stable across processes.
+    fn key(&self, text: &str) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(self.model.as_bytes());
+        hasher.write(&[0]);
+        hasher.write(text.as_bytes());
+        hasher.finish()
+    }
+
+    async fn lookup(&self, text: &str) -> Option<EmbeddingVector> {
+        let key = self.key(text);
+        if let Some(vector) = self.memory.lock().unwrap().get(key, text, self.ttl) {
+            return Some(vector);
+        }
+        let entry = self.read_disk(key).await?;
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(entry.created_at))
+            .unwrap_or_default();
+        if entry.model != self.model || entry.text != text || self.ttl.is_some_and(|t| age > t) {
+            return None;
+        }
+        let vector = EmbeddingVector::new(entry.vector);
+        let inserted = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.memory
+            .lock()
+            .unwrap()
+            .insert(key, text, vector.clone(), inserted, self.capacity);
+        Some(vector)
+    }
+
+    async fn store(&self, text: &str, vector: &EmbeddingVector) {
+        let key = self.key(text);
+        self.memory
+            .lock()
+            .unwrap()
+            .insert(key, text, vector.clone(), Instant::now(), self.capacity);
+        self.write_disk(key, text, vector).await;
+    }
+
+    async fn read_disk(&self, key: u64) -> Option<DiskEntry> {
+        let path = self.disk_dir.as_ref()?.join(format!("{key:016x}.json"));
+        let bytes = tokio::fs::read(&path).await.ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt embedding cache file {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    async fn write_disk(&self, key: u64, text: &str, vector: &EmbeddingVector) {
+        let Some(dir) = &self.disk_dir else {
+            return;
+        };
+        let entry = DiskEntry {
+            model: self.model.clone(),
+            text: text.to_string(),
+            vector: vector.0.clone(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        let path = dir.join(format!("{key:016x}.json"));
+        let result = async {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(&path, serde_json::to_vec(&entry)?).await
+        };
+        if let Err(e) = result.await {
+            tracing::warn!("Failed to write embedding cache file {}: {e}", path.display());
+        }
+    }
+}
+
+#[async_trait]
+impl Embedding for CachingEmbedding {
+    async fn embed(&self, text: &str) -> Result<EmbeddingVector> {
+        if let Some(vector) = self.lookup(text).await {
+            return Ok(vector);
+        }
+        let vector = self.inner.embed(text).await?;
+        self.store(text, &vector).await;
+        Ok(vector)
+    }
+
+    /// Only texts missing from the cache are sent to the wrapped embedding,
+    /// each once however often it repeats in `texts`.
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<EmbeddingVector>> {
+        let mut found: HashMap<&str, EmbeddingVector> = HashMap::new();
+        let mut missing: Vec<&str> = Vec::new();
+        let mut seen: HashSet<&str> = HashSet::new();
+        for &text in texts {
+            if !seen.insert(text) {
+                continue;
+            }
+            match self.lookup(text).await {
+                Some(vector) => {
+                    found.insert(text, vector);
+                }
+                None => missing.push(text),
+            }
+        }
+
+        if !missing.is_empty() {
+            let vectors = self.inner.embed_batch(&missing).await?;
+            if vectors.len() != missing.len() {
+                return Err(AyasError::Model(ModelError::InvalidResponse(format!(
+                    "embedding returned {} vectors for {} texts",
+                    vectors.len(),
+                    missing.len()
+                ))));
+            }
+            for (text, vector) in missing.into_iter().zip(vectors) {
+                self.store(text, &vector).await;
+                found.insert(text, vector);
+            }
+        }
+        Ok(texts.iter().map(|text| found[text].clone()).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+}
+
+/// A cached vector as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    model: String,
+    text: String,
+    vector: Vec<f32>,
+    /// Unix seconds.
+    created_at: u64,
+}
+
+struct LruEntry {
+    text: String,
+    vector: EmbeddingVector,
+    inserted: Instant,
+    last_used: u64,
+}
+
+/// In-memory LRU map from key to vector. The text is kept to rule out hash
+/// collisions.
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<u64, LruEntry>,
+    /// `last_used` tick → key, oldest first.
+    order: BTreeMap<u64, u64>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: u64, text: &str, ttl: Option<Duration>) -> Option<EmbeddingVector> {
+        let entry = self.entries.get_mut(&key)?;
+        if entry.text != text {
+            return None;
+        }
+        if ttl.is_some_and(|ttl| entry.inserted.elapsed() > ttl) {
+            self.order.remove(&entry.last_used);
+            self.entries.remove(&key);
+            return None;
+        }
+        self.tick += 1;
+        self.order.remove(&entry.last_used);
+        self.order.insert(self.tick, key);
+        entry.last_used = self.tick;
+        Some(entry.vector.clone())
+    }
+
+    fn insert(
+        &mut self,
+        key: u64,
+        text: &str,
+        vector: EmbeddingVector,
+        inserted: Instant,
+        capacity: usize,
+    ) {
+        self.tick += 1;
+        let entry = LruEntry {
+            text: text.to_string(),
+            vector,
+            inserted,
+            last_used: self.tick,
+        };
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.order.remove(&old.last_used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Embeds a text as `[len, 1]` and counts the texts it was asked for.
+    #[derive(Default)]
+    struct CountingEmbedder {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Embedding for CountingEmbedder {
+        async fn embed(&self, text: &str) -> Result<EmbeddingVector> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(EmbeddingVector::new(vec![text.len() as f32, 1.0]))
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    fn cached(inner: &Arc<CountingEmbedder>) -> CachingEmbedding {
+        CachingEmbedding::new(inner.clone(), "counting")
+    }
+
+    #[tokio::test]
+    async fn identical_inputs_embed_once() {
+        let inner = Arc::new(CountingEmbedder::default());
+        let cache = cached(&inner);
+
+        let first = cache.embed("hello").await.unwrap();
+        let second = cache.embed("hello").await.unwrap();
+        assert_eq!(first.0, second.0);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        let batch = cache.embed_batch(&["hello", "hi", "hi"]).await.unwrap();
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch[1].0, vec![2.0, 1.0]);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn capacity_evicts_least_recently_used() {
+        let inner = Arc::new(CountingEmbedder::default());
+        let cache = cached(&inner).with_capacity(2);
+
+        cache.embed("a").await.unwrap();
+        cache.embed("b").await.unwrap();
+        cache.embed("a").await.unwrap();
+        cache.embed("c").await.unwrap(); // evicts "b"
+        assert_eq!(cache.len(), 2);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+
+        cache.embed("a").await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+        cache.embed("b").await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_refetched() {
+        let inner = Arc::new(CountingEmbedder::default());
+        let cache = cached(&inner).with_ttl(Duration::from_millis(20));
+
+        cache.embed("hello").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        cache.embed("hello").await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn disk_cache_survives_a_new_wrapper() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = Arc::new(CountingEmbedder::default());
+
+        cached(&inner).with_disk_cache(dir.path()).embed("hello").await.unwrap();
+        let reopened = cached(&inner).with_disk_cache(dir.path());
+        assert!(reopened.is_empty());
+        assert_eq!(reopened.embed("hello").await.unwrap().0, vec![5.0, 1.0]);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+
+        // Another model never reads the first model's vectors.
+        let other = CachingEmbedding::new(inner.clone(), "other").with_disk_cache(dir.path());
+        other.embed("hello").await.unwrap();
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Returns one vector too few for every batch.
+    struct ShortBatchEmbedder;
+
+    #[async_trait]
+    impl Embedding for ShortBatchEmbedder {
+        async fn embed(&self, _text: &str) -> Result<EmbeddingVector> {
+            Ok(EmbeddingVector::new(vec![1.0, 1.0]))
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<EmbeddingVector>> {
+            Ok(vec![EmbeddingVector::new(vec![1.0, 1.0]); texts.len() - 1])
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn short_batch_is_an_error() {
+        let cache = CachingEmbedding::new(Arc::new(ShortBatchEmbedder), "short");
+        let err = cache.embed_batch(&["a", "b"]).await.unwrap_err();
+        assert!(err.to_string().contains("1 vectors for 2 texts"), "{err}");
+        assert!(cache.is_empty());
+    }
+}
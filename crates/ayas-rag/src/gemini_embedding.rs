@@ -71,7 +71,7 @@ impl Embedding for GeminiEmbedding {
             .json(&request)
             .send()
             .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+            .map_err(|e| AyasError::Model(ModelError::api_request(e.to_string())))?;
 
         let status = response.status();
         if status == reqwest::StatusCode::UNAUTHORIZED
@@ -88,9 +88,10 @@ impl Embedding for GeminiEmbedding {
         }
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
-            return Err(AyasError::Model(ModelError::ApiRequest(format!(
-                "Gemini API error {status}: {body}"
-            ))));
+            return Err(AyasError::Model(ModelError::http(
+                status.as_u16(),
+                format!("Gemini API error: {body}"),
+            )));
         }
 
         let body: GeminiEmbedResponse = response
@@ -5,12 +5,23 @@ use tokio::sync::RwLock;
 
 use ayas_core::error::Result;
 
-use crate::store::VectorStore;
+use crate::retriever::mmr_select;
+use crate::store::{check_dimension, VectorStore};
 use crate::types::{Document, EmbeddingVector, SearchOptions, SearchResult};
 
+type Entries = HashMap<String, (Document, EmbeddingVector)>;
+
 /// An in-memory vector store backed by a HashMap.
+///
+/// All embeddings must share one dimension: the first document added fixes
+/// it, and later documents and queries of another size are rejected.
 pub struct InMemoryVectorStore {
-    data: RwLock<HashMap<String, (Document, EmbeddingVector)>>,
+    data: RwLock<Entries>,
+}
+
+/// Dimension of the stored embeddings, if any are stored.
+fn stored_dimension(data: &Entries) -> Option<usize> {
+    data.values().next().map(|(_, emb)| emb.dimension())
 }
 
 impl InMemoryVectorStore {
@@ -21,6 +32,53 @@ impl InMemoryVectorStore {
     }
 }
 
+impl InMemoryVectorStore {
+    /// Maximal Marginal Relevance search over the stored embeddings.
+    ///
+    /// Takes the `fetch_k` most similar documents (after the score threshold;
+    /// at least `options.k`) and picks `options.k` of them with
+    /// [`mmr_select`], trading relevance (`lambda = 1.0`) for diversity
+    /// (`lambda = 0.0`).
+    pub async fn max_marginal_relevance_search(
+        &self,
+        query: &EmbeddingVector,
+        options: SearchOptions,
+        fetch_k: usize,
+        lambda: f32,
+    ) -> Result<Vec<SearchResult>> {
+        let data = self.data.read().await;
+        if let Some(expected) = stored_dimension(&data) {
+            check_dimension(expected, query)?;
+        }
+
+        let mut scored: Vec<(SearchResult, &EmbeddingVector)> = data
+            .values()
+            .map(|(doc, emb)| {
+                let result = SearchResult {
+                    document: doc.clone(),
+                    score: query.cosine_similarity(emb),
+                };
+                (result, emb)
+            })
+            .collect();
+        if let Some(threshold) = options.score_threshold {
+            scored.retain(|(r, _)| r.score >= threshold);
+        }
+        scored.sort_by(|a, b| {
+            b.0.score
+                .partial_cmp(&a.0.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scored.truncate(fetch_k.max(options.k));
+
+        let (candidates, embeddings): (Vec<SearchResult>, Vec<EmbeddingVector>) = scored
+            .into_iter()
+            .map(|(result, emb)| (result, emb.clone()))
+            .unzip();
+        Ok(mmr_select(query, &candidates, &embeddings, options.k, lambda))
+    }
+}
+
 impl Default for InMemoryVectorStore {
     fn default() -> Self {
         Self::new()
@@ -29,8 +87,26 @@ impl Default for InMemoryVectorStore {
 
 #[async_trait]
 impl VectorStore for InMemoryVectorStore {
+    /// Same as [`VectorStore::upsert`]: a document whose id is already
+    /// stored replaces it.
     async fn add_documents(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>> {
+        self.upsert(docs).await
+    }
+
+    /// Replacing every stored document may change the store's dimension,
+    /// e.g. when re-embedding the corpus with another model.
+    async fn upsert(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>> {
         let mut data = self.data.write().await;
+        let expected = data
+            .iter()
+            .find(|(id, _)| !docs.iter().any(|(doc, _)| &doc.id == *id))
+            .map(|(_, (_, emb))| emb.dimension())
+            .or_else(|| docs.first().map(|(_, emb)| emb.dimension()));
+        if let Some(expected) = expected {
+            for (_, emb) in &docs {
+                check_dimension(expected, emb)?;
+            }
+        }
         let ids: Vec<String> = docs
             .into_iter()
             .map(|(doc, emb)| {
@@ -48,6 +124,9 @@ impl VectorStore for InMemoryVectorStore {
         options: SearchOptions,
     ) -> Result<Vec<SearchResult>> {
         let data = self.data.read().await;
+        if let Some(expected) = stored_dimension(&data) {
+            check_dimension(expected, query)?;
+        }
 
         let mut scored: Vec<SearchResult> = data
             .values()
@@ -165,6 +244,111 @@ mod tests {
         assert!((results[0].score - 1.0).abs() < 1e-6);
     }
 
+    #[tokio::test]
+    async fn mmr_search_diversifies_over_fetch_k_pool() {
+        let store = InMemoryVectorStore::new();
+        store
+            .add_documents(vec![
+                (make_doc("a", "original"), make_emb(vec![1.0, 0.0, 0.0])),
+                (make_doc("b", "near duplicate"), make_emb(vec![0.99, 0.01, 0.0])),
+                (make_doc("c", "related"), make_emb(vec![0.6, 0.8, 0.0])),
+            ])
+            .await
+            .unwrap();
+        let query = make_emb(vec![1.0, 0.1, 0.0]);
+        let options = SearchOptions { k: 2, score_threshold: None };
+        let ids = |results: Vec<SearchResult>| {
+            let mut ids: Vec<String> = results.into_iter().map(|r| r.document.id).collect();
+            ids.sort();
+            ids
+        };
+
+        let similar = store.similarity_search(&query, options.clone()).await.unwrap();
+        assert_eq!(ids(similar), vec!["a", "b"]);
+
+        let diverse = store
+            .max_marginal_relevance_search(&query, options.clone(), 8, 0.3)
+            .await
+            .unwrap();
+        assert_eq!(diverse.len(), 2);
+        assert!(ids(diverse).contains(&"c".to_string()));
+
+        // A pool of 2 leaves nothing to diversify with.
+        let pooled = store.max_marginal_relevance_search(&query, options, 2, 0.3).await.unwrap();
+        assert_eq!(ids(pooled), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn upsert_replaces_reembedded_document() {
+        let store = InMemoryVectorStore::new();
+        store
+            .add_documents(vec![
+                (make_doc("d1", "old text"), make_emb(vec![1.0, 0.0])),
+                (make_doc("d2", "other"), make_emb(vec![0.7, 0.7])),
+            ])
+            .await
+            .unwrap();
+
+        let ids = store
+            .upsert(vec![(make_doc("d1", "new text"), make_emb(vec![0.0, 1.0]))])
+            .await
+            .unwrap();
+        assert_eq!(ids, vec!["d1"]);
+
+        let results = store
+            .similarity_search(&make_emb(vec![0.0, 1.0]), SearchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].document.id, "d1");
+        assert_eq!(results[0].document.content, "new text");
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+
+        // A partial re-embedding must keep the store's dimension...
+        assert!(store
+            .upsert(vec![(make_doc("d1", "new text"), make_emb(vec![0.0, 1.0, 0.0]))])
+            .await
+            .is_err());
+        // ...while replacing every document may change it.
+        store.delete(&["d2".into()]).await.unwrap();
+        store
+            .upsert(vec![(make_doc("d1", "new text"), make_emb(vec![0.0, 1.0, 0.0]))])
+            .await
+            .unwrap();
+        let results = store
+            .similarity_search(&make_emb(vec![0.0, 1.0, 0.0]), SearchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dimension_mismatch_is_rejected() {
+        let store = InMemoryVectorStore::new();
+        let mixed = store
+            .add_documents(vec![
+                (make_doc("d1", "three"), make_emb(vec![1.0, 0.0, 0.0])),
+                (make_doc("d2", "two"), make_emb(vec![1.0, 0.0])),
+            ])
+            .await;
+        assert!(matches!(mixed, Err(ayas_core::error::AyasError::Validation(_))));
+        assert!(store.get("d1").await.unwrap().is_none());
+
+        store
+            .add_documents(vec![(make_doc("d1", "three"), make_emb(vec![1.0, 0.0, 0.0]))])
+            .await
+            .unwrap();
+        let err = store
+            .similarity_search(&make_emb(vec![1.0, 0.0]), SearchOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("store uses 3, vector has 2"));
+        assert!(store
+            .add_documents(vec![(make_doc("d2", "two"), make_emb(vec![1.0, 0.0]))])
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn similarity_search_with_threshold() {
         let store = InMemoryVectorStore::new();
@@ -1,8 +1,9 @@
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 
 use ayas_core::error::{AyasError, ModelError, Result};
+use ayas_llm::retry::{retry_after_secs, send_with_retry};
 
 use crate::embedding::Embedding;
 use crate::types::EmbeddingVector;
@@ -33,12 +34,101 @@ impl OpenAiEmbeddingModel {
     }
 }
 
+/// What a batch does when the API rejects it because of some of its inputs
+/// (HTTP 400, e.g. an input over the model's token limit).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchFailurePolicy {
+    /// The whole batch fails.
+    #[default]
+    Fail,
+    /// The input the error names is skipped and the rest resent. If the
+    /// error names none, every input of the rejected request is skipped.
+    Skip,
+    /// The rejected request is split in halves and each resent, down to
+    /// single inputs, so only the inputs that fail on their own are skipped.
+    Split,
+}
+
+/// An input [`OpenAiEmbedding::embed_batch_partial`] could not embed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingFailure {
+    /// Position in the input texts.
+    pub index: usize,
+    pub error: String,
+}
+
+/// Result of [`OpenAiEmbedding::embed_batch_partial`]: a vector per input,
+/// `None` where the input failed.
+#[derive(Debug, Clone, Default)]
+pub struct BatchEmbeddings {
+    pub vectors: Vec<Option<EmbeddingVector>>,
+    /// Failed inputs, by index.
+    pub failures: Vec<EmbeddingFailure>,
+}
+
+impl BatchEmbeddings {
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// All vectors, or an error naming the failed inputs.
+    pub fn into_complete(self) -> Result<Vec<EmbeddingVector>> {
+        if !self.failures.is_empty() {
+            let failed: Vec<String> = self
+                .failures
+                .iter()
+                .map(|f| format!("input {}: {}", f.index, f.error))
+                .collect();
+            return Err(AyasError::Model(ModelError::api_request(format!(
+                "{} of {} inputs failed to embed ({})",
+                self.failures.len(),
+                self.vectors.len(),
+                failed.join("; ")
+            ))));
+        }
+        Ok(self.vectors.into_iter().flatten().collect())
+    }
+
+    fn fail(&mut self, index: usize, error: String) {
+        self.failures.push(EmbeddingFailure { index, error });
+    }
+}
+
+/// Why a request failed.
+enum ApiFailure {
+    /// The API rejected the inputs; `index` is the offending input's position
+    /// in the request if the error names one.
+    Rejected { message: String, index: Option<usize> },
+    Other(AyasError),
+}
+
+impl From<AyasError> for ApiFailure {
+    fn from(e: AyasError) -> Self {
+        Self::Other(e)
+    }
+}
+
+impl From<ApiFailure> for AyasError {
+    fn from(failure: ApiFailure) -> Self {
+        match failure {
+            ApiFailure::Rejected { message, .. } => {
+                AyasError::Model(ModelError::http(400, message))
+            }
+            ApiFailure::Other(e) => e,
+        }
+    }
+}
+
 /// OpenAI Embeddings provider.
 pub struct OpenAiEmbedding {
     client: Client,
     api_key: String,
     model: OpenAiEmbeddingModel,
     base_url: String,
+    /// Reduced output size requested from the API (`text-embedding-3` only).
+    dimensions: Option<usize>,
+    max_auto_retries: u32,
+    failure_policy: BatchFailurePolicy,
 }
 
 impl OpenAiEmbedding {
@@ -60,6 +150,9 @@ impl OpenAiEmbedding {
             api_key,
             model,
             base_url: "https://api.openai.com".into(),
+            dimensions: None,
+            max_auto_retries: 0,
+            failure_policy: BatchFailurePolicy::default(),
         })
     }
 
@@ -69,37 +162,121 @@ impl OpenAiEmbedding {
         self
     }
 
-    async fn call_api(&self, input: Vec<&str>) -> Result<Vec<EmbeddingVector>> {
+    /// Ask the API for `dimensions`-long embeddings instead of the model's
+    /// full size, trading some accuracy for smaller vectors.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Retry requests answered with 429 or 503 up to `max_auto_retries` times,
+    /// honouring `Retry-After` and otherwise backing off exponentially.
+    pub fn with_max_auto_retries(mut self, max_auto_retries: u32) -> Self {
+        self.max_auto_retries = max_auto_retries;
+        self
+    }
+
+    /// How batches handle inputs the API rejects (default: fail the batch).
+    pub fn with_failure_policy(mut self, policy: BatchFailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Embed `texts`, isolating inputs the API rejects according to the
+    /// failure policy instead of failing the whole batch. Auth, transport
+    /// and exhausted rate-limit errors still fail the call.
+    pub async fn embed_batch_partial(&self, texts: &[&str]) -> Result<BatchEmbeddings> {
+        let mut out = BatchEmbeddings {
+            vectors: vec![None; texts.len()],
+            failures: Vec::new(),
+        };
+        let mut pending = vec![(0..texts.len()).collect::<Vec<usize>>()];
+        while let Some(mut batch) = pending.pop() {
+            if batch.is_empty() {
+                continue;
+            }
+            let input: Vec<&str> = batch.iter().map(|&i| texts[i]).collect();
+            let (message, index) = match self.call_api(&input).await {
+                Ok(vectors) => {
+                    for (i, vector) in batch.into_iter().zip(vectors) {
+                        out.vectors[i] = Some(vector);
+                    }
+                    continue;
+                }
+                Err(ApiFailure::Rejected { message, index }) => (message, index),
+                Err(ApiFailure::Other(e)) => return Err(e),
+            };
+
+            match self.failure_policy {
+                BatchFailurePolicy::Fail => {
+                    return Err(AyasError::Model(ModelError::http(400, message)));
+                }
+                _ if batch.len() == 1 => out.fail(batch[0], message),
+                BatchFailurePolicy::Skip => match index.filter(|&i| i < batch.len()) {
+                    Some(position) => {
+                        out.fail(batch.remove(position), message);
+                        pending.push(batch);
+                    }
+                    None => {
+                        for i in batch {
+                            out.fail(i, message.clone());
+                        }
+                    }
+                },
+                BatchFailurePolicy::Split => {
+                    let right = batch.split_off(batch.len() / 2);
+                    // Left half first
+                    pending.push(right);
+                    pending.push(batch);
+                }
+            }
+        }
+        out.failures.sort_by_key(|f| f.index);
+        Ok(out)
+    }
+
+    async fn call_api(
+        &self,
+        input: &[&str],
+    ) -> std::result::Result<Vec<EmbeddingVector>, ApiFailure> {
         let request = EmbeddingRequest {
-            input: input.into_iter().map(String::from).collect(),
+            input: input.iter().map(|s| s.to_string()).collect(),
             model: self.model.as_str().to_string(),
+            dimensions: self.dimensions,
         };
 
-        let response = self
-            .client
-            .post(format!("{}/v1/embeddings", self.base_url))
-            .bearer_auth(&self.api_key)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AyasError::Model(ModelError::ApiRequest(e.to_string())))?;
+        let url = format!("{}/v1/embeddings", self.base_url);
+        let response = send_with_retry(self.max_auto_retries, || {
+            self.client
+                .post(&url)
+                .bearer_auth(&self.api_key)
+                .json(&request)
+        })
+        .await?;
 
         let status = response.status();
-        if status == reqwest::StatusCode::UNAUTHORIZED {
+        if status == StatusCode::UNAUTHORIZED {
             return Err(AyasError::Model(ModelError::Auth(
                 "Invalid OpenAI API key".into(),
-            )));
+            ))
+            .into());
         }
-        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        if status == StatusCode::TOO_MANY_REQUESTS {
             return Err(AyasError::Model(ModelError::RateLimited {
-                retry_after_secs: None,
-            }));
+                retry_after_secs: retry_after_secs(response.headers()),
+            })
+            .into());
         }
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
-            return Err(AyasError::Model(ModelError::ApiRequest(format!(
-                "OpenAI API error {status}: {body}"
-            ))));
+            let message = format!("OpenAI API error {status}: {body}");
+            if status == StatusCode::BAD_REQUEST {
+                return Err(ApiFailure::Rejected {
+                    index: offending_index(&body),
+                    message,
+                });
+            }
+            return Err(AyasError::Model(ModelError::http(status.as_u16(), message)).into());
         }
 
         let body: EmbeddingResponse = response
@@ -112,6 +289,14 @@ impl OpenAiEmbedding {
             .into_iter()
             .map(|d| (d.index, EmbeddingVector::new(d.embedding)))
             .collect();
+        if embeddings.len() != input.len() {
+            return Err(AyasError::Model(ModelError::InvalidResponse(format!(
+                "Expected {} embeddings, got {}",
+                input.len(),
+                embeddings.len()
+            )))
+            .into());
+        }
 
         // Sort by index to maintain input order
         embeddings.sort_by_key(|(i, _)| *i);
@@ -120,25 +305,45 @@ impl OpenAiEmbedding {
     }
 }
 
+/// Position of the input an error response blames, from a `param` such as
+/// `input[3]` or the same pattern in the message.
+fn offending_index(body: &str) -> Option<usize> {
+    let response: ErrorResponse = serde_json::from_str(body).ok()?;
+    let error = response.error;
+    [error.param.as_deref(), Some(error.message.as_str())]
+        .into_iter()
+        .flatten()
+        .find_map(|text| {
+            let start = text.find("input[")? + "input[".len();
+            let digits: String = text[start..].chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+}
+
 #[async_trait]
 impl Embedding for OpenAiEmbedding {
     async fn embed(&self, text: &str) -> Result<EmbeddingVector> {
-        let results = self.call_api(vec![text]).await?;
+        let results = self.call_api(&[text]).await?;
         results
             .into_iter()
             .next()
             .ok_or_else(|| AyasError::Model(ModelError::InvalidResponse("Empty response".into())))
     }
 
+    /// With a failure policy other than [`BatchFailurePolicy::Fail`], rejected
+    /// inputs are isolated first and the error names them all.
     async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<EmbeddingVector>> {
         if texts.is_empty() {
             return Ok(vec![]);
         }
-        self.call_api(texts.to_vec()).await
+        if self.failure_policy == BatchFailurePolicy::Fail {
+            return Ok(self.call_api(texts).await?);
+        }
+        self.embed_batch_partial(texts).await?.into_complete()
     }
 
     fn dimension(&self) -> usize {
-        self.model.dimension()
+        self.dimensions.unwrap_or_else(|| self.model.dimension())
     }
 }
 
@@ -146,6 +351,8 @@ impl Embedding for OpenAiEmbedding {
 struct EmbeddingRequest {
     input: Vec<String>,
     model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
 }
 
 #[derive(Deserialize)]
@@ -159,6 +366,19 @@ struct EmbeddingData {
     index: usize,
 }
 
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    param: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,6 +469,30 @@ mod tests {
         assert_eq!(embedding.base_url, "http://localhost:8080");
     }
 
+    #[test]
+    fn dimensions_are_requested_and_reported() {
+        let embedding =
+            OpenAiEmbedding::with_api_key("key".into(), OpenAiEmbeddingModel::TextEmbedding3Large)
+                .unwrap();
+        assert_eq!(embedding.dimension(), 3072);
+        let embedding = embedding.with_dimensions(256);
+        assert_eq!(embedding.dimension(), 256);
+
+        let request = EmbeddingRequest {
+            input: vec!["hi".into()],
+            model: "text-embedding-3-large".into(),
+            dimensions: embedding.dimensions,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["dimensions"], 256);
+
+        let request = EmbeddingRequest {
+            dimensions: None,
+            ..request
+        };
+        assert!(serde_json::to_value(&request).unwrap().get("dimensions").is_none());
+    }
+
     #[test]
     fn response_out_of_order_indices() {
         let json = r#"{
@@ -269,4 +513,43 @@ mod tests {
         assert_eq!(embeddings[0].1, vec![0.1, 0.2]);
         assert_eq!(embeddings[1].1, vec![0.4, 0.5]);
     }
+
+    #[test]
+    fn offending_index_from_param_or_message() {
+        let body = r#"{"error": {"message": "too long", "param": "input[3]"}}"#;
+        assert_eq!(offending_index(body), Some(3));
+        let body = r#"{"error": {"message": "Invalid 'input[12]': string too long."}}"#;
+        assert_eq!(offending_index(body), Some(12));
+        let body = r#"{"error": {"message": "maximum context length is 8192 tokens"}}"#;
+        assert_eq!(offending_index(body), None);
+        assert_eq!(offending_index("not json"), None);
+    }
+
+    #[test]
+    fn batch_embeddings_into_complete() {
+        let vector = |v: f32| Some(EmbeddingVector::new(vec![v]));
+        let complete = BatchEmbeddings {
+            vectors: vec![vector(1.0), vector(2.0)],
+            failures: Vec::new(),
+        };
+        assert!(complete.is_complete());
+        assert_eq!(complete.into_complete().unwrap().len(), 2);
+
+        let partial = BatchEmbeddings {
+            vectors: vec![None, vector(1.0), None],
+            failures: vec![
+                EmbeddingFailure {
+                    index: 0,
+                    error: "too long".into(),
+                },
+                EmbeddingFailure {
+                    index: 2,
+                    error: "empty".into(),
+                },
+            ],
+        };
+        let err = partial.into_complete().unwrap_err().to_string();
+        assert!(err.contains("2 of 3 inputs"), "{err}");
+        assert!(err.contains("input 0: too long; input 2: empty"), "{err}");
+    }
 }
@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -5,14 +7,21 @@ use serde_json::Value;
 
 use ayas_core::error::{AyasError, Result};
 
-use crate::store::VectorStore;
+use crate::store::{check_dimension, VectorStore};
 use crate::types::{Document, EmbeddingVector, SearchOptions, SearchResult};
 
 /// Qdrant vector store using the REST API.
+///
+/// Once the collection's dimension is known (from [`Self::with_dimension`]
+/// or [`Self::ensure_collection`]), vectors of another size are rejected
+/// before they reach Qdrant. The first write creates the collection if it
+/// is missing, sized to the written vectors.
 pub struct QdrantStore {
     client: Client,
     base_url: String,
     collection_name: String,
+    dimension: OnceLock<usize>,
+    collection_ready: tokio::sync::OnceCell<()>,
 }
 
 impl QdrantStore {
@@ -25,6 +34,20 @@ impl QdrantStore {
             client: Client::new(),
             base_url,
             collection_name: collection_name.to_string(),
+            dimension: OnceLock::new(),
+            collection_ready: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// A store for another collection on the same Qdrant server, sharing
+    /// this store's HTTP client.
+    pub fn with_collection(&self, collection_name: &str) -> Self {
+        Self {
+            client: self.client.clone(),
+            base_url: self.base_url.clone(),
+            collection_name: collection_name.to_string(),
+            dimension: OnceLock::new(),
+            collection_ready: tokio::sync::OnceCell::new(),
         }
     }
 
@@ -33,7 +56,22 @@ impl QdrantStore {
         self
     }
 
+    /// Declare the collection's vector dimension.
+    pub fn with_dimension(mut self, dimension: usize) -> Self {
+        self.dimension = OnceLock::from(dimension);
+        self
+    }
+
+    fn check_dimension(&self, vector: &EmbeddingVector) -> Result<()> {
+        match self.dimension.get() {
+            Some(&expected) => check_dimension(expected, vector),
+            None => Ok(()),
+        }
+    }
+
     /// Ensure the collection exists with the given vector dimension.
+    ///
+    /// An existing collection of another dimension is an error.
     pub async fn ensure_collection(&self, dimension: usize) -> Result<()> {
         let url = format!(
             "{}/collections/{}",
@@ -49,6 +87,18 @@ impl QdrantStore {
             .map_err(|e| AyasError::Other(format!("Qdrant connection error: {e}")))?;
 
         if resp.status().is_success() {
+            let info: Value = resp
+                .json()
+                .await
+                .map_err(|e| AyasError::Other(format!("Qdrant response parse error: {e}")))?;
+            let size = info["result"]["config"]["params"]["vectors"]["size"].as_u64();
+            if let Some(size) = size.filter(|&size| size as usize != dimension) {
+                return Err(AyasError::Validation(format!(
+                    "Qdrant collection '{}' has dimension {size}, expected {dimension}",
+                    self.collection_name
+                )));
+            }
+            let _ = self.dimension.set(dimension);
             return Ok(());
         }
 
@@ -75,13 +125,27 @@ impl QdrantStore {
             )));
         }
 
+        let _ = self.dimension.set(dimension);
         Ok(())
     }
 }
 
 #[async_trait]
 impl VectorStore for QdrantStore {
+    /// Same as [`VectorStore::upsert`]: Qdrant replaces points by id.
     async fn add_documents(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>> {
+        self.upsert(docs).await
+    }
+
+    async fn upsert(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>> {
+        for (_, emb) in &docs {
+            self.check_dimension(emb)?;
+        }
+        if let Some((_, first)) = docs.first() {
+            self.collection_ready
+                .get_or_try_init(|| self.ensure_collection(first.dimension()))
+                .await?;
+        }
         let points: Vec<QdrantPoint> = docs
             .iter()
             .map(|(doc, emb)| QdrantPoint {
@@ -98,8 +162,9 @@ impl VectorStore for QdrantStore {
 
         let body = QdrantUpsertRequest { points };
 
+        // Wait for the points to be indexed, so they are searchable on return.
         let url = format!(
-            "{}/collections/{}/points",
+            "{}/collections/{}/points?wait=true",
             self.base_url, self.collection_name
         );
 
@@ -124,6 +189,7 @@ impl VectorStore for QdrantStore {
         query: &EmbeddingVector,
         options: SearchOptions,
     ) -> Result<Vec<SearchResult>> {
+        self.check_dimension(query)?;
         let body = QdrantSearchRequest {
             vector: query.0.clone(),
             limit: options.k,
@@ -322,12 +388,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn with_collection_shares_server() {
+        let store = QdrantStore::new("tenant-a")
+            .with_url("http://qdrant:6333".into())
+            .with_dimension(3);
+        let other = store.with_collection("tenant-b");
+        assert_eq!(other.base_url, "http://qdrant:6333");
+        assert_eq!(other.collection_name, "tenant-b");
+        assert_eq!(other.dimension.get(), None);
+    }
+
     #[test]
     fn with_url_override() {
         let store = QdrantStore::new("coll").with_url("http://override:8080".into());
         assert_eq!(store.base_url, "http://override:8080");
     }
 
+    #[tokio::test]
+    async fn dimension_mismatch_fails_before_request() {
+        let store = QdrantStore::new("coll")
+            .with_url("http://127.0.0.1:9".into())
+            .with_dimension(3);
+        let doc = Document {
+            id: "p1".into(),
+            content: "hello".into(),
+            metadata: Default::default(),
+        };
+
+        let err = store
+            .add_documents(vec![(doc, EmbeddingVector::new(vec![1.0, 0.0]))])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Validation(_)));
+        let err = store
+            .similarity_search(&EmbeddingVector::new(vec![1.0]), SearchOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Validation(_)));
+    }
+
     #[test]
     fn serialize_upsert_request() {
         let req = QdrantUpsertRequest {
@@ -1,15 +1,19 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::Deserialize;
 use serde_json::Value;
+use tokio::sync::RwLock;
 
 use ayas_core::config::RunnableConfig;
 use ayas_core::error::{AyasError, Result};
 use ayas_core::runnable::Runnable;
 
 use crate::embedding::Embedding;
+use crate::splitter::TextSplitter;
 use crate::store::VectorStore;
-use crate::types::{EmbeddingVector, SearchOptions, SearchResult};
+use crate::types::{Document, EmbeddingVector, SearchOptions, SearchResult};
 
 // ---------------------------------------------------------------------------
 // SimilarityRetriever (original Retriever, renamed for clarity)
@@ -59,6 +63,60 @@ impl Runnable for Retriever {
 /// Alias for the basic similarity retriever.
 pub type SimilarityRetriever = Retriever;
 
+// ---------------------------------------------------------------------------
+// RetrieverRunnable
+// ---------------------------------------------------------------------------
+
+/// Adapts one of the retrievers in this module to typed input and output,
+/// for composing into chains with `pipe`: takes the query and returns the
+/// matching results.
+pub struct RetrieverRunnable<R> {
+    retriever: R,
+}
+
+impl<R> RetrieverRunnable<R>
+where
+    R: Runnable<Input = Value, Output = Value>,
+{
+    pub fn new(retriever: R) -> Self {
+        Self { retriever }
+    }
+}
+
+#[async_trait]
+impl<R> Runnable for RetrieverRunnable<R>
+where
+    R: Runnable<Input = Value, Output = Value>,
+{
+    type Input = String;
+    type Output = Vec<SearchResult>;
+
+    async fn invoke(&self, input: String, config: &RunnableConfig) -> Result<Vec<SearchResult>> {
+        let results = self.retriever.invoke(Value::String(input), config).await?;
+        results_from_json(results)
+    }
+}
+
+/// A Runnable that joins the results' contents, separated by blank lines,
+/// into a context string for a prompt.
+pub struct FormatDocs;
+
+#[async_trait]
+impl Runnable for FormatDocs {
+    type Input = Vec<SearchResult>;
+    type Output = String;
+
+    async fn invoke(&self, results: Vec<SearchResult>, _config: &RunnableConfig) -> Result<String> {
+        let contents: Vec<String> = results.into_iter().map(|r| r.document.content).collect();
+        Ok(contents.join("\n\n"))
+    }
+}
+
+/// See [`FormatDocs`].
+pub fn format_docs() -> FormatDocs {
+    FormatDocs
+}
+
 // ---------------------------------------------------------------------------
 // ThresholdRetriever
 // ---------------------------------------------------------------------------
@@ -145,6 +203,13 @@ impl MaxMarginalRelevanceRetriever {
             lambda,
         }
     }
+
+    /// Fetch `fetch_k` candidates for MMR to pick from. A pool smaller than
+    /// `k` is raised to `k`.
+    pub fn with_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.fetch_k = fetch_k;
+        self
+    }
 }
 
 #[async_trait]
@@ -161,7 +226,7 @@ impl Runnable for MaxMarginalRelevanceRetriever {
 
         // Fetch more candidates than needed
         let options = SearchOptions {
-            k: self.fetch_k,
+            k: self.fetch_k.max(self.k),
             score_threshold: None,
         };
         let candidates = self.store.similarity_search(&query_embedding, options).await?;
@@ -250,6 +315,147 @@ pub fn mmr_select(
         .collect()
 }
 
+// ---------------------------------------------------------------------------
+// ParentDocumentRetriever
+// ---------------------------------------------------------------------------
+
+/// Chunk metadata key holding the id of the document the chunk came from.
+pub const PARENT_ID_KEY: &str = "parent_id";
+
+/// A retriever that searches small chunks but returns their parent documents.
+///
+/// [`Self::add_documents`] keeps each parent and indexes its chunks, tagged
+/// with [`PARENT_ID_KEY`]. A query searches the chunks (over-fetching
+/// `4 * options.k` of them by default, since several may share a parent) and
+/// returns up to `options.k` distinct parents, ranked and scored by their
+/// best chunk. A chunk without a known parent is returned as itself.
+pub struct ParentDocumentRetriever {
+    embedder: Arc<dyn Embedding>,
+    /// Store for the chunks.
+    store: Arc<dyn VectorStore>,
+    splitter: Arc<dyn TextSplitter>,
+    parents: RwLock<HashMap<String, IndexedParent>>,
+    options: SearchOptions,
+    /// Number of chunks to fetch before collapsing them to parents.
+    fetch_k: usize,
+}
+
+impl ParentDocumentRetriever {
+    pub fn new(
+        embedder: Arc<dyn Embedding>,
+        store: Arc<dyn VectorStore>,
+        splitter: Arc<dyn TextSplitter>,
+        options: SearchOptions,
+    ) -> Self {
+        Self {
+            embedder,
+            store,
+            splitter,
+            parents: RwLock::new(HashMap::new()),
+            fetch_k: 4 * options.k,
+            options,
+        }
+    }
+
+    /// Fetch `fetch_k` chunks per query. A pool smaller than `options.k` is
+    /// raised to `options.k`.
+    pub fn with_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.fetch_k = fetch_k;
+        self
+    }
+
+    /// Split, embed and index `docs`, replacing the chunks of a parent with
+    /// the same id. Returns the parent ids.
+    pub async fn add_documents(&self, docs: Vec<Document>) -> Result<Vec<String>> {
+        let mut chunks = Vec::new();
+        let mut chunk_ids = Vec::with_capacity(docs.len());
+        for doc in &docs {
+            let mut ids = Vec::new();
+            for mut chunk in self.splitter.split_document(doc) {
+                chunk.metadata.insert(PARENT_ID_KEY.into(), doc.id.clone().into());
+                ids.push(chunk.id.clone());
+                chunks.push(chunk);
+            }
+            chunk_ids.push(ids);
+        }
+        let texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+        let embeddings = self.embedder.embed_batch(&texts).await?;
+
+        let mut parents = self.parents.write().await;
+        // Chunks of a re-added parent that the new split does not overwrite
+        let new_ids: HashSet<&str> = chunks.iter().map(|c| c.id.as_str()).collect();
+        let stale: Vec<String> = docs
+            .iter()
+            .filter_map(|doc| parents.get(&doc.id))
+            .flat_map(|parent| &parent.chunk_ids)
+            .filter(|id| !new_ids.contains(id.as_str()))
+            .cloned()
+            .collect();
+        if !stale.is_empty() {
+            self.store.delete(&stale).await?;
+        }
+        self.store.upsert(chunks.into_iter().zip(embeddings).collect()).await?;
+
+        Ok(docs
+            .into_iter()
+            .zip(chunk_ids)
+            .map(|(document, chunk_ids)| {
+                let id = document.id.clone();
+                parents.insert(id.clone(), IndexedParent { document, chunk_ids });
+                id
+            })
+            .collect())
+    }
+}
+
+/// A parent document and the ids of its chunks in the store.
+struct IndexedParent {
+    document: Document,
+    chunk_ids: Vec<String>,
+}
+
+#[async_trait]
+impl Runnable for ParentDocumentRetriever {
+    type Input = Value;
+    type Output = Value;
+
+    async fn invoke(&self, input: Value, _config: &RunnableConfig) -> Result<Value> {
+        let query = input
+            .as_str()
+            .ok_or_else(|| AyasError::Other("Retriever input must be a JSON string".into()))?;
+
+        let embedding = self.embedder.embed(query).await?;
+        let options = SearchOptions {
+            k: self.fetch_k.max(self.options.k),
+            ..self.options.clone()
+        };
+        let hits = self.store.similarity_search(&embedding, options).await?;
+
+        // Hits are sorted by score, so a parent's first hit is its best.
+        let parents = self.parents.read().await;
+        let mut seen = HashSet::new();
+        let results: Vec<SearchResult> = hits
+            .into_iter()
+            .filter_map(|hit| {
+                let parent = hit
+                    .document
+                    .metadata
+                    .get(PARENT_ID_KEY)
+                    .and_then(|id| id.as_str())
+                    .and_then(|id| parents.get(id));
+                let document = parent.map_or(hit.document, |p| p.document.clone());
+                seen.insert(document.id.clone()).then_some(SearchResult {
+                    document,
+                    score: hit.score,
+                })
+            })
+            .take(self.options.k)
+            .collect();
+
+        Ok(results_to_json(&results))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -269,12 +475,30 @@ fn results_to_json(results: &[SearchResult]) -> Value {
     Value::Array(output)
 }
 
+/// A result as [`results_to_json`] writes it.
+#[derive(Deserialize)]
+struct ResultRow {
+    #[serde(flatten)]
+    document: Document,
+    score: f32,
+}
+
+fn results_from_json(results: Value) -> Result<Vec<SearchResult>> {
+    let rows: Vec<ResultRow> = serde_json::from_value(results)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| SearchResult {
+            document: row.document,
+            score: row.score,
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::memory::InMemoryVectorStore;
-    use crate::types::{Document, EmbeddingVector};
-    use std::collections::HashMap;
+    use crate::splitter::RecursiveCharacterSplitter;
 
     /// A mock embedder that returns a fixed vector based on input hash.
     struct MockEmbedder {
@@ -344,6 +568,56 @@ mod tests {
         assert_eq!(arr[0]["content"], "hello world");
     }
 
+    #[tokio::test]
+    async fn format_docs_joins_contents() {
+        let result = |content: &str| SearchResult {
+            document: Document {
+                id: content.into(),
+                content: content.into(),
+                metadata: HashMap::new(),
+            },
+            score: 1.0,
+        };
+        let context = format_docs()
+            .invoke(vec![result("first"), result("second")], &RunnableConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(context, "first\n\nsecond");
+    }
+
+    #[tokio::test]
+    async fn retriever_runnable_wraps_a_retriever() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let embedder = Arc::new(MockEmbedder::new(3));
+        let doc = |id: &str, content: &str, embedding: Vec<f32>| {
+            let metadata = HashMap::from([("source".to_string(), Value::from(id))]);
+            let doc = Document {
+                id: id.into(),
+                content: content.into(),
+                metadata,
+            };
+            (doc, EmbeddingVector::new(embedding))
+        };
+        store
+            .add_documents(vec![
+                doc("d1", "hello", vec![104.0, 1.0, 0.0]),
+                doc("d2", "zzz", vec![0.0, 0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+
+        let retriever = RetrieverRunnable::new(ThresholdRetriever::new(embedder, store, 0.5, 10));
+        let results = retriever
+            .invoke("hello".into(), &RunnableConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, "d1");
+        assert_eq!(results[0].document.metadata["source"], "d1");
+        assert!(results[0].score > 0.5);
+    }
+
     #[tokio::test]
     async fn retriever_invoke_non_string_input_errors() {
         let store = Arc::new(InMemoryVectorStore::new());
@@ -542,4 +816,130 @@ mod tests {
         let results = mmr_select(&query, &candidates, &embeddings, 10, 0.5);
         assert_eq!(results.len(), 2);
     }
+
+    /// Embeds each text as its entry in a fixed table.
+    struct TableEmbedder(HashMap<&'static str, Vec<f32>>);
+
+    #[async_trait]
+    impl Embedding for TableEmbedder {
+        async fn embed(&self, text: &str) -> Result<EmbeddingVector> {
+            Ok(EmbeddingVector::new(self.0[text].clone()))
+        }
+
+        fn dimension(&self) -> usize {
+            3
+        }
+    }
+
+    #[tokio::test]
+    async fn mmr_retriever_diversifies_over_fetch_k_pool() {
+        let embedder = Arc::new(TableEmbedder(HashMap::from([
+            ("apple", vec![1.0, 0.0, 0.0]),
+            ("apple pie", vec![1.0, 0.0, 0.0]),
+            ("apple tart", vec![0.99, 0.01, 0.0]),
+            ("banana", vec![0.6, 0.8, 0.0]),
+        ])));
+        let store = Arc::new(InMemoryVectorStore::new());
+        for (id, content) in [("a", "apple pie"), ("b", "apple tart"), ("c", "banana")] {
+            let doc = Document {
+                id: id.into(),
+                content: content.into(),
+                metadata: HashMap::new(),
+            };
+            let embedding = embedder.embed(content).await.unwrap();
+            store.add_documents(vec![(doc, embedding)]).await.unwrap();
+        }
+        let ids = |result: Value| {
+            let mut ids: Vec<String> = result
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        };
+        let config = RunnableConfig::default();
+        let query = || Value::String("apple".into());
+
+        let relevant =
+            MaxMarginalRelevanceRetriever::new(embedder.clone(), store.clone(), 2, 8, 1.0);
+        assert_eq!(ids(relevant.invoke(query(), &config).await.unwrap()), vec!["a", "b"]);
+
+        let diverse =
+            MaxMarginalRelevanceRetriever::new(embedder.clone(), store.clone(), 2, 8, 0.3);
+        assert_eq!(ids(diverse.invoke(query(), &config).await.unwrap()), vec!["a", "c"]);
+
+        // A pool of 2 leaves nothing to diversify with.
+        let narrow = diverse.with_fetch_k(2);
+        assert_eq!(ids(narrow.invoke(query(), &config).await.unwrap()), vec!["a", "b"]);
+    }
+
+    // ---- ParentDocumentRetriever tests ----
+
+    #[tokio::test]
+    async fn parent_retriever_collapses_chunks_to_parent() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let embedder = Arc::new(MockEmbedder::new(3));
+        let splitter = Arc::new(RecursiveCharacterSplitter::new(10, 0));
+        let options = SearchOptions {
+            k: 2,
+            score_threshold: None,
+        };
+        let retriever = ParentDocumentRetriever::new(embedder, store.clone(), splitter, options);
+
+        let parent = |id: &str, content: &str| Document {
+            id: id.into(),
+            content: content.into(),
+            metadata: HashMap::new(),
+        };
+        retriever
+            .add_documents(vec![parent("p1", "apple one apple two"), parent("p2", "zebra")])
+            .await
+            .unwrap();
+
+        // Both "apple" chunks embed like the query; they must yield one "p1".
+        let chunk = store.get("p1#1").await.unwrap().unwrap();
+        assert_eq!(chunk.content, "apple two");
+        assert_eq!(chunk.metadata[PARENT_ID_KEY], "p1");
+
+        let result = retriever
+            .invoke(Value::String("apricot".into()), &RunnableConfig::default())
+            .await
+            .unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0]["id"], "p1");
+        assert_eq!(arr[0]["content"], "apple one apple two");
+        assert_eq!(arr[1]["id"], "p2");
+    }
+
+    #[tokio::test]
+    async fn parent_retriever_readd_drops_stale_chunks() {
+        let store = Arc::new(InMemoryVectorStore::new());
+        let embedder = Arc::new(MockEmbedder::new(3));
+        let splitter = Arc::new(RecursiveCharacterSplitter::new(10, 0));
+        let options = SearchOptions::default();
+        let retriever = ParentDocumentRetriever::new(embedder, store.clone(), splitter, options);
+        let parent = |content: &str| Document {
+            id: "p1".into(),
+            content: content.into(),
+            metadata: HashMap::new(),
+        };
+
+        retriever.add_documents(vec![parent("apple one apple two")]).await.unwrap();
+        assert!(store.get("p1#1").await.unwrap().is_some());
+
+        retriever.add_documents(vec![parent("kiwi")]).await.unwrap();
+        assert_eq!(store.get("p1#0").await.unwrap().unwrap().content, "kiwi");
+        assert!(store.get("p1#1").await.unwrap().is_none());
+
+        let result = retriever
+            .invoke(Value::String("apricot".into()), &RunnableConfig::default())
+            .await
+            .unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 1);
+        assert_eq!(arr[0]["content"], "kiwi");
+    }
 }
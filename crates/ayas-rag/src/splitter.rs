@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::types::Document;
+
+/// A piece of a larger text, located by byte offsets into it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub content: String,
+    /// Byte offset of `content` in the source text.
+    pub start: usize,
+    /// Byte offset just past `content` in the source text.
+    pub end: usize,
+    /// Splitter-specific context, copied into the chunk's [`Document`].
+    pub metadata: HashMap<String, Value>,
+}
+
+/// Trait for splitting text into chunks small enough to embed.
+pub trait TextSplitter: Send + Sync {
+    /// Split `text` into chunks, in order.
+    fn split_text(&self, text: &str) -> Vec<TextChunk>;
+
+    /// Split a document into one document per chunk.
+    ///
+    /// Chunk `i` gets the id `{doc.id}#{i}` and the document's metadata plus
+    /// `chunk_index`, `start_offset` and `end_offset` (byte offsets into the
+    /// document's content) and any metadata the splitter adds.
+    fn split_document(&self, doc: &Document) -> Vec<Document> {
+        self.split_text(&doc.content)
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut metadata = doc.metadata.clone();
+                metadata.extend(chunk.metadata);
+                metadata.insert("chunk_index".into(), index.into());
+                metadata.insert("start_offset".into(), chunk.start.into());
+                metadata.insert("end_offset".into(), chunk.end.into());
+                Document {
+                    id: format!("{}#{index}", doc.id),
+                    content: chunk.content,
+                    metadata,
+                }
+            })
+            .collect()
+    }
+
+    /// Split each document in turn.
+    fn split_documents(&self, docs: &[Document]) -> Vec<Document> {
+        docs.iter().flat_map(|doc| self.split_document(doc)).collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RecursiveCharacterSplitter
+// ---------------------------------------------------------------------------
+
+/// Splits on the first separator that occurs in the text, recursing with the
+/// remaining separators into pieces that are still too long, then merges
+/// the pieces back into chunks of at most `chunk_size` characters.
+///
+/// Consecutive chunks share up to `chunk_overlap` characters. Chunks are
+/// trimmed of surrounding whitespace. A piece no separator can break is
+/// kept whole, even if longer than `chunk_size`; the default separators end
+/// with `""`, which splits between characters, so this only happens with
+/// custom separators.
+#[derive(Debug, Clone)]
+pub struct RecursiveCharacterSplitter {
+    pub chunk_size: usize,
+    pub chunk_overlap: usize,
+    /// Tried in order; the default is paragraphs, lines, words, characters.
+    pub separators: Vec<String>,
+}
+
+impl RecursiveCharacterSplitter {
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            chunk_size,
+            chunk_overlap,
+            separators: ["\n\n", "\n", " ", ""].map(String::from).to_vec(),
+        }
+    }
+
+    pub fn with_separators(mut self, separators: Vec<String>) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Break `text[start..end]` into contiguous pieces of at most
+    /// `chunk_size` characters where the separators allow. Separators stay
+    /// at the end of the piece they terminate.
+    fn pieces(
+        &self,
+        text: &str,
+        (start, end): (usize, usize),
+        separators: &[String],
+        out: &mut Vec<(usize, usize)>,
+    ) {
+        let span = &text[start..end];
+        if span.chars().count() <= self.chunk_size {
+            out.push((start, end));
+            return;
+        }
+        let Some(pos) = separators.iter().position(|sep| span.contains(sep.as_str())) else {
+            out.push((start, end));
+            return;
+        };
+        let (separator, rest) = (&separators[pos], &separators[pos + 1..]);
+
+        if separator.is_empty() {
+            out.extend(span.char_indices().map(|(i, c)| (start + i, start + i + c.len_utf8())));
+            return;
+        }
+        let mut piece_start = start;
+        for (i, sep) in span.match_indices(separator.as_str()) {
+            let piece_end = start + i + sep.len();
+            self.pieces(text, (piece_start, piece_end), rest, out);
+            piece_start = piece_end;
+        }
+        if piece_start < end {
+            self.pieces(text, (piece_start, end), rest, out);
+        }
+    }
+}
+
+impl TextSplitter for RecursiveCharacterSplitter {
+    fn split_text(&self, text: &str) -> Vec<TextChunk> {
+        let mut pieces = Vec::new();
+        self.pieces(text, (0, text.len()), &self.separators, &mut pieces);
+        let lens: Vec<usize> = pieces.iter().map(|&(s, e)| text[s..e].chars().count()).collect();
+
+        let mut chunks = Vec::new();
+        let mut first = 0;
+        while first < pieces.len() {
+            // Grow the chunk while it fits.
+            let mut last = first;
+            let mut size = lens[first];
+            while last + 1 < pieces.len() && size + lens[last + 1] <= self.chunk_size {
+                last += 1;
+                size += lens[last];
+            }
+            if let Some(chunk) = trimmed_chunk(text, pieces[first].0, pieces[last].1) {
+                chunks.push(chunk);
+            }
+            if last + 1 == pieces.len() {
+                break;
+            }
+
+            // Start the next chunk with the trailing pieces that fit in the
+            // overlap, always moving forward by at least one piece.
+            let mut next = last + 1;
+            let mut overlap = 0;
+            while next - 1 > first && overlap + lens[next - 1] <= self.chunk_overlap {
+                next -= 1;
+                overlap += lens[next];
+            }
+            first = next;
+        }
+        chunks
+    }
+}
+
+/// `text[start..end]` without surrounding whitespace, or `None` if nothing
+/// else is left.
+fn trimmed_chunk(text: &str, start: usize, end: usize) -> Option<TextChunk> {
+    let span = &text[start..end];
+    let trimmed = span.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let start = start + (span.len() - span.trim_start().len());
+    Some(TextChunk {
+        content: trimmed.to_string(),
+        start,
+        end: start + trimmed.len(),
+        metadata: HashMap::new(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// MarkdownSplitter
+// ---------------------------------------------------------------------------
+
+/// Splits Markdown into sections at ATX headings (`#` to `######`), ignoring
+/// `#` lines inside fenced code blocks.
+///
+/// Each section keeps its heading line, and its chunk's `headers` metadata
+/// lists the headings it is nested under, outermost first, ending with its
+/// own. Text before the first heading has no headers. With
+/// [`Self::with_chunking`], long sections are split further and each piece
+/// carries the section's headers.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownSplitter {
+    chunking: Option<RecursiveCharacterSplitter>,
+}
+
+impl MarkdownSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split sections with `splitter` after splitting on headings.
+    pub fn with_chunking(mut self, splitter: RecursiveCharacterSplitter) -> Self {
+        self.chunking = Some(splitter);
+        self
+    }
+
+    /// Sections as `(start, end, headers)`, covering the whole text.
+    fn sections(text: &str) -> Vec<(usize, usize, Vec<String>)> {
+        let mut sections = Vec::new();
+        // (level, heading text) of the enclosing headings.
+        let mut stack: Vec<(usize, String)> = Vec::new();
+        let mut section_start = 0;
+        let mut in_fence = false;
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            let Some((level, title)) = heading(trimmed) else {
+                continue;
+            };
+            if line_start > section_start {
+                let headers = stack.iter().map(|(_, h)| h.clone()).collect();
+                sections.push((section_start, line_start, headers));
+            }
+            while stack.last().is_some_and(|(l, _)| *l >= level) {
+                stack.pop();
+            }
+            stack.push((level, title));
+            section_start = line_start;
+        }
+        if section_start < text.len() {
+            let headers = stack.iter().map(|(_, h)| h.clone()).collect();
+            sections.push((section_start, text.len(), headers));
+        }
+        sections
+    }
+}
+
+/// Level and text of an ATX heading line.
+fn heading(line: &str) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t', '\n'])) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim_end().to_string()))
+}
+
+impl TextSplitter for MarkdownSplitter {
+    fn split_text(&self, text: &str) -> Vec<TextChunk> {
+        let mut chunks = Vec::new();
+        for (start, end, headers) in Self::sections(text) {
+            let pieces: Vec<TextChunk> = match &self.chunking {
+                Some(splitter) => splitter
+                    .split_text(&text[start..end])
+                    .into_iter()
+                    .map(|c| TextChunk {
+                        start: start + c.start,
+                        end: start + c.end,
+                        ..c
+                    })
+                    .collect(),
+                None => trimmed_chunk(text, start, end).into_iter().collect(),
+            };
+            for mut chunk in pieces {
+                chunk.metadata.insert("headers".into(), headers.clone().into());
+                chunks.push(chunk);
+            }
+        }
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(chunks: &[TextChunk]) -> Vec<&str> {
+        chunks.iter().map(|c| c.content.as_str()).collect()
+    }
+
+    #[test]
+    fn recursive_prefers_paragraphs_then_words() {
+        let text = "one two three\n\nfour five six seven eight";
+        let splitter = RecursiveCharacterSplitter::new(15, 0);
+        let chunks = splitter.split_text(text);
+        assert_eq!(contents(&chunks), vec!["one two three", "four five six", "seven eight"]);
+        for chunk in &chunks {
+            assert!(chunk.content.chars().count() <= 15);
+            assert_eq!(&text[chunk.start..chunk.end], chunk.content);
+        }
+    }
+
+    #[test]
+    fn recursive_overlaps_consecutive_chunks() {
+        let text = "a b c d e f g h";
+        let chunks = RecursiveCharacterSplitter::new(8, 4).split_text(text);
+        assert_eq!(contents(&chunks), vec!["a b c d", "c d e f", "e f g h"]);
+    }
+
+    #[test]
+    fn recursive_falls_back_to_characters() {
+        let chunks = RecursiveCharacterSplitter::new(4, 0).split_text("abcdefghij");
+        assert_eq!(contents(&chunks), vec!["abcd", "efgh", "ij"]);
+
+        let custom = RecursiveCharacterSplitter::new(4, 0).with_separators(vec![" ".into()]);
+        assert_eq!(contents(&custom.split_text("abcdefghij")), vec!["abcdefghij"]);
+    }
+
+    #[test]
+    fn recursive_offsets_are_bytes() {
+        let text = "héllo wörld ünïcode";
+        for chunk in RecursiveCharacterSplitter::new(6, 0).split_text(text) {
+            assert_eq!(&text[chunk.start..chunk.end], chunk.content);
+        }
+    }
+
+    #[test]
+    fn markdown_records_heading_path() {
+        let text = concat!(
+            "intro\n# Guide\ntext\n",
+            "## Setup\n```sh\n# not a heading\n```\n",
+            "## Usage\nrun it\n",
+        );
+        let chunks = MarkdownSplitter::new().split_text(text);
+        assert_eq!(
+            contents(&chunks),
+            vec![
+                "intro",
+                "# Guide\ntext",
+                "## Setup\n```sh\n# not a heading\n```",
+                "## Usage\nrun it",
+            ]
+        );
+        let headers: Vec<&Value> = chunks.iter().map(|c| &c.metadata["headers"]).collect();
+        assert_eq!(headers[0], &serde_json::json!([]));
+        assert_eq!(headers[2], &serde_json::json!(["Guide", "Setup"]));
+        assert_eq!(headers[3], &serde_json::json!(["Guide", "Usage"]));
+    }
+
+    #[test]
+    fn markdown_chunks_long_sections() {
+        let text = "# A\nalpha beta gamma delta\n# B\nshort";
+        let splitter =
+            MarkdownSplitter::new().with_chunking(RecursiveCharacterSplitter::new(12, 0));
+        let chunks = splitter.split_text(text);
+        assert_eq!(contents(&chunks), vec!["# A\nalpha", "beta gamma", "delta", "# B\nshort"]);
+        for chunk in &chunks {
+            assert_eq!(&text[chunk.start..chunk.end], chunk.content);
+        }
+        assert_eq!(chunks[2].metadata["headers"], serde_json::json!(["A"]));
+    }
+
+    #[test]
+    fn split_document_adds_chunk_metadata() {
+        let doc = Document {
+            id: "readme".into(),
+            content: "first part\n\nsecond part".into(),
+            metadata: HashMap::from([("source".into(), Value::from("README.md"))]),
+        };
+        let chunks = RecursiveCharacterSplitter::new(12, 0).split_document(&doc);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].id, "readme#1");
+        assert_eq!(chunks[1].content, "second part");
+        assert_eq!(chunks[1].metadata["source"], "README.md");
+        assert_eq!(chunks[1].metadata["chunk_index"], 1);
+        assert_eq!(chunks[1].metadata["start_offset"], 12);
+        assert_eq!(chunks[1].metadata["end_offset"], 23);
+    }
+}
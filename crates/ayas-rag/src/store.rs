@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, Result};
 
 use crate::types::{Document, EmbeddingVector, SearchOptions, SearchResult};
 
@@ -10,6 +10,17 @@ pub trait VectorStore: Send + Sync {
     /// Add documents with their embeddings. Returns the document IDs.
     async fn add_documents(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>>;
 
+    /// Insert documents, replacing any already stored under the same
+    /// `Document.id`. Returns the document IDs.
+    ///
+    /// The default deletes the ids, then adds the documents; stores that can
+    /// replace in place override it.
+    async fn upsert(&self, docs: Vec<(Document, EmbeddingVector)>) -> Result<Vec<String>> {
+        let ids: Vec<String> = docs.iter().map(|(doc, _)| doc.id.clone()).collect();
+        self.delete(&ids).await?;
+        self.add_documents(docs).await
+    }
+
     /// Search for similar documents.
     async fn similarity_search(
         &self,
@@ -23,3 +34,15 @@ pub trait VectorStore: Send + Sync {
     /// Get a document by ID.
     async fn get(&self, id: &str) -> Result<Option<Document>>;
 }
+
+/// Check that `vector` has the dimension a store was indexed with.
+pub fn check_dimension(expected: usize, vector: &EmbeddingVector) -> Result<()> {
+    if vector.dimension() == expected {
+        Ok(())
+    } else {
+        Err(AyasError::Validation(format!(
+            "Embedding dimension mismatch: store uses {expected}, vector has {}",
+            vector.dimension()
+        )))
+    }
+}
@@ -32,6 +32,21 @@ impl EmbeddingVector {
         &self.0
     }
 
+    /// Keep the first `dim` components and rescale to unit length.
+    ///
+    /// Suits Matryoshka-trained embeddings (such as OpenAI's
+    /// `text-embedding-3`), whose leading components carry the most signal.
+    /// A vector already `dim` long or shorter is only renormalized; a zero
+    /// vector stays zero.
+    pub fn truncate(mut self, dim: usize) -> Self {
+        self.0.truncate(dim);
+        let norm = self.0.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            self.0.iter_mut().for_each(|x| *x /= norm);
+        }
+        self
+    }
+
     /// Cosine similarity with another vector.
     pub fn cosine_similarity(&self, other: &EmbeddingVector) -> f32 {
         let dot: f32 = self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum();
@@ -104,6 +119,18 @@ mod tests {
         assert_eq!(zero.cosine_similarity(&a), 0.0);
     }
 
+    #[test]
+    fn truncate_renormalizes() {
+        let v = EmbeddingVector::new(vec![3.0, 4.0, 12.0]).truncate(2);
+        assert_eq!(v.dimension(), 2);
+        assert!((v.0[0] - 0.6).abs() < 1e-6);
+        assert!((v.0[1] - 0.8).abs() < 1e-6);
+
+        let zero = EmbeddingVector::new(vec![0.0, 0.0, 1.0]).truncate(2);
+        assert_eq!(zero.0, vec![0.0, 0.0]);
+        assert_eq!(EmbeddingVector::new(vec![2.0]).truncate(4).0, vec![1.0]);
+    }
+
     #[test]
     fn cosine_similarity_symmetric() {
         let a = EmbeddingVector::new(vec![1.0, 2.0, 3.0]);
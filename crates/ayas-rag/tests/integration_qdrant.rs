@@ -0,0 +1,68 @@
+//! Integration tests for QdrantStore.
+//!
+//! Run with: `cargo test -p ayas-rag --test integration_qdrant -- --ignored`
+//!
+//! Requires QDRANT_URL env var to be set.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use ayas_rag::qdrant_store::QdrantStore;
+use ayas_rag::store::VectorStore;
+use ayas_rag::types::{Document, EmbeddingVector, SearchOptions};
+
+fn make_doc(content: &str) -> Document {
+    Document {
+        id: Uuid::new_v4().to_string(),
+        content: content.into(),
+        metadata: HashMap::new(),
+    }
+}
+
+async fn drop_collection(name: &str) {
+    let url = std::env::var("QDRANT_URL").expect("QDRANT_URL must be set");
+    let _ = reqwest::Client::new()
+        .delete(format!("{url}/collections/{name}"))
+        .send()
+        .await;
+}
+
+#[tokio::test]
+#[ignore]
+async fn collections_are_searched_independently() {
+    let name_a = format!("ayas-test-a-{}", Uuid::new_v4().as_simple());
+    let name_b = format!("ayas-test-b-{}", Uuid::new_v4().as_simple());
+    let store_a = QdrantStore::new(&name_a);
+    let store_b = store_a.with_collection(&name_b);
+
+    let doc_a = make_doc("tenant a");
+    let doc_b = make_doc("tenant b");
+    // The first write creates each collection.
+    store_a
+        .upsert(vec![(doc_a.clone(), EmbeddingVector::new(vec![1.0, 0.0, 0.0]))])
+        .await
+        .unwrap();
+    store_b
+        .upsert(vec![(doc_b.clone(), EmbeddingVector::new(vec![1.0, 0.0]))])
+        .await
+        .unwrap();
+
+    let query = SearchOptions::default();
+    let hits_a = store_a
+        .similarity_search(&EmbeddingVector::new(vec![1.0, 0.0, 0.0]), query.clone())
+        .await
+        .unwrap();
+    let hits_b = store_b
+        .similarity_search(&EmbeddingVector::new(vec![1.0, 0.0]), query)
+        .await
+        .unwrap();
+
+    drop_collection(&name_a).await;
+    drop_collection(&name_b).await;
+
+    assert_eq!(hits_a.len(), 1);
+    assert_eq!(hits_a[0].document.content, "tenant a");
+    assert_eq!(hits_b.len(), 1);
+    assert_eq!(hits_b[0].document.content, "tenant b");
+}
@@ -0,0 +1,130 @@
+//! Batch failure handling and rate-limit retries of `OpenAiEmbedding`
+//! against a local mock of `/v1/embeddings`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use axum::Router;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use serde_json::{Value, json};
+
+use ayas_core::error::{AyasError, ModelError};
+use ayas_rag::prelude::*;
+
+/// Inputs containing this are rejected with HTTP 400.
+const TOO_LONG: &str = "TOO LONG";
+
+/// Serve an embeddings endpoint that answers 429 to the first `rate_limited`
+/// calls and rejects any batch holding a [`TOO_LONG`] input, naming its
+/// position in `param` when `report_index` is set. Other inputs embed as
+/// `[len]`. Returns the base URL and the call counter.
+async fn mock_openai(rate_limited: u32, report_index: bool) -> (String, Arc<AtomicU32>) {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    let app = Router::new().route(
+        "/v1/embeddings",
+        post(move |axum::Json(body): axum::Json<Value>| {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < rate_limited {
+                    let body = json!({"error": {"message": "slow down"}}).to_string();
+                    return (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "0")], body)
+                        .into_response();
+                }
+                let input: Vec<String> = serde_json::from_value(body["input"].clone()).unwrap();
+                if let Some(i) = input.iter().position(|text| text.contains(TOO_LONG)) {
+                    let param = report_index.then(|| format!("input[{i}]"));
+                    let body = json!({"error": {"message": "too long", "param": param}});
+                    return (StatusCode::BAD_REQUEST, body.to_string()).into_response();
+                }
+                let data: Vec<Value> = input
+                    .iter()
+                    .enumerate()
+                    .map(|(i, text)| json!({"embedding": [text.len() as f32], "index": i}))
+                    .collect();
+                axum::Json(json!({ "data": data })).into_response()
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (format!("http://{addr}"), calls)
+}
+
+fn embedding(base_url: String) -> OpenAiEmbedding {
+    OpenAiEmbedding::with_api_key("key".into(), OpenAiEmbeddingModel::TextEmbedding3Small)
+        .unwrap()
+        .with_base_url(base_url)
+}
+
+const TEXTS: [&str; 4] = ["a", "bb", TOO_LONG, "dddd"];
+
+#[tokio::test]
+async fn fail_policy_rejects_the_whole_batch() {
+    let (base_url, _) = mock_openai(0, true).await;
+    let err = embedding(base_url).embed_batch(&TEXTS).await.unwrap_err();
+    assert!(matches!(err, AyasError::Model(ModelError::ApiRequest { .. })));
+}
+
+#[tokio::test]
+async fn skip_policy_drops_the_reported_input() {
+    let (base_url, calls) = mock_openai(0, true).await;
+    let embedding = embedding(base_url).with_failure_policy(BatchFailurePolicy::Skip);
+
+    let batch = embedding.embed_batch_partial(&TEXTS).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(batch.failures.len(), 1);
+    assert_eq!(batch.failures[0].index, 2);
+    let lens: Vec<Option<f32>> = batch.vectors.iter().map(|v| v.as_ref().map(|v| v.0[0])).collect();
+    assert_eq!(lens, vec![Some(1.0), Some(2.0), None, Some(4.0)]);
+
+    // The trait method still wants every vector
+    assert!(embedding.embed_batch(&TEXTS).await.is_err());
+}
+
+#[tokio::test]
+async fn skip_policy_without_index_fails_the_request() {
+    let (base_url, _) = mock_openai(0, false).await;
+    let embedding = embedding(base_url).with_failure_policy(BatchFailurePolicy::Skip);
+
+    let batch = embedding.embed_batch_partial(&TEXTS).await.unwrap();
+    let failed: Vec<usize> = batch.failures.iter().map(|f| f.index).collect();
+    assert_eq!(failed, vec![0, 1, 2, 3]);
+}
+
+#[tokio::test]
+async fn split_policy_narrows_down_to_the_failing_input() {
+    let (base_url, _) = mock_openai(0, false).await;
+    let embedding = embedding(base_url).with_failure_policy(BatchFailurePolicy::Split);
+
+    let batch = embedding.embed_batch_partial(&TEXTS).await.unwrap();
+    let failed: Vec<usize> = batch.failures.iter().map(|f| f.index).collect();
+    assert_eq!(failed, vec![2]);
+    assert_eq!(batch.vectors.iter().filter(|v| v.is_some()).count(), 3);
+}
+
+#[tokio::test]
+async fn rate_limited_requests_are_retried() {
+    let (base_url, calls) = mock_openai(2, true).await;
+    let vectors = embedding(base_url)
+        .with_max_auto_retries(2)
+        .embed_batch(&["a", "bb"])
+        .await
+        .unwrap();
+    assert_eq!(vectors.len(), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let (base_url, _) = mock_openai(1, true).await;
+    let err = embedding(base_url).embed("a").await.unwrap_err();
+    assert!(matches!(
+        err,
+        AyasError::Model(ModelError::RateLimited {
+            retry_after_secs: Some(0)
+        })
+    ));
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use ayas_chain::lambda::RunnableLambda;
+use ayas_chain::mock::MockChatModel;
+use ayas_chain::parallel::RunnableParallel;
+use ayas_chain::parser::StringOutputParser;
+use ayas_chain::prompt::PromptTemplate;
+use ayas_core::config::RunnableConfig;
+use ayas_core::error::Result;
+use ayas_core::runnable::{Runnable, RunnableExt};
+use ayas_rag::embedding::Embedding;
+use ayas_rag::memory::InMemoryVectorStore;
+use ayas_rag::retriever::{format_docs, Retriever, RetrieverRunnable};
+use ayas_rag::store::VectorStore;
+use ayas_rag::types::{Document, EmbeddingVector, SearchOptions};
+
+/// Embeds texts by topic keyword, so related texts share a vector.
+struct KeywordEmbedder;
+
+#[async_trait]
+impl Embedding for KeywordEmbedder {
+    async fn embed(&self, text: &str) -> Result<EmbeddingVector> {
+        let text = text.to_lowercase();
+        let has = |word: &str| if text.contains(word) { 1.0 } else { 0.0 };
+        Ok(EmbeddingVector::new(vec![has("rust"), has("python"), 0.1]))
+    }
+
+    fn dimension(&self) -> usize {
+        3
+    }
+}
+
+fn doc(id: &str, content: &str) -> Document {
+    Document {
+        id: id.into(),
+        content: content.into(),
+        metadata: HashMap::new(),
+    }
+}
+
+/// E2E test: retrieve -> format -> prompt -> model -> parser, with the
+/// question passed alongside the retrieved context.
+#[tokio::test]
+async fn retrieve_format_prompt_model_chain() {
+    let embedder = Arc::new(KeywordEmbedder);
+    let store = Arc::new(InMemoryVectorStore::new());
+    let docs = [
+        doc("rust", "Rust guarantees memory safety without a garbage collector."),
+        doc("python", "Python is dynamically typed."),
+    ];
+    for d in docs {
+        let embedding = embedder.embed(&d.content).await.unwrap();
+        store.add_documents(vec![(d, embedding)]).await.unwrap();
+    }
+
+    let options = SearchOptions {
+        k: 1,
+        score_threshold: None,
+    };
+    let retriever = RetrieverRunnable::new(Retriever::new(embedder, store, options));
+    let question = RunnableLambda::new(|q: String, _config| async move { Ok(q) });
+    let to_vars = RunnableLambda::new(|(context, question): (String, String), _config| async move {
+        Ok(HashMap::from([
+            ("context".to_string(), context),
+            ("question".to_string(), question),
+        ]))
+    });
+    let prompt = PromptTemplate::from_messages(vec![
+        ("system", "Answer from this context:\n{context}"),
+        ("user", "{question}"),
+    ]);
+    // Echo the prompt back so the test can see what reached the model.
+    let model = MockChatModel::with_handler(|messages| {
+        let prompt: Vec<&str> = messages.iter().map(|m| m.content()).collect();
+        MockChatModel::text_result(prompt.join(" | "))
+    });
+
+    let chain = RunnableParallel::new(retriever.pipe(format_docs()), question)
+        .pipe(to_vars)
+        .pipe(prompt)
+        .pipe(model)
+        .pipe(StringOutputParser);
+
+    let answer = chain
+        .invoke("Why use Rust?".to_string(), &RunnableConfig::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        answer,
+        "Answer from this context:\n\
+         Rust guarantees memory safety without a garbage collector. | Why use Rust?"
+    );
+}
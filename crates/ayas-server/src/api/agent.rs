@@ -1,49 +1,95 @@
 use std::sync::Arc;
 
+use axum::http::HeaderMap;
 use axum::{Json, Router, extract::State, routing::post};
 use axum::response::Sse;
 use axum::response::sse::Event;
-use futures::stream;
-use futures::Stream;
 
 use ayas_core::message::Message;
 use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::moderation::{Moderator, NoopModerator};
+use ayas_core::tool::{CoercingTool, Tool};
 use ayas_llm::factory::create_chat_model;
 use ayas_llm::provider::Provider;
 
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
-use crate::sse::{sse_done, sse_event};
+use crate::moderation::{last_user_text, moderate, moderator_from_env};
+use crate::sse::{ReplayBuffers, ReplayStream, sse_done, sse_event};
 use crate::tools::build_tools;
-use crate::types::{AgentInvokeRequest, AgentSseEvent};
+use crate::types::{AgentInvokeRequest, AgentSseEvent, FlaggedContent, ModerationStage};
 
 /// Factory function type for creating ChatModel instances (same as chat.rs).
-pub type AgentModelFactory =
-    Arc<dyn Fn(&Provider, String, String) -> Box<dyn ChatModel> + Send + Sync>;
+pub type AgentModelFactory = Arc<
+    dyn Fn(&Provider, String, String) -> ayas_core::error::Result<Box<dyn ChatModel>> + Send + Sync,
+>;
 
 /// Create the default factory that delegates to ayas_llm::factory.
 pub fn default_agent_factory() -> AgentModelFactory {
     Arc::new(|provider, api_key, model_id| create_chat_model(provider, api_key, model_id))
 }
 
+/// Shared state for the agent routes.
+#[derive(Clone)]
+pub struct AgentState {
+    pub factory: AgentModelFactory,
+    pub moderator: Arc<dyn Moderator>,
+    /// Recent events per stream, replayed to clients reconnecting with
+    /// `Last-Event-ID`.
+    pub replay: ReplayBuffers,
+}
+
 pub fn routes() -> Router {
-    routes_with_factory(default_agent_factory())
+    routes_with_moderator(default_agent_factory(), moderator_from_env())
 }
 
 pub fn routes_with_factory(factory: AgentModelFactory) -> Router {
+    routes_with_moderator(factory, Arc::new(NoopModerator))
+}
+
+pub fn routes_with_moderator(factory: AgentModelFactory, moderator: Arc<dyn Moderator>) -> Router {
     Router::new()
         .route("/agent/invoke", post(agent_invoke))
-        .with_state(factory)
+        .with_state(AgentState {
+            factory,
+            moderator,
+            replay: ReplayBuffers::new(),
+        })
+}
+
+fn flagged_event(flagged: FlaggedContent) -> Result<Event, std::convert::Infallible> {
+    sse_event(&AgentSseEvent::Flagged {
+        stage: flagged.stage,
+        categories: flagged.categories,
+    })
 }
 
 async fn agent_invoke(
-    State(factory): State<AgentModelFactory>,
+    State(state): State<AgentState>,
+    headers: HeaderMap,
     api_keys: ApiKeys,
     Json(req): Json<AgentInvokeRequest>,
-) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
+) -> Result<Sse<ReplayStream>, AppError> {
+    if let Some(resumed) = state.replay.resume(&headers)? {
+        return Ok(Sse::new(resumed));
+    }
+
     let api_key = api_keys.get_key_for(&req.provider)?;
-    let model = factory(&req.provider, api_key, req.model);
-    let tools = build_tools(&req.tools, Vec::new());
+
+    let input = last_user_text(&req.messages).unwrap_or_default();
+    if let Some(flagged) = moderate(&*state.moderator, ModerationStage::Input, &input).await? {
+        let events = vec![flagged_event(flagged), sse_done()];
+        return Ok(Sse::new(state.replay.record(events)));
+    }
+
+    let model = (state.factory)(&req.provider, api_key, req.model)?;
+    let mut tools = build_tools(&req.tools, Vec::new());
+    if req.coerce_arguments {
+        tools = tools
+            .into_iter()
+            .map(|tool| Box::new(CoercingTool::new(Arc::from(tool))) as Box<dyn Tool>)
+            .collect();
+    }
     let tool_defs: Vec<_> = tools.iter().map(|t| t.definition()).collect();
     let recursion_limit = req.recursion_limit.unwrap_or(10);
 
@@ -102,22 +148,23 @@ async fn agent_invoke(
                 }));
 
                 // Find and execute tool
-                let tool_result =
+                let (tool_result, tool_msg) =
                     if let Some(tool) = tools.iter().find(|t| t.definition().name == tc.name) {
                         match tool.call(tc.arguments.clone()).await {
-                            Ok(r) => r,
-                            Err(e) => format!("Tool error: {e}"),
+                            Ok(r) => (r.clone(), Message::tool(r, &tc.id)),
+                            Err(e) => (e.to_string(), Message::tool_failure(&e, &tc.id)),
                         }
                     } else {
-                        format!("Tool '{}' not found", tc.name)
+                        let error = format!("Tool '{}' not found", tc.name);
+                        (error.clone(), Message::tool_error(error, &tc.id))
                     };
 
                 events.push(sse_event(&AgentSseEvent::ToolResult {
                     tool_name: tc.name.clone(),
-                    result: tool_result.clone(),
+                    result: tool_result,
                 }));
 
-                messages.push(Message::tool(tool_result, &tc.id));
+                messages.push(tool_msg);
             }
 
             step += 1;
@@ -126,6 +173,20 @@ async fn agent_invoke(
             let content = result.message.content().to_string();
             messages.push(result.message);
 
+            match moderate(&*state.moderator, ModerationStage::Output, &content).await {
+                Ok(None) => {}
+                Ok(Some(flagged)) => {
+                    events.push(flagged_event(flagged));
+                    break;
+                }
+                Err(e) => {
+                    events.push(sse_event(&AgentSseEvent::Error {
+                        message: e.to_string(),
+                    }));
+                    break;
+                }
+            }
+
             events.push(sse_event(&AgentSseEvent::Message { content }));
             events.push(sse_event(&AgentSseEvent::Done {
                 total_steps: step + 1,
@@ -136,7 +197,7 @@ async fn agent_invoke(
 
     events.push(sse_done());
 
-    Ok(Sse::new(stream::iter(events)))
+    Ok(Sse::new(state.replay.record(events)))
 }
 
 #[cfg(test)]
@@ -174,10 +235,7 @@ mod tests {
         ) -> ayas_core::error::Result<ChatResult> {
             let mut responses = self.responses.lock().unwrap();
             if responses.is_empty() {
-                Ok(ChatResult {
-                    message: Message::ai("fallback"),
-                    usage: None,
-                })
+                Ok(ChatResult::new(Message::ai("fallback")))
             } else {
                 Ok(responses.remove(0))
             }
@@ -198,7 +256,7 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> ayas_core::error::Result<ChatResult> {
-            Err(ModelError::ApiRequest("Mock model error".into()).into())
+            Err(ModelError::api_request("Mock model error").into())
         }
 
         fn model_name(&self) -> &str {
@@ -218,17 +276,42 @@ mod tests {
     fn app_with_sequence(responses: Vec<ChatResult>) -> Router {
         let factory: AgentModelFactory = Arc::new(move |_provider, _key, _model| {
             let responses = responses.clone();
-            Box::new(SequenceMockModel::new(responses))
+            Ok(Box::new(SequenceMockModel::new(responses)))
         });
         Router::new().nest("/api", routes_with_factory(factory))
     }
 
     fn app_with_error() -> Router {
         let factory: AgentModelFactory =
-            Arc::new(|_provider, _key, _model| Box::new(ErrorMockModel));
+            Arc::new(|_provider, _key, _model| Ok(Box::new(ErrorMockModel)));
         Router::new().nest("/api", routes_with_factory(factory))
     }
 
+    /// Moderator that flags any text containing "forbidden".
+    struct KeywordModerator;
+
+    #[async_trait]
+    impl Moderator for KeywordModerator {
+        async fn check(
+            &self,
+            text: &str,
+        ) -> ayas_core::error::Result<ayas_core::moderation::ModerationResult> {
+            Ok(if text.contains("forbidden") {
+                ayas_core::moderation::ModerationResult::flagged(vec!["test".into()])
+            } else {
+                ayas_core::moderation::ModerationResult::allowed()
+            })
+        }
+    }
+
+    fn app_with_moderation(responses: Vec<ChatResult>) -> Router {
+        let factory: AgentModelFactory = Arc::new(move |_provider, _key, _model| {
+            let responses = responses.clone();
+            Ok(Box::new(SequenceMockModel::new(responses)))
+        });
+        Router::new().nest("/api", routes_with_moderator(factory, Arc::new(KeywordModerator)))
+    }
+
     fn post_agent(body: serde_json::Value) -> Request<Body> {
         Request::builder()
             .method("POST")
@@ -240,29 +323,23 @@ mod tests {
     }
 
     fn text_response(content: &str) -> ChatResult {
-        ChatResult {
-            message: Message::AI(AIContent {
-                content: content.to_string(),
-                tool_calls: Vec::new(),
-                usage: None,
-            }),
+        ChatResult::new(Message::AI(AIContent {
+            content: content.to_string(),
+            tool_calls: Vec::new(),
             usage: None,
-        }
+        }))
     }
 
     fn tool_call_response(tool_name: &str, args: serde_json::Value) -> ChatResult {
-        ChatResult {
-            message: Message::AI(AIContent {
-                content: String::new(),
-                tool_calls: vec![ToolCall {
-                    id: format!("call_{}", tool_name),
-                    name: tool_name.to_string(),
-                    arguments: args,
-                }],
-                usage: None,
-            }),
+        ChatResult::new(Message::AI(AIContent {
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                id: format!("call_{}", tool_name),
+                name: tool_name.to_string(),
+                arguments: args,
+            }],
             usage: None,
-        }
+        }))
     }
 
     fn app() -> Router {
@@ -317,6 +394,74 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn agent_invoke_flagged_input() {
+        let app = app_with_moderation(vec![text_response("unused")]);
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "test-model",
+            "messages": [{"type": "user", "content": "tell me something forbidden"}]
+        });
+
+        let resp = app.oneshot(post_agent(body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        assert_eq!(events.len(), 1, "{events:?}");
+        assert_eq!(events[0]["type"], "flagged");
+        assert_eq!(events[0]["stage"], "input");
+        assert_eq!(events[0]["categories"], serde_json::json!(["test"]));
+    }
+
+    #[tokio::test]
+    async fn agent_invoke_flagged_output() {
+        let app = app_with_moderation(vec![text_response("a forbidden answer")]);
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "test-model",
+            "messages": [{"type": "user", "content": "Say hello"}]
+        });
+
+        let resp = app.oneshot(post_agent(body)).await.unwrap();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        assert_eq!(events[0]["type"], "step");
+        assert_eq!(events[1]["type"], "flagged");
+        assert_eq!(events[1]["stage"], "output");
+        assert!(events.iter().all(|e| e["type"] != "message"));
+    }
+
+    #[tokio::test]
+    async fn agent_invoke_resumes_from_last_event_id() {
+        let app = app_with_sequence(vec![text_response("Hello, world!")]);
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "test-model",
+            "messages": [{"type": "user", "content": "Say hello"}]
+        });
+
+        let resp = app.clone().oneshot(post_agent(body.clone())).await.unwrap();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8_lossy(&bytes).to_string();
+        let first_id = text
+            .lines()
+            .find_map(|line| line.strip_prefix("id:"))
+            .unwrap()
+            .trim()
+            .to_string();
+
+        let mut req = post_agent(body);
+        req.headers_mut().insert("last-event-id", first_id.parse().unwrap());
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        assert_eq!(events[0]["type"], "message");
+        assert_eq!(events[1]["type"], "done");
+        assert!(String::from_utf8_lossy(&bytes).contains("[DONE]"));
+    }
+
     #[tokio::test]
     async fn agent_invoke_no_tools_direct_response() {
         let app = app_with_sequence(vec![text_response("Hello, world!")]);
@@ -433,6 +578,67 @@ mod tests {
         assert!(msg.contains("Mock model error"), "Error message: {}", msg);
     }
 
+    /// Mock ChatModel that records the conversation of its last call.
+    struct RecordingMockModel {
+        inner: SequenceMockModel,
+        seen: Arc<std::sync::Mutex<Vec<Message>>>,
+    }
+
+    #[async_trait]
+    impl ChatModel for RecordingMockModel {
+        async fn generate(
+            &self,
+            messages: &[Message],
+            options: &CallOptions,
+        ) -> ayas_core::error::Result<ChatResult> {
+            *self.seen.lock().unwrap() = messages.to_vec();
+            self.inner.generate(messages, options).await
+        }
+
+        fn model_name(&self) -> &str {
+            "recording-mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn agent_invoke_tool_error_is_not_prefixed_for_the_model() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let factory: AgentModelFactory = {
+            let seen = seen.clone();
+            Arc::new(move |_provider, _key, _model| {
+                Ok(Box::new(RecordingMockModel {
+                    inner: SequenceMockModel::new(vec![
+                        tool_call_response("calculator", serde_json::json!({"expression": "1/0"})),
+                        text_response("Cannot divide by zero"),
+                    ]),
+                    seen: seen.clone(),
+                }))
+            })
+        };
+        let app = Router::new().nest("/api", routes_with_factory(factory));
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "test-model",
+            "tools": ["calculator"],
+            "messages": [{"type": "user", "content": "What is 1/0?"}]
+        });
+
+        let resp = app.oneshot(post_agent(body)).await.unwrap();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        let tool_result = events.iter().find(|e| e["type"] == "tool_result").unwrap();
+        let result = tool_result["result"].as_str().unwrap();
+        assert!(result.starts_with("Tool error: ") && !result.contains("Tool error: Tool error"));
+
+        // The provider marks the result as an error; the text itself is bare
+        let seen = seen.lock().unwrap();
+        let Some(Message::Tool { content, is_error, .. }) = seen.last() else {
+            panic!("expected a tool result, got {seen:?}");
+        };
+        assert!(is_error);
+        assert!(!content.starts_with("Tool error"), "content: {content}");
+    }
+
     #[tokio::test]
     async fn agent_invoke_tool_not_found() {
         let app = app_with_sequence(vec![
@@ -4,45 +4,94 @@ use axum::{Json, Router, extract::State, routing::post};
 
 use ayas_core::message::Message;
 use ayas_core::model::{CallOptions, ChatModel};
+use ayas_core::moderation::{Moderator, NoopModerator};
 use ayas_llm::factory::create_chat_model;
 use ayas_llm::provider::Provider;
 
+use crate::conversation::{
+    ConversationStore, InMemoryConversationStore, conversation_store_from_env,
+};
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
-use crate::types::{ChatInvokeRequest, ChatInvokeResponse};
+use crate::moderation::{last_user_text, moderate, moderator_from_env};
+use crate::types::{ChatInvokeRequest, ChatInvokeResponse, FlaggedContent, ModerationStage};
 
 /// Factory function type for creating ChatModel instances.
-pub type ChatModelFactory =
-    Arc<dyn Fn(&Provider, String, String) -> Box<dyn ChatModel> + Send + Sync>;
+pub type ChatModelFactory = Arc<
+    dyn Fn(&Provider, String, String) -> ayas_core::error::Result<Box<dyn ChatModel>> + Send + Sync,
+>;
 
 /// Create the default factory that delegates to ayas_llm::factory.
 pub fn default_model_factory() -> ChatModelFactory {
     Arc::new(|provider, api_key, model_id| create_chat_model(provider, api_key, model_id))
 }
 
+/// Shared state for the chat routes.
+#[derive(Clone)]
+pub struct ChatState {
+    pub factory: ChatModelFactory,
+    pub moderator: Arc<dyn Moderator>,
+    pub conversations: Arc<dyn ConversationStore>,
+}
+
 pub fn routes() -> Router {
-    routes_with_factory(default_model_factory())
+    routes_with_state(ChatState {
+        factory: default_model_factory(),
+        moderator: moderator_from_env(),
+        conversations: conversation_store_from_env(),
+    })
 }
 
 pub fn routes_with_factory(factory: ChatModelFactory) -> Router {
+    routes_with_moderator(factory, Arc::new(NoopModerator))
+}
+
+pub fn routes_with_moderator(factory: ChatModelFactory, moderator: Arc<dyn Moderator>) -> Router {
+    routes_with_state(ChatState {
+        factory,
+        moderator,
+        conversations: Arc::new(InMemoryConversationStore::default()),
+    })
+}
+
+pub fn routes_with_state(state: ChatState) -> Router {
     Router::new()
         .route("/chat/invoke", post(chat_invoke))
-        .with_state(factory)
+        .with_state(state)
+}
+
+fn flagged_response(flagged: FlaggedContent) -> Json<ChatInvokeResponse> {
+    Json(ChatInvokeResponse {
+        content: String::new(),
+        tokens_in: 0,
+        tokens_out: 0,
+        flagged: Some(flagged),
+    })
 }
 
 async fn chat_invoke(
-    State(factory): State<ChatModelFactory>,
+    State(state): State<ChatState>,
     api_keys: ApiKeys,
     Json(req): Json<ChatInvokeRequest>,
 ) -> Result<Json<ChatInvokeResponse>, AppError> {
     let api_key = api_keys.get_key_for(&req.provider)?;
-    let model = factory(&req.provider, api_key, req.model);
 
-    // Build messages, prepending system prompt if provided
+    let input = last_user_text(&req.messages).unwrap_or_default();
+    if let Some(flagged) = moderate(&*state.moderator, ModerationStage::Input, &input).await? {
+        return Ok(flagged_response(flagged));
+    }
+
+    let model = (state.factory)(&req.provider, api_key, req.model)?;
+
+    // Build messages: system prompt, then the session's history, then this turn
     let mut messages = Vec::new();
     if let Some(system_prompt) = &req.system_prompt {
         messages.push(Message::system(system_prompt.as_str()));
     }
+    if let Some(session_id) = &req.session_id {
+        messages.extend(state.conversations.get(session_id).await?);
+    }
+    let turn_start = messages.len();
     messages.extend(req.messages);
 
     let options = CallOptions {
@@ -52,6 +101,11 @@ async fn chat_invoke(
     };
 
     let result = model.generate(&messages, &options).await?;
+    let content = result.message.content().to_string();
+
+    if let Some(flagged) = moderate(&*state.moderator, ModerationStage::Output, &content).await? {
+        return Ok(flagged_response(flagged));
+    }
 
     let (tokens_in, tokens_out) = result
         .usage
@@ -59,10 +113,17 @@ async fn chat_invoke(
         .map(|u| (u.input_tokens, u.output_tokens))
         .unwrap_or((0, 0));
 
+    if let Some(session_id) = &req.session_id {
+        let mut turn = messages.split_off(turn_start);
+        turn.push(result.message);
+        state.conversations.append(session_id, &turn).await?;
+    }
+
     Ok(Json(ChatInvokeResponse {
-        content: result.message.content().to_string(),
+        content,
         tokens_in,
         tokens_out,
+        flagged: None,
     }))
 }
 
@@ -113,21 +174,24 @@ mod tests {
                 .unwrap()
                 .push(options.clone());
 
-            Ok(ayas_core::model::ChatResult {
-                message: Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: Some(UsageMetadata {
-                        input_tokens: 10,
-                        output_tokens: 5,
-                        total_tokens: 15,
-                    }),
+            let message = Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
+                usage: Some(UsageMetadata {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    total_tokens: 15,
+                    ..Default::default()
                 }),
+            });
+            Ok(ayas_core::model::ChatResult {
                 usage: Some(UsageMetadata {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 15,
+                    ..Default::default()
                 }),
+                ..ayas_core::model::ChatResult::new(message)
             })
         }
 
@@ -143,7 +207,7 @@ mod tests {
         let cc = call_count.clone();
         let factory: ChatModelFactory = Arc::new(move |_provider, _key, _model| {
             cc.fetch_add(1, Ordering::Relaxed);
-            Box::new(MockChatModel::new(resp.clone()))
+            Ok(Box::new(MockChatModel::new(resp.clone())))
         });
         (factory, call_count)
     }
@@ -154,6 +218,26 @@ mod tests {
         (router, count)
     }
 
+    /// Moderator that flags any text containing "forbidden".
+    struct KeywordModerator;
+
+    #[async_trait]
+    impl Moderator for KeywordModerator {
+        async fn check(&self, text: &str) -> Result<ayas_core::moderation::ModerationResult> {
+            Ok(if text.contains("forbidden") {
+                ayas_core::moderation::ModerationResult::flagged(vec!["test".into()])
+            } else {
+                ayas_core::moderation::ModerationResult::allowed()
+            })
+        }
+    }
+
+    fn app_with_moderator(response: &str) -> (Router, Arc<AtomicUsize>) {
+        let (factory, count) = mock_factory(response);
+        let routes = routes_with_moderator(factory, Arc::new(KeywordModerator));
+        (Router::new().nest("/api", routes), count)
+    }
+
     fn post_chat(body: serde_json::Value) -> Request<Body> {
         post_chat_with_headers(body, vec![("X-Gemini-Key", "test-key")])
     }
@@ -194,6 +278,90 @@ mod tests {
         assert_eq!(result.tokens_out, 5);
     }
 
+    /// Model that answers with the number of messages it was sent.
+    struct HistoryLenModel;
+
+    #[async_trait]
+    impl ChatModel for HistoryLenModel {
+        async fn generate(
+            &self,
+            messages: &[Message],
+            _options: &CallOptions,
+        ) -> Result<ayas_core::model::ChatResult> {
+            Ok(ayas_core::model::ChatResult::new(Message::ai(messages.len().to_string())))
+        }
+
+        fn model_name(&self) -> &str {
+            "history-len"
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_invoke_session_keeps_history() {
+        let factory: ChatModelFactory =
+            Arc::new(|_provider, _key, _model| Ok(Box::new(HistoryLenModel)));
+        let app = Router::new().nest("/api", routes_with_factory(factory));
+        let turn = |content: &str, session_id: Option<&str>| {
+            serde_json::json!({
+                "provider": "gemini",
+                "model": "gemini-2.0-flash",
+                "messages": [{"type": "user", "content": content}],
+                "session_id": session_id,
+            })
+        };
+
+        let mut replies = Vec::new();
+        for (content, session_id) in [("Hi", Some("s1")), ("And?", Some("s1")), ("Hi", None)] {
+            let resp = app.clone().oneshot(post_chat(turn(content, session_id))).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+            let result: ChatInvokeResponse = serde_json::from_slice(&bytes).unwrap();
+            replies.push(result.content);
+        }
+        // The second turn sees the first user message and reply; no session sees only itself
+        assert_eq!(replies, vec!["1", "3", "1"]);
+    }
+
+    #[tokio::test]
+    async fn chat_invoke_flagged_input_skips_model() {
+        let (app, count) = app_with_moderator("unused");
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "gemini-2.0-flash",
+            "messages": [{"type": "user", "content": "something forbidden"}]
+        });
+
+        let resp = app.oneshot(post_chat(body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let result: ChatInvokeResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(result.content, "");
+        let flagged = result.flagged.unwrap();
+        assert_eq!(flagged.stage, ModerationStage::Input);
+        assert_eq!(flagged.categories, vec!["test"]);
+    }
+
+    #[tokio::test]
+    async fn chat_invoke_flagged_output_is_withheld() {
+        let (app, count) = app_with_moderator("a forbidden answer");
+        let body = serde_json::json!({
+            "provider": "gemini",
+            "model": "gemini-2.0-flash",
+            "messages": [{"type": "user", "content": "Hi"}]
+        });
+
+        let resp = app.oneshot(post_chat(body)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let result: ChatInvokeResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(result.content, "");
+        assert_eq!(result.flagged.unwrap().stage, ModerationStage::Output);
+    }
+
     #[tokio::test]
     async fn chat_invoke_with_system_prompt() {
         // We verify the system prompt is prepended by checking the mock was called.
@@ -282,12 +450,13 @@ mod tests {
                 ) -> Result<ayas_core::model::ChatResult> {
                     self.opts.lock().unwrap().push(options.clone());
                     Ok(ayas_core::model::ChatResult {
-                        message: Message::ai("OK"),
                         usage: Some(UsageMetadata {
                             input_tokens: 1,
                             output_tokens: 1,
                             total_tokens: 2,
+                            ..Default::default()
                         }),
+                        ..ayas_core::model::ChatResult::new(Message::ai("OK"))
                     })
                 }
 
@@ -296,9 +465,9 @@ mod tests {
                 }
             }
 
-            Box::new(CapturingModel {
+            Ok(Box::new(CapturingModel {
                 opts: opts_inner,
-            })
+            }))
         });
 
         let app = Router::new().nest("/api", routes_with_factory(factory));
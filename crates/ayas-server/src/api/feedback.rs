@@ -1,11 +1,11 @@
 use axum::extract::State;
 use axum::{Json, Router, routing::post};
 
-use ayas_smith::types::FeedbackFilter;
+use ayas_smith::types::{FeedbackFilter, FeedbackStat, FeedbackStatsFilter, TimeBucket};
 
 use crate::error::AppError;
 use crate::run_types::{
-    Feedback, FeedbackQueryRequest, FeedbackRequest, FeedbackResponse,
+    Feedback, FeedbackQueryRequest, FeedbackRequest, FeedbackResponse, FeedbackStatsRequest,
 };
 use crate::state::AppState;
 
@@ -13,6 +13,7 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/feedback", post(submit_feedback))
         .route("/feedback/query", post(query_feedback))
+        .route("/feedback/stats", post(feedback_stats))
 }
 
 async fn submit_feedback(
@@ -71,6 +72,31 @@ async fn query_feedback(
     Ok(Json(feedbacks))
 }
 
+async fn feedback_stats(
+    State(state): State<AppState>,
+    Json(req): Json<FeedbackStatsRequest>,
+) -> Result<Json<Vec<FeedbackStat>>, AppError> {
+    let bucket = req
+        .bucket
+        .as_deref()
+        .map(str::parse::<TimeBucket>)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+    let filter = FeedbackStatsFilter {
+        project: req.project,
+        key: req.key,
+        created_after: req.created_after,
+        created_before: req.created_before,
+        bucket,
+    };
+    let stats = state
+        .smith_store
+        .feedback_stats(&filter)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(Json(stats))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,4 +278,57 @@ mod tests {
             assert!(result.is_empty());
         }
     }
+
+    #[tokio::test]
+    async fn feedback_stats_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        for score in [0.5, 1.0] {
+            let app = test_app(dir.path());
+            let body = serde_json::json!({
+                "run_id": Uuid::new_v4(),
+                "key": "correctness",
+                "score": score
+            });
+            let req = Request::builder()
+                .method("POST")
+                .uri("/api/feedback")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_string(&body).unwrap()))
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        let app = test_app(dir.path());
+        let body = serde_json::json!({ "bucket": "daily" });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/feedback/stats")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let stats: Vec<FeedbackStat> = serde_json::from_slice(&bytes).unwrap();
+        let total: i64 = stats.iter().map(|s| s.count).sum();
+        assert_eq!(total, 2);
+        assert!(stats.iter().all(|s| s.key == "correctness" && s.bucket.is_some()));
+    }
+
+    #[tokio::test]
+    async fn feedback_stats_rejects_unknown_bucket() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = test_app(dir.path());
+        let body = serde_json::json!({ "bucket": "weekly" });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/feedback/stats")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&body).unwrap()))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -9,20 +9,28 @@ use futures::stream;
 use serde::Serialize;
 
 use ayas_core::config::RunnableConfig;
+use ayas_core::error::AyasError;
 use ayas_deep_research::gemini::GeminiInteractionsClient;
 use ayas_graph::compiled::StepInfo;
 use ayas_graph::stream::StreamEvent;
 use ayas_llm::factory::create_chat_model;
+use ayas_llm::provider::Provider;
+use ayas_rag::embedding::Embedding;
+use ayas_rag::gemini_embedding::GeminiEmbedding;
+use ayas_rag::qdrant_store::QdrantStore;
+use ayas_rag::store::VectorStore;
 
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
 use crate::graph_convert::{
-    GraphBuildContext, GraphModelFactory, GraphResearchFactory, GraphToolsFactory,
-    convert_to_state_graph_with_context, validate_graph,
+    GraphBuildContext, GraphModelFactory, GraphResearchFactory, GraphRetrievalFactory,
+    GraphToolsFactory, RetrievalBackend, convert_to_state_graph_with_context, validate_graph,
 };
 use crate::graph_gen;
-use crate::sse::{sse_done, sse_event};
-use crate::tracing_middleware::{TracingContext, is_tracing_requested};
+use crate::sse::{spawn_until_disconnected, sse_done, sse_event};
+use crate::tracing_middleware::{
+    TracingContext, is_tracing_requested, trace_parent, trace_sample_override,
+};
 use crate::types::{
     GraphChannelDto, GraphEdgeDto, GraphExecuteRequest, GraphGenerateRequest,
     GraphGenerateResponse, GraphNodeDto, GraphStreamRequest, GraphValidateRequest,
@@ -51,6 +59,24 @@ pub fn default_tools_factory() -> GraphToolsFactory {
     })
 }
 
+/// Create the default retrieval factory: the store name is a Qdrant collection
+/// whose vectors were built with Gemini embeddings.
+pub fn default_retrieval_factory() -> GraphRetrievalFactory {
+    Arc::new(qdrant_retrieval_backend)
+}
+
+fn qdrant_retrieval_backend(
+    store_name: &str,
+    api_keys: &ApiKeys,
+) -> Result<RetrievalBackend, AyasError> {
+    let api_key = api_keys
+        .get_key_for(&Provider::Gemini)
+        .map_err(|e| AyasError::Other(format!("API key error: {e}")))?;
+    let embedder: Arc<dyn Embedding> = Arc::new(GeminiEmbedding::with_api_key(api_key)?);
+    let store: Arc<dyn VectorStore> = Arc::new(QdrantStore::new(store_name));
+    Ok((embedder, store))
+}
+
 pub fn routes() -> Router {
     routes_with_factory(default_graph_factory())
 }
@@ -102,6 +128,40 @@ fn build_runnable_config(recursion_limit: Option<usize>) -> RunnableConfig {
     config
 }
 
+/// Propagate an `x-trace-sample` override to every traced run in the graph.
+fn apply_trace_sample_override(config: &mut RunnableConfig, headers: &axum::http::HeaderMap) {
+    if let Some(sampled) = trace_sample_override(headers) {
+        ayas_smith::context::set_trace_sampled(config, sampled);
+    }
+}
+
+/// Join the trace named by an `x-ayas-trace-parent` header, if any: runs
+/// traced inside the graph nest under the given parent run.
+fn apply_trace_parent(config: &mut RunnableConfig, headers: &axum::http::HeaderMap) {
+    if let Some((trace_id, parent_run_id)) = trace_parent(headers) {
+        ayas_smith::context::set_trace_parent(config, trace_id, parent_run_id);
+    }
+}
+
+/// Tracing for one graph request: globally enabled via env, or requested
+/// per request, unless the request forces `never`. Recorded runs join the
+/// request's trace parent.
+fn request_tracing_context(headers: &axum::http::HeaderMap) -> Option<TracingContext> {
+    TracingContext::from_env()
+        .or_else(|| {
+            if is_tracing_requested(headers) {
+                Some(TracingContext::from_env_config())
+            } else {
+                None
+            }
+        })
+        .filter(|_| trace_sample_override(headers) != Some(false))
+        .map(|ctx| match trace_parent(headers) {
+            Some((trace_id, parent_run_id)) => ctx.with_trace_parent(trace_id, parent_run_id),
+            None => ctx,
+        })
+}
+
 async fn graph_execute(
     State(factory): State<GraphModelFactory>,
     api_keys: ApiKeys,
@@ -109,13 +169,7 @@ async fn graph_execute(
     Json(req): Json<GraphExecuteRequest>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
     // Set up optional tracing (env var or per-request header)
-    let tracing_ctx = TracingContext::from_env().or_else(|| {
-        if is_tracing_requested(&headers) {
-            Some(TracingContext::from_env_config())
-        } else {
-            None
-        }
-    });
+    let tracing_ctx = request_tracing_context(&headers);
     let trace_input = if tracing_ctx.is_some() {
         Some(req.input.clone())
     } else {
@@ -127,11 +181,14 @@ async fn graph_execute(
         api_keys,
         research_factory: Some(default_research_factory()),
         tools_factory: Some(default_tools_factory()),
+        retrieval_factory: Some(default_retrieval_factory()),
     };
     let compiled = convert_to_state_graph_with_context(
         &req.nodes, &req.edges, &req.channels, Some(context),
     )?;
-    let config = build_runnable_config(req.recursion_limit);
+    let mut config = build_runnable_config(req.recursion_limit);
+    apply_trace_sample_override(&mut config, &headers);
+    apply_trace_parent(&mut config, &headers);
 
     let mut events: Vec<Result<Event, std::convert::Infallible>> = Vec::new();
     let steps = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
@@ -194,13 +251,7 @@ async fn graph_invoke_stream(
     headers: axum::http::HeaderMap,
     Json(req): Json<GraphExecuteRequest>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
-    let tracing_ctx = TracingContext::from_env().or_else(|| {
-        if is_tracing_requested(&headers) {
-            Some(TracingContext::from_env_config())
-        } else {
-            None
-        }
-    });
+    let tracing_ctx = request_tracing_context(&headers);
     let trace_input = if tracing_ctx.is_some() {
         Some(req.input.clone())
     } else {
@@ -212,17 +263,21 @@ async fn graph_invoke_stream(
         api_keys,
         research_factory: Some(default_research_factory()),
         tools_factory: Some(default_tools_factory()),
+        retrieval_factory: Some(default_retrieval_factory()),
     };
     let compiled = convert_to_state_graph_with_context(
         &req.nodes, &req.edges, &req.channels, Some(context),
     )?;
-    let config = build_runnable_config(req.recursion_limit);
+    let mut config = build_runnable_config(req.recursion_limit);
+    apply_trace_sample_override(&mut config, &headers);
+    apply_trace_parent(&mut config, &headers);
 
     let (tx, mut rx) = tokio::sync::mpsc::channel::<StreamEvent>(64);
 
     let input = req.input;
 
-    tokio::spawn(async move {
+    spawn_until_disconnected(tx, move |tx, cancel| async move {
+        let config = config.with_cancellation(cancel);
         let _ = compiled.invoke_with_streaming(input, &config, tx).await;
     });
 
@@ -265,6 +320,10 @@ async fn graph_invoke_stream(
 }
 
 /// Multi-mode streaming endpoint.
+///
+/// Each event is sent with its mode as the SSE event name (`values`,
+/// `updates`, `messages`, `debug`, plus `complete`/`error`), so clients can
+/// subscribe per mode.
 async fn graph_stream(
     State(factory): State<GraphModelFactory>,
     api_keys: ApiKeys,
@@ -272,14 +331,19 @@ async fn graph_stream(
 ) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
     use ayas_core::stream::{StreamEvent as CoreEvent, parse_stream_modes};
 
-    let modes = parse_stream_modes(req.stream_mode.as_deref().unwrap_or(""))
-        .map_err(|e| AppError::BadRequest(e))?;
+    let modes = if req.stream_modes.is_empty() {
+        parse_stream_modes(req.stream_mode.as_deref().unwrap_or(""))
+    } else {
+        parse_stream_modes(&req.stream_modes.join(","))
+    }
+    .map_err(AppError::BadRequest)?;
 
     let context = GraphBuildContext {
         factory,
         api_keys,
         research_factory: Some(default_research_factory()),
         tools_factory: Some(default_tools_factory()),
+        retrieval_factory: Some(default_retrieval_factory()),
     };
     let compiled = convert_to_state_graph_with_context(
         &req.nodes, &req.edges, &req.channels, Some(context),
@@ -289,7 +353,8 @@ async fn graph_stream(
     let (tx, mut rx) = tokio::sync::mpsc::channel::<CoreEvent>(64);
 
     let modes_clone = modes.clone();
-    tokio::spawn(async move {
+    spawn_until_disconnected(tx, move |tx, cancel| async move {
+        let config = config.with_cancellation(cancel);
         let _ = compiled
             .stream_with_modes(req.input, &config, &modes_clone, tx)
             .await;
@@ -323,7 +388,7 @@ async fn graph_generate(
     Json(req): Json<GraphGenerateRequest>,
 ) -> Result<Json<GraphGenerateResponse>, AppError> {
     let api_key = api_keys.get_key_for(&req.provider)?;
-    let model = factory(&req.provider, api_key, req.model);
+    let model = factory(&req.provider, api_key, req.model)?;
 
     // Try LLM generation, fall back to template on failure
     match graph_gen::generate_graph(model.as_ref(), &req.prompt).await {
@@ -402,18 +467,16 @@ mod tests {
             _messages: &[ayas_core::message::Message],
             _options: &ayas_core::model::CallOptions,
         ) -> Result<ayas_core::model::ChatResult> {
-            Ok(ayas_core::model::ChatResult {
-                message: ayas_core::message::Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: Some(UsageMetadata {
-                        input_tokens: 100,
-                        output_tokens: 50,
-                        total_tokens: 150,
-                    }),
+            Ok(ayas_core::model::ChatResult::new(ayas_core::message::Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
+                usage: Some(UsageMetadata {
+                    input_tokens: 100,
+                    output_tokens: 50,
+                    total_tokens: 150,
+                    ..Default::default()
                 }),
-                usage: None,
-            })
+            })))
         }
 
         fn model_name(&self) -> &str {
@@ -427,9 +490,9 @@ mod tests {
         let cc = call_count.clone();
         let factory: GraphModelFactory = Arc::new(move |_provider, _key, _model| {
             cc.fetch_add(1, Ordering::Relaxed);
-            Box::new(MockGraphModel {
+            Ok(Box::new(MockGraphModel {
                 response: resp.clone(),
-            })
+            }))
         });
         (factory, call_count)
     }
@@ -793,6 +856,137 @@ mod tests {
         );
     }
 
+    fn linear_stream_body(modes: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "nodes": [
+                {"id": "a", "type": "passthrough"},
+                {"id": "b", "type": "passthrough"}
+            ],
+            "edges": [
+                {"from": "start", "to": "a"},
+                {"from": "a", "to": "b"},
+                {"from": "b", "to": "end"}
+            ],
+            "channels": [{"key": "value", "type": "LastValue"}],
+            "input": {"value": "hello"},
+            "stream_modes": modes
+        })
+    }
+
+    async fn post_graph_stream(body: serde_json::Value) -> axum::response::Response {
+        app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/graph/stream")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_graph_stream_selected_modes() {
+        let body = linear_stream_body(serde_json::json!(["updates", "debug"]));
+        let resp = post_graph_stream(body).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        let text = String::from_utf8_lossy(&bytes);
+        let event_names: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("event:"))
+            .map(str::trim)
+            .collect();
+
+        let updates: Vec<_> = events.iter().filter(|e| e["type"] == "updates").collect();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0]["node"], "a");
+        assert!(events.iter().any(|e| e["type"] == "debug"));
+        assert!(!events.iter().any(|e| e["type"] == "values"));
+        assert!(events.iter().any(|e| e["type"] == "graph_complete"));
+        // Events are named by mode so clients can subscribe per mode
+        assert!(event_names.contains(&"updates"));
+        assert!(event_names.contains(&"complete"));
+    }
+
+    #[tokio::test]
+    async fn test_graph_stream_defaults_to_values() {
+        let resp = post_graph_stream(linear_stream_body(serde_json::json!([]))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+
+        let values: Vec<_> = events.iter().filter(|e| e["type"] == "values").collect();
+        assert!(!values.is_empty());
+        assert_eq!(values.last().unwrap()["state"]["value"], "hello");
+        assert!(!events.iter().any(|e| e["type"] == "updates"));
+    }
+
+    #[tokio::test]
+    async fn test_graph_stream_rejects_unknown_mode() {
+        let body = linear_stream_body(serde_json::json!(["values", "bogus"]));
+        let resp = post_graph_stream(body).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_graph_stream_runs_map_workers() {
+        let (factory, _call_count) = mock_graph_factory("mapped");
+        let app = Router::new().nest("/api", routes_with_factory(factory));
+
+        let body = serde_json::json!({
+            "nodes": [
+                {"id": "map", "type": "map", "config": {
+                    "input_channel": "items",
+                    "worker": "summarize"
+                }},
+                {"id": "summarize", "type": "llm", "config": {
+                    "provider": "gemini",
+                    "input_channel": "item",
+                    "output_channel": "results"
+                }}
+            ],
+            "edges": [
+                {"from": "start", "to": "map"},
+                {"from": "map", "to": "end"}
+            ],
+            "channels": [
+                {"key": "items", "type": "LastValue"},
+                {"key": "results", "type": "Append"}
+            ],
+            "input": {"items": ["a", "b", "c"]},
+            "stream_modes": ["values"]
+        });
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/graph/stream")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header("X-Gemini-Key", "test-key")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        let complete = events
+            .iter()
+            .find(|e| e["type"] == "graph_complete")
+            .unwrap_or_else(|| panic!("Expected graph_complete event, got: {events:?}"));
+        assert_eq!(
+            complete["output"]["results"],
+            serde_json::json!(["mapped", "mapped", "mapped"])
+        );
+    }
+
     #[tokio::test]
     async fn test_graph_execute_with_mock_llm() {
         let (factory, call_count) = mock_graph_factory("LLM response text");
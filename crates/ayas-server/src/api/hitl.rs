@@ -10,18 +10,24 @@ use serde::Serialize;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use ayas_checkpoint::prelude::{CheckpointConfigExt, GraphOutput};
+use ayas_checkpoint::prelude::{CheckpointConfigExt, CheckpointStore, GraphOutput};
 use ayas_core::config::RunnableConfig;
-use ayas_graph::compiled::StepInfo;
+use ayas_graph::compiled::{CompiledStateGraph, StepInfo};
 
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
 use crate::graph_convert::{GraphBuildContext, convert_to_state_graph_with_context};
-use crate::api::graph::{default_graph_factory, default_research_factory, default_tools_factory};
+use crate::api::graph::{
+    default_graph_factory, default_research_factory, default_retrieval_factory,
+    default_tools_factory,
+};
 use crate::session::InterruptSession;
 use crate::sse::{sse_done, sse_event};
 use crate::state::AppState;
-use crate::types::{ExecuteResumableRequest, GraphChannelDto, GraphEdgeDto, GraphNodeDto, ResumeRequest};
+use crate::types::{
+    ExecuteResumableRequest, GraphChannelDto, GraphEdgeDto, GraphNodeDto, HitlResumeRequest,
+    ResumeRequest,
+};
 
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -54,16 +60,22 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/graph/execute-resumable", post(execute_resumable))
         .route("/graph/resume", post(resume))
+        .route("/hitl/resume", post(resume_thread))
         .route("/graph/sessions", get(list_sessions))
         .route("/graph/sessions/{id}", delete(cancel_session))
 }
 
+/// Checkpoint metadata key holding the `{nodes, edges, channels}` definition
+/// a thread runs, so any of its checkpoints can be resumed with the same graph.
+const GRAPH_SPEC_KEY: &str = "graph_spec";
+
 fn build_context(api_keys: ApiKeys) -> GraphBuildContext {
     GraphBuildContext {
         factory: default_graph_factory(),
         api_keys,
         research_factory: Some(default_research_factory()),
         tools_factory: Some(default_tools_factory()),
+        retrieval_factory: Some(default_retrieval_factory()),
     }
 }
 
@@ -75,8 +87,6 @@ async fn execute_resumable(
     let context = build_context(api_keys);
     let compiled = convert_to_state_graph_with_context(&req.nodes, &req.edges, &req.channels, Some(context))?;
 
-    let config = RunnableConfig::default().with_thread_id(&req.thread_id);
-
     let steps = Arc::new(std::sync::Mutex::new(Vec::new()));
     let steps_clone = steps.clone();
     let observer = move |info: StepInfo| {
@@ -88,6 +98,9 @@ async fn execute_resumable(
         "edges": req.edges,
         "channels": req.channels,
     });
+    let config = RunnableConfig::default()
+        .with_thread_id(&req.thread_id)
+        .with_checkpoint_metadata(GRAPH_SPEC_KEY, graph_def.clone());
 
     let mut events: Vec<Result<Event, std::convert::Infallible>> = Vec::new();
 
@@ -164,8 +177,71 @@ async fn resume(
         .await
         .ok_or_else(|| AppError::Internal(format!("Session '{}' not found", req.session_id)))?;
 
-    // Reconstruct graph from stored definition
-    let graph_def = &session.graph_definition;
+    let compiled = compile_graph_definition(&session.graph_definition, api_keys)?;
+
+    let target = ResumeTarget {
+        session_id: Some(req.session_id),
+        thread_id: &session.thread_id,
+        checkpoint_id: &session.checkpoint_id,
+        resume_value: req.resume_value,
+        interrupt_key: None,
+        graph_def: session.graph_definition,
+    };
+    let events = resume_from_checkpoint(&state, &compiled, target).await;
+    Ok(Sse::new(stream::iter(events)))
+}
+
+/// Resume a thread from any of its checkpoints, rebuilding the graph the
+/// thread was started with.
+async fn resume_thread(
+    State(state): State<AppState>,
+    api_keys: ApiKeys,
+    Json(req): Json<HitlResumeRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
+    let checkpoint = state
+        .checkpoint_store
+        .get(&req.thread_id, &req.checkpoint_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Checkpoint '{}' not found in thread '{}'",
+                req.checkpoint_id, req.thread_id
+            ))
+        })?;
+    let graph_def = checkpoint
+        .metadata
+        .extra
+        .get(GRAPH_SPEC_KEY)
+        .cloned()
+        .ok_or_else(|| {
+            AppError::NotFound(format!("No graph recorded for thread '{}'", req.thread_id))
+        })?;
+
+    let compiled = compile_graph_definition(&graph_def, api_keys)?;
+    // Keep the pending session (if any) in step with the thread
+    let session_id = state
+        .session_store
+        .find_by_thread(&req.thread_id)
+        .await
+        .map(|s| s.session_id);
+
+    let target = ResumeTarget {
+        session_id,
+        thread_id: &req.thread_id,
+        checkpoint_id: &req.checkpoint_id,
+        resume_value: req.resume_value,
+        interrupt_key: req.interrupt_key,
+        graph_def,
+    };
+    let events = resume_from_checkpoint(&state, &compiled, target).await;
+    Ok(Sse::new(stream::iter(events)))
+}
+
+/// Rebuild a compiled graph from a stored `{nodes, edges, channels}` definition.
+fn compile_graph_definition(
+    graph_def: &Value,
+    api_keys: ApiKeys,
+) -> Result<CompiledStateGraph, AppError> {
     let nodes: Vec<GraphNodeDto> = serde_json::from_value(
         graph_def.get("nodes").cloned().unwrap_or(json!([])),
     )
@@ -180,12 +256,48 @@ async fn resume(
     .map_err(|e| AppError::Internal(format!("Failed to deserialize graph channels: {e}")))?;
 
     let context = build_context(api_keys);
-    let compiled = convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context))?;
+    Ok(convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context))?)
+}
 
+/// Where a thread is resumed from and with what.
+struct ResumeTarget<'a> {
+    /// The thread's pending session, reused if the run interrupts again.
+    session_id: Option<String>,
+    thread_id: &'a str,
+    checkpoint_id: &'a str,
+    resume_value: Value,
+    /// Interrupt the resume value is aimed at, if the caller named one.
+    interrupt_key: Option<String>,
+    /// The `{nodes, edges, channels}` definition `compiled` was built from.
+    graph_def: Value,
+}
+
+/// Continue a thread from `target` and collect the rest of the run as SSE
+/// events.
+///
+/// On completion the thread's session is removed; on a new interrupt it is
+/// replaced (reusing `session_id` when given) so it points at the new checkpoint.
+async fn resume_from_checkpoint(
+    state: &AppState,
+    compiled: &CompiledStateGraph,
+    target: ResumeTarget<'_>,
+) -> Vec<Result<Event, std::convert::Infallible>> {
+    let ResumeTarget {
+        session_id,
+        thread_id,
+        checkpoint_id,
+        resume_value,
+        interrupt_key,
+        graph_def,
+    } = target;
     let config = RunnableConfig::default()
-        .with_thread_id(&session.thread_id)
-        .with_checkpoint_id(&session.checkpoint_id)
-        .with_resume_value(req.resume_value);
+        .with_thread_id(thread_id)
+        .with_checkpoint_id(checkpoint_id)
+        .with_checkpoint_metadata(GRAPH_SPEC_KEY, graph_def.clone());
+    let config = match interrupt_key {
+        Some(key) => config.with_resume_value_for(key, resume_value),
+        None => config.with_resume_value(resume_value),
+    };
 
     let steps = Arc::new(std::sync::Mutex::new(Vec::new()));
     let steps_clone = steps.clone();
@@ -217,7 +329,9 @@ async fn resume(
             match output {
                 GraphOutput::Complete(final_state) => {
                     // Execution completed; delete the session
-                    state.session_store.delete(&req.session_id).await;
+                    if let Some(session_id) = &session_id {
+                        state.session_store.delete(session_id).await;
+                    }
                     events.push(sse_event(&HitlSseEvent::Complete {
                         output: final_state,
                         total_steps: captured_steps.len(),
@@ -229,21 +343,21 @@ async fn resume(
                     state: interrupt_state,
                 } => {
                     // Interrupted again; update session with new checkpoint
-                    let graph_def_clone = session.graph_definition.clone();
+                    let session_id = session_id.unwrap_or_else(|| Uuid::new_v4().to_string());
                     let updated_session = InterruptSession {
-                        session_id: req.session_id.clone(),
-                        thread_id: session.thread_id.clone(),
+                        session_id: session_id.clone(),
+                        thread_id: thread_id.to_string(),
                         checkpoint_id: checkpoint_id.clone(),
                         interrupt_value: interrupt_value.clone(),
-                        graph_definition: graph_def_clone,
+                        graph_definition: graph_def,
                         created_at: Utc::now(),
                     };
                     // Delete old, create updated
-                    state.session_store.delete(&req.session_id).await;
+                    state.session_store.delete(&session_id).await;
                     state.session_store.create(updated_session).await;
 
                     events.push(sse_event(&HitlSseEvent::Interrupted {
-                        session_id: req.session_id,
+                        session_id,
                         checkpoint_id,
                         interrupt_value,
                         state: interrupt_state,
@@ -259,7 +373,7 @@ async fn resume(
     }
 
     events.push(sse_done());
-    Ok(Sse::new(stream::iter(events)))
+    events
 }
 
 async fn list_sessions(
@@ -288,6 +402,8 @@ mod tests {
     use http_body_util::BodyExt;
     use tower::ServiceExt;
 
+    use crate::session::SessionStore;
+
     fn app() -> Router {
         let state = AppState::with_smith_dir(
             ayas_smith::client::SmithConfig::default().base_dir,
@@ -523,7 +639,7 @@ mod tests {
 
         let app = Router::new()
             .route("/api/graph/sessions/{id}", delete(cancel_session))
-            .with_state(state);
+            .with_state(state.clone());
 
         let resp = app
             .oneshot(
@@ -581,4 +697,99 @@ mod tests {
 
         assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    #[tokio::test]
+    async fn hitl_resume_by_thread_and_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::with_smith_dir(dir.path().to_path_buf());
+        let app = Router::new().nest("/api", routes().with_state(state.clone()));
+
+        let body = json!({
+            "thread_id": "hitl-thread",
+            "nodes": [
+                {"id": "n1", "type": "passthrough"},
+                {"id": "blocker", "type": "interrupt", "config": {"value": "approve?"}},
+                {"id": "n2", "type": "passthrough"}
+            ],
+            "edges": [
+                {"from": "start", "to": "n1"},
+                {"from": "n1", "to": "blocker"},
+                {"from": "blocker", "to": "n2"},
+                {"from": "n2", "to": "end"}
+            ],
+            "channels": [{"key": "value", "type": "LastValue"}],
+            "input": {"value": "data"}
+        });
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/graph/execute-resumable")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        let interrupted = events.iter().find(|e| e["type"] == "interrupted").unwrap();
+        let checkpoint_id = interrupted["checkpoint_id"].as_str().unwrap().to_string();
+
+        // Resume after a restart: only the checkpoint store survives, and the
+        // graph comes from the spec stored with the checkpoint
+        let restarted = AppState {
+            session_store: SessionStore::new(),
+            ..state
+        };
+        let app = Router::new().nest("/api", routes().with_state(restarted.clone()));
+        let resume_body = json!({
+            "thread_id": "hitl-thread",
+            "checkpoint_id": checkpoint_id,
+            "resume_value": "approved"
+        });
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/hitl/resume")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&resume_body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse_events(&bytes);
+        let complete = events.iter().find(|e| e["type"] == "complete");
+        assert!(complete.is_some(), "Expected complete event after resume, got: {events:?}");
+        assert!(restarted.session_store.list_pending().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn hitl_resume_unknown_thread() {
+        let app = app();
+        let body = json!({
+            "thread_id": "never-started",
+            "checkpoint_id": "cp-1",
+            "resume_value": "approved"
+        });
+
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/hitl/resume")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
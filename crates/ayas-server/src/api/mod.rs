@@ -31,6 +31,11 @@ async fn env_keys() -> Json<EnvKeysResponse> {
 }
 
 pub fn api_routes(state: AppState) -> Router {
+    let pipeline_state = pipeline::PipelineState {
+        runs: state.pipeline_runs.clone(),
+        ..Default::default()
+    };
+
     // Stateful routes: convert Router<AppState> to Router<()> via .with_state()
     let stateful: Router = runs::routes()
         .merge(feedback::routes())
@@ -45,7 +50,7 @@ pub fn api_routes(state: AppState) -> Router {
         .merge(agent::routes())
         .merge(graph::routes())
         .merge(research::routes())
-        .merge(pipeline::routes());
+        .merge(pipeline::routes_with_state(pipeline_state));
 
     Router::new()
         .route("/health", get(|| async { "ok" }))
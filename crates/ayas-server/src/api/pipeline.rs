@@ -1,26 +1,37 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use axum::response::sse::Event;
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAliveStream};
 use axum::response::Sse;
 use axum::{Json, Router, routing::post};
-use futures::Stream;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
 use ayas_core::config::RunnableConfig;
-use ayas_core::message::Message;
+use ayas_core::hash::Fnv1a;
+use ayas_core::message::{ContentPart, Message};
 use ayas_core::model::{CallOptions, ChatModel, ResponseFormat};
 use ayas_core::runnable::Runnable;
 use ayas_deep_research::file_search::{FileSearchClient, GeminiFileSearchClient};
 use ayas_deep_research::gemini::GeminiInteractionsClient;
 use ayas_deep_research::runnable::{DeepResearchInput, DeepResearchRunnable};
-use ayas_deep_research::types::ToolConfig;
-use ayas_llm::gemini::GeminiChatModel;
+use ayas_deep_research::types::{FileSearchStore, ToolConfig};
+use ayas_llm::config::ProviderConfig;
+use ayas_llm::factory::create_chat_model_with_config;
+use ayas_llm::provider::Provider;
 
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
-use crate::sse::{sse_done, sse_event, sse_response};
+use crate::pipeline_spec::{self, ITEM_VAR, PipelineDocument, PipelineSpec, PipelineStep, StepKind};
+use crate::sse::{
+    ReplayBuffers, ReplayStream, spawn_until_disconnected, sse_done, sse_event, sse_response,
+};
 
 // Embed demo files at compile time
 const NEEDS_MD: &str = include_str!("../../../../demo/needs.md");
@@ -30,9 +41,85 @@ const STEP2_PROMPT: &str = include_str!("../../../../demo/step2_prompt.md");
 const STEP3_PROMPT: &str = include_str!("../../../../demo/step3_prompt.md");
 
 const FILE_SEARCH_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// STEP 2 model when the request names neither provider nor model.
+const DEFAULT_EXTRACTION_MODEL: &str = "gemini-2.0-flash";
+/// Upper bound on a single Deep Research interaction, so a hung one does not
+/// pin the pipeline task.
+const RESEARCH_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+type EventSender = mpsc::Sender<Result<Event, std::convert::Infallible>>;
+
+/// Cancellation tokens of in-flight pipeline runs, by run id.
+#[derive(Clone, Default)]
+pub struct PipelineRuns {
+    tokens: Arc<Mutex<HashMap<String, CancellationToken>>>,
+}
+
+impl PipelineRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, run_id: &str, token: CancellationToken) {
+        self.tokens.lock().unwrap().insert(run_id.to_string(), token);
+    }
+
+    /// Register `run_id` until the returned guard is dropped.
+    fn track(&self, run_id: &str, token: CancellationToken) -> RegisteredRun {
+        self.register(run_id, token);
+        RegisteredRun {
+            runs: self.clone(),
+            run_id: run_id.to_string(),
+        }
+    }
+
+    fn remove(&self, run_id: &str) {
+        self.tokens.lock().unwrap().remove(run_id);
+    }
+
+    /// Cancel a running pipeline. Returns false if no run has that id.
+    pub fn abort(&self, run_id: &str) -> bool {
+        match self.tokens.lock().unwrap().remove(run_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Removes a run from [`PipelineRuns`] when dropped, however the run ends.
+struct RegisteredRun {
+    runs: PipelineRuns,
+    run_id: String,
+}
+
+impl Drop for RegisteredRun {
+    fn drop(&mut self) {
+        self.runs.remove(&self.run_id);
+    }
+}
+
+/// Shared state for the pipeline routes.
+#[derive(Clone, Default)]
+pub struct PipelineState {
+    /// Recent events per stream, replayed to clients reconnecting with
+    /// `Last-Event-ID`.
+    pub replay: ReplayBuffers,
+    pub runs: PipelineRuns,
+}
 
 pub fn routes() -> Router {
-    Router::new().route("/pipeline/hypothesis", post(pipeline_hypothesis))
+    routes_with_state(PipelineState::default())
+}
+
+pub fn routes_with_state(state: PipelineState) -> Router {
+    Router::new()
+        .route("/pipeline/hypothesis", post(pipeline_hypothesis))
+        .route("/pipeline/run", post(pipeline_run))
+        .route("/pipeline/{id}/abort", post(pipeline_abort))
+        .with_state(state)
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -44,6 +131,16 @@ pub struct PipelineRequest {
     pub needs: Option<String>,
     pub seeds: Option<String>,
     pub hypotheses: Option<Vec<ManualHypothesis>>, // Manual mode
+    /// Reuse a File Search store built from identical needs/seeds content
+    /// instead of uploading again; the store is kept after the run, for up
+    /// to a week.
+    #[serde(default)]
+    pub reuse_store: bool,
+    /// Provider for STEP 2 structured extraction (default Gemini).
+    pub extraction_provider: Option<Provider>,
+    /// Model for STEP 2; defaults to `gemini-2.0-flash` on Gemini and the
+    /// provider's first default model otherwise.
+    pub extraction_model: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -59,6 +156,20 @@ fn default_hypothesis_count() -> u32 {
     3
 }
 
+/// Body of `/pipeline/run`: a user-defined pipeline and how to run it.
+#[derive(Debug, serde::Deserialize)]
+pub struct PipelineRunRequest {
+    #[serde(flatten)]
+    pub spec: PipelineSpec,
+    /// Reuse a File Search store built from identical documents.
+    #[serde(default)]
+    pub reuse_store: bool,
+    /// Provider for `llm_structured` steps (default Gemini).
+    pub extraction_provider: Option<Provider>,
+    /// Model for `llm_structured` steps.
+    pub extraction_model: Option<String>,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum PipelineSseEvent {
@@ -103,6 +214,43 @@ enum PipelineSseEvent {
     Error {
         message: String,
     },
+    /// Overall progress of the run, for a determinate progress bar.
+    Progress {
+        percent: u32,
+        stage: String,
+    },
+    /// First event of a run; `run_id` is what `/pipeline/{id}/abort` takes.
+    RunStarted {
+        run_id: String,
+    },
+    /// A `/pipeline/run` step finished with `output`, stored under `id`.
+    StepOutput {
+        step: u32,
+        id: String,
+        output: serde_json::Value,
+    },
+    /// A `parallel_map` element of a `/pipeline/run` step started.
+    ItemStart {
+        step: u32,
+        index: u32,
+        label: String,
+    },
+    ItemComplete {
+        step: u32,
+        index: u32,
+        label: String,
+        text: String,
+    },
+    ItemError {
+        step: u32,
+        index: u32,
+        label: String,
+        message: String,
+    },
+    /// Last event of a `/pipeline/run` run: every step's output by id.
+    PipelineComplete {
+        outputs: serde_json::Value,
+    },
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -147,23 +295,549 @@ fn hypothesis_schema() -> serde_json::Value {
     })
 }
 
-/// Send a pipeline SSE event via the channel.
-async fn send_event(
-    tx: &mpsc::Sender<Result<Event, std::convert::Infallible>>,
-    event: &PipelineSseEvent,
+/// Step ids of the hypothesis pipeline; its events number them 1 to 3.
+const HYPOTHESIS_STEPS: [&str; 3] = ["step1", "step2", "step3"];
+
+/// The needs and seeds documents, named as the step prompts refer to them.
+fn hypothesis_documents(needs_text: String, seeds_text: String) -> Vec<PipelineDocument> {
+    vec![
+        PipelineDocument {
+            name: "target_specification.txt".into(),
+            content: needs_text,
+            mime_type: "text/markdown".into(),
+        },
+        PipelineDocument {
+            name: "technical_assets.json".into(),
+            content: seeds_text,
+            mime_type: "text/markdown".into(),
+        },
+    ]
+}
+
+/// STEP 3: a Deep Research per hypothesis in the array at `over`, with
+/// `documents` attached to each run.
+fn hypothesis_step3(over: &str, documents: Vec<PipelineDocument>) -> PipelineStep {
+    PipelineStep {
+        id: HYPOTHESIS_STEPS[2].into(),
+        description: None,
+        kind: StepKind::ParallelMap {
+            over: over.into(),
+            prompt: STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", "{item.title}"),
+            label: Some("title".into()),
+            documents,
+        },
+    }
+}
+
+/// The hypothesis pipeline as a spec: STEP 1 researches a report, STEP 2
+/// extracts hypotheses from it, STEP 3 researches each hypothesis.
+fn hypothesis_spec(hypothesis_count: u32, documents: Vec<PipelineDocument>) -> PipelineSpec {
+    let mut variables = Map::new();
+    variables.insert("HYPOTHESIS_COUNT".into(), hypothesis_count.into());
+    PipelineSpec {
+        steps: vec![
+            PipelineStep {
+                id: HYPOTHESIS_STEPS[0].into(),
+                description: Some("Deep Research: 仮説生成レポート作成中...".into()),
+                kind: StepKind::Research {
+                    prompt: STEP1_PROMPT.into(),
+                },
+            },
+            PipelineStep {
+                id: HYPOTHESIS_STEPS[1].into(),
+                description: Some("構造化出力: 仮説をJSON抽出中...".into()),
+                kind: StepKind::LlmStructured {
+                    prompt: STEP2_PROMPT.replace("{STEP21_OUTPUT}", "{step1}"),
+                    schema: hypothesis_schema(),
+                    schema_name: "hypotheses".into(),
+                },
+            },
+            // The STEP 3 prompt reads the STEP 1 report as `hypothesis_context`
+            hypothesis_step3(
+                "step2.hypotheses",
+                vec![PipelineDocument {
+                    name: "hypothesis_context".into(),
+                    content: "{step1}".into(),
+                    mime_type: "text/markdown".into(),
+                }],
+            ),
+        ],
+        variables,
+        documents,
+    }
+}
+
+/// Manual mode: STEP 3 only, over the user's hypothesis titles.
+fn manual_hypothesis_spec(titles: &[String], documents: Vec<PipelineDocument>) -> PipelineSpec {
+    let hypotheses = titles
+        .iter()
+        .map(|title| serde_json::json!({ "title": title }))
+        .collect();
+    let mut variables = Map::new();
+    variables.insert("hypotheses".into(), Value::Array(hypotheses));
+    PipelineSpec {
+        steps: vec![hypothesis_step3("hypotheses", Vec::new())],
+        variables,
+        documents,
+    }
+}
+
+/// Turns the executor's progress into SSE events, so each endpoint keeps its
+/// own event vocabulary. `index` is the step's position in the spec.
+trait PipelinePresenter: Send + Sync {
+    /// Events sent once the documents are ready, before the first step.
+    fn started(&self) -> Vec<PipelineSseEvent> {
+        Vec::new()
+    }
+
+    /// Progress reached when the step finishes; steps split the range from
+    /// `base` (documents ready) to [`PROGRESS_STEPS_DONE`] evenly by default.
+    fn milestone(&self, _step: &PipelineStep, index: usize, count: usize, base: u32) -> u32 {
+        progress_between(base, PROGRESS_STEPS_DONE, index as u32 + 1, count)
+    }
+
+    /// `items` is the element count of a `parallel_map` step.
+    fn step_start(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        items: Option<usize>,
+    ) -> PipelineSseEvent;
+
+    /// Events for a finished step. An error fails the run with
+    /// [`PipelinePresenter::step_failed`].
+    fn step_complete(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        output: &Value,
+    ) -> Result<Vec<PipelineSseEvent>, String>;
+
+    /// `message` reads like "failed: ..." and is prefixed with the step.
+    fn step_failed(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        message: String,
+    ) -> PipelineSseEvent;
+
+    fn item_start(&self, index: usize, item: u32, label: String) -> PipelineSseEvent;
+
+    fn item_complete(
+        &self,
+        index: usize,
+        item: u32,
+        label: String,
+        text: String,
+    ) -> PipelineSseEvent;
+
+    fn item_failed(
+        &self,
+        index: usize,
+        item: u32,
+        label: String,
+        message: String,
+    ) -> PipelineSseEvent;
+
+    /// Last event of a successful run, given the step outputs by id.
+    fn complete(&self, outputs: Map<String, Value>) -> PipelineSseEvent;
+}
+
+/// Events of `/pipeline/run`: steps numbered from 1 and outputs as JSON.
+struct SpecPresenter;
+
+impl PipelinePresenter for SpecPresenter {
+    fn step_start(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        _items: Option<usize>,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::StepStart {
+            step: index as u32 + 1,
+            description: step.description().to_string(),
+        }
+    }
+
+    fn step_complete(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        output: &Value,
+    ) -> Result<Vec<PipelineSseEvent>, String> {
+        Ok(vec![PipelineSseEvent::StepOutput {
+            step: index as u32 + 1,
+            id: step.id.clone(),
+            output: output.clone(),
+        }])
+    }
+
+    fn step_failed(
+        &self,
+        _index: usize,
+        step: &PipelineStep,
+        message: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::Error {
+            message: format!("Step '{}' {message}", step.id),
+        }
+    }
+
+    fn item_start(&self, index: usize, item: u32, label: String) -> PipelineSseEvent {
+        PipelineSseEvent::ItemStart {
+            step: index as u32 + 1,
+            index: item,
+            label,
+        }
+    }
+
+    fn item_complete(
+        &self,
+        index: usize,
+        item: u32,
+        label: String,
+        text: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::ItemComplete {
+            step: index as u32 + 1,
+            index: item,
+            label,
+            text,
+        }
+    }
+
+    fn item_failed(
+        &self,
+        index: usize,
+        item: u32,
+        label: String,
+        message: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::ItemError {
+            step: index as u32 + 1,
+            index: item,
+            label,
+            message,
+        }
+    }
+
+    fn complete(&self, outputs: Map<String, Value>) -> PipelineSseEvent {
+        PipelineSseEvent::PipelineComplete {
+            outputs: Value::Object(outputs),
+        }
+    }
+}
+
+/// Events of `/pipeline/hypothesis`, as its client has always received them.
+#[derive(Default)]
+struct HypothesisPresenter {
+    /// Manual mode titles, announced as unscored hypotheses up front.
+    manual_titles: Vec<String>,
+}
+
+/// STEP number (1-3) of a hypothesis pipeline step.
+fn hypothesis_step_number(step: &PipelineStep) -> u32 {
+    HYPOTHESIS_STEPS
+        .iter()
+        .position(|id| *id == step.id)
+        .map_or(0, |i| i as u32 + 1)
+}
+
+impl PipelinePresenter for HypothesisPresenter {
+    fn started(&self) -> Vec<PipelineSseEvent> {
+        self.manual_titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| PipelineSseEvent::Hypothesis {
+                index: i as u32,
+                title: title.clone(),
+                score: 0.0,
+                physical_contradiction: String::new(),
+                cap_id_fingerprint: String::new(),
+                verdict_tag: String::new(),
+                verdict_reason: String::new(),
+            })
+            .collect()
+    }
+
+    fn milestone(&self, step: &PipelineStep, _index: usize, _count: usize, _base: u32) -> u32 {
+        match hypothesis_step_number(step) {
+            1 => PROGRESS_STEP1,
+            2 => PROGRESS_STEP2,
+            _ => PROGRESS_STEPS_DONE,
+        }
+    }
+
+    fn step_start(
+        &self,
+        _index: usize,
+        step: &PipelineStep,
+        items: Option<usize>,
+    ) -> PipelineSseEvent {
+        let description = match items {
+            Some(count) => {
+                format!("Deep Research x{count}: 各仮説の深掘りレポートを並列実行中...")
+            }
+            None => step.description().to_string(),
+        };
+        PipelineSseEvent::StepStart {
+            step: hypothesis_step_number(step),
+            description,
+        }
+    }
+
+    fn step_complete(
+        &self,
+        _index: usize,
+        step: &PipelineStep,
+        output: &Value,
+    ) -> Result<Vec<PipelineSseEvent>, String> {
+        let step = hypothesis_step_number(step);
+        match step {
+            1 => Ok(vec![PipelineSseEvent::StepComplete {
+                step,
+                summary: format!(
+                    "レポート生成完了 ({} chars)",
+                    output.as_str().map_or(0, str::len)
+                ),
+            }]),
+            2 => {
+                let hypotheses: HypothesesOutput = serde_json::from_value(output.clone())
+                    .map_err(|e| format!("JSON parse failed: {e}"))?;
+                let mut events = vec![PipelineSseEvent::StepComplete {
+                    step,
+                    summary: format!("{}件の仮説を抽出", hypotheses.hypotheses.len()),
+                }];
+                events.extend(hypotheses.hypotheses.into_iter().enumerate().map(|(i, h)| {
+                    PipelineSseEvent::Hypothesis {
+                        index: i as u32,
+                        title: h.title,
+                        score: h.synthesis_score,
+                        physical_contradiction: h.physical_contradiction,
+                        cap_id_fingerprint: h.cap_id_fingerprint,
+                        verdict_tag: h.verdict_tag,
+                        verdict_reason: h.verdict_reason,
+                    }
+                }));
+                Ok(events)
+            }
+            _ => Ok(vec![PipelineSseEvent::StepComplete {
+                step,
+                summary: format!(
+                    "{}件の深掘りレポート完了",
+                    output.as_array().map_or(0, Vec::len)
+                ),
+            }]),
+        }
+    }
+
+    fn step_failed(
+        &self,
+        _index: usize,
+        step: &PipelineStep,
+        message: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::Error {
+            message: format!("STEP {} {message}", hypothesis_step_number(step)),
+        }
+    }
+
+    fn item_start(&self, _index: usize, item: u32, label: String) -> PipelineSseEvent {
+        PipelineSseEvent::Step3Start {
+            index: item,
+            title: label,
+        }
+    }
+
+    fn item_complete(
+        &self,
+        _index: usize,
+        item: u32,
+        label: String,
+        text: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::Step3Complete {
+            index: item,
+            title: label,
+            text,
+        }
+    }
+
+    fn item_failed(
+        &self,
+        _index: usize,
+        item: u32,
+        label: String,
+        message: String,
+    ) -> PipelineSseEvent {
+        PipelineSseEvent::Step3Error {
+            index: item,
+            title: label,
+            message,
+        }
+    }
+
+    fn complete(&self, mut outputs: Map<String, Value>) -> PipelineSseEvent {
+        PipelineSseEvent::Complete {
+            step1_text: outputs
+                .remove(HYPOTHESIS_STEPS[0])
+                .as_ref()
+                .map(pipeline_spec::value_text)
+                .unwrap_or_default(),
+            hypotheses: outputs.remove(HYPOTHESIS_STEPS[1]).unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Final events of a cancelled run. After a disconnect nobody is listening;
+/// after an abort the client learns why the stream ended.
+async fn send_aborted(tx: &EventSender) {
+    send_event(tx, &PipelineSseEvent::Error {
+        message: "aborted".into(),
+    })
+    .await;
+    let _ = tx.send(sse_done()).await;
+}
+
+/// Clean up the File Search store of a cancelled run and end its stream.
+async fn abort_run(
+    tx: &EventSender,
+    fs_client: &dyn FileSearchClient,
+    store_name: &str,
+    reuse_store: bool,
 ) {
+    cleanup_store(fs_client, store_name, reuse_store).await;
+    send_aborted(tx).await;
+}
+
+/// Send a pipeline SSE event via the channel.
+async fn send_event(tx: &EventSender, event: &PipelineSseEvent) {
     let _ = tx.send(sse_event(event)).await;
 }
 
+/// Overall progress (percent) reached at each pipeline milestone. Steps
+/// advance from the preceding milestone to theirs, a `parallel_map` step
+/// element by element.
+const PROGRESS_FILES_UPLOADED: u32 = 10;
+const PROGRESS_INDEXED: u32 = 20;
+const PROGRESS_STEP1: u32 = 50;
+const PROGRESS_STEP2: u32 = 60;
+const PROGRESS_STEPS_DONE: u32 = 95;
+
+/// Progress from `start` to `end` once `done` of `total` units are complete.
+fn progress_between(start: u32, end: u32, done: u32, total: usize) -> u32 {
+    if total == 0 {
+        return end;
+    }
+    let done = u64::from(done).min(total as u64);
+    start + ((u64::from(end.saturating_sub(start)) * done) / total as u64) as u32
+}
+
+/// Send a pipeline progress event.
+async fn send_progress(tx: &EventSender, percent: u32, stage: &str) {
+    send_event(tx, &PipelineSseEvent::Progress {
+        percent,
+        stage: stage.into(),
+    })
+    .await;
+}
+
+/// Deep Research runnable for a pipeline step, bounded by [`RESEARCH_TIMEOUT`].
+fn research_runnable(client: &Arc<GeminiInteractionsClient>) -> DeepResearchRunnable {
+    DeepResearchRunnable::new(client.clone()).with_timeout(RESEARCH_TIMEOUT)
+}
+
+/// Display-name prefix of stores kept for reuse across pipeline runs.
+const CACHED_STORE_PREFIX: &str = "pipeline-cache-";
+
+/// Age after which a cached store is deleted instead of reused, so stores
+/// for documents that are no longer sent do not pile up.
+const CACHED_STORE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Stable content hash of the pipeline documents (FNV-1a, hex).
+fn content_hash(documents: &[PipelineDocument]) -> String {
+    let mut hasher = Fnv1a::new();
+    // Separator bytes keep ("ab", "c") and ("a", "bc") distinct
+    for doc in documents {
+        hasher.write(doc.name.as_bytes());
+        hasher.write(&[0]);
+        hasher.write(doc.content.as_bytes());
+        hasher.write(&[0]);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// Whether a cached store was created more than [`CACHED_STORE_TTL`] before
+/// `now`. A store without a readable creation time is kept.
+fn is_expired(store: &FileSearchStore, now: DateTime<Utc>) -> bool {
+    store
+        .create_time
+        .as_deref()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        .and_then(|created| (now - created.with_timezone(&Utc)).to_std().ok())
+        .is_some_and(|age| age > CACHED_STORE_TTL)
+}
+
+/// Find a fully indexed store with the given display name. Cached stores
+/// past [`CACHED_STORE_TTL`] are deleted (best-effort) rather than reused.
+async fn find_cached_store(
+    fs_client: &dyn FileSearchClient,
+    display_name: &str,
+) -> Option<String> {
+    let stores = match fs_client.list_stores().await {
+        Ok(stores) => stores,
+        Err(e) => {
+            warn!(error = %e, "Failed to list File Search stores, building a new one");
+            return None;
+        }
+    };
+    let now = Utc::now();
+    let (expired, cached): (Vec<_>, Vec<_>) = stores
+        .into_iter()
+        .filter(|s| s.display_name.starts_with(CACHED_STORE_PREFIX))
+        .partition(|s| is_expired(s, now));
+    for store in expired {
+        info!(store = %store.name, "Deleting expired File Search Store");
+        let _ = fs_client.delete_store(&store.name).await;
+    }
+
+    let is_zero = |count: &Option<String>| count.as_deref().unwrap_or("0") == "0";
+    cached
+        .into_iter()
+        .find(|s| {
+            s.display_name == display_name
+                && is_zero(&s.pending_documents_count)
+                && is_zero(&s.failed_documents_count)
+        })
+        .map(|s| s.name)
+}
+
 /// Set up File Search Store: upload files, create store, import files, wait for indexing.
-/// Returns (store_name, fs_client) on success.
+///
+/// With `reuse_store`, a store previously built from the same content is
+/// looked up by its content-hash display name and returned instead.
+/// Returns the store name.
 async fn setup_file_search(
-    api_key: &str,
-    needs_text: &str,
-    seeds_text: &str,
-    tx: &mpsc::Sender<Result<Event, std::convert::Infallible>>,
-) -> Result<(String, GeminiFileSearchClient), String> {
-    let fs_client = GeminiFileSearchClient::new(api_key);
+    fs_client: &dyn FileSearchClient,
+    documents: &[PipelineDocument],
+    reuse_store: bool,
+    tx: &EventSender,
+) -> Result<String, String> {
+    let display_name = if reuse_store {
+        let display_name = format!("{CACHED_STORE_PREFIX}{}", content_hash(documents));
+        if let Some(store_name) = find_cached_store(fs_client, &display_name).await {
+            info!(store = %store_name, "Reusing File Search Store");
+            send_event(tx, &PipelineSseEvent::FileSearchSetup {
+                status: "ready".into(),
+            })
+            .await;
+            send_progress(tx, PROGRESS_INDEXED, "indexed").await;
+            return Ok(store_name);
+        }
+        display_name
+    } else {
+        format!("pipeline-{}", uuid::Uuid::new_v4())
+    };
 
     // Upload files
     send_event(tx, &PipelineSseEvent::FileSearchSetup {
@@ -171,21 +845,16 @@ async fn setup_file_search(
     })
     .await;
 
-    let uploaded_needs = fs_client
-        .upload_file("needs.md", "text/markdown", needs_text.as_bytes())
-        .await
-        .map_err(|e| format!("Failed to upload needs.md: {e}"))?;
-
-    let uploaded_seeds = fs_client
-        .upload_file("seeds.md", "text/markdown", seeds_text.as_bytes())
-        .await
-        .map_err(|e| format!("Failed to upload seeds.md: {e}"))?;
-
-    info!(
-        needs = %uploaded_needs.name,
-        seeds = %uploaded_seeds.name,
-        "Files uploaded"
-    );
+    let mut uploaded = Vec::with_capacity(documents.len());
+    for doc in documents {
+        let file = fs_client
+            .upload_file(&doc.name, &doc.mime_type, doc.content.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to upload {}: {e}", doc.name))?;
+        info!(document = %doc.name, file = %file.name, "File uploaded");
+        uploaded.push((doc.name.as_str(), file.name));
+    }
+    send_progress(tx, PROGRESS_FILES_UPLOADED, "files_uploaded").await;
 
     // Create store
     send_event(tx, &PipelineSseEvent::FileSearchSetup {
@@ -194,7 +863,7 @@ async fn setup_file_search(
     .await;
 
     let store = fs_client
-        .create_store(&format!("pipeline-{}", uuid::Uuid::new_v4()))
+        .create_store(&display_name)
         .await
         .map_err(|e| format!("Failed to create store: {e}"))?;
 
@@ -206,22 +875,18 @@ async fn setup_file_search(
     })
     .await;
 
-    let op1 = fs_client
-        .import_file(&store.name, &uploaded_needs.name)
-        .await
-        .map_err(|e| format!("Failed to import needs.md: {e}"))?;
-
-    let op2 = fs_client
-        .import_file(&store.name, &uploaded_seeds.name)
-        .await
-        .map_err(|e| format!("Failed to import seeds.md: {e}"))?;
+    let mut operations = Vec::with_capacity(uploaded.len());
+    for (doc_name, file_name) in &uploaded {
+        let op = fs_client
+            .import_file(&store.name, file_name)
+            .await
+            .map_err(|e| format!("Failed to import {doc_name}: {e}"))?;
+        operations.push(op);
+    }
 
     // Wait for import operations to complete
-    if !op1.done {
-        wait_for_operation(&fs_client, &op1.name).await?;
-    }
-    if !op2.done {
-        wait_for_operation(&fs_client, &op2.name).await?;
+    for op in operations.iter().filter(|op| !op.done) {
+        wait_for_operation(fs_client, &op.name).await?;
     }
 
     // Wait for store to finish indexing
@@ -234,15 +899,23 @@ async fn setup_file_search(
         status: "ready".into(),
     })
     .await;
+    send_progress(tx, PROGRESS_INDEXED, "indexed").await;
 
     info!(store = %store.name, "File Search Store ready");
 
-    Ok((store.name, fs_client))
+    Ok(store.name)
+}
+
+/// Delete the run's store (best-effort) unless it is kept for reuse.
+async fn cleanup_store(fs_client: &dyn FileSearchClient, store_name: &str, reuse_store: bool) {
+    if !reuse_store {
+        let _ = fs_client.delete_store(store_name).await;
+    }
 }
 
 /// Poll an operation until done.
 async fn wait_for_operation(
-    client: &GeminiFileSearchClient,
+    client: &dyn FileSearchClient,
     operation_name: &str,
 ) -> Result<(), String> {
     loop {
@@ -266,749 +939,886 @@ async fn wait_for_operation(
 }
 
 async fn pipeline_hypothesis(
+    State(state): State<PipelineState>,
+    headers: HeaderMap,
     api_keys: ApiKeys,
     Json(req): Json<PipelineRequest>,
-) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, AppError> {
-    let api_key = api_keys.get_key_for(&ayas_llm::provider::Provider::Gemini)?;
-    let hypothesis_count = req.hypothesis_count;
-    let mode = req.mode.clone();
-    let needs_text = req.needs.unwrap_or_else(|| NEEDS_MD.to_string());
-    let seeds_text = req.seeds.unwrap_or_else(|| SEEDS_MD.to_string());
-    let manual_hypotheses = req.hypotheses;
-
-    let (tx, rx) = mpsc::channel::<Result<Event, std::convert::Infallible>>(32);
-
-    tokio::spawn(async move {
-        run_pipeline(tx, api_key, hypothesis_count, mode, needs_text, seeds_text, manual_hypotheses).await;
-    });
-
-    let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
-    Ok(sse_response(stream))
-}
-
-async fn run_pipeline(
-    tx: mpsc::Sender<Result<Event, std::convert::Infallible>>,
-    api_key: String,
-    hypothesis_count: u32,
-    mode: String,
-    needs_text: String,
-    seeds_text: String,
-    manual_hypotheses: Option<Vec<ManualHypothesis>>,
-) {
-    info!(mode = %mode, hypothesis_count, "Pipeline started");
-
-    // === Set up File Search Store ===
-    let (store_name, fs_client) =
-        match setup_file_search(&api_key, &needs_text, &seeds_text, &tx).await {
-            Ok(result) => result,
-            Err(msg) => {
-                warn!(error = %msg, "File Search setup failed, falling back to inline text");
-                // Fallback: run pipeline without File Search
-                run_pipeline_inline(
-                    tx,
-                    api_key,
-                    hypothesis_count,
-                    mode,
-                    needs_text,
-                    seeds_text,
-                    manual_hypotheses,
-                )
-                .await;
-                return;
-            }
-        };
+) -> Result<Sse<KeepAliveStream<ReplayStream>>, AppError> {
+    // A reconnecting client picks up the running pipeline instead of starting another
+    if let Some(resumed) = state.replay.resume(&headers)? {
+        return Ok(sse_response(resumed));
+    }
 
-    let research_client = Arc::new(GeminiInteractionsClient::new(&api_key));
+    let api_key = api_keys.get_key_for(&Provider::Gemini)?;
+    let extraction_model =
+        build_extraction_model(&api_keys, req.extraction_provider, req.extraction_model)?;
+    let documents = hypothesis_documents(
+        req.needs.unwrap_or_else(|| NEEDS_MD.to_string()),
+        req.seeds.unwrap_or_else(|| SEEDS_MD.to_string()),
+    );
 
-    if mode == "manual" {
-        // === Manual mode: skip STEP 1 & 2, go straight to STEP 3 ===
-        let titles: Vec<String> = manual_hypotheses
+    info!(mode = %req.mode, hypothesis_count = req.hypothesis_count, "Hypothesis pipeline");
+    let (spec, presenter) = if req.mode == "manual" {
+        // Manual mode: skip STEP 1 & 2, go straight to STEP 3
+        let titles: Vec<String> = req
+            .hypotheses
             .unwrap_or_default()
             .into_iter()
             .map(|h| h.title)
             .filter(|t| !t.trim().is_empty())
             .collect();
-
         if titles.is_empty() {
-            send_event(&tx, &PipelineSseEvent::Error {
+            let error = PipelineSseEvent::Error {
                 message: "Manual mode requires at least one hypothesis title".into(),
-            })
-            .await;
-            let _ = fs_client.delete_store(&store_name).await;
-            let _ = tx.send(sse_done()).await;
-            return;
+            };
+            return Ok(sse_response(state.replay.record(vec![sse_event(&error), sse_done()])));
         }
+        let spec = manual_hypothesis_spec(&titles, documents);
+        let presenter = HypothesisPresenter {
+            manual_titles: titles,
+        };
+        (spec, presenter)
+    } else {
+        let spec = hypothesis_spec(req.hypothesis_count, documents);
+        (spec, HypothesisPresenter::default())
+    };
 
-        // Emit hypotheses with score=0.0
-        for (i, title) in titles.iter().enumerate() {
-            send_event(&tx, &PipelineSseEvent::Hypothesis {
-                index: i as u32,
-                title: title.clone(),
-                score: 0.0,
-                physical_contradiction: String::new(),
-                cap_id_fingerprint: String::new(),
-                verdict_tag: String::new(),
-                verdict_reason: String::new(),
-            })
-            .await;
-        }
+    Ok(spawn_run(&state, api_key, extraction_model, spec, req.reuse_store, presenter))
+}
 
-        // === STEP 3 ===
-        send_event(&tx, &PipelineSseEvent::StepStart {
-            step: 3,
-            description: format!(
-                "Deep Research x{}: 各仮説の深掘りレポートを並列実行中...",
-                titles.len()
-            ),
-        })
-        .await;
-
-        let (step3_tx, mut step3_rx) = mpsc::channel::<(u32, String, Result<String, String>)>(16);
-
-        for (i, title) in titles.iter().enumerate() {
-            let tx_inner = tx.clone();
-            let step3_tx = step3_tx.clone();
-            let title = title.clone();
-            let idx = i as u32;
-            let prompt3 = STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", &title);
-            let input3 = DeepResearchInput::new(&prompt3).with_tools(vec![
-                ToolConfig::FileSearch {
-                    file_search_store_names: vec![store_name.clone()],
-                },
-            ]);
-            let research = DeepResearchRunnable::new(research_client.clone());
-            let config = RunnableConfig::default();
+/// Run a user-defined [`PipelineSpec`].
+async fn pipeline_run(
+    State(state): State<PipelineState>,
+    headers: HeaderMap,
+    api_keys: ApiKeys,
+    Json(req): Json<PipelineRunRequest>,
+) -> Result<Sse<KeepAliveStream<ReplayStream>>, AppError> {
+    if let Some(resumed) = state.replay.resume(&headers)? {
+        return Ok(sse_response(resumed));
+    }
 
-            let _ = tx_inner
-                .send(sse_event(&PipelineSseEvent::Step3Start {
-                    index: idx,
-                    title: title.clone(),
-                }))
-                .await;
+    req.spec.validate().map_err(AppError::BadRequest)?;
+    let api_key = api_keys.get_key_for(&Provider::Gemini)?;
+    let model = build_extraction_model(&api_keys, req.extraction_provider, req.extraction_model)?;
 
-            tokio::spawn(async move {
-                info!(idx, title = %title, "STEP 3 Deep Research invoke start");
-                let result = match research.invoke(input3, &config).await {
-                    Ok(output) => {
-                        info!(idx, "STEP 3 Deep Research invoke OK ({} chars)", output.text.len());
-                        Ok(output.text)
-                    }
-                    Err(e) => {
-                        warn!(idx, error = %e, "STEP 3 Deep Research invoke failed");
-                        Err(e.to_string())
-                    }
-                };
-                let _ = step3_tx.send((idx, title, result)).await;
-            });
-        }
-        drop(step3_tx);
+    Ok(spawn_run(&state, api_key, model, req.spec, req.reuse_store, SpecPresenter))
+}
 
-        let total = titles.len();
-        let mut completed = 0u32;
-        while let Some((idx, title, result)) = step3_rx.recv().await {
-            completed += 1;
-            info!(idx, completed, total, "STEP 3 result received");
-            match result {
-                Ok(text) => {
-                    send_event(&tx, &PipelineSseEvent::Step3Complete {
-                        index: idx,
-                        title,
-                        text,
-                    })
-                    .await;
-                }
-                Err(message) => {
-                    send_event(&tx, &PipelineSseEvent::Step3Error {
-                        index: idx,
-                        title,
-                        message,
-                    })
-                    .await;
-                }
-            }
-            if completed as usize == total {
-                break;
-            }
+/// Stream a run of `spec`, registered under a fresh run id for
+/// `/pipeline/{id}/abort`.
+fn spawn_run(
+    state: &PipelineState,
+    api_key: String,
+    model: Box<dyn ChatModel>,
+    spec: PipelineSpec,
+    reuse_store: bool,
+    presenter: impl PipelinePresenter + 'static,
+) -> Sse<KeepAliveStream<ReplayStream>> {
+    let (tx, rx) = mpsc::channel::<Result<Event, std::convert::Infallible>>(32);
+    let run_id = uuid::Uuid::new_v4().to_string();
+
+    // Stop the Deep Research work once the client is gone for good (the replay
+    // buffer holds the receiver through short disconnects) or the run is aborted.
+    // The closure runs before the task is spawned, so the run can be aborted as
+    // soon as its id is streamed.
+    let runs = state.runs.clone();
+    spawn_until_disconnected(tx, move |tx, cancel| {
+        let registered = runs.track(&run_id, cancel.clone());
+        async move {
+            let _registered = registered;
+            send_event(&tx, &PipelineSseEvent::RunStarted { run_id }).await;
+            run_spec(tx, cancel, api_key, model, spec, reuse_store, &presenter).await;
         }
+    });
 
-        info!(completed, "Manual mode pipeline complete");
-
-        send_event(&tx, &PipelineSseEvent::StepComplete {
-            step: 3,
-            summary: format!("{}件の深掘りレポート完了", completed),
-        })
-        .await;
+    sse_response(state.replay.pump(rx))
+}
 
-        send_event(&tx, &PipelineSseEvent::Complete {
-            step1_text: String::new(),
-            hypotheses: serde_json::Value::Null,
-        })
-        .await;
+/// Build the model for structured extraction steps requested by the client.
+fn build_extraction_model(
+    api_keys: &ApiKeys,
+    provider: Option<Provider>,
+    model: Option<String>,
+) -> Result<Box<dyn ChatModel>, AppError> {
+    let provider = provider.unwrap_or(Provider::Gemini);
+    let model = model.unwrap_or_else(|| match provider {
+        Provider::Gemini => DEFAULT_EXTRACTION_MODEL.to_string(),
+        _ => provider.default_models()[0].to_string(),
+    });
+    let api_key = api_keys.get_key_for(&provider)?;
+    let config = ProviderConfig::from_env(&provider)?;
+    Ok(create_chat_model_with_config(&provider, api_key, model, &config)?)
+}
 
-        // Cleanup store (best-effort)
-        let _ = fs_client.delete_store(&store_name).await;
-        let _ = tx.send(sse_done()).await;
-        return;
+/// Cancel a running pipeline; its stream ends with an `aborted` error.
+async fn pipeline_abort(
+    State(state): State<PipelineState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if !state.runs.abort(&id) {
+        return Err(AppError::NotFound(format!("Pipeline run '{id}' not found")));
     }
+    info!(run_id = %id, "Pipeline run aborted");
+    Ok(Json(serde_json::json!({"status": "aborted", "run_id": id})))
+}
 
-    // === LLM mode (default): STEP 1 → 2 → 3 ===
-
-    // === STEP 1: Deep Research with File Search ===
-    send_event(&tx, &PipelineSseEvent::StepStart {
-        step: 1,
-        description: "Deep Research: 仮説生成レポート作成中...".into(),
-    })
-    .await;
-
-    let research = DeepResearchRunnable::new(research_client.clone());
-    let config = RunnableConfig::default();
-
-    let prompt1 = STEP1_PROMPT.replace("{HYPOTHESIS_COUNT}", &hypothesis_count.to_string());
-    let input1 = DeepResearchInput::new(&prompt1).with_tools(vec![ToolConfig::FileSearch {
-        file_search_store_names: vec![store_name.clone()],
-    }]);
-
-    let output1 = match research.invoke(input1, &config).await {
-        Ok(o) => o,
-        Err(e) => {
-            send_event(&tx, &PipelineSseEvent::Error {
-                message: format!("STEP 1 failed: {}", e),
-            })
-            .await;
-            let _ = fs_client.delete_store(&store_name).await;
-            let _ = tx.send(sse_done()).await;
-            return;
-        }
-    };
-
-    send_event(&tx, &PipelineSseEvent::StepComplete {
-        step: 1,
-        summary: format!("レポート生成完了 ({} chars)", output1.text.len()),
-    })
-    .await;
-
-    // === STEP 2: Structured output extraction ===
-    send_event(&tx, &PipelineSseEvent::StepStart {
-        step: 2,
-        description: "構造化出力: 仮説をJSON抽出中...".into(),
-    })
-    .await;
-
-    let model = GeminiChatModel::new(api_key.clone(), "gemini-2.0-flash".into());
-    let prompt2 = STEP2_PROMPT
-        .replace("{HYPOTHESIS_COUNT}", &hypothesis_count.to_string())
-        .replace("{STEP21_OUTPUT}", &output1.text);
-
-    let messages = vec![Message::user(prompt2.as_str())];
-    let options = CallOptions {
-        response_format: Some(ResponseFormat::JsonSchema {
-            name: "hypotheses".into(),
-            schema: hypothesis_schema(),
-            strict: true,
-        }),
-        ..Default::default()
-    };
+/// Where research steps find the pipeline's documents.
+enum ResearchSources {
+    /// An indexed File Search store.
+    FileSearch(String),
+    /// The documents themselves, when File Search could not be set up.
+    Inline(Vec<ContentPart>),
+}
 
-    let result2 = match model.generate(&messages, &options).await {
-        Ok(r) => r,
-        Err(e) => {
-            send_event(&tx, &PipelineSseEvent::Error {
-                message: format!("STEP 2 failed: {}", e),
-            })
-            .await;
-            let _ = fs_client.delete_store(&store_name).await;
-            let _ = tx.send(sse_done()).await;
-            return;
-        }
-    };
+impl ResearchSources {
+    fn inline(documents: &[PipelineDocument]) -> Self {
+        Self::Inline(
+            documents
+                .iter()
+                .map(|doc| document_part(&doc.name, &doc.content))
+                .collect(),
+        )
+    }
 
-    let json_text = result2.message.content().to_string();
-    let hypotheses: HypothesesOutput = match serde_json::from_str(&json_text) {
-        Ok(h) => h,
-        Err(e) => {
-            send_event(&tx, &PipelineSseEvent::Error {
-                message: format!("STEP 2 JSON parse failed: {}", e),
-            })
-            .await;
-            let _ = fs_client.delete_store(&store_name).await;
-            let _ = tx.send(sse_done()).await;
-            return;
+    /// `extra` is attached after the inline documents; File Search runs
+    /// only search the store.
+    fn input(&self, prompt: String, extra: &[ContentPart]) -> DeepResearchInput {
+        let input = DeepResearchInput::new(prompt);
+        match self {
+            Self::FileSearch(store_name) => input.with_tools(vec![ToolConfig::FileSearch {
+                file_search_store_names: vec![store_name.clone()],
+            }]),
+            Self::Inline(parts) => input.with_attachments([parts, extra].concat()),
         }
-    };
-
-    let hypotheses_json: serde_json::Value =
-        serde_json::from_str(&json_text).unwrap_or(serde_json::Value::Null);
-
-    send_event(&tx, &PipelineSseEvent::StepComplete {
-        step: 2,
-        summary: format!("{}件の仮説を抽出", hypotheses.hypotheses.len()),
-    })
-    .await;
+    }
+}
 
-    for (i, h) in hypotheses.hypotheses.iter().enumerate() {
-        send_event(&tx, &PipelineSseEvent::Hypothesis {
-            index: i as u32,
-            title: h.title.clone(),
-            score: h.synthesis_score,
-            physical_contradiction: h.physical_contradiction.clone(),
-            cap_id_fingerprint: h.cap_id_fingerprint.clone(),
-            verdict_tag: h.verdict_tag.clone(),
-            verdict_reason: h.verdict_reason.clone(),
-        })
-        .await;
+/// A document as an inline attachment, headed by its name.
+fn document_part(name: &str, content: &str) -> ContentPart {
+    ContentPart::Text {
+        text: format!("=== {name} ===\n{content}"),
     }
+}
 
-    // === STEP 3: Independent parallel Deep Research per hypothesis with File Search ===
-    send_event(&tx, &PipelineSseEvent::StepStart {
-        step: 3,
-        description: format!(
-            "Deep Research x{}: 各仮説の深掘りレポートを並列実行中...",
-            hypotheses.hypotheses.len()
-        ),
-    })
-    .await;
+/// How running a spec's steps ended.
+enum StepsOutcome {
+    /// Every step finished; their outputs by step id.
+    Completed(Map<String, Value>),
+    /// A step failed; the error event to send.
+    Failed(PipelineSseEvent),
+    Aborted,
+}
 
-    let (step3_tx, mut step3_rx) = mpsc::channel::<(u32, String, Result<String, String>)>(16);
+/// Why a single step did not produce an output.
+enum StepError {
+    /// Reads like "failed: ..."; the presenter names the step.
+    Failed(String),
+    Aborted,
+}
 
-    for (i, h) in hypotheses.hypotheses.iter().enumerate() {
-        let tx_inner = tx.clone();
-        let step3_tx = step3_tx.clone();
-        let title = h.title.clone();
-        let idx = i as u32;
-        let prompt3 = STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", &title);
+/// One element of a `parallel_map` step, ready to research.
+struct MapJob {
+    item: Value,
+    label: String,
+    prompt: String,
+    /// The step's documents, rendered for this element.
+    documents: Vec<ContentPart>,
+}
 
-        // Use File Search tool instead of inline text attachments
-        let input3 = DeepResearchInput::new(&prompt3).with_tools(vec![
-            ToolConfig::FileSearch {
-                file_search_store_names: vec![store_name.clone()],
-            },
-        ]);
-        let research = DeepResearchRunnable::new(research_client.clone());
-        let config = RunnableConfig::default();
+/// Bind each element to `{item}` and render the step's prompt and documents
+/// for it.
+fn map_jobs(
+    items: &[Value],
+    prompt: &str,
+    label: Option<&str>,
+    documents: &[PipelineDocument],
+    ctx: &Map<String, Value>,
+) -> Vec<MapJob> {
+    let mut ctx = ctx.clone();
+    items
+        .iter()
+        .map(|item| {
+            ctx.insert(ITEM_VAR.into(), item.clone());
+            let label = label
+                .and_then(|path| pipeline_spec::lookup(&ctx, &format!("{ITEM_VAR}.{path}")))
+                .unwrap_or(item);
+            MapJob {
+                item: item.clone(),
+                label: pipeline_spec::value_text(label),
+                prompt: pipeline_spec::render(prompt, &ctx),
+                documents: documents
+                    .iter()
+                    .map(|doc| document_part(&doc.name, &pipeline_spec::render(&doc.content, &ctx)))
+                    .collect(),
+            }
+        })
+        .collect()
+}
 
-        let _ = tx_inner
-            .send(sse_event(&PipelineSseEvent::Step3Start {
-                index: idx,
-                title: title.clone(),
-            }))
-            .await;
+/// Run `spec`: set up File Search over its documents (attaching them inline
+/// if that fails), run the steps in order, and clean up the store.
+async fn run_spec(
+    tx: EventSender,
+    cancel: CancellationToken,
+    api_key: String,
+    model: Box<dyn ChatModel>,
+    spec: PipelineSpec,
+    reuse_store: bool,
+    presenter: &dyn PipelinePresenter,
+) {
+    info!(steps = spec.steps.len(), reuse_store, "Pipeline started");
 
-        tokio::spawn(async move {
-            let result = match research.invoke(input3, &config).await {
-                Ok(output) => Ok(output.text),
-                Err(e) => Err(e.to_string()),
-            };
-            let _ = step3_tx.send((idx, title, result)).await;
-        });
-    }
-    drop(step3_tx);
-
-    let total = hypotheses.hypotheses.len();
-    let mut completed = 0u32;
-    while let Some((idx, title, result)) = step3_rx.recv().await {
-        completed += 1;
-        match result {
-            Ok(text) => {
-                send_event(&tx, &PipelineSseEvent::Step3Complete {
-                    index: idx,
-                    title,
-                    text,
-                })
-                .await;
-            }
-            Err(message) => {
-                send_event(&tx, &PipelineSseEvent::Step3Error {
-                    index: idx,
-                    title,
-                    message,
-                })
-                .await;
+    let fs_client = GeminiFileSearchClient::new(&api_key);
+    let mut store_name = None;
+    if !spec.documents.is_empty() {
+        match setup_file_search(&fs_client, &spec.documents, reuse_store, &tx).await {
+            Ok(name) => store_name = Some(name),
+            Err(msg) => {
+                warn!(error = %msg, "File Search setup failed, falling back to inline text");
             }
         }
-        if completed as usize == total {
-            break;
-        }
     }
+    let (sources, base) = match &store_name {
+        Some(name) => (ResearchSources::FileSearch(name.clone()), PROGRESS_INDEXED),
+        None => (ResearchSources::inline(&spec.documents), 0),
+    };
 
-    send_event(&tx, &PipelineSseEvent::StepComplete {
-        step: 3,
-        summary: format!("{}件の深掘りレポート完了", completed),
-    })
-    .await;
-
-    send_event(&tx, &PipelineSseEvent::Complete {
-        step1_text: output1.text,
-        hypotheses: hypotheses_json,
-    })
-    .await;
+    let runner = StepRunner {
+        tx: &tx,
+        cancel: &cancel,
+        research_client: Arc::new(GeminiInteractionsClient::new(&api_key)),
+        model: model.as_ref(),
+        sources: &sources,
+        presenter,
+    };
+    match runner.run(&spec, base).await {
+        StepsOutcome::Completed(outputs) => {
+            send_progress(&tx, 100, "complete").await;
+            send_event(&tx, &presenter.complete(outputs)).await;
+        }
+        StepsOutcome::Failed(event) => send_event(&tx, &event).await,
+        StepsOutcome::Aborted => {
+            match &store_name {
+                Some(name) => abort_run(&tx, &fs_client, name, reuse_store).await,
+                None => send_aborted(&tx).await,
+            }
+            return;
+        }
+    }
 
     // Cleanup store (best-effort)
-    let _ = fs_client.delete_store(&store_name).await;
+    if let Some(name) = &store_name {
+        cleanup_store(&fs_client, name, reuse_store).await;
+    }
     let _ = tx.send(sse_done()).await;
 }
 
-/// Fallback pipeline using inline text attachments (when File Search setup fails).
-async fn run_pipeline_inline(
-    tx: mpsc::Sender<Result<Event, std::convert::Infallible>>,
-    api_key: String,
-    hypothesis_count: u32,
-    mode: String,
-    needs_text: String,
-    seeds_text: String,
-    manual_hypotheses: Option<Vec<ManualHypothesis>>,
-) {
-    use ayas_core::message::ContentPart;
+/// Runs a spec's steps, sending their events as they happen.
+struct StepRunner<'a> {
+    tx: &'a EventSender,
+    cancel: &'a CancellationToken,
+    research_client: Arc<GeminiInteractionsClient>,
+    model: &'a dyn ChatModel,
+    sources: &'a ResearchSources,
+    presenter: &'a dyn PipelinePresenter,
+}
 
-    let send = |event: &PipelineSseEvent| {
-        let tx = tx.clone();
-        let e = sse_event(event);
-        async move {
-            let _ = tx.send(e).await;
+impl StepRunner<'_> {
+    /// Run the steps in order; `base` is the progress already reached.
+    async fn run(&self, spec: &PipelineSpec, base: u32) -> StepsOutcome {
+        for event in self.presenter.started() {
+            send_event(self.tx, &event).await;
         }
-    };
-
-    let research_client = Arc::new(GeminiInteractionsClient::new(&api_key));
 
-    if mode == "manual" {
-        let titles: Vec<String> = manual_hypotheses
-            .unwrap_or_default()
-            .into_iter()
-            .map(|h| h.title)
-            .filter(|t| !t.trim().is_empty())
-            .collect();
+        let mut ctx = spec.variables.clone();
+        let mut progress = base;
+        for (index, step) in spec.steps.iter().enumerate() {
+            if self.cancel.is_cancelled() {
+                return StepsOutcome::Aborted;
+            }
+            let milestone = self.presenter.milestone(step, index, spec.steps.len(), base);
+
+            let jobs = match &step.kind {
+                StepKind::ParallelMap {
+                    over,
+                    prompt,
+                    label,
+                    documents,
+                } => {
+                    match pipeline_spec::lookup(&ctx, over) {
+                        Some(Value::Array(items)) => {
+                            Some(map_jobs(items, prompt, label.as_deref(), documents, &ctx))
+                        }
+                        _ => {
+                            let message = format!("failed: '{over}' is not an array");
+                            let event = self.presenter.step_failed(index, step, message);
+                            return StepsOutcome::Failed(event);
+                        }
+                    }
+                }
+                _ => None,
+            };
+            let start = self.presenter.step_start(index, step, jobs.as_ref().map(Vec::len));
+            send_event(self.tx, &start).await;
+
+            let result = match &step.kind {
+                StepKind::Research { prompt } => self
+                    .research(pipeline_spec::render(prompt, &ctx))
+                    .await
+                    .map(Value::String),
+                StepKind::LlmStructured {
+                    prompt,
+                    schema,
+                    schema_name,
+                } => {
+                    let prompt = pipeline_spec::render(prompt, &ctx);
+                    self.structured(prompt, schema, schema_name).await
+                }
+                StepKind::ParallelMap { .. } => {
+                    let jobs = jobs.unwrap_or_default();
+                    self.parallel_map(index, step, jobs, (progress, milestone)).await
+                }
+            };
+            let output = match result {
+                Ok(output) => output,
+                Err(StepError::Failed(message)) => {
+                    return StepsOutcome::Failed(self.presenter.step_failed(index, step, message));
+                }
+                Err(StepError::Aborted) => return StepsOutcome::Aborted,
+            };
 
-        if titles.is_empty() {
-            send(&PipelineSseEvent::Error {
-                message: "Manual mode requires at least one hypothesis title".into(),
-            })
-            .await;
-            let _ = tx.send(sse_done()).await;
-            return;
+            match self.presenter.step_complete(index, step, &output) {
+                Ok(events) => {
+                    for event in &events {
+                        send_event(self.tx, event).await;
+                    }
+                }
+                Err(message) => {
+                    return StepsOutcome::Failed(self.presenter.step_failed(index, step, message));
+                }
+            }
+            send_progress(self.tx, milestone, &step.id).await;
+            progress = milestone;
+            ctx.insert(step.id.clone(), output);
         }
 
-        for (i, title) in titles.iter().enumerate() {
-            send(&PipelineSseEvent::Hypothesis {
-                index: i as u32,
-                title: title.clone(),
-                score: 0.0,
-                physical_contradiction: String::new(),
-                cap_id_fingerprint: String::new(),
-                verdict_tag: String::new(),
-                verdict_reason: String::new(),
-            })
-            .await;
+        let outputs = spec
+            .steps
+            .iter()
+            .filter_map(|step| ctx.remove(&step.id).map(|output| (step.id.clone(), output)))
+            .collect();
+        StepsOutcome::Completed(outputs)
+    }
+
+    /// One Deep Research run over the pipeline's documents.
+    async fn research(&self, prompt: String) -> Result<String, StepError> {
+        let research = research_runnable(&self.research_client);
+        let config = RunnableConfig::default();
+        let input = self.sources.input(prompt, &[]);
+        match self.cancel.run_until_cancelled(research.invoke(input, &config)).await {
+            Some(Ok(output)) => Ok(output.text),
+            Some(Err(e)) => Err(StepError::Failed(format!("failed: {e}"))),
+            None => Err(StepError::Aborted),
         }
+    }
 
-        send(&PipelineSseEvent::StepStart {
-            step: 3,
-            description: format!(
-                "Deep Research x{}: 各仮説の深掘りレポートを並列実行中...",
-                titles.len()
-            ),
-        })
-        .await;
-
-        let (step3_tx, mut step3_rx) = mpsc::channel::<(u32, String, Result<String, String>)>(16);
-
-        for (i, title) in titles.iter().enumerate() {
-            let tx_inner = tx.clone();
-            let step3_tx = step3_tx.clone();
-            let title = title.clone();
-            let idx = i as u32;
-            let prompt3 = STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", &title);
-            let input3 = DeepResearchInput::new(&prompt3).with_attachments(vec![
-                ContentPart::Text {
-                    text: format!("=== technical_assets.json ===\n{}", seeds_text),
-                },
-                ContentPart::Text {
-                    text: format!("=== target_specification.txt ===\n{}", needs_text),
-                },
-            ]);
-            let research = DeepResearchRunnable::new(research_client.clone());
-            let config = RunnableConfig::default();
+    /// One structured output call, parsed as JSON.
+    async fn structured(
+        &self,
+        prompt: String,
+        schema: &Value,
+        schema_name: &str,
+    ) -> Result<Value, StepError> {
+        let messages = vec![Message::user(prompt.as_str())];
+        let options = CallOptions {
+            response_format: Some(ResponseFormat::JsonSchema {
+                name: schema_name.into(),
+                schema: schema.clone(),
+                strict: true,
+            }),
+            ..Default::default()
+        };
 
-            let _ = tx_inner
-                .send(sse_event(&PipelineSseEvent::Step3Start {
-                    index: idx,
-                    title: title.clone(),
-                }))
-                .await;
+        let generate = self.model.generate(&messages, &options);
+        let result = match self.cancel.run_until_cancelled(generate).await {
+            Some(Ok(result)) => result,
+            Some(Err(e)) => return Err(StepError::Failed(format!("failed: {e}"))),
+            None => return Err(StepError::Aborted),
+        };
+        serde_json::from_str(result.message.content())
+            .map_err(|e| StepError::Failed(format!("JSON parse failed: {e}")))
+    }
 
+    /// Independent parallel Deep Research per element, advancing progress
+    /// from `start` to `end` as they complete.
+    async fn parallel_map(
+        &self,
+        index: usize,
+        step: &PipelineStep,
+        jobs: Vec<MapJob>,
+        (start, end): (u32, u32),
+    ) -> Result<Value, StepError> {
+        let total = jobs.len();
+        let (result_tx, mut result_rx) = mpsc::channel::<(u32, Result<String, String>)>(16);
+        let mut items = Vec::with_capacity(total);
+        let mut labels = Vec::with_capacity(total);
+
+        for (i, job) in jobs.into_iter().enumerate() {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+            let item = i as u32;
+            let event = self.presenter.item_start(index, item, job.label.clone());
+            send_event(self.tx, &event).await;
+
+            let research = research_runnable(&self.research_client);
+            let input = self.sources.input(job.prompt, &job.documents);
+            let cancel = self.cancel.clone();
+            let result_tx = result_tx.clone();
             tokio::spawn(async move {
-                let result = match research.invoke(input3, &config).await {
-                    Ok(output) => Ok(output.text),
-                    Err(e) => Err(e.to_string()),
+                let config = RunnableConfig::default();
+                let result = match cancel.run_until_cancelled(research.invoke(input, &config)).await
+                {
+                    Some(Ok(output)) => Ok(output.text),
+                    Some(Err(e)) => {
+                        warn!(item, error = %e, "Deep Research invoke failed");
+                        Err(e.to_string())
+                    }
+                    None => return,
                 };
-                let _ = step3_tx.send((idx, title, result)).await;
+                let _ = result_tx.send((item, result)).await;
             });
+            items.push(job.item);
+            labels.push(job.label);
         }
-        drop(step3_tx);
+        drop(result_tx);
 
-        let total = titles.len();
+        let mut results = vec![Value::Null; items.len()];
         let mut completed = 0u32;
-        while let Some((idx, title, result)) = step3_rx.recv().await {
+        while let Some((item, result)) = result_rx.recv().await {
             completed += 1;
-            match result {
+            let i = item as usize;
+            let label = labels[i].clone();
+            let event = match result {
                 Ok(text) => {
-                    send(&PipelineSseEvent::Step3Complete {
-                        index: idx,
-                        title,
-                        text,
-                    })
-                    .await;
+                    results[i] = serde_json::json!({ "item": items[i], "text": text });
+                    self.presenter.item_complete(index, item, label, text)
                 }
                 Err(message) => {
-                    send(&PipelineSseEvent::Step3Error {
-                        index: idx,
-                        title,
-                        message,
-                    })
-                    .await;
+                    results[i] = serde_json::json!({ "item": items[i], "error": message });
+                    self.presenter.item_failed(index, item, label, message)
                 }
-            }
-            if completed as usize == total {
-                break;
-            }
+            };
+            send_event(self.tx, &event).await;
+            let percent = progress_between(start, end, completed, total);
+            send_progress(self.tx, percent, &step.id).await;
         }
 
-        send(&PipelineSseEvent::StepComplete {
-            step: 3,
-            summary: format!("{}件の深掘りレポート完了", completed),
-        })
-        .await;
+        if self.cancel.is_cancelled() {
+            return Err(StepError::Aborted);
+        }
+        Ok(Value::Array(results))
+    }
+}
 
-        send(&PipelineSseEvent::Complete {
-            step1_text: String::new(),
-            hypotheses: serde_json::Value::Null,
-        })
-        .await;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
 
-        let _ = tx.send(sse_done()).await;
-        return;
+    fn app() -> Router {
+        Router::new().nest("/api", routes())
     }
 
-    // LLM mode fallback with inline text
+    fn docs(needs: &str, seeds: &str) -> Vec<PipelineDocument> {
+        hypothesis_documents(needs.into(), seeds.into())
+    }
 
-    send(&PipelineSseEvent::StepStart {
-        step: 1,
-        description: "Deep Research: 仮説生成レポート作成中...".into(),
-    })
-    .await;
+    #[tokio::test]
+    async fn pipeline_missing_key() {
+        unsafe {
+            std::env::remove_var("GEMINI_API_KEY");
+        }
+        let app = app();
+        let body = serde_json::json!({ "hypothesis_count": 3 });
 
-    let research = DeepResearchRunnable::new(research_client.clone());
-    let config = RunnableConfig::default();
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/pipeline/hypothesis")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
 
-    let prompt1 = STEP1_PROMPT.replace("{HYPOTHESIS_COUNT}", &hypothesis_count.to_string());
-    let input1 = DeepResearchInput::new(&prompt1).with_attachments(vec![
-        ContentPart::Text {
-            text: format!("=== target_specification.txt ===\n{}", needs_text),
-        },
-        ContentPart::Text {
-            text: format!("=== technical_assets.json ===\n{}", seeds_text),
-        },
-    ]);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
 
-    let output1 = match research.invoke(input1, &config).await {
-        Ok(o) => o,
-        Err(e) => {
-            send(&PipelineSseEvent::Error {
-                message: format!("STEP 1 failed: {}", e),
-            })
-            .await;
-            let _ = tx.send(sse_done()).await;
-            return;
-        }
-    };
+    #[test]
+    fn content_hash_is_stable_and_separates_inputs() {
+        let hash = |needs: &str, seeds: &str| content_hash(&docs(needs, seeds));
+        assert_eq!(hash("needs", "seeds"), hash("needs", "seeds"));
+        assert_ne!(hash("needs", "seeds"), hash("needs", "seeds2"));
+        assert_ne!(hash("ab", "c"), hash("a", "bc"));
+        assert_eq!(hash("", "").len(), 16);
+    }
 
-    send(&PipelineSseEvent::StepComplete {
-        step: 1,
-        summary: format!("レポート生成完了 ({} chars)", output1.text.len()),
-    })
-    .await;
+    #[test]
+    fn pipeline_request_reuse_store_defaults_off() {
+        let req: PipelineRequest = serde_json::from_str("{}").unwrap();
+        assert!(!req.reuse_store);
+        let req: PipelineRequest = serde_json::from_str(r#"{"reuse_store": true}"#).unwrap();
+        assert!(req.reuse_store);
+    }
 
-    send(&PipelineSseEvent::StepStart {
-        step: 2,
-        description: "構造化出力: 仮説をJSON抽出中...".into(),
-    })
-    .await;
+    #[tokio::test]
+    async fn setup_file_search_reuses_matching_store() {
+        use ayas_deep_research::file_search::MockFileSearchClient;
 
-    let model = GeminiChatModel::new(api_key.clone(), "gemini-2.0-flash".into());
-    let prompt2 = STEP2_PROMPT
-        .replace("{HYPOTHESIS_COUNT}", &hypothesis_count.to_string())
-        .replace("{STEP21_OUTPUT}", &output1.text);
-
-    let messages = vec![Message::user(prompt2.as_str())];
-    let options = CallOptions {
-        response_format: Some(ResponseFormat::JsonSchema {
-            name: "hypotheses".into(),
-            schema: hypothesis_schema(),
-            strict: true,
-        }),
-        ..Default::default()
-    };
+        let client = MockFileSearchClient::ready("fileSearchStores/cached");
+        let (tx, _rx) = mpsc::channel(64);
 
-    let result2 = match model.generate(&messages, &options).await {
-        Ok(r) => r,
-        Err(e) => {
-            send(&PipelineSseEvent::Error {
-                message: format!("STEP 2 failed: {}", e),
-            })
-            .await;
-            let _ = tx.send(sse_done()).await;
-            return;
-        }
-    };
+        let documents = docs("needs", "seeds");
+        let first = setup_file_search(&client, &documents, true, &tx).await.unwrap();
+        assert_eq!(first, "fileSearchStores/cached");
+        assert_eq!(client.list_stores().await.unwrap().len(), 1);
 
-    let json_text = result2.message.content().to_string();
-    let hypotheses: HypothesesOutput = match serde_json::from_str(&json_text) {
-        Ok(h) => h,
-        Err(e) => {
-            send(&PipelineSseEvent::Error {
-                message: format!("STEP 2 JSON parse failed: {}", e),
-            })
-            .await;
-            let _ = tx.send(sse_done()).await;
-            return;
-        }
-    };
+        // Same content: found via list_stores, nothing new is created
+        let second = setup_file_search(&client, &documents, true, &tx).await.unwrap();
+        assert_eq!(second, first);
+        assert_eq!(client.list_stores().await.unwrap().len(), 1);
 
-    let hypotheses_json: serde_json::Value =
-        serde_json::from_str(&json_text).unwrap_or(serde_json::Value::Null);
+        // Changed content builds a new store
+        setup_file_search(&client, &docs("needs v2", "seeds"), true, &tx).await.unwrap();
+        assert_eq!(client.list_stores().await.unwrap().len(), 2);
 
-    send(&PipelineSseEvent::StepComplete {
-        step: 2,
-        summary: format!("{}件の仮説を抽出", hypotheses.hypotheses.len()),
-    })
-    .await;
+        cleanup_store(&client, &first, true).await;
+        assert!(client.deleted_stores().is_empty());
+    }
 
-    for (i, h) in hypotheses.hypotheses.iter().enumerate() {
-        send(&PipelineSseEvent::Hypothesis {
-            index: i as u32,
-            title: h.title.clone(),
-            score: h.synthesis_score,
-            physical_contradiction: h.physical_contradiction.clone(),
-            cap_id_fingerprint: h.cap_id_fingerprint.clone(),
-            verdict_tag: h.verdict_tag.clone(),
-            verdict_reason: h.verdict_reason.clone(),
-        })
-        .await;
+    #[tokio::test]
+    async fn setup_file_search_deletes_expired_cached_stores() {
+        use ayas_deep_research::file_search::MockFileSearchClient;
+
+        let documents = docs("needs", "seeds");
+        let display_name = format!("{CACHED_STORE_PREFIX}{}", content_hash(&documents));
+        let store = |name: &str, display_name: &str, create_time: DateTime<Utc>| {
+            FileSearchStore {
+                name: name.into(),
+                display_name: display_name.into(),
+                active_documents_count: Some("2".into()),
+                pending_documents_count: Some("0".into()),
+                failed_documents_count: Some("0".into()),
+                create_time: Some(create_time.to_rfc3339()),
+            }
+        };
+        let old = Utc::now() - CACHED_STORE_TTL * 2;
+        let client = MockFileSearchClient::ready("fileSearchStores/rebuilt")
+            .with_existing_store(store("fileSearchStores/stale", &display_name, old))
+            .with_existing_store(store("fileSearchStores/other", "pipeline-cache-0", old))
+            .with_existing_store(store("fileSearchStores/mine", "my-store", old))
+            .with_existing_store(store("fileSearchStores/fresh", "pipeline-cache-1", Utc::now()));
+        let (tx, _rx) = mpsc::channel(64);
+
+        let name = setup_file_search(&client, &documents, true, &tx).await.unwrap();
+        assert_eq!(name, "fileSearchStores/rebuilt");
+        assert_eq!(client.deleted_stores(), ["fileSearchStores/stale", "fileSearchStores/other"]);
     }
 
-    send(&PipelineSseEvent::StepStart {
-        step: 3,
-        description: format!(
-            "Deep Research x{}: 各仮説の深掘りレポートを並列実行中...",
-            hypotheses.hypotheses.len()
-        ),
-    })
-    .await;
+    #[tokio::test]
+    async fn setup_file_search_without_reuse_always_creates() {
+        use ayas_deep_research::file_search::MockFileSearchClient;
+
+        let client = MockFileSearchClient::ready("fileSearchStores/tmp");
+        let (tx, _rx) = mpsc::channel(64);
+
+        let documents = docs("needs", "seeds");
+        let name = setup_file_search(&client, &documents, false, &tx).await.unwrap();
+        setup_file_search(&client, &documents, false, &tx).await.unwrap();
+        let stores = client.list_stores().await.unwrap();
+        assert_eq!(stores.len(), 2);
+        assert!(stores.iter().all(|s| {
+            s.display_name.starts_with("pipeline-")
+                && !s.display_name.starts_with(CACHED_STORE_PREFIX)
+        }));
+
+        cleanup_store(&client, &name, false).await;
+        assert_eq!(client.deleted_stores(), [name]);
+    }
 
-    let (step3_tx, mut step3_rx) = mpsc::channel::<(u32, String, Result<String, String>)>(16);
-
-    for (i, h) in hypotheses.hypotheses.iter().enumerate() {
-        let tx_inner = tx.clone();
-        let step3_tx = step3_tx.clone();
-        let title = h.title.clone();
-        let idx = i as u32;
-        let prompt3 = STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", &title);
-        let input3 = DeepResearchInput::new(&prompt3).with_attachments(vec![
-            ContentPart::Text {
-                text: format!("=== hypothesis_context ===\n{}", output1.text),
-            },
-            ContentPart::Text {
-                text: format!("=== technical_assets.json ===\n{}", seeds_text),
-            },
-            ContentPart::Text {
-                text: format!("=== target_specification.txt ===\n{}", needs_text),
-            },
-        ]);
-        let research = DeepResearchRunnable::new(research_client.clone());
-        let config = RunnableConfig::default();
+    #[test]
+    fn progress_advances_per_completion() {
+        let percents: Vec<u32> = (0..=3)
+            .map(|done| progress_between(PROGRESS_STEP2, PROGRESS_STEPS_DONE, done, 3))
+            .collect();
+        assert_eq!(percents, vec![60, 71, 83, 95]);
+        assert_eq!(progress_between(0, PROGRESS_STEPS_DONE, 1, 2), 47);
+        assert_eq!(progress_between(PROGRESS_STEP2, 95, 0, 0), PROGRESS_STEPS_DONE);
+    }
 
-        let _ = tx_inner
-            .send(sse_event(&PipelineSseEvent::Step3Start {
-                index: idx,
-                title: title.clone(),
-            }))
-            .await;
+    #[test]
+    fn progress_event_json() {
+        let event = PipelineSseEvent::Progress {
+            percent: 42,
+            stage: "step3".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"type": "progress", "percent": 42, "stage": "step3"})
+        );
+    }
 
-        tokio::spawn(async move {
-            let result = match research.invoke(input3, &config).await {
-                Ok(output) => Ok(output.text),
-                Err(e) => Err(e.to_string()),
-            };
-            let _ = step3_tx.send((idx, title, result)).await;
-        });
+    #[test]
+    fn extraction_model_defaults_to_gemini_flash() {
+        let keys = ApiKeys {
+            gemini_key: Some("gk".into()),
+            ..Default::default()
+        };
+        let model = build_extraction_model(&keys, None, None).unwrap();
+        assert_eq!(model.model_name(), DEFAULT_EXTRACTION_MODEL);
     }
-    drop(step3_tx);
-
-    let total = hypotheses.hypotheses.len();
-    let mut completed = 0u32;
-    while let Some((idx, title, result)) = step3_rx.recv().await {
-        completed += 1;
-        match result {
-            Ok(text) => {
-                send(&PipelineSseEvent::Step3Complete {
-                    index: idx,
-                    title,
-                    text,
-                })
-                .await;
-            }
-            Err(message) => {
-                send(&PipelineSseEvent::Step3Error {
-                    index: idx,
-                    title,
-                    message,
-                })
-                .await;
-            }
-        }
-        if completed as usize == total {
-            break;
-        }
+
+    #[test]
+    fn extraction_model_uses_requested_provider() {
+        let keys = ApiKeys {
+            gemini_key: Some("gk".into()),
+            anthropic_key: Some("ak".into()),
+            ..Default::default()
+        };
+        let model = build_extraction_model(
+            &keys,
+            Some(Provider::Claude),
+            Some("claude-sonnet-4-5-20250929".into()),
+        )
+        .unwrap();
+        assert_eq!(model.model_name(), "claude-sonnet-4-5-20250929");
+
+        let model = build_extraction_model(&keys, Some(Provider::Claude), None).unwrap();
+        assert_eq!(model.model_name(), Provider::Claude.default_models()[0]);
     }
 
-    send(&PipelineSseEvent::StepComplete {
-        step: 3,
-        summary: format!("{}件の深掘りレポート完了", completed),
-    })
-    .await;
+    #[test]
+    fn extraction_model_errors_are_reported_up_front() {
+        let keys = ApiKeys {
+            gemini_key: Some("gk".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            build_extraction_model(&keys, Some(Provider::OpenAI), None),
+            Err(AppError::MissingApiKey(_))
+        ));
+        assert!(matches!(
+            build_extraction_model(&keys, None, Some("gemini-2.0-flsh".into())),
+            Err(AppError::Ayas(_))
+        ));
+    }
 
-    send(&PipelineSseEvent::Complete {
-        step1_text: output1.text,
-        hypotheses: hypotheses_json,
-    })
-    .await;
+    #[test]
+    fn pipeline_runs_abort_cancels_once() {
+        let runs = PipelineRuns::new();
+        let token = CancellationToken::new();
+        runs.register("run-1", token.clone());
 
-    let _ = tx.send(sse_done()).await;
-}
+        assert!(!runs.abort("other"));
+        assert!(runs.abort("run-1"));
+        assert!(token.is_cancelled());
+        assert!(!runs.abort("run-1"));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::body::Body;
-    use axum::http::{Request, StatusCode, header};
-    use tower::ServiceExt;
+    #[test]
+    fn tracked_run_is_removed_on_drop() {
+        let runs = PipelineRuns::new();
+        let registered = runs.track("run-1", CancellationToken::new());
+        drop(registered);
+        assert!(!runs.abort("run-1"));
+    }
 
-    fn app() -> Router {
-        Router::new().nest("/api", routes())
+    #[tokio::test]
+    async fn pipeline_abort_endpoint() {
+        let state = PipelineState::default();
+        let token = CancellationToken::new();
+        state.runs.register("run-1", token.clone());
+        let app = Router::new().nest("/api", routes_with_state(state));
+
+        let abort = |id: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/pipeline/{id}/abort"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let resp = app.clone().oneshot(abort("run-1")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(token.is_cancelled());
+
+        let resp = app.oneshot(abort("run-1")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn pipeline_missing_key() {
-        unsafe {
-            std::env::remove_var("GEMINI_API_KEY");
+    async fn abort_run_cleans_up_and_ends_stream() {
+        use ayas_deep_research::file_search::MockFileSearchClient;
+
+        let client = MockFileSearchClient::ready("fileSearchStores/tmp");
+        let (tx, mut rx) = mpsc::channel(8);
+        abort_run(&tx, &client, "fileSearchStores/tmp", false).await;
+        drop(tx);
+
+        assert_eq!(client.deleted_stores(), ["fileSearchStores/tmp"]);
+        let mut events = 0;
+        while rx.recv().await.is_some() {
+            events += 1;
         }
-        let app = app();
-        let body = serde_json::json!({ "hypothesis_count": 3 });
+        // Error{aborted} followed by [DONE]
+        assert_eq!(events, 2);
+    }
 
-        let resp = app
+    #[test]
+    fn hypothesis_spec_renders_the_demo_prompts() {
+        let spec = hypothesis_spec(3, docs("needs", "seeds"));
+        assert!(spec.validate().is_ok());
+        let ids: Vec<&str> = spec.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, HYPOTHESIS_STEPS);
+
+        let mut ctx = spec.variables.clone();
+        let StepKind::Research { prompt } = &spec.steps[0].kind else {
+            panic!("STEP 1 should be research");
+        };
+        assert_eq!(
+            pipeline_spec::render(prompt, &ctx),
+            STEP1_PROMPT.replace("{HYPOTHESIS_COUNT}", "3")
+        );
+
+        ctx.insert("step1".into(), "REPORT".into());
+        let StepKind::LlmStructured { prompt, .. } = &spec.steps[1].kind else {
+            panic!("STEP 2 should be structured extraction");
+        };
+        assert_eq!(
+            pipeline_spec::render(prompt, &ctx),
+            STEP2_PROMPT
+                .replace("{HYPOTHESIS_COUNT}", "3")
+                .replace("{STEP21_OUTPUT}", "REPORT")
+        );
+
+        ctx.insert(
+            "step2".into(),
+            serde_json::json!({"hypotheses": [{"title": "A"}, {"title": "B"}]}),
+        );
+        let StepKind::ParallelMap {
+            over,
+            prompt,
+            label,
+            documents,
+        } = &spec.steps[2].kind
+        else {
+            panic!("STEP 3 should be a parallel map");
+        };
+        let Some(Value::Array(items)) = pipeline_spec::lookup(&ctx, over) else {
+            panic!("STEP 3 should map over the extracted hypotheses");
+        };
+        let jobs = map_jobs(items, prompt, label.as_deref(), documents, &ctx);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[1].label, "B");
+        assert_eq!(jobs[1].prompt, STEP3_PROMPT.replace("{HYPOTHESIS_TITLE}", "B"));
+
+        // Without File Search each STEP 3 run gets the STEP 1 report inline
+        let inline = ResearchSources::inline(&spec.documents);
+        let input = inline.input(jobs[1].prompt.clone(), &jobs[1].documents);
+        assert_eq!(input.attachments.len(), 3);
+        assert_eq!(
+            input.attachments[2],
+            document_part("hypothesis_context", "REPORT")
+        );
+
+        let file_search = ResearchSources::FileSearch("store".into());
+        let input = file_search.input(jobs[1].prompt.clone(), &jobs[1].documents);
+        assert!(input.attachments.is_empty());
+    }
+
+    #[test]
+    fn manual_spec_runs_only_step3() {
+        let spec = manual_hypothesis_spec(&["A".into(), "B".into()], docs("needs", "seeds"));
+        assert!(spec.validate().is_ok());
+        assert_eq!(spec.steps.len(), 1);
+        assert_eq!(hypothesis_step_number(&spec.steps[0]), 3);
+
+        let presenter = HypothesisPresenter {
+            manual_titles: vec!["A".into(), "B".into()],
+        };
+        assert_eq!(presenter.started().len(), 2);
+        assert_eq!(presenter.milestone(&spec.steps[0], 0, 1, PROGRESS_INDEXED), 95);
+    }
+
+    #[test]
+    fn hypothesis_presenter_keeps_the_hypothesis_events() {
+        let spec = hypothesis_spec(1, Vec::new());
+        let presenter = HypothesisPresenter::default();
+        let to_json = |event: &PipelineSseEvent| serde_json::to_value(event).unwrap();
+
+        let output = serde_json::json!({"hypotheses": [{
+            "title": "A",
+            "physical_contradiction": "pc",
+            "cap_id_fingerprint": "cap",
+            "verdict_tag": "tag",
+            "verdict_reason": "why",
+            "synthesis_score": 0.5
+        }]});
+        let events = presenter.step_complete(1, &spec.steps[1], &output).unwrap();
+        assert_eq!(
+            to_json(&events[0]),
+            serde_json::json!({"type": "step_complete", "step": 2, "summary": "1件の仮説を抽出"})
+        );
+        assert_eq!(to_json(&events[1])["type"], "hypothesis");
+        assert_eq!(to_json(&events[1])["score"], 0.5);
+
+        let bad = serde_json::json!({"hypotheses": [{"title": "A"}]});
+        let message = presenter.step_complete(1, &spec.steps[1], &bad).unwrap_err();
+        let event = to_json(&presenter.step_failed(1, &spec.steps[1], message));
+        assert!(event["message"].as_str().unwrap().starts_with("STEP 2 JSON parse failed"));
+
+        let event = presenter.item_complete(2, 0, "A".into(), "text".into());
+        assert_eq!(
+            to_json(&event),
+            serde_json::json!({"type": "step3_complete", "index": 0, "title": "A", "text": "text"})
+        );
+
+        let mut outputs = Map::new();
+        outputs.insert("step1".into(), "REPORT".into());
+        outputs.insert("step2".into(), output.clone());
+        assert_eq!(
+            to_json(&presenter.complete(outputs)),
+            serde_json::json!({"type": "complete", "step1_text": "REPORT", "hypotheses": output})
+        );
+    }
+
+    #[test]
+    fn spec_presenter_splits_progress_evenly() {
+        let spec = hypothesis_spec(1, Vec::new());
+        let milestones: Vec<u32> = (0..3)
+            .map(|i| SpecPresenter.milestone(&spec.steps[i], i, 3, PROGRESS_INDEXED))
+            .collect();
+        assert_eq!(milestones, vec![45, 70, 95]);
+    }
+
+    #[tokio::test]
+    async fn pipeline_run_rejects_invalid_spec() {
+        let body = serde_json::json!({
+            "steps": [{"id": "deep", "type": "parallel_map", "over": "missing", "prompt": "p"}]
+        });
+
+        let resp = app()
             .oneshot(
                 Request::builder()
                     .method("POST")
-                    .uri("/api/pipeline/hypothesis")
+                    .uri("/api/pipeline/run")
                     .header(header::CONTENT_TYPE, "application/json")
+                    .header("X-Gemini-Key", "gk")
                     .body(Body::from(serde_json::to_string(&body).unwrap()))
                     .unwrap(),
             )
@@ -1018,6 +1828,31 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn manual_mode_requires_a_title() {
+        let body = serde_json::json!({"mode": "manual", "hypotheses": [{"title": "  "}]});
+
+        let resp = app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/pipeline/hypothesis")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header("X-Gemini-Key", "gk")
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("\"type\":\"error\""), "{text}");
+        assert!(text.contains("Manual mode requires at least one hypothesis title"), "{text}");
+        assert!(text.contains("[DONE]"), "{text}");
+    }
+
     #[tokio::test]
     async fn pipeline_invalid_json() {
         let app = app();
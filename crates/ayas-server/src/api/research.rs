@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{Json, Router, routing::post};
 use axum::response::Sse;
@@ -7,15 +8,20 @@ use futures::stream;
 use futures::Stream;
 
 use ayas_core::config::RunnableConfig;
+use ayas_core::error::{AyasError, ModelError};
 use ayas_core::runnable::Runnable;
 use ayas_deep_research::gemini::GeminiInteractionsClient;
 use ayas_deep_research::runnable::{DeepResearchInput, DeepResearchRunnable};
+use ayas_deep_research::types::Citation;
 
 use crate::error::AppError;
 use crate::extractors::ApiKeys;
 use crate::sse::{sse_done, sse_event};
 use crate::types::ResearchInvokeRequest;
 
+/// Upper bound on one research interaction before a `timeout` event is sent.
+const RESEARCH_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
 pub fn routes() -> Router {
     Router::new().route("/research/invoke", post(research_invoke))
 }
@@ -24,7 +30,13 @@ pub fn routes() -> Router {
 #[serde(tag = "type", rename_all = "snake_case")]
 enum ResearchSseEvent {
     Progress { message: String },
-    Complete { text: String, interaction_id: String },
+    Complete {
+        text: String,
+        interaction_id: String,
+        citations: Vec<Citation>,
+    },
+    /// The interaction did not finish in time; it may still be resumed.
+    Timeout { interaction_id: String, message: String },
     Error { message: String },
 }
 
@@ -36,7 +48,7 @@ async fn research_invoke(
     let api_key = api_keys.get_key_for(&ayas_llm::provider::Provider::Gemini)?;
 
     let client = Arc::new(GeminiInteractionsClient::new(api_key));
-    let runnable = DeepResearchRunnable::new(client);
+    let runnable = DeepResearchRunnable::new(client).with_timeout(RESEARCH_TIMEOUT);
 
     let mut input = DeepResearchInput::new(&req.query);
     if !req.attachments.is_empty() {
@@ -48,6 +60,9 @@ async fn research_invoke(
     if let Some(prev_id) = req.previous_interaction_id {
         input = input.with_previous_interaction_id(prev_id);
     }
+    if let Some(resume_id) = req.resume_interaction_id {
+        input = input.with_resume_interaction_id(resume_id);
+    }
 
     let mut events: Vec<Result<Event, std::convert::Infallible>> = Vec::new();
 
@@ -61,6 +76,17 @@ async fn research_invoke(
             events.push(sse_event(&ResearchSseEvent::Complete {
                 text: output.text,
                 interaction_id: output.interaction_id,
+                citations: output.citations,
+            }));
+        }
+        Err(AyasError::Model(ModelError::DeepResearchTimeout {
+            interaction_id,
+            polls,
+            elapsed_secs,
+        })) => {
+            events.push(sse_event(&ResearchSseEvent::Timeout {
+                message: format!("Timed out after {polls} polls ({elapsed_secs}s)"),
+                interaction_id,
             }));
         }
         Err(e) => {
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+use tokio::sync::RwLock;
+
+use ayas_core::error::{AyasError, Result};
+use ayas_core::message::Message;
+
+/// How long a conversation may sit idle before it is forgotten.
+pub const DEFAULT_CONVERSATION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Message history of chat sessions, keyed by session id.
+///
+/// A session expires once it has not been appended to for the store's TTL;
+/// reading an expired session returns no messages and appending to it starts
+/// a new history.
+#[async_trait]
+pub trait ConversationStore: Send + Sync {
+    /// The messages of a session, oldest first.
+    async fn get(&self, session_id: &str) -> Result<Vec<Message>>;
+
+    /// Append messages to a session, creating it if needed.
+    async fn append(&self, session_id: &str, messages: &[Message]) -> Result<()>;
+}
+
+/// Pick the conversation store from the environment.
+///
+/// `AYAS_CONVERSATION_DB` names a SQLite file to persist conversations in;
+/// otherwise they are kept in memory. `AYAS_CONVERSATION_TTL_SECS` overrides
+/// [`DEFAULT_CONVERSATION_TTL`].
+pub fn conversation_store_from_env() -> Arc<dyn ConversationStore> {
+    let ttl = std::env::var("AYAS_CONVERSATION_TTL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_CONVERSATION_TTL);
+    if let Ok(path) = std::env::var("AYAS_CONVERSATION_DB") {
+        match SqliteConversationStore::new(&path, ttl) {
+            Ok(store) => return Arc::new(store),
+            Err(e) => tracing::error!("Failed to open conversation database {path}: {e}"),
+        }
+    }
+    Arc::new(InMemoryConversationStore::new(ttl))
+}
+
+fn ttl_millis(ttl: Duration) -> i64 {
+    i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX)
+}
+
+struct Conversation {
+    messages: Vec<Message>,
+    updated_at: i64,
+}
+
+/// In-memory conversation store; sessions are lost on restart.
+pub struct InMemoryConversationStore {
+    ttl_ms: i64,
+    sessions: RwLock<HashMap<String, Conversation>>,
+}
+
+impl InMemoryConversationStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl_ms: ttl_millis(ttl),
+            sessions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn is_live(&self, conversation: &Conversation, now: i64) -> bool {
+        now.saturating_sub(conversation.updated_at) < self.ttl_ms
+    }
+}
+
+impl Default for InMemoryConversationStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONVERSATION_TTL)
+    }
+}
+
+#[async_trait]
+impl ConversationStore for InMemoryConversationStore {
+    async fn get(&self, session_id: &str) -> Result<Vec<Message>> {
+        let now = Utc::now().timestamp_millis();
+        let sessions = self.sessions.read().await;
+        Ok(sessions
+            .get(session_id)
+            .filter(|c| self.is_live(c, now))
+            .map(|c| c.messages.clone())
+            .unwrap_or_default())
+    }
+
+    async fn append(&self, session_id: &str, messages: &[Message]) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, c| self.is_live(c, now));
+        let conversation = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| Conversation {
+                messages: Vec::new(),
+                updated_at: now,
+            });
+        conversation.messages.extend_from_slice(messages);
+        conversation.updated_at = now;
+        Ok(())
+    }
+}
+
+/// SQLite-backed conversation store that survives restarts.
+///
+/// Operations run on a blocking thread, like `SqliteCheckpointStore`.
+pub struct SqliteConversationStore {
+    conn: Arc<Mutex<Connection>>,
+    ttl_ms: i64,
+}
+
+fn sqlite_error(context: &str, e: impl std::fmt::Display) -> AyasError {
+    AyasError::Other(format!("conversation store: {context}: {e}"))
+}
+
+impl SqliteConversationStore {
+    /// Open (or create) a SQLite database at the given path.
+    pub fn new(path: impl AsRef<Path>, ttl: Duration) -> Result<Self> {
+        let conn = Connection::open(path).map_err(|e| sqlite_error("open database", e))?;
+        Self::with_connection(conn, ttl)
+    }
+
+    /// Create an in-memory SQLite database (useful for tests).
+    pub fn in_memory(ttl: Duration) -> Result<Self> {
+        let conn = Connection::open_in_memory().map_err(|e| sqlite_error("open database", e))?;
+        Self::with_connection(conn, ttl)
+    }
+
+    fn with_connection(conn: Connection, ttl: Duration) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS conversations (
+                session_id TEXT NOT NULL PRIMARY KEY,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS conversation_messages (
+                session_id TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                message TEXT NOT NULL,
+                PRIMARY KEY (session_id, seq)
+            );",
+        )
+        .map_err(|e| sqlite_error("create tables", e))?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            ttl_ms: ttl_millis(ttl),
+        })
+    }
+}
+
+/// Delete sessions idle since before `cutoff` along with their messages.
+fn purge_idle(conn: &Connection, cutoff: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM conversation_messages WHERE session_id IN
+            (SELECT session_id FROM conversations WHERE updated_at <= ?1)",
+        params![cutoff],
+    )?;
+    conn.execute("DELETE FROM conversations WHERE updated_at <= ?1", params![cutoff])?;
+    Ok(())
+}
+
+#[async_trait]
+impl ConversationStore for SqliteConversationStore {
+    async fn get(&self, session_id: &str) -> Result<Vec<Message>> {
+        let conn = Arc::clone(&self.conn);
+        let session_id = session_id.to_string();
+        let cutoff = Utc::now().timestamp_millis().saturating_sub(self.ttl_ms);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().unwrap();
+            let updated_at: Option<i64> = conn
+                .query_row(
+                    "SELECT updated_at FROM conversations WHERE session_id = ?1",
+                    params![session_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| sqlite_error("read session", e))?;
+            if updated_at.is_none_or(|t| t <= cutoff) {
+                return Ok(Vec::new());
+            }
+
+            let mut stmt = conn
+                .prepare(
+                    "SELECT message FROM conversation_messages
+                     WHERE session_id = ?1 ORDER BY seq ASC",
+                )
+                .map_err(|e| sqlite_error("read messages", e))?;
+            let rows = stmt
+                .query_map(params![session_id], |row| row.get::<_, String>(0))
+                .map_err(|e| sqlite_error("read messages", e))?;
+            let mut messages = Vec::new();
+            for row in rows {
+                let json = row.map_err(|e| sqlite_error("read messages", e))?;
+                messages.push(serde_json::from_str(&json)?);
+            }
+            Ok(messages)
+        })
+        .await
+        .map_err(|e| sqlite_error("spawn_blocking", e))?
+    }
+
+    async fn append(&self, session_id: &str, messages: &[Message]) -> Result<()> {
+        let conn = Arc::clone(&self.conn);
+        let session_id = session_id.to_string();
+        let encoded = messages
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let now = Utc::now().timestamp_millis();
+        let cutoff = now.saturating_sub(self.ttl_ms);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap();
+            let tx = conn
+                .transaction()
+                .map_err(|e| sqlite_error("begin transaction", e))?;
+            purge_idle(&tx, cutoff).map_err(|e| sqlite_error("purge idle sessions", e))?;
+            tx.execute(
+                "INSERT INTO conversations (session_id, updated_at) VALUES (?1, ?2)
+                 ON CONFLICT (session_id) DO UPDATE SET updated_at = excluded.updated_at",
+                params![session_id, now],
+            )
+            .map_err(|e| sqlite_error("upsert session", e))?;
+            let next_seq: i64 = tx
+                .query_row(
+                    "SELECT COALESCE(MAX(seq) + 1, 0) FROM conversation_messages
+                     WHERE session_id = ?1",
+                    params![session_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| sqlite_error("read sequence", e))?;
+            for (offset, json) in encoded.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO conversation_messages (session_id, seq, message)
+                     VALUES (?1, ?2, ?3)",
+                    params![session_id, next_seq + offset as i64, json],
+                )
+                .map_err(|e| sqlite_error("insert message", e))?;
+            }
+            tx.commit().map_err(|e| sqlite_error("commit", e))
+        })
+        .await
+        .map_err(|e| sqlite_error("spawn_blocking", e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn roundtrip(store: &dyn ConversationStore) {
+        store.append("s1", &[Message::user("hi")]).await.unwrap();
+        store.append("s1", &[Message::ai("hello")]).await.unwrap();
+        store.append("s2", &[Message::user("other")]).await.unwrap();
+
+        let messages = store.get("s1").await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content(), "hi");
+        assert_eq!(messages[1].content(), "hello");
+        assert!(store.get("missing").await.unwrap().is_empty());
+    }
+
+    async fn expires_idle_sessions(store: &dyn ConversationStore) {
+        store.append("s1", &[Message::user("hi")]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(store.get("s1").await.unwrap().is_empty());
+
+        store.append("s1", &[Message::user("again")]).await.unwrap();
+        let messages = store.get("s1").await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content(), "again");
+    }
+
+    #[tokio::test]
+    async fn in_memory_roundtrip() {
+        roundtrip(&InMemoryConversationStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn in_memory_expires_idle_sessions() {
+        expires_idle_sessions(&InMemoryConversationStore::new(Duration::from_millis(40))).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_roundtrip() {
+        let store = SqliteConversationStore::in_memory(DEFAULT_CONVERSATION_TTL).unwrap();
+        roundtrip(&store).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_expires_idle_sessions() {
+        let store = SqliteConversationStore::in_memory(Duration::from_millis(40)).unwrap();
+        expires_idle_sessions(&store).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("conversations.db");
+        let store = SqliteConversationStore::new(&path, DEFAULT_CONVERSATION_TTL).unwrap();
+        store.append("s1", &[Message::user("hi")]).await.unwrap();
+        drop(store);
+
+        let reopened = SqliteConversationStore::new(&path, DEFAULT_CONVERSATION_TTL).unwrap();
+        assert_eq!(reopened.get("s1").await.unwrap().len(), 1);
+    }
+}
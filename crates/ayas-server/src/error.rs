@@ -20,6 +20,18 @@ impl From<AyasError> for AppError {
     }
 }
 
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::MissingApiKey(provider) => write!(f, "Missing API key for {provider}"),
+            AppError::Ayas(err) => write!(f, "{err}"),
+            AppError::BadRequest(msg) | AppError::Internal(msg) | AppError::NotFound(msg) => {
+                f.write_str(msg)
+            }
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
@@ -34,6 +46,18 @@ impl IntoResponse for AppError {
             AppError::Ayas(AyasError::Model(ModelError::RateLimited { .. })) => {
                 (StatusCode::TOO_MANY_REQUESTS, "Rate limited".into())
             }
+            AppError::Ayas(AyasError::Model(err @ ModelError::UnknownModel { .. })) => {
+                (StatusCode::BAD_REQUEST, err.to_string())
+            }
+            AppError::Ayas(AyasError::Model(err @ ModelError::ContentFiltered { .. })) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, err.to_string())
+            }
+            AppError::Ayas(AyasError::Model(err @ ModelError::DeepResearchTimeout { .. })) => {
+                (StatusCode::GATEWAY_TIMEOUT, err.to_string())
+            }
+            AppError::Ayas(AyasError::Model(err @ ModelError::Unsupported(_))) => {
+                (StatusCode::NOT_IMPLEMENTED, err.to_string())
+            }
             AppError::Ayas(AyasError::Graph(GraphError::RecursionLimit { limit })) => (
                 StatusCode::BAD_REQUEST,
                 format!("Recursion limit ({limit}) exceeded"),
@@ -59,6 +83,33 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn missing_api_key_displays_provider() {
+        let err = AppError::MissingApiKey("gemini".into());
+        assert_eq!(err.to_string(), "Missing API key for gemini");
+    }
+
+    #[test]
+    fn unknown_model_returns_400() {
+        let err = AppError::Ayas(AyasError::Model(ModelError::UnknownModel {
+            model: "gemini-flsh".into(),
+            suggestions: vec!["gemini-flash".into()],
+        }));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn deep_research_timeout_returns_504() {
+        let err = AppError::Ayas(AyasError::Model(ModelError::DeepResearchTimeout {
+            interaction_id: "int-1".into(),
+            polls: 3,
+            elapsed_secs: 15,
+        }));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
     #[test]
     fn auth_error_returns_401() {
         let err = AppError::Ayas(AyasError::Model(ModelError::Auth("bad key".into())));
@@ -75,6 +126,23 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
     }
 
+    #[test]
+    fn content_filtered_returns_422() {
+        let err = AppError::Ayas(AyasError::Model(ModelError::ContentFiltered {
+            reason: "SAFETY".into(),
+            categories: vec![],
+        }));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn unsupported_returns_501() {
+        let err = AppError::Ayas(AyasError::Model(ModelError::Unsupported("nope".into())));
+        let resp = err.into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_IMPLEMENTED);
+    }
+
     #[test]
     fn recursion_limit_returns_400() {
         let err = AppError::Ayas(AyasError::Graph(GraphError::RecursionLimit { limit: 25 }));
@@ -2,31 +2,41 @@ use std::sync::Arc;
 
 use serde_json::{json, Value};
 
-use ayas_checkpoint::prelude::interrupt_output;
+use ayas_checkpoint::prelude::{
+    interrupt_output, interrupt_output_for, send_output, SendDirective,
+};
 use ayas_core::config::RunnableConfig;
-use ayas_core::error::{AyasError, Result};
-use ayas_core::message::{AIContent, ContentPart, ContentSource, Message};
-use ayas_core::model::{CallOptions, ChatModel, ResponseFormat};
+use ayas_core::error::{AyasError, GraphError, Result};
+use ayas_core::message::{AIContent, ContentPart, ContentSource, Message, UsageMetadata};
+use ayas_core::model::{generate_with_config, CallOptions, ChatModel, ResponseFormat};
 use ayas_core::runnable::Runnable;
-use ayas_core::tool::Tool;
+use ayas_core::tool::{CoercingTool, Tool};
 use ayas_deep_research::client::InteractionsClient;
 use ayas_deep_research::runnable::{DeepResearchInput, DeepResearchRunnable};
 use ayas_deep_research::types::ToolConfig;
-use ayas_agent::react::create_react_agent;
+use ayas_adl::expression::CompiledExpression;
+use ayas_adl::policy::NodePolicy;
+use ayas_agent::react::{create_react_agent_with_options, ReactAgentOptions};
 use ayas_graph::channel::ChannelSpec;
 use ayas_graph::compiled::CompiledStateGraph;
 use ayas_graph::constants::END;
 use ayas_graph::edge::{ConditionalEdge, ConditionalFanOutEdge};
 use ayas_graph::node::NodeFn;
 use ayas_graph::state_graph::StateGraph;
+use ayas_graph::subgraph::subgraph_node;
 use ayas_llm::provider::Provider;
+use ayas_rag::embedding::Embedding;
+use ayas_rag::retriever::Retriever;
+use ayas_rag::store::VectorStore;
+use ayas_rag::types::SearchOptions;
 
 use crate::extractors::ApiKeys;
+use crate::tools::web_search::WebSearchTool;
 use crate::types::{GraphChannelDto, GraphEdgeDto, GraphNodeDto};
 
 /// Factory function type for creating ChatModel instances in graph context.
 pub type GraphModelFactory =
-    Arc<dyn Fn(&Provider, String, String) -> Box<dyn ChatModel> + Send + Sync>;
+    Arc<dyn Fn(&Provider, String, String) -> Result<Box<dyn ChatModel>> + Send + Sync>;
 
 /// Factory function type for creating InteractionsClient instances for Deep Research.
 pub type GraphResearchFactory =
@@ -36,12 +46,21 @@ pub type GraphResearchFactory =
 pub type GraphToolsFactory =
     Arc<dyn Fn(&[String]) -> Vec<Arc<dyn Tool>> + Send + Sync>;
 
+/// A vector store together with the embedding model its vectors were built with.
+pub type RetrievalBackend = (Arc<dyn Embedding>, Arc<dyn VectorStore>);
+
+/// Factory function type for resolving a named vector store for retrieve nodes.
+pub type GraphRetrievalFactory =
+    Arc<dyn Fn(&str, &ApiKeys) -> Result<RetrievalBackend> + Send + Sync>;
+
 /// Context for building a graph with real LLM execution.
+#[derive(Clone)]
 pub struct GraphBuildContext {
     pub factory: GraphModelFactory,
     pub api_keys: ApiKeys,
     pub research_factory: Option<GraphResearchFactory>,
     pub tools_factory: Option<GraphToolsFactory>,
+    pub retrieval_factory: Option<GraphRetrievalFactory>,
 }
 
 /// Convert frontend graph DTOs into a compiled StateGraph (backward-compatible).
@@ -124,53 +143,81 @@ pub fn convert_to_state_graph_with_context(
         graph.add_channel("__error", ChannelSpec::LastValue { default: Value::Null });
     }
 
+    // Nodes that can recover from failures (error edges or retries) log each failed attempt
+    let recovers_errors = !error_edge_nodes.is_empty()
+        || nodes.iter().any(|n| {
+            n.config
+                .as_ref()
+                .and_then(|c| c.get("max_retries"))
+                .and_then(|v| v.as_u64())
+                .is_some_and(|r| r > 0)
+        });
+    if recovers_errors {
+        graph.add_channel(ERRORS_CHANNEL, ChannelSpec::Append);
+    }
+
+    // LLM nodes with a model report token usage; add their usage channels unless declared
+    let usage_channels: std::collections::HashSet<&str> = nodes
+        .iter()
+        .filter(|n| context.is_some() && n.node_type == "llm")
+        .map(|n| n.config.as_ref().map_or(USAGE_CHANNEL, usage_channel))
+        .filter(|key| !channels.iter().any(|ch| ch.key == *key))
+        .collect();
+    for key in usage_channels {
+        graph.add_channel(key, ChannelSpec::LastValue { default: Value::Null });
+    }
+
     // Wrap context in Arc so it can be shared across node closures
     let ctx = context.map(Arc::new);
 
     // Helper: wrap a node function with retry + error catching when needed
     // If the node has on_error edges, catch errors and set state.__error instead of propagating
     // If the node has max_retries in config, retry with exponential backoff
-    fn wrap_node_fn<F>(
-        id: String,
-        has_error_edge: bool,
-        max_retries: usize,
-        inner: F,
-    ) -> NodeFn
+    // If the node has timeout_ms in config, each attempt fails after that long
+    // Every failed attempt is appended to state.__errors as {node, message, attempt}
+    fn wrap_node_fn<F>(id: String, has_error_edge: bool, policy: NodePolicy, inner: F) -> NodeFn
     where
-        F: Fn(Value) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Value>> + Send>>
+        F: Fn(
+                Value,
+                RunnableConfig,
+            ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Value>> + Send>>
             + Send
             + Sync
             + 'static,
     {
         let inner = Arc::new(inner);
-        NodeFn::new(id, move |state: Value, _cfg| {
+        let node_id = id.clone();
+        NodeFn::new(id, move |state: Value, run_config: RunnableConfig| {
             let inner = inner.clone();
-            let state_clone = state.clone();
+            let node_id = node_id.clone();
+            let policy = policy.clone();
             Box::pin(async move {
-                let mut last_err = String::new();
-                for attempt in 0..=max_retries {
-                    if attempt > 0 {
-                        let delay = std::time::Duration::from_millis(100 * (1 << (attempt - 1)));
-                        tokio::time::sleep(delay).await;
-                    }
-                    match inner(state_clone.clone()).await {
-                        Ok(val) => return Ok(val),
-                        Err(e) => {
-                            last_err = e.to_string();
-                            if attempt == max_retries {
-                                break;
-                            }
-                        }
-                    }
+                let run = policy
+                    .run_attempts(&node_id, || inner(state.clone(), run_config.clone()))
+                    .await;
+                let (result, errors) = match run {
+                    Ok((output, errors)) => (Some(output), errors),
+                    Err(errors) => (None, errors),
+                };
+                let failures: Vec<Value> = errors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        json!({ "node": node_id, "message": e.to_string(), "attempt": i + 1 })
+                    })
+                    .collect();
+                if let Some(output) = result {
+                    return Ok(with_error_log(output, failures));
                 }
                 // All retries exhausted
+                let last_err = errors.last().map(|e| e.to_string()).unwrap_or_default();
                 if has_error_edge {
                     // Set __error in state and return successfully so error edges can route
-                    let mut output = state_clone;
+                    let mut output = state;
                     if let Value::Object(ref mut map) = output {
                         map.insert("__error".to_string(), Value::String(last_err));
                     }
-                    Ok(output)
+                    Ok(with_error_log(output, failures))
                 } else {
                     Err(AyasError::Other(last_err))
                 }
@@ -178,14 +225,35 @@ pub fn convert_to_state_graph_with_context(
         })
     }
 
+    // Map nodes send each item to a worker node instead of routing to it
+    let mut map_workers: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+    for node in nodes.iter().filter(|n| n.node_type == "map") {
+        let worker = map_worker(node).ok_or_else(|| {
+            GraphError::InvalidGraph(format!("Map node '{}' has no 'worker' configured", node.id))
+        })?;
+        if !nodes.iter().any(|n| n.id == worker) {
+            return Err(GraphError::InvalidGraph(format!(
+                "Map node '{}' targets unknown worker '{worker}'",
+                node.id
+            ))
+            .into());
+        }
+        map_workers.insert(node.id.clone(), worker.to_string());
+    }
+
     // Add nodes (skip start/end - they are virtual)
     for node in nodes {
         let has_error_edge = error_edge_nodes.contains(&node.id);
         let node_config = node.config.clone().unwrap_or(Value::Null);
-        let max_retries = node_config
-            .get("max_retries")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0) as usize;
+        let policy = NodePolicy {
+            max_retries: node_config
+                .get("max_retries")
+                .and_then(|v| v.as_u64())
+                .map(|r| r.min(u32::MAX as u64) as u32),
+            timeout_ms: node_config.get("timeout_ms").and_then(|v| v.as_u64()),
+            on_error: None,
+        };
 
         match node.node_type.as_str() {
             "start" | "end" => continue,
@@ -197,12 +265,12 @@ pub fn convert_to_state_graph_with_context(
                 let node_fn = wrap_node_fn(
                     id,
                     has_error_edge,
-                    max_retries,
-                    move |state: Value| {
+                    policy.clone(),
+                    move |state: Value, run_config: RunnableConfig| {
                         let config = config.clone();
                         let ctx = ctx.clone();
                         Box::pin(async move {
-                            build_llm_node(state, &config, ctx.as_deref()).await
+                            build_llm_node(state, &config, ctx.as_deref(), &run_config).await
                         })
                     },
                 );
@@ -214,8 +282,8 @@ pub fn convert_to_state_graph_with_context(
                 let node_fn = wrap_node_fn(
                     id,
                     has_error_edge,
-                    max_retries,
-                    move |state: Value| {
+                    policy.clone(),
+                    move |state: Value, _run_config| {
                         let config = config.clone();
                         Box::pin(async move { build_transform_node(state, &config) })
                     },
@@ -230,8 +298,8 @@ pub fn convert_to_state_graph_with_context(
                 let node_fn = wrap_node_fn(
                     id,
                     has_error_edge,
-                    max_retries,
-                    move |state: Value| {
+                    policy.clone(),
+                    move |state: Value, _run_config| {
                         let config = config.clone();
                         let ctx = ctx.clone();
                         Box::pin(async move {
@@ -249,17 +317,83 @@ pub fn convert_to_state_graph_with_context(
                 let node_fn = wrap_node_fn(
                     id,
                     has_error_edge,
-                    max_retries,
-                    move |state: Value| {
+                    policy.clone(),
+                    move |state: Value, run_config: RunnableConfig| {
+                        let config = config.clone();
+                        let ctx = ctx.clone();
+                        Box::pin(async move {
+                            build_agent_node(state, &config, ctx.as_deref(), &run_config).await
+                        })
+                    },
+                );
+                graph.add_node(node_fn)?;
+            }
+            "web_search" => {
+                let config = node_config;
+                let id = node.id.clone();
+                let tool = Arc::new(WebSearchTool::new());
+
+                let node_fn = wrap_node_fn(
+                    id,
+                    has_error_edge,
+                    policy.clone(),
+                    move |state: Value, _run_config| {
+                        let config = config.clone();
+                        let tool = tool.clone();
+                        Box::pin(async move { build_web_search_node(state, &config, &tool).await })
+                    },
+                );
+                graph.add_node(node_fn)?;
+            }
+            "retrieve" => {
+                let config = node_config;
+                let id = node.id.clone();
+                let ctx = ctx.clone();
+
+                let node_fn = wrap_node_fn(
+                    id,
+                    has_error_edge,
+                    policy.clone(),
+                    move |state: Value, run_config: RunnableConfig| {
                         let config = config.clone();
                         let ctx = ctx.clone();
                         Box::pin(async move {
-                            build_agent_node(state, &config, ctx.as_deref()).await
+                            build_retrieve_node(state, &config, ctx.as_deref(), &run_config).await
                         })
                     },
                 );
                 graph.add_node(node_fn)?;
             }
+            "map" => {
+                let config = node_config;
+                let id = node.id.clone();
+                let worker = map_workers[&node.id].clone();
+
+                let node_fn = wrap_node_fn(
+                    id,
+                    has_error_edge,
+                    policy.clone(),
+                    move |state: Value, _run_config| {
+                        let config = config.clone();
+                        let worker = worker.clone();
+                        Box::pin(async move { build_map_node(state, &config, &worker) })
+                    },
+                );
+                graph.add_node(node_fn)?;
+            }
+            "subgraph" => {
+                let inner = Arc::new(build_subgraph_node(&node.id, &node_config, ctx.as_deref())?);
+                let node_fn = wrap_node_fn(
+                    node.id.clone(),
+                    has_error_edge,
+                    policy.clone(),
+                    move |state: Value, run_config: RunnableConfig| {
+                        let inner = inner.clone();
+                        Box::pin(async move { inner.invoke(state, &run_config).await })
+                    },
+                );
+                graph.add_node(node_fn)?;
+            }
             "interrupt" => {
                 let config = node_config;
                 let id = node.id.clone();
@@ -270,12 +404,16 @@ pub fn convert_to_state_graph_with_context(
                             .get("value")
                             .cloned()
                             .unwrap_or_else(|| json!({"prompt": "Human input needed"}));
-                        let mut output = interrupt_output(interrupt_val);
-                        // Merge state values through
+                        // A "key" lets the resume value land in resume_values[key]
+                        let mut output = match config.get("key").and_then(|k| k.as_str()) {
+                            Some(key) => interrupt_output_for(key, interrupt_val),
+                            None => interrupt_output(interrupt_val),
+                        };
+                        // Merge state values through (the error log is append-only)
                         if let Value::Object(ref state_map) = state {
                             if let Value::Object(ref mut out_map) = output {
                                 for (k, v) in state_map {
-                                    if !out_map.contains_key(k) {
+                                    if k != ERRORS_CHANNEL && !out_map.contains_key(k) {
                                         out_map.insert(k.clone(), v.clone());
                                     }
                                 }
@@ -289,8 +427,9 @@ pub fn convert_to_state_graph_with_context(
             _ => {
                 // passthrough / conditional / unknown → passthrough node
                 let id = node.id.clone();
-                let node_fn =
-                    NodeFn::new(id, |state: Value, _cfg| async move { Ok(state) });
+                let node_fn = NodeFn::new(id, |state: Value, _cfg| async move {
+                    Ok(with_error_log(state, Vec::new()))
+                });
                 graph.add_node(node_fn)?;
             }
         }
@@ -332,6 +471,14 @@ pub fn convert_to_state_graph_with_context(
         finish_nodes.retain(|fp| fp != src);
     }
 
+    // Map node → its normal (non-error, non-fan-out) target
+    let mut map_next: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+
+    // Targets a string-returning Rhai condition may name
+    let node_ids: Arc<std::collections::HashSet<String>> =
+        Arc::new(nodes.iter().map(|n| n.id.clone()).collect());
+
     // Add edges (skip start→X and X→end, handled by entry/finish points)
     for edge in edges {
         if edge.from == "start" || edge.to == "end" {
@@ -348,6 +495,12 @@ pub fn convert_to_state_graph_with_context(
             continue;
         }
 
+        // Map nodes route through a conditional edge that also declares the worker
+        if map_workers.contains_key(&edge.from) && !edge.fan_out && edge.condition.is_none() {
+            map_next.insert(edge.from.clone(), edge.to.clone());
+            continue;
+        }
+
         // Fan-out edges: group by source and add as ConditionalFanOutEdge later
         if edge.fan_out {
             fan_out_groups
@@ -369,26 +522,26 @@ pub fn convert_to_state_graph_with_context(
                 || condition.contains("&&")
                 || condition.contains("||")
                 || condition.contains('(')
-                || condition.contains('!');
+                || condition.contains('!')
+                || condition.contains('"');
 
+            // Parse Rhai expressions once here; only the scope is rebuilt per call.
+            // An expression that fails to parse never matches, as before.
+            let compiled = is_expression.then(|| CompiledExpression::compile(&condition).ok());
+
+            let node_ids = node_ids.clone();
             let cond_edge = ConditionalEdge::new(
                 &edge.from,
                 move |state: &Value| {
-                    if is_expression {
-                        // Rhai expression evaluation
-                        let mut engine = rhai::Engine::new();
-                        engine.set_max_operations(10_000);
-                        engine.set_max_call_levels(8);
-                        engine.set_max_expr_depths(32, 16);
-
-                        if let Ok(dynamic_state) = rhai::serde::to_dynamic(state) {
-                            let mut scope = rhai::Scope::new();
-                            scope.push_dynamic("state", dynamic_state);
-                            if let Ok(result) = engine.eval_with_scope::<bool>(&mut scope, &condition) {
-                                if result {
-                                    return to_target.clone();
-                                }
+                    if let Some(expr) = &compiled {
+                        // Rhai expression evaluation: `true` takes the edge, a string
+                        // names the next node directly (multi-way switch)
+                        match expr.as_ref().map(|e| e.evaluate_value(state)) {
+                            Some(Ok(Value::Bool(true))) => return to_target.clone(),
+                            Some(Ok(Value::String(target))) if node_ids.contains(&target) => {
+                                return target;
                             }
+                            _ => {}
                         }
                         END.to_string()
                     } else {
@@ -425,6 +578,22 @@ pub fn convert_to_state_graph_with_context(
         graph.add_conditional_fan_out_edges(fan_out);
     }
 
+    // Add map routing: always continue to the normal target; the worker only runs
+    // through sends, but is listed in the path map so it counts as reachable
+    for (from, worker) in &map_workers {
+        if on_error_targets.contains_key(from) {
+            continue;
+        }
+        let next = map_next.get(from).cloned().unwrap_or_else(|| END.to_string());
+        let path_map = std::collections::HashMap::from([
+            (next.clone(), next.clone()),
+            (worker.clone(), worker.clone()),
+        ]);
+        let cond_edge =
+            ConditionalEdge::new(from, move |_state: &Value| next.clone(), Some(path_map));
+        graph.add_conditional_edges(cond_edge);
+    }
+
     // Add on_error routing: conditional edges that route to error_target if __error is set
     for (from, error_target) in &on_error_targets {
         let raw_target = error_node_normal_targets
@@ -473,11 +642,87 @@ fn parse_attachments(config: &Value) -> Vec<ContentPart> {
         .collect()
 }
 
+/// Substitute `{key}` placeholders in a node prompt from the graph state.
+///
+/// `{{` and `}}` produce literal braces. `{INPUT}` is the node's input unless
+/// the state has an `INPUT` key. A placeholder that is not an identifier or
+/// names no state key is left as written, so prompts quoting JSON keep
+/// working. Strings are inserted as-is, other values as JSON.
+fn interpolate_prompt(template: &str, state: &Value, input: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if let Some(end) = tail.strip_prefix('{').and_then(|t| t.find('}'))
+            && let Some(value) = placeholder_value(&tail[1..=end], state, input)
+        {
+            out.push_str(&value);
+            rest = &tail[end + 2..];
+            continue;
+        }
+        out.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholder_value(key: &str, state: &Value, input: &str) -> Option<String> {
+    if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    match state.get(key) {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(v) => Some(v.to_string()),
+        None if key == "INPUT" => Some(input.to_string()),
+        None => None,
+    }
+}
+
+/// State key an LLM node writes its summed token usage to, unless configured.
+const USAGE_CHANNEL: &str = "__usage";
+
+/// Append channel logging every failed node attempt as `{node, message, attempt}`.
+const ERRORS_CHANNEL: &str = "__errors";
+
+/// Replace the error log a node passes through with just its own `failures`.
+///
+/// Nodes return the full state, so without this an append channel would
+/// re-append every earlier record on each step.
+fn with_error_log(mut output: Value, failures: Vec<Value>) -> Value {
+    if let Value::Object(ref mut map) = output {
+        if failures.is_empty() {
+            map.remove(ERRORS_CHANNEL);
+        } else {
+            map.insert(ERRORS_CHANNEL.to_string(), Value::Array(failures));
+        }
+    }
+    output
+}
+
+/// The `usage_channel` of an LLM node's config.
+fn usage_channel(config: &Value) -> &str {
+    config
+        .get("usage_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or(USAGE_CHANNEL)
+}
+
 /// Build LLM node logic: calls a real ChatModel if context is available, otherwise dummy.
+///
+/// The token usage of every model call in the tool-calling loop is summed and
+/// written to the node's usage channel, so the node's output carries its cost.
 async fn build_llm_node(
     state: Value,
     config: &Value,
     context: Option<&GraphBuildContext>,
+    run_config: &RunnableConfig,
 ) -> Result<Value> {
     let prompt = config
         .get("prompt")
@@ -526,7 +771,7 @@ async fn build_llm_node(
         ayas_core::error::AyasError::Other(format!("API key error: {e:?}"))
     })?;
 
-    let model = (ctx.factory)(&provider, api_key, model_id);
+    let model = (ctx.factory)(&provider, api_key, model_id)?;
 
     // Read user input from state
     let user_input = match state.get(input_channel) {
@@ -537,7 +782,7 @@ async fn build_llm_node(
 
     let mut messages = Vec::new();
     if !prompt.is_empty() {
-        messages.push(Message::system(prompt));
+        messages.push(Message::system(interpolate_prompt(prompt, &state, &user_input)));
     }
 
     // Build user message: multimodal if attachments present
@@ -572,6 +817,18 @@ async fn build_llm_node(
     } else {
         Vec::new()
     };
+    let coerce = config
+        .get("coerce_arguments")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let tools: Vec<Arc<dyn Tool>> = if coerce {
+        tools
+            .into_iter()
+            .map(|t| Arc::new(CoercingTool::new(t)) as Arc<dyn Tool>)
+            .collect()
+    } else {
+        tools
+    };
 
     let tool_defs: Vec<ayas_core::tool::ToolDefinition> =
         tools.iter().map(|t| t.definition()).collect();
@@ -591,35 +848,27 @@ async fn build_llm_node(
     };
 
     // Tool-calling loop: LLM → tool execution → LLM → ... until no tool calls or limit
+    let mut usage: Option<UsageMetadata> = None;
     let mut iteration = 0;
-    loop {
-        let result = model.generate(&messages, &options).await?;
+    let response_text = loop {
+        let result = generate_with_config(&*model, &messages, &options, run_config).await?;
+        if let Some(call_usage) = &result.usage {
+            usage.get_or_insert_with(UsageMetadata::default).accumulate(call_usage);
+        }
 
         // Check for tool calls
         let tool_calls = match &result.message {
             Message::AI(AIContent { tool_calls, .. }) if !tool_calls.is_empty() => {
                 tool_calls.clone()
             }
-            _ => {
-                // No tool calls — we're done
-                let response_text = result.message.content().to_string();
-                let mut state = state;
-                if let Value::Object(ref mut map) = state {
-                    map.insert(output_channel.to_string(), Value::String(response_text));
-                }
-                return Ok(state);
-            }
+            // No tool calls — we're done
+            _ => break result.message.content().to_string(),
         };
 
         iteration += 1;
         if iteration > max_tool_iterations {
             // Exceeded iteration limit — return last content
-            let response_text = result.message.content().to_string();
-            let mut state = state;
-            if let Value::Object(ref mut map) = state {
-                map.insert(output_channel.to_string(), Value::String(response_text));
-            }
-            return Ok(state);
+            break result.message.content().to_string();
         }
 
         // Add AI message with tool calls to conversation
@@ -627,14 +876,25 @@ async fn build_llm_node(
 
         // Execute tool calls
         for tc in tool_calls {
-            let output = if let Some(tool) = tools_map.get(&tc.name) {
-                tool.call(tc.arguments.clone()).await.unwrap_or_else(|e| format!("Tool error: {e}"))
-            } else {
-                format!("Unknown tool: {}", tc.name)
+            let tool_msg = match tools_map.get(&tc.name) {
+                Some(tool) => match tool.call(tc.arguments.clone()).await {
+                    Ok(output) => Message::tool(output, &tc.id),
+                    Err(e) => Message::tool_failure(&e, &tc.id),
+                },
+                None => Message::tool_error(format!("Unknown tool: {}", tc.name), &tc.id),
             };
-            messages.push(Message::tool(output, &tc.id));
+            messages.push(tool_msg);
+        }
+    };
+
+    let mut state = state;
+    if let Value::Object(ref mut map) = state {
+        map.insert(output_channel.to_string(), Value::String(response_text));
+        if let Some(usage) = usage {
+            map.insert(usage_channel(config).to_string(), serde_json::to_value(usage)?);
         }
     }
+    Ok(state)
 }
 
 /// Build Deep Research node logic: calls the Interactions API if context is available.
@@ -697,7 +957,7 @@ async fn build_deep_research_node(
     let query = if prompt.is_empty() {
         user_input
     } else {
-        prompt.replace("{INPUT}", &user_input)
+        interpolate_prompt(prompt, &state, &user_input)
     };
 
     // Build attachments: first from config.attachments_text (direct text), then from state channel
@@ -750,11 +1010,22 @@ async fn build_deep_research_node(
         .with_agent(agent)
         .with_poll_interval(std::time::Duration::from_secs(5));
 
-    let mut input = DeepResearchInput::new(query).with_attachments(attachments);
+    let mut tools = Vec::new();
     if let Some(names) = file_search_store_names {
-        input = input.with_tools(vec![ToolConfig::FileSearch {
+        tools.push(ToolConfig::FileSearch {
             file_search_store_names: names,
-        }]);
+        });
+    }
+    if config.get("web_search").and_then(|v| v.as_bool()).unwrap_or(false) {
+        tools.push(ToolConfig::WebSearch);
+    }
+    if config.get("code_execution").and_then(|v| v.as_bool()).unwrap_or(false) {
+        tools.push(ToolConfig::CodeExecution);
+    }
+
+    let mut input = DeepResearchInput::new(query).with_attachments(attachments);
+    if !tools.is_empty() {
+        input = input.with_tools(tools);
     }
     let runnable_config = RunnableConfig::default();
 
@@ -773,6 +1044,7 @@ async fn build_agent_node(
     state: Value,
     config: &Value,
     context: Option<&GraphBuildContext>,
+    run_config: &RunnableConfig,
 ) -> Result<Value> {
     let input_channel = config
         .get("input_channel")
@@ -818,7 +1090,7 @@ async fn build_agent_node(
         AyasError::Other(format!("API key error: {e:?}"))
     })?;
 
-    let model: Arc<dyn ChatModel> = Arc::from((ctx.factory)(&provider, api_key, model_id));
+    let model: Arc<dyn ChatModel> = Arc::from((ctx.factory)(&provider, api_key, model_id)?);
 
     // Build tools
     let tool_names: Vec<String> = config
@@ -846,6 +1118,7 @@ async fn build_agent_node(
     // Build initial messages (multimodal if attachments present)
     let mut initial_messages: Vec<Value> = Vec::new();
     if !system_prompt.is_empty() {
+        let system_prompt = interpolate_prompt(system_prompt, &state, &user_input);
         initial_messages.push(serde_json::to_value(&Message::system(system_prompt))
             .map_err(AyasError::Serialization)?);
     }
@@ -860,15 +1133,48 @@ async fn build_agent_node(
     initial_messages.push(serde_json::to_value(&user_msg)
         .map_err(AyasError::Serialization)?);
 
-    // Create and run the ReAct agent
-    let agent_graph = create_react_agent(model, tools)?;
+    // Create and run the ReAct agent with the node's sampling settings
+    let stop: Vec<String> = match config.get("stop") {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(arr)) => {
+            arr.iter().filter_map(|v| v.as_str().map(String::from)).collect()
+        }
+        _ => Vec::new(),
+    };
+    let call_options = CallOptions {
+        temperature: config.get("temperature").and_then(|v| v.as_f64()),
+        top_p: config.get("top_p").and_then(|v| v.as_f64()),
+        max_tokens: config
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as u32),
+        stop,
+        ..Default::default()
+    };
+    let coerce = config
+        .get("coerce_arguments")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let agent_options = ReactAgentOptions::new()
+        .with_call_options(call_options)
+        .with_coerce_arguments(coerce);
+    let agent_graph = create_react_agent_with_options(model, tools, agent_options)?;
 
     let recursion_limit = config
         .get("recursion_limit")
         .and_then(|v| v.as_u64())
         .unwrap_or(25) as usize;
+    // Keep the stream handle so the inner agent's tokens reach the outer stream,
+    // the budget so its model calls count towards the run's spending, the
+    // cancellation token so a disconnect stops it too, and the configurable
+    // values so per-invocation model overrides apply
     let agent_config = RunnableConfig {
         recursion_limit,
+        configurable: run_config.configurable.clone(),
+        stream_tx: run_config.stream_tx.clone(),
+        budget: run_config.budget,
+        usage: run_config.usage.clone(),
+        cancel: run_config.cancel.clone(),
         ..Default::default()
     };
 
@@ -897,6 +1203,190 @@ async fn build_agent_node(
     Ok(state)
 }
 
+/// Build Web Search node logic: searches for the query in `input_channel` and
+/// writes the results to `output_channel` as an array of `{title, url, content, score}`.
+async fn build_web_search_node(
+    state: Value,
+    config: &Value,
+    tool: &WebSearchTool,
+) -> Result<Value> {
+    let input_channel = config
+        .get("input_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("value");
+    let output_channel = config
+        .get("output_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("value");
+    let max_results = config
+        .get("max_results")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(5) as u32;
+
+    let query = match state.get(input_channel) {
+        Some(Value::String(s)) if !s.trim().is_empty() => s.clone(),
+        Some(Value::String(_)) | Some(Value::Null) | None => {
+            return Err(AyasError::Other(format!(
+                "web_search: no query in channel '{input_channel}'"
+            )));
+        }
+        Some(v) => v.to_string(),
+    };
+
+    let results = tool.search(&query, max_results).await?;
+    let results = serde_json::to_value(results)
+        .map_err(|e| AyasError::Other(format!("Failed to serialize search results: {e}")))?;
+
+    let mut state = state;
+    if let Value::Object(ref mut map) = state {
+        map.insert(output_channel.to_string(), results);
+    }
+    Ok(state)
+}
+
+/// Build Retrieve node logic: embeds the text in `input_channel`, searches the
+/// configured vector store and writes the matching documents to `output_channel`.
+async fn build_retrieve_node(
+    state: Value,
+    config: &Value,
+    context: Option<&GraphBuildContext>,
+    run_config: &RunnableConfig,
+) -> Result<Value> {
+    let input_channel = config
+        .get("input_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("value");
+    let output_channel = config
+        .get("output_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("value");
+    let store_name = config
+        .get("store")
+        .or_else(|| config.get("collection"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AyasError::Other("Retrieve node requires a 'store' name".to_string()))?;
+    let options = SearchOptions {
+        k: config
+            .get("k")
+            .and_then(|v| v.as_u64())
+            .map(|k| k as usize)
+            .unwrap_or(SearchOptions::default().k),
+        score_threshold: config
+            .get("threshold")
+            .and_then(|v| v.as_f64())
+            .map(|t| t as f32),
+    };
+
+    let (ctx, retrieval_factory) = context
+        .and_then(|ctx| ctx.retrieval_factory.as_ref().map(|f| (ctx, f)))
+        .ok_or_else(|| {
+            AyasError::Other("Retrieve node requires retrieval_factory in context".to_string())
+        })?;
+    let (embedder, store) = (retrieval_factory)(store_name, &ctx.api_keys)?;
+
+    let query = match state.get(input_channel) {
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+        None => String::new(),
+    };
+
+    let retriever = Retriever::new(embedder, store, options);
+    let documents = retriever.invoke(Value::String(query), run_config).await?;
+
+    let mut state = state;
+    if let Value::Object(ref mut map) = state {
+        map.insert(output_channel.to_string(), documents);
+    }
+    Ok(state)
+}
+
+/// Config of a `subgraph` node: an inline graph, run with the parent's
+/// channels renamed through the mappings (see [`subgraph_node`]).
+#[derive(serde::Deserialize)]
+struct SubgraphConfig {
+    graph: SubgraphDto,
+    #[serde(default)]
+    input_mapping: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    output_mapping: std::collections::HashMap<String, String>,
+}
+
+#[derive(serde::Deserialize)]
+struct SubgraphDto {
+    nodes: Vec<GraphNodeDto>,
+    edges: Vec<GraphEdgeDto>,
+    #[serde(default)]
+    channels: Vec<GraphChannelDto>,
+}
+
+fn parse_subgraph_config(node_id: &str, config: &Value) -> Result<SubgraphConfig> {
+    serde_json::from_value(config.clone()).map_err(|e| {
+        GraphError::InvalidGraph(format!("Subgraph node '{node_id}' has an invalid config: {e}"))
+            .into()
+    })
+}
+
+/// Compile a `subgraph` node's inline graph, sharing the parent's context.
+fn build_subgraph_node(
+    node_id: &str,
+    config: &Value,
+    context: Option<&GraphBuildContext>,
+) -> Result<NodeFn> {
+    let SubgraphConfig {
+        graph,
+        input_mapping,
+        output_mapping,
+    } = parse_subgraph_config(node_id, config)?;
+    let compiled = convert_to_state_graph_with_context(
+        &graph.nodes,
+        &graph.edges,
+        &graph.channels,
+        context.cloned(),
+    )
+    .map_err(|e| GraphError::InvalidGraph(format!("Subgraph node '{node_id}': {e}")))?;
+    Ok(subgraph_node(
+        node_id,
+        Arc::new(compiled),
+        input_mapping,
+        output_mapping,
+    ))
+}
+
+/// The worker node id configured on a map node.
+fn map_worker(node: &GraphNodeDto) -> Option<&str> {
+    node.config.as_ref()?.get("worker")?.as_str()
+}
+
+/// Build Map node logic: sends each element of the array in `input_channel` to the
+/// worker node, with the element merged into the worker's state as `item_channel`.
+fn build_map_node(state: Value, config: &Value, worker: &str) -> Result<Value> {
+    let input_channel = config
+        .get("input_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("value");
+    let item_channel = config
+        .get("item_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or("item");
+
+    let items = state
+        .get(input_channel)
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| {
+            AyasError::Other(format!("Map node input channel '{input_channel}' is not an array"))
+        })?;
+
+    let sends = items
+        .iter()
+        .map(|item| {
+            let mut input = serde_json::Map::new();
+            input.insert(item_channel.to_string(), item.clone());
+            SendDirective::new(worker, Value::Object(input))
+        })
+        .collect();
+    Ok(send_output(sends))
+}
+
 /// Build Transform node logic: evaluates a Rhai expression against the state.
 fn build_transform_node(state: Value, config: &Value) -> Result<Value> {
     let Some(expression) = config.get("expression").and_then(|v| v.as_str()) else {
@@ -916,32 +1406,11 @@ fn build_transform_node(state: Value, config: &Value) -> Result<Value> {
         .and_then(|v| v.as_str())
         .unwrap_or("value");
 
-    // Rhai Engine is !Sync → create fresh per evaluation
-    let mut engine = rhai::Engine::new();
-    engine.set_max_operations(10_000);
-    engine.set_max_call_levels(8);
-    engine.set_max_expr_depths(32, 16);
-    engine.set_max_string_size(4096);
-    engine.set_max_array_size(256);
-    engine.set_max_map_size(128);
-
-    let dynamic_state = rhai::serde::to_dynamic(&state).map_err(|e| {
-        ayas_core::error::AyasError::Other(format!(
-            "Failed to convert state to Rhai dynamic: {e}"
-        ))
-    })?;
-
-    let mut scope = rhai::Scope::new();
-    scope.push_dynamic("state", dynamic_state);
-
-    let result: rhai::Dynamic = engine.eval_with_scope(&mut scope, expression).map_err(|e| {
-        ayas_core::error::AyasError::Other(format!("Rhai expression error: {e}"))
-    })?;
-
-    // Convert Rhai result back to serde_json::Value
-    let result_value: Value = rhai::serde::from_dynamic(&result).unwrap_or_else(|_| {
-        Value::String(result.to_string())
-    });
+    let result_value = CompiledExpression::compile(expression)
+        .and_then(|expr| expr.evaluate_value(&state))
+        .map_err(|e| {
+            ayas_core::error::AyasError::Other(format!("Rhai expression error: {e}"))
+        })?;
 
     let mut state = state;
     if let Value::Object(ref mut map) = state {
@@ -983,6 +1452,32 @@ pub fn validate_graph(
         }
     }
 
+    // Check map nodes name an existing worker
+    let mut map_workers: Vec<(&str, &str)> = Vec::new();
+    for node in nodes.iter().filter(|n| n.node_type == "map") {
+        match map_worker(node) {
+            None => errors.push(format!("Map node '{}' has no 'worker' configured", node.id)),
+            Some(worker) if !node_ids.contains(worker) => errors.push(format!(
+                "Map node '{}' targets unknown worker '{worker}'",
+                node.id
+            )),
+            Some(worker) => map_workers.push((node.id.as_str(), worker)),
+        }
+    }
+
+    // Check subgraph nodes' inline graphs
+    for node in nodes.iter().filter(|n| n.node_type == "subgraph") {
+        let config = node.config.clone().unwrap_or(Value::Null);
+        match parse_subgraph_config(&node.id, &config) {
+            Ok(sub) => errors.extend(
+                validate_graph(&sub.graph.nodes, &sub.graph.edges, &sub.graph.channels)
+                    .into_iter()
+                    .map(|e| format!("Subgraph node '{}': {e}", node.id)),
+            ),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
     // Check for unreachable nodes
     let mut reachable: std::collections::HashSet<&str> = std::collections::HashSet::new();
     let mut queue: Vec<&str> = edges
@@ -997,6 +1492,17 @@ pub fn validate_graph(
         for edge in edges {
             if edge.from == node {
                 queue.push(&edge.to);
+                // A Rhai condition returning a string can route to any node it names
+                if let Some(condition) = &edge.condition {
+                    queue.extend(
+                        quoted_literals(condition).filter(|name| node_ids.contains(name)),
+                    );
+                }
+            }
+        }
+        for (map, worker) in &map_workers {
+            if *map == node {
+                queue.push(worker);
             }
         }
     }
@@ -1012,6 +1518,11 @@ pub fn validate_graph(
     errors
 }
 
+/// Double-quoted string literals in a Rhai expression.
+fn quoted_literals(expression: &str) -> impl Iterator<Item = &str> {
+    expression.split('"').skip(1).step_by(2)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1197,14 +1708,11 @@ mod tests {
             _messages: &[ayas_core::message::Message],
             _options: &CallOptions,
         ) -> ayas_core::error::Result<ChatResult> {
-            Ok(ChatResult {
-                message: Message::AI(AIContent {
-                    content: self.response.clone(),
-                    tool_calls: Vec::new(),
-                    usage: None,
-                }),
+            Ok(ChatResult::new(Message::AI(AIContent {
+                content: self.response.clone(),
+                tool_calls: Vec::new(),
                 usage: None,
-            })
+            })))
         }
 
         fn model_name(&self) -> &str {
@@ -1215,9 +1723,9 @@ mod tests {
     fn mock_factory(response: &str) -> GraphModelFactory {
         let resp = response.to_string();
         Arc::new(move |_provider, _key, _model| {
-            Box::new(MockChatModel {
+            Ok(Box::new(MockChatModel {
                 response: resp.clone(),
-            })
+            }))
         })
     }
 
@@ -1241,6 +1749,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -1306,6 +1815,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -1436,6 +1946,7 @@ mod tests {
             },
             research_factory: Some(mock_research_factory("Deep research output")),
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled =
@@ -1470,6 +1981,7 @@ mod tests {
             },
             research_factory: Some(mock_research_factory("Template research result")),
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled =
@@ -1506,6 +2018,7 @@ mod tests {
             },
             research_factory: Some(mock_research_factory("Research with attachments")),
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled =
@@ -1544,6 +2057,7 @@ mod tests {
             },
             research_factory: Some(mock_research_factory("File search research result")),
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled =
@@ -1578,6 +2092,7 @@ mod tests {
             },
             research_factory: Some(mock_research_factory("Research with direct text")),
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled =
@@ -1755,6 +2270,43 @@ mod tests {
         assert_eq!(output["result"], "");
     }
 
+    #[tokio::test]
+    async fn test_conditional_edge_rhai_string_routes_to_named_node() {
+        let mut nodes = vec![node("check", "passthrough")];
+        for tier in ["high", "mid", "low"] {
+            let mut n = node(tier, "transform");
+            let expression = format!("\"{tier}\"");
+            n.config = Some(json!({ "expression": expression, "output_channel": "result" }));
+            nodes.push(n);
+        }
+        let condition = r#"if state.score > 80 { "high" } else if state.score > 50 { "mid" }
+            else if state.score > 0 { "low" } else { "missing" }"#;
+        let edges = vec![
+            edge("start", "check"),
+            GraphEdgeDto {
+                from: "check".into(),
+                to: "high".into(),
+                condition: Some(condition.into()),
+                fan_out: false,
+                on_error: false,
+            },
+            edge("high", "end"),
+            edge("mid", "end"),
+            edge("low", "end"),
+        ];
+        let channels = vec![channel("score", "LastValue"), channel("result", "LastValue")];
+
+        let errors = validate_graph(&nodes, &edges, &channels);
+        assert!(errors.is_empty(), "Validation should pass: {:?}", errors);
+
+        let compiled = convert_to_state_graph(&nodes, &edges, &channels).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        for (score, expected) in [(90, "high"), (60, "mid"), (10, "low"), (0, "")] {
+            let output = compiled.invoke(json!({"score": score}), &config).await.unwrap();
+            assert_eq!(output["result"], expected, "score {score}");
+        }
+    }
+
     // --- LLM tool-calling tests ---
 
     use ayas_core::message::ToolCall;
@@ -1774,29 +2326,37 @@ mod tests {
             _options: &CallOptions,
         ) -> ayas_core::error::Result<ChatResult> {
             let count = self.call_count.fetch_add(1, Ordering::Relaxed);
+            let usage = |input_tokens: u64, output_tokens: u64| UsageMetadata {
+                input_tokens,
+                output_tokens,
+                total_tokens: input_tokens + output_tokens,
+                ..Default::default()
+            };
             if count == 0 {
                 // First call: return tool call
-                Ok(ChatResult {
-                    message: Message::AI(AIContent {
-                        content: String::new(),
-                        tool_calls: vec![ToolCall {
-                            id: "call_1".into(),
-                            name: "calculator".into(),
-                            arguments: json!({"expression": "2+3"}),
-                        }],
-                        usage: None,
-                    }),
+                let message = Message::AI(AIContent {
+                    content: String::new(),
+                    tool_calls: vec![ToolCall {
+                        id: "call_1".into(),
+                        name: "calculator".into(),
+                        arguments: json!({"expression": "2+3"}),
+                    }],
                     usage: None,
+                });
+                Ok(ChatResult {
+                    usage: Some(usage(10, 2)),
+                    ..ChatResult::new(message)
                 })
             } else {
                 // Second call: return final text
-                Ok(ChatResult {
-                    message: Message::AI(AIContent {
-                        content: self.final_response.clone(),
-                        tool_calls: Vec::new(),
-                        usage: None,
-                    }),
+                let message = Message::AI(AIContent {
+                    content: self.final_response.clone(),
+                    tool_calls: Vec::new(),
                     usage: None,
+                });
+                Ok(ChatResult {
+                    usage: Some(usage(20, 5)),
+                    ..ChatResult::new(message)
                 })
             }
         }
@@ -1811,10 +2371,10 @@ mod tests {
         let counter = Arc::new(AtomicUsize::new(0));
         let counter_clone = counter.clone();
         let factory: GraphModelFactory = Arc::new(move |_p, _k, _m| {
-            Box::new(MockToolCallingModel {
+            Ok(Box::new(MockToolCallingModel {
                 call_count: counter_clone.clone(),
                 final_response: resp.clone(),
-            })
+            }))
         });
         (factory, counter)
     }
@@ -1855,6 +2415,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: Some(mock_tools_factory()),
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -1870,6 +2431,41 @@ mod tests {
         assert_eq!(call_count.load(Ordering::Relaxed), 2);
     }
 
+    #[tokio::test]
+    async fn test_llm_node_sums_usage_across_tool_loop() {
+        let (factory, _) = mock_tool_calling_factory("The answer is 5");
+        let mut n = node("llm_1", "llm");
+        n.config = Some(json!({
+            "tools": ["calculator"],
+            "usage_channel": "llm_cost"
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "llm_1"), edge("llm_1", "end")];
+        let channels = vec![channel("value", "LastValue")];
+
+        let context = GraphBuildContext {
+            factory,
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: Some(mock_tools_factory()),
+            retrieval_factory: None,
+        };
+
+        let compiled = convert_to_state_graph_with_context(
+            &nodes, &edges, &channels, Some(context),
+        )
+        .unwrap();
+
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"value": "2+3?"}), &config).await.unwrap();
+        assert_eq!(output["llm_cost"]["input_tokens"], 30);
+        assert_eq!(output["llm_cost"]["output_tokens"], 7);
+        assert_eq!(output["llm_cost"]["total_tokens"], 37);
+    }
+
     #[tokio::test]
     async fn test_llm_node_no_tools_no_loop() {
         let mut n = node("llm_1", "llm");
@@ -1890,6 +2486,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: Some(mock_tools_factory()),
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -1903,6 +2500,133 @@ mod tests {
         assert_eq!(output["value"], "Direct response");
     }
 
+    fn subgraph_node_dto() -> GraphNodeDto {
+        let mut inner = node("llm_1", "llm");
+        inner.config = Some(json!({"prompt": "Answer", "provider": "gemini"}));
+        let mut n = node("sub", "subgraph");
+        n.config = Some(json!({
+            "graph": {
+                "nodes": [inner],
+                "edges": [edge("start", "llm_1"), edge("llm_1", "end")],
+                "channels": [channel("value", "LastValue")]
+            },
+            "input_mapping": {"question": "value"},
+            "output_mapping": {"value": "answer"}
+        }));
+        n
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_node_runs_inline_graph_with_parent_context() {
+        let nodes = vec![subgraph_node_dto()];
+        let edges = vec![edge("start", "sub"), edge("sub", "end")];
+        let channels = vec![channel("question", "LastValue"), channel("answer", "LastValue")];
+        let context = GraphBuildContext {
+            factory: mock_factory("From the subgraph"),
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+
+        let compiled =
+            convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"question": "Why?"}), &config).await.unwrap();
+        assert_eq!(output["answer"], "From the subgraph");
+        assert_eq!(output["question"], "Why?");
+    }
+
+    #[test]
+    fn subgraph_node_errors_name_the_node() {
+        let mut broken = node("sub", "subgraph");
+        broken.config = Some(json!({"graph": {"nodes": [], "edges": []}}));
+        let edges = vec![edge("start", "sub"), edge("sub", "end")];
+
+        let errors = validate_graph(&[broken.clone()], &edges, &[]);
+        assert!(
+            errors.contains(&"Subgraph node 'sub': Graph must have an edge from 'start'".into()),
+            "{errors:?}"
+        );
+        assert!(validate_graph(&[subgraph_node_dto()], &edges, &[]).is_empty());
+
+        broken.config = Some(json!({"input_mapping": {}}));
+        let err = convert_to_state_graph(&[broken], &edges, &[]).err().unwrap();
+        assert!(err.to_string().contains("Subgraph node 'sub' has an invalid config"), "{err}");
+    }
+
+    #[test]
+    fn interpolate_prompt_substitutes_and_escapes() {
+        let state = json!({"name": "Ada", "count": 3, "INPUT": "from state"});
+        assert_eq!(
+            interpolate_prompt("Hi {name}, {count} left", &state, "in"),
+            "Hi Ada, 3 left"
+        );
+        assert_eq!(
+            interpolate_prompt("{{name}} is {{literal}}", &state, "in"),
+            "{name} is {literal}"
+        );
+        let json_like = r#"{missing} {"k": 1} {"#;
+        assert_eq!(interpolate_prompt(json_like, &state, "in"), json_like);
+        assert_eq!(interpolate_prompt("Q: {INPUT}", &json!({}), "in"), "Q: in");
+        assert_eq!(interpolate_prompt("Q: {INPUT}", &state, "in"), "Q: from state");
+    }
+
+    async fn run_prompt_node(node_type: &str, prompt_key: &str) -> Value {
+        let mut config = serde_json::Map::new();
+        config.insert(prompt_key.into(), json!("Help {user} with {topic}. Reply in {{json}}."));
+        config.insert("output_channel".into(), json!("reply"));
+        let mut n = node("n1", node_type);
+        n.config = Some(Value::Object(config));
+        let edges = vec![edge("start", "n1"), edge("n1", "end")];
+        let channels = vec![
+            channel("value", "LastValue"),
+            channel("user", "LastValue"),
+            channel("topic", "LastValue"),
+            channel("reply", "LastValue"),
+        ];
+        let context = GraphBuildContext {
+            // Answer with the system prompt the node sent
+            factory: Arc::new(|_p, _k, _m| {
+                Ok(Box::new(ayas_chain::mock::MockChatModel::with_handler(
+                    |messages| {
+                        let system = messages
+                            .iter()
+                            .find(|m| matches!(m, Message::System { .. }))
+                            .map(|m| m.content().to_string())
+                            .unwrap_or_default();
+                        ayas_chain::mock::MockChatModel::text_result(system)
+                    },
+                )))
+            }),
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+        let compiled =
+            convert_to_state_graph_with_context(&[n], &edges, &channels, Some(context)).unwrap();
+        let input = json!({"value": "hi", "user": "Ada", "topic": "Rust"});
+        let output = compiled
+            .invoke(input, &ayas_core::config::RunnableConfig::default())
+            .await
+            .unwrap();
+        output["reply"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_llm_and_agent_prompts_interpolate_state() {
+        let expected = json!("Help Ada with Rust. Reply in {json}.");
+        assert_eq!(run_prompt_node("llm", "prompt").await, expected);
+        assert_eq!(run_prompt_node("agent", "system_prompt").await, expected);
+    }
+
     // --- Agent node tests ---
 
     #[tokio::test]
@@ -1958,6 +2682,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: Some(mock_tools_factory()),
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -1973,6 +2698,175 @@ mod tests {
         assert_eq!(call_count.load(Ordering::Relaxed), 2);
     }
 
+    /// Model that records the options of each call and answers directly.
+    struct OptionsRecordingModel(Arc<std::sync::Mutex<Vec<CallOptions>>>);
+
+    #[async_trait]
+    impl ChatModel for OptionsRecordingModel {
+        async fn generate(
+            &self,
+            _messages: &[Message],
+            options: &CallOptions,
+        ) -> ayas_core::error::Result<ChatResult> {
+            self.0.lock().unwrap().push(options.clone());
+            Ok(ChatResult::new(Message::ai("ok")))
+        }
+
+        fn model_name(&self) -> &str {
+            "options-recording"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_node_passes_sampling_options() {
+        let mut n = node("agent_1", "agent");
+        n.config = Some(json!({
+            "temperature": 0.3,
+            "top_p": 0.8,
+            "max_tokens": 256,
+            "stop": ["STOP"]
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "agent_1"), edge("agent_1", "end")];
+        let channels = vec![channel("value", "LastValue")];
+
+        let recorded_calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = recorded_calls.clone();
+        let factory: GraphModelFactory = Arc::new(move |_p, _k, _m| {
+            Ok(Box::new(OptionsRecordingModel(recorded.clone())))
+        });
+        let context = GraphBuildContext {
+            factory,
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+
+        let compiled = convert_to_state_graph_with_context(
+            &nodes, &edges, &channels, Some(context),
+        )
+        .unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"value": "Hi"}), &config).await.unwrap();
+        assert_eq!(output["value"], "ok");
+
+        {
+            let calls = recorded_calls.lock().unwrap();
+            assert_eq!(calls[0].temperature, Some(0.3));
+            assert_eq!(calls[0].top_p, Some(0.8));
+            assert_eq!(calls[0].max_tokens, Some(256));
+            assert_eq!(calls[0].stop, vec!["STOP".to_string()]);
+        }
+
+        // Per-invocation overrides win over the node's settings
+        let config = ayas_core::config::RunnableConfig::default()
+            .with_model_overrides(json!({"temperature": 0.9}));
+        compiled.invoke(json!({"value": "Hi"}), &config).await.unwrap();
+        let calls = recorded_calls.lock().unwrap();
+        assert_eq!(calls[1].temperature, Some(0.9));
+        assert_eq!(calls[1].top_p, Some(0.8));
+    }
+
+    /// Collect the text of the token events a graph streams for `node_name`.
+    async fn streamed_tokens(
+        compiled: &CompiledStateGraph,
+        input: Value,
+        node_name: &str,
+    ) -> (Value, String) {
+        use ayas_core::model::ChatStreamEvent;
+        use ayas_graph::stream::StreamEvent;
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke_with_streaming(input, &config, tx).await.unwrap();
+
+        let mut text = String::new();
+        while let Some(event) = rx.recv().await {
+            if let StreamEvent::Token {
+                node_name: n,
+                event: ChatStreamEvent::Token(t),
+                ..
+            } = event
+            {
+                assert_eq!(n, node_name);
+                text.push_str(&t);
+            }
+        }
+        (output, text)
+    }
+
+    #[tokio::test]
+    async fn test_llm_node_streams_tokens() {
+        let mut n = node("llm_1", "llm");
+        n.config = Some(json!({"prompt": "Be brief", "provider": "gemini"}));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "llm_1"), edge("llm_1", "end")];
+        let channels = vec![channel("value", "LastValue")];
+
+        let context = GraphBuildContext {
+            factory: mock_factory("Hello from LLM!"),
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+        let compiled = convert_to_state_graph_with_context(
+            &nodes, &edges, &channels, Some(context),
+        )
+        .unwrap();
+
+        let (output, text) =
+            streamed_tokens(&compiled, json!({"value": "What is Rust?"}), "llm_1").await;
+        assert_eq!(output["value"], "Hello from LLM!");
+        assert_eq!(text, "Hello from LLM!");
+    }
+
+    #[tokio::test]
+    async fn test_agent_node_streams_inner_tokens() {
+        let mut n = node("agent_1", "agent");
+        n.config = Some(json!({
+            "provider": "gemini",
+            "tools": ["calculator"],
+            "input_channel": "value",
+            "output_channel": "result"
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "agent_1"), edge("agent_1", "end")];
+        let channels = vec![
+            channel("value", "LastValue"),
+            channel("result", "LastValue"),
+        ];
+
+        let (factory, _call_count) = mock_tool_calling_factory("Agent final answer");
+        let context = GraphBuildContext {
+            factory,
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: Some(mock_tools_factory()),
+            retrieval_factory: None,
+        };
+        let compiled = convert_to_state_graph_with_context(
+            &nodes, &edges, &channels, Some(context),
+        )
+        .unwrap();
+
+        let input = json!({"value": "What is 2+3?", "result": ""});
+        let (output, text) = streamed_tokens(&compiled, input, "agent_1").await;
+        assert_eq!(output["result"], "Agent final answer");
+        // The inner ReAct agent's model calls surface under the outer node
+        assert!(text.ends_with("Agent final answer"), "{text}");
+    }
+
     // --- Error edge tests ---
 
     #[tokio::test]
@@ -1981,10 +2875,10 @@ mod tests {
         let call_count = Arc::new(AtomicUsize::new(0));
         let cc = call_count.clone();
         let factory: GraphModelFactory = Arc::new(move |_p, _k, _m| {
-            Box::new(MockFailingModel {
+            Ok(Box::new(MockFailingModel {
                 fail_count: 100, // always fails
                 call_count: cc.clone(),
-            })
+            }))
         });
 
         let mut fail_node = node("fail_node", "llm");
@@ -2024,6 +2918,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
@@ -2034,6 +2929,55 @@ mod tests {
         assert!(output["__error"].as_str().is_some_and(|s| !s.is_empty()));
     }
 
+    #[tokio::test]
+    async fn test_error_log_records_every_recovered_failure() {
+        let failing = |id: &str, extra: Value| {
+            let mut n = node(id, "transform");
+            let mut config = json!({
+                "expression": "undefined_var.crash()",
+                "output_channel": "value"
+            });
+            config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            n.config = Some(config);
+            n
+        };
+        let error_edge = |from: &str, to: &str| GraphEdgeDto {
+            from: from.into(),
+            to: to.into(),
+            condition: None,
+            fan_out: false,
+            on_error: true,
+        };
+        let nodes = vec![
+            failing("first", json!({})),
+            failing("second", json!({ "max_retries": 1 })),
+            node("handler", "passthrough"),
+        ];
+        let edges = vec![
+            edge("start", "first"),
+            edge("first", "end"),
+            error_edge("first", "second"),
+            edge("second", "end"),
+            error_edge("second", "handler"),
+            edge("handler", "end"),
+        ];
+        let channels = vec![channel("value", "LastValue")];
+
+        let compiled = convert_to_state_graph(&nodes, &edges, &channels).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"value": "test"}), &config).await.unwrap();
+
+        let errors = output[ERRORS_CHANNEL].as_array().unwrap();
+        let attempts: Vec<(&str, u64)> = errors
+            .iter()
+            .map(|e| (e["node"].as_str().unwrap(), e["attempt"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(attempts, vec![("first", 1), ("second", 1), ("second", 2)]);
+        assert!(errors.iter().all(|e| e["message"].as_str().is_some_and(|m| !m.is_empty())));
+        // __error still carries the last failure for routing
+        assert_eq!(output["__error"], errors[2]["message"]);
+    }
+
     #[tokio::test]
     async fn test_no_error_edge_propagates_error() {
         // Without on_error edge, the error should propagate
@@ -2051,6 +2995,253 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // --- Map tests ---
+
+    /// Mock model that echoes the last user message.
+    struct MockEchoModel;
+
+    #[async_trait]
+    impl ChatModel for MockEchoModel {
+        async fn generate(
+            &self,
+            messages: &[Message],
+            _options: &CallOptions,
+        ) -> ayas_core::error::Result<ChatResult> {
+            let input = messages.last().map(|m| m.content().to_string()).unwrap_or_default();
+            Ok(ChatResult::new(Message::ai(format!("echo: {input}"))))
+        }
+
+        fn model_name(&self) -> &str {
+            "mock-echo"
+        }
+    }
+
+    fn map_nodes(worker: &str) -> Vec<GraphNodeDto> {
+        let mut map = node("map", "map");
+        map.config = Some(json!({"input_channel": "items", "worker": worker}));
+        let mut llm = node("summarize", "llm");
+        llm.config = Some(json!({
+            "provider": "gemini",
+            "input_channel": "item",
+            "output_channel": "results"
+        }));
+        vec![map, llm]
+    }
+
+    #[tokio::test]
+    async fn test_map_node_runs_worker_per_item() {
+        let nodes = map_nodes("summarize");
+        let edges = vec![edge("start", "map"), edge("map", "end")];
+        let channels = vec![channel("items", "LastValue"), channel("results", "Append")];
+
+        let context = GraphBuildContext {
+            factory: Arc::new(|_p, _k, _m| Ok(Box::new(MockEchoModel))),
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+
+        let compiled =
+            convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled
+            .invoke(json!({"items": ["a", "b", "c"]}), &config)
+            .await
+            .unwrap();
+        assert_eq!(output["results"], json!(["echo: a", "echo: b", "echo: c"]));
+    }
+
+    #[test]
+    fn test_map_node_unknown_worker_is_rejected() {
+        let nodes = map_nodes("missing");
+        let edges = vec![edge("start", "map"), edge("map", "end")];
+
+        let errors = validate_graph(&nodes, &edges, &[]);
+        assert!(errors.iter().any(|e| e.contains("unknown worker 'missing'")));
+        let err = convert_to_state_graph(&nodes, &edges, &[]).err().unwrap();
+        assert!(err.to_string().contains("unknown worker 'missing'"));
+    }
+
+    #[test]
+    fn test_map_worker_counts_as_reachable() {
+        let nodes = map_nodes("summarize");
+        let edges = vec![edge("start", "map"), edge("map", "end")];
+        assert!(validate_graph(&nodes, &edges, &[]).is_empty());
+    }
+
+    // --- Web search tests ---
+
+    #[tokio::test]
+    async fn test_web_search_node_without_api_key_fails() {
+        let tool = WebSearchTool::with_api_key(None);
+        let config = json!({"input_channel": "query", "output_channel": "results"});
+        let err = build_web_search_node(json!({"query": "rust"}), &config, &tool)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("TAVILY_API_KEY"));
+    }
+
+    #[tokio::test]
+    async fn test_web_search_node_missing_query_routes_to_error_edge() {
+        let mut search = node("search", "web_search");
+        search.config = Some(json!({
+            "input_channel": "query",
+            "output_channel": "results"
+        }));
+        let handler = node("handler", "passthrough");
+
+        let nodes = vec![search, handler];
+        let edges = vec![
+            edge("start", "search"),
+            edge("search", "end"),
+            GraphEdgeDto {
+                from: "search".into(),
+                to: "handler".into(),
+                condition: None,
+                fan_out: false,
+                on_error: true,
+            },
+            edge("handler", "end"),
+        ];
+        let channels = vec![channel("query", "LastValue"), channel("results", "LastValue")];
+
+        let compiled = convert_to_state_graph(&nodes, &edges, &channels).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"query": ""}), &config).await.unwrap();
+        let err = output["__error"].as_str().unwrap();
+        assert!(err.contains("no query in channel 'query'"));
+    }
+
+    // --- Retrieve tests ---
+
+    /// Embeds texts mentioning "rust" along one axis and everything else along another.
+    struct KeywordEmbedder;
+
+    #[async_trait]
+    impl Embedding for KeywordEmbedder {
+        async fn embed(&self, text: &str) -> Result<ayas_rag::types::EmbeddingVector> {
+            let v = if text.to_lowercase().contains("rust") {
+                vec![1.0, 0.0]
+            } else {
+                vec![0.0, 1.0]
+            };
+            Ok(ayas_rag::types::EmbeddingVector::new(v))
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+    }
+
+    async fn mock_retrieval_factory() -> GraphRetrievalFactory {
+        use ayas_rag::memory::InMemoryVectorStore;
+        use ayas_rag::types::Document;
+
+        let store = Arc::new(InMemoryVectorStore::new());
+        let docs = [("d1", "The Rust book"), ("d2", "A cooking guide")];
+        let mut entries = Vec::new();
+        for (id, content) in docs {
+            let doc = Document {
+                id: id.into(),
+                content: content.into(),
+                metadata: Default::default(),
+            };
+            entries.push((doc, KeywordEmbedder.embed(content).await.unwrap()));
+        }
+        store.add_documents(entries).await.unwrap();
+
+        Arc::new(move |name: &str, _keys: &ApiKeys| {
+            if name != "docs" {
+                return Err(AyasError::Other(format!("unknown store '{name}'")));
+            }
+            let embedder: Arc<dyn Embedding> = Arc::new(KeywordEmbedder);
+            let store: Arc<dyn VectorStore> = store.clone();
+            Ok((embedder, store))
+        })
+    }
+
+    fn retrieve_context(retrieval_factory: Option<GraphRetrievalFactory>) -> GraphBuildContext {
+        GraphBuildContext {
+            factory: mock_factory("unused"),
+            api_keys: ApiKeys::default(),
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_node_writes_top_k_documents() {
+        let mut n = node("retrieve", "retrieve");
+        n.config = Some(json!({
+            "store": "docs",
+            "k": 1,
+            "input_channel": "question",
+            "output_channel": "documents"
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "retrieve"), edge("retrieve", "end")];
+        let channels = vec![channel("question", "LastValue"), channel("documents", "LastValue")];
+
+        let context = retrieve_context(Some(mock_retrieval_factory().await));
+        let compiled =
+            convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled
+            .invoke(json!({"question": "How do I learn Rust?"}), &config)
+            .await
+            .unwrap();
+
+        let documents = output["documents"].as_array().unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["id"], "d1");
+        assert_eq!(documents[0]["content"], "The Rust book");
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_node_threshold_filters_documents() {
+        let mut n = node("retrieve", "retrieve");
+        n.config = Some(json!({
+            "store": "docs",
+            "k": 5,
+            "threshold": 0.5,
+            "output_channel": "documents"
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "retrieve"), edge("retrieve", "end")];
+        let channels = vec![channel("value", "LastValue"), channel("documents", "LastValue")];
+
+        let context = retrieve_context(Some(mock_retrieval_factory().await));
+        let compiled =
+            convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let output = compiled.invoke(json!({"value": "dinner ideas"}), &config).await.unwrap();
+
+        let documents = output["documents"].as_array().unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["id"], "d2");
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_node_without_factory_fails() {
+        let mut n = node("retrieve", "retrieve");
+        n.config = Some(json!({"store": "docs"}));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "retrieve"), edge("retrieve", "end")];
+        let channels = vec![channel("value", "LastValue")];
+
+        let context = retrieve_context(None);
+        let compiled =
+            convert_to_state_graph_with_context(&nodes, &edges, &channels, Some(context)).unwrap();
+        let config = ayas_core::config::RunnableConfig::default();
+        let err = compiled.invoke(json!({"value": "q"}), &config).await.unwrap_err();
+        assert!(err.to_string().contains("retrieval_factory"));
+    }
+
     // --- Retry tests ---
 
     /// Mock model that fails N times then succeeds.
@@ -2072,14 +3263,11 @@ mod tests {
                     "Intentional failure #{count}"
                 )))
             } else {
-                Ok(ChatResult {
-                    message: Message::AI(AIContent {
-                        content: "recovered".into(),
-                        tool_calls: Vec::new(),
-                        usage: None,
-                    }),
+                Ok(ChatResult::new(Message::AI(AIContent {
+                    content: "recovered".into(),
+                    tool_calls: Vec::new(),
                     usage: None,
-                })
+                })))
             }
         }
 
@@ -2093,10 +3281,10 @@ mod tests {
         let call_count = Arc::new(AtomicUsize::new(0));
         let cc = call_count.clone();
         let factory: GraphModelFactory = Arc::new(move |_p, _k, _m| {
-            Box::new(MockFailingModel {
+            Ok(Box::new(MockFailingModel {
                 fail_count: 2,
                 call_count: cc.clone(),
-            })
+            }))
         });
 
         let mut n = node("llm_1", "llm");
@@ -2118,6 +3306,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -2138,10 +3327,10 @@ mod tests {
         let call_count = Arc::new(AtomicUsize::new(0));
         let cc = call_count.clone();
         let factory: GraphModelFactory = Arc::new(move |_p, _k, _m| {
-            Box::new(MockFailingModel {
+            Ok(Box::new(MockFailingModel {
                 fail_count: 10,
                 call_count: cc.clone(),
-            })
+            }))
         });
 
         let mut n = node("llm_1", "llm");
@@ -2163,6 +3352,7 @@ mod tests {
             },
             research_factory: None,
             tools_factory: None,
+            retrieval_factory: None,
         };
 
         let compiled = convert_to_state_graph_with_context(
@@ -2177,4 +3367,61 @@ mod tests {
         // 1 initial + 2 retries = 3 total attempts
         assert_eq!(call_count.load(Ordering::Relaxed), 3);
     }
+
+    struct MockSlowModel;
+
+    #[async_trait]
+    impl ChatModel for MockSlowModel {
+        async fn generate(
+            &self,
+            _messages: &[Message],
+            _options: &CallOptions,
+        ) -> ayas_core::error::Result<ChatResult> {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(ChatResult::new(Message::ai("too late")))
+        }
+
+        fn model_name(&self) -> &str {
+            "mock-slow-model"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_ms_fails_slow_node() {
+        let factory: GraphModelFactory = Arc::new(|_p, _k, _m| Ok(Box::new(MockSlowModel)));
+
+        let mut n = node("llm_1", "llm");
+        n.config = Some(json!({
+            "prompt": "test",
+            "provider": "gemini",
+            "model": "gemini-2.5-flash",
+            "timeout_ms": 50
+        }));
+        let nodes = vec![n];
+        let edges = vec![edge("start", "llm_1"), edge("llm_1", "end")];
+        let channels = vec![channel("value", "LastValue")];
+
+        let context = GraphBuildContext {
+            factory,
+            api_keys: ApiKeys {
+                gemini_key: Some("test-key".into()),
+                ..Default::default()
+            },
+            research_factory: None,
+            tools_factory: None,
+            retrieval_factory: None,
+        };
+
+        let compiled = convert_to_state_graph_with_context(
+            &nodes, &edges, &channels, Some(context),
+        )
+        .unwrap();
+
+        let config = ayas_core::config::RunnableConfig::default();
+        let err = compiled
+            .invoke(json!({"value": "test"}), &config)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out after 50ms"), "{err}");
+    }
 }
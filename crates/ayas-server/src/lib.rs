@@ -1,12 +1,15 @@
 pub mod error;
 pub mod extractors;
 pub mod session;
+pub mod conversation;
 pub mod state;
 pub mod types;
 pub mod run_types;
 pub mod tools;
 pub mod graph_convert;
 pub mod graph_gen;
+pub mod pipeline_spec;
+pub mod moderation;
 pub mod tracing_middleware;
 pub mod tracing_mw;
 pub mod api;
@@ -1,5 +1,7 @@
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use ayas_server::state::AppState;
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::registry()
@@ -10,12 +12,58 @@ async fn main() {
         )
         .init();
 
-    let app = ayas_server::app_router().await;
+    if let Err(e) = ayas_llm::factory::validate_env_config() {
+        tracing::error!("Invalid provider configuration: {e}");
+        std::process::exit(1);
+    }
+
+    let state = AppState::new().await;
+    let smith_client = state.smith_client.clone();
+    let app = ayas_server::app_router_with_state(state);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "3001".to_string());
     let addr = format!("0.0.0.0:{port}");
     tracing::info!("Ayas server listening on {addr}");
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+
+    // Write any buffered trace runs before exiting
+    smith_client.shutdown().await;
+    tracing::info!("Ayas server stopped");
+}
+
+/// Resolve on Ctrl+C or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sig) => {
+                sig.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutdown signal received, flushing traces");
 }
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use ayas_core::error::Result;
+use ayas_core::message::Message;
+use ayas_core::moderation::{Moderator, NoopModerator};
+use ayas_llm::moderation::OpenAIModerator;
+
+use crate::types::{FlaggedContent, ModerationStage};
+
+/// Build the moderator for the chat and agent endpoints.
+///
+/// `AYAS_MODERATION=openai` (with `OPENAI_API_KEY` set) enables the OpenAI
+/// Moderation API; anything else leaves moderation off. Asking for OpenAI
+/// moderation without a key logs a warning, since moderation stays off.
+pub fn moderator_from_env() -> Arc<dyn Moderator> {
+    let enabled = std::env::var("AYAS_MODERATION").is_ok_and(|v| v == "openai");
+    match std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty()) {
+        Some(api_key) if enabled => Arc::new(OpenAIModerator::new(api_key)),
+        None if enabled => {
+            tracing::warn!(
+                "AYAS_MODERATION=openai but OPENAI_API_KEY is not set; moderation is off"
+            );
+            Arc::new(NoopModerator)
+        }
+        _ => Arc::new(NoopModerator),
+    }
+}
+
+/// Text of the latest user message, which is the new input of a request.
+pub fn last_user_text(messages: &[Message]) -> Option<String> {
+    messages.iter().rev().find_map(|m| match m {
+        Message::User { content } => Some(content.text()),
+        _ => None,
+    })
+}
+
+/// Run `text` through the moderator, returning the refusal if it was flagged.
+pub async fn moderate(
+    moderator: &dyn Moderator,
+    stage: ModerationStage,
+    text: &str,
+) -> Result<Option<FlaggedContent>> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    let result = moderator.check(text).await?;
+    Ok(result.flagged.then_some(FlaggedContent {
+        stage,
+        categories: result.categories,
+    }))
+}
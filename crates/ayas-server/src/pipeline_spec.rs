@@ -0,0 +1,272 @@
+//! Data-driven description of a multi-step research pipeline.
+//!
+//! A [`PipelineSpec`] lists steps that run in order. Each step's output is
+//! stored under its id and can be referenced by later prompts as `{id}` or,
+//! for JSON outputs, `{id.field}`. Prompt placeholders that resolve to
+//! nothing are left untouched, so prompts may contain literal braces.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Name under which a `parallel_map` step exposes the current element.
+pub const ITEM_VAR: &str = "item";
+
+/// A user-defined pipeline: documents to research over and the steps to run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineSpec {
+    pub steps: Vec<PipelineStep>,
+    /// Values available to every prompt, by name.
+    #[serde(default)]
+    pub variables: Map<String, Value>,
+    /// Documents the research steps search (File Search, or inline
+    /// attachments when the store cannot be set up).
+    #[serde(default)]
+    pub documents: Vec<PipelineDocument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineDocument {
+    pub name: String,
+    pub content: String,
+    #[serde(default = "default_mime_type")]
+    pub mime_type: String,
+}
+
+fn default_mime_type() -> String {
+    "text/plain".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStep {
+    /// Name of the step's output for later prompts.
+    pub id: String,
+    /// Shown when the step starts; defaults to the id.
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub kind: StepKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepKind {
+    /// One Deep Research run; the output is the report text.
+    Research { prompt: String },
+    /// One LLM call constrained to `schema`; the output is the parsed JSON.
+    LlmStructured {
+        prompt: String,
+        schema: Value,
+        #[serde(default = "default_schema_name")]
+        schema_name: String,
+    },
+    /// One Deep Research run per element of the array at `over`, run in
+    /// parallel with the element bound to `{item}`. The output is an array of
+    /// `{"item", "text"}` or `{"item", "error"}` objects in input order.
+    ParallelMap {
+        over: String,
+        prompt: String,
+        /// Path inside each element used to label it in events.
+        #[serde(default)]
+        label: Option<String>,
+        /// Extra documents for each run, rendered like the prompt (e.g. an
+        /// earlier report as `{step1}`). Attached alongside the pipeline's
+        /// documents when those are inlined rather than indexed for File Search.
+        #[serde(default)]
+        documents: Vec<PipelineDocument>,
+    },
+}
+
+fn default_schema_name() -> String {
+    "output".to_string()
+}
+
+impl PipelineStep {
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or(&self.id)
+    }
+}
+
+impl PipelineSpec {
+    /// Check that step ids are usable as placeholders and that every
+    /// `parallel_map` reads a variable or an earlier step.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("Pipeline spec needs at least one step".into());
+        }
+        let mut seen = HashSet::new();
+        for step in &self.steps {
+            let id = step.id.as_str();
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("Invalid step id '{id}': use letters, digits and '_'"));
+            }
+            if id == ITEM_VAR || self.variables.contains_key(id) {
+                return Err(format!("Step id '{id}' shadows a variable"));
+            }
+            if let StepKind::ParallelMap { over, .. } = &step.kind {
+                let root = over.split('.').next().unwrap_or_default();
+                if !seen.contains(root) && !self.variables.contains_key(root) {
+                    return Err(format!(
+                        "Step '{id}' maps over '{over}', which is neither a variable \
+                         nor an earlier step"
+                    ));
+                }
+            }
+            if !seen.insert(id) {
+                return Err(format!("Duplicate step id '{id}'"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolve a dotted path such as `step2.hypotheses.0.title`.
+pub fn lookup<'a>(ctx: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+    if path.is_empty() || !path.chars().all(valid) {
+        return None;
+    }
+    let mut segments = path.split('.');
+    let mut value = ctx.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// Text of a value in a prompt: strings as-is, anything else as JSON.
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace each `{path}` in `template` that resolves in `ctx`. Substituted
+/// text is not scanned again.
+pub fn render(template: &str, ctx: &Map<String, Value>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let resolved = after
+            .find('}')
+            .and_then(|close| lookup(ctx, &after[..close]).map(|value| (close, value)));
+        match resolved {
+            Some((close, value)) => {
+                out.push_str(&value_text(value));
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn spec_from_json() {
+        let spec: PipelineSpec = serde_json::from_value(json!({
+            "variables": {"topic": "batteries"},
+            "documents": [{"name": "notes.md", "content": "..."}],
+            "steps": [
+                {"id": "survey", "type": "research", "prompt": "Survey {topic}"},
+                {
+                    "id": "ideas",
+                    "type": "llm_structured",
+                    "prompt": "List ideas from {survey}",
+                    "schema": {"type": "object"}
+                },
+                {
+                    "id": "deep",
+                    "type": "parallel_map",
+                    "description": "Deep dives",
+                    "over": "ideas.items",
+                    "prompt": "Investigate {item.name}",
+                    "label": "name"
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(spec.documents[0].mime_type, "text/plain");
+        assert!(matches!(spec.steps[0].kind, StepKind::Research { .. }));
+        assert_eq!(spec.steps[0].description(), "survey");
+        match &spec.steps[1].kind {
+            StepKind::LlmStructured { schema_name, .. } => assert_eq!(schema_name, "output"),
+            other => panic!("unexpected step {other:?}"),
+        }
+        assert_eq!(spec.steps[2].description(), "Deep dives");
+        assert!(spec.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_specs() {
+        let step = |id: &str, kind: Value| {
+            let mut step = json!({"id": id, "prompt": "p"});
+            step.as_object_mut().unwrap().extend(ctx(kind));
+            step
+        };
+        let research = |id: &str| step(id, json!({"type": "research"}));
+        let map_over = |id: &str, over: &str| {
+            step(id, json!({"type": "parallel_map", "over": over}))
+        };
+        let check = |steps: Vec<Value>| {
+            let spec: PipelineSpec = serde_json::from_value(json!({
+                "variables": {"topic": "x"},
+                "steps": steps
+            }))
+            .unwrap();
+            spec.validate()
+        };
+
+        assert!(check(vec![]).is_err());
+        assert!(check(vec![research("a.b")]).is_err());
+        assert!(check(vec![research("topic")]).is_err());
+        assert!(check(vec![research("item")]).is_err());
+        assert!(check(vec![research("a"), research("a")]).is_err());
+        assert!(check(vec![map_over("a", "later"), research("later")]).is_err());
+        assert!(check(vec![research("first"), map_over("a", "first.list")]).is_ok());
+        assert!(check(vec![map_over("a", "topic")]).is_ok());
+    }
+
+    #[test]
+    fn render_substitutes_known_paths_only() {
+        let ctx = ctx(json!({
+            "count": 3,
+            "report": "text with {count}",
+            "ideas": {"items": [{"name": "one"}, {"name": "two"}]}
+        }));
+
+        assert_eq!(render("{count} ideas", &ctx), "3 ideas");
+        assert_eq!(render("from: {report}", &ctx), "from: text with {count}");
+        assert_eq!(render("{ideas.items.1.name}", &ctx), "two");
+        assert_eq!(render("{ideas.items.0}", &ctx), r#"{"name":"one"}"#);
+        assert_eq!(
+            render("{unknown} {C,m} {} {p∈P} {{count}}", &ctx),
+            "{unknown} {C,m} {} {p∈P} {3}"
+        );
+        assert_eq!(render("trailing {", &ctx), "trailing {");
+    }
+}
@@ -305,6 +305,21 @@ pub struct FeedbackQueryRequest {
     pub key: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedbackStatsRequest {
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub created_before: Option<DateTime<Utc>>,
+    /// "hourly" or "daily"; omit for one row per key.
+    #[serde(default)]
+    pub bucket: Option<String>,
+}
+
 // --- Projects ---
 
 #[derive(Debug, Deserialize)]
@@ -50,6 +50,12 @@ impl SessionStore {
         list.sort_by(|a, b| a.created_at.cmp(&b.created_at));
         list
     }
+
+    /// The pending session for a thread, if it is waiting on an interrupt.
+    pub async fn find_by_thread(&self, thread_id: &str) -> Option<InterruptSession> {
+        let sessions = self.sessions.read().await;
+        sessions.values().find(|s| s.thread_id == thread_id).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -117,4 +123,14 @@ mod tests {
         let store = SessionStore::new();
         assert!(store.list_pending().await.is_empty());
     }
+
+    #[tokio::test]
+    async fn find_by_thread() {
+        let store = SessionStore::new();
+        store.create(make_session("s1")).await;
+
+        let found = store.find_by_thread("thread-s1").await;
+        assert_eq!(found.unwrap().session_id, "s1");
+        assert!(store.find_by_thread("thread-other").await.is_none());
+    }
 }
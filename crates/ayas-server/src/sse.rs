@@ -1,8 +1,37 @@
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use axum::http::HeaderMap;
 use axum::response::sse::{Event, KeepAlive};
 use futures::Stream;
 use serde::Serialize;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::AppError;
+
+/// Events kept per SSE session for `Last-Event-ID` replay.
+///
+/// Each buffered event stays in memory until its session is evicted, so a
+/// bigger buffer costs memory on every session. A client that falls further
+/// behind than this replays from the oldest event still held and misses the
+/// ones before it.
+pub const REPLAY_BUFFER_SIZE: usize = 256;
+
+/// Sessions kept for replay; the oldest is evicted beyond this.
+pub const MAX_REPLAY_SESSIONS: usize = 64;
+
+/// How long a buffered producer keeps running with no client attached before
+/// its work is cancelled.
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(30);
+
+/// A boxed SSE event stream, as served from a replay buffer.
+pub type ReplayStream = Pin<Box<dyn Stream<Item = Result<Event, Infallible>> + Send>>;
 
 /// Create an SSE response from a stream of events with keep-alive.
 /// Uses a 5-second interval to prevent proxy/network timeouts during long operations.
@@ -30,6 +59,247 @@ pub fn sse_done() -> Result<Event, std::convert::Infallible> {
     Ok(Event::default().data("[DONE]"))
 }
 
+/// Spawn the producer behind an SSE stream, cancelling it when the client goes away.
+///
+/// `work` gets the sender and a token that is cancelled once the receiving end
+/// of `tx` is dropped (axum drops the response stream on disconnect). The work
+/// is still awaited after cancellation so it can clean up, so it should check
+/// the token between steps rather than rely on being aborted.
+pub fn spawn_until_disconnected<T, F, Fut>(tx: mpsc::Sender<T>, work: F) -> JoinHandle<()>
+where
+    T: Send + 'static,
+    F: FnOnce(mpsc::Sender<T>, CancellationToken) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let token = CancellationToken::new();
+    let watch_tx = tx.clone();
+    let work = work(tx, token.clone());
+    tokio::spawn(async move {
+        tokio::pin!(work);
+        tokio::select! {
+            _ = &mut work => return,
+            _ = watch_tx.closed() => {}
+        }
+        drop(watch_tx);
+        tracing::info!("SSE client disconnected, cancelling in-flight work");
+        token.cancel();
+        work.await;
+    })
+}
+
+/// Per-session ring buffers of recent SSE events, for resuming a dropped stream.
+///
+/// Events served through a buffer get an `id` of the form `{session}:{seq}`.
+/// A client that reconnects with that id in `Last-Event-ID` is sent the
+/// buffered events after it, then follows the live stream.
+#[derive(Clone)]
+pub struct ReplayBuffers {
+    sessions: Arc<Mutex<ReplaySessions>>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct ReplaySessions {
+    logs: HashMap<String, Arc<ReplayLog>>,
+    order: VecDeque<String>,
+}
+
+impl Default for ReplayBuffers {
+    fn default() -> Self {
+        Self::with_capacity(REPLAY_BUFFER_SIZE)
+    }
+}
+
+impl ReplayBuffers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep up to `capacity` events per session.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sessions: Arc::new(Mutex::new(ReplaySessions::default())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Start buffering a new session.
+    pub fn create(&self) -> Arc<ReplayLog> {
+        let log = Arc::new(ReplayLog::new(uuid::Uuid::new_v4().to_string(), self.capacity));
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.logs.insert(log.session_id.clone(), log.clone());
+        sessions.order.push_back(log.session_id.clone());
+        while sessions.order.len() > MAX_REPLAY_SESSIONS {
+            if let Some(evicted) = sessions.order.pop_front() {
+                sessions.logs.remove(&evicted);
+            }
+        }
+        log
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<Arc<ReplayLog>> {
+        self.sessions.lock().unwrap().logs.get(session_id).cloned()
+    }
+
+    /// The stream to resume if the request carries a `Last-Event-ID` header.
+    pub fn resume(&self, headers: &HeaderMap) -> Result<Option<ReplayStream>, AppError> {
+        let Some(value) = headers.get("last-event-id") else {
+            return Ok(None);
+        };
+        let (session_id, seq) = value
+            .to_str()
+            .ok()
+            .and_then(parse_event_id)
+            .ok_or_else(|| AppError::BadRequest("Invalid Last-Event-ID header".into()))?;
+        let log = self.get(session_id).ok_or_else(|| {
+            AppError::NotFound(format!("SSE session '{session_id}' is no longer buffered"))
+        })?;
+        Ok(Some(log.subscribe(seq)))
+    }
+
+    /// Buffer events already produced and stream them.
+    pub fn record(
+        &self,
+        events: impl IntoIterator<Item = Result<Event, Infallible>>,
+    ) -> ReplayStream {
+        let log = self.create();
+        for event in events {
+            log.push(event);
+        }
+        log.finish();
+        log.subscribe(0)
+    }
+
+    /// Buffer events from a producer channel and stream them.
+    ///
+    /// The producer keeps running across client disconnects. Once no client
+    /// has been attached for [`RECONNECT_GRACE`], `rx` is dropped, which
+    /// cancels work started with [`spawn_until_disconnected`].
+    pub fn pump(&self, mut rx: mpsc::Receiver<Result<Event, Infallible>>) -> ReplayStream {
+        let log = self.create();
+        let stream = log.subscribe(0);
+        tokio::spawn(async move {
+            let mut unattended: Option<Instant> = None;
+            let mut check = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    event = rx.recv() => match event {
+                        Some(event) => log.push(event),
+                        None => break,
+                    },
+                    _ = check.tick() => {
+                        if log.subscribers() > 0 {
+                            unattended = None;
+                        } else if unattended.get_or_insert_with(Instant::now).elapsed()
+                            >= RECONNECT_GRACE
+                        {
+                            tracing::info!("No SSE client reconnected, cancelling buffered work");
+                            break;
+                        }
+                    }
+                }
+            }
+            log.finish();
+        });
+        stream
+    }
+}
+
+/// Split an event id into its session id and sequence number.
+fn parse_event_id(id: &str) -> Option<(&str, u64)> {
+    let (session_id, seq) = id.rsplit_once(':')?;
+    Some((session_id, seq.parse().ok()?))
+}
+
+/// The buffered events of one SSE session.
+pub struct ReplayLog {
+    session_id: String,
+    capacity: usize,
+    state: Mutex<LogState>,
+    changed: watch::Sender<()>,
+}
+
+#[derive(Default)]
+struct LogState {
+    events: VecDeque<(u64, Event)>,
+    next_seq: u64,
+    finished: bool,
+}
+
+impl ReplayLog {
+    fn new(session_id: String, capacity: usize) -> Self {
+        Self {
+            session_id,
+            capacity,
+            state: Mutex::new(LogState::default()),
+            changed: watch::channel(()).0,
+        }
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Assign the event the next id and buffer it, evicting the oldest if full.
+    pub fn push(&self, event: Result<Event, Infallible>) {
+        let event = match event {
+            Ok(event) => event,
+            Err(never) => match never {},
+        };
+        {
+            let mut state = self.state.lock().unwrap();
+            state.next_seq += 1;
+            let seq = state.next_seq;
+            let event = event.id(format!("{}:{seq}", self.session_id));
+            state.events.push_back((seq, event));
+            if state.events.len() > self.capacity {
+                state.events.pop_front();
+            }
+        }
+        self.changed.send_replace(());
+    }
+
+    /// Mark the session complete; streams end after the last buffered event.
+    pub fn finish(&self) {
+        self.state.lock().unwrap().finished = true;
+        self.changed.send_replace(());
+    }
+
+    fn subscribers(&self) -> usize {
+        self.changed.receiver_count()
+    }
+
+    /// The first buffered event after `seq`, or whether the session is over.
+    fn next_after(&self, seq: u64) -> Result<(u64, Event), bool> {
+        let state = self.state.lock().unwrap();
+        state
+            .events
+            .iter()
+            .find(|(s, _)| *s > seq)
+            .cloned()
+            .ok_or(state.finished)
+    }
+
+    /// Stream the buffered events after `seq`, then live ones until finished.
+    pub fn subscribe(self: &Arc<Self>, seq: u64) -> ReplayStream {
+        let rx = self.changed.subscribe();
+        Box::pin(futures::stream::unfold(
+            (self.clone(), rx, seq),
+            |(log, mut rx, seq)| async move {
+                loop {
+                    rx.borrow_and_update();
+                    match log.next_after(seq) {
+                        Ok((next, event)) => return Some((Ok(event), (log, rx, next))),
+                        Err(true) => return None,
+                        Err(false) => {}
+                    }
+                    rx.changed().await.ok()?;
+                }
+            },
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,4 +319,91 @@ mod tests {
         let result = sse_done();
         assert!(result.is_ok());
     }
+
+    async fn event_ids(stream: ReplayStream) -> Vec<String> {
+        use axum::response::IntoResponse;
+        use http_body_util::BodyExt;
+        let body = axum::response::Sse::new(stream).into_response().into_body();
+        let bytes = body.collect().await.unwrap().to_bytes();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter_map(|line| line.strip_prefix("id:"))
+            .map(|id| id.trim().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn replay_log_keeps_most_recent_events() {
+        let buffers = ReplayBuffers::with_capacity(2);
+        let log = buffers.create();
+        for i in 0..3 {
+            log.push(sse_event(&i));
+        }
+        log.finish();
+
+        let session = log.session_id().to_string();
+        let ids = event_ids(log.subscribe(0)).await;
+        assert_eq!(ids, vec![format!("{session}:2"), format!("{session}:3")]);
+    }
+
+    #[tokio::test]
+    async fn resume_replays_events_after_last_event_id() {
+        let buffers = ReplayBuffers::new();
+        let session = {
+            let stream = buffers.record((0..3).map(|i| sse_event(&i)));
+            event_ids(stream).await[0].split(':').next().unwrap().to_string()
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert("last-event-id", format!("{session}:1").parse().unwrap());
+        let resumed = buffers.resume(&headers).unwrap().unwrap();
+        assert_eq!(
+            event_ids(resumed).await,
+            vec![format!("{session}:2"), format!("{session}:3")]
+        );
+
+        headers.insert("last-event-id", "gone:1".parse().unwrap());
+        assert!(matches!(buffers.resume(&headers), Err(AppError::NotFound(_))));
+        headers.insert("last-event-id", "no-sequence".parse().unwrap());
+        assert!(matches!(buffers.resume(&headers), Err(AppError::BadRequest(_))));
+        assert!(buffers.resume(&HeaderMap::new()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn pump_follows_live_events() {
+        let buffers = ReplayBuffers::new();
+        let (tx, rx) = mpsc::channel(4);
+        let stream = buffers.pump(rx);
+        tx.send(sse_event(&1)).await.unwrap();
+        tx.send(sse_done()).await.unwrap();
+        drop(tx);
+        assert_eq!(event_ids(stream).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn spawn_until_disconnected_cancels_on_receiver_drop() {
+        let (tx, rx) = mpsc::channel::<u32>(4);
+        let (seen_tx, seen_rx) = tokio::sync::oneshot::channel();
+        let handle = spawn_until_disconnected(tx, |_tx, token| async move {
+            token.cancelled().await;
+            let _ = seen_tx.send(());
+        });
+
+        drop(rx);
+        handle.await.unwrap();
+        assert!(seen_rx.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn spawn_until_disconnected_leaves_finished_work_alone() {
+        let (tx, mut rx) = mpsc::channel::<u32>(4);
+        let handle = spawn_until_disconnected(tx, |tx, token| async move {
+            let _ = tx.send(1).await;
+            assert!(!token.is_cancelled());
+        });
+
+        handle.await.unwrap();
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+    }
 }
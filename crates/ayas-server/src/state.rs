@@ -7,6 +7,7 @@ use ayas_smith::client::{SmithClient, SmithConfig};
 use ayas_smith::duckdb_store::DuckDbStore;
 use ayas_smith::store::SmithStore;
 
+use crate::api::pipeline::PipelineRuns;
 use crate::session::SessionStore;
 
 /// Shared application state.
@@ -17,6 +18,8 @@ pub struct AppState {
     pub smith_base_dir: PathBuf,
     pub smith_client: SmithClient,
     pub smith_store: Arc<dyn SmithStore>,
+    /// In-flight pipeline runs, for aborting them.
+    pub pipeline_runs: PipelineRuns,
 }
 
 impl AppState {
@@ -45,6 +48,7 @@ impl AppState {
             smith_base_dir: smith_dir,
             smith_client,
             smith_store,
+            pipeline_runs: PipelineRuns::new(),
         }
     }
 
@@ -58,6 +62,7 @@ impl AppState {
             smith_store: Arc::new(DuckDbStore::new(&smith_dir)),
             smith_base_dir: smith_dir,
             smith_client,
+            pipeline_runs: PipelineRuns::new(),
         }
     }
 }
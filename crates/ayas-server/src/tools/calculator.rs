@@ -1,45 +1,246 @@
+use std::sync::OnceLock;
+
 use async_trait::async_trait;
+use ayas_adl::expression::new_sandboxed_engine;
 use ayas_core::error::{AyasError, Result, ToolError};
 use ayas_core::tool::{Tool, ToolDefinition};
 
-/// Calculator tool that evaluates math expressions using the `meval` crate.
+/// Error message raised by the `/` and `%` overloads for a zero divisor.
+const DIVISION_BY_ZERO: &str = "Division by zero";
+
+/// Calculator tool that evaluates math expressions with a sandboxed Rhai engine.
+///
+/// All numbers are floating point, `^` is exponentiation, and `sqrt`, `pow`,
+/// `abs`, `min`, `max`, `exp`, `ln`, `log`, `floor`, `ceiling`, `round` and
+/// the trigonometric functions are available along with the constants `pi`
+/// and `e`. Literals may use scientific notation (`1.5e3`).
+#[derive(Debug, Clone, Copy, Default)]
 pub struct CalculatorTool;
 
+impl CalculatorTool {
+    /// The same calculator, rounding results to `decimals` decimal places.
+    pub fn with_precision(self, decimals: usize) -> RoundedCalculatorTool {
+        RoundedCalculatorTool { decimals }
+    }
+}
+
+/// [`CalculatorTool`] rounding its results to a fixed number of decimal
+/// places; see [`CalculatorTool::with_precision`].
+#[derive(Debug, Clone, Copy)]
+pub struct RoundedCalculatorTool {
+    decimals: usize,
+}
+
 #[async_trait]
 impl Tool for CalculatorTool {
     fn definition(&self) -> ToolDefinition {
-        ToolDefinition {
-            name: "calculator".into(),
-            description: "Evaluates mathematical expressions. Supports +, -, *, /, ^, parentheses, and common math functions.".into(),
-            parameters: serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "expression": {
-                        "type": "string",
-                        "description": "The mathematical expression to evaluate (e.g., '2 * (3 + 4)')"
-                    }
-                },
-                "required": ["expression"]
-            }),
-        }
+        definition()
+    }
+
+    async fn call(&self, input: serde_json::Value) -> Result<String> {
+        calculate(&input, None)
+    }
+}
+
+#[async_trait]
+impl Tool for RoundedCalculatorTool {
+    fn definition(&self) -> ToolDefinition {
+        definition()
     }
 
     async fn call(&self, input: serde_json::Value) -> Result<String> {
-        let expr = input
-            .get("expression")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AyasError::Tool(ToolError::InvalidInput("missing 'expression' field".into())))?;
-
-        let result: f64 = meval::eval_str(expr)
-            .map_err(|e| AyasError::Tool(ToolError::ExecutionFailed(format!("Failed to evaluate '{expr}': {e}"))))?;
-
-        // Format: remove trailing zeros for clean output
-        if result == result.floor() && result.abs() < 1e15 {
-            Ok(format!("{}", result as i64))
-        } else {
-            Ok(result.to_string())
+        calculate(&input, Some(self.decimals))
+    }
+}
+
+fn definition() -> ToolDefinition {
+    ToolDefinition {
+        name: "calculator".into(),
+        description: "Evaluates mathematical expressions. Supports +, -, *, /, ^, parentheses, and common math functions.".into(),
+        parameters: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "The mathematical expression to evaluate (e.g., '2 * (3 + 4)')"
+                }
+            },
+            "required": ["expression"]
+        }),
+    }
+}
+
+/// Evaluate the `expression` field of `input`, rounded to `precision`
+/// decimal places if given.
+fn calculate(input: &serde_json::Value, precision: Option<usize>) -> Result<String> {
+    let expr = input
+        .get("expression")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AyasError::Tool(ToolError::InvalidInput("missing 'expression' field".into())))?;
+
+    let result = evaluate(expr)?;
+    if !result.is_finite() {
+        return Err(AyasError::Tool(ToolError::ExecutionFailed(format!(
+            "'{expr}' does not evaluate to a finite number"
+        ))));
+    }
+    Ok(format_result(result, precision))
+}
+
+fn format_result(value: f64, precision: Option<usize>) -> String {
+    let abs = value.abs();
+    if abs >= 1e15 || (abs != 0.0 && abs < 1e-6) {
+        return match precision {
+            Some(p) => format!("{value:.p$e}"),
+            None => format!("{value:e}"),
+        };
+    }
+    let value = match precision {
+        Some(p) => format!("{value:.p$}").parse().unwrap_or(value),
+        None => value,
+    };
+    // Format: remove trailing zeros for clean output
+    if value == value.floor() {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Evaluate `expr` as a floating-point Rhai expression.
+fn evaluate(expr: &str) -> Result<f64> {
+    let engine = calculator_engine();
+    let mut scope = rhai::Scope::new();
+    scope.push_constant("pi", std::f64::consts::PI);
+    scope.push_constant("e", std::f64::consts::E);
+
+    let value: rhai::Dynamic = engine
+        .eval_expression_with_scope(&mut scope, &normalize(expr))
+        .map_err(|e| eval_error(expr, *e))?;
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|n| n as f64))
+        .map_err(|_| {
+            AyasError::Tool(ToolError::ExecutionFailed(format!(
+                "'{expr}' evaluated to a {}, not a number",
+                value.type_name()
+            )))
+        })
+}
+
+/// Shared calculator engine: the ADL sandbox plus floating-point overloads.
+fn calculator_engine() -> &'static rhai::Engine {
+    static ENGINE: OnceLock<rhai::Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = new_sandboxed_engine();
+        // Required for the `/` and `%` overloads below to take effect
+        engine.set_fast_operators(false);
+        engine.register_fn("/", |a: f64, b: f64| checked(b, a / b));
+        engine.register_fn("%", |a: f64, b: f64| checked(b, a % b));
+        engine.register_fn("pow", f64::powf);
+        engine.register_fn("min", f64::min);
+        engine.register_fn("max", f64::max);
+        engine
+    })
+}
+
+/// `result` of dividing by `divisor`, or a division-by-zero error.
+fn checked(divisor: f64, result: f64) -> std::result::Result<f64, Box<rhai::EvalAltResult>> {
+    if divisor == 0.0 {
+        Err(DIVISION_BY_ZERO.into())
+    } else {
+        Ok(result)
+    }
+}
+
+/// Rewrite `expr` into Rhai syntax: integer literals become floats (so `7 / 2`
+/// is 3.5) and `^` becomes `**`.
+fn normalize(expr: &str) -> String {
+    let chars: Vec<char> = expr.chars().collect();
+    let digits = |i: &mut usize, out: &mut String| {
+        while *i < chars.len() && chars[*i].is_ascii_digit() {
+            out.push(chars[*i]);
+            *i += 1;
+        }
+    };
+    let mut out = String::with_capacity(expr.len() + 8);
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_alphabetic() || c == '_' {
+            // Identifiers keep their digits (`log10`, `x2`)
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                out.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        let starts_number = c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()));
+        if !starts_number {
+            if c == '^' {
+                out.push_str("**");
+            } else {
+                out.push(c);
+            }
+            i += 1;
+            continue;
+        }
+
+        if c == '.' {
+            out.push('0');
+        }
+        digits(&mut i, &mut out);
+        out.push('.');
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+        }
+        let fraction_start = out.len();
+        digits(&mut i, &mut out);
+        if out.len() == fraction_start {
+            out.push('0');
+        }
+        // Exponent: e, E, e+, e-
+        if matches!(chars.get(i), Some('e' | 'E')) {
+            let sign = matches!(chars.get(i + 1), Some('+' | '-'));
+            let exp_digit = if sign { i + 2 } else { i + 1 };
+            if chars.get(exp_digit).is_some_and(|d| d.is_ascii_digit()) {
+                out.push('e');
+                if sign {
+                    out.push(chars[i + 1]);
+                }
+                i = exp_digit;
+                digits(&mut i, &mut out);
+            }
         }
     }
+    out
+}
+
+/// Map a Rhai evaluation error to a tool error naming the problem.
+fn eval_error(expr: &str, mut err: rhai::EvalAltResult) -> AyasError {
+    use rhai::EvalAltResult as E;
+
+    while let E::ErrorInFunctionCall(_, _, inner, _) = err {
+        err = *inner;
+    }
+    let error = match &err {
+        E::ErrorRuntime(value, _) if value.to_string() == DIVISION_BY_ZERO => {
+            ToolError::ExecutionFailed(format!("{DIVISION_BY_ZERO} in '{expr}'"))
+        }
+        E::ErrorArithmetic(message, _) if message.contains("zero") => {
+            ToolError::ExecutionFailed(format!("{DIVISION_BY_ZERO} in '{expr}'"))
+        }
+        E::ErrorFunctionNotFound(signature, _) => {
+            let name = signature.split(['(', ' ']).next().unwrap_or(signature);
+            ToolError::InvalidInput(format!("Undefined function '{name}' in '{expr}'"))
+        }
+        E::ErrorVariableNotFound(name, _) => {
+            ToolError::InvalidInput(format!("Undefined variable '{name}' in '{expr}'"))
+        }
+        _ => ToolError::ExecutionFailed(format!("Failed to evaluate '{expr}': {err}")),
+    };
+    AyasError::Tool(error)
 }
 
 #[cfg(test)]
@@ -97,4 +298,59 @@ mod tests {
             .await;
         assert!(result.is_err());
     }
+
+    async fn eval(tool: &dyn Tool, expression: &str) -> Result<String> {
+        tool.call(serde_json::json!({ "expression": expression })).await
+    }
+
+    #[tokio::test]
+    async fn eval_functions_and_powers() {
+        let tool = CalculatorTool;
+        assert_eq!(eval(&tool, "sqrt(16) + pow(2, 3)").await.unwrap(), "12");
+        assert_eq!(eval(&tool, "2 ^ 10").await.unwrap(), "1024");
+        assert_eq!(eval(&tool, "max(abs(-3), 2) * (1 + 1)").await.unwrap(), "6");
+        assert_eq!(eval(&tool, "7 / 2").await.unwrap(), "3.5");
+        assert_eq!(eval(&tool, "round(pi * 100) / 100").await.unwrap(), "3.14");
+    }
+
+    #[tokio::test]
+    async fn eval_scientific_notation() {
+        let tool = CalculatorTool;
+        assert_eq!(eval(&tool, "1.5e3 + 2E-1").await.unwrap(), "1500.2");
+        assert_eq!(eval(&tool, "1e20 * 3").await.unwrap(), "3e20");
+        assert_eq!(eval(&tool, ".5 * 4").await.unwrap(), "2");
+    }
+
+    #[tokio::test]
+    async fn eval_with_precision() {
+        let tool = CalculatorTool.with_precision(2);
+        assert_eq!(eval(&tool, "10 / 3").await.unwrap(), "3.33");
+        assert_eq!(eval(&tool, "2 / 8").await.unwrap(), "0.25");
+        assert_eq!(eval(&tool, "1 / 3e7").await.unwrap(), "3.33e-8");
+    }
+
+    #[tokio::test]
+    async fn eval_division_by_zero() {
+        let tool = CalculatorTool;
+        for expression in ["1 / 0", "5 % (2 - 2)"] {
+            let err = eval(&tool, expression).await.unwrap_err();
+            assert!(matches!(err, AyasError::Tool(ToolError::ExecutionFailed(_))));
+            assert!(err.to_string().contains("Division by zero"), "{expression}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn eval_undefined_function() {
+        let tool = CalculatorTool;
+        let err = eval(&tool, "foo(2)").await.unwrap_err();
+        assert!(matches!(err, AyasError::Tool(ToolError::InvalidInput(_))));
+        assert!(err.to_string().contains("Undefined function 'foo'"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn eval_rejects_statements() {
+        let tool = CalculatorTool;
+        assert!(eval(&tool, "let x = 1; x").await.is_err());
+        assert!(eval(&tool, "loop { }").await.is_err());
+    }
 }
@@ -1,8 +1,15 @@
 use async_trait::async_trait;
-use ayas_core::error::Result;
+use ayas_core::error::{AyasError, Result, ToolError};
 use ayas_core::tool::{Tool, ToolDefinition};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 
-/// Tool that returns the current date and time.
+/// Output format when no `format` argument is given.
+const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
+
+/// Tool that returns the current date and time, optionally in a given
+/// timezone and strftime format.
 pub struct DateTimeTool;
 
 #[async_trait]
@@ -10,24 +17,64 @@ impl Tool for DateTimeTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "datetime".into(),
-            description: "Returns the current date and time in UTC.".into(),
+            description: "Returns the current date and time in UTC, or in the given timezone."
+                .into(),
             parameters: serde_json::json!({
                 "type": "object",
-                "properties": {},
+                "properties": {
+                    "timezone": {
+                        "type": "string",
+                        "description": "IANA timezone name (e.g., 'Asia/Tokyo'). Defaults to UTC."
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "strftime-style format (e.g., '%Y-%m-%d'). Defaults to date, time and zone."
+                    }
+                },
                 "required": []
             }),
         }
     }
 
-    async fn call(&self, _input: serde_json::Value) -> Result<String> {
-        let now = chrono::Utc::now();
-        Ok(now.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+    async fn call(&self, input: serde_json::Value) -> Result<String> {
+        let timezone = input.get("timezone").and_then(|v| v.as_str());
+        let format = input.get("format").and_then(|v| v.as_str());
+        format_time(Utc::now(), timezone, format)
+    }
+}
+
+/// Format `now` in `timezone` (UTC when `None`) with `format`.
+fn format_time(now: DateTime<Utc>, timezone: Option<&str>, format: Option<&str>) -> Result<String> {
+    let format = format.unwrap_or(DEFAULT_FORMAT);
+    // Formatting an invalid specifier panics, so reject it up front
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.contains(&Item::Error) {
+        return Err(AyasError::Tool(ToolError::InvalidInput(format!(
+            "Invalid format string '{format}'"
+        ))));
+    }
+
+    match timezone {
+        None => Ok(now.format_with_items(items.iter()).to_string()),
+        Some(name) => {
+            let tz: Tz = name.parse().map_err(|_| {
+                AyasError::Tool(ToolError::InvalidInput(format!(
+                    "Unknown timezone '{name}'; expected an IANA name such as 'Europe/London'"
+                )))
+            })?;
+            Ok(now.with_timezone(&tz).format_with_items(items.iter()).to_string())
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+
+    fn fixed_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, 12, 30, 0).unwrap()
+    }
 
     #[test]
     fn definition_has_correct_schema() {
@@ -35,6 +82,8 @@ mod tests {
         let def = tool.definition();
         assert_eq!(def.name, "datetime");
         assert!(def.description.contains("date"));
+        assert!(def.parameters["properties"].get("timezone").is_some());
+        assert!(def.validate().is_ok());
     }
 
     #[tokio::test]
@@ -52,4 +101,34 @@ mod tests {
         let result = tool.call(serde_json::json!({})).await.unwrap();
         assert!(!result.is_empty());
     }
+
+    #[test]
+    fn formats_in_timezones() {
+        let now = fixed_time();
+        assert_eq!(format_time(now, None, None).unwrap(), "2026-01-15 12:30:00 UTC");
+        assert_eq!(
+            format_time(now, Some("Asia/Tokyo"), None).unwrap(),
+            "2026-01-15 21:30:00 JST"
+        );
+        assert_eq!(
+            format_time(now, Some("America/New_York"), Some("%H:%M %z")).unwrap(),
+            "07:30 -0500"
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_timezone() {
+        let tool = DateTimeTool;
+        let err = tool
+            .call(serde_json::json!({"timezone": "Mars/Olympus_Mons"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AyasError::Tool(ToolError::InvalidInput(_))));
+        assert!(err.to_string().contains("Unknown timezone 'Mars/Olympus_Mons'"));
+    }
+
+    #[test]
+    fn rejects_invalid_format() {
+        assert!(format_time(fixed_time(), None, Some("%Q")).is_err());
+    }
 }
@@ -17,6 +17,57 @@ impl WebSearchTool {
             client: reqwest::Client::new(),
         }
     }
+
+    /// Create a tool with an explicit API key instead of reading the environment.
+    pub fn with_api_key(api_key: Option<String>) -> Self {
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Run a search and return the structured results.
+    pub async fn search(&self, query: &str, max_results: u32) -> Result<Vec<SearchResult>> {
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| {
+                AyasError::Other(
+                    "TAVILY_API_KEY environment variable not set. Web search is unavailable."
+                        .into(),
+                )
+            })?;
+
+        let tavily_req = TavilyRequest {
+            api_key: api_key.clone(),
+            query: query.to_string(),
+            search_depth: "basic".into(),
+            max_results,
+        };
+
+        let response = self
+            .client
+            .post("https://api.tavily.com/search")
+            .json(&tavily_req)
+            .send()
+            .await
+            .map_err(|e| AyasError::Other(format!("Web search request failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AyasError::Other(format!(
+                "Tavily API error ({status}): {body}"
+            )));
+        }
+
+        let tavily_resp: TavilyResponse = response
+            .json()
+            .await
+            .map_err(|e| AyasError::Other(format!("Failed to parse search response: {e}")))?;
+
+        Ok(tavily_resp.results)
+    }
 }
 
 #[derive(Serialize)]
@@ -29,16 +80,16 @@ struct TavilyRequest {
 
 #[derive(Deserialize)]
 struct TavilyResponse {
-    results: Vec<TavilyResult>,
+    results: Vec<SearchResult>,
 }
 
-#[derive(Deserialize)]
-struct TavilyResult {
-    title: String,
-    url: String,
-    content: String,
-    #[allow(dead_code)]
-    score: f64,
+/// A single web search result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub content: String,
+    pub score: f64,
 }
 
 #[async_trait]
@@ -76,47 +127,11 @@ impl Tool for WebSearchTool {
             .and_then(|v| v.as_u64())
             .unwrap_or(5) as u32;
 
-        let api_key = self
-            .api_key
-            .as_ref()
-            .ok_or_else(|| {
-                AyasError::Other(
-                    "TAVILY_API_KEY environment variable not set. Web search is unavailable."
-                        .into(),
-                )
-            })?;
-
-        let tavily_req = TavilyRequest {
-            api_key: api_key.clone(),
-            query: query.to_string(),
-            search_depth: "basic".into(),
-            max_results,
-        };
-
-        let response = self
-            .client
-            .post("https://api.tavily.com/search")
-            .json(&tavily_req)
-            .send()
-            .await
-            .map_err(|e| AyasError::Other(format!("Web search request failed: {e}")))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(AyasError::Other(format!(
-                "Tavily API error ({status}): {body}"
-            )));
-        }
-
-        let tavily_resp: TavilyResponse = response
-            .json()
-            .await
-            .map_err(|e| AyasError::Other(format!("Failed to parse search response: {e}")))?;
+        let results = self.search(query, max_results).await?;
 
         // Format results as readable text
         let mut output = String::new();
-        for (i, result) in tavily_resp.results.iter().enumerate() {
+        for (i, result) in results.iter().enumerate() {
             output.push_str(&format!(
                 "{}. {}\n   URL: {}\n   {}\n\n",
                 i + 1,
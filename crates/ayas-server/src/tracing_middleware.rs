@@ -1,6 +1,10 @@
 use ayas_smith::client::{SmithClient, SmithConfig};
+use ayas_smith::context::{
+    TRACE_PARENT_HEADER, TRACE_SAMPLE_HEADER, parse_sample_override, parse_trace_parent,
+};
 use ayas_smith::types::{Run, RunType};
 use serde_json::Value;
+use uuid::Uuid;
 
 /// Context for tracing graph executions via ayas-smith.
 ///
@@ -9,6 +13,8 @@ use serde_json::Value;
 pub struct TracingContext {
     client: SmithClient,
     project: String,
+    /// `(trace_id, parent_run_id)` the recorded runs join, if any.
+    parent: Option<(Uuid, Uuid)>,
 }
 
 impl TracingContext {
@@ -17,9 +23,16 @@ impl TracingContext {
         Self {
             client,
             project: project.into(),
+            parent: None,
         }
     }
 
+    /// Record runs as children of `parent_run_id` in an existing trace.
+    pub fn with_trace_parent(mut self, trace_id: Uuid, parent_run_id: Uuid) -> Self {
+        self.parent = Some((trace_id, parent_run_id));
+        self
+    }
+
     /// Create from environment variables.  Returns `None` when tracing is not
     /// globally enabled.
     ///
@@ -54,7 +67,11 @@ impl TracingContext {
         }
 
         let client = SmithClient::new(config);
-        Self { client, project }
+        Self {
+            client,
+            project,
+            parent: None,
+        }
     }
 
     /// Record a graph execution run.
@@ -70,9 +87,12 @@ impl TracingContext {
     ) {
         let input_json = serde_json::to_string(input).unwrap_or_else(|_| "{}".into());
 
-        let builder = Run::builder(name, RunType::Graph)
+        let mut builder = Run::builder(name, RunType::Graph)
             .project(&self.project)
             .input(&input_json);
+        if let Some((trace_id, parent_run_id)) = self.parent {
+            builder = builder.trace_id(trace_id).parent_run_id(parent_run_id);
+        }
 
         let run = if let Some(err) = error {
             builder.finish_err(err)
@@ -86,12 +106,33 @@ impl TracingContext {
 }
 
 /// Check `X-Trace-Enabled` header to enable per-request tracing.
+///
+/// `x-trace-sample: always` also enables tracing for the request.
 pub fn is_tracing_requested(headers: &axum::http::HeaderMap) -> bool {
     headers
         .get("X-Trace-Enabled")
         .and_then(|v| v.to_str().ok())
         .map(|v| v == "true" || v == "1")
         .unwrap_or(false)
+        || trace_sample_override(headers) == Some(true)
+}
+
+/// Read a forced sampling decision from the `x-trace-sample` header
+/// (`always` / `never`).
+pub fn trace_sample_override(headers: &axum::http::HeaderMap) -> Option<bool> {
+    headers
+        .get(TRACE_SAMPLE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_sample_override)
+}
+
+/// Read the `(trace_id, parent_run_id)` to join from the
+/// `x-ayas-trace-parent` header.
+pub fn trace_parent(headers: &axum::http::HeaderMap) -> Option<(Uuid, Uuid)> {
+    headers
+        .get(TRACE_PARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_trace_parent)
 }
 
 #[cfg(test)]
@@ -135,6 +176,20 @@ mod tests {
         assert!(!is_tracing_requested(&headers));
     }
 
+    #[test]
+    fn test_trace_sample_override_header() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(trace_sample_override(&headers), None);
+
+        headers.insert("x-trace-sample", "always".parse().unwrap());
+        assert_eq!(trace_sample_override(&headers), Some(true));
+        assert!(is_tracing_requested(&headers));
+
+        headers.insert("x-trace-sample", "never".parse().unwrap());
+        assert_eq!(trace_sample_override(&headers), Some(false));
+        assert!(!is_tracing_requested(&headers));
+    }
+
     #[tokio::test]
     async fn test_tracing_context_records_run() {
         let dir = tempfile::tempdir().unwrap();
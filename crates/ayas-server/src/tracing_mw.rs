@@ -3,17 +3,25 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use axum::body::Body;
-use axum::http::{Request, Response};
+use axum::http::{HeaderValue, Request, Response};
 use tower::{Layer, Service};
 
 use ayas_smith::client::SmithClient;
+use ayas_smith::context::{TRACE_PARENT_HEADER, format_trace_parent};
 use ayas_smith::types::{Run, RunType};
 
+use crate::tracing_middleware::trace_parent;
+
 /// Tower layer that auto-traces HTTP requests to ayas-smith.
 ///
 /// Tracing is activated when either:
 /// - The `X-Trace-Enabled: true` (or `1`) request header is present, or
 /// - The `AYAS_TRACING_ENABLED` environment variable is `true` or `1`.
+///
+/// A request carrying `x-ayas-trace-parent` joins that trace: its run is
+/// recorded under the given parent. The header is then rewritten to point
+/// at the request's run, so handlers nest under it, and echoed on the
+/// response for the caller.
 #[derive(Clone)]
 pub struct TracingLayer {
     client: SmithClient,
@@ -57,7 +65,7 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request<Body>) -> Self::Future {
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
         let client = self.client.clone();
         let mut inner = self.inner.clone();
         // swap to ensure inner is ready (standard tower pattern)
@@ -84,10 +92,12 @@ where
                 return inner.call(req).await;
             }
 
-            let builder = Run::builder(
+            let run_id = uuid::Uuid::new_v4();
+            let mut builder = Run::builder(
                 format!("{method} {path}"),
                 RunType::Chain,
             )
+            .run_id(run_id)
             .project(client.project().to_string())
             .input(
                 serde_json::json!({"method": &method, "path": &path}).to_string(),
@@ -95,11 +105,22 @@ where
             .metadata(
                 serde_json::json!({"source": "auto-tracing"}).to_string(),
             );
+            let trace_id = match trace_parent(req.headers()) {
+                Some((trace_id, parent_run_id)) => {
+                    builder = builder.trace_id(trace_id).parent_run_id(parent_run_id);
+                    trace_id
+                }
+                None => run_id,
+            };
+            let own_parent = HeaderValue::from_str(&format_trace_parent(trace_id, run_id))
+                .expect("hex UUIDs are a valid header value");
+            req.headers_mut().insert(TRACE_PARENT_HEADER, own_parent.clone());
 
-            let result = inner.call(req).await;
+            let mut result = inner.call(req).await;
 
-            match &result {
-                Ok(_resp) => {
+            match &mut result {
+                Ok(resp) => {
+                    resp.headers_mut().insert(TRACE_PARENT_HEADER, own_parent);
                     let run = builder.finish_ok(
                         serde_json::json!({"status": "ok"}).to_string(),
                     );
@@ -179,6 +200,67 @@ mod tests {
         assert!(project_dir.exists(), "Expected trace data directory");
     }
 
+    #[tokio::test]
+    async fn tracing_joins_incoming_trace_parent() {
+        use ayas_smith::context::parse_trace_parent;
+        use ayas_smith::types::RunFilter;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = ayas_smith::client::SmithConfig::default()
+            .with_base_dir(dir.path())
+            .with_project("parent-test")
+            .with_batch_size(1)
+            .with_flush_interval(std::time::Duration::from_millis(50));
+        let client = SmithClient::new(config);
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let seen_in_handler = seen.clone();
+        let app = Router::new()
+            .route(
+                "/health",
+                get(move |headers: axum::http::HeaderMap| {
+                    let seen = seen_in_handler.clone();
+                    async move {
+                        *seen.lock().unwrap() = trace_parent(&headers);
+                        "ok"
+                    }
+                }),
+            )
+            .layer(TracingLayer::new(client.clone()));
+
+        let trace_id = uuid::Uuid::new_v4();
+        let upstream_run = uuid::Uuid::new_v4();
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header("x-trace-enabled", "true")
+                    .header(TRACE_PARENT_HEADER, format_trace_parent(trace_id, upstream_run))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // The handler and the response both see the request's own run.
+        let seen = *seen.lock().unwrap();
+        let (seen_trace, http_run) = seen.unwrap();
+        assert_eq!(seen_trace, trace_id);
+        let echoed = resp.headers()[TRACE_PARENT_HEADER].to_str().unwrap();
+        assert_eq!(parse_trace_parent(echoed), Some((trace_id, http_run)));
+
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        let filter = RunFilter {
+            project: Some("parent-test".into()),
+            ..Default::default()
+        };
+        let runs = client.store().unwrap().list_runs(&filter).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, http_run);
+        assert_eq!(runs[0].trace_id, trace_id);
+        assert_eq!(runs[0].parent_run_id, Some(upstream_run));
+    }
+
     #[tokio::test]
     async fn tracing_noop_client_header_ignored() {
         let client = SmithClient::noop();
@@ -16,6 +16,10 @@ pub struct ChatInvokeRequest {
     pub temperature: Option<f64>,
     #[serde(default)]
     pub max_tokens: Option<u32>,
+    /// Continue a server-side conversation: its stored history is sent before
+    /// `messages`, and this turn is appended to it.
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +27,26 @@ pub struct ChatInvokeResponse {
     pub content: String,
     pub tokens_in: u64,
     pub tokens_out: u64,
+    /// Set when moderation refused the request; `content` is then empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flagged: Option<FlaggedContent>,
+}
+
+// --- Moderation ---
+
+/// Which side of a model call a moderation check ran on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationStage {
+    Input,
+    Output,
+}
+
+/// Details of a moderation refusal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlaggedContent {
+    pub stage: ModerationStage,
+    pub categories: Vec<String>,
 }
 
 // --- Agent ---
@@ -36,6 +60,9 @@ pub struct AgentInvokeRequest {
     pub messages: Vec<Message>,
     #[serde(default)]
     pub recursion_limit: Option<usize>,
+    /// Coerce stringified tool-call arguments to the tool's schema types.
+    #[serde(default)]
+    pub coerce_arguments: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -63,6 +90,10 @@ pub enum AgentSseEvent {
     Error {
         message: String,
     },
+    Flagged {
+        stage: ModerationStage,
+        categories: Vec<String>,
+    },
 }
 
 // --- Graph ---
@@ -140,6 +171,10 @@ pub struct GraphStreamRequest {
     /// Defaults to "values" if omitted.
     #[serde(default)]
     pub stream_mode: Option<String>,
+    /// Stream modes as a list, e.g. `["values", "debug"]`. Takes precedence
+    /// over `stream_mode` when non-empty.
+    #[serde(default)]
+    pub stream_modes: Vec<String>,
     #[serde(default)]
     pub recursion_limit: Option<usize>,
 }
@@ -177,6 +212,18 @@ pub struct ResumeRequest {
     pub resume_value: serde_json::Value,
 }
 
+/// Resume a thread directly from one of its checkpoints.
+#[derive(Debug, Deserialize)]
+pub struct HitlResumeRequest {
+    pub thread_id: String,
+    pub checkpoint_id: String,
+    #[serde(default)]
+    pub resume_value: serde_json::Value,
+    /// Key of the interrupt this answers; rejected if the checkpoint waits on another.
+    #[serde(default)]
+    pub interrupt_key: Option<String>,
+}
+
 // --- Research ---
 
 #[derive(Debug, Deserialize)]
@@ -186,6 +233,9 @@ pub struct ResearchInvokeRequest {
     pub agent: Option<String>,
     #[serde(default)]
     pub previous_interaction_id: Option<String>,
+    /// Re-attach to an in-progress interaction instead of starting a new one.
+    #[serde(default)]
+    pub resume_interaction_id: Option<String>,
     #[serde(default)]
     pub attachments: Vec<ayas_core::message::ContentPart>,
 }
@@ -281,6 +331,7 @@ mod tests {
             content: "Hello!".into(),
             tokens_in: 10,
             tokens_out: 5,
+            flagged: None,
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("\"content\":\"Hello!\""));
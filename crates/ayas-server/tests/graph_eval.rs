@@ -152,6 +152,7 @@ Respond with ONLY a JSON object: {{"relevance": N, "completeness": N, "correctne
 fn get_model() -> Box<dyn ChatModel> {
     let api_key = std::env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY must be set");
     create_chat_model(&Provider::Gemini, api_key, "gemini-2.0-flash".into())
+        .expect("gemini-2.0-flash is a known model")
 }
 
 fn create_smith_client(trace_dir: &std::path::Path) -> SmithClient {
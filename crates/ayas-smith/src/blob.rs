@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::error::{Result, SmithError};
+use crate::types::Run;
+
+/// JSON key marking an offloaded payload: `{"__ref__": "<key>"}`.
+pub const BLOB_REF_KEY: &str = "__ref__";
+
+/// Storage for run payloads too large to keep inline.
+///
+/// Methods are synchronous so the same store can be used from the async
+/// writer and from the blocking DuckDB query client.
+pub trait BlobStore: Send + Sync + std::fmt::Debug {
+    /// Store a payload and return its key.
+    fn put(&self, data: &[u8]) -> Result<String>;
+
+    /// Fetch a payload by key, or `None` if it does not exist.
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+}
+
+/// In-process blob store (for tests and ephemeral sessions).
+#[derive(Debug, Default)]
+pub struct InMemoryBlobStore {
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryBlobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored blobs.
+    pub fn len(&self) -> usize {
+        self.blobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl BlobStore for InMemoryBlobStore {
+    fn put(&self, data: &[u8]) -> Result<String> {
+        let key = Uuid::new_v4().to_string();
+        self.blobs.lock().unwrap().insert(key.clone(), data.to_vec());
+        Ok(key)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.blobs.lock().unwrap().get(key).cloned())
+    }
+}
+
+/// Blob store writing one file per payload under a directory.
+#[derive(Debug, Clone)]
+pub struct FsBlobStore {
+    dir: PathBuf,
+}
+
+impl FsBlobStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        // Keys are generated UUIDs; reject anything that could escape `dir`.
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(SmithError::Query(format!("invalid blob key '{key}'")));
+        }
+        Ok(self.dir.join(key))
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    fn put(&self, data: &[u8]) -> Result<String> {
+        std::fs::create_dir_all(&self.dir)?;
+        let key = Uuid::new_v4().to_string();
+        std::fs::write(self.path_for(&key)?, data)?;
+        Ok(key)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path_for(key)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Replace `payload` with a blob reference if it exceeds `max_inline_bytes`.
+pub fn offload_payload(
+    payload: &str,
+    max_inline_bytes: usize,
+    blobs: &dyn BlobStore,
+) -> Result<String> {
+    if payload.len() <= max_inline_bytes {
+        return Ok(payload.to_string());
+    }
+    let key = blobs.put(payload.as_bytes())?;
+    Ok(serde_json::json!({ BLOB_REF_KEY: key }).to_string())
+}
+
+/// Return the blob key if `payload` is a `{"__ref__": "<key>"}` reference.
+pub fn blob_ref(payload: &str) -> Option<String> {
+    // Cheap pre-check so ordinary payloads are not parsed.
+    if !payload.trim_start().starts_with('{') || !payload.contains(BLOB_REF_KEY) {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(payload).ok()?;
+    let map = value.as_object()?;
+    if map.len() != 1 {
+        return None;
+    }
+    map.get(BLOB_REF_KEY)?.as_str().map(str::to_string)
+}
+
+/// Resolve a blob reference back to its payload.
+///
+/// Non-reference payloads are returned unchanged, as are references whose
+/// blob is missing.
+pub fn resolve_payload(payload: &str, blobs: &dyn BlobStore) -> Result<String> {
+    let Some(key) = blob_ref(payload) else {
+        return Ok(payload.to_string());
+    };
+    match blobs.get(&key)? {
+        Some(data) => String::from_utf8(data)
+            .map_err(|e| SmithError::Query(format!("blob '{key}' is not UTF-8: {e}"))),
+        None => Ok(payload.to_string()),
+    }
+}
+
+/// Offload a run's oversized `input` and `output` in place.
+pub fn offload_run(run: &mut Run, max_inline_bytes: usize, blobs: &dyn BlobStore) -> Result<()> {
+    run.input = offload_payload(&run.input, max_inline_bytes, blobs)?;
+    if let Some(output) = &run.output {
+        run.output = Some(offload_payload(output, max_inline_bytes, blobs)?);
+    }
+    Ok(())
+}
+
+/// Resolve blob references in a run's `input` and `output` in place.
+pub fn resolve_run(run: &mut Run, blobs: &dyn BlobStore) -> Result<()> {
+    run.input = resolve_payload(&run.input, blobs)?;
+    if let Some(output) = &run.output {
+        run.output = Some(resolve_payload(output, blobs)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RunType;
+
+    #[test]
+    fn small_payload_stays_inline() {
+        let blobs = InMemoryBlobStore::new();
+        let out = offload_payload(r#"{"q": "hi"}"#, 100, &blobs).unwrap();
+        assert_eq!(out, r#"{"q": "hi"}"#);
+        assert!(blobs.is_empty());
+    }
+
+    #[test]
+    fn large_payload_roundtrip_in_memory() {
+        let blobs = InMemoryBlobStore::new();
+        let big = format!(r#"{{"doc": "{}"}}"#, "x".repeat(1000));
+        let stored = offload_payload(&big, 100, &blobs).unwrap();
+        assert!(blob_ref(&stored).is_some());
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(resolve_payload(&stored, &blobs).unwrap(), big);
+    }
+
+    #[test]
+    fn fs_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = FsBlobStore::new(dir.path().join("blobs"));
+        let key = blobs.put(b"payload").unwrap();
+        assert_eq!(blobs.get(&key).unwrap().as_deref(), Some(&b"payload"[..]));
+        assert!(blobs.get(&Uuid::new_v4().to_string()).unwrap().is_none());
+        assert!(blobs.get("../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn blob_ref_detection() {
+        assert_eq!(blob_ref(r#"{"__ref__": "abc"}"#).as_deref(), Some("abc"));
+        assert!(blob_ref(r#"{"__ref__": "abc", "other": 1}"#).is_none());
+        assert!(blob_ref(r#""__ref__""#).is_none());
+        assert!(blob_ref("plain text").is_none());
+    }
+
+    #[test]
+    fn missing_blob_leaves_reference() {
+        let blobs = InMemoryBlobStore::new();
+        let reference = r#"{"__ref__": "missing"}"#;
+        assert_eq!(resolve_payload(reference, &blobs).unwrap(), reference);
+    }
+
+    #[test]
+    fn offload_and_resolve_run() {
+        let blobs = InMemoryBlobStore::new();
+        let mut run = Run::builder("chain", RunType::Chain)
+            .input("i".repeat(50))
+            .finish_ok("o".repeat(5));
+        offload_run(&mut run, 10, &blobs).unwrap();
+        assert!(blob_ref(&run.input).is_some());
+        assert_eq!(run.output.as_deref(), Some("ooooo"));
+
+        resolve_run(&mut run, &blobs).unwrap();
+        assert_eq!(run.input, "i".repeat(50));
+    }
+}
@@ -6,8 +6,9 @@ use uuid::Uuid;
 use crate::error::SmithError;
 use crate::store::SmithStore;
 use crate::types::{
-    Dataset, Example, Feedback, FeedbackFilter, LatencyStats, Project, Run, RunFilter, RunPatch,
-    RunStatus, RunType, TokenUsageSummary,
+    Dataset, Example, Feedback, FeedbackFilter, FeedbackStat, FeedbackStatsFilter, LatencyStats,
+    ModelTokenUsage, Project, Run, RunAggregate, RunFilter, RunPatch, RunStatus, RunType,
+    TimeBucket, TokenUsageSummary,
 };
 
 /// ClickHouse-backed SmithStore using the HTTP API.
@@ -198,6 +199,91 @@ impl ClickHouseStore {
         Ok(())
     }
 
+    /// WHERE conditions for the aggregation filters (project, run type,
+    /// status, name, time range and tags).
+    fn aggregate_conditions(filter: &RunFilter) -> Vec<String> {
+        let ts = |t: &chrono::DateTime<Utc>| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let mut conditions = Vec::new();
+        if let Some(ref project) = filter.project {
+            conditions.push(format!("project = '{}'", Self::escape_string(project)));
+        }
+        if let Some(run_type) = filter.run_type {
+            conditions.push(format!("run_type = '{}'", run_type.as_str()));
+        }
+        if let Some(status) = filter.status {
+            conditions.push(format!("status = '{}'", status.as_str()));
+        }
+        if let Some(ref name) = filter.name {
+            conditions.push(format!("name = '{}'", Self::escape_string(name)));
+        }
+        if let Some(ref start_after) = filter.start_after {
+            conditions.push(format!("start_time > '{}'", ts(start_after)));
+        }
+        if let Some(ref start_before) = filter.start_before {
+            conditions.push(format!("start_time < '{}'", ts(start_before)));
+        }
+        if let Some(tags) = Self::tags_condition(&filter.tags) {
+            conditions.push(tags);
+        }
+        conditions
+    }
+
+    /// Condition matching runs that carry every tag in `tags`.
+    fn tags_condition(tags: &[String]) -> Option<String> {
+        if tags.is_empty() {
+            return None;
+        }
+        let list: Vec<String> = tags
+            .iter()
+            .map(|t| format!("'{}'", Self::escape_string(t)))
+            .collect();
+        Some(format!("hasAll(tags, [{}])", list.join(", ")))
+    }
+
+    /// Build the `SELECT` for [`SmithStore::feedback_stats`].
+    fn feedback_stats_sql(filter: &FeedbackStatsFilter) -> String {
+        let ts = |t: &chrono::DateTime<Utc>| t.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+        let mut conditions = Vec::new();
+        if let Some(ref project) = filter.project {
+            conditions.push(format!(
+                "run_id IN (SELECT run_id FROM runs WHERE project = '{}')",
+                Self::escape_string(project)
+            ));
+        }
+        if let Some(ref key) = filter.key {
+            conditions.push(format!("key = '{}'", Self::escape_string(key)));
+        }
+        if let Some(ref after) = filter.created_after {
+            conditions.push(format!("created_at > '{}'", ts(after)));
+        }
+        if let Some(ref before) = filter.created_before {
+            conditions.push(format!("created_at < '{}'", ts(before)));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let bucket = match filter.bucket {
+            Some(TimeBucket::Hourly) => "toString(toStartOfHour(created_at))",
+            Some(TimeBucket::Daily) => "toString(toStartOfDay(created_at))",
+            None => "''",
+        };
+        format!(
+            "SELECT
+                key,
+                {bucket} as bucket,
+                count() as cnt,
+                avg(score) as mean,
+                if(count() > 1, stddevSamp(score), 0) as stddev
+             FROM feedback
+             {where_clause}
+             GROUP BY key, bucket
+             ORDER BY key, bucket
+             FORMAT JSONEachRow"
+        )
+    }
+
     fn escape_string(s: &str) -> String {
         s.replace('\\', "\\\\").replace('\'', "\\'")
     }
@@ -349,6 +435,9 @@ impl SmithStore for ClickHouseStore {
                 start_after.format("%Y-%m-%d %H:%M:%S%.3f")
             ));
         }
+        if let Some(tags) = Self::tags_condition(&filter.tags) {
+            conditions.push(tags);
+        }
 
         let where_clause = if conditions.is_empty() {
             String::new()
@@ -428,6 +517,7 @@ impl SmithStore for ClickHouseStore {
             total_output_tokens: ch_i64(&parsed["total_output"]),
             total_tokens: ch_i64(&parsed["total"]),
             run_count: ch_i64(&parsed["run_count"]),
+            by_model: Default::default(),
         })
     }
 
@@ -457,6 +547,88 @@ impl SmithStore for ClickHouseStore {
         })
     }
 
+    async fn aggregate(&self, filter: &RunFilter) -> Result<RunAggregate, SmithError> {
+        let conditions = Self::aggregate_conditions(filter);
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sql = format!(
+            "SELECT
+                count() as run_count,
+                quantileIf(0.5)(latency_ms, latency_ms IS NOT NULL) as p50,
+                quantileIf(0.9)(latency_ms, latency_ms IS NOT NULL) as p90,
+                quantileIf(0.95)(latency_ms, latency_ms IS NOT NULL) as p95,
+                quantileIf(0.99)(latency_ms, latency_ms IS NOT NULL) as p99,
+                ifNull(sum(input_tokens), 0) as total_input,
+                ifNull(sum(output_tokens), 0) as total_output,
+                ifNull(sum(total_tokens), 0) as total,
+                count(total_tokens) as token_runs
+             FROM runs FINAL
+             {where_clause}
+             FORMAT JSONEachRow"
+        );
+
+        let body = self.query(&sql).await?;
+        let parsed: serde_json::Value =
+            serde_json::from_str(body.trim()).unwrap_or(serde_json::json!({}));
+        // quantile over an empty set yields NaN; report 0 like the other stores.
+        let pct = |v: &serde_json::Value| {
+            let f = ch_f64(v);
+            if f.is_finite() { f } else { 0.0 }
+        };
+
+        let mut by_model_conditions = conditions.clone();
+        by_model_conditions.push("run_type = 'llm'".into());
+        by_model_conditions.push("total_tokens IS NOT NULL".into());
+        let by_model_sql = format!(
+            "SELECT
+                name,
+                ifNull(sum(input_tokens), 0) as input,
+                ifNull(sum(output_tokens), 0) as output,
+                count() as run_count
+             FROM runs FINAL
+             WHERE {}
+             GROUP BY name
+             FORMAT JSONEachRow",
+            by_model_conditions.join(" AND ")
+        );
+        let by_model_body = self.query(&by_model_sql).await?;
+        let by_model = by_model_body
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+            .filter_map(|row| {
+                let name = row["name"].as_str()?.to_string();
+                let usage = ModelTokenUsage {
+                    input_tokens: ch_i64(&row["input"]),
+                    output_tokens: ch_i64(&row["output"]),
+                    run_count: ch_i64(&row["run_count"]),
+                };
+                Some((name, usage))
+            })
+            .collect();
+
+        Ok(RunAggregate {
+            run_count: ch_i64(&parsed["run_count"]),
+            latency: LatencyStats {
+                p50: pct(&parsed["p50"]),
+                p90: pct(&parsed["p90"]),
+                p95: pct(&parsed["p95"]),
+                p99: pct(&parsed["p99"]),
+            },
+            tokens: TokenUsageSummary {
+                total_input_tokens: ch_i64(&parsed["total_input"]),
+                total_output_tokens: ch_i64(&parsed["total_output"]),
+                total_tokens: ch_i64(&parsed["total"]),
+                run_count: ch_i64(&parsed["token_runs"]),
+                by_model,
+            },
+            total_cost: None,
+        })
+    }
+
     async fn put_feedback(&self, feedback: &Feedback) -> Result<(), SmithError> {
         let row = serde_json::json!({
             "id": feedback.id.to_string(),
@@ -471,6 +643,30 @@ impl SmithStore for ClickHouseStore {
             .await
     }
 
+    async fn feedback_stats(
+        &self,
+        filter: &FeedbackStatsFilter,
+    ) -> Result<Vec<FeedbackStat>, SmithError> {
+        let body = self.query(&Self::feedback_stats_sql(filter)).await?;
+        let mut stats = Vec::new();
+        for line in body.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed: serde_json::Value =
+                serde_json::from_str(line).map_err(SmithError::Serialization)?;
+            stats.push(FeedbackStat {
+                key: parsed["key"].as_str().unwrap_or("").to_string(),
+                bucket: ch_datetime(&parsed["bucket"]),
+                count: ch_i64(&parsed["cnt"]),
+                mean: ch_f64(&parsed["mean"]),
+                stddev: ch_f64(&parsed["stddev"]),
+            });
+        }
+        Ok(stats)
+    }
+
     async fn list_feedback(&self, filter: &FeedbackFilter) -> Result<Vec<Feedback>, SmithError> {
         let mut conditions = Vec::new();
 
@@ -816,6 +1012,50 @@ mod tests {
         assert_eq!(store.password, "secret");
     }
 
+    #[test]
+    fn feedback_stats_sql_from_filter() {
+        let filter = FeedbackStatsFilter {
+            project: Some("p'1".into()),
+            key: Some("correctness".into()),
+            bucket: Some(TimeBucket::Daily),
+            ..Default::default()
+        };
+        let sql = ClickHouseStore::feedback_stats_sql(&filter);
+        assert!(sql.contains("project = 'p\\'1'"));
+        assert!(sql.contains("key = 'correctness'"));
+        assert!(sql.contains("toStartOfDay(created_at)"));
+        assert!(sql.contains("GROUP BY key, bucket"));
+
+        let sql = ClickHouseStore::feedback_stats_sql(&FeedbackStatsFilter::default());
+        assert!(!sql.contains("WHERE"));
+        assert!(sql.contains("'' as bucket"));
+    }
+
+    #[test]
+    fn aggregate_conditions_from_filter() {
+        let filter = RunFilter {
+            project: Some("p'1".into()),
+            run_type: Some(RunType::Llm),
+            name: Some("gpt".into()),
+            start_after: Some(
+                chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            ..Default::default()
+        };
+        let conditions = ClickHouseStore::aggregate_conditions(&filter);
+        assert_eq!(
+            conditions,
+            vec![
+                "project = 'p\\'1'".to_string(),
+                "run_type = 'llm'".to_string(),
+                "name = 'gpt'".to_string(),
+                "start_time > '2024-01-02 03:04:05.000'".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn parse_uuid_valid() {
         let id = Uuid::new_v4();
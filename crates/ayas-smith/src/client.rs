@@ -4,14 +4,34 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use chrono::Utc;
+use tokio::sync::{mpsc, oneshot};
 use uuid::Uuid;
 
+use crate::blob::{self, BlobStore, FsBlobStore};
 use crate::duckdb_store::DuckDbStore;
-use crate::error::Result;
+use crate::error::{Result, SmithError};
+use crate::redact::{redact_run, Redactor};
 use crate::store::SmithStore;
-use crate::types::{Run, RunBuilder, RunStatus, RunType};
+use crate::types::{
+    FeedbackStat, FeedbackStatsFilter, Run, RunBuilder, RunStatus, RunTree, RunType,
+};
 use crate::writer;
 
+/// What `submit_run` does when the writer's channel is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Drop the run being submitted (never blocks the caller).
+    #[default]
+    DropNewest,
+    /// Evict the oldest queued run to make room for the new one.
+    DropOldest,
+    /// Wait until the writer makes room.
+    ///
+    /// Blocks the submitting thread; on a current-thread Tokio runtime,
+    /// where waiting would deadlock the writer, the run is dropped instead.
+    Block,
+}
+
 /// Configuration for the SmithClient.
 #[derive(Debug, Clone)]
 pub struct SmithConfig {
@@ -27,6 +47,18 @@ pub struct SmithConfig {
     pub enabled: bool,
     /// Bounded channel capacity for backpressure control.
     pub channel_capacity: usize,
+    /// Behaviour when the channel is full.
+    pub backpressure: BackpressurePolicy,
+    /// Payloads larger than this many bytes are offloaded to `blob_store`
+    /// and replaced by a `{"__ref__": "<key>"}` reference. `None` keeps
+    /// everything inline.
+    pub max_inline_bytes: Option<usize>,
+    /// Store for offloaded payloads. Defaults to `<base_dir>/blobs` when
+    /// `max_inline_bytes` is set.
+    pub blob_store: Option<Arc<dyn BlobStore>>,
+    /// Applied to every submitted run before it is queued for writing.
+    /// `None` stores runs unchanged.
+    pub redactor: Option<Arc<dyn Redactor>>,
 }
 
 impl Default for SmithConfig {
@@ -41,6 +73,10 @@ impl Default for SmithConfig {
             flush_interval: Duration::from_secs(5),
             enabled: true,
             channel_capacity: 10_000,
+            backpressure: BackpressurePolicy::default(),
+            max_inline_bytes: None,
+            blob_store: None,
+            redactor: None,
         }
     }
 }
@@ -71,23 +107,66 @@ impl SmithConfig {
         self
     }
 
+    pub fn with_backpressure(mut self, policy: BackpressurePolicy) -> Self {
+        self.backpressure = policy;
+        self
+    }
+
+    pub fn with_max_inline_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_inline_bytes = Some(max_bytes);
+        self
+    }
+
+    pub fn with_blob_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
     pub fn disabled(mut self) -> Self {
         self.enabled = false;
         self
     }
+
+    /// The blob store used for offloading, if offloading is enabled.
+    pub fn resolved_blob_store(&self) -> Option<Arc<dyn BlobStore>> {
+        self.max_inline_bytes?;
+        let store = self.blob_store.clone().unwrap_or_else(|| {
+            Arc::new(FsBlobStore::new(self.base_dir.join("blobs"))) as Arc<dyn BlobStore>
+        });
+        Some(store)
+    }
+}
+
+/// Out-of-band requests to the background writer.
+enum WriterControl {
+    /// Drain queued runs, write them, then acknowledge.
+    Flush(oneshot::Sender<()>),
+    /// Like `Flush`, then stop the writer task.
+    Shutdown(oneshot::Sender<()>),
 }
 
 struct Inner {
     sender: flume::Sender<Run>,
+    /// Receiver handle used to evict the oldest run under `DropOldest`.
+    receiver: flume::Receiver<Run>,
+    control: mpsc::UnboundedSender<WriterControl>,
     config: SmithConfig,
     store: Arc<dyn SmithStore>,
     drop_count: AtomicU64,
+    shut_down: AtomicBool,
 }
 
 /// Client for submitting traced runs to background Parquet writer.
 ///
 /// Runs are buffered and written in batches for minimal performance impact.
-/// Uses a bounded channel for backpressure; full channel drops runs silently.
+/// Uses a bounded channel; what happens when it is full is governed by
+/// [`SmithConfig::backpressure`]. Call [`SmithClient::shutdown`] before exit
+/// so buffered runs are not lost.
 #[derive(Clone)]
 pub struct SmithClient {
     inner: Option<Arc<Inner>>,
@@ -100,8 +179,11 @@ impl SmithClient {
             return Self::noop();
         }
 
-        let store = Arc::new(DuckDbStore::new(&config.base_dir));
-        Self::with_store(config, store)
+        let mut store = DuckDbStore::new(&config.base_dir);
+        if let Some(blobs) = config.resolved_blob_store() {
+            store = store.with_blob_store(blobs);
+        }
+        Self::with_store(config, Arc::new(store))
     }
 
     /// Create a new SmithClient with a custom store implementation.
@@ -111,14 +193,18 @@ impl SmithClient {
         }
 
         let (sender, receiver) = flume::bounded(config.channel_capacity);
+        let (control, control_rx) = mpsc::unbounded_channel();
         let inner = Arc::new(Inner {
             sender,
+            receiver: receiver.clone(),
+            control,
             config: config.clone(),
             store: store.clone(),
             drop_count: AtomicU64::new(0),
+            shut_down: AtomicBool::new(false),
         });
 
-        tokio::spawn(background_writer(receiver, config, store));
+        tokio::spawn(background_writer(receiver, control_rx, config, store));
 
         Self { inner: Some(inner) }
     }
@@ -128,26 +214,113 @@ impl SmithClient {
         Self { inner: None }
     }
 
+    /// Start a run and return its [`RunGuard`].
+    ///
+    /// Starts with the same `idempotency_key` resolve to the same run, so a
+    /// retried create upserts rather than duplicating it. Without a key the
+    /// builder's run id is used as-is.
+    pub fn start_run(&self, builder: RunBuilder, idempotency_key: Option<&str>) -> RunGuard {
+        let builder = match idempotency_key {
+            Some(key) => builder.idempotency_key(key),
+            None => builder,
+        };
+        RunGuard::new(self.clone(), builder)
+    }
+
     /// Check if this client is enabled (not noop).
     pub fn is_enabled(&self) -> bool {
         self.inner.is_some()
     }
 
     /// Submit a run to the background writer.
-    /// Uses try_send for non-blocking send; drops the run if the channel is full.
-    pub fn submit_run(&self, run: Run) {
-        if let Some(inner) = &self.inner {
-            if inner.sender.try_send(run).is_err() {
-                let prev = inner.drop_count.fetch_add(1, Ordering::Relaxed);
-                if prev == 0 {
-                    eprintln!(
-                        "ayas-smith: channel full or closed, runs are being dropped"
-                    );
+    ///
+    /// When the channel is full the configured [`BackpressurePolicy`] applies.
+    /// Runs submitted after [`shutdown`](Self::shutdown) are dropped.
+    pub fn submit_run(&self, mut run: Run) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if inner.shut_down.load(Ordering::Acquire) {
+            inner.record_drop();
+            return;
+        }
+        if let Some(redactor) = &inner.config.redactor {
+            redact_run(redactor.as_ref(), &mut run);
+        }
+
+        let run = match inner.sender.try_send(run) {
+            Ok(()) => return,
+            Err(flume::TrySendError::Disconnected(_)) => {
+                inner.record_drop();
+                return;
+            }
+            Err(flume::TrySendError::Full(run)) => run,
+        };
+
+        match inner.config.backpressure {
+            BackpressurePolicy::DropNewest => inner.record_drop(),
+            BackpressurePolicy::DropOldest => {
+                let mut run = run;
+                // Another submitter may refill the slot we free; retry a few times.
+                for _ in 0..3 {
+                    if inner.receiver.try_recv().is_ok() {
+                        inner.record_drop();
+                    }
+                    match inner.sender.try_send(run) {
+                        Ok(()) => return,
+                        Err(flume::TrySendError::Full(r)) => run = r,
+                        Err(flume::TrySendError::Disconnected(_)) => break,
+                    }
+                }
+                inner.record_drop();
+            }
+            BackpressurePolicy::Block => {
+                let sent = match tokio::runtime::Handle::try_current() {
+                    Ok(handle) => match handle.runtime_flavor() {
+                        tokio::runtime::RuntimeFlavor::CurrentThread => false,
+                        _ => tokio::task::block_in_place(|| inner.sender.send(run).is_ok()),
+                    },
+                    Err(_) => inner.sender.send(run).is_ok(),
+                };
+                if !sent {
+                    inner.record_drop();
                 }
             }
         }
     }
 
+    /// Write every run submitted so far to the store.
+    ///
+    /// Resolves once the background writer has drained its queue and buffer.
+    /// A no-op for disabled or shut-down clients.
+    pub async fn flush(&self) {
+        self.send_control(WriterControl::Flush).await;
+    }
+
+    /// Flush buffered runs and stop the background writer.
+    ///
+    /// Subsequent submissions are dropped. Safe to call more than once.
+    pub async fn shutdown(&self) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        if inner.shut_down.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.send_control(WriterControl::Shutdown).await;
+    }
+
+    async fn send_control(&self, make: fn(oneshot::Sender<()>) -> WriterControl) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let (ack, done) = oneshot::channel();
+        if inner.control.send(make(ack)).is_ok() {
+            // An error means the writer already exited, so there is nothing to wait for.
+            let _ = done.await;
+        }
+    }
+
     /// Number of runs dropped because the channel was full or closed.
     pub fn drop_count(&self) -> u64 {
         self.inner
@@ -156,6 +329,13 @@ impl SmithClient {
             .unwrap_or(0)
     }
 
+    /// Whether submitted runs are scrubbed by a [`Redactor`].
+    pub fn has_redactor(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|i| i.config.redactor.is_some())
+    }
+
     /// Get the project name for this client.
     pub fn project(&self) -> &str {
         self.inner
@@ -173,14 +353,61 @@ impl SmithClient {
     pub fn store(&self) -> Option<&Arc<dyn SmithStore>> {
         self.inner.as_ref().map(|i| &i.store)
     }
+
+    /// Feedback score statistics per key (and time bucket) from the store.
+    ///
+    /// Returns an empty list for a disabled client.
+    pub async fn feedback_stats(
+        &self,
+        filter: &FeedbackStatsFilter,
+    ) -> Result<Vec<FeedbackStat>> {
+        match &self.inner {
+            Some(inner) => inner.store.feedback_stats(filter).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Fetch a run and all of its descendants in this client's project as a tree.
+    ///
+    /// The root's whole trace is loaded in one store query and nested by
+    /// `parent_run_id`; see [`RunTree::build`].
+    pub async fn get_run_tree(&self, root_run_id: Uuid) -> Result<RunTree> {
+        let store = self
+            .store()
+            .ok_or_else(|| SmithError::Query("smith client is disabled".into()))?;
+        let project = self.project();
+        let root = store
+            .get_run(root_run_id, project)
+            .await?
+            .ok_or_else(|| SmithError::Query(format!("run {root_run_id} not found")))?;
+        let runs = store.get_trace(root.trace_id, project).await?;
+        Ok(RunTree::build(root, runs))
+    }
+}
+
+impl Inner {
+    fn record_drop(&self) {
+        let prev = self.drop_count.fetch_add(1, Ordering::Relaxed);
+        if prev == 0 {
+            eprintln!("ayas-smith: channel full or closed, runs are being dropped");
+        }
+    }
 }
 
 async fn background_writer(
     receiver: flume::Receiver<Run>,
+    mut control: mpsc::UnboundedReceiver<WriterControl>,
     config: SmithConfig,
     store: Arc<dyn SmithStore>,
 ) {
     let mut buffer: Vec<Run> = Vec::new();
+    let offload = config
+        .resolved_blob_store()
+        .zip(config.max_inline_bytes)
+        .map(|(blobs, max_inline_bytes)| Offload {
+            blobs,
+            max_inline_bytes,
+        });
 
     loop {
         let flush_timeout = tokio::time::sleep(config.flush_interval);
@@ -192,31 +419,64 @@ async fn background_writer(
                     Ok(run) => {
                         buffer.push(run);
                         if buffer.len() >= config.batch_size {
-                            flush_buffer(&mut buffer, &*store).await;
+                            flush_buffer(&mut buffer, &*store, offload.as_ref()).await;
                         }
                     }
                     Err(_) => {
                         // Channel closed, flush remaining and exit
-                        flush_buffer(&mut buffer, &*store).await;
+                        flush_buffer(&mut buffer, &*store, offload.as_ref()).await;
                         return;
                     }
                 }
             }
+            ctrl = control.recv() => {
+                buffer.extend(receiver.drain());
+                flush_buffer(&mut buffer, &*store, offload.as_ref()).await;
+                match ctrl {
+                    Some(WriterControl::Flush(ack)) => {
+                        let _ = ack.send(());
+                    }
+                    Some(WriterControl::Shutdown(ack)) => {
+                        let _ = ack.send(());
+                        return;
+                    }
+                    // Client dropped: nothing can submit or flush anymore
+                    None => return,
+                }
+            }
             _ = &mut flush_timeout => {
                 if !buffer.is_empty() {
-                    flush_buffer(&mut buffer, &*store).await;
+                    flush_buffer(&mut buffer, &*store, offload.as_ref()).await;
                 }
             }
         }
     }
 }
 
-async fn flush_buffer(buffer: &mut Vec<Run>, store: &dyn SmithStore) {
+struct Offload {
+    blobs: Arc<dyn BlobStore>,
+    max_inline_bytes: usize,
+}
+
+async fn flush_buffer(
+    buffer: &mut Vec<Run>,
+    store: &dyn SmithStore,
+    offload: Option<&Offload>,
+) {
     if buffer.is_empty() {
         return;
     }
 
-    let runs = std::mem::take(buffer);
+    let mut runs = std::mem::take(buffer);
+
+    if let Some(offload) = offload {
+        for run in &mut runs {
+            // Fail-safe: payloads that can't be offloaded stay inline
+            if let Err(e) = blob::offload_run(run, offload.max_inline_bytes, &*offload.blobs) {
+                eprintln!("ayas-smith: failed to offload run payload: {e}");
+            }
+        }
+    }
 
     // Fail-safe: errors in writing don't propagate
     if let Err(e) = store.put_runs(&runs).await {
@@ -375,6 +635,122 @@ mod tests {
         assert_eq!(config.channel_capacity, 5_000);
     }
 
+    #[test]
+    fn smith_config_blob_offload() {
+        let config = SmithConfig::default().with_base_dir("/tmp/test");
+        assert!(config.max_inline_bytes.is_none());
+        assert!(config.resolved_blob_store().is_none());
+
+        let config = config.with_max_inline_bytes(1024);
+        assert_eq!(config.max_inline_bytes, Some(1024));
+        assert!(config.resolved_blob_store().is_some());
+    }
+
+    #[tokio::test]
+    async fn submit_offloads_large_payloads() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = Arc::new(crate::blob::InMemoryBlobStore::new());
+        let config = SmithConfig::default()
+            .with_base_dir(dir.path())
+            .with_project("blob-proj")
+            .with_batch_size(1)
+            .with_flush_interval(Duration::from_millis(50))
+            .with_max_inline_bytes(64)
+            .with_blob_store(blobs.clone());
+
+        let client = SmithClient::new(config);
+        let big_input = "x".repeat(1000);
+        let run = Run::builder("big", RunType::Chain)
+            .project("blob-proj")
+            .input(big_input.clone())
+            .finish_ok("small");
+        let run_id = run.run_id;
+        client.submit_run(run);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(blobs.len(), 1);
+
+        // Raw rows keep only the reference
+        let raw = crate::query::SmithQuery::new(dir.path())
+            .unwrap()
+            .get_run(run_id, "blob-proj")
+            .unwrap()
+            .unwrap();
+        assert!(crate::blob::blob_ref(&raw.input).is_some());
+        assert_eq!(raw.output.as_deref(), Some("small"));
+
+        // The client's store resolves it transparently
+        let resolved = client
+            .store()
+            .unwrap()
+            .get_run(run_id, "blob-proj")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.input, big_input);
+    }
+
+    #[tokio::test]
+    async fn redactor_scrubs_runs_before_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = unflushed_config(dir.path(), "pii-proj").with_redactor(Arc::new(
+            crate::redact::PatternRedactor::new(),
+        ));
+        let client = SmithClient::new(config);
+
+        let run = Run::builder("pii", RunType::Chain)
+            .project("pii-proj")
+            .input(r#"{"question":"reach me at jane@example.com"}"#)
+            .metadata(r#"{"configurable":{"api_key":"sk-secret","model":"m"}}"#)
+            .finish_ok(r#""card 4111-1111-1111-1111""#);
+        let run_id = run.run_id;
+        client.submit_run(run);
+        client.flush().await;
+
+        let stored = client
+            .store()
+            .unwrap()
+            .get_run(run_id, "pii-proj")
+            .await
+            .unwrap()
+            .unwrap();
+        for field in [&stored.input, &stored.metadata, stored.output.as_ref().unwrap()] {
+            assert!(!field.contains("jane@example.com"), "{field}");
+            assert!(!field.contains("sk-secret"), "{field}");
+            assert!(!field.contains("4111"), "{field}");
+        }
+        assert!(stored.input.contains("[EMAIL]"));
+        assert!(stored.metadata.contains(r#""model":"m""#));
+    }
+
+    #[tokio::test]
+    async fn start_run_with_same_key_upserts_one_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = SmithClient::new(unflushed_config(dir.path(), "idem-proj"));
+        let builder = || Run::builder("create", RunType::Chain).project("idem-proj");
+
+        let first = client.start_run(builder(), Some("order-42"));
+        let retry = client.start_run(builder(), Some("order-42"));
+        assert_eq!(first.run_id(), retry.run_id());
+        assert_eq!(retry.trace_id(), retry.run_id());
+        retry.finish_ok(r#""created""#);
+        client.flush().await;
+
+        let filter = crate::types::RunFilter {
+            project: Some("idem-proj".into()),
+            ..Default::default()
+        };
+        let runs = client.store().unwrap().list_runs(&filter).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, first.run_id());
+        assert_eq!(runs[0].status, RunStatus::Success);
+
+        let other = client.start_run(builder(), None);
+        assert_ne!(other.run_id(), first.run_id());
+        other.finish_ok("{}");
+        first.finish_ok(r#""created""#);
+    }
+
     #[test]
     fn smith_config_disabled() {
         let config = SmithConfig::default().disabled();
@@ -441,6 +817,122 @@ mod tests {
         assert!(project_dir.exists());
     }
 
+    async fn stored_run_names(client: &SmithClient, project: &str) -> Vec<String> {
+        let filter = crate::types::RunFilter {
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let mut names: Vec<String> = client
+            .store()
+            .unwrap()
+            .list_runs(&filter)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn unflushed_config(dir: &std::path::Path, project: &str) -> SmithConfig {
+        SmithConfig::default()
+            .with_base_dir(dir)
+            .with_project(project)
+            .with_batch_size(1000)
+            .with_flush_interval(Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn flush_writes_buffered_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = SmithClient::new(unflushed_config(dir.path(), "flush-proj"));
+
+        for i in 0..3 {
+            let run = Run::builder(format!("run-{i}"), RunType::Chain)
+                .project("flush-proj")
+                .finish_ok("ok");
+            client.submit_run(run);
+        }
+        client.flush().await;
+
+        assert_eq!(stored_run_names(&client, "flush-proj").await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn shutdown_flushes_and_rejects_new_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = SmithClient::new(unflushed_config(dir.path(), "shutdown-proj"));
+
+        let run = Run::builder("before", RunType::Chain)
+            .project("shutdown-proj")
+            .finish_ok("ok");
+        client.submit_run(run);
+        client.shutdown().await;
+        assert_eq!(stored_run_names(&client, "shutdown-proj").await, vec!["before"]);
+
+        let run = Run::builder("after", RunType::Chain)
+            .project("shutdown-proj")
+            .finish_ok("ok");
+        client.submit_run(run);
+        assert_eq!(client.drop_count(), 1);
+
+        // Idempotent, and flush after shutdown returns immediately
+        client.shutdown().await;
+        client.flush().await;
+    }
+
+    #[tokio::test]
+    async fn noop_client_flush_and_shutdown() {
+        let client = SmithClient::noop();
+        client.flush().await;
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_newest_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = unflushed_config(dir.path(), "oldest-proj")
+            .with_channel_capacity(2)
+            .with_backpressure(BackpressurePolicy::DropOldest);
+        let client = SmithClient::new(config);
+
+        // Current-thread runtime: the writer can't drain until we yield
+        for i in 0..5 {
+            let run = Run::builder(format!("run-{i}"), RunType::Chain)
+                .project("oldest-proj")
+                .finish_ok("ok");
+            client.submit_run(run);
+        }
+        assert_eq!(client.drop_count(), 3);
+
+        client.flush().await;
+        assert_eq!(
+            stored_run_names(&client, "oldest-proj").await,
+            vec!["run-3", "run-4"]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn block_policy_loses_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = unflushed_config(dir.path(), "block-proj")
+            .with_channel_capacity(1)
+            .with_backpressure(BackpressurePolicy::Block);
+        let client = SmithClient::new(config);
+
+        for i in 0..20 {
+            let run = Run::builder(format!("run-{i:02}"), RunType::Chain)
+                .project("block-proj")
+                .finish_ok("ok");
+            client.submit_run(run);
+        }
+        client.flush().await;
+
+        assert_eq!(client.drop_count(), 0);
+        assert_eq!(stored_run_names(&client, "block-proj").await.len(), 20);
+    }
+
     #[tokio::test]
     async fn bounded_channel_drops_when_full() {
         let dir = tempfile::tempdir().unwrap();
@@ -483,6 +975,39 @@ mod tests {
         assert_eq!(client.project(), "default");
     }
 
+    #[tokio::test]
+    async fn get_run_tree_from_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SmithConfig::default()
+            .with_base_dir(dir.path())
+            .with_project("tree-test");
+        let client = SmithClient::new(config);
+
+        let root = Run::builder("root", RunType::Chain)
+            .project("tree-test")
+            .finish_ok("done");
+        let child = Run::builder("llm", RunType::Llm)
+            .parent_run_id(root.run_id)
+            .trace_id(root.trace_id)
+            .project("tree-test")
+            .finish_llm("hi", 1, 1, 2);
+        let grandchild = Run::builder("tool", RunType::Tool)
+            .parent_run_id(child.run_id)
+            .trace_id(root.trace_id)
+            .project("tree-test")
+            .finish_ok("5");
+        flush_runs(&[root.clone(), child, grandchild], dir.path(), "tree-test").unwrap();
+
+        let tree = client.get_run_tree(root.run_id).await.unwrap();
+        assert_eq!(tree.run.run_id, root.run_id);
+        assert_eq!(tree.descendant_count, 2);
+        assert_eq!(tree.children[0].run.name, "llm");
+        assert_eq!(tree.children[0].children[0].run.name, "tool");
+
+        assert!(client.get_run_tree(Uuid::new_v4()).await.is_err());
+        assert!(SmithClient::noop().get_run_tree(root.run_id).await.is_err());
+    }
+
     // --- RunGuard tests ---
 
     #[tokio::test]
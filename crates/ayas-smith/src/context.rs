@@ -1,11 +1,24 @@
 use chrono::{DateTime, Utc};
 
 use ayas_core::config::RunnableConfig;
+use ayas_core::hash::Fnv1a;
 use uuid::Uuid;
 
 const TRACE_ID_KEY: &str = "__smith_trace_id";
 const PARENT_RUN_ID_KEY: &str = "__smith_parent_run_id";
 const DOTTED_ORDER_KEY: &str = "__smith_dotted_order";
+const SAMPLED_KEY: &str = "__smith_sampled";
+
+/// Request header that overrides the sampling decision for a trace.
+///
+/// `always` forces the trace to be recorded, `never` drops it.
+pub const TRACE_SAMPLE_HEADER: &str = "x-trace-sample";
+
+/// Request header that joins an existing trace.
+///
+/// The value is `<trace_id>-<parent_run_id>`, both UUIDs in their 32-digit
+/// hex form (see [`format_trace_parent`]).
+pub const TRACE_PARENT_HEADER: &str = "x-ayas-trace-parent";
 
 /// Task-local trace context for propagating trace hierarchy to
 /// `TracedChatModel` and `TracedTool` which don't receive `RunnableConfig`.
@@ -14,6 +27,13 @@ pub struct SmithTraceCtx {
     pub trace_id: Uuid,
     pub parent_run_id: Option<Uuid>,
     pub dotted_order: String,
+    /// Sampling decision made at the root of the trace, if any.
+    pub sampled: Option<bool>,
+    /// Tags of the enclosing run, recorded on nested model and tool runs.
+    pub tags: Vec<String>,
+    /// Metadata JSON of the enclosing run, recorded on nested model and
+    /// tool runs.
+    pub metadata: Option<String>,
 }
 
 tokio::task_local! {
@@ -66,9 +86,92 @@ pub fn trace_context(config: &RunnableConfig) -> (Uuid, Option<Uuid>, Option<Str
     (trace_id, parent_run_id, dotted_order)
 }
 
+/// Read the trace's sampling decision from a RunnableConfig.
+///
+/// Returns `None` when no decision has been made yet (i.e. at the root).
+pub fn trace_sampled(config: &RunnableConfig) -> Option<bool> {
+    config
+        .configurable
+        .get(SAMPLED_KEY)
+        .and_then(|v| v.as_bool())
+}
+
+/// Record the trace's sampling decision so that every child config inherits it.
+pub fn set_trace_sampled(config: &mut RunnableConfig, sampled: bool) {
+    config
+        .configurable
+        .insert(SAMPLED_KEY.into(), serde_json::Value::Bool(sampled));
+}
+
+/// Seed a root config with a trace and parent run from another process, so
+/// its runs join that trace under `parent_run_id`.
+pub fn set_trace_parent(config: &mut RunnableConfig, trace_id: Uuid, parent_run_id: Uuid) {
+    config.configurable.insert(
+        TRACE_ID_KEY.into(),
+        serde_json::Value::String(trace_id.to_string()),
+    );
+    config.configurable.insert(
+        PARENT_RUN_ID_KEY.into(),
+        serde_json::Value::String(parent_run_id.to_string()),
+    );
+}
+
+/// Format a [`TRACE_PARENT_HEADER`] value.
+pub fn format_trace_parent(trace_id: Uuid, parent_run_id: Uuid) -> String {
+    format!("{}-{}", trace_id.simple(), parent_run_id.simple())
+}
+
+/// Parse a [`TRACE_PARENT_HEADER`] value into `(trace_id, parent_run_id)`.
+pub fn parse_trace_parent(value: &str) -> Option<(Uuid, Uuid)> {
+    let (trace_id, parent_run_id) = value.trim().split_once('-')?;
+    Some((
+        Uuid::parse_str(trace_id).ok()?,
+        Uuid::parse_str(parent_run_id).ok()?,
+    ))
+}
+
+/// Parse a [`TRACE_SAMPLE_HEADER`] value into a forced sampling decision.
+pub fn parse_sample_override(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "always" => Some(true),
+        "never" => Some(false),
+        _ => None,
+    }
+}
+
+/// Deterministic head-sampling decision for a trace.
+///
+/// Hashes the trace id (FNV-1a) so every process sampling the same trace
+/// with the same rate reaches the same decision.
+pub fn should_sample_trace(trace_id: Uuid, sample_rate: f64) -> bool {
+    if sample_rate >= 1.0 {
+        return true;
+    }
+    if sample_rate <= 0.0 {
+        return false;
+    }
+    let mut hasher = Fnv1a::new();
+    hasher.write(trace_id.as_bytes());
+    hasher.finish_unit() < sample_rate
+}
+
+/// Union of `parent` and `child` tags: parent tags first, then the child's
+/// new ones, without duplicates.
+pub fn merge_tags(parent: &[String], child: &[String]) -> Vec<String> {
+    let mut tags = parent.to_vec();
+    for tag in child {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    tags
+}
+
 /// Create a child RunnableConfig that propagates the trace context.
 ///
 /// The child gets a new `run_id` and the parent context is set to `current_run_id`.
+/// Tags and metadata are inherited; a child adding its own should merge them
+/// in with [`merge_tags`] so its tags are the union with its parent's.
 pub fn child_config(
     config: &RunnableConfig,
     current_run_id: Uuid,
@@ -124,6 +227,23 @@ mod tests {
         assert_eq!(parent, Some(pid));
     }
 
+    #[test]
+    fn trace_parent_roundtrip_seeds_config() {
+        let trace_id = Uuid::new_v4();
+        let parent = Uuid::new_v4();
+        let header = format_trace_parent(trace_id, parent);
+        assert_eq!(parse_trace_parent(&header), Some((trace_id, parent)));
+        assert_eq!(parse_trace_parent("not-a-trace"), None);
+        assert_eq!(parse_trace_parent(&trace_id.to_string()), None);
+
+        let mut config = RunnableConfig::default();
+        set_trace_parent(&mut config, trace_id, parent);
+        let (got_trace, got_parent, dotted_order) = trace_context(&config);
+        assert_eq!(got_trace, trace_id);
+        assert_eq!(got_parent, Some(parent));
+        assert_eq!(dotted_order, None);
+    }
+
     #[test]
     fn trace_context_with_dotted_order() {
         let mut config = RunnableConfig::default();
@@ -168,6 +288,14 @@ mod tests {
         assert_eq!(child.metadata["key"], serde_json::json!("value"));
     }
 
+    #[test]
+    fn merge_tags_is_ordered_union() {
+        let parent = vec!["prod".to_string(), "v2".to_string()];
+        let child = vec!["v2".to_string(), "retriever".to_string()];
+        assert_eq!(merge_tags(&parent, &child), vec!["prod", "v2", "retriever"]);
+        assert_eq!(merge_tags(&[], &child), child);
+    }
+
     #[test]
     fn child_config_preserves_recursion_limit() {
         let config = RunnableConfig::new().with_recursion_limit(50);
@@ -192,6 +320,39 @@ mod tests {
         assert_eq!(c2_dotted.as_deref(), Some("root.child1"));
     }
 
+    #[test]
+    fn sampled_flag_propagates_to_children() {
+        let mut config = RunnableConfig::default();
+        assert_eq!(trace_sampled(&config), None);
+
+        set_trace_sampled(&mut config, false);
+        let child = child_config(&config, Uuid::new_v4(), Uuid::new_v4(), "root");
+        let grandchild = child_config(&child, Uuid::new_v4(), Uuid::new_v4(), "root.child");
+        assert_eq!(trace_sampled(&child), Some(false));
+        assert_eq!(trace_sampled(&grandchild), Some(false));
+    }
+
+    #[test]
+    fn parse_sample_override_values() {
+        assert_eq!(parse_sample_override("always"), Some(true));
+        assert_eq!(parse_sample_override(" Always "), Some(true));
+        assert_eq!(parse_sample_override("never"), Some(false));
+        assert_eq!(parse_sample_override("sometimes"), None);
+    }
+
+    #[test]
+    fn should_sample_trace_bounds_and_determinism() {
+        let id = Uuid::new_v4();
+        assert!(should_sample_trace(id, 1.0));
+        assert!(!should_sample_trace(id, 0.0));
+        assert_eq!(should_sample_trace(id, 0.5), should_sample_trace(id, 0.5));
+
+        let sampled = (0..2000)
+            .filter(|_| should_sample_trace(Uuid::new_v4(), 0.25))
+            .count();
+        assert!((350..650).contains(&sampled), "sampled {sampled} of 2000");
+    }
+
     #[test]
     fn build_dotted_order_root() {
         let time = Utc::now();
@@ -241,6 +402,9 @@ mod tests {
             trace_id,
             parent_run_id: Some(parent_id),
             dotted_order: "20250210T120000000000Z.abc12345".into(),
+            sampled: None,
+            tags: Vec::new(),
+            metadata: None,
         };
 
         let result = SMITH_TRACE_CTX
@@ -1,21 +1,24 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use uuid::Uuid;
 
+use crate::blob::BlobStore;
 use crate::error::SmithError;
 use crate::query::SmithQuery;
 use crate::store::SmithStore;
 use crate::types::{
-    Dataset, Example, Feedback, FeedbackFilter, LatencyStats, Project, Run, RunFilter, RunPatch,
-    TokenUsageSummary,
+    Dataset, Example, Feedback, FeedbackFilter, FeedbackStat, FeedbackStatsFilter, LatencyStats,
+    Project, Run, RunAggregate, RunFilter, RunPatch, TokenUsageSummary,
 };
 use crate::writer;
 
 /// DuckDB + Parquet backed implementation of [`SmithStore`].
 pub struct DuckDbStore {
     base_dir: PathBuf,
+    blob_store: Option<Arc<dyn BlobStore>>,
 }
 
 impl DuckDbStore {
@@ -23,9 +26,28 @@ impl DuckDbStore {
     pub fn new(base_dir: impl Into<PathBuf>) -> Self {
         Self {
             base_dir: base_dir.into(),
+            blob_store: None,
         }
     }
 
+    /// Resolve offloaded payloads through `store` when reading runs.
+    pub fn with_blob_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    /// Open a query client that resolves blob references, if configured.
+    fn run_query(
+        base_dir: PathBuf,
+        blob_store: Option<Arc<dyn BlobStore>>,
+    ) -> Result<SmithQuery, SmithError> {
+        let query = SmithQuery::new(base_dir)?;
+        Ok(match blob_store {
+            Some(store) => query.with_blob_store(store),
+            None => query,
+        })
+    }
+
     /// Get the base directory.
     pub fn base_dir(&self) -> &Path {
         &self.base_dir
@@ -36,7 +58,7 @@ fn feedback_file(base_dir: &Path) -> PathBuf {
     base_dir.join("_feedback").join("feedback.json")
 }
 
-fn load_feedback_sync(base_dir: &Path) -> Result<Vec<Feedback>, SmithError> {
+pub(crate) fn load_feedback_sync(base_dir: &Path) -> Result<Vec<Feedback>, SmithError> {
     let path = feedback_file(base_dir);
     if !path.exists() {
         return Ok(Vec::new());
@@ -214,8 +236,9 @@ impl SmithStore for DuckDbStore {
     async fn list_runs(&self, filter: &RunFilter) -> Result<Vec<Run>, SmithError> {
         let base_dir = self.base_dir.clone();
         let filter = filter.clone();
+        let blob_store = self.blob_store.clone();
         tokio::task::spawn_blocking(move || {
-            let query = SmithQuery::new(base_dir)?;
+            let query = Self::run_query(base_dir, blob_store)?;
             query.list_runs(&filter)
         })
         .await
@@ -225,8 +248,9 @@ impl SmithStore for DuckDbStore {
     async fn get_run(&self, run_id: Uuid, project: &str) -> Result<Option<Run>, SmithError> {
         let base_dir = self.base_dir.clone();
         let project = project.to_string();
+        let blob_store = self.blob_store.clone();
         tokio::task::spawn_blocking(move || {
-            let query = SmithQuery::new(base_dir)?;
+            let query = Self::run_query(base_dir, blob_store)?;
             query.get_run(run_id, &project)
         })
         .await
@@ -236,8 +260,9 @@ impl SmithStore for DuckDbStore {
     async fn get_trace(&self, trace_id: Uuid, project: &str) -> Result<Vec<Run>, SmithError> {
         let base_dir = self.base_dir.clone();
         let project = project.to_string();
+        let blob_store = self.blob_store.clone();
         tokio::task::spawn_blocking(move || {
-            let query = SmithQuery::new(base_dir)?;
+            let query = Self::run_query(base_dir, blob_store)?;
             query.get_trace(trace_id, &project)
         })
         .await
@@ -251,8 +276,9 @@ impl SmithStore for DuckDbStore {
     ) -> Result<Vec<Run>, SmithError> {
         let base_dir = self.base_dir.clone();
         let project = project.to_string();
+        let blob_store = self.blob_store.clone();
         tokio::task::spawn_blocking(move || {
-            let query = SmithQuery::new(base_dir)?;
+            let query = Self::run_query(base_dir, blob_store)?;
             query.get_children(parent_run_id, &project)
         })
         .await
@@ -284,6 +310,35 @@ impl SmithStore for DuckDbStore {
         .map_err(|e| SmithError::Query(format!("spawn_blocking failed: {e}")))?
     }
 
+    async fn aggregate(&self, filter: &RunFilter) -> Result<RunAggregate, SmithError> {
+        let base_dir = self.base_dir.clone();
+        let filter = filter.clone();
+        tokio::task::spawn_blocking(move || {
+            let query = SmithQuery::new(base_dir)?;
+            query.aggregate(&filter)
+        })
+        .await
+        .map_err(|e| SmithError::Query(format!("spawn_blocking failed: {e}")))?
+    }
+
+    async fn search_text(
+        &self,
+        query: &str,
+        filter: &RunFilter,
+        limit: usize,
+    ) -> Result<Vec<Run>, SmithError> {
+        let base_dir = self.base_dir.clone();
+        let query_text = query.to_string();
+        let filter = filter.clone();
+        let blob_store = self.blob_store.clone();
+        tokio::task::spawn_blocking(move || {
+            let query = Self::run_query(base_dir, blob_store)?;
+            query.search_text(&query_text, &filter, limit)
+        })
+        .await
+        .map_err(|e| SmithError::Query(format!("spawn_blocking failed: {e}")))?
+    }
+
     async fn put_feedback(&self, feedback: &Feedback) -> Result<(), SmithError> {
         let base_dir = self.base_dir.clone();
         let feedback = feedback.clone();
@@ -323,6 +378,20 @@ impl SmithStore for DuckDbStore {
         .map_err(|e| SmithError::Query(format!("spawn_blocking failed: {e}")))?
     }
 
+    async fn feedback_stats(
+        &self,
+        filter: &FeedbackStatsFilter,
+    ) -> Result<Vec<FeedbackStat>, SmithError> {
+        let base_dir = self.base_dir.clone();
+        let filter = filter.clone();
+        tokio::task::spawn_blocking(move || {
+            let query = SmithQuery::new(base_dir)?;
+            query.feedback_stats(&filter)
+        })
+        .await
+        .map_err(|e| SmithError::Query(format!("spawn_blocking failed: {e}")))?
+    }
+
     // --- Project management ---
 
     async fn create_project(&self, project: &Project) -> Result<(), SmithError> {
@@ -552,6 +621,66 @@ mod tests {
         assert!(stats.p50 >= 0.0);
     }
 
+    #[tokio::test]
+    async fn aggregate_via_store() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let store = DuckDbStore::new(dir.path());
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        let agg = store.aggregate(&filter).await.unwrap();
+        // The fixture holds a chain run and its LLM child; only the child
+        // reports tokens.
+        assert_eq!(agg.run_count, 2);
+        assert_eq!(agg.tokens.total_tokens, 60);
+        assert_eq!(agg.tokens.run_count, 1);
+        assert!(agg.latency.p95 >= agg.latency.p50);
+
+        let llm_only = RunFilter {
+            project: Some("test-proj".into()),
+            run_type: Some(RunType::Llm),
+            ..Default::default()
+        };
+        let agg = store.aggregate(&llm_only).await.unwrap();
+        assert_eq!(agg.run_count, 1);
+        assert_eq!(agg.tokens.total_input_tokens, 50);
+    }
+
+    #[tokio::test]
+    async fn search_text_via_store() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let store = DuckDbStore::new(dir.path());
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        let found = store.search_text("hello", &filter, 10).await.unwrap();
+
+        // Same result as the naive scan used by stores without SQL
+        let mut expected: Vec<Run> = store
+            .list_runs(&filter)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.contains_text("hello"))
+            .collect();
+        expected.sort_by_key(|r| std::cmp::Reverse(r.start_time));
+        let ids = |runs: &[Run]| runs.iter().map(|r| r.run_id).collect::<Vec<_>>();
+        assert_eq!(ids(&found), ids(&expected));
+        assert!(!found.is_empty());
+
+        let missing = RunFilter {
+            project: Some("nope".into()),
+            ..Default::default()
+        };
+        assert!(store.search_text("hello", &missing, 10).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn feedback_roundtrip() {
         let dir = tempfile::tempdir().unwrap();
@@ -605,6 +734,91 @@ mod tests {
         assert_eq!(result.len(), 2);
     }
 
+    #[tokio::test]
+    async fn feedback_stats_sql_matches_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DuckDbStore::new(dir.path());
+
+        let run = Run::builder("chain", RunType::Chain)
+            .project("stats-proj")
+            .finish_ok("ok");
+        flush_runs(std::slice::from_ref(&run), dir.path(), "stats-proj").unwrap();
+
+        let day = |d: u32, h: u32| {
+            chrono::NaiveDate::from_ymd_opt(2024, 3, d)
+                .unwrap()
+                .and_hms_opt(h, 30, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let entries = [
+            ("correctness", 1.0, day(1, 9), run.run_id),
+            ("correctness", 0.0, day(1, 10), run.run_id),
+            ("correctness", 0.5, day(2, 9), run.run_id),
+            ("helpfulness", 0.8, day(1, 9), run.run_id),
+            ("correctness", 1.0, day(1, 9), Uuid::new_v4()),
+        ];
+        let mut all = Vec::new();
+        for (key, score, created_at, run_id) in entries {
+            let fb = Feedback {
+                id: Uuid::new_v4(),
+                run_id,
+                key: key.into(),
+                score,
+                comment: None,
+                created_at,
+            };
+            store.put_feedback(&fb).await.unwrap();
+            all.push(fb);
+        }
+
+        // Whole store, per key
+        let stats = store.feedback_stats(&FeedbackStatsFilter::default()).await.unwrap();
+        let expected = FeedbackStat::from_feedback(&all, None);
+        assert_eq!(stats.len(), expected.len());
+        for (got, want) in stats.iter().zip(&expected) {
+            assert_eq!(got.key, want.key);
+            assert_eq!(got.count, want.count);
+            assert!((got.mean - want.mean).abs() < 1e-9);
+            assert!((got.stddev - want.stddev).abs() < 1e-9);
+        }
+
+        // Project-scoped, daily buckets
+        let filter = FeedbackStatsFilter {
+            project: Some("stats-proj".into()),
+            key: Some("correctness".into()),
+            bucket: Some(crate::types::TimeBucket::Daily),
+            ..Default::default()
+        };
+        let stats = store.feedback_stats(&filter).await.unwrap();
+        assert_eq!(stats.len(), 2);
+        let midnight = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(stats[0].bucket, Some(midnight));
+        assert_eq!(stats[0].count, 2);
+        assert_eq!(stats[1].count, 1);
+
+        // Time range
+        let filter = FeedbackStatsFilter {
+            created_after: Some(day(1, 12)),
+            ..Default::default()
+        };
+        let stats = store.feedback_stats(&filter).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        assert!((stats[0].mean - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn feedback_stats_empty_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DuckDbStore::new(dir.path());
+        let stats = store.feedback_stats(&FeedbackStatsFilter::default()).await.unwrap();
+        assert!(stats.is_empty());
+    }
+
     #[tokio::test]
     async fn list_feedback_empty() {
         let dir = tempfile::tempdir().unwrap();
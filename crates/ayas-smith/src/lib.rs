@@ -1,3 +1,4 @@
+pub mod blob;
 pub mod client;
 #[cfg(feature = "clickhouse")]
 pub mod clickhouse_store;
@@ -6,7 +7,9 @@ pub mod duckdb_store;
 pub mod error;
 #[cfg(feature = "postgres")]
 pub mod postgres_store;
+pub mod pricing;
 pub mod query;
+pub mod redact;
 pub mod retry;
 pub mod store;
 pub mod traced;
@@ -16,15 +19,18 @@ pub mod writer;
 
 /// Prelude for convenient imports.
 pub mod prelude {
+    pub use crate::blob::{BlobStore, FsBlobStore, InMemoryBlobStore};
     #[cfg(feature = "clickhouse")]
     pub use crate::clickhouse_store::ClickHouseStore;
-    pub use crate::client::{RunGuard, SmithClient, SmithConfig};
+    pub use crate::client::{BackpressurePolicy, RunGuard, SmithClient, SmithConfig};
     pub use crate::context::{child_config, trace_context};
     pub use crate::duckdb_store::DuckDbStore;
     pub use crate::error::SmithError;
     #[cfg(feature = "postgres")]
     pub use crate::postgres_store::PostgresSmithStore;
+    pub use crate::pricing::{ModelPrice, PricingTable};
     pub use crate::query::SmithQuery;
+    pub use crate::redact::{PatternRedactor, Redactor};
     pub use crate::retry::with_retry;
     pub use crate::store::SmithStore;
     pub use crate::traced::{
@@ -32,7 +38,8 @@ pub mod prelude {
     };
     pub use crate::tracing_layer::SmithLayer;
     pub use crate::types::{
-        Dataset, Example, Feedback, FeedbackFilter, LatencyStats, Project, Run, RunFilter,
-        RunPatch, RunStatus, RunType, TokenUsageSummary,
+        Dataset, Example, Feedback, FeedbackFilter, FeedbackStat, FeedbackStatsFilter,
+        LatencyStats, ModelTokenUsage, Project, Run, RunAggregate, RunFilter, RunPatch, RunStatus,
+        RunTree, RunType, TimeBucket, TokenUsageSummary,
     };
 }
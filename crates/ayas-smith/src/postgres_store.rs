@@ -112,7 +112,8 @@ impl SmithStore for PostgresSmithStore {
                         input_tokens = EXCLUDED.input_tokens,
                         output_tokens = EXCLUDED.output_tokens,
                         total_tokens = EXCLUDED.total_tokens,
-                        latency_ms = EXCLUDED.latency_ms",
+                        latency_ms = EXCLUDED.latency_ms
+                     WHERE runs.status = 'running' OR EXCLUDED.status <> 'running'",
                     &[
                         &run.run_id,
                         &run.parent_run_id,
@@ -243,6 +244,10 @@ impl SmithStore for PostgresSmithStore {
             params.push(Box::new(trace_id));
             idx += 1;
         }
+        if !filter.tags.is_empty() {
+            conditions.push(format!("tags @> ${idx}"));
+            params.push(Box::new(filter.tags.clone()));
+        }
 
         let where_clause = if conditions.is_empty() {
             String::new()
@@ -336,6 +341,7 @@ impl SmithStore for PostgresSmithStore {
             total_output_tokens: row.get::<_, i64>("total_output"),
             total_tokens: row.get::<_, i64>("total"),
             run_count: row.get::<_, i64>("run_count"),
+            by_model: Default::default(),
         })
     }
 
@@ -0,0 +1,187 @@
+//! Per-model token pricing for turning token counts into dollar cost.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// USD price per 1,000 tokens for one model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_1k: f64,
+    pub output_per_1k: f64,
+}
+
+impl ModelPrice {
+    pub fn new(input_per_1k: f64, output_per_1k: f64) -> Self {
+        Self {
+            input_per_1k,
+            output_per_1k,
+        }
+    }
+
+    /// Cost in USD of the given token counts.
+    pub fn cost(&self, input_tokens: i64, output_tokens: i64) -> f64 {
+        (input_tokens as f64 / 1000.0) * self.input_per_1k
+            + (output_tokens as f64 / 1000.0) * self.output_per_1k
+    }
+}
+
+/// Built-in list prices (USD per 1K input/output tokens).
+///
+/// Keys are matched as prefixes, so `claude-sonnet-4-5` also prices the
+/// dated `claude-sonnet-4-5-20250929`. Override with
+/// [`PricingTable::with_price`] when these drift.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    // Gemini
+    ("gemini-3-pro-preview", 0.002, 0.012),
+    ("gemini-3-flash-preview", 0.0005, 0.003),
+    ("gemini-2.5-pro", 0.00125, 0.01),
+    ("gemini-2.5-flash", 0.0003, 0.0025),
+    ("gemini-2.5-flash-lite", 0.0001, 0.0004),
+    ("gemini-2.0-flash", 0.0001, 0.0004),
+    ("gemini-1.5-pro", 0.00125, 0.005),
+    ("gemini-1.5-flash", 0.000075, 0.0003),
+    // Claude
+    ("claude-opus-4", 0.015, 0.075),
+    ("claude-opus-4-5", 0.005, 0.025),
+    ("claude-opus-4-6", 0.005, 0.025),
+    ("claude-sonnet-4", 0.003, 0.015),
+    ("claude-haiku-4-5", 0.001, 0.005),
+    ("claude-3-7-sonnet", 0.003, 0.015),
+    ("claude-3-5-sonnet", 0.003, 0.015),
+    ("claude-3-5-haiku", 0.0008, 0.004),
+    // OpenAI
+    ("gpt-5.2", 0.00175, 0.014),
+    ("gpt-5.2-pro", 0.021, 0.168),
+    ("gpt-5", 0.00125, 0.01),
+    ("gpt-5-mini", 0.00025, 0.002),
+    ("gpt-4.1", 0.002, 0.008),
+    ("gpt-4.1-mini", 0.0004, 0.0016),
+    ("gpt-4.1-nano", 0.0001, 0.0004),
+    ("gpt-4o", 0.0025, 0.01),
+    ("gpt-4o-mini", 0.00015, 0.0006),
+    ("o3", 0.002, 0.008),
+    ("o3-mini", 0.0011, 0.0044),
+    ("o4-mini", 0.0011, 0.0044),
+];
+
+/// Model name → price lookup.
+///
+/// `PricingTable::default()` carries the built-in prices for common Gemini,
+/// Claude and OpenAI models; `PricingTable::new()` starts empty. Lookups try
+/// an exact match first, then the longest key that prefixes the model name.
+/// Unknown models cost zero and log a warning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        let prices = DEFAULT_PRICES
+            .iter()
+            .map(|(model, input, output)| (model.to_string(), ModelPrice::new(*input, *output)))
+            .collect();
+        Self { prices }
+    }
+}
+
+impl PricingTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Add or override the price of a model.
+    pub fn with_price(
+        mut self,
+        model: impl Into<String>,
+        input_per_1k: f64,
+        output_per_1k: f64,
+    ) -> Self {
+        self.set_price(model, ModelPrice::new(input_per_1k, output_per_1k));
+        self
+    }
+
+    /// Add or override the price of a model in place.
+    pub fn set_price(&mut self, model: impl Into<String>, price: ModelPrice) {
+        self.prices.insert(model.into(), price);
+    }
+
+    /// Price for a model, by exact name or longest matching prefix.
+    pub fn get(&self, model: &str) -> Option<ModelPrice> {
+        if let Some(price) = self.prices.get(model) {
+            return Some(*price);
+        }
+        self.prices
+            .iter()
+            .filter(|(key, _)| model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, price)| *price)
+    }
+
+    /// Cost in USD for a model's token counts; zero (with a warning) if the
+    /// model has no price.
+    pub fn cost(&self, model: &str, input_tokens: i64, output_tokens: i64) -> f64 {
+        match self.get(model) {
+            Some(price) => price.cost(input_tokens, output_tokens),
+            None => {
+                if input_tokens > 0 || output_tokens > 0 {
+                    tracing::warn!(model, "no price for model; counting its tokens as zero cost");
+                }
+                0.0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_table_prices_common_models() {
+        let table = PricingTable::default();
+        assert!(table.get("gemini-2.5-flash").is_some());
+        assert!(table.get("gpt-4o-mini").is_some());
+        assert!(table.get("claude-sonnet-4-5-20250929").is_some());
+        assert!(PricingTable::new().get("gpt-4o").is_none());
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let table = PricingTable::default();
+        assert_eq!(table.get("gpt-4o-mini-2024-07-18"), table.get("gpt-4o-mini"));
+        assert_ne!(table.get("gpt-4o-mini"), table.get("gpt-4o"));
+        assert_eq!(table.get("claude-opus-4-5-20251101"), table.get("claude-opus-4-5"));
+    }
+
+    #[test]
+    fn cost_per_1k() {
+        let table = PricingTable::new().with_price("m", 1.0, 2.0);
+        let cost = table.cost("m", 1500, 500);
+        assert!((cost - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn override_replaces_default() {
+        let table = PricingTable::default().with_price("gpt-4o", 0.0, 0.0);
+        assert_eq!(table.cost("gpt-4o", 1000, 1000), 0.0);
+    }
+
+    #[test]
+    fn unknown_model_costs_zero() {
+        let table = PricingTable::default();
+        assert_eq!(table.cost("my-local-llama", 10_000, 10_000), 0.0);
+    }
+
+    #[test]
+    fn pricing_table_serde_roundtrip() {
+        let table = PricingTable::new().with_price("m", 0.5, 1.5);
+        let json = serde_json::to_string(&table).unwrap();
+        let back: PricingTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.get("m"), Some(ModelPrice::new(0.5, 1.5)));
+    }
+}
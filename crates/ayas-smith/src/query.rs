@@ -1,11 +1,18 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use chrono::{DateTime, TimeZone, Utc};
 use duckdb::{params, Connection};
 use uuid::Uuid;
 
+use crate::blob::{self, BlobStore};
 use crate::error::{Result, SmithError};
-use crate::types::{LatencyStats, Run, RunFilter, RunStatus, RunType, TokenUsageSummary};
+use crate::pricing::PricingTable;
+use crate::types::{
+    FeedbackStat, FeedbackStatsFilter, LatencyStats, ModelTokenUsage, Run, RunAggregate,
+    RunFilter, RunStatus, RunType, TokenUsageSummary,
+};
 
 /// Explicit column list with timestamp casts for reliable reading from DuckDB.
 /// DuckDB's Rust bindings may not auto-cast Timestamp columns to String,
@@ -21,6 +28,7 @@ const SELECT_COLUMNS: &str = "\
 pub struct SmithQuery {
     conn: Connection,
     base_dir: PathBuf,
+    blob_store: Option<Arc<dyn BlobStore>>,
 }
 
 impl SmithQuery {
@@ -34,9 +42,24 @@ impl SmithQuery {
         Ok(Self {
             conn,
             base_dir: base_dir.into(),
+            blob_store: None,
         })
     }
 
+    /// Resolve offloaded `{"__ref__": ...}` payloads through `store` when
+    /// reading runs.
+    pub fn with_blob_store(mut self, store: Arc<dyn BlobStore>) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    fn resolve_blobs(&self, run: &mut Run) -> Result<()> {
+        match &self.blob_store {
+            Some(blobs) => blob::resolve_run(run, &**blobs),
+            None => Ok(()),
+        }
+    }
+
     /// Get the glob pattern for Parquet files in a project directory.
     fn parquet_glob(&self, project: &str) -> String {
         let dir = self.base_dir.join(project);
@@ -63,37 +86,7 @@ impl SmithQuery {
         }
         let glob = self.parquet_glob(project);
 
-        let mut conditions = Vec::new();
-        let mut param_values: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
-
-        if let Some(ref rt) = filter.run_type {
-            conditions.push("run_type = ?".to_string());
-            param_values.push(Box::new(rt.as_str().to_string()));
-        }
-        if let Some(ref status) = filter.status {
-            conditions.push("status = ?".to_string());
-            param_values.push(Box::new(status.as_str().to_string()));
-        }
-        if let Some(ref name) = filter.name {
-            conditions.push("name = ?".to_string());
-            param_values.push(Box::new(name.clone()));
-        }
-        if let Some(ref trace_id) = filter.trace_id {
-            conditions.push("trace_id = ?".to_string());
-            param_values.push(Box::new(trace_id.to_string()));
-        }
-        if let Some(ref parent_id) = filter.parent_run_id {
-            conditions.push("parent_run_id = ?".to_string());
-            param_values.push(Box::new(parent_id.to_string()));
-        }
-        if let Some(ref after) = filter.start_after {
-            conditions.push("start_time > ?".to_string());
-            param_values.push(Box::new(after.to_rfc3339()));
-        }
-        if let Some(ref before) = filter.start_before {
-            conditions.push("start_time < ?".to_string());
-            param_values.push(Box::new(before.to_rfc3339()));
-        }
+        let (conditions, param_values) = filter_conditions(filter);
 
         let limit_clause = filter
             .limit
@@ -125,14 +118,15 @@ impl SmithQuery {
         let params_refs: Vec<&dyn duckdb::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
 
         let mut stmt = self.conn.prepare(&sql)?;
-        let rows = stmt.query_map(params_refs.as_slice(), |row| {
-            Ok(row_to_run(row))
-        })?;
+        let rows = stmt.query_map(params_refs.as_slice(), row_to_run)?;
 
         let mut runs = Vec::new();
         for row in rows {
             match row {
-                Ok(run) => runs.push(run),
+                Ok(mut run) => {
+                    self.resolve_blobs(&mut run)?;
+                    runs.push(run);
+                }
                 Err(e) => return Err(SmithError::DuckDb(e)),
             }
         }
@@ -154,12 +148,13 @@ impl SmithQuery {
         );
 
         let mut stmt = self.conn.prepare(&sql)?;
-        let mut rows = stmt.query_map(params![run_id.to_string()], |row| {
-            Ok(row_to_run(row))
-        })?;
+        let mut rows = stmt.query_map(params![run_id.to_string()], row_to_run)?;
 
         match rows.next() {
-            Some(Ok(run)) => Ok(Some(run)),
+            Some(Ok(mut run)) => {
+                self.resolve_blobs(&mut run)?;
+                Ok(Some(run))
+            }
             Some(Err(e)) => Err(SmithError::DuckDb(e)),
             None => Ok(None),
         }
@@ -185,22 +180,72 @@ impl SmithQuery {
         self.list_runs(&filter)
     }
 
-    /// Get token usage summary for runs matching the filter.
-    pub fn token_usage_summary(&self, filter: &RunFilter) -> Result<TokenUsageSummary> {
+    /// Case-insensitive substring search over run inputs and outputs,
+    /// newest first.
+    ///
+    /// `filter` narrows the candidate runs as in [`list_runs`](Self::list_runs);
+    /// its `limit` and `offset` are ignored in favour of `limit`. The match
+    /// is a `contains(lower(...))` predicate, so every run in the project's
+    /// Parquet files is scanned: cost grows linearly with stored payload
+    /// size, and the other filter fields are the only way to prune it.
+    /// Payloads offloaded to a blob store are matched on their reference,
+    /// not their content.
+    pub fn search_text(&self, query: &str, filter: &RunFilter, limit: usize) -> Result<Vec<Run>> {
         let project = filter.project.as_deref().unwrap_or("default");
         if !self.has_parquet_files(project) {
-            return Ok(TokenUsageSummary::default());
+            return Ok(Vec::new());
         }
         let glob = self.parquet_glob(project);
 
-        let mut conditions = vec!["run_type = 'llm'".to_string()];
-        if let Some(ref name) = filter.name {
-            conditions.push(format!("name = '{name}'"));
+        let (mut conditions, mut param_values) = filter_conditions(filter);
+        let needle = query.to_lowercase();
+        conditions.push(
+            "(contains(lower(input), ?) OR contains(lower(COALESCE(output, '')), ?))".to_string(),
+        );
+        param_values.push(Box::new(needle.clone()));
+        param_values.push(Box::new(needle));
+
+        let sql = format!(
+            "WITH deduped AS (\
+                SELECT *, ROW_NUMBER() OVER (\
+                    PARTITION BY run_id \
+                    ORDER BY CASE WHEN status != 'running' THEN 1 ELSE 0 END DESC, \
+                             CASE WHEN end_time IS NOT NULL THEN 1 ELSE 0 END DESC\
+                ) AS _rn \
+                FROM read_parquet('{glob}')\
+            ) \
+            SELECT {SELECT_COLUMNS} FROM deduped WHERE _rn = 1 AND {conditions} \
+            ORDER BY start_time DESC LIMIT {limit}",
+            conditions = conditions.join(" AND "),
+        );
+
+        let params_refs: Vec<&dyn duckdb::ToSql> = param_values.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_refs.as_slice(), row_to_run)?;
+
+        let mut runs = Vec::new();
+        for row in rows {
+            let mut run = row?;
+            self.resolve_blobs(&mut run)?;
+            runs.push(run);
         }
-        if let Some(ref trace_id) = filter.trace_id {
-            conditions.push(format!("trace_id = '{trace_id}'"));
+        Ok(runs)
+    }
+
+    /// Get token usage summary for the LLM runs matching the filter.
+    ///
+    /// `limit`/`offset` are ignored.
+    pub fn token_usage_summary(&self, filter: &RunFilter) -> Result<TokenUsageSummary> {
+        let project = filter.project.as_deref().unwrap_or("default");
+        if !self.has_parquet_files(project) {
+            return Ok(TokenUsageSummary::default());
         }
+        let glob = self.parquet_glob(project);
+        let (filter_conds, param_values) = filter_conditions(filter);
 
+        let mut conditions = vec!["run_type = 'llm'".to_string()];
+        conditions.extend(filter_conds);
         let where_clause = format!(" WHERE {}", conditions.join(" AND "));
 
         let sql = format!(
@@ -211,21 +256,59 @@ impl SmithQuery {
              FROM read_parquet('{glob}'){where_clause}"
         );
 
+        let params_refs: Vec<&dyn duckdb::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
         let mut stmt = self.conn.prepare(&sql)?;
-        let mut rows = stmt.query_map([], |row| {
+        let mut rows = stmt.query_map(params_refs.as_slice(), |row| {
             Ok(TokenUsageSummary {
                 total_input_tokens: row.get::<_, i64>(0)?,
                 total_output_tokens: row.get::<_, i64>(1)?,
                 total_tokens: row.get::<_, i64>(2)?,
                 run_count: row.get::<_, i64>(3)?,
+                by_model: BTreeMap::new(),
             })
         })?;
 
-        match rows.next() {
-            Some(Ok(summary)) => Ok(summary),
-            Some(Err(e)) => Err(SmithError::DuckDb(e)),
-            None => Ok(TokenUsageSummary::default()),
+        let mut summary = match rows.next() {
+            Some(Ok(summary)) => summary,
+            Some(Err(e)) => return Err(SmithError::DuckDb(e)),
+            None => return Ok(TokenUsageSummary::default()),
+        };
+
+        let by_model_sql = format!(
+            "SELECT name, COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0), \
+             COUNT(*) FROM read_parquet('{glob}'){where_clause} AND total_tokens IS NOT NULL \
+             GROUP BY name"
+        );
+        summary.by_model = self.usage_by_model(&by_model_sql, params_refs.as_slice())?;
+        Ok(summary)
+    }
+
+    /// Run a `SELECT name, input, output, count ... GROUP BY name` query.
+    fn usage_by_model(
+        &self,
+        sql: &str,
+        params: &[&dyn duckdb::ToSql],
+    ) -> Result<BTreeMap<String, ModelTokenUsage>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ModelTokenUsage {
+                    input_tokens: row.get::<_, i64>(1)?,
+                    output_tokens: row.get::<_, i64>(2)?,
+                    run_count: row.get::<_, i64>(3)?,
+                },
+            ))
+        })?;
+
+        let mut by_model = BTreeMap::new();
+        for row in rows {
+            let (name, usage) = row?;
+            by_model.insert(name, usage);
         }
+        Ok(by_model)
     }
 
     /// Get latency percentiles for runs matching the filter.
@@ -272,6 +355,195 @@ impl SmithQuery {
         }
     }
 
+    /// Compute run count, latency percentiles and token totals in a single
+    /// SQL query over the deduplicated runs matching the filter.
+    ///
+    /// Honors `run_type`, `status`, `name`, `trace_id`, `parent_run_id` and
+    /// the `start_after`/`start_before` time range; `limit`/`offset` are ignored.
+    pub fn aggregate(&self, filter: &RunFilter) -> Result<RunAggregate> {
+        let project = filter.project.as_deref().unwrap_or("default");
+        if !self.has_parquet_files(project) {
+            return Ok(RunAggregate::default());
+        }
+        let glob = self.parquet_glob(project);
+        let (conditions, param_values) = filter_conditions(filter);
+
+        let and_where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" AND {}", conditions.join(" AND "))
+        };
+        let deduped = format!(
+            "WITH deduped AS (\
+                SELECT *, ROW_NUMBER() OVER (\
+                    PARTITION BY run_id \
+                    ORDER BY CASE WHEN status != 'running' THEN 1 ELSE 0 END DESC, \
+                             CASE WHEN end_time IS NOT NULL THEN 1 ELSE 0 END DESC\
+                ) AS _rn \
+                FROM read_parquet('{glob}')\
+            ) "
+        );
+
+        let sql = format!(
+            "{deduped}\
+            SELECT \
+             COUNT(*) AS cnt, \
+             COALESCE(percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_ms), 0) AS p50, \
+             COALESCE(percentile_cont(0.9) WITHIN GROUP (ORDER BY latency_ms), 0) AS p90, \
+             COALESCE(percentile_cont(0.95) WITHIN GROUP (ORDER BY latency_ms), 0) AS p95, \
+             COALESCE(percentile_cont(0.99) WITHIN GROUP (ORDER BY latency_ms), 0) AS p99, \
+             COALESCE(SUM(input_tokens), 0) AS total_input, \
+             COALESCE(SUM(output_tokens), 0) AS total_output, \
+             COALESCE(SUM(total_tokens), 0) AS total, \
+             COUNT(total_tokens) AS token_runs \
+            FROM deduped WHERE _rn = 1{and_where_clause}"
+        );
+
+        let params_refs: Vec<&dyn duckdb::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows = stmt.query_map(params_refs.as_slice(), |row| {
+            Ok(RunAggregate {
+                run_count: row.get::<_, i64>(0)?,
+                latency: LatencyStats {
+                    p50: row.get::<_, f64>(1)?,
+                    p90: row.get::<_, f64>(2)?,
+                    p95: row.get::<_, f64>(3)?,
+                    p99: row.get::<_, f64>(4)?,
+                },
+                tokens: TokenUsageSummary {
+                    total_input_tokens: row.get::<_, i64>(5)?,
+                    total_output_tokens: row.get::<_, i64>(6)?,
+                    total_tokens: row.get::<_, i64>(7)?,
+                    run_count: row.get::<_, i64>(8)?,
+                    by_model: BTreeMap::new(),
+                },
+                total_cost: None,
+            })
+        })?;
+
+        let mut aggregate = match rows.next() {
+            Some(Ok(aggregate)) => aggregate,
+            Some(Err(e)) => return Err(SmithError::DuckDb(e)),
+            None => return Ok(RunAggregate::default()),
+        };
+
+        let by_model_sql = format!(
+            "{deduped}\
+            SELECT name, COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0), \
+             COUNT(*) \
+            FROM deduped WHERE _rn = 1 AND run_type = 'llm' AND total_tokens IS NOT NULL\
+            {and_where_clause} GROUP BY name"
+        );
+        aggregate.tokens.by_model = self.usage_by_model(&by_model_sql, params_refs.as_slice())?;
+        Ok(aggregate)
+    }
+
+    /// [`aggregate`](Self::aggregate) with `total_cost` priced from `pricing`.
+    pub fn aggregate_with_cost(
+        &self,
+        filter: &RunFilter,
+        pricing: &PricingTable,
+    ) -> Result<RunAggregate> {
+        Ok(self.aggregate(filter)?.with_cost(pricing))
+    }
+
+    /// Feedback score statistics per key (and time bucket), computed in SQL.
+    ///
+    /// Feedback lives in a JSON file, so it is loaded into a temporary table
+    /// on this connection first.
+    pub fn feedback_stats(&self, filter: &FeedbackStatsFilter) -> Result<Vec<FeedbackStat>> {
+        let items = crate::duckdb_store::load_feedback_sync(&self.base_dir)?;
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ts = |t: &DateTime<Utc>| t.format("%Y-%m-%d %H:%M:%S%.6f").to_string();
+        self.conn.execute_batch(
+            "CREATE OR REPLACE TEMP TABLE _feedback \
+             (run_id VARCHAR, key VARCHAR, score DOUBLE, created_at VARCHAR)",
+        )?;
+        {
+            let mut appender = self.conn.appender("_feedback")?;
+            for f in &items {
+                appender.append_row(params![
+                    f.run_id.to_string(),
+                    f.key,
+                    f.score,
+                    ts(&f.created_at)
+                ])?;
+            }
+        }
+
+        let mut conditions: Vec<String> = Vec::new();
+        let mut param_values: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
+        if let Some(ref project) = filter.project {
+            if !self.has_parquet_files(project) {
+                return Ok(Vec::new());
+            }
+            let glob = self.parquet_glob(project);
+            conditions.push(format!("run_id IN (SELECT run_id FROM read_parquet('{glob}'))"));
+        }
+        if let Some(ref key) = filter.key {
+            conditions.push("key = ?".into());
+            param_values.push(Box::new(key.clone()));
+        }
+        if let Some(ref after) = filter.created_after {
+            conditions.push("ts > CAST(? AS TIMESTAMP)".into());
+            param_values.push(Box::new(ts(after)));
+        }
+        if let Some(ref before) = filter.created_before {
+            conditions.push("ts < CAST(? AS TIMESTAMP)".into());
+            param_values.push(Box::new(ts(before)));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        let bucket_expr = match filter.bucket {
+            // date_trunc returns a DATE for day buckets and up; keep the time part
+            Some(bucket) => format!(
+                "CAST(CAST(date_trunc('{}', ts) AS TIMESTAMP) AS VARCHAR)",
+                bucket.as_str()
+            ),
+            None => "CAST(NULL AS VARCHAR)".into(),
+        };
+
+        let sql = format!(
+            "WITH fb AS (\
+                SELECT run_id, key, score, CAST(created_at AS TIMESTAMP) AS ts FROM _feedback\
+            ) \
+            SELECT key, {bucket_expr} AS bucket, COUNT(*) AS cnt, AVG(score) AS mean, \
+             COALESCE(STDDEV_SAMP(score), 0) AS stddev \
+            FROM fb{where_clause} \
+            GROUP BY key, bucket ORDER BY key, bucket"
+        );
+
+        let params_refs: Vec<&dyn duckdb::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            let bucket: Option<String> = row.get(1)?;
+            Ok(FeedbackStat {
+                key: row.get::<_, String>(0)?,
+                bucket: bucket.as_deref().map(|b| parse_timestamp(b, 1)).transpose()?,
+                count: row.get::<_, i64>(2)?,
+                mean: row.get::<_, f64>(3)?,
+                stddev: row.get::<_, f64>(4)?,
+            })
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+        Ok(stats)
+    }
+
     /// Execute a raw SQL query and return results as JSON strings.
     pub fn raw_query(&self, sql: &str) -> Result<Vec<String>> {
         let mut stmt = self.conn.prepare(sql)?;
@@ -311,7 +583,51 @@ impl SmithQuery {
     }
 }
 
-fn row_to_run(row: &duckdb::Row<'_>) -> Run {
+/// Build parameterized WHERE conditions for the filter (excluding project,
+/// which selects the parquet directory, and `limit`/`offset`).
+fn filter_conditions(filter: &RunFilter) -> (Vec<String>, Vec<Box<dyn duckdb::ToSql>>) {
+    let mut conditions = Vec::new();
+    let mut param_values: Vec<Box<dyn duckdb::ToSql>> = Vec::new();
+
+    if let Some(ref rt) = filter.run_type {
+        conditions.push("run_type = ?".to_string());
+        param_values.push(Box::new(rt.as_str().to_string()));
+    }
+    if let Some(ref status) = filter.status {
+        conditions.push("status = ?".to_string());
+        param_values.push(Box::new(status.as_str().to_string()));
+    }
+    if let Some(ref name) = filter.name {
+        conditions.push("name = ?".to_string());
+        param_values.push(Box::new(name.clone()));
+    }
+    if let Some(ref trace_id) = filter.trace_id {
+        conditions.push("trace_id = ?".to_string());
+        param_values.push(Box::new(trace_id.to_string()));
+    }
+    if let Some(ref parent_id) = filter.parent_run_id {
+        conditions.push("parent_run_id = ?".to_string());
+        param_values.push(Box::new(parent_id.to_string()));
+    }
+    if let Some(ref after) = filter.start_after {
+        conditions.push("start_time > ?".to_string());
+        param_values.push(Box::new(after.to_rfc3339()));
+    }
+    if let Some(ref before) = filter.start_before {
+        conditions.push("start_time < ?".to_string());
+        param_values.push(Box::new(before.to_rfc3339()));
+    }
+    // Tags are stored as a JSON array string; match each quoted tag.
+    for tag in &filter.tags {
+        conditions.push("tags LIKE ? ESCAPE '\\'".to_string());
+        let quoted = serde_json::to_string(tag).unwrap_or_default();
+        let escaped = quoted.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        param_values.push(Box::new(format!("%{escaped}%")));
+    }
+    (conditions, param_values)
+}
+
+fn row_to_run(row: &duckdb::Row<'_>) -> duckdb::Result<Run> {
     let run_id_str: String = row.get(0).unwrap_or_default();
     let parent_run_id_str: Option<String> = row.get(1).ok();
     let trace_id_str: String = row.get(2).unwrap_or_default();
@@ -337,7 +653,7 @@ fn row_to_run(row: &duckdb::Row<'_>) -> Run {
 
     let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
 
-    Run {
+    Ok(Run {
         run_id: run_id_str.parse().unwrap_or_default(),
         parent_run_id: parent_run_id_str
             .as_deref()
@@ -346,8 +662,8 @@ fn row_to_run(row: &duckdb::Row<'_>) -> Run {
         name,
         run_type: run_type_str.parse().unwrap_or(RunType::Chain),
         project,
-        start_time: parse_timestamp(&start_time_str),
-        end_time: end_time_str.as_deref().map(parse_timestamp),
+        start_time: parse_timestamp(&start_time_str, 6)?,
+        end_time: end_time_str.as_deref().map(|s| parse_timestamp(s, 7)).transpose()?,
         status: status_str.parse().unwrap_or(RunStatus::Success),
         input,
         output,
@@ -359,21 +675,30 @@ fn row_to_run(row: &duckdb::Row<'_>) -> Run {
         total_tokens,
         latency_ms,
         dotted_order,
-    }
+    })
 }
 
-fn parse_timestamp(s: &str) -> DateTime<Utc> {
+/// Parse a timestamp DuckDB returned as text in result column `column`.
+fn parse_timestamp(s: &str, column: usize) -> duckdb::Result<DateTime<Utc>> {
     // Try parsing as RFC3339 first, then fall back to common DuckDB timestamp formats
     if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
-        return dt.with_timezone(&Utc);
+        return Ok(dt.with_timezone(&Utc));
+    }
+    // TIMESTAMPTZ, e.g. "2024-03-01 10:00:00.123+00"
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Ok(dt.with_timezone(&Utc));
     }
     if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f") {
-        return Utc.from_utc_datetime(&dt);
+        return Ok(Utc.from_utc_datetime(&dt));
     }
     if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
-        return Utc.from_utc_datetime(&dt);
+        return Ok(Utc.from_utc_datetime(&dt));
     }
-    Utc::now()
+    Err(duckdb::Error::FromSqlConversionFailure(
+        column,
+        duckdb::types::Type::Text,
+        format!("unrecognized timestamp '{s}'").into(),
+    ))
 }
 
 #[cfg(test)]
@@ -494,6 +819,50 @@ mod tests {
         assert_eq!(runs.len(), 1);
     }
 
+    #[test]
+    fn search_text_case_insensitive() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        // "hello" appears in the chain's input and the LLM's output
+        let runs = client.search_text("HELLO", &filter, 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.windows(2).all(|w| w[0].start_time >= w[1].start_time));
+        assert!(runs.iter().all(|r| r.contains_text("hello")));
+
+        assert_eq!(client.search_text("hello", &filter, 1).unwrap().len(), 1);
+        assert!(client.search_text("no such text", &filter, 10).unwrap().is_empty());
+
+        let llm_only = RunFilter {
+            run_type: Some(RunType::Llm),
+            ..filter.clone()
+        };
+        let runs = client.search_text("hello", &llm_only, 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name, "gpt-4o");
+    }
+
+    #[test]
+    fn parse_timestamp_formats() {
+        let expected = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        for text in [
+            "2024-03-01T10:00:00Z",
+            "2024-03-01 10:00:00",
+            "2024-03-01 10:00:00+00",
+            "2024-03-01 19:00:00+09:00",
+        ] {
+            assert_eq!(parse_timestamp(text, 0).unwrap(), expected, "{text}");
+        }
+        let with_millis = parse_timestamp("2024-03-01 10:00:00.250+00", 0).unwrap();
+        assert_eq!(with_millis.timestamp_subsec_millis(), 250);
+        assert!(parse_timestamp("yesterday", 0).is_err());
+    }
+
     #[test]
     fn get_run_by_id() {
         let dir = tempfile::tempdir().unwrap();
@@ -558,6 +927,22 @@ mod tests {
         assert_eq!(summary.run_count, 1);
     }
 
+    #[test]
+    fn token_usage_summary_binds_filter_values() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            name: Some("x' OR '1'='1".into()),
+            ..Default::default()
+        };
+        let summary = client.token_usage_summary(&filter).unwrap();
+        assert_eq!(summary.run_count, 0);
+        assert!(summary.by_model.is_empty());
+    }
+
     #[test]
     fn latency_percentiles_query() {
         let dir = tempfile::tempdir().unwrap();
@@ -574,6 +959,106 @@ mod tests {
         assert!(stats.p90 >= stats.p50);
     }
 
+    #[test]
+    fn aggregate_query() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        let agg = client.aggregate(&filter).unwrap();
+        assert_eq!(agg.run_count, 3);
+        assert_eq!(agg.tokens.total_input_tokens, 50);
+        assert_eq!(agg.tokens.total_output_tokens, 10);
+        assert_eq!(agg.tokens.run_count, 1);
+        assert!(agg.latency.p99 >= agg.latency.p50);
+    }
+
+    #[test]
+    fn aggregate_time_range_and_type() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let future = RunFilter {
+            project: Some("test-proj".into()),
+            start_after: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(client.aggregate(&future).unwrap().run_count, 0);
+
+        let tools = RunFilter {
+            project: Some("test-proj".into()),
+            run_type: Some(RunType::Tool),
+            start_before: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let agg = client.aggregate(&tools).unwrap();
+        assert_eq!(agg.run_count, 1);
+        assert_eq!(agg.tokens.total_tokens, 0);
+    }
+
+    #[test]
+    fn aggregate_with_cost_prices_llm_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        create_test_runs(dir.path());
+
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        let pricing = PricingTable::new().with_price("gpt-4o", 1.0, 10.0);
+        let agg = client.aggregate_with_cost(&filter, &pricing).unwrap();
+
+        let usage = &agg.tokens.by_model["gpt-4o"];
+        assert_eq!((usage.input_tokens, usage.output_tokens, usage.run_count), (50, 10, 1));
+        assert!((agg.total_cost.unwrap() - (0.05 + 0.1)).abs() < 1e-9);
+
+        let summary = client.token_usage_summary(&filter).unwrap();
+        assert_eq!(summary.by_model.len(), 1);
+        assert!((summary.cost(&pricing) - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn aggregate_missing_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = SmithQuery::new(dir.path()).unwrap();
+        let filter = RunFilter {
+            project: Some("nope".into()),
+            ..Default::default()
+        };
+        assert_eq!(client.aggregate(&filter).unwrap().run_count, 0);
+    }
+
+    #[test]
+    fn list_runs_resolves_blob_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let blobs = Arc::new(crate::blob::InMemoryBlobStore::new());
+        let mut run = Run::builder("big", RunType::Chain)
+            .project("test-proj")
+            .input("payload ".repeat(100))
+            .finish_ok("ok");
+        crate::blob::offload_run(&mut run, 32, &*blobs).unwrap();
+        flush_runs(std::slice::from_ref(&run), dir.path(), "test-proj").unwrap();
+
+        let filter = RunFilter {
+            project: Some("test-proj".into()),
+            ..Default::default()
+        };
+        let raw = SmithQuery::new(dir.path()).unwrap().list_runs(&filter).unwrap();
+        assert_eq!(raw[0].input, run.input);
+
+        let client = SmithQuery::new(dir.path()).unwrap().with_blob_store(blobs);
+        let runs = client.list_runs(&filter).unwrap();
+        assert_eq!(runs[0].input, "payload ".repeat(100));
+        let single = client.get_run(run.run_id, "test-proj").unwrap().unwrap();
+        assert_eq!(single.input, "payload ".repeat(100));
+    }
+
     #[test]
     fn raw_query_basic() {
         let client = SmithQuery::new("/tmp/nonexistent").unwrap();
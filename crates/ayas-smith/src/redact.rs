@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::types::Run;
+
+/// Replacement for values under a denied key.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Configurable keys hidden by [`PatternRedactor::new`].
+pub const DEFAULT_DENIED_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "authorization",
+    "password",
+    "secret",
+    "token",
+    "access_token",
+];
+
+/// Scrubs sensitive data from a run before it is persisted.
+///
+/// Applied by [`SmithClient`](crate::client::SmithClient) to every submitted
+/// run when set in [`SmithConfig`](crate::client::SmithConfig).
+pub trait Redactor: Send + Sync + fmt::Debug {
+    /// Redact a JSON value in place.
+    fn redact(&self, value: &mut Value);
+}
+
+/// Redact a run's input, output, error and metadata.
+///
+/// Fields holding JSON are redacted structurally; other text is redacted as a
+/// single string.
+pub fn redact_run(redactor: &dyn Redactor, run: &mut Run) {
+    redact_field(redactor, &mut run.input);
+    redact_field(redactor, &mut run.metadata);
+    if let Some(output) = &mut run.output {
+        redact_field(redactor, output);
+    }
+    if let Some(error) = &mut run.error {
+        redact_field(redactor, error);
+    }
+}
+
+fn redact_field(redactor: &dyn Redactor, field: &mut String) {
+    match serde_json::from_str::<Value>(field) {
+        Ok(mut value) => {
+            redactor.redact(&mut value);
+            if let Ok(json) = serde_json::to_string(&value) {
+                *field = json;
+            }
+        }
+        Err(_) => {
+            let mut value = Value::String(std::mem::take(field));
+            redactor.redact(&mut value);
+            *field = match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+        }
+    }
+}
+
+struct Rule {
+    regex: Regex,
+    replacement: String,
+    /// Extra check on a match; failing matches are left as is.
+    validate: Option<fn(&str) -> bool>,
+}
+
+/// Regex-based redactor for emails, phone numbers and credit-card numbers,
+/// plus a denylist of object keys whose values are always hidden.
+pub struct PatternRedactor {
+    rules: Vec<Rule>,
+    denied_keys: HashSet<String>,
+}
+
+impl PatternRedactor {
+    /// Built-in patterns and [`DEFAULT_DENIED_KEYS`].
+    pub fn new() -> Self {
+        Self::empty()
+            .with_rule(
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+                "[EMAIL]",
+                None,
+            )
+            .with_rule(r"\b(?:\d[ -]?){12,18}\d\b", "[CARD]", Some(luhn_valid))
+            .with_rule(
+                r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]\d{3}[\s.-]\d{4}\b",
+                "[PHONE]",
+                None,
+            )
+            .with_denied_keys(DEFAULT_DENIED_KEYS.iter().copied())
+    }
+
+    /// No patterns and no denied keys.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            denied_keys: HashSet::new(),
+        }
+    }
+
+    /// Add a pattern whose matches are replaced by `replacement`.
+    ///
+    /// Panics if `pattern` is not a valid regex.
+    pub fn with_pattern(self, pattern: &str, replacement: impl Into<String>) -> Self {
+        self.with_rule(pattern, replacement, None)
+    }
+
+    /// Hide the value of every object key with one of these names (case-insensitive).
+    pub fn with_denied_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.denied_keys
+            .extend(keys.into_iter().map(|k| k.as_ref().to_ascii_lowercase()));
+        self
+    }
+
+    fn with_rule(
+        mut self,
+        pattern: &str,
+        replacement: impl Into<String>,
+        validate: Option<fn(&str) -> bool>,
+    ) -> Self {
+        self.rules.push(Rule {
+            regex: Regex::new(pattern).expect("invalid redaction pattern"),
+            replacement: replacement.into(),
+            validate,
+        });
+        self
+    }
+
+    fn redact_text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for rule in &self.rules {
+            text = rule
+                .regex
+                .replace_all(&text, |caps: &regex::Captures| {
+                    let found = &caps[0];
+                    match rule.validate {
+                        Some(valid) if !valid(found) => found.to_string(),
+                        _ => rule.replacement.clone(),
+                    }
+                })
+                .into_owned();
+        }
+        text
+    }
+}
+
+impl Default for PatternRedactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for PatternRedactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatternRedactor")
+            .field("patterns", &self.rules.iter().map(|r| r.regex.as_str()).collect::<Vec<_>>())
+            .field("denied_keys", &self.denied_keys)
+            .finish()
+    }
+}
+
+impl Redactor for PatternRedactor {
+    fn redact(&self, value: &mut Value) {
+        match value {
+            Value::String(s) => *s = self.redact_text(s),
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact(v)),
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    if self.denied_keys.contains(&key.to_ascii_lowercase()) {
+                        *v = Value::String(REDACTED.into());
+                    } else {
+                        self.redact(v);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Luhn checksum, so that long numbers which are not card numbers
+/// (timestamps, ids) are left alone.
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RunType;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_email_phone_and_card() {
+        let redactor = PatternRedactor::new();
+        let mut value = json!({
+            "message": "Mail jane.doe@example.com or call +1 415-555-0132",
+            "payment": ["card 4111 1111 1111 1111"],
+        });
+        redactor.redact(&mut value);
+        assert_eq!(value["message"], "Mail [EMAIL] or call [PHONE]");
+        assert_eq!(value["payment"][0], "card [CARD]");
+    }
+
+    #[test]
+    fn leaves_non_card_numbers_and_dates() {
+        let redactor = PatternRedactor::new();
+        let mut value = json!("ts 1700000000000 on 2024-01-15");
+        redactor.redact(&mut value);
+        assert_eq!(value, "ts 1700000000000 on 2024-01-15");
+    }
+
+    #[test]
+    fn denied_keys_are_hidden_case_insensitively() {
+        let redactor = PatternRedactor::empty().with_denied_keys(["api_key", "ssn"]);
+        let mut value = json!({"configurable": {"API_KEY": "sk-123", "SSN": 1, "model": "m"}});
+        redactor.redact(&mut value);
+        assert_eq!(value["configurable"]["API_KEY"], REDACTED);
+        assert_eq!(value["configurable"]["SSN"], REDACTED);
+        assert_eq!(value["configurable"]["model"], "m");
+    }
+
+    #[test]
+    fn redact_run_handles_json_and_plain_text() {
+        let mut run = Run::builder("r", RunType::Chain)
+            .input(r#"{"email":"a@b.io"}"#)
+            .finish_err("failed for a@b.io");
+        redact_run(&PatternRedactor::new(), &mut run);
+        assert_eq!(run.input, r#"{"email":"[EMAIL]"}"#);
+        assert_eq!(run.error.as_deref(), Some("failed for [EMAIL]"));
+    }
+}
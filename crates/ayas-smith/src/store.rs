@@ -1,10 +1,12 @@
+use std::collections::HashSet;
+
 use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::error::SmithError;
 use crate::types::{
-    Dataset, Example, Feedback, FeedbackFilter, LatencyStats, Project, Run, RunFilter, RunPatch,
-    TokenUsageSummary,
+    Dataset, Example, Feedback, FeedbackFilter, FeedbackStat, FeedbackStatsFilter, LatencyStats,
+    Project, Run, RunAggregate, RunFilter, RunPatch, TokenUsageSummary,
 };
 
 /// Storage abstraction for Smith tracing data.
@@ -53,12 +55,94 @@ pub trait SmithStore: Send + Sync {
     /// Get latency percentiles for runs matching the filter.
     async fn latency_percentiles(&self, filter: &RunFilter) -> Result<LatencyStats, SmithError>;
 
+    /// Compute run count, latency percentiles and token totals for runs
+    /// matching the filter (project, run type, status, name, time range).
+    ///
+    /// The default implementation fetches the matching runs and aggregates
+    /// in memory; backends with a query engine should override it to push
+    /// the aggregation into the query. `limit` and `offset` are ignored.
+    async fn aggregate(&self, filter: &RunFilter) -> Result<RunAggregate, SmithError> {
+        let filter = RunFilter {
+            limit: None,
+            offset: None,
+            ..filter.clone()
+        };
+        let runs = self.list_runs(&filter).await?;
+        Ok(RunAggregate::from_runs(&runs))
+    }
+
+    /// Case-insensitive substring search over run inputs and outputs,
+    /// returning at most `limit` runs, newest first.
+    ///
+    /// `filter` narrows the candidates; its `limit` and `offset` are
+    /// ignored. The default implementation lists every matching run and
+    /// scans the payloads in memory, so it loads the whole filtered set;
+    /// backends with a query engine should override it.
+    async fn search_text(
+        &self,
+        query: &str,
+        filter: &RunFilter,
+        limit: usize,
+    ) -> Result<Vec<Run>, SmithError> {
+        let filter = RunFilter {
+            limit: None,
+            offset: None,
+            ..filter.clone()
+        };
+        let mut runs: Vec<Run> = self
+            .list_runs(&filter)
+            .await?
+            .into_iter()
+            .filter(|r| r.contains_text(query))
+            .collect();
+        runs.sort_by_key(|r| std::cmp::Reverse(r.start_time));
+        runs.truncate(limit);
+        Ok(runs)
+    }
+
     /// Persist a feedback entry.
     async fn put_feedback(&self, feedback: &Feedback) -> Result<(), SmithError>;
 
     /// List feedback matching the given filter.
     async fn list_feedback(&self, filter: &FeedbackFilter) -> Result<Vec<Feedback>, SmithError>;
 
+    /// Count, mean and standard deviation of feedback scores per key,
+    /// optionally per time bucket.
+    ///
+    /// The default implementation lists the feedback and aggregates in
+    /// memory; backends with a query engine should override it.
+    async fn feedback_stats(
+        &self,
+        filter: &FeedbackStatsFilter,
+    ) -> Result<Vec<FeedbackStat>, SmithError> {
+        let items = self
+            .list_feedback(&FeedbackFilter {
+                run_id: None,
+                key: filter.key.clone(),
+            })
+            .await?;
+
+        let project_runs: Option<HashSet<Uuid>> = match &filter.project {
+            Some(project) => {
+                let runs = self
+                    .list_runs(&RunFilter {
+                        project: Some(project.clone()),
+                        ..Default::default()
+                    })
+                    .await?;
+                Some(runs.into_iter().map(|r| r.run_id).collect())
+            }
+            None => None,
+        };
+
+        let items: Vec<Feedback> = items
+            .into_iter()
+            .filter(|f| filter.matches(f))
+            .filter(|f| project_runs.as_ref().is_none_or(|ids| ids.contains(&f.run_id)))
+            .collect();
+        Ok(FeedbackStat::from_feedback(&items, filter.bucket))
+    }
+
     // --- Project management ---
 
     /// Create a new project.
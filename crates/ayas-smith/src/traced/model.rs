@@ -1,14 +1,17 @@
+use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 
 use ayas_core::error::Result;
-use ayas_core::message::Message;
-use ayas_core::model::{CallOptions, ChatModel, ChatResult};
+use ayas_core::message::{AIContent, Message, ToolCall, UsageMetadata};
+use ayas_core::model::{CallOptions, ChatModel, ChatResult, ChatStreamEvent};
 
-use crate::client::SmithClient;
+use crate::client::{RunGuard, SmithClient};
 use crate::context::{build_dotted_order, SMITH_TRACE_CTX};
-use crate::types::{Run, RunType};
+use crate::types::{Run, RunBuilder, RunType};
 
 /// A ChatModel wrapper that records tracing information for each generation.
 pub struct TracedChatModel {
@@ -20,34 +23,25 @@ impl TracedChatModel {
     pub fn new(inner: Arc<dyn ChatModel>, client: SmithClient) -> Self {
         Self { inner, client }
     }
-}
-
-#[async_trait]
-impl ChatModel for TracedChatModel {
-    async fn generate(
-        &self,
-        messages: &[Message],
-        options: &CallOptions,
-    ) -> Result<ChatResult> {
-        if !self.client.is_enabled() {
-            return self.inner.generate(messages, options).await;
-        }
 
+    /// Builder for an LLM run over `messages`, nested under the task-local
+    /// trace context (set by TracedRunnable) when there is one.
+    fn run_builder(&self, messages: &[Message]) -> RunBuilder {
         let input_json = serde_json::to_string(&messages).unwrap_or_else(|_| "[]".into());
         let run_id = uuid::Uuid::new_v4();
         let start_time = chrono::Utc::now();
 
-        // Try to get trace context from task-local (set by TracedRunnable)
-        let ctx = SMITH_TRACE_CTX.try_with(|c| c.clone()).ok();
-
         let mut builder = Run::builder(self.inner.model_name(), RunType::Llm)
             .run_id(run_id)
             .project(self.client.project())
             .input(&input_json)
             .start_time(start_time);
 
-        if let Some(ref ctx) = ctx {
-            builder = builder.trace_id(ctx.trace_id);
+        if let Ok(ctx) = SMITH_TRACE_CTX.try_with(|c| c.clone()) {
+            builder = builder.trace_id(ctx.trace_id).tags(ctx.tags.clone());
+            if let Some(ref metadata) = ctx.metadata {
+                builder = builder.metadata(metadata);
+            }
             if let Some(pid) = ctx.parent_run_id {
                 builder = builder.parent_run_id(pid);
             }
@@ -55,7 +49,86 @@ impl ChatModel for TracedChatModel {
                 build_dotted_order(start_time, run_id, Some(&ctx.dotted_order));
             builder = builder.dotted_order(dotted_order);
         }
+        builder
+    }
+}
+
+/// State of a traced stream: the inner events, the open run and what has
+/// been streamed so far.
+struct StreamRun {
+    events: Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>,
+    /// Taken once the run is finished.
+    guard: Option<RunGuard>,
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    tool_args: HashMap<String, String>,
+    usage: Option<UsageMetadata>,
+}
+
+impl StreamRun {
+    fn record(&mut self, event: &ChatStreamEvent) {
+        match event {
+            ChatStreamEvent::Token(t) => self.text.push_str(t),
+            ChatStreamEvent::ToolCallStart { id, name } => {
+                self.tool_args.insert(id.clone(), String::new());
+                self.tool_calls.push(ToolCall {
+                    id: id.clone(),
+                    name: name.clone(),
+                    arguments: serde_json::Value::Null,
+                });
+            }
+            ChatStreamEvent::ToolCallDelta { id, arguments } => {
+                if let Some(buf) = self.tool_args.get_mut(id) {
+                    buf.push_str(arguments);
+                }
+            }
+            ChatStreamEvent::Usage(u) => self.usage = Some(u.clone()),
+            ChatStreamEvent::Reasoning(_) | ChatStreamEvent::Done => {}
+        }
+    }
 
+    /// Finish the run with the accumulated message and usage.
+    fn finish(&mut self) {
+        let Some(guard) = self.guard.take() else {
+            return;
+        };
+        let mut tool_calls = std::mem::take(&mut self.tool_calls);
+        for tc in &mut tool_calls {
+            if let Some(args) = self.tool_args.get(&tc.id) {
+                tc.arguments = serde_json::from_str(args)
+                    .unwrap_or_else(|_| serde_json::Value::String(args.clone()));
+            }
+        }
+        let message = Message::AI(AIContent {
+            content: std::mem::take(&mut self.text),
+            tool_calls,
+            usage: self.usage.clone(),
+        });
+        let output_json = serde_json::to_string(&message).unwrap_or_else(|_| "null".into());
+        let (input_tokens, output_tokens, total_tokens) = match self.usage {
+            Some(ref usage) => (
+                usage.input_tokens as i64,
+                usage.output_tokens as i64,
+                usage.total_tokens as i64,
+            ),
+            None => (0, 0, 0),
+        };
+        guard.finish_llm(output_json, input_tokens, output_tokens, total_tokens);
+    }
+}
+
+#[async_trait]
+impl ChatModel for TracedChatModel {
+    async fn generate(
+        &self,
+        messages: &[Message],
+        options: &CallOptions,
+    ) -> Result<ChatResult> {
+        if !self.client.is_enabled() {
+            return self.inner.generate(messages, options).await;
+        }
+
+        let builder = self.run_builder(messages);
         match self.inner.generate(messages, options).await {
             Ok(result) => {
                 let output_json =
@@ -87,13 +160,72 @@ impl ChatModel for TracedChatModel {
     fn model_name(&self) -> &str {
         self.inner.model_name()
     }
+
+    /// Counting is not traced.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u64> {
+        self.inner.count_tokens(messages).await
+    }
+
+    /// Stream from the inner model, tracing the call as one LLM run.
+    ///
+    /// The run is submitted as running when the stream opens and finished
+    /// with the accumulated output and usage on `Done` (or when the inner
+    /// stream ends), or as an error on the first failed event. A stream
+    /// dropped before then is recorded as an error by its [`RunGuard`].
+    async fn stream(
+        &self,
+        messages: &[Message],
+        options: &CallOptions,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
+        if !self.client.is_enabled() {
+            return self.inner.stream(messages, options).await;
+        }
+
+        let guard = RunGuard::new(self.client.clone(), self.run_builder(messages));
+        let events = match self.inner.stream(messages, options).await {
+            Ok(events) => events,
+            Err(e) => {
+                guard.finish_err(e.to_string());
+                return Err(e);
+            }
+        };
+
+        let state = StreamRun {
+            events,
+            guard: Some(guard),
+            text: String::new(),
+            tool_calls: Vec::new(),
+            tool_args: HashMap::new(),
+            usage: None,
+        };
+        Ok(Box::pin(futures::stream::unfold(state, |mut state| async move {
+            match state.events.next().await {
+                Some(Ok(event)) => {
+                    state.record(&event);
+                    if event == ChatStreamEvent::Done {
+                        state.finish();
+                    }
+                    Some((Ok(event), state))
+                }
+                Some(Err(e)) => {
+                    if let Some(guard) = state.guard.take() {
+                        guard.finish_err(e.to_string());
+                    }
+                    Some((Err(e), state))
+                }
+                None => {
+                    state.finish();
+                    None
+                }
+            }
+        })))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::context::SmithTraceCtx;
-    use ayas_core::message::{AIContent, UsageMetadata};
 
     struct MockModel;
 
@@ -104,21 +236,24 @@ mod tests {
             _messages: &[Message],
             _options: &CallOptions,
         ) -> Result<ChatResult> {
-            Ok(ChatResult {
-                message: Message::AI(AIContent {
-                    content: "Hello!".into(),
-                    tool_calls: Vec::new(),
-                    usage: Some(UsageMetadata {
-                        input_tokens: 10,
-                        output_tokens: 5,
-                        total_tokens: 15,
-                    }),
+            let message = Message::AI(AIContent {
+                content: "Hello!".into(),
+                tool_calls: Vec::new(),
+                usage: Some(UsageMetadata {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    total_tokens: 15,
+                    ..Default::default()
                 }),
+            });
+            Ok(ChatResult {
                 usage: Some(UsageMetadata {
                     input_tokens: 10,
                     output_tokens: 5,
                     total_tokens: 15,
+                    ..Default::default()
                 }),
+                ..ChatResult::new(message)
             })
         }
 
@@ -127,6 +262,95 @@ mod tests {
         }
     }
 
+    /// Streams one token, then fails.
+    struct BrokenStreamModel;
+
+    #[async_trait]
+    impl ChatModel for BrokenStreamModel {
+        async fn generate(
+            &self,
+            _messages: &[Message],
+            _options: &CallOptions,
+        ) -> Result<ChatResult> {
+            unreachable!("only streamed")
+        }
+
+        fn model_name(&self) -> &str {
+            "broken-model"
+        }
+
+        async fn stream(
+            &self,
+            _messages: &[Message],
+            _options: &CallOptions,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent>> + Send>>> {
+            Ok(Box::pin(futures::stream::iter(vec![
+                Ok(ChatStreamEvent::Token("Hel".into())),
+                Err(ayas_core::error::AyasError::Other("connection reset".into())),
+            ])))
+        }
+    }
+
+    fn stream_client(dir: &std::path::Path) -> SmithClient {
+        let config = crate::client::SmithConfig::default()
+            .with_base_dir(dir)
+            .with_project("stream-proj")
+            .with_batch_size(1)
+            .with_flush_interval(std::time::Duration::from_millis(50));
+        SmithClient::new(config)
+    }
+
+    async fn only_llm_run(client: &SmithClient) -> Run {
+        client.flush().await;
+        let filter = crate::types::RunFilter {
+            project: Some("stream-proj".into()),
+            run_type: Some(RunType::Llm),
+            ..Default::default()
+        };
+        let mut runs = client.store().unwrap().list_runs(&filter).await.unwrap();
+        assert_eq!(runs.len(), 1);
+        runs.remove(0)
+    }
+
+    #[tokio::test]
+    async fn traced_model_stream_records_output_and_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = stream_client(dir.path());
+        let model = TracedChatModel::new(Arc::new(MockModel), client.clone());
+
+        let events: Vec<_> = model
+            .stream(&[Message::user("Hi")], &CallOptions::default())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(events.last().unwrap().as_ref().unwrap(), &ChatStreamEvent::Done);
+
+        let run = only_llm_run(&client).await;
+        assert_eq!(run.status, crate::types::RunStatus::Success);
+        assert!(run.output.unwrap().contains("Hello!"));
+        assert_eq!(run.total_tokens, Some(15));
+    }
+
+    #[tokio::test]
+    async fn traced_model_stream_error_marks_run_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = stream_client(dir.path());
+        let model = TracedChatModel::new(Arc::new(BrokenStreamModel), client.clone());
+
+        let events: Vec<_> = model
+            .stream(&[Message::user("Hi")], &CallOptions::default())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(events[1].is_err());
+
+        let run = only_llm_run(&client).await;
+        assert_eq!(run.status, crate::types::RunStatus::Error);
+        assert!(run.error.unwrap().contains("connection reset"));
+    }
+
     #[tokio::test]
     async fn traced_model_generate() {
         let model = TracedChatModel::new(Arc::new(MockModel), SmithClient::noop());
@@ -177,6 +401,9 @@ mod tests {
             trace_id,
             parent_run_id: Some(parent_run_id),
             dotted_order: "20250210T120000000000Z.abc12345".into(),
+            sampled: None,
+            tags: vec!["nightly".into()],
+            metadata: None,
         };
 
         let model = TracedChatModel::new(Arc::new(MockModel), client);
@@ -203,6 +430,7 @@ mod tests {
         assert_eq!(runs[0].trace_id, trace_id);
         assert_eq!(runs[0].parent_run_id, Some(parent_run_id));
         assert!(runs[0].dotted_order.is_some());
+        assert_eq!(runs[0].tags, vec!["nightly"]);
         assert!(runs[0]
             .dotted_order
             .as_ref()
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use serde::Serialize;
 
@@ -6,7 +8,10 @@ use ayas_core::error::Result;
 use ayas_core::runnable::Runnable;
 
 use crate::client::SmithClient;
-use crate::context::{build_dotted_order, child_config, trace_context, SmithTraceCtx, SMITH_TRACE_CTX};
+use crate::context::{
+    build_dotted_order, child_config, merge_tags, trace_context, trace_sampled, SmithTraceCtx,
+    SMITH_TRACE_CTX,
+};
 use crate::types::{Run, RunType};
 
 /// A Runnable wrapper that records tracing information for each invocation.
@@ -15,6 +20,8 @@ pub struct TracedRunnable<R: Runnable> {
     client: SmithClient,
     name: String,
     run_type: RunType,
+    tags: Vec<String>,
+    metadata: HashMap<String, serde_json::Value>,
 }
 
 impl<R: Runnable> TracedRunnable<R> {
@@ -24,8 +31,23 @@ impl<R: Runnable> TracedRunnable<R> {
             client,
             name: name.into(),
             run_type,
+            tags: Vec::new(),
+            metadata: HashMap::new(),
         }
     }
+
+    /// Tag this run and everything it calls, in addition to the caller's tags.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Add metadata to this run and everything it calls; it overrides the
+    /// caller's metadata under the same key.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
 }
 
 #[async_trait]
@@ -43,6 +65,11 @@ where
             return self.inner.invoke(input, config).await;
         }
 
+        let mut scoped = config.clone();
+        scoped.tags = merge_tags(&config.tags, &self.tags);
+        scoped.metadata.extend(self.metadata.clone());
+        let config = &scoped;
+
         let (trace_id, parent_run_id, parent_dotted_order) = trace_context(config);
         let run_id = uuid::Uuid::new_v4();
         let start_time = chrono::Utc::now();
@@ -62,6 +89,10 @@ where
         if let Some(pid) = parent_run_id {
             builder = builder.parent_run_id(pid);
         }
+        let metadata = run_metadata(config, self.client.has_redactor());
+        if let Some(ref metadata) = metadata {
+            builder = builder.metadata(metadata);
+        }
 
         let child_cfg = child_config(config, run_id, trace_id, &dotted_order);
 
@@ -71,6 +102,9 @@ where
             trace_id,
             parent_run_id: Some(run_id),
             dotted_order: dotted_order.clone(),
+            sampled: trace_sampled(config),
+            tags: config.tags.clone(),
+            metadata,
         };
 
         let result = SMITH_TRACE_CTX
@@ -94,6 +128,34 @@ where
     }
 }
 
+/// Run metadata: the config's `metadata` entries, plus the user-set
+/// `configurable` entries (trace propagation keys excluded) under
+/// `"configurable"` when `with_configurable` is set, or `None` when there
+/// are none.
+///
+/// `configurable` often carries credentials, so it is only recorded when the
+/// client redacts runs before storing them.
+fn run_metadata(config: &RunnableConfig, with_configurable: bool) -> Option<String> {
+    let configurable: serde_json::Map<String, serde_json::Value> = config
+        .configurable
+        .iter()
+        .filter(|(k, _)| with_configurable && !k.starts_with("__smith_"))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    let mut metadata: serde_json::Map<String, serde_json::Value> = config
+        .metadata
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    if !configurable.is_empty() {
+        metadata.insert("configurable".into(), serde_json::Value::Object(configurable));
+    }
+    if metadata.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&metadata).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +229,50 @@ mod tests {
         assert!(dir.path().join("default").exists());
     }
 
+    #[test]
+    fn configurable_recorded_only_when_requested() {
+        let mut config = RunnableConfig::default();
+        config
+            .configurable
+            .insert("api_key".into(), serde_json::json!("sk-secret"));
+        assert_eq!(run_metadata(&config, false), None);
+        let metadata: serde_json::Value =
+            serde_json::from_str(&run_metadata(&config, true).unwrap()).unwrap();
+        assert_eq!(metadata["configurable"]["api_key"], "sk-secret");
+    }
+
+    #[tokio::test]
+    async fn traced_runnable_redacts_configurable() {
+        let dir = tempfile::tempdir().unwrap();
+        let smith_config = crate::client::SmithConfig::default()
+            .with_base_dir(dir.path())
+            .with_project("redact-proj")
+            .with_redactor(std::sync::Arc::new(crate::redact::PatternRedactor::new()));
+        let client = SmithClient::new(smith_config);
+
+        let traced = TracedRunnable::new(AddOne, client.clone(), "add-one", RunType::Chain);
+        let mut config = RunnableConfig::default();
+        config
+            .configurable
+            .insert("api_key".into(), serde_json::json!("sk-secret"));
+        config
+            .configurable
+            .insert("user".into(), serde_json::json!("jane@example.com"));
+        traced.invoke(5, &config).await.unwrap();
+        client.flush().await;
+
+        let query = crate::query::SmithQuery::new(dir.path()).unwrap();
+        let filter = crate::types::RunFilter {
+            project: Some("redact-proj".into()),
+            ..Default::default()
+        };
+        let runs = query.list_runs(&filter).unwrap();
+        assert_eq!(runs.len(), 1);
+        let metadata: serde_json::Value = serde_json::from_str(&runs[0].metadata).unwrap();
+        assert_eq!(metadata["configurable"]["api_key"], crate::redact::REDACTED);
+        assert_eq!(metadata["configurable"]["user"], "[EMAIL]");
+    }
+
     #[tokio::test]
     async fn traced_runnable_sets_dotted_order() {
         let dir = tempfile::tempdir().unwrap();
@@ -192,4 +298,44 @@ mod tests {
         assert_eq!(runs.len(), 1);
         assert!(runs[0].dotted_order.is_some());
     }
+
+    #[tokio::test]
+    async fn traced_runnable_propagates_tags_and_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let smith_config = crate::client::SmithConfig::default()
+            .with_base_dir(dir.path())
+            .with_project("tags-proj");
+        let client = SmithClient::new(smith_config);
+
+        let inner = TracedRunnable::new(AddOne, client.clone(), "step", RunType::Chain)
+            .with_tag("step");
+        let outer = TracedRunnable::new(inner, client.clone(), "pipeline", RunType::Chain)
+            .with_tag("pipeline")
+            .with_metadata("version", serde_json::json!(2));
+        let config = RunnableConfig::new()
+            .with_tag("prod")
+            .with_metadata("user_id", serde_json::json!("u-1"));
+        outer.invoke(5, &config).await.unwrap();
+        client.flush().await;
+
+        let query = crate::query::SmithQuery::new(dir.path()).unwrap();
+        let by_tags = |tags: &[&str]| {
+            let filter = crate::types::RunFilter {
+                project: Some("tags-proj".into()),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            };
+            query.list_runs(&filter).unwrap()
+        };
+
+        assert_eq!(by_tags(&["prod"]).len(), 2);
+        assert!(by_tags(&["missing"]).is_empty());
+        let step_runs = by_tags(&["prod", "step"]);
+        assert_eq!(step_runs.len(), 1);
+        assert_eq!(step_runs[0].name, "step");
+        assert_eq!(step_runs[0].tags, vec!["prod", "pipeline", "step"]);
+        let metadata: serde_json::Value = serde_json::from_str(&step_runs[0].metadata).unwrap();
+        assert_eq!(metadata["user_id"], "u-1");
+        assert_eq!(metadata["version"], 2);
+    }
 }
@@ -47,7 +47,10 @@ impl Tool for TracedTool {
             .start_time(start_time);
 
         if let Some(ref ctx) = ctx {
-            builder = builder.trace_id(ctx.trace_id);
+            builder = builder.trace_id(ctx.trace_id).tags(ctx.tags.clone());
+            if let Some(ref metadata) = ctx.metadata {
+                builder = builder.metadata(metadata);
+            }
             if let Some(pid) = ctx.parent_run_id {
                 builder = builder.parent_run_id(pid);
             }
@@ -170,6 +173,9 @@ mod tests {
             trace_id,
             parent_run_id: Some(parent_run_id),
             dotted_order: "20250210T120000000000Z.abc12345".into(),
+            sampled: None,
+            tags: vec!["nightly".into()],
+            metadata: None,
         };
 
         let tool = TracedTool::new(Arc::new(MockTool), client);
@@ -193,5 +199,6 @@ mod tests {
         assert_eq!(runs[0].trace_id, trace_id);
         assert_eq!(runs[0].parent_run_id, Some(parent_run_id));
         assert!(runs[0].dotted_order.is_some());
+        assert_eq!(runs[0].tags, vec!["nightly"]);
     }
 }
@@ -18,8 +18,17 @@
 //!     "result".into()
 //! }
 //! ```
-
-use std::collections::HashMap;
+//!
+//! # Sampling
+//!
+//! [`SmithLayer::with_sample_rate`] enables head-based sampling: the decision
+//! is made once when a root span is created and inherited by every span in
+//! its trace. A root span can force the decision with
+//! `smith.sample = "always"` (or `"never"`), e.g. from an `x-trace-sample`
+//! request header, and a decision already recorded in the task-local
+//! [`SMITH_TRACE_CTX`] is honoured.
+
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use chrono::Utc;
@@ -31,7 +40,9 @@ use tracing_subscriber::registry::LookupSpan;
 use uuid::Uuid;
 
 use crate::client::SmithClient;
-use crate::context::build_dotted_order;
+use crate::context::{
+    build_dotted_order, parse_sample_override, should_sample_trace, SMITH_TRACE_CTX,
+};
 use crate::types::{Run, RunType};
 
 /// Metadata attached to each span tracked by `SmithLayer`.
@@ -47,6 +58,8 @@ struct SpanData {
     output: Option<String>,
     start_time: chrono::DateTime<Utc>,
     dotted_order: String,
+    error: Option<String>,
+    sampled: bool,
 }
 
 /// Visitor that extracts `smith.*` fields from span attributes.
@@ -55,6 +68,8 @@ struct SmithFieldVisitor {
     input: Option<String>,
     output: Option<String>,
     project: Option<String>,
+    error: Option<String>,
+    sample: Option<String>,
 }
 
 impl SmithFieldVisitor {
@@ -64,6 +79,8 @@ impl SmithFieldVisitor {
             input: None,
             output: None,
             project: None,
+            error: None,
+            sample: None,
         }
     }
 
@@ -79,6 +96,8 @@ impl Visit for SmithFieldVisitor {
             "smith.input" => self.input = Some(format!("{value:?}").trim_matches('"').to_string()),
             "smith.output" => self.output = Some(format!("{value:?}").trim_matches('"').to_string()),
             "smith.project" => self.project = Some(format!("{value:?}").trim_matches('"').to_string()),
+            "smith.error" => self.error = Some(format!("{value:?}").trim_matches('"').to_string()),
+            "smith.sample" => self.sample = Some(format!("{value:?}").trim_matches('"').to_string()),
             _ => {}
         }
     }
@@ -89,32 +108,42 @@ impl Visit for SmithFieldVisitor {
             "smith.input" => self.input = Some(value.to_string()),
             "smith.output" => self.output = Some(value.to_string()),
             "smith.project" => self.project = Some(value.to_string()),
+            "smith.error" => self.error = Some(value.to_string()),
+            "smith.sample" => self.sample = Some(value.to_string()),
             _ => {}
         }
     }
 }
 
-/// Visitor that extracts `smith.output` recorded after span creation.
+/// Visitor that extracts `smith.output` / `smith.error` recorded after span creation.
 struct OutputVisitor {
     output: Option<String>,
+    error: Option<String>,
 }
 
 impl OutputVisitor {
     fn new() -> Self {
-        Self { output: None }
+        Self {
+            output: None,
+            error: None,
+        }
     }
 }
 
 impl Visit for OutputVisitor {
     fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
-        if field.name() == "smith.output" {
-            self.output = Some(format!("{value:?}").trim_matches('"').to_string());
+        match field.name() {
+            "smith.output" => self.output = Some(format!("{value:?}").trim_matches('"').to_string()),
+            "smith.error" => self.error = Some(format!("{value:?}").trim_matches('"').to_string()),
+            _ => {}
         }
     }
 
     fn record_str(&mut self, field: &Field, value: &str) {
-        if field.name() == "smith.output" {
-            self.output = Some(value.to_string());
+        match field.name() {
+            "smith.output" => self.output = Some(value.to_string()),
+            "smith.error" => self.error = Some(value.to_string()),
+            _ => {}
         }
     }
 }
@@ -127,6 +156,11 @@ impl Visit for OutputVisitor {
 pub struct SmithLayer {
     client: SmithClient,
     spans: Arc<Mutex<HashMap<Id, SpanData>>>,
+    sample_rate: f64,
+    always_sample_errors: bool,
+    /// Run ids of open spans in unsampled traces that are ancestors of an
+    /// errored span; they are recorded as they close.
+    promoted: Arc<Mutex<HashSet<Uuid>>>,
 }
 
 impl SmithLayer {
@@ -135,7 +169,46 @@ impl SmithLayer {
         Self {
             client,
             spans: Arc::new(Mutex::new(HashMap::new())),
+            sample_rate: 1.0,
+            always_sample_errors: true,
+            promoted: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Record only this fraction of traces (clamped to `0.0..=1.0`).
+    pub fn with_sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether errored spans of unsampled traces are still recorded
+    /// (default: `true`). Spans that closed before the error are lost.
+    pub fn with_always_sample_errors(mut self, always: bool) -> Self {
+        self.always_sample_errors = always;
+        self
+    }
+
+    /// Sampling decision for a new root span.
+    fn sample_root(&self, trace_id: Uuid, forced: Option<&str>) -> bool {
+        forced
+            .and_then(parse_sample_override)
+            .or_else(|| SMITH_TRACE_CTX.try_with(|c| c.sampled).ok().flatten())
+            .unwrap_or_else(|| should_sample_trace(trace_id, self.sample_rate))
+    }
+
+    /// Decide whether a closing span of an unsampled trace is still recorded:
+    /// it errored, or it is an ancestor of a span that did. Siblings of the
+    /// errored span are not.
+    fn keep_unsampled(&self, data: &SpanData) -> bool {
+        if !self.always_sample_errors {
+            return false;
+        }
+        let mut promoted = self.promoted.lock().unwrap();
+        let keep = promoted.remove(&data.run_id) || data.error.is_some();
+        if keep && let Some(parent) = data.parent_run_id {
+            promoted.insert(parent);
         }
+        keep
     }
 
     fn find_parent_span_data(&self, ctx: &Context<'_, impl Subscriber + for<'a> LookupSpan<'a>>, attrs: &Attributes<'_>) -> Option<SpanData> {
@@ -181,14 +254,15 @@ where
 
         let parent_data = self.find_parent_span_data(&ctx, attrs);
 
-        let (trace_id, parent_run_id, dotted_order) = match &parent_data {
+        let (trace_id, parent_run_id, dotted_order, sampled) = match &parent_data {
             Some(parent) => {
                 let dotted = build_dotted_order(start_time, run_id, Some(&parent.dotted_order));
-                (parent.trace_id, Some(parent.run_id), dotted)
+                (parent.trace_id, Some(parent.run_id), dotted, parent.sampled)
             }
             None => {
                 let dotted = build_dotted_order(start_time, run_id, None);
-                (run_id, None, dotted)
+                let sampled = self.sample_root(run_id, visitor.sample.as_deref());
+                (run_id, None, dotted, sampled)
             }
         };
 
@@ -208,6 +282,8 @@ where
             output: visitor.output,
             start_time,
             dotted_order,
+            error: visitor.error,
+            sampled,
         };
 
         self.spans.lock().unwrap().insert(id.clone(), span_data);
@@ -217,11 +293,18 @@ where
         let mut visitor = OutputVisitor::new();
         values.record(&mut visitor);
 
-        if let Some(output) = visitor.output {
-            let mut spans = self.spans.lock().unwrap();
-            if let Some(data) = spans.get_mut(id) {
+        if visitor.output.is_none() && visitor.error.is_none() {
+            return;
+        }
+
+        let mut spans = self.spans.lock().unwrap();
+        if let Some(data) = spans.get_mut(id) {
+            if let Some(output) = visitor.output {
                 data.output = Some(output);
             }
+            if let Some(error) = visitor.error {
+                data.error = Some(error);
+            }
         }
     }
 
@@ -235,6 +318,10 @@ where
             return;
         };
 
+        if !data.sampled && !self.keep_unsampled(&data) {
+            return;
+        }
+
         let end_time = Utc::now();
         let latency_ms = (end_time - data.start_time).num_milliseconds();
 
@@ -250,9 +337,10 @@ where
             builder = builder.parent_run_id(pid);
         }
 
-        let mut run = match &data.output {
-            Some(output) => builder.finish_ok(output),
-            None => builder.finish_ok(""),
+        let mut run = match (&data.error, &data.output) {
+            (Some(error), _) => builder.finish_err(error),
+            (None, Some(output)) => builder.finish_ok(output),
+            (None, None) => builder.finish_ok(""),
         };
         run.latency_ms = Some(latency_ms);
 
@@ -420,6 +508,141 @@ mod tests {
         assert_eq!(runs[0].project, "custom-proj");
     }
 
+    fn setup_sampled_layer(
+        dir: &std::path::Path,
+        rate: f64,
+    ) -> (SmithClient, tracing::subscriber::DefaultGuard) {
+        let config = SmithConfig::default()
+            .with_base_dir(dir)
+            .with_project("layer-test")
+            .with_batch_size(1)
+            .with_flush_interval(Duration::from_millis(50));
+
+        let client = SmithClient::new(config);
+        let layer = SmithLayer::new(client.clone()).with_sample_rate(rate);
+
+        let subscriber = tracing_subscriber::registry().with(layer);
+        let guard = tracing::subscriber::set_default(subscriber);
+
+        (client, guard)
+    }
+
+    fn layer_runs(dir: &std::path::Path) -> Vec<Run> {
+        let query = SmithQuery::new(dir).unwrap();
+        let filter = RunFilter {
+            project: Some("layer-test".into()),
+            ..Default::default()
+        };
+        query.list_runs(&filter).unwrap()
+    }
+
+    #[tokio::test]
+    async fn zero_sample_rate_drops_whole_trace() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_client, _guard) = setup_sampled_layer(dir.path(), 0.0);
+
+        {
+            let parent = tracing::info_span!("parent", smith.run_type = "chain", smith.input = "p");
+            let _p = parent.enter();
+            {
+                let child = tracing::info_span!("child", smith.run_type = "llm", smith.input = "c");
+                let _c = child.enter();
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!dir.path().join("layer-test").exists());
+    }
+
+    #[tokio::test]
+    async fn forced_sample_records_whole_trace() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_client, _guard) = setup_sampled_layer(dir.path(), 0.0);
+
+        {
+            let parent = tracing::info_span!(
+                "parent",
+                smith.run_type = "chain",
+                smith.input = "p",
+                smith.sample = "always",
+            );
+            let _p = parent.enter();
+            {
+                let child = tracing::info_span!("child", smith.run_type = "llm", smith.input = "c");
+                let _c = child.enter();
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(layer_runs(dir.path()).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn task_local_decision_is_honoured() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_client, _guard) = setup_sampled_layer(dir.path(), 0.0);
+
+        let ctx = crate::context::SmithTraceCtx {
+            trace_id: Uuid::new_v4(),
+            parent_run_id: None,
+            dotted_order: String::new(),
+            sampled: Some(true),
+            tags: Vec::new(),
+            metadata: None,
+        };
+        SMITH_TRACE_CTX
+            .scope(ctx, async {
+                let span = tracing::info_span!("root", smith.run_type = "chain", smith.input = "x");
+                let _enter = span.enter();
+            })
+            .await;
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(layer_runs(dir.path()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn errors_in_unsampled_trace_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_client, _guard) = setup_sampled_layer(dir.path(), 0.0);
+
+        {
+            let parent = tracing::info_span!("parent", smith.run_type = "chain", smith.input = "p");
+            let _p = parent.enter();
+            {
+                let ok = tracing::info_span!("ok_child", smith.run_type = "tool", smith.input = "a");
+                let _o = ok.enter();
+            }
+            {
+                let failing = tracing::info_span!(
+                    "failing_child",
+                    smith.run_type = "llm",
+                    smith.input = "b",
+                    smith.error = tracing::field::Empty,
+                );
+                let _f = failing.enter();
+                failing.record("smith.error", "boom");
+            }
+            {
+                // Closes after the error, but is not one of its ancestors
+                let late =
+                    tracing::info_span!("late_child", smith.run_type = "tool", smith.input = "c");
+                let _l = late.enter();
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let runs = layer_runs(dir.path());
+        assert_eq!(runs.len(), 2);
+        assert!(!runs.iter().any(|r| r.name == "late_child"));
+
+        let failing = runs.iter().find(|r| r.name == "failing_child").unwrap();
+        assert_eq!(failing.status, RunStatus::Error);
+        assert_eq!(failing.error.as_deref(), Some("boom"));
+        assert!(runs.iter().any(|r| r.name == "parent"));
+        assert!(!runs.iter().any(|r| r.name == "ok_child"));
+    }
+
     #[tokio::test]
     async fn noop_client_layer_does_not_panic() {
         let client = SmithClient::noop();
@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::pricing::PricingTable;
+
 /// Type of a traced run.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -130,6 +134,11 @@ impl Run {
     }
 }
 
+/// Run id for an idempotency key (a UUID v5, stable across processes).
+pub fn idempotent_run_id(key: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+}
+
 /// Builder for constructing Run instances.
 pub struct RunBuilder {
     run_id: Uuid,
@@ -151,6 +160,18 @@ impl RunBuilder {
         self
     }
 
+    /// Derive the run id from `key`, so every run built with the same key
+    /// shares one id and stores upsert it instead of adding a row. A root
+    /// run's trace id follows its run id.
+    pub fn idempotency_key(mut self, key: &str) -> Self {
+        let id = idempotent_run_id(key);
+        if self.trace_id == self.run_id {
+            self.trace_id = id;
+        }
+        self.run_id = id;
+        self
+    }
+
     pub fn parent_run_id(mut self, id: Uuid) -> Self {
         self.parent_run_id = Some(id);
         self
@@ -287,6 +308,78 @@ impl RunBuilder {
     }
 }
 
+/// Maximum nesting depth when reconstructing a [`RunTree`].
+pub const MAX_RUN_TREE_DEPTH: usize = 64;
+
+/// A run with its descendants nested beneath it, children ordered by start time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTree {
+    pub run: Run,
+    pub children: Vec<RunTree>,
+    /// Sum of `self_latency_ms` over this run and all of its descendants, so
+    /// time spent in nested runs is counted once.
+    pub subtree_latency_ms: i64,
+    /// This run's latency minus its direct children's latency (never negative).
+    pub self_latency_ms: i64,
+    /// Number of descendant runs.
+    pub descendant_count: usize,
+}
+
+impl RunTree {
+    /// Build the tree rooted at `root` from a flat list of runs (e.g. a trace).
+    ///
+    /// Runs that are not descendants of `root` are ignored. Each run is placed
+    /// at most once and nesting stops at [`MAX_RUN_TREE_DEPTH`], so malformed
+    /// parent links (cycles) cannot cause unbounded recursion.
+    pub fn build(root: Run, runs: Vec<Run>) -> Self {
+        let mut by_parent: std::collections::HashMap<Uuid, Vec<Run>> =
+            std::collections::HashMap::new();
+        for run in runs {
+            if run.run_id == root.run_id {
+                continue;
+            }
+            if let Some(parent) = run.parent_run_id {
+                by_parent.entry(parent).or_default().push(run);
+            }
+        }
+        for children in by_parent.values_mut() {
+            children.sort_by_key(|r| r.start_time);
+        }
+        let mut visited = std::collections::HashSet::new();
+        Self::build_node(root, &mut by_parent, &mut visited, 0)
+    }
+
+    fn build_node(
+        run: Run,
+        by_parent: &mut std::collections::HashMap<Uuid, Vec<Run>>,
+        visited: &mut std::collections::HashSet<Uuid>,
+        depth: usize,
+    ) -> Self {
+        visited.insert(run.run_id);
+        let mut children = Vec::new();
+        if depth < MAX_RUN_TREE_DEPTH {
+            for child in by_parent.remove(&run.run_id).unwrap_or_default() {
+                if visited.contains(&child.run_id) {
+                    continue;
+                }
+                children.push(Self::build_node(child, by_parent, visited, depth + 1));
+            }
+        }
+
+        let own = run.latency_ms.unwrap_or(0);
+        let direct: i64 = children.iter().map(|c| c.run.latency_ms.unwrap_or(0)).sum();
+        let self_latency_ms = (own - direct).max(0);
+        Self {
+            subtree_latency_ms: self_latency_ms
+                + children.iter().map(|c| c.subtree_latency_ms).sum::<i64>(),
+            self_latency_ms,
+            descendant_count: children.iter().map(|c| c.descendant_count + 1).sum(),
+            run,
+            children,
+        }
+    }
+}
+
 /// Summary of token usage from query results.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TokenUsageSummary {
@@ -294,6 +387,47 @@ pub struct TokenUsageSummary {
     pub total_output_tokens: i64,
     pub total_tokens: i64,
     pub run_count: i64,
+    /// Token usage of `llm` runs keyed by model (the run name), used for
+    /// pricing. Empty when the backend does not break usage down.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_model: BTreeMap<String, ModelTokenUsage>,
+}
+
+impl TokenUsageSummary {
+    /// Dollar cost of the per-model usage under `pricing`.
+    ///
+    /// Models missing from the table count as zero (with a warning).
+    pub fn cost(&self, pricing: &PricingTable) -> f64 {
+        self.by_model
+            .iter()
+            .map(|(model, usage)| pricing.cost(model, usage.input_tokens, usage.output_tokens))
+            .sum()
+    }
+
+    /// Per-model usage of the `llm` runs among `runs`.
+    pub fn usage_by_model<'a>(
+        runs: impl IntoIterator<Item = &'a Run>,
+    ) -> BTreeMap<String, ModelTokenUsage> {
+        let mut by_model: BTreeMap<String, ModelTokenUsage> = BTreeMap::new();
+        for run in runs {
+            if run.run_type != RunType::Llm || run.total_tokens.is_none() {
+                continue;
+            }
+            let usage = by_model.entry(run.name.clone()).or_default();
+            usage.input_tokens += run.input_tokens.unwrap_or(0);
+            usage.output_tokens += run.output_tokens.unwrap_or(0);
+            usage.run_count += 1;
+        }
+        by_model
+    }
+}
+
+/// Token usage of a single model.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelTokenUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub run_count: i64,
 }
 
 /// Latency statistics from query results.
@@ -305,6 +439,77 @@ pub struct LatencyStats {
     pub p99: f64,
 }
 
+/// Aggregate metrics over a filtered set of runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunAggregate {
+    /// Number of runs matching the filter.
+    pub run_count: i64,
+    /// Latency percentiles over runs with a recorded latency.
+    pub latency: LatencyStats,
+    /// Token totals; `tokens.run_count` counts runs with token usage.
+    pub tokens: TokenUsageSummary,
+    /// Dollar cost of `tokens.by_model`, set by [`RunAggregate::with_cost`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost: Option<f64>,
+}
+
+impl RunAggregate {
+    /// Compute the aggregate in memory, for backends without a query engine.
+    ///
+    /// Percentiles use linear interpolation, matching SQL `percentile_cont`.
+    pub fn from_runs(runs: &[Run]) -> Self {
+        let mut latencies: Vec<f64> = runs
+            .iter()
+            .filter_map(|r| r.latency_ms)
+            .map(|l| l as f64)
+            .collect();
+        latencies.sort_by(f64::total_cmp);
+
+        let with_tokens: Vec<&Run> = runs.iter().filter(|r| r.total_tokens.is_some()).collect();
+        let sum = |f: fn(&Run) -> Option<i64>| -> i64 {
+            with_tokens.iter().map(|r| f(r).unwrap_or(0)).sum()
+        };
+
+        Self {
+            run_count: runs.len() as i64,
+            latency: LatencyStats {
+                p50: percentile_cont(&latencies, 0.5),
+                p90: percentile_cont(&latencies, 0.9),
+                p95: percentile_cont(&latencies, 0.95),
+                p99: percentile_cont(&latencies, 0.99),
+            },
+            tokens: TokenUsageSummary {
+                total_input_tokens: sum(|r| r.input_tokens),
+                total_output_tokens: sum(|r| r.output_tokens),
+                total_tokens: sum(|r| r.total_tokens),
+                run_count: with_tokens.len() as i64,
+                by_model: TokenUsageSummary::usage_by_model(runs),
+            },
+            total_cost: None,
+        }
+    }
+
+    /// Price the aggregate's token usage and record it in `total_cost`.
+    pub fn with_cost(mut self, pricing: &PricingTable) -> Self {
+        self.total_cost = Some(self.tokens.cost(pricing));
+        self
+    }
+}
+
+/// Continuous percentile of pre-sorted values (0.0 when empty).
+fn percentile_cont(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        n => {
+            let rank = p * (n - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
 /// Filter criteria for querying runs.
 #[derive(Debug, Clone, Default)]
 pub struct RunFilter {
@@ -312,6 +517,7 @@ pub struct RunFilter {
     pub run_type: Option<RunType>,
     pub status: Option<RunStatus>,
     pub name: Option<String>,
+    /// Only runs carrying all of these tags.
     pub tags: Vec<String>,
     pub start_after: Option<DateTime<Utc>>,
     pub start_before: Option<DateTime<Utc>>,
@@ -340,6 +546,117 @@ pub struct FeedbackFilter {
     pub key: Option<String>,
 }
 
+/// Time bucket for grouping aggregate statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBucket {
+    Hourly,
+    Daily,
+}
+
+impl TimeBucket {
+    /// SQL `date_trunc` unit for this bucket.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hourly => "hour",
+            Self::Daily => "day",
+        }
+    }
+
+    /// Start of the bucket containing `t`.
+    pub fn truncate(&self, t: DateTime<Utc>) -> DateTime<Utc> {
+        use chrono::{DurationRound, TimeDelta};
+        let step = match self {
+            Self::Hourly => TimeDelta::hours(1),
+            Self::Daily => TimeDelta::days(1),
+        };
+        t.duration_trunc(step).unwrap_or(t)
+    }
+}
+
+impl std::str::FromStr for TimeBucket {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "hourly" | "hour" => Ok(Self::Hourly),
+            "daily" | "day" => Ok(Self::Daily),
+            other => Err(format!("unknown time bucket: '{other}'")),
+        }
+    }
+}
+
+/// Filter criteria for feedback statistics.
+#[derive(Debug, Clone, Default)]
+pub struct FeedbackStatsFilter {
+    /// Only feedback on runs in this project.
+    pub project: Option<String>,
+    pub key: Option<String>,
+    /// Only feedback created strictly after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only feedback created strictly before this time.
+    pub created_before: Option<DateTime<Utc>>,
+    /// Group statistics into time buckets as well as by key.
+    pub bucket: Option<TimeBucket>,
+}
+
+impl FeedbackStatsFilter {
+    /// Whether `feedback` passes the key and time-range criteria.
+    ///
+    /// `project` needs run lookups and is applied by the store.
+    pub fn matches(&self, feedback: &Feedback) -> bool {
+        self.key.as_ref().is_none_or(|k| feedback.key == *k)
+            && self.created_after.is_none_or(|t| feedback.created_at > t)
+            && self.created_before.is_none_or(|t| feedback.created_at < t)
+    }
+}
+
+/// Score statistics for one feedback key (and time bucket, if requested).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedbackStat {
+    pub key: String,
+    /// Start of the time bucket; `None` when not bucketed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<DateTime<Utc>>,
+    pub count: i64,
+    pub mean: f64,
+    /// Sample standard deviation (0.0 for fewer than two scores).
+    pub stddev: f64,
+}
+
+impl FeedbackStat {
+    /// Compute statistics in memory, for backends without a query engine.
+    ///
+    /// Results are ordered by key, then bucket.
+    pub fn from_feedback(items: &[Feedback], bucket: Option<TimeBucket>) -> Vec<Self> {
+        let mut groups: BTreeMap<(String, Option<DateTime<Utc>>), Vec<f64>> = BTreeMap::new();
+        for f in items {
+            let start = bucket.map(|b| b.truncate(f.created_at));
+            groups.entry((f.key.clone(), start)).or_default().push(f.score);
+        }
+
+        groups
+            .into_iter()
+            .map(|((key, bucket), scores)| {
+                let n = scores.len() as f64;
+                let mean = scores.iter().sum::<f64>() / n;
+                let stddev = if scores.len() < 2 {
+                    0.0
+                } else {
+                    (scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+                };
+                Self {
+                    key,
+                    bucket,
+                    count: scores.len() as i64,
+                    mean,
+                    stddev,
+                }
+            })
+            .collect()
+    }
+}
+
 /// Partial update for an existing run (2-phase lifecycle).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RunPatch {
@@ -390,6 +707,17 @@ impl Run {
             self.latency_ms = Some(ms);
         }
     }
+
+    /// Case-insensitive substring match of `query` against the serialized
+    /// input and output.
+    pub fn contains_text(&self, query: &str) -> bool {
+        let needle = query.to_lowercase();
+        self.input.to_lowercase().contains(&needle)
+            || self
+                .output
+                .as_ref()
+                .is_some_and(|o| o.to_lowercase().contains(&needle))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -581,6 +909,200 @@ mod tests {
         assert_eq!(stats.p99, 0.0);
     }
 
+    #[test]
+    fn run_aggregate_from_runs() {
+        let mut runs: Vec<Run> = (1..=10)
+            .map(|i| {
+                let mut run = Run::builder("step", RunType::Chain).finish_ok("ok");
+                run.latency_ms = Some(i * 10);
+                run
+            })
+            .collect();
+        runs.push(Run::builder("llm", RunType::Llm).finish_llm("hi", 30, 20, 50));
+        runs.last_mut().unwrap().latency_ms = None;
+
+        let agg = RunAggregate::from_runs(&runs);
+        assert_eq!(agg.run_count, 11);
+        assert!((agg.latency.p50 - 55.0).abs() < 1e-9);
+        assert!((agg.latency.p90 - 91.0).abs() < 1e-9);
+        assert!(agg.latency.p99 <= 100.0);
+        assert_eq!(agg.tokens.total_input_tokens, 30);
+        assert_eq!(agg.tokens.total_output_tokens, 20);
+        assert_eq!(agg.tokens.total_tokens, 50);
+        assert_eq!(agg.tokens.run_count, 1);
+    }
+
+    #[test]
+    fn token_usage_cost_by_model() {
+        let runs = vec![
+            Run::builder("gpt-4o", RunType::Llm).finish_llm("a", 1000, 500, 1500),
+            Run::builder("gpt-4o", RunType::Llm).finish_llm("b", 1000, 500, 1500),
+            Run::builder("local-model", RunType::Llm).finish_llm("c", 100, 100, 200),
+            Run::builder("chain", RunType::Chain).finish_ok("ok"),
+        ];
+        let agg = RunAggregate::from_runs(&runs);
+        assert_eq!(agg.tokens.by_model.len(), 2);
+        assert_eq!(agg.tokens.by_model["gpt-4o"].run_count, 2);
+        assert!(agg.total_cost.is_none());
+
+        let pricing = PricingTable::new().with_price("gpt-4o", 0.01, 0.02);
+        // 2 * (1.0 * 0.01 + 0.5 * 0.02); the unpriced model counts as zero
+        assert!((agg.tokens.cost(&pricing) - 0.04).abs() < 1e-9);
+        let priced = agg.with_cost(&pricing);
+        assert!((priced.total_cost.unwrap() - 0.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn token_usage_summary_without_breakdown_deserializes() {
+        let json = r#"{"total_input_tokens":1,"total_output_tokens":2,"total_tokens":3,"run_count":1}"#;
+        let summary: TokenUsageSummary = serde_json::from_str(json).unwrap();
+        assert!(summary.by_model.is_empty());
+        assert_eq!(summary.cost(&PricingTable::default()), 0.0);
+    }
+
+    fn tree_run(name: &str, parent: Option<Uuid>, offset_ms: i64, latency: i64) -> Run {
+        let mut run = Run::builder(name, RunType::Chain).finish_ok("ok");
+        run.parent_run_id = parent;
+        run.start_time = DateTime::<Utc>::UNIX_EPOCH + chrono::Duration::milliseconds(offset_ms);
+        run.latency_ms = Some(latency);
+        run
+    }
+
+    #[test]
+    fn run_tree_build_orders_and_rolls_up() {
+        let root = tree_run("root", None, 0, 100);
+        let late = tree_run("late", Some(root.run_id), 50, 30);
+        let early = tree_run("early", Some(root.run_id), 10, 40);
+        let grandchild = tree_run("grandchild", Some(early.run_id), 15, 25);
+        let unrelated = tree_run("unrelated", Some(Uuid::new_v4()), 5, 999);
+
+        let tree = RunTree::build(
+            root.clone(),
+            vec![root, late, grandchild, early, unrelated],
+        );
+        let names: Vec<_> = tree.children.iter().map(|c| c.run.name.as_str()).collect();
+        assert_eq!(names, ["early", "late"]);
+        assert_eq!(tree.children[0].children[0].run.name, "grandchild");
+        assert_eq!(tree.descendant_count, 3);
+        assert_eq!(tree.self_latency_ms, 30);
+        assert_eq!(tree.children[0].self_latency_ms, 15);
+        // Children run inside the root, so the rolled-up total is the root's own latency
+        assert_eq!(tree.subtree_latency_ms, 100);
+        assert_eq!(tree.children[0].subtree_latency_ms, 40);
+    }
+
+    #[test]
+    fn run_tree_tolerates_cycles() {
+        let mut a = tree_run("a", None, 0, 10);
+        let b = tree_run("b", Some(a.run_id), 1, 5);
+        // Corrupt data: the root claims to be a child of its own child.
+        a.parent_run_id = Some(b.run_id);
+        let mut c = tree_run("c", Some(b.run_id), 2, 1);
+        c.run_id = a.run_id;
+
+        let tree = RunTree::build(a.clone(), vec![a, b, c]);
+        assert_eq!(tree.descendant_count, 1);
+        assert_eq!(tree.children[0].run.name, "b");
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn run_tree_depth_is_capped() {
+        let mut runs = vec![tree_run("r0", None, 0, 1)];
+        for i in 1..(MAX_RUN_TREE_DEPTH + 10) {
+            let parent = runs[i - 1].run_id;
+            runs.push(tree_run(&format!("r{i}"), Some(parent), i as i64, 1));
+        }
+        let tree = RunTree::build(runs[0].clone(), runs);
+        assert_eq!(tree.descendant_count, MAX_RUN_TREE_DEPTH);
+    }
+
+    #[test]
+    fn run_aggregate_empty() {
+        let agg = RunAggregate::from_runs(&[]);
+        assert_eq!(agg.run_count, 0);
+        assert_eq!(agg.latency.p95, 0.0);
+        assert_eq!(agg.tokens.total_tokens, 0);
+    }
+
+    fn feedback_at(key: &str, score: f64, created_at: &str) -> Feedback {
+        Feedback {
+            id: Uuid::new_v4(),
+            run_id: Uuid::new_v4(),
+            key: key.into(),
+            score,
+            comment: None,
+            created_at: DateTime::parse_from_rfc3339(created_at).unwrap().with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn feedback_stats_by_key() {
+        let items = vec![
+            feedback_at("correctness", 1.0, "2024-01-01T10:00:00Z"),
+            feedback_at("correctness", 0.0, "2024-01-02T10:00:00Z"),
+            feedback_at("helpfulness", 0.5, "2024-01-01T11:00:00Z"),
+        ];
+        let stats = FeedbackStat::from_feedback(&items, None);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].key, "correctness");
+        assert_eq!(stats[0].count, 2);
+        assert!((stats[0].mean - 0.5).abs() < 1e-9);
+        assert!((stats[0].stddev - 0.5f64.sqrt()).abs() < 1e-9);
+        assert_eq!(stats[1].stddev, 0.0);
+        assert!(stats[1].bucket.is_none());
+    }
+
+    #[test]
+    fn feedback_stats_daily_and_hourly_buckets() {
+        let items = vec![
+            feedback_at("k", 1.0, "2024-01-01T10:15:00Z"),
+            feedback_at("k", 0.0, "2024-01-01T10:45:00Z"),
+            feedback_at("k", 1.0, "2024-01-01T23:59:00Z"),
+            feedback_at("k", 1.0, "2024-01-02T00:01:00Z"),
+        ];
+        let daily = FeedbackStat::from_feedback(&items, Some(TimeBucket::Daily));
+        assert_eq!(daily.iter().map(|s| s.count).collect::<Vec<_>>(), [3, 1]);
+        assert_eq!(
+            daily[1].bucket.unwrap(),
+            DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z").unwrap()
+        );
+
+        let hourly = FeedbackStat::from_feedback(&items, Some(TimeBucket::Hourly));
+        assert_eq!(hourly.iter().map(|s| s.count).collect::<Vec<_>>(), [2, 1, 1]);
+    }
+
+    #[test]
+    fn feedback_stats_filter_matches() {
+        let fb = feedback_at("k", 1.0, "2024-01-01T10:00:00Z");
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        assert!(FeedbackStatsFilter::default().matches(&fb));
+        let filter = FeedbackStatsFilter {
+            key: Some("other".into()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&fb));
+        let filter = FeedbackStatsFilter {
+            created_after: Some(at("2024-01-01T09:00:00Z")),
+            created_before: Some(at("2024-01-01T11:00:00Z")),
+            ..Default::default()
+        };
+        assert!(filter.matches(&fb));
+        let filter = FeedbackStatsFilter {
+            created_after: Some(at("2024-01-01T10:00:00Z")),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&fb));
+    }
+
+    #[test]
+    fn time_bucket_parse() {
+        assert_eq!("daily".parse::<TimeBucket>().unwrap(), TimeBucket::Daily);
+        assert_eq!("hour".parse::<TimeBucket>().unwrap(), TimeBucket::Hourly);
+        assert!("weekly".parse::<TimeBucket>().is_err());
+    }
+
     #[test]
     fn feedback_serde_roundtrip() {
         let fb = Feedback {
@@ -681,6 +1203,19 @@ mod tests {
         assert_eq!(run.status, RunStatus::Success);
     }
 
+    #[test]
+    fn run_contains_text() {
+        let run = Run::builder("test", RunType::Chain)
+            .input(r#"{"query": "Rust Traits"}"#)
+            .finish_ok(r#"{"answer": "Use impl blocks"}"#);
+        assert!(run.contains_text("rust traits"));
+        assert!(run.contains_text("IMPL"));
+        assert!(!run.contains_text("python"));
+
+        let running = Run::builder("test", RunType::Chain).input("abc").start();
+        assert!(!running.contains_text("xyz"));
+    }
+
     #[test]
     fn run_patch_serde_roundtrip() {
         let patch = RunPatch {
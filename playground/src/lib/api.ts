@@ -76,8 +76,11 @@ export interface GraphValidateResponse {
 }
 
 export interface GraphSseEvent {
-  type: 'node_start' | 'node_end' | 'complete' | 'graph_complete' | 'interrupted' | 'error';
+  type: 'node_start' | 'node_end' | 'token' | 'complete' | 'graph_complete' | 'interrupted' | 'error';
   node_id?: string;
+  node_name?: string;
+  // Model stream event from a running node (type === 'token')
+  event?: { type: string; data?: unknown };
   step_number?: number;
   state?: Record<string, unknown>;
   output?: unknown;
@@ -89,6 +92,20 @@ export interface GraphSseEvent {
   interrupt_value?: unknown;
 }
 
+export type GraphStreamMode = 'values' | 'updates' | 'messages' | 'debug';
+
+export interface GraphStreamEvent {
+  type: 'values' | 'updates' | 'message' | 'debug' | 'graph_complete' | 'error';
+  state?: Record<string, unknown>;
+  node?: string;
+  data?: unknown;
+  chunk?: string;
+  event_type?: string;
+  payload?: unknown;
+  output?: unknown;
+  message?: string;
+}
+
 // --- Saved Graphs ---
 
 export interface NodePosition {
@@ -301,6 +318,28 @@ export function graphInvokeStream(
   );
 }
 
+export function graphStream(
+  nodes: GraphNodeDto[],
+  edges: GraphEdgeDto[],
+  channels: GraphChannelDto[],
+  input: unknown,
+  streamModes: GraphStreamMode[],
+  onEvent: (event: GraphStreamEvent) => void,
+  signal?: AbortSignal,
+  recursionLimit?: number,
+): Promise<void> {
+  const headers: Record<string, string> = { 'Content-Type': 'application/json' };
+  const body: Record<string, unknown> = {
+    nodes,
+    edges,
+    channels,
+    input,
+    stream_modes: streamModes,
+  };
+  if (recursionLimit !== undefined) body.recursion_limit = recursionLimit;
+  return streamSSE('/api/graph/stream', body, headers, onEvent, signal);
+}
+
 export async function graphGenerate(
   prompt: string,
   provider: Provider,
@@ -352,6 +391,23 @@ export function graphResume(
   );
 }
 
+export function hitlResume(
+  threadId: string,
+  checkpointId: string,
+  resumeValue: unknown,
+  onEvent: (event: GraphSseEvent) => void,
+  signal?: AbortSignal,
+): Promise<void> {
+  const headers: Record<string, string> = { 'Content-Type': 'application/json' };
+  return streamSSE(
+    '/api/hitl/resume',
+    { thread_id: threadId, checkpoint_id: checkpointId, resume_value: resumeValue },
+    headers,
+    onEvent,
+    signal,
+  );
+}
+
 // --- Saved Graphs API ---
 
 export async function saveGraph(